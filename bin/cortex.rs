@@ -10,32 +10,45 @@
 //! (`create-service`/`delete-service`, `import`/`extend`, `activate`/`deactivate`, `delete-corpus`,
 //! `sandbox`), the `report`/`runs`/`document` read surface (the CLI twins of the web/agent report
 //! ladder, run-history, and per-article forensics — `report` drills overview → severity → category
-//! → `what` → affected documents), the `snapshot`/`rerun` campaign actions, and dataset export.
+//! → `what` → affected documents), the `snapshot`/`rerun` campaign actions, dataset export, and
+//! the `fsck` / `gc-archives` result-archive maintenance.
 
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
 use cortex::backend::{
-  self, DatasetExportOutcome, GroupBy, RerunOptions, SandboxSelection, TaskReportOptions,
-  create_sandbox, default_db_address, export_html_dataset, list_task_diffs, summary_task_diffs,
-  task_messages,
+  self, CLASS_SEVERITIES, ClassGrain, ControlKind, ControlSignal, DEFAULT_TREND_RUNS,
+  DatasetExportOutcome, DocumentFilter, FsckOptions, FsckReport, GcReason, GroupBy, MessageSearch,
+  RerunOptions, RunRegressions, SandboxSelection, TaskReportOptions, backfill_fingerprints,
+  class_movers, class_trend, collect_archives, create_sandbox, default_db_address,
+  export_html_dataset, fsck_archives, list_task_diffs, search_messages, summary_task_diffs,
+  task_messages, validate_message_text, wake_dispatcher,
 };
 use cortex::bootstrap::{self, DoctorReport};
-use cortex::config::{auth_file_path, config_file_path};
+use cortex::config::{auth_file_path, config, config_file_path};
+use cortex::dispatcher::worker_auth::{CurveKeys, curve_key_path};
 use cortex::frontend::audit::AuditDto;
 use cortex::frontend::corpora::CorpusDto;
+use cortex::frontend::dead_letters::DeadLetterDto;
 use cortex::frontend::helpers::group_thousands;
 use cortex::frontend::jobs::JobDto;
 use cortex::frontend::params::{MAX_REPORT_OFFSET, MAX_REPORT_PAGE_SIZE};
-use cortex::frontend::reports::is_valid_rerun_severity;
+use cortex::frontend::reports::{PoisonEntryDto, is_valid_rerun_severity};
+use cortex::frontend::runs::{ClassMoveDto, ClassMoversDto, ClassTrendDto, ClassTrendPointDto};
+use cortex::frontend::search::{MessageHitDto, MessageSearchDto};
 use cortex::frontend::services::ServiceDto;
+use cortex::frontend::worker_keys::WorkerKeyDto;
 use cortex::helpers::TaskStatus;
-use cortex::importer::Importer;
+use cortex::helpers::log_parsers;
+use cortex::importer::preview::{self, ImportPreview};
+use cortex::importer::{Importer, unpackers};
 use cortex::models::{
-  AuditEntry, Corpus, DiffStatusFilter, HistoricalRun, NewCorpus, NewService, Service, Session,
-  Task, WorkerMetadata,
+  ActivationWeight, AuditEntry, Corpus, DeadLetter, DeadLetterResolution, DiffStatusFilter,
+  HistoricalRun, IMPORT_SERVICE_ID, NewCorpus, NewService, PoisonEntry, Service, Session, Task,
+  WorkerKey, WorkerMetadata,
 };
+use cortex::storage::storage;
 
 /// Formats a timestamp the same way the web/agent surfaces do (RFC 3339, seconds) so the CLI's run
 /// JSON matches `RunDto`.
@@ -134,6 +147,66 @@ enum Command {
     #[arg(long)]
     json: bool,
   },
+  /// Triage the dispatch dead-letter queue — tasks the dispatcher gave up on.
+  ///
+  /// The CLI twin of the `/admin/dead-letters` screen and the agent `GET /api/dead-letters`. A task
+  /// whose every lease timed out is reported fatal (`cortex/never_completed_with_retries`) and
+  /// dead-lettered with the workers it was leased to and when: the infrastructure lost it, the
+  /// converter did not crash on it. Lists the open entries by default; `--requeue <ID>` resets the
+  /// task to TODO for another attempt, `--quarantine <ID>` parks it for good (never leased, left
+  /// out of whole-corpus reruns) until requeued.
+  DeadLetters {
+    /// List only entries in this state: open (default), requeued, quarantined, or all.
+    #[arg(long, default_value = "open")]
+    state: String,
+    /// Max entries to list, most-recent first (default 50, capped at 500).
+    #[arg(long)]
+    limit: Option<i64>,
+    /// Requeue this dead-letter's task (by entry id) instead of listing.
+    #[arg(long, conflicts_with = "quarantine")]
+    requeue: Option<i64>,
+    /// Quarantine this dead-letter's task (by entry id) instead of listing.
+    #[arg(long)]
+    quarantine: Option<i64>,
+    /// Who is credited with a requeue / quarantine.
+    #[arg(long, default_value = "admin")]
+    owner: String,
+    /// Emit JSON (the same shape as the agent `DeadLetterDto` list) instead of a text table.
+    #[arg(long)]
+    json: bool,
+  },
+  /// Manage the worker key allowlist of the authenticated worker transport.
+  ///
+  /// The CLI twin of the `/admin/worker-keys` screen and the agent `GET/POST/DELETE
+  /// /api/worker-keys`. With `dispatcher.worker_auth` on, the dispatcher encrypts its worker
+  /// connections (ZMQ CURVE) and accepts only workers whose public key is listed here. Lists the
+  /// keys by default; `--add <KEY>` allowlists a worker's Z85 public key, `--generate` creates a
+  /// fresh worker key pair and allowlists it (the secret is shown once), `--revoke <ID>` refuses a
+  /// key from now on, and `--server-key` prints the dispatcher's public key, creating its key file
+  /// (`dispatcher.curve_key_file`) on first use.
+  WorkerKeys {
+    /// Allowlist this Z85-encoded CURVE public key (40 characters).
+    #[arg(long, conflicts_with_all = ["generate", "revoke", "server_key"])]
+    add: Option<String>,
+    /// Generate a worker key pair and allowlist its public key.
+    #[arg(long, conflicts_with_all = ["revoke", "server_key"])]
+    generate: bool,
+    /// Operator note stored with an added or generated key (host, fleet, owner).
+    #[arg(long, default_value = "")]
+    label: String,
+    /// Revoke the key with this id.
+    #[arg(long, conflicts_with = "server_key")]
+    revoke: Option<i32>,
+    /// Print the dispatcher's CURVE public key, creating the key file if missing.
+    #[arg(long)]
+    server_key: bool,
+    /// Who is credited with an added, generated or revoked key.
+    #[arg(long, default_value = "admin")]
+    owner: String,
+    /// Emit JSON (the same shape as the agent `WorkerKeyDto` list) instead of a text table.
+    #[arg(long)]
+    json: bool,
+  },
   /// List all registered corpora (handles, names, document counts, paths).
   ///
   /// The CLI twin of the overview screen and the agent `GET /api/corpora`, sharing the
@@ -221,6 +294,37 @@ enum Command {
     #[arg(long)]
     json: bool,
   },
+  /// Full-text search over the conversion messages.
+  ///
+  /// The CLI twin of the web `/search` screen + agent `GET /api/search/messages`: the messages
+  /// whose `what` or details match a query (web-search syntax — `"a phrase"`, `or`, `-word`), with
+  /// the documents they belong to and the matches marked `[[…]]`. The same query scopes a sandbox
+  /// (`cortex sandbox --text`) and a rerun (`cortex rerun --text`).
+  Search {
+    /// The query.
+    query: String,
+    /// Only messages of this corpus.
+    #[arg(long)]
+    corpus: Option<String>,
+    /// Only messages of this service (e.g. tex_to_html).
+    #[arg(long)]
+    service: Option<String>,
+    /// Only messages of this severity (`info`|`warning`|`error`|`fatal`|`invalid`).
+    #[arg(long)]
+    severity: Option<String>,
+    /// Only messages of this category.
+    #[arg(long)]
+    category: Option<String>,
+    /// Page offset (default 0).
+    #[arg(long)]
+    offset: Option<i64>,
+    /// Max messages shown (default 100, capped at 1000).
+    #[arg(long)]
+    limit: Option<i64>,
+    /// Emit JSON (the agent `MessageSearchDto` shape) instead of text.
+    #[arg(long)]
+    json: bool,
+  },
   /// Run history for a `(corpus, service)` — per-run severity tallies over time.
   ///
   /// The CLI twin of the web run-history screen + agent `GET /api/runs/<c>/<s>`: each conversion
@@ -234,6 +338,45 @@ enum Command {
     /// Emit JSON (the same shape as the agent `RunDto` list) instead of a text table.
     #[arg(long)]
     json: bool,
+    /// Release gate: exit `1` if the latest completed run regressed against the run before it
+    /// (worse documents, new or jumped message classes), `3` if it has no regression summary
+    /// (yet). With it, an unknown corpus or service exits `2`, like any other usage error.
+    #[arg(long)]
+    check: bool,
+  },
+  /// Message-class trends across the runs of a `(corpus, service)`.
+  ///
+  /// The CLI twin of the web `/runs/<c>/<s>/trends` screen + agent
+  /// `GET /api/runs/<c>/<s>/{trend,movers}`, over the per-run class rollup frozen when each run
+  /// completes on drain. With `--severity` and `--category` (and optionally `--what`), one class's
+  /// document and message counts run by run; otherwise the fastest-growing and fastest-shrinking
+  /// classes between the first and the last run of the window.
+  Trends {
+    /// Corpus name.
+    corpus: String,
+    /// Service name (e.g. tex_to_html).
+    service: String,
+    /// Message severity: warning, error, fatal or invalid (required to chart a class).
+    #[arg(long)]
+    severity: Option<String>,
+    /// Message category to chart.
+    #[arg(long)]
+    category: Option<String>,
+    /// Message `what` to chart (default: the whole category).
+    #[arg(long)]
+    what: Option<String>,
+    /// Grain of the movers: `what` (default) or `category`.
+    #[arg(long, default_value = "what")]
+    grain: String,
+    /// Runs in the window (default 20).
+    #[arg(long)]
+    runs: Option<i64>,
+    /// Movers listed per direction (default 20, max 100).
+    #[arg(long)]
+    limit: Option<i64>,
+    /// Emit JSON (the agent `ClassTrendDto` / `ClassMoversDto` shape) instead of text.
+    #[arg(long)]
+    json: bool,
   },
   /// Compare two saved task-status snapshots of a `(corpus, service)`.
   ///
@@ -280,7 +423,8 @@ enum Command {
   /// Per-article forensics for one document — its status + every log message.
   ///
   /// The CLI twin of the web forensic screen + agent `GET /api/corpus/<c>/<svc>/document/<name>`:
-  /// the document's status + every worker-log message.
+  /// the document's status + every worker-log message, and its failed-lease history if the
+  /// dispatcher ever lost a worker on it. `--release` lifts a poison-document hold.
   Document {
     /// Corpus name.
     corpus: String,
@@ -291,6 +435,12 @@ enum Command {
     /// Include info-level messages (loaded files / debug noise), hidden by default.
     #[arg(long)]
     all: bool,
+    /// Release the document's poison-document hold (for every service) before showing it.
+    #[arg(long)]
+    release: bool,
+    /// Who is credited with a release.
+    #[arg(long, default_value = "admin")]
+    owner: String,
     /// Emit JSON (the same shape as the agent `DocumentReportDto`) instead of a text table.
     #[arg(long)]
     json: bool,
@@ -355,12 +505,20 @@ enum Command {
     /// Restrict to a message `what` (requires `--severity --category`).
     #[arg(long)]
     what: Option<String>,
+    /// Restrict to documents with a message matching this search query (as `cortex search`);
+    /// `--severity` is then a message severity.
+    #[arg(long)]
+    text: Option<String>,
     /// Description recorded for the run (audit trail).
     #[arg(long)]
     description: Option<String>,
     /// Owner credited for the run (audit identity).
     #[arg(long, default_value = "admin")]
     owner: String,
+    /// Leasing priority for the rerun tasks: higher is dispatched sooner, ahead of the service's
+    /// ordinary backlog (priority 0). Omit to return the tasks to the ordinary backlog.
+    #[arg(long, allow_negative_numbers = true)]
+    priority: Option<i32>,
     /// Actually execute the rerun (without this, the command is a dry run that only prints the
     /// scope).
     #[arg(long)]
@@ -380,7 +538,7 @@ enum Command {
     #[arg(long)]
     service: String,
     /// Filter by task status (`todo`|`no_problem`|`warning`|`error`|`fatal`|`invalid`) —
-    /// intersected with the message filter; supply at least one of the two.
+    /// intersected with the message filter; supply at least one of the two (or a document filter).
     #[arg(long)]
     status: Option<String>,
     /// Filter by message severity (`info`|`warning`|`error`|`fatal`|`invalid`) — tasks that
@@ -393,12 +551,18 @@ enum Command {
     /// Restrict to a message `what` within the category.
     #[arg(long)]
     what: Option<String>,
+    /// Restrict to documents with a message matching this search query (as `cortex search`), of
+    /// any severity unless `--message-severity` is given.
+    #[arg(long)]
+    text: Option<String>,
     /// Restrict to entries whose path contains this substring (e.g. `/2506/` for one arXiv month).
     #[arg(long)]
     entry: Option<String>,
     /// Cap the carve at the first N entries (by entry order) — a deterministic size limit.
     #[arg(long)]
     max_entries: Option<i64>,
+    #[command(flatten)]
+    documents: DocumentArgs,
     /// Actually create the sandbox (without this, the command is a dry run that only prints the
     /// scope).
     #[arg(long)]
@@ -411,6 +575,21 @@ enum Command {
   /// (the usual first step after `cortex init`). Runs synchronously to completion (the web/agent
   /// run it as a background job); exits `1` on a name clash, an unreadable path, or an import
   /// error.
+  ///
+  /// With `--manifest`, imports exactly the documents a JSON-lines or CSV manifest lists (a `path`
+  /// column, relative paths resolving against the corpus path), keeping every other column as the
+  /// document's metadata. Missing, unreadable and malformed rows are skipped and reported.
+  ///
+  /// A complex corpus is first unpacked into one `<name>/<name>.zip` per document by its
+  /// `--unpacker`: `arxiv` (the default — top-level `.tar`s of per-paper `.gz` sources),
+  /// `archives` (a tree of per-paper `.zip`/`.tar.gz`/`.tar.zst`/`.tar.xz`), `flat-zip` (top-level
+  /// zips of many papers), `bundles` (top-level `.tar.zst`/`.tar.xz` of many papers) or `loose`
+  /// (one subdirectory per document). `extend` reuses the corpus's unpacker.
+  ///
+  /// With `--dry-run`, nothing is registered, unpacked or imported: the path is walked and every
+  /// source sniffed, then the projected entry count, the per-month spread, the skipped paths (with
+  /// reasons), symlink-loop hits and the largest entries are printed — check those before starting
+  /// a long import.
   Import {
     /// Corpus name — the unique handle used everywhere else.
     name: String,
@@ -422,6 +601,21 @@ enum Command {
     /// Optional human-readable description.
     #[arg(long, default_value = "")]
     description: String,
+    /// Import the documents listed in this manifest (`.jsonl` or `.csv`) instead of walking the
+    /// corpus path.
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+    /// Set the documents' arXiv primary categories from this arXiv metadata file (JSON lines with
+    /// `id` and `categories`, as in the public arXiv metadata snapshot).
+    #[arg(long)]
+    arxiv_metadata: Option<PathBuf>,
+    /// How the sources of a complex corpus are laid out on disk (an unpacker key).
+    #[arg(long, value_name = "KEY")]
+    unpacker: Option<String>,
+    /// Only preview the import: report what it would find, without touching the database or the
+    /// disk.
+    #[arg(long)]
+    dry_run: bool,
   },
   /// Re-scan a corpus's path for newly-arrived documents and import them.
   ///
@@ -433,6 +627,23 @@ enum Command {
   Extend {
     /// Corpus name to re-scan.
     corpus: String,
+    /// Set the documents' arXiv primary categories from this arXiv metadata file.
+    #[arg(long)]
+    arxiv_metadata: Option<PathBuf>,
+  },
+  /// Record the facts of a corpus's documents (month, size, files, main `.tex`, category).
+  ///
+  /// Inspects every entry that has no document row yet — the backfill for a corpus imported before
+  /// documents were inspected (`import`/`extend` inspect new entries themselves) — and optionally
+  /// sets the arXiv primary categories from a metadata file. The facts drive the `/breakdown`
+  /// report and the document filters of `sandbox` and `export-dataset`.
+  IndexDocuments {
+    /// Corpus name to index.
+    corpus: String,
+    /// Set the documents' arXiv primary categories from this arXiv metadata file (JSON lines with
+    /// `id` and `categories`).
+    #[arg(long)]
+    arxiv_metadata: Option<PathBuf>,
   },
   /// Define a new conversion service in the registry.
   ///
@@ -480,6 +691,76 @@ enum Command {
     #[arg(long, conflicts_with = "seconds")]
     clear: bool,
   },
+  /// Set or clear a service's cap on lease renewals.
+  ///
+  /// The CLI twin of the registry screen's inline renewals form and the agent
+  /// `PUT /api/services/<service>/renewals`. A worker still converting a slow document may send
+  /// "still working on task N" heartbeats, each extending the task's lease; the cap bounds how many
+  /// times one lease may be extended (0 disables renewal). `--clear` falls back to the global
+  /// `dispatcher.max_lease_renewals`. Takes effect on the next dispatch.
+  SetLeaseRenewals {
+    /// Service name (e.g. tex_to_html).
+    service: String,
+    /// New cap (0 or more). Omit and pass --clear to remove the override.
+    count: Option<i32>,
+    /// Clear the per-service override (fall back to the global cap).
+    #[arg(long, conflicts_with = "count")]
+    clear: bool,
+  },
+  /// Set how many previous result archives per document a service keeps.
+  ///
+  /// The CLI twin of the registry screen's inline versions form and the agent
+  /// `PUT /api/services/<service>/versions`. With a count above 0, each new result moves the
+  /// document's previous archive aside (tagged with the run that wrote it) instead of overwriting
+  /// it, so the document page can compare two runs side by side; the oldest beyond the count are
+  /// removed. Kept versions age out with the retention prune. 0 overwrites in place.
+  SetResultVersions {
+    /// Service name (e.g. tex_to_html).
+    service: String,
+    /// Previous results to keep per document (0 or more).
+    count: i32,
+  },
+  /// Select the log parser that reads a service's worker logs.
+  ///
+  /// The CLI twin of the registry screen's inline log-format form and the agent
+  /// `PUT /api/services/<service>/log-parser`. `latexml` (the default) reads LaTeXML's messages,
+  /// `json-lines` one JSON object per line, and `exit-code` only the tool's exit code on the last
+  /// line of `cortex.log` — so a service wrapping another tool reports in its own format. Applies
+  /// to results received once the dispatcher refreshes its services.
+  SetLogParser {
+    /// Service name (e.g. tex_to_html).
+    service: String,
+    /// Parser key (latexml, json-lines or exit-code).
+    parser: String,
+  },
+  /// Set what happens to a pipeline service's results when its upstream reruns a document.
+  ///
+  /// The CLI twin of the registry screen's inline pipeline form and the agent
+  /// `PUT /api/services/<service>/pipeline`. A service whose `inputconverter` names another service
+  /// consumes that service's result archives; `rerun` (the default) resets its results for the
+  /// reran documents to await the fresh upstream output, cascading further downstream, while `keep`
+  /// retains them.
+  SetUpstreamRerun {
+    /// Service name (the downstream service).
+    service: String,
+    /// The policy.
+    #[arg(value_parser = ["rerun", "keep"])]
+    policy: String,
+  },
+  /// Set the fair-share weight of a service's activation on a corpus.
+  ///
+  /// The CLI twin of `PUT /api/corpora/<c>/services/<s>/weight`. When several corpora are activated
+  /// for one service, each lease batch is split across them in proportion to their weights — raise
+  /// a sandbox campaign's weight to keep it interactive beside a full-corpus run. Takes effect on
+  /// the dispatcher's next lease batch; `cortex status` shows the target vs achieved shares.
+  SetWeight {
+    /// Corpus name.
+    corpus: String,
+    /// Service name (e.g. tex_to_html).
+    service: String,
+    /// New weight (at least 1).
+    weight: i32,
+  },
   /// Activate a service on a corpus (create its conversion tasks).
   ///
   /// The CLI twin of the corpus screen's "Register a service" form and the agent
@@ -497,6 +778,10 @@ enum Command {
     /// Description recorded for the activation run (audit trail).
     #[arg(long, default_value = "activated via cortex CLI")]
     description: String,
+    /// Fair-share weight: this corpus's relative slice of the service's leases while other corpora
+    /// are converting with it too (default 1).
+    #[arg(long)]
+    weight: Option<i32>,
   },
   /// Deactivate (retire) a service from a corpus.
   ///
@@ -554,7 +839,8 @@ enum Command {
     /// Output directory for the archives + manifest (created if missing).
     #[arg(long)]
     out: PathBuf,
-    /// Bucket archives by `month` (one zip per year-month) or `severity` (one zip per severity).
+    /// Bucket archives by `month` (one zip per year-month), `severity` (one zip per severity) or
+    /// `category` (one zip per arXiv primary category).
     #[arg(long, default_value = "month")]
     group_by: String,
     /// Comma-separated severities to include.
@@ -569,7 +855,99 @@ enum Command {
     /// archive per bucket (no size limit).
     #[arg(long)]
     max_archive_mb: Option<u64>,
+    #[command(flatten)]
+    documents: DocumentArgs,
+  },
+  /// Check a corpus/service's result archives against the task store.
+  ///
+  /// The CLI twin of the agent `POST /api/corpora/<c>/services/<s>/fsck` job. Reports missing
+  /// archives, archives whose size or checksum differs from the one recorded at commit, zips
+  /// without a `cortex.log`, and orphan archives of deleted tasks or sandboxes. Dry-run by
+  /// default; `--reset-damaged` sends the damaged tasks back to TODO and `--remove-orphans`
+  /// deletes the orphans (both audited). Exits `1` if anything is left damaged or orphaned.
+  Fsck {
+    /// Corpus name to check.
+    corpus: String,
+    /// Service name whose result archives are checked.
+    service: String,
+    /// Reset the tasks with a missing or damaged archive to TODO.
+    #[arg(long)]
+    reset_damaged: bool,
+    /// Delete the orphan archives.
+    #[arg(long)]
+    remove_orphans: bool,
+    /// Who is credited with a repair.
+    #[arg(long, default_value = "admin")]
+    owner: String,
+    /// Emit JSON (the same shape as the agent job's result) instead of a text summary.
+    #[arg(long)]
+    json: bool,
+  },
+  /// Collect the result archives of deleted corpora, services and sandboxes.
+  ///
+  /// The CLI twin of the retention screen's archive collection (`/admin/retention`) and the agent
+  /// `POST /api/maintenance/archive-gc` job. Lists every archive no live task corresponds to, with
+  /// its size and the total a delete would free. Dry-run by default; pass `--yes` to delete them
+  /// (audited).
+  GcArchives {
+    /// Actually delete (without this, the command is a dry run that only prints the accounting).
+    #[arg(long)]
+    yes: bool,
+    /// Who is credited with a delete.
+    #[arg(long, default_value = "admin")]
+    owner: String,
+    /// Emit JSON (the same shape as the agent job's result) instead of a text summary.
+    #[arg(long)]
+    json: bool,
   },
+  /// Fingerprint the log messages stored before message fingerprints existed.
+  ///
+  /// Messages without a fingerprint belong to no cluster of the report ladder's "Group by root
+  /// cause" view. Walks each log table in batches of short transactions, so it can run against a
+  /// live deployment and be interrupted and resumed.
+  BackfillFingerprints {
+    /// Messages fingerprinted per statement.
+    #[arg(long, default_value_t = 5000)]
+    batch: i64,
+  },
+}
+
+/// Narrowing by the facts of the entries' documents, shared by `sandbox` and `export-dataset`.
+#[derive(Args)]
+struct DocumentArgs {
+  /// Only documents of this arXiv submission month (`yymm`, e.g. `2605`).
+  #[arg(long)]
+  yymm: Option<String>,
+  /// Only documents of this arXiv primary category (`hep-th`), or of any category of an archive
+  /// (`math`).
+  #[arg(long)]
+  primary_category: Option<String>,
+  /// Only sources of at least this many bytes.
+  #[arg(long)]
+  min_source_bytes: Option<i64>,
+  /// Only sources of at most this many bytes.
+  #[arg(long)]
+  max_source_bytes: Option<i64>,
+  /// Only sources of at least this many files.
+  #[arg(long)]
+  min_files: Option<i32>,
+  /// Only sources of at most this many files.
+  #[arg(long)]
+  max_files: Option<i32>,
+}
+
+impl From<DocumentArgs> for DocumentFilter {
+  fn from(args: DocumentArgs) -> Self {
+    DocumentFilter {
+      yymm: args.yymm,
+      primary_category: args.primary_category,
+      min_source_bytes: args.min_source_bytes,
+      max_source_bytes: args.max_source_bytes,
+      min_files: args.min_files,
+      max_files: args.max_files,
+    }
+    .normalized()
+  }
 }
 
 fn main() {
@@ -592,6 +970,23 @@ fn main() {
       json,
     } => run_jobs(active, limit, json),
     Command::Audit { actor, limit, json } => run_audit(actor, limit, json),
+    Command::DeadLetters {
+      state,
+      limit,
+      requeue,
+      quarantine,
+      owner,
+      json,
+    } => run_dead_letters(state, limit, requeue, quarantine, owner, json),
+    Command::WorkerKeys {
+      add,
+      generate,
+      label,
+      revoke,
+      server_key,
+      owner,
+      json,
+    } => run_worker_keys(add, generate, label, revoke, server_key, owner, json),
     Command::Corpora { json } => run_corpora(json),
     Command::Services { json } => run_services(json),
     Command::Report {
@@ -617,7 +1012,29 @@ fn main() {
       corpus,
       service,
       json,
-    } => run_runs(corpus, service, json),
+      check,
+    } => run_runs(corpus, service, json, check),
+    Command::Trends {
+      corpus,
+      service,
+      severity,
+      category,
+      what,
+      grain,
+      runs,
+      limit,
+      json,
+    } => run_trends(TrendsArgs {
+      corpus,
+      service,
+      severity,
+      category,
+      what,
+      grain,
+      runs,
+      limit,
+      json,
+    }),
     Command::Diff {
       corpus,
       service,
@@ -666,16 +1083,20 @@ fn main() {
       service,
       name,
       all,
+      release,
+      owner,
       json,
-    } => run_document(corpus, service, name, all, json),
+    } => run_document(corpus, service, name, all, release, owner, json),
     Command::Rerun {
       corpus,
       service,
       severity,
       category,
       what,
+      text,
       description,
       owner,
+      priority,
       yes,
     } => run_rerun(
       corpus,
@@ -683,8 +1104,10 @@ fn main() {
       severity,
       category,
       what,
+      text,
       description,
       owner,
+      priority,
       yes,
     ),
     Command::Sandbox {
@@ -695,8 +1118,10 @@ fn main() {
       message_severity,
       category,
       what,
+      text,
       entry,
       max_entries,
+      documents,
       yes,
     } => run_sandbox(
       parent,
@@ -706,8 +1131,10 @@ fn main() {
       message_severity,
       category,
       what,
+      text,
       entry,
       max_entries,
+      documents.into(),
       yes,
     ),
     Command::Import {
@@ -715,8 +1142,28 @@ fn main() {
       path,
       complex,
       description,
-    } => run_import(name, path, complex, description),
-    Command::Extend { corpus } => run_extend(corpus),
+      manifest,
+      arxiv_metadata,
+      unpacker,
+      dry_run,
+    } => run_import(
+      name,
+      path,
+      complex,
+      description,
+      manifest,
+      arxiv_metadata,
+      unpacker,
+      dry_run,
+    ),
+    Command::Extend {
+      corpus,
+      arxiv_metadata,
+    } => run_extend(corpus, arxiv_metadata),
+    Command::IndexDocuments {
+      corpus,
+      arxiv_metadata,
+    } => run_index_documents(corpus, arxiv_metadata),
     Command::CreateService {
       name,
       inputformat,
@@ -739,7 +1186,8 @@ fn main() {
       service,
       owner,
       description,
-    } => run_activate(corpus, service, owner, description),
+      weight,
+    } => run_activate(corpus, service, owner, description, weight),
     Command::Deactivate {
       corpus,
       service,
@@ -750,6 +1198,19 @@ fn main() {
       seconds,
       clear,
     } => run_set_service_lease(service, seconds, clear),
+    Command::SetLeaseRenewals {
+      service,
+      count,
+      clear,
+    } => run_set_lease_renewals(service, count, clear),
+    Command::SetResultVersions { service, count } => run_set_result_versions(service, count),
+    Command::SetLogParser { service, parser } => run_set_log_parser(service, parser),
+    Command::SetUpstreamRerun { service, policy } => run_set_upstream_rerun(service, policy),
+    Command::SetWeight {
+      corpus,
+      service,
+      weight,
+    } => run_set_weight(corpus, service, weight),
     Command::DeleteCorpus { name, yes } => run_delete_corpus(name, yes),
     Command::DeleteService { name, yes } => run_delete_service(name, yes),
     Command::TuneDb => println!("{}", bootstrap::db_tuning_guidance()),
@@ -767,7 +1228,47 @@ fn main() {
       group_by,
       severity,
       max_archive_mb,
-    } => run_export_dataset(corpus, service, out, group_by, severity, max_archive_mb),
+      documents,
+    } => run_export_dataset(
+      corpus,
+      service,
+      out,
+      group_by,
+      severity,
+      max_archive_mb,
+      documents.into(),
+    ),
+    Command::Fsck {
+      corpus,
+      service,
+      reset_damaged,
+      remove_orphans,
+      owner,
+      json,
+    } => run_fsck(
+      corpus,
+      service,
+      FsckOptions {
+        reset_damaged,
+        remove_orphans,
+      },
+      owner,
+      json,
+    ),
+    Command::GcArchives { yes, owner, json } => run_gc_archives(yes, owner, json),
+    Command::BackfillFingerprints { batch } => run_backfill_fingerprints(batch),
+    Command::Search {
+      query,
+      corpus,
+      service,
+      severity,
+      category,
+      offset,
+      limit,
+      json,
+    } => run_search(
+      query, corpus, service, severity, category, offset, limit, json,
+    ),
   }
 }
 
@@ -849,9 +1350,11 @@ fn run_report(args: ReportArgs) {
   }
 }
 
-/// The service overview rung (no drill flags): valid-task total + per-status counts/shares.
+/// The service overview rung (no drill flags): valid-task total + per-status counts/shares, plus
+/// the outstanding backlog per leasing priority.
 fn report_overview(backend: &mut backend::Backend, corpus: &Corpus, service: &Service, json: bool) {
   let stats = backend.progress_report(corpus, service);
+  let backlog = backend.priority_backlog(corpus, service);
   let count = |key: &str| stats.get(key).copied().unwrap_or(0.0) as i64;
   let percent = |key: &str| stats.get(&format!("{key}_percent")).copied().unwrap_or(0.0);
   let total = count("total");
@@ -862,11 +1365,16 @@ fn report_overview(backend: &mut backend::Backend, corpus: &Corpus, service: &Se
         |key| serde_json::json!({ "status": key, "tasks": count(&key), "percent": percent(&key) }),
       )
       .collect();
+    let backlog: Vec<_> = backlog
+      .iter()
+      .map(|(priority, tasks)| serde_json::json!({ "priority": priority, "tasks": tasks }))
+      .collect();
     let overview = serde_json::json!({
       "corpus": corpus.name,
       "service": service.name,
       "total": total,
       "statuses": statuses,
+      "backlog": backlog,
     });
     println!(
       "{}",
@@ -877,6 +1385,14 @@ fn report_overview(backend: &mut backend::Backend, corpus: &Corpus, service: &Se
     for key in TaskStatus::keys() {
       println!("  {:<12} {:>10}  ({:.2}%)", key, count(&key), percent(&key));
     }
+    // The outstanding backlog per leasing priority, in dispatch order — shown once a prioritized
+    // rerun has split it.
+    if backlog.iter().any(|&(priority, _)| priority != 0) {
+      println!("  backlog by priority:");
+      for (priority, tasks) in &backlog {
+        println!("    p{priority:<10} {tasks:>10}");
+      }
+    }
   }
 }
 
@@ -1117,20 +1633,25 @@ fn report_entries(
 /// Prints the run history for a `(corpus, service)` — the CLI surface of the web run-history screen
 /// and the agent `GET /api/runs/<c>/<s>`, via `HistoricalRun::find_by` then `with_live_tallies`
 /// (live for the open run). `--json` mirrors the agent `RunDto` list. Exits `1` on an unknown pair.
-fn run_runs(corpus_name: String, service_name: String, json: bool) {
+/// With `--check`, an unknown pair exits `2` instead (a usage error, which a release gate must not
+/// mistake for a regression); then exits `1` if the latest completed run regressed and `3` if it
+/// carries no regression summary (none completed yet, its summary is still being computed, or it
+/// closed without one) — a gate must not pass blind.
+fn run_runs(corpus_name: String, service_name: String, json: bool, check: bool) {
   let mut backend = backend::from_address(default_db_address());
+  let unknown_pair_exit = if check { 2 } else { 1 };
   let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
     Ok(corpus) => corpus,
     Err(_) => {
       eprintln!("No such corpus: {corpus_name}");
-      std::process::exit(1);
+      std::process::exit(unknown_pair_exit);
     },
   };
   let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
     Ok(service) => service,
     Err(_) => {
       eprintln!("No such service: {service_name}");
-      std::process::exit(1);
+      std::process::exit(unknown_pair_exit);
     },
   };
   let stored =
@@ -1149,6 +1670,7 @@ fn run_runs(corpus_name: String, service_name: String, json: bool) {
           "completed": r.end_time.is_some(), "total": r.total,
           "no_problem": r.no_problem, "warning": r.warning, "error": r.error,
           "fatal": r.fatal, "invalid": r.invalid, "in_progress": r.in_progress,
+          "regressions": r.regressions,
         })
       })
       .collect();
@@ -1195,60 +1717,310 @@ fn run_runs(corpus_name: String, service_name: String, json: bool) {
           r.fatal - older.fatal,
         );
       }
+      if let Some(summary) = RunRegressions::of(r) {
+        println!(
+          "       regressions: {} worse doc(s) · {} new class(es) · {} jumped class(es){}",
+          summary.worse_documents,
+          summary.new_class_count,
+          summary.jumped_class_count,
+          if summary.regressed() {
+            "  [REGRESSED]"
+          } else {
+            ""
+          }
+        );
+      }
       if !r.description.trim().is_empty() {
         println!("       {}", r.description.trim());
       }
     }
   }
-}
-
-/// Parses an optional `--previous`/`--current` snapshot timestamp (the `available_dates` format,
-/// `YYYY-MM-DD HH:MM:SS[.f]`). `None`/empty means "let the backend pick the default pair". The raw
-/// string is returned as the `Err` so the caller can name it in the error message.
-fn parse_cli_snapshot_date(raw: Option<&str>) -> Result<Option<chrono::NaiveDateTime>, String> {
-  match raw.map(str::trim).filter(|value| !value.is_empty()) {
-    None => Ok(None),
-    Some(value) => chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
-      .map(Some)
-      .map_err(|_| value.to_string()),
+  if check {
+    // `find_by` is newest-first, so the first completed run is the latest.
+    let latest = runs.iter().find(|r| r.end_time.is_some());
+    match latest.and_then(|r| RunRegressions::of(r).map(|summary| (r, summary))) {
+      None => {
+        eprintln!(
+          "Check: no completed run of {} / {} has a regression summary.",
+          corpus.name, service.name
+        );
+        std::process::exit(3);
+      },
+      Some((run, summary)) if summary.regressed() => {
+        eprintln!(
+          "Check FAILED: run #{} ({}) regressed: {} worse document(s), {} new and {} jumped message class(es).",
+          run.id,
+          iso(run.start_time),
+          summary.worse_documents,
+          summary.new_class_count,
+          summary.jumped_class_count
+        );
+        for transition in &summary.worse_transitions {
+          eprintln!(
+            "  {} -> {}: {}",
+            transition.previous_status, transition.current_status, transition.task_count
+          );
+        }
+        for class in summary.new_classes.iter().take(10) {
+          eprintln!(
+            "  new    {} {}/{}: {} task(s)",
+            class.severity, class.category, class.what, class.current_tasks
+          );
+        }
+        for class in summary.jumped_classes.iter().take(10) {
+          eprintln!(
+            "  jumped {} {}/{}: {} -> {} task(s)",
+            class.severity, class.category, class.what, class.previous_tasks, class.current_tasks
+          );
+        }
+        std::process::exit(1);
+      },
+      Some((run, _)) => eprintln!(
+        "Check passed: run #{} ({}) has no regressions.",
+        run.id,
+        iso(run.start_time)
+      ),
+    }
   }
 }
 
-/// Compares two saved task-status snapshots of a `(corpus, service)` — the CLI twin of the web
-/// `/runs/<c>/<s>/diff` screen + agent `GET /api/runs/<c>/<s>/diff`, over the shared
-/// `summary_task_diffs`. Prints the snapshots available to compare and the (previous → current)
-/// status-transition matrix; `--json` mirrors the agent `RunDiffDto` shape exactly.
-fn run_diff(
-  corpus_name: String,
-  service_name: String,
-  previous: Option<String>,
-  current: Option<String>,
+/// Inputs for the `trends` command (struct-passed to keep one argument).
+struct TrendsArgs {
+  /// Corpus name.
+  corpus: String,
+  /// Service name.
+  service: String,
+  /// Message severity; with `category`, charts one class.
+  severity: Option<String>,
+  /// Message category to chart.
+  category: Option<String>,
+  /// Message `what` to chart; `None` = the whole category.
+  what: Option<String>,
+  /// Grain of the movers (`what` / `category`).
+  grain: String,
+  /// Runs in the window.
+  runs: Option<i64>,
+  /// Movers listed per direction.
+  limit: Option<i64>,
+  /// Emit JSON instead of text.
   json: bool,
-) {
-  let previous_date = match parse_cli_snapshot_date(previous.as_deref()) {
-    Ok(date) => date,
-    Err(raw) => {
-      eprintln!("error: --previous {raw:?} is not a YYYY-MM-DD HH:MM:SS timestamp");
-      std::process::exit(2);
-    },
-  };
-  let current_date = match parse_cli_snapshot_date(current.as_deref()) {
-    Ok(date) => date,
-    Err(raw) => {
-      eprintln!("error: --current {raw:?} is not a YYYY-MM-DD HH:MM:SS timestamp");
-      std::process::exit(2);
-    },
-  };
+}
 
+/// Prints message-class trends across the runs of a `(corpus, service)` — the CLI surface of the
+/// web trends screen and the agent `GET /api/runs/<c>/<s>/{trend,movers}`, via the shared
+/// `backend::class_trend` / `backend::class_movers`. Exits `2` on an unknown severity or grain or a
+/// category without a severity, `1` on an unknown corpus/service or a failed query.
+fn run_trends(args: TrendsArgs) {
+  let given = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+  let severity = given(args.severity);
+  let category = given(args.category);
+  let what = given(args.what);
+  if let Some(severity) = severity.as_deref()
+    && !CLASS_SEVERITIES.contains(&severity)
+  {
+    eprintln!(
+      "Invalid --severity {severity:?}: use one of {}.",
+      CLASS_SEVERITIES.join("|")
+    );
+    std::process::exit(2);
+  }
+  let Some(grain) = ClassGrain::from_key(&args.grain) else {
+    eprintln!("Invalid --grain {:?}: use what|category.", args.grain);
+    std::process::exit(2);
+  };
+  if category.is_some() && severity.is_none() {
+    eprintln!("--category needs a --severity: a class is charted within one severity.");
+    std::process::exit(2);
+  }
   let mut backend = backend::from_address(default_db_address());
-  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
+  let corpus = match Corpus::find_by_name(&args.corpus.to_lowercase(), &mut backend.connection) {
     Ok(corpus) => corpus,
     Err(_) => {
-      eprintln!("No such corpus: {corpus_name}");
+      eprintln!("No such corpus: {}", args.corpus);
       std::process::exit(1);
     },
   };
-  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+  let service = match Service::find_by_name(&args.service.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
+    Err(_) => {
+      eprintln!("No such service: {}", args.service);
+      std::process::exit(1);
+    },
+  };
+  let runs = args.runs.unwrap_or(DEFAULT_TREND_RUNS);
+
+  if let (Some(severity), Some(category)) = (severity.as_deref(), category.as_deref()) {
+    let points = match class_trend(
+      &mut backend.connection,
+      corpus.id,
+      service.id,
+      severity,
+      category,
+      what.as_deref(),
+      runs,
+    ) {
+      Ok(points) => points,
+      Err(error) => {
+        eprintln!("cortex trends failed: {error}");
+        std::process::exit(1);
+      },
+    };
+    let points: Vec<ClassTrendPointDto> =
+      points.into_iter().map(ClassTrendPointDto::from).collect();
+    if args.json {
+      let dto = ClassTrendDto {
+        severity: severity.to_string(),
+        category: category.to_string(),
+        what,
+        points,
+      };
+      println!("{}", serde_json::to_string_pretty(&dto).unwrap_or_default());
+      return;
+    }
+    let class = match what.as_deref() {
+      Some(what) => format!("{severity} {category}/{what}"),
+      None => format!("{severity} {category}"),
+    };
+    println!(
+      "{class} over the last {} run(s) of {} / {}:",
+      points.len(),
+      corpus.name,
+      service.name
+    );
+    for point in &points {
+      println!(
+        "  {}  {:>8} doc(s)  {:>9} message(s)  {}",
+        point.start_time,
+        group_thousands(point.task_count),
+        group_thousands(point.message_count),
+        point.description.trim()
+      );
+    }
+    return;
+  }
+
+  let limit = args.limit.unwrap_or(20).clamp(1, 100);
+  let movers = match class_movers(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    grain,
+    severity.as_deref(),
+    runs,
+    limit,
+  ) {
+    Ok(movers) => movers,
+    Err(error) => {
+      eprintln!("cortex trends failed: {error}");
+      std::process::exit(1);
+    },
+  };
+  if args.json {
+    let dto = ClassMoversDto {
+      grain: grain.to_key().to_string(),
+      severity,
+      run_count: movers.runs.len(),
+      first_run: movers.runs.first().map(|run| run.public_id.clone()),
+      last_run: movers.runs.last().map(|run| run.public_id.clone()),
+      growing: movers.growing.into_iter().map(ClassMoveDto::from).collect(),
+      shrinking: movers
+        .shrinking
+        .into_iter()
+        .map(ClassMoveDto::from)
+        .collect(),
+    };
+    println!("{}", serde_json::to_string_pretty(&dto).unwrap_or_default());
+    return;
+  }
+  let (Some(first), Some(last)) = (movers.runs.first(), movers.runs.last()) else {
+    println!(
+      "No run of {} / {} has frozen its message classes yet (they are recorded when a run completes on drain).",
+      corpus.name, service.name
+    );
+    return;
+  };
+  println!(
+    "Message classes of {} / {} from run #{} ({}) to run #{} ({}), {} run(s):",
+    corpus.name,
+    service.name,
+    first.id,
+    iso(first.start_time),
+    last.id,
+    iso(last.start_time),
+    movers.runs.len()
+  );
+  for (heading, moved) in [
+    ("Fastest-growing", &movers.growing),
+    ("Fastest-shrinking", &movers.shrinking),
+  ] {
+    println!("{heading}:");
+    if moved.is_empty() {
+      println!("  (none)");
+    }
+    for class in moved {
+      let name = match &class.what {
+        Some(what) => format!("{}/{}", class.category, what),
+        None => class.category.clone(),
+      };
+      println!(
+        "  {:+8}  {} {}  ({} -> {} doc(s))",
+        class.change(),
+        class.severity,
+        name,
+        class.first_tasks,
+        class.last_tasks
+      );
+    }
+  }
+}
+
+/// Parses an optional `--previous`/`--current` snapshot timestamp (the `available_dates` format,
+/// `YYYY-MM-DD HH:MM:SS[.f]`). `None`/empty means "let the backend pick the default pair". The raw
+/// string is returned as the `Err` so the caller can name it in the error message.
+fn parse_cli_snapshot_date(raw: Option<&str>) -> Result<Option<chrono::NaiveDateTime>, String> {
+  match raw.map(str::trim).filter(|value| !value.is_empty()) {
+    None => Ok(None),
+    Some(value) => chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+      .map(Some)
+      .map_err(|_| value.to_string()),
+  }
+}
+
+/// Compares two saved task-status snapshots of a `(corpus, service)` — the CLI twin of the web
+/// `/runs/<c>/<s>/diff` screen + agent `GET /api/runs/<c>/<s>/diff`, over the shared
+/// `summary_task_diffs`. Prints the snapshots available to compare and the (previous → current)
+/// status-transition matrix; `--json` mirrors the agent `RunDiffDto` shape exactly.
+fn run_diff(
+  corpus_name: String,
+  service_name: String,
+  previous: Option<String>,
+  current: Option<String>,
+  json: bool,
+) {
+  let previous_date = match parse_cli_snapshot_date(previous.as_deref()) {
+    Ok(date) => date,
+    Err(raw) => {
+      eprintln!("error: --previous {raw:?} is not a YYYY-MM-DD HH:MM:SS timestamp");
+      std::process::exit(2);
+    },
+  };
+  let current_date = match parse_cli_snapshot_date(current.as_deref()) {
+    Ok(date) => date,
+    Err(raw) => {
+      eprintln!("error: --current {raw:?} is not a YYYY-MM-DD HH:MM:SS timestamp");
+      std::process::exit(2);
+    },
+  };
+
+  let mut backend = backend::from_address(default_db_address());
+  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
+    Ok(corpus) => corpus,
+    Err(_) => {
+      eprintln!("No such corpus: {corpus_name}");
+      std::process::exit(1);
+    },
+  };
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
     Ok(service) => service,
     Err(_) => {
       eprintln!("No such service: {service_name}");
@@ -1485,9 +2257,19 @@ fn run_snapshot(corpus_name: String, service_name: String, json: bool) {
 /// Prints one document's per-article forensics — the CLI surface of the web forensic screen + agent
 /// `GET /api/corpus/<c>/<svc>/document/<name>` (via the shared `Task::find_by_name` +
 /// `backend::task_messages`). Leads with the status + a severity-count summary, then the actionable
-/// messages; info noise is hidden unless `--all`. `--json` mirrors `DocumentReportDto`. Exits `1`
-/// on an unknown corpus / service / document.
-fn run_document(corpus_name: String, service_name: String, name: String, all: bool, json: bool) {
+/// messages; info noise is hidden unless `--all`, and the failed-lease / poison hold history is
+/// shown when there is one. `--release` first lifts the document's hold (`PoisonEntry::release`) as
+/// `--owner`. `--json` mirrors `DocumentReportDto`. Exits `1` on an unknown corpus / service /
+/// document, or on `--release` of a document that is not held.
+fn run_document(
+  corpus_name: String,
+  service_name: String,
+  name: String,
+  all: bool,
+  release: bool,
+  owner: String,
+  json: bool,
+) {
   let mut backend = backend::from_address(default_db_address());
   let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
     Ok(corpus) => corpus,
@@ -1513,6 +2295,26 @@ fn run_document(corpus_name: String, service_name: String, name: String, all: bo
       std::process::exit(1);
     },
   };
+  if release {
+    match backend.release_poison_entry(&task.entry, &owner) {
+      Ok(_) => eprintln!("Released the hold on {}.", task.entry.trim_end()),
+      Err(diesel::result::Error::NotFound) => {
+        eprintln!("{name} is not held.");
+        std::process::exit(1);
+      },
+      Err(error) => {
+        eprintln!("Could not release {name}: {error}");
+        std::process::exit(1);
+      },
+    }
+  }
+  let poison = match PoisonEntry::find(&mut backend.connection, &task.entry) {
+    Ok(entry) => entry.map(|entry| PoisonEntryDto::load(entry, &mut backend.connection)),
+    Err(error) => {
+      eprintln!("Could not read the poison history of {name}: {error}");
+      std::process::exit(1);
+    },
+  };
   let status = TaskStatus::from_raw(task.status);
   // Sampled at backend::DOCUMENT_MESSAGE_CAP per severity; `counts` are the true totals so the
   // summary is accurate even when a pathological document carries millions of messages.
@@ -1542,6 +2344,7 @@ fn run_document(corpus_name: String, service_name: String, name: String, all: bo
         "fatal": counts.fatal, "invalid": counts.invalid, "total": counts.total(),
       },
       "messages_truncated": truncated,
+      "poison": poison,
     });
     println!(
       "{}",
@@ -1564,6 +2367,24 @@ fn run_document(corpus_name: String, service_name: String, name: String, all: bo
       counts.invalid,
       counts.info,
     );
+    if let Some(history) = &poison {
+      if let Some(since) = &history.held_at {
+        println!(
+          "  HELD as a poison document since {since}: not leased by any service until released (--release)"
+        );
+      }
+      println!(
+        "  {} failed lease(s), {} since the last release; latest {} ({}, {})",
+        history.incidents,
+        history.strikes,
+        history.last_incident_at,
+        history.last_service.as_deref().unwrap_or("-"),
+        history.last_worker.as_deref().unwrap_or("-"),
+      );
+      if let (Some(by), Some(at)) = (&history.released_by, &history.released_at) {
+        println!("  last released by {by} at {at}");
+      }
+    }
     if truncated {
       println!(
         "  (showing a sample of {shown}; this document has {} messages total)",
@@ -1624,6 +2445,15 @@ fn run_pause_resume(corpus_name: String, service_name: String, json: bool, pause
       std::process::exit(1);
     },
   };
+  let kind = if pause {
+    ControlKind::Pause
+  } else {
+    ControlKind::Resume
+  };
+  wake_dispatcher(
+    &mut backend.connection,
+    ControlSignal::scoped(kind, corpus.id, service.id),
+  );
   if json {
     println!(
       "{}",
@@ -1645,7 +2475,8 @@ fn run_pause_resume(corpus_name: String, service_name: String, json: bool, pause
 
 /// Marks a filtered scope for reconversion — the CLI surface of the web/agent rerun, via the shared
 /// `Backend::mark_rerun` (resets the matching tasks to TODO for the dispatcher). Dry-run by
-/// default; `--yes` executes. Exits `1` on an unknown corpus/service, `2` on an invalid severity.
+/// default; `--yes` executes. Exits `1` on an unknown corpus/service, `2` on an invalid severity
+/// or search query.
 #[allow(clippy::too_many_arguments)]
 fn run_rerun(
   corpus_name: String,
@@ -1653,8 +2484,10 @@ fn run_rerun(
   severity: Option<String>,
   category: Option<String>,
   what: Option<String>,
+  text: Option<String>,
   description: Option<String>,
   owner: String,
+  priority: Option<i32>,
   yes: bool,
 ) {
   let mut backend = backend::from_address(default_db_address());
@@ -1672,14 +2505,22 @@ fn run_rerun(
       std::process::exit(1);
     },
   };
+  let text = match text.as_deref().map(validate_message_text) {
+    None => None,
+    Some(Ok(text)) => Some(text.to_string()),
+    Some(Err(reason)) => {
+      eprintln!("Invalid --text: {reason}");
+      std::process::exit(2);
+    },
+  };
   // Validate the rerun severity with the SAME context-aware rule as the agent + human surfaces
-  // (R-9): without `--category` it's a task status; with one it's a message severity.
+  // (R-9): without `--category`/`--text` it's a task status; with one it's a message severity.
   if let Some(sev) = &severity
-    && !is_valid_rerun_severity(sev, category.is_some())
+    && !is_valid_rerun_severity(sev, category.is_some() || text.is_some())
   {
     eprintln!(
-      "Invalid --severity {sev:?} for this rerun: without --category use a task status \
-         (no_problem|warning|error|fatal|invalid); with --category use a message severity \
+      "Invalid --severity {sev:?} for this rerun: without --category/--text use a task status \
+         (no_problem|warning|error|fatal|invalid); with either use a message severity \
          (warning|error|fatal|invalid|info)."
     );
     std::process::exit(2);
@@ -1694,6 +2535,12 @@ fn run_rerun(
   if let Some(w) = &what {
     scope.push_str(&format!("  what={w}"));
   }
+  if let Some(t) = &text {
+    scope.push_str(&format!("  text={t:?}"));
+  }
+  if let Some(p) = priority {
+    scope.push_str(&format!("  priority={p}"));
+  }
 
   if !yes {
     println!("Dry run — would mark for reconversion:");
@@ -1707,11 +2554,19 @@ fn run_rerun(
     severity_opt: severity,
     category_opt: category,
     what_opt: what,
+    text_opt: text,
     owner_opt: Some(owner),
     description_opt: Some(description.unwrap_or_else(|| "cli rerun".to_string())),
+    priority_opt: priority,
   };
   match backend.mark_rerun(options) {
-    Ok(()) => println!("Marked for reconversion: {scope}"),
+    Ok(()) => {
+      wake_dispatcher(
+        &mut backend.connection,
+        ControlSignal::scoped(ControlKind::Rerun, corpus.id, service.id),
+      );
+      println!("Marked for reconversion: {scope}")
+    },
     Err(error) => {
       eprintln!("rerun failed: {error}");
       std::process::exit(1);
@@ -1722,7 +2577,7 @@ fn run_rerun(
 /// Carves a sandbox corpus from a parent by a message-condition filter — the CLI surface of the
 /// web/agent sandbox, via the shared `backend::create_sandbox` (one backend op, three surfaces).
 /// Dry-run by default; `--yes` creates. Exits `1` on an unknown parent/service or a taken sandbox
-/// name, `2` on an invalid severity.
+/// name, `2` on an invalid filter.
 #[allow(clippy::too_many_arguments)]
 fn run_sandbox(
   parent_name: String,
@@ -1732,8 +2587,10 @@ fn run_sandbox(
   message_severity: Option<String>,
   category: Option<String>,
   what: Option<String>,
+  text: Option<String>,
   entry: Option<String>,
   max_entries: Option<i64>,
+  documents: DocumentFilter,
   yes: bool,
 ) {
   let mut backend = backend::from_address(default_db_address());
@@ -1764,9 +2621,11 @@ fn run_sandbox(
     message_severity: blank_to_none(message_severity),
     category: blank_to_none(category),
     what: blank_to_none(what),
+    text: blank_to_none(text),
     entry: blank_to_none(entry),
     max_entries: max_entries.filter(|n| *n > 0),
     severity: None,
+    document: documents,
   };
   // Pre-flight the intersecting status/message filters (the same set as the web/agent path).
   if let Err(reason) = selection.validate() {
@@ -1858,7 +2717,7 @@ fn run_delete_service(name: String, yes: bool) {
       std::process::exit(1);
     },
   };
-  if service.id <= 2 {
+  if service.id <= IMPORT_SERVICE_ID {
     eprintln!(
       "'{}' is an infrastructure service (init/import) and cannot be deleted.",
       service.name
@@ -1897,9 +2756,34 @@ fn run_delete_service(name: String, yes: bool) {
 /// Registers a corpus and imports its documents synchronously — the CLI surface of the web "Add a
 /// corpus" form and the agent `POST /api/corpora`, driving the same `Importer` machinery (one
 /// capability across all three surfaces). Pre-flights the path + name like the agent does (so a
-/// doomed import is never half-registered), runs the walk to completion, then prints the
-/// document count. Exits `1` on a name clash, an unreadable path, or an import error.
-fn run_import(name: String, path: String, complex: bool, description: String) {
+/// doomed import is never half-registered), runs the walk (or the `--manifest` import) to
+/// completion, then prints the document count. Exits `1` on a name clash, an unreadable path, or an
+/// import error. With `dry_run`, stops after the pre-flight and prints the import preview instead.
+#[allow(clippy::too_many_arguments)]
+fn run_import(
+  name: String,
+  path: String,
+  complex: bool,
+  description: String,
+  manifest: Option<PathBuf>,
+  arxiv_metadata: Option<PathBuf>,
+  unpacker: Option<String>,
+  dry_run: bool,
+) {
+  let unpacker = unpacker.filter(|key| key != unpackers::DEFAULT_UNPACKER);
+  if let Some(key) = &unpacker {
+    if unpackers::find(key).is_none() {
+      eprintln!(
+        "error: --unpacker {key:?} is not a known unpacker (use {})",
+        unpackers::keys()
+      );
+      std::process::exit(2);
+    }
+    if !complex {
+      eprintln!("error: --unpacker applies to --complex corpora only");
+      std::process::exit(2);
+    }
+  }
   let mut backend = backend::from_address(default_db_address());
   // Name must be free (matches the agent's 409).
   if Corpus::find_by_name(&name, &mut backend.connection).is_ok() {
@@ -1915,6 +2799,24 @@ fn run_import(name: String, path: String, complex: bool, description: String) {
     eprintln!("Path {path:?} is not a readable directory on this host.");
     std::process::exit(1);
   }
+  if let Some(manifest) = &manifest
+    && !std::fs::metadata(manifest)
+      .map(|meta| meta.is_file())
+      .unwrap_or(false)
+  {
+    eprintln!("Manifest {manifest:?} is not a readable file on this host.");
+    std::process::exit(1);
+  }
+  if dry_run {
+    match preview::preview_import(&path, complex, unpacker.as_deref(), manifest.as_deref()) {
+      Ok(report) => print_import_preview(&name, &path, &report),
+      Err(error) => {
+        eprintln!("Preview failed: {error}");
+        std::process::exit(1);
+      },
+    }
+    return;
+  }
   if let Err(error) = backend.add(&NewCorpus {
     name: name.clone(),
     path: path.clone(),
@@ -1924,25 +2826,72 @@ fn run_import(name: String, path: String, complex: bool, description: String) {
     eprintln!("Could not register the corpus: {error}");
     std::process::exit(1);
   }
-  let corpus = match Corpus::find_by_name(&name, &mut backend.connection) {
+  let mut corpus = match Corpus::find_by_name(&name, &mut backend.connection) {
     Ok(corpus) => corpus,
     Err(error) => {
       eprintln!("Registered the corpus but could not reload it: {error}");
       std::process::exit(1);
     },
   };
+  if unpacker.is_some()
+    && let Err(error) = corpus.set_unpacker(unpacker.as_deref(), &mut backend.connection)
+  {
+    eprintln!("Registered the corpus but could not set its unpacker: {error}");
+    std::process::exit(1);
+  }
   let corpus_id = corpus.id;
-  println!("Importing {name} from {path} …");
+  if complex && manifest.is_none() {
+    println!(
+      "Importing {name} from {path} (unpacker: {}) …",
+      corpus.unpacker_key()
+    );
+  } else {
+    println!("Importing {name} from {path} …");
+  }
   let mut importer = Importer {
     corpus,
     backend,
     cwd: Importer::cwd(),
     active_prefixes: std::collections::HashSet::new(),
   };
-  if let Err(error) = importer.process() {
+  if let Some(manifest) = manifest {
+    match importer.import_manifest(&manifest) {
+      Ok(report) => {
+        println!(
+          "Manifest: {} row(s) listed, {} valid; skipped {} missing, {} unreadable, {} invalid.",
+          group_thousands(report.listed as i64),
+          group_thousands(report.valid as i64),
+          group_thousands(report.missing as i64),
+          group_thousands(report.unreadable as i64),
+          group_thousands(report.invalid as i64),
+        );
+        for problem in &report.problems {
+          println!(
+            "  line {}: {} — {}",
+            problem.line,
+            problem.path.as_deref().unwrap_or("(no path)"),
+            problem.reason
+          );
+        }
+        if report.skipped() > report.problems.len() {
+          println!(
+            "  … and {} more skipped row(s).",
+            report.skipped() - report.problems.len()
+          );
+        }
+      },
+      Err(error) => {
+        eprintln!("Import failed: {error}");
+        std::process::exit(1);
+      },
+    }
+  } else if let Err(error) = importer.process() {
     eprintln!("Import failed: {error}");
     std::process::exit(1);
   }
+  if let Some(arxiv_metadata) = arxiv_metadata {
+    apply_arxiv_metadata(&mut importer, &arxiv_metadata);
+  }
   let imported = Corpus::document_counts(&mut importer.backend.connection)
     .get(&corpus_id)
     .copied()
@@ -1953,6 +2902,74 @@ fn run_import(name: String, path: String, complex: bool, description: String) {
   );
 }
 
+/// Prints an import preview (`cortex import --dry-run`): the projected entries with their
+/// per-month spread, then the largest entries, the skipped paths and the symlink-loop hits.
+fn print_import_preview(name: &str, path: &str, report: &ImportPreview) {
+  let unpacker = report
+    .unpacker
+    .as_deref()
+    .map(|key| format!(" (unpacker: {key})"))
+    .unwrap_or_default();
+  println!("Dry run: importing {name} from {path}{unpacker} would register:");
+  println!(
+    "  {} entr{} — {} already unpacked, {} to unpack",
+    group_thousands(report.entries as i64),
+    if report.entries == 1 { "y" } else { "ies" },
+    group_thousands(report.unpacked as i64),
+    group_thousands(report.to_unpack as i64),
+  );
+  if !report.months.is_empty() {
+    println!("By month:");
+    for (yymm, count) in &report.months {
+      println!("  {yymm}  {:>10}", group_thousands(*count as i64));
+    }
+    if report.unknown_month > 0 {
+      println!(
+        "  ????  {:>10}",
+        group_thousands(report.unknown_month as i64)
+      );
+    }
+  }
+  if !report.largest.is_empty() {
+    println!("Largest entries:");
+    for entry in &report.largest {
+      println!(
+        "  {:>15} bytes  {}",
+        group_thousands(entry.bytes as i64),
+        entry.entry
+      );
+    }
+  }
+  println!(
+    "Skipped: {} path(s).",
+    group_thousands(report.skipped as i64)
+  );
+  for skipped in &report.skipped_paths {
+    println!("  {} — {}", skipped.path, skipped.reason);
+  }
+  if report.skipped > report.skipped_paths.len() {
+    println!(
+      "  … and {} more.",
+      group_thousands((report.skipped - report.skipped_paths.len()) as i64)
+    );
+  }
+  if report.symlink_loops > 0 {
+    println!(
+      "Symlink loops: {} director{} beyond the walk depth — not followed:",
+      group_thousands(report.symlink_loops as i64),
+      if report.symlink_loops == 1 {
+        "y"
+      } else {
+        "ies"
+      },
+    );
+    for path in &report.loop_paths {
+      println!("  {path}");
+    }
+  }
+  println!("Nothing was registered or written. Re-run without --dry-run to import.");
+}
+
 /// Defines a new conversion service in the registry — the CLI surface of the registry screen's
 /// "Register a service" form and the agent `POST /api/services`. Defines the service (it does *not*
 /// activate it on any corpus — that's `activate`). An empty `--inputconverter` is treated as none.
@@ -2028,63 +3045,235 @@ fn run_set_service_lease(service_name: String, seconds: Option<i32>, clear: bool
   }
 }
 
-/// Re-scans a corpus's path for newly-arrived documents and imports them — the CLI surface of the
-/// corpus screen's "Extend" button and the agent `POST /api/corpora/<name>/extend`, driving the
-/// same `Importer::extend_corpus` + `Backend::extend_service`. Adds an import task per new document
-/// and extends each already-active real service (id > 2) to cover them, leaving existing tasks +
-/// results untouched. Prints how many new documents were added. Exits `1` on an unknown corpus or
-/// an extend error.
-fn run_extend(corpus_name: String) {
+/// Sets or clears a service's lease-renewal cap. The CLI twin of
+/// `PUT /api/services/<service>/renewals`. Exits `2` on bad args, `1` if the service is unknown or
+/// the update fails.
+fn run_set_lease_renewals(service_name: String, count: Option<i32>, clear: bool) {
+  let value = match (clear, count) {
+    (true, _) => None,
+    (false, Some(count)) if count >= 0 => Some(count),
+    (false, Some(_)) => {
+      eprintln!("error: <COUNT> must be zero or more (or pass --clear to remove it)");
+      std::process::exit(2);
+    },
+    (false, None) => {
+      eprintln!("error: provide <COUNT> to set the cap, or --clear to remove the override");
+      std::process::exit(2);
+    },
+  };
   let mut backend = backend::from_address(default_db_address());
-  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
-    Ok(corpus) => corpus,
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
     Err(_) => {
-      eprintln!("No such corpus: {corpus_name}");
+      eprintln!("No service named {service_name:?}.");
       std::process::exit(1);
     },
   };
-  let corpus_id = corpus.id;
-  let corpus_path = corpus.path.clone();
-  // Pre-flight the source path (extend re-scans it): a gone/unreadable mount otherwise silently
-  // yields "0 new" (glob over a missing dir is an empty set, not an error). Fail transparently —
-  // the same guard the web/agent extend applies (422 there, exit 1 here).
-  if !std::path::Path::new(corpus_path.trim_end()).is_dir() {
-    eprintln!(
-      "error: corpus path {corpus_path:?} is not a readable directory — is the data mount present?"
-    );
+  if let Err(error) = service.set_max_lease_renewals(value, &mut backend.connection) {
+    eprintln!("Could not update the renewal cap: {error}");
     std::process::exit(1);
   }
-  let before = Corpus::document_counts(&mut backend.connection)
-    .get(&corpus_id)
-    .copied()
-    .unwrap_or(0);
-  println!(
-    "Extending {} — re-scanning {corpus_path} for new documents …",
-    corpus.name
-  );
-  let mut importer = Importer {
-    corpus,
-    backend,
-    cwd: Importer::cwd(),
-    active_prefixes: std::collections::HashSet::new(),
+  match value {
+    Some(count) => println!(
+      "Set '{service_name}' to at most {count} lease renewal(s) per task (effective on the next \
+       dispatch)."
+    ),
+    None => println!(
+      "Cleared '{service_name}' renewal cap override (using the global dispatcher default)."
+    ),
+  }
+}
+
+/// Sets how many previous result archives per document a service keeps. The CLI twin of
+/// `PUT /api/services/<service>/versions`. Exits `2` on a negative count, `1` if the service is
+/// unknown or the update fails.
+fn run_set_result_versions(service_name: String, count: i32) {
+  if count < 0 {
+    eprintln!("error: <COUNT> must be zero or more");
+    std::process::exit(2);
+  }
+  let mut backend = backend::from_address(default_db_address());
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
+    Err(_) => {
+      eprintln!("No service named {service_name:?}.");
+      std::process::exit(1);
+    },
   };
-  if let Err(error) = importer.extend_corpus() {
-    eprintln!("Extend failed: {error}");
+  if let Err(error) = service.set_keep_result_versions(count, &mut backend.connection) {
+    eprintln!("Could not update the kept result versions: {error}");
     std::process::exit(1);
   }
-  // Extend each already-active real service (id > 2) to the new documents so they get conversion
+  if count == 0 {
+    println!("'{service_name}' now overwrites its results in place (no versions kept).");
+  } else {
+    println!(
+      "'{service_name}' now keeps the {count} previous result(s) of each document (from its next \
+       result on)."
+    );
+  }
+}
+
+/// Selects the log parser of a service. The CLI twin of `PUT /api/services/<service>/log-parser`.
+/// Exits `2` on an unknown parser key, `1` if the service is unknown or the update fails.
+fn run_set_log_parser(service_name: String, parser: String) {
+  if log_parsers::find(&parser).is_none() {
+    eprintln!(
+      "error: {parser:?} is not a known log parser (use {})",
+      log_parsers::keys()
+    );
+    std::process::exit(2);
+  }
+  let mut backend = backend::from_address(default_db_address());
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
+    Err(_) => {
+      eprintln!("No service named {service_name:?}.");
+      std::process::exit(1);
+    },
+  };
+  let key = Some(parser.as_str()).filter(|key| *key != log_parsers::DEFAULT_LOG_PARSER);
+  if let Err(error) = service.set_log_parser(key, &mut backend.connection) {
+    eprintln!("Could not update the log parser: {error}");
+    std::process::exit(1);
+  }
+  println!("'{service_name}' worker logs are now read as {parser} (from its next result on).");
+}
+
+/// Sets a pipeline service's `on_upstream_rerun` policy — the CLI twin of
+/// `PUT /api/services/<service>/pipeline`. Exits `1` if the service is unknown or the update fails.
+fn run_set_upstream_rerun(service_name: String, policy: String) {
+  let mut backend = backend::from_address(default_db_address());
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
+    Err(_) => {
+      eprintln!("No service named {service_name:?}.");
+      std::process::exit(1);
+    },
+  };
+  if let Err(error) = service.set_on_upstream_rerun(&policy, &mut backend.connection) {
+    eprintln!("Could not update the pipeline policy: {error}");
+    std::process::exit(1);
+  }
+  match service.upstream() {
+    Some(upstream) => {
+      println!("Set '{service_name}' to {policy} its results when '{upstream}' reruns a document.")
+    },
+    None => println!(
+      "Set '{service_name}' on-upstream-rerun to {policy} (it has no upstream service yet, so this \
+       applies once its converter names one)."
+    ),
+  }
+}
+
+/// Sets the fair-share weight of `service`'s activation on `corpus` — the CLI twin of
+/// `PUT /api/corpora/<c>/services/<s>/weight`, with the same checks. Exits `1` on a weight below 1,
+/// an unknown corpus/service or a service not activated on the corpus.
+fn run_set_weight(corpus_name: String, service_name: String, weight: i32) {
+  if let Err(reason) = ActivationWeight::validate(weight) {
+    eprintln!("{reason}");
+    std::process::exit(1);
+  }
+  let mut backend = backend::from_address(default_db_address());
+  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
+    Ok(corpus) => corpus,
+    Err(_) => {
+      eprintln!("No such corpus: {corpus_name}");
+      std::process::exit(1);
+    },
+  };
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
+    Err(_) => {
+      eprintln!("No such service: {service_name}");
+      std::process::exit(1);
+    },
+  };
+  if !ActivationWeight::is_activated(&mut backend.connection, corpus.id, service.id) {
+    eprintln!("{} is not activated on {}.", service.name, corpus.name);
+    std::process::exit(1);
+  }
+  if let Err(error) = backend.set_activation_weight(&corpus, &service, weight) {
+    eprintln!("Could not set the weight: {error}");
+    std::process::exit(1);
+  }
+  println!(
+    "Set {} on {} to fair-share weight {weight} (effective on the next lease batch).",
+    service.name, corpus.name
+  );
+}
+
+/// Re-scans a corpus's path for newly-arrived documents and imports them — the CLI surface of the
+/// corpus screen's "Extend" button and the agent `POST /api/corpora/<name>/extend`, driving the
+/// same `Importer::extend_corpus` + `Backend::extend_service`. Adds an import task per new document
+/// and extends each already-active real service (id > 2) to cover them, leaving existing tasks +
+/// results untouched. Prints how many new documents were added. Exits `1` on an unknown corpus or
+/// an extend error.
+fn run_extend(corpus_name: String, arxiv_metadata: Option<PathBuf>) {
+  let mut backend = backend::from_address(default_db_address());
+  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
+    Ok(corpus) => corpus,
+    Err(_) => {
+      eprintln!("No such corpus: {corpus_name}");
+      std::process::exit(1);
+    },
+  };
+  let corpus_id = corpus.id;
+  let corpus_path = corpus.path.clone();
+  // Pre-flight the source path (extend re-scans it): a gone/unreadable mount otherwise silently
+  // yields "0 new" (glob over a missing dir is an empty set, not an error). Fail transparently —
+  // the same guard the web/agent extend applies (422 there, exit 1 here).
+  if !std::path::Path::new(corpus_path.trim_end()).is_dir() {
+    eprintln!(
+      "error: corpus path {corpus_path:?} is not a readable directory — is the data mount present?"
+    );
+    std::process::exit(1);
+  }
+  let before = Corpus::document_counts(&mut backend.connection)
+    .get(&corpus_id)
+    .copied()
+    .unwrap_or(0);
+  println!(
+    "Extending {} — re-scanning {corpus_path} for new documents …",
+    corpus.name
+  );
+  let mut importer = Importer {
+    corpus,
+    backend,
+    cwd: Importer::cwd(),
+    active_prefixes: std::collections::HashSet::new(),
+  };
+  let report = match importer.extend_corpus() {
+    Ok(report) => report,
+    Err(error) => {
+      eprintln!("Extend failed: {error}");
+      std::process::exit(1);
+    },
+  };
+  if let Some(arxiv_metadata) = arxiv_metadata {
+    apply_arxiv_metadata(&mut importer, &arxiv_metadata);
+  }
+  // Extend each already-active real service (id > 2) to the new documents so they get conversion
   // tasks too — the magic init/import services are not "activated" services. A per-service failure
   // is logged but doesn't abort the others.
   let services = importer
     .corpus
     .select_services(&mut importer.backend.connection)
     .unwrap_or_default();
-  for service in services.iter().filter(|service| service.id > 2) {
+  for service in services
+    .iter()
+    .filter(|service| service.id > IMPORT_SERVICE_ID)
+  {
     if let Err(error) = importer.backend.extend_service(service, &importer.corpus) {
       eprintln!(
         "Warning: could not extend service {}: {error}",
         service.name
       );
+    } else {
+      wake_dispatcher(
+        &mut importer.backend.connection,
+        ControlSignal::scoped(ControlKind::Activate, corpus_id, service.id),
+      );
     }
   }
   let after = Corpus::document_counts(&mut importer.backend.connection)
@@ -2096,6 +3285,91 @@ fn run_extend(corpus_name: String) {
     group_thousands(after - before),
     group_thousands(after)
   );
+  println!(
+    "  changed in place: {} ({} conversion task(s) reset to TODO)",
+    group_thousands(report.changed as i64),
+    group_thousands(report.reset_tasks as i64)
+  );
+  for entry in &report.changed_entries {
+    println!("    {entry}");
+  }
+  println!(
+    "  vanished: {} (flagged; their tasks are kept)",
+    group_thousands(report.vanished as i64)
+  );
+  for entry in &report.vanished_entries {
+    println!("    {entry}");
+  }
+  let unlisted =
+    report.changed - report.changed_entries.len() + report.vanished - report.vanished_entries.len();
+  if unlisted > 0 {
+    println!(
+      "  … and {} more not listed",
+      group_thousands(unlisted as i64)
+    );
+  }
+  if report.reappeared > 0 {
+    println!(
+      "  reappeared: {} (vanished earlier, now back)",
+      group_thousands(report.reappeared as i64)
+    );
+  }
+  if report.unreadable > 0 {
+    println!(
+      "  unreadable: {} (could not be checked; see the warnings above)",
+      group_thousands(report.unreadable as i64)
+    );
+  }
+}
+
+/// Records the document facts of every not-yet-inspected entry of a corpus (the backfill for a
+/// corpus imported before documents were inspected), then optionally applies an arXiv metadata
+/// file. Exits `1` on an unknown corpus or an unreadable source/metadata file.
+fn run_index_documents(corpus_name: String, arxiv_metadata: Option<PathBuf>) {
+  let mut backend = backend::from_address(default_db_address());
+  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
+    Ok(corpus) => corpus,
+    Err(_) => {
+      eprintln!("No such corpus: {corpus_name}");
+      std::process::exit(1);
+    },
+  };
+  println!("Indexing the documents of {} …", corpus.name);
+  let mut importer = Importer {
+    corpus,
+    backend,
+    cwd: Importer::cwd(),
+    active_prefixes: std::collections::HashSet::new(),
+  };
+  match importer.index_documents() {
+    Ok(inspected) => println!(
+      "Inspected {} new document(s).",
+      group_thousands(inspected as i64)
+    ),
+    Err(error) => {
+      eprintln!("Indexing failed: {error}");
+      std::process::exit(1);
+    },
+  }
+  if let Some(arxiv_metadata) = arxiv_metadata {
+    apply_arxiv_metadata(&mut importer, &arxiv_metadata);
+  }
+}
+
+/// Sets a corpus's arXiv primary categories from `path` for `import`/`extend`/`index-documents`,
+/// exiting `1` if the file cannot be read.
+fn apply_arxiv_metadata(importer: &mut Importer, path: &std::path::Path) {
+  match importer.apply_arxiv_metadata(path) {
+    Ok(updated) => println!(
+      "Set the primary category of {} document(s) from {}.",
+      group_thousands(updated as i64),
+      path.display()
+    ),
+    Err(error) => {
+      eprintln!("Could not apply arXiv metadata {}: {error}", path.display());
+      std::process::exit(1);
+    },
+  }
 }
 
 /// Activates a `service` on a `corpus` synchronously — the CLI surface of the corpus screen's
@@ -2103,8 +3377,18 @@ fn run_extend(corpus_name: String) {
 /// `Backend::register_service` (which is idempotent-neutral: it *refuses* an already-activated pair
 /// rather than wiping its results). Creates one TODO task per imported document, then prints the
 /// count. Exits `1` on an unknown corpus/service, an infrastructure service, or an activation
-/// error.
-fn run_activate(corpus_name: String, service_name: String, owner: String, description: String) {
+/// error. `--weight` sets the new activation's fair-share weight.
+fn run_activate(
+  corpus_name: String,
+  service_name: String,
+  owner: String,
+  description: String,
+  weight: Option<i32>,
+) {
+  if let Some(Err(reason)) = weight.map(ActivationWeight::validate) {
+    eprintln!("{reason}");
+    std::process::exit(1);
+  }
   let mut backend = backend::from_address(default_db_address());
   let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
     Ok(corpus) => corpus,
@@ -2122,7 +3406,7 @@ fn run_activate(corpus_name: String, service_name: String, owner: String, descri
   };
   // Magic ids 1=init, 2=import are infrastructure (CLAUDE.md); activating them is nonsensical and
   // the backend would otherwise reject it as "already registered" — give a clear reason instead.
-  if service.id <= 2 {
+  if service.id <= IMPORT_SERVICE_ID {
     eprintln!(
       "'{}' is an infrastructure service (init/import) and cannot be activated on a corpus.",
       service.name
@@ -2136,6 +3420,16 @@ fn run_activate(corpus_name: String, service_name: String, owner: String, descri
     eprintln!("Activation failed: {error}");
     std::process::exit(1);
   }
+  if let Some(weight) = weight
+    && let Err(error) = backend.set_activation_weight(&corpus, &service, weight)
+  {
+    eprintln!("Activated, but setting the fair-share weight failed: {error}");
+    std::process::exit(1);
+  }
+  wake_dispatcher(
+    &mut backend.connection,
+    ControlSignal::scoped(ControlKind::Activate, corpus.id, service.id),
+  );
   // Each activated pair gets one task per imported document, so the corpus's document count is the
   // number just queued.
   let queued = Corpus::document_counts(&mut backend.connection)
@@ -2171,9 +3465,9 @@ fn run_deactivate(corpus_name: String, service_name: String, yes: bool) {
       std::process::exit(1);
     },
   };
-  // Deactivating `import` (2) would wipe the corpus's document registry; `init` (1) is
+  // Deactivating `import` would wipe the corpus's document registry; `init` (1) is
   // infrastructure too. The web screen never offers these — refuse them here as well.
-  if service.id <= 2 {
+  if service.id <= IMPORT_SERVICE_ID {
     eprintln!(
       "'{}' is an infrastructure service (init/import) and cannot be deactivated from a corpus.",
       service.name
@@ -2217,14 +3511,19 @@ fn run_export_dataset(
   group_by: String,
   severity: Vec<String>,
   max_archive_mb: Option<u64>,
+  documents: DocumentFilter,
 ) {
   let group_by = match GroupBy::from_key(&group_by) {
     Some(group_by) => group_by,
     None => {
-      eprintln!("error: --group-by must be 'month' or 'severity' (got {group_by:?})");
+      eprintln!("error: --group-by must be 'month', 'severity' or 'category' (got {group_by:?})");
       std::process::exit(2);
     },
   };
+  if let Err(reason) = documents.validate() {
+    eprintln!("error: invalid document filter: {reason}");
+    std::process::exit(2);
+  }
   let severities: Vec<TaskStatus> = match severity
     .iter()
     .map(|key| TaskStatus::from_key(key).ok_or_else(|| key.clone()))
@@ -2258,16 +3557,18 @@ fn run_export_dataset(
     corpus.name,
     service.name,
     out.display(),
-    group_by_label(group_by),
+    group_by.to_key(),
   );
   match export_html_dataset(
     &mut backend.connection,
     &corpus,
     &service,
     &severities,
+    &documents,
     group_by,
     max_archive_mb,
     &out,
+    &*storage(),
     |line| println!("{line}"),
   ) {
     Ok(outcome) => print_export_summary(&outcome),
@@ -2278,13 +3579,6 @@ fn run_export_dataset(
   }
 }
 
-fn group_by_label(group_by: GroupBy) -> &'static str {
-  match group_by {
-    GroupBy::Month => "month",
-    GroupBy::Severity => "severity",
-  }
-}
-
 fn print_export_summary(outcome: &DatasetExportOutcome) {
   println!(
     "\nDone: {} archive(s), {} document(s) bundled, {} skipped.",
@@ -2294,6 +3588,280 @@ fn print_export_summary(outcome: &DatasetExportOutcome) {
   );
 }
 
+fn run_fsck(
+  corpus_name: String,
+  service_name: String,
+  options: FsckOptions,
+  owner: String,
+  json: bool,
+) {
+  let mut backend = backend::from_address(default_db_address());
+  let corpus = match Corpus::find_by_name(&corpus_name.to_lowercase(), &mut backend.connection) {
+    Ok(corpus) => corpus,
+    Err(error) => {
+      eprintln!("error: corpus {corpus_name:?} not found: {error}");
+      std::process::exit(1);
+    },
+  };
+  let service = match Service::find_by_name(&service_name.to_lowercase(), &mut backend.connection) {
+    Ok(service) => service,
+    Err(error) => {
+      eprintln!("error: service {service_name:?} not found: {error}");
+      std::process::exit(1);
+    },
+  };
+  let report = match fsck_archives(
+    &mut backend.connection,
+    &corpus,
+    &service,
+    &*storage(),
+    options,
+    &owner,
+    |line| {
+      if !json {
+        println!("{line}");
+      }
+    },
+  ) {
+    Ok(report) => report,
+    Err(error) => {
+      eprintln!("cortex fsck failed: {error}");
+      std::process::exit(1);
+    },
+  };
+  if json {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&report).expect("serialize fsck report")
+    );
+  } else {
+    print_fsck_summary(&report);
+  }
+  let repaired_damage = report.damaged() == 0 || report.reset > 0;
+  let repaired_orphans = report.orphan_count == 0 || report.removed == report.orphan_count;
+  if !(repaired_damage && repaired_orphans) {
+    std::process::exit(1);
+  }
+}
+
+fn print_fsck_summary(report: &FsckReport) {
+  println!(
+    "\n{} / {}: {} archive(s) checked ({} without a recorded checksum).",
+    report.corpus, report.service, report.checked, report.unrecorded
+  );
+  let findings: [(&str, usize, &[String]); 3] = [
+    ("missing", report.missing_count, &report.missing),
+    (
+      "checksum mismatch",
+      report.checksum_mismatch_count,
+      &report.checksum_mismatch,
+    ),
+    (
+      "no cortex.log",
+      report.no_cortex_log_count,
+      &report.no_cortex_log,
+    ),
+  ];
+  for (label, count, paths) in findings {
+    println!("  {label}: {count}");
+    for path in paths {
+      println!("    {path}");
+    }
+  }
+  println!(
+    "  orphans: {} ({} bytes)",
+    report.orphan_count, report.orphan_bytes
+  );
+  for orphan in &report.orphans {
+    println!("    {} ({} bytes)", orphan.path, orphan.bytes);
+  }
+  if report.dry_run {
+    if !report.is_clean() {
+      println!("Dry run: pass --reset-damaged and/or --remove-orphans to repair.");
+    }
+  } else {
+    println!(
+      "Reset {} damaged task(s) to TODO; removed {} orphan archive(s).",
+      report.reset, report.removed
+    );
+  }
+}
+
+fn run_gc_archives(yes: bool, owner: String, json: bool) {
+  let mut backend = backend::from_address(default_db_address());
+  let report = match collect_archives(&mut backend.connection, &*storage(), yes, &owner, |line| {
+    if !json {
+      println!("{line}");
+    }
+  }) {
+    Ok(report) => report,
+    Err(error) => {
+      eprintln!("cortex gc-archives failed: {error}");
+      std::process::exit(1);
+    },
+  };
+  if json {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&report).expect("serialize archive gc report")
+    );
+    return;
+  }
+  for archive in &report.archives {
+    let reason = match archive.reason {
+      GcReason::DeletedTask => "deleted task",
+      GcReason::Orphan => "orphan",
+    };
+    println!("  {} ({} bytes, {reason})", archive.path, archive.bytes);
+  }
+  println!(
+    "\n{} collectible archive(s), {} bytes: {} of deleted tasks ({} bytes), {} orphan(s) ({} \
+     bytes); {} stale record(s).",
+    report.archive_count,
+    report.total_bytes,
+    report.deleted_task_archives,
+    report.deleted_task_bytes,
+    report.orphan_archives,
+    report.orphan_bytes,
+    report.stale_records,
+  );
+  if report.dry_run {
+    if report.archive_count > 0 {
+      println!("Dry run: pass --yes to delete them.");
+    }
+  } else {
+    println!(
+      "Deleted {} archive(s), freeing {} bytes.",
+      report.removed, report.freed_bytes
+    );
+    if report.changed > 0 {
+      println!(
+        "Left {} archive(s) alone that changed since the scan.",
+        report.changed
+      );
+    }
+  }
+}
+
+/// Fingerprints the messages stored before fingerprints existed, printing each table's progress.
+fn run_backfill_fingerprints(batch: i64) {
+  let mut backend = backend::from_address(default_db_address());
+  let report = match backfill_fingerprints(&mut backend.connection, batch, |table, messages| {
+    println!("  {table}: {messages} message(s) fingerprinted");
+  }) {
+    Ok(report) => report,
+    Err(error) => {
+      eprintln!("cortex backfill-fingerprints failed: {error}");
+      std::process::exit(1);
+    },
+  };
+  let total: usize = report.iter().map(|table| table.messages).sum();
+  for table in &report {
+    println!("{}: {}", table.table, table.messages);
+  }
+  println!("Fingerprinted {total} message(s).");
+}
+
+/// Full-text search over the conversion messages — the CLI surface of `GET /api/search/messages`
+/// (via the shared `backend::search_messages`). `--json` mirrors the agent `MessageSearchDto`.
+/// Exits `2` on a blank or overlong query or an unknown severity, `1` on an unknown corpus/service
+/// or a failed search.
+#[allow(clippy::too_many_arguments)]
+fn run_search(
+  query: String,
+  corpus_name: Option<String>,
+  service_name: Option<String>,
+  severity: Option<String>,
+  category: Option<String>,
+  offset: Option<i64>,
+  limit: Option<i64>,
+  json: bool,
+) {
+  let text = match validate_message_text(&query) {
+    Ok(text) => text.to_string(),
+    Err(reason) => {
+      eprintln!("Invalid query: {reason}");
+      std::process::exit(2);
+    },
+  };
+  let mut backend = backend::from_address(default_db_address());
+  let corpus = corpus_name.map(|name| {
+    Corpus::find_by_name(&name.to_lowercase(), &mut backend.connection).unwrap_or_else(|_| {
+      eprintln!("No such corpus: {name}");
+      std::process::exit(1);
+    })
+  });
+  let service = service_name.map(|name| {
+    Service::find_by_name(&name.to_lowercase(), &mut backend.connection).unwrap_or_else(|_| {
+      eprintln!("No such service: {name}");
+      std::process::exit(1);
+    })
+  });
+  let offset = offset.unwrap_or(0).clamp(0, MAX_REPORT_OFFSET);
+  let page_size = limit.unwrap_or(100).clamp(1, MAX_REPORT_PAGE_SIZE);
+  let search = MessageSearch {
+    text,
+    corpus_id: corpus.as_ref().map(|corpus| corpus.id),
+    service_id: service.as_ref().map(|service| service.id),
+    severity,
+    category,
+  };
+  let report = match search_messages(&mut backend.connection, &search, page_size, offset) {
+    Ok(Some(report)) => report,
+    Ok(None) => {
+      eprintln!(
+        "Invalid --severity {:?}: use a message severity (info|warning|error|fatal|invalid).",
+        search.severity.unwrap_or_default()
+      );
+      std::process::exit(2);
+    },
+    Err(error) => {
+      eprintln!("cortex search failed: {error}");
+      std::process::exit(1);
+    },
+  };
+  let hits: Vec<MessageHitDto> = report.hits.into_iter().map(MessageHitDto::from).collect();
+  if json {
+    let dto = MessageSearchDto {
+      q: search.text,
+      corpus: corpus.map(|corpus| corpus.name),
+      service: service.map(|service| service.name),
+      severity: search.severity,
+      category: search.category,
+      total_messages: report.total_messages,
+      total_tasks: report.total_tasks,
+      offset,
+      page_size,
+      hits,
+    };
+    println!("{}", serde_json::to_string_pretty(&dto).unwrap_or_default());
+    return;
+  }
+  println!(
+    "{} message(s) in {} document(s) match {:?}",
+    report.total_messages, report.total_tasks, search.text
+  );
+  for hit in &hits {
+    let details: String = hit
+      .highlights
+      .iter()
+      .map(|stretch| {
+        if stretch.matched {
+          format!("[[{}]]", stretch.text)
+        } else {
+          stretch.text.clone()
+        }
+      })
+      .collect();
+    println!(
+      "  {}/{}/{}  {} {}/{}",
+      hit.corpus, hit.service, hit.name, hit.severity, hit.category, hit.what
+    );
+    println!("      {}", details.replace('\n', " "));
+  }
+  print_page_hint(hits.len(), offset, page_size);
+}
+
 fn run_set_admin_token(token: Option<String>, generate: bool, owner: String) {
   let token = match (generate, token) {
     (true, _) => bootstrap::generate_token(),
@@ -2470,6 +4038,7 @@ fn run_status(json: bool) {
     .ok()
     .and_then(|runs| runs.into_iter().next())
     .map(|run| run.with_live_tallies(connection));
+  let fair_share = ActivationWeight::shares(connection).unwrap_or_default();
 
   if json {
     let last = last_run.as_ref().map(|run| {
@@ -2491,6 +4060,7 @@ fn run_status(json: bool) {
       "active_jobs": active_jobs,
       "jobs_failed_recent": jobs_failed_recent,
       "last_run": last,
+      "fair_share": fair_share,
     });
     println!(
       "{}",
@@ -2524,6 +4094,30 @@ fn run_status(json: bool) {
       ),
       None => println!("  last run:         none yet"),
     }
+    // Fair-share leasing only matters where a service is shared by several corpora; list those.
+    let shared: Vec<_> = fair_share
+      .iter()
+      .filter(|share| {
+        fair_share
+          .iter()
+          .filter(|other| other.service == share.service)
+          .count()
+          > 1
+      })
+      .collect();
+    if !shared.is_empty() {
+      println!("  fair share:       (target → achieved, recent leases)");
+      for share in shared {
+        println!(
+          "    {:<16} {:<24} weight {:>3}   {:>5.1}% → {:>5.1}%",
+          share.service,
+          share.corpus,
+          share.weight,
+          share.target_share * 100.0,
+          share.achieved_share * 100.0
+        );
+      }
+    }
   }
 }
 
@@ -2620,6 +4214,215 @@ fn run_audit(actor: Option<String>, limit: Option<i64>, json: bool) {
   }
 }
 
+/// Lists or resolves dispatch dead-letters — the CLI surface of the `/admin/dead-letters` screen
+/// and the agent `GET /api/dead-letters` / `POST /api/dead-letters/<id>/<action>`, sharing
+/// `DeadLetter::list` + the `DeadLetterDto` and the backend's `resolve_dead_letter`. Exits `2` for
+/// an unknown state, `1` if a resolve fails (unknown id, the entry was already resolved, or its
+/// task has moved on since).
+fn run_dead_letters(
+  state: String,
+  limit: Option<i64>,
+  requeue: Option<i64>,
+  quarantine: Option<i64>,
+  owner: String,
+  json: bool,
+) {
+  let mut backend = backend::from_address(default_db_address());
+  let resolve = requeue
+    .map(|id| (id, DeadLetterResolution::Requeue))
+    .or_else(|| quarantine.map(|id| (id, DeadLetterResolution::Quarantine)));
+  if let Some((id, resolution)) = resolve {
+    match backend.resolve_dead_letter(id, resolution, &owner) {
+      Ok(letter) => println!(
+        "Dead-letter {} (task {}) is now {}.",
+        letter.id, letter.task_id, letter.state
+      ),
+      Err(error) => {
+        eprintln!("Could not resolve dead-letter {id}: {error}");
+        std::process::exit(1);
+      },
+    }
+    return;
+  }
+  if !matches!(state.as_str(), "open" | "requeued" | "quarantined" | "all") {
+    eprintln!("Unknown state {state:?}: expected open, requeued, quarantined or all.");
+    std::process::exit(2);
+  }
+  let limit = limit.unwrap_or(50).clamp(1, 500);
+  let filter = Some(state.as_str()).filter(|state| *state != "all");
+  let listings = match DeadLetter::list(&mut backend.connection, filter, limit, 0) {
+    Ok(listings) => listings,
+    Err(error) => {
+      eprintln!("Could not read the dead-letter queue: {error}");
+      std::process::exit(1);
+    },
+  };
+  let dtos: Vec<DeadLetterDto> = listings.into_iter().map(DeadLetterDto::from).collect();
+  if json {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&dtos).unwrap_or_default()
+    );
+    return;
+  }
+  if dtos.is_empty() {
+    println!("No {state} dead-letters.");
+    return;
+  }
+  println!("{} dead-letter(s), most recent first:", dtos.len());
+  for letter in &dtos {
+    println!(
+      "  #{}  {}  {}/{} {}  task {} ({}), {} retries  [{}{}]",
+      letter.id,
+      letter.dead_at,
+      letter.corpus,
+      letter.service,
+      letter.entry,
+      letter.task_id,
+      letter.task_status,
+      letter.retries,
+      letter.state,
+      letter
+        .resolved_by
+        .as_ref()
+        .map(|by| format!(" by {by}"))
+        .unwrap_or_default()
+    );
+    for lease in &letter.leases {
+      println!("        leased to {} at {}", lease.worker, lease.leased_at);
+    }
+  }
+}
+
+/// Manages the worker key allowlist — the CLI surface of the `/admin/worker-keys` screen and the
+/// agent `/api/worker-keys`, sharing `models::WorkerKey` + the `WorkerKeyDto`. Exits `2` for a
+/// malformed key, `1` if a write fails (a key listed before, an unknown or revoked id, an unusable
+/// key file).
+#[allow(clippy::too_many_arguments)]
+fn run_worker_keys(
+  add: Option<String>,
+  generate: bool,
+  label: String,
+  revoke: Option<i32>,
+  server_key: bool,
+  owner: String,
+  json: bool,
+) {
+  if server_key {
+    let path = curve_key_path();
+    match CurveKeys::load_or_create(&path) {
+      Ok((keys, created)) => {
+        if created {
+          println!("Created the dispatcher key pair in {}.", path.display());
+        }
+        println!("Server public key: {}", keys.public_key);
+        if !config().dispatcher.worker_auth {
+          println!(
+            "(worker auth is off — set `dispatcher.worker_auth = true` to enforce the allowlist)"
+          );
+        }
+      },
+      Err(error) => {
+        eprintln!("Could not read or create the dispatcher key pair: {error}");
+        std::process::exit(1);
+      },
+    }
+    return;
+  }
+  let mut backend = backend::from_address(default_db_address());
+  if let Some(id) = revoke {
+    match WorkerKey::revoke(&mut backend.connection, id, &owner) {
+      Ok(key) => println!("Revoked worker key {} ({}).", key.id, key.public_key),
+      Err(error) => {
+        eprintln!("Could not revoke worker key {id} (unknown or already revoked): {error}");
+        std::process::exit(1);
+      },
+    }
+    return;
+  }
+  let (public_key, secret_key) = match (add, generate) {
+    (Some(key), _) => match WorkerKey::parse_public_key(&key) {
+      Some(key) => (Some(key), None),
+      None => {
+        eprintln!("Not a Z85-encoded CURVE public key: {key:?}");
+        std::process::exit(2);
+      },
+    },
+    (None, true) => match CurveKeys::generate() {
+      Ok(keys) => (Some(keys.public_key), Some(keys.secret_key)),
+      Err(error) => {
+        eprintln!("Could not generate a worker key pair: {error}");
+        std::process::exit(1);
+      },
+    },
+    (None, false) => (None, None),
+  };
+  if let Some(public_key) = public_key {
+    match WorkerKey::add(&mut backend.connection, &public_key, label.trim(), &owner) {
+      Ok(key) => {
+        println!("Allowlisted worker key {} ({}).", key.id, key.public_key);
+        if let Some(secret_key) = secret_key {
+          println!(
+            "\n  public key: {public_key}\n  secret key: {secret_key}\n  (hand both to the worker now — the secret is shown only once)"
+          );
+        }
+      },
+      Err(error) => {
+        eprintln!("Could not allowlist the key (listed before?): {error}");
+        std::process::exit(1);
+      },
+    }
+    return;
+  }
+  let keys = match WorkerKey::all(&mut backend.connection) {
+    Ok(keys) => keys,
+    Err(error) => {
+      eprintln!("Could not read the worker key allowlist: {error}");
+      std::process::exit(1);
+    },
+  };
+  let dtos: Vec<WorkerKeyDto> = keys.into_iter().map(WorkerKeyDto::from).collect();
+  if json {
+    println!(
+      "{}",
+      serde_json::to_string_pretty(&dtos).unwrap_or_default()
+    );
+    return;
+  }
+  println!(
+    "Worker auth is {}.",
+    if config().dispatcher.worker_auth {
+      "on"
+    } else {
+      "off"
+    }
+  );
+  if dtos.is_empty() {
+    println!("No worker keys. Add one with `cortex worker-keys --add <KEY>` or `--generate`.");
+    return;
+  }
+  for key in &dtos {
+    println!(
+      "  #{}  {}  {}  added {} by {}, last seen {}  [{}]",
+      key.id,
+      key.public_key,
+      if key.label.is_empty() {
+        "-"
+      } else {
+        &key.label
+      },
+      key.created_at,
+      key.created_by,
+      key.last_seen_at.as_deref().unwrap_or("never"),
+      match (&key.revoked_at, &key.revoked_by) {
+        (Some(at), Some(by)) => format!("revoked {at} by {by}"),
+        (Some(at), None) => format!("revoked {at}"),
+        _ => "active".to_string(),
+      }
+    );
+  }
+}
+
 /// Lists all registered corpora — the CLI surface of the overview screen and the agent
 /// `GET /api/corpora`, sharing `Corpus::all` + `Corpus::document_counts` + the `CorpusDto`. The
 /// text view leads with each corpus's `public_id` (the stable external handle), then name · doc
@@ -2678,11 +4481,8 @@ fn run_corpora(json: bool) {
 /// services); `--json` mirrors the agent list.
 fn run_services(json: bool) {
   let mut backend = backend::from_address(default_db_address());
-  let dtos: Vec<ServiceDto> = Service::all(&mut backend.connection)
-    .unwrap_or_default()
-    .into_iter()
-    .map(ServiceDto::from)
-    .collect();
+  let dtos: Vec<ServiceDto> =
+    ServiceDto::registry(Service::all(&mut backend.connection).unwrap_or_default());
   if json {
     println!(
       "{}",
@@ -2697,7 +4497,7 @@ fn run_services(json: bool) {
   println!("{} service(s):", dtos.len());
   for service in &dtos {
     println!(
-      "  {}  {}  v{}  ·  {} → {}{}{}",
+      "  {}  {}  v{}  ·  {} → {}{}{}{}{}{}{}",
       service.public_id,
       service.name,
       service.version,
@@ -2707,6 +4507,30 @@ fn run_services(json: bool) {
       match service.lease_timeout_seconds {
         Some(seconds) => format!("  ·  lease {seconds}s"),
         None => String::new(),
+      },
+      match service.max_lease_renewals {
+        Some(count) => format!("  ·  max {count} renewal(s)"),
+        None => String::new(),
+      },
+      if service.keep_result_versions > 0 {
+        format!(
+          "  ·  keeps {} result version(s)",
+          service.keep_result_versions
+        )
+      } else {
+        String::new()
+      },
+      if service.log_parser != log_parsers::DEFAULT_LOG_PARSER {
+        format!("  ·  log: {}", service.log_parser)
+      } else {
+        String::new()
+      },
+      match service.upstream {
+        Some(ref upstream) => format!(
+          "  ·  after {upstream} (on upstream rerun: {})",
+          service.on_upstream_rerun
+        ),
+        None => String::new(),
       }
     );
   }
@@ -29,6 +29,7 @@ use cortex::helpers::{TaskStatus, generate_report};
 use cortex::models::{Corpus, Service, Task};
 use cortex::schema::tasks;
 use cortex::schema::tasks::dsl::{corpus_id, service_id, status};
+use cortex::storage::LocalStorage;
 
 static ENTRY_ZIP_NAME_REGEX: LazyLock<Regex> =
   LazyLock::new(|| Regex::new(r"[^/]+\.zip$").unwrap());
@@ -86,7 +87,7 @@ fn main() {
         // for those here (this offline pass re-derives reports from existing result files — the
         // live reaper is what retries them), so skip the None just like the dispatcher's
         // sink does.
-        if let Some(report) = generate_report(task, html_entry_path) {
+        if let Some(report) = generate_report(task, html_entry_path, &LocalStorage) {
           batch_reports.push(report);
         }
       } else {
@@ -0,0 +1,3 @@
+-- Drop the priority leasing index and the column, restoring unordered TODO leasing.
+drop index if exists todo_priority_index;
+ALTER TABLE tasks DROP COLUMN priority;
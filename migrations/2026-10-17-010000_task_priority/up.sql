@@ -0,0 +1,21 @@
+-- Task priorities: which TODO tasks the ventilator leases first.
+--
+-- `priority` is a plain integer, higher = sooner; 0 (the default) is the ordinary backlog, so the
+-- column is purely additive — every existing task keeps leasing as before. A rerun can raise its
+-- scope (`cortex rerun --priority`, `POST /api/reports/<c>/<s>/rerun?priority=`) so a hot
+-- regression fix jumps ahead of a long full-corpus run on the same service. Adding a column with a
+-- constant default is a catalog-only change (no table rewrite) on PostgreSQL >= 11.
+ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+-- The leasing query (`tasks_aggregate::fetch_tasks`) now reads
+--   SELECT id FROM tasks WHERE service_id = $1 AND status = 0
+--   ORDER BY priority DESC, id LIMIT $queue_size FOR UPDATE SKIP LOCKED
+-- This TODO-only partial index serves that order directly, so a queue's worth of the highest
+-- priority rows is read off the front of the index instead of sorting the whole TODO backlog.
+--
+-- NOTE (production): as with `todo_index`, build it CONCURRENTLY out-of-band on the live table
+-- first and let this migration no-op:
+--   CREATE INDEX CONCURRENTLY IF NOT EXISTS todo_priority_index
+--     ON tasks(service_id, priority DESC, id) WHERE status = 0;
+create index if not exists todo_priority_index
+  on tasks(service_id, priority desc, id) where status = 0;
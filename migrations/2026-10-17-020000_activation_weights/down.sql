@@ -0,0 +1,3 @@
+-- Drop the fair-share weights and their corpus-scoped leasing index.
+drop index if exists todo_fair_share_index;
+drop table if exists activation_weights;
@@ -0,0 +1,42 @@
+-- Weighted fair-share leasing across the corpora activated for one service.
+--
+-- Before this, the ventilator leased a service's TODO tasks in one `priority DESC, id` sweep, so
+-- whichever corpus was activated first (full arXiv, millions of rows) was drained before a small
+-- sandbox campaign on the same service got a single lease. Each `(corpus, service)` activation now
+-- carries a `weight`; `tasks_aggregate::fetch_tasks` splits every lease batch across the service's
+-- corpora in proportion to their weights (equal weights by default), handing any share a corpus
+-- can't use to the rest.
+--
+-- The same row records what the dispatcher actually achieved: `leased_total` is a lifetime lease
+-- counter (a Prometheus counter — `rate()` over it gives the windowed share), `leased_recent` a
+-- decayed per-batch tally the admin status screen divides into a current "achieved share" without
+-- a scan of `tasks`.
+create table if not exists activation_weights (
+  corpus_id      integer          not null references corpora(id)  on delete cascade,
+  service_id     integer          not null references services(id) on delete cascade,
+  weight         integer          not null default 1 check (weight > 0),
+  leased_total   bigint           not null default 0,
+  leased_recent  double precision not null default 0,
+  last_leased_at timestamp,
+  primary key (corpus_id, service_id)
+);
+
+-- Backfill one default-weight row per existing activation (magic `init`/`import` services excluded:
+-- they are never leased). One pass over `tasks` on a live DB; run it out-of-band first if needed —
+-- `ON CONFLICT DO NOTHING` makes a pre-populated table a no-op.
+insert into activation_weights (corpus_id, service_id)
+  select distinct corpus_id, service_id from tasks where service_id > 2
+  on conflict do nothing;
+
+-- The per-corpus lease claim reads
+--   SELECT id FROM tasks WHERE service_id = $1 AND corpus_id = $2 AND status = 0
+--   ORDER BY priority DESC, id LIMIT $share FOR UPDATE SKIP LOCKED
+-- `todo_priority_index` has no `corpus_id`, so a small corpus's claim would walk past the giant
+-- corpus's TODO rows first. This TODO-only sibling serves the corpus-scoped order directly.
+--
+-- NOTE (production): build it CONCURRENTLY out-of-band on the live table first and let this
+-- migration no-op:
+--   CREATE INDEX CONCURRENTLY IF NOT EXISTS todo_fair_share_index
+--     ON tasks(service_id, corpus_id, priority DESC, id) WHERE status = 0;
+create index if not exists todo_fair_share_index
+  on tasks(service_id, corpus_id, priority desc, id) where status = 0;
@@ -0,0 +1,3 @@
+-- Drop the pipeline invalidation policy. Tasks still awaiting an upstream (raw status -7) stay
+-- Blocked; resume them explicitly if needed.
+ALTER TABLE services DROP COLUMN on_upstream_rerun;
@@ -0,0 +1,14 @@
+-- Service pipelines: a service whose `inputconverter` names another registered service consumes
+-- that service's result archive, per document (the old `dependencies` table, dropped in
+-- 2026-06-14-050000, was never scheduled). Pipeline state lives on `tasks.status`: a downstream
+-- task waiting on its upstream is `Blocked` with the raw status -7 (a paused task is -6), and the
+-- finalize path releases it to TODO when the document's upstream task succeeds.
+--
+-- `on_upstream_rerun` is the downstream service's invalidation policy when an upstream rerun
+-- re-converts a document:
+--   'rerun' (default) — the document's downstream task is reset to await the fresh upstream result
+--                       and re-converts once it lands (transitively down the pipeline);
+--   'keep'            — downstream results are left as they are.
+-- A constant default is a catalog-only change (no table rewrite) on PostgreSQL >= 11.
+ALTER TABLE services ADD COLUMN on_upstream_rerun VARCHAR(16) NOT NULL DEFAULT 'rerun'
+  CHECK (on_upstream_rerun IN ('rerun', 'keep'));
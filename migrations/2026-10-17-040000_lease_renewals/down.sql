@@ -0,0 +1,2 @@
+ALTER TABLE worker_metadata DROP COLUMN total_renewals;
+ALTER TABLE services DROP COLUMN max_lease_renewals;
@@ -0,0 +1,12 @@
+-- Lease renewal: a worker on a legitimately slow conversion may send "still working on task N"
+-- heartbeats on the ventilator socket, each extending that task's in-flight lease by one
+-- `lease_timeout_seconds` from the heartbeat. The lease itself stays in dispatcher memory
+-- (`InFlightSet`); only the cap and the per-worker tally are persisted.
+--
+-- `max_lease_renewals` caps how many times one lease may be extended, so a runaway worker cannot
+-- hold a task forever. NULL = the global `dispatcher.max_lease_renewals` default (the same
+-- override-or-default shape as `lease_timeout_seconds`); 0 disables renewal for the service.
+ALTER TABLE services ADD COLUMN max_lease_renewals INTEGER CHECK (max_lease_renewals >= 0);
+
+-- Renewals accepted per worker, so operators can see which workers rely on them.
+ALTER TABLE worker_metadata ADD COLUMN total_renewals INTEGER NOT NULL DEFAULT 0;
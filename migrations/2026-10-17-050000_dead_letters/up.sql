@@ -0,0 +1,28 @@
+-- Dispatch dead-letters: a task whose lease timed out more than `MAX_DISPATCH_RETRIES` times is
+-- given up on by the dispatcher's reaper and reported `Fatal` with `cortex/never_completed_with_retries`.
+-- In the task table that is indistinguishable from a genuine conversion fatal, so each give-up is
+-- also recorded here, with the lease history that explains it: which worker identities the task
+-- was leased to, and when. "The converter crashed" (a result came back Fatal) vs "the
+-- infrastructure lost it" (a row here) is then a query, not a guess.
+--
+-- `workers[i]` was leased the task at `leased_at[i]` (parallel arrays, in lease order).
+--
+-- `state` is the operator's triage of the entry:
+--   open        - recorded by the dispatcher, not yet looked at
+--   requeued    - the task was reset to TODO for another attempt
+--   quarantined - the task was parked permanently (status -8): never leased, never rerun in bulk
+CREATE TABLE dead_letters (
+  id BIGSERIAL PRIMARY KEY,
+  task_id BIGINT NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+  retries INTEGER NOT NULL,
+  workers TEXT[] NOT NULL DEFAULT '{}',
+  leased_at TIMESTAMP[] NOT NULL DEFAULT '{}',
+  dead_at TIMESTAMP NOT NULL DEFAULT now(),
+  state VARCHAR(16) NOT NULL DEFAULT 'open'
+    CHECK (state IN ('open', 'requeued', 'quarantined')),
+  resolved_at TIMESTAMP,
+  resolved_by VARCHAR(200)
+);
+-- The admin screen lists the open entries newest first.
+CREATE INDEX dead_letters_state_dead_at_idx ON dead_letters (state, dead_at DESC);
+CREATE INDEX dead_letters_task_id_idx ON dead_letters (task_id);
@@ -0,0 +1 @@
+DROP TABLE poison_entries;
@@ -0,0 +1,28 @@
+-- Poison-document quarantine. Some documents reliably OOM or hang their worker (KNOWN_ISSUES W-6),
+-- and every rerun campaign re-leases them and loses workers again. The dispatcher's reaper records
+-- each lease of an entry that expired without a result (the worker crashed, was OOM-killed or
+-- hung), across services and runs, and once an entry collects `dispatcher.quarantine_threshold`
+-- such strikes it is quarantined: its tasks keep their status (so reports still list them), but
+-- `fetch_tasks` skips them until an operator releases the entry.
+--
+-- Keyed by the entry path alone (not per corpus): the path is the document, so a sandbox carved
+-- from the corpus (same paths) does not get to rediscover the same poison.
+--
+--   incidents       - lifetime lease expiries of the entry (never reset; the history)
+--   strikes         - lease expiries since the entry was last released (compared to the threshold)
+--   quarantined_at  - set while quarantined, NULL otherwise
+--   released_at/by  - the latest operator release
+CREATE TABLE poison_entries (
+  entry VARCHAR(4096) PRIMARY KEY,
+  incidents INTEGER NOT NULL DEFAULT 0,
+  strikes INTEGER NOT NULL DEFAULT 0,
+  last_incident_at TIMESTAMP NOT NULL DEFAULT now(),
+  last_service_id INTEGER REFERENCES services (id) ON DELETE SET NULL,
+  last_worker VARCHAR(200),
+  quarantined_at TIMESTAMP,
+  released_at TIMESTAMP,
+  released_by VARCHAR(200)
+);
+-- The lease query's anti-join only ever looks at the (few) quarantined rows.
+CREATE INDEX poison_entries_quarantined_idx ON poison_entries (entry)
+  WHERE quarantined_at IS NOT NULL;
@@ -0,0 +1,9 @@
+ALTER TABLE worker_metadata
+  DROP COLUMN refusal,
+  DROP COLUMN registered_at,
+  DROP COLUMN input_formats,
+  DROP COLUMN max_input_bytes,
+  DROP COLUMN concurrency,
+  DROP COLUMN git_sha,
+  DROP COLUMN host,
+  DROP COLUMN declared_version;
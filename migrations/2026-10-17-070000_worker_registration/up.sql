@@ -0,0 +1,19 @@
+-- Worker registration handshake: a worker may open with one registration frame on the ventilator
+-- socket, `[identity, service, "register", <json>]`, declaring what it is and what it can take. The
+-- dispatcher keeps the declaration in memory to decide what to lease the worker; these columns
+-- only mirror it for the `/workers/<service>` screen and its agent twin. A worker that never
+-- registers keeps NULLs here and is leased to exactly as before.
+--
+-- `declared_version` is compared with `services.version`: a mismatch is refused every lease, and
+-- `refusal` records why (NULL = the dispatcher leases to it). `concurrency` caps the worker's
+-- in-flight leases; `max_input_bytes` caps the input archive size of the tasks it is handed;
+-- `input_formats` (empty = any) must include the service's `inputformat`.
+ALTER TABLE worker_metadata
+  ADD COLUMN declared_version REAL,
+  ADD COLUMN host VARCHAR(255),
+  ADD COLUMN git_sha VARCHAR(64),
+  ADD COLUMN concurrency INTEGER CHECK (concurrency > 0),
+  ADD COLUMN max_input_bytes BIGINT CHECK (max_input_bytes > 0),
+  ADD COLUMN input_formats TEXT[] NOT NULL DEFAULT '{}',
+  ADD COLUMN registered_at TIMESTAMP,
+  ADD COLUMN refusal VARCHAR(200);
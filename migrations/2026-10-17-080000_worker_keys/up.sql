@@ -0,0 +1,20 @@
+-- Worker key allowlist for the authenticated worker transport. With `dispatcher.worker_auth` on,
+-- the ventilator is a ZMQ CURVE server: a worker's connection handshake is only accepted if its
+-- CURVE public key is listed here and not revoked, and every result the sink accepts must carry the
+-- lease token that was handed out over that encrypted channel.
+--
+--   public_key        - the worker's CURVE public key, Z85-encoded (always 40 characters)
+--   label             - free-form operator note (host, fleet, owner)
+--   created_at/by     - who allowlisted the key, and when
+--   revoked_at/by     - set once revoked; a revoked key is kept for the record, never reused
+--   last_seen_at      - the latest accepted handshake with the key
+CREATE TABLE worker_keys (
+  id SERIAL PRIMARY KEY,
+  public_key VARCHAR(40) NOT NULL UNIQUE,
+  label VARCHAR(200) NOT NULL DEFAULT '',
+  created_at TIMESTAMP NOT NULL DEFAULT now(),
+  created_by VARCHAR(200) NOT NULL DEFAULT '',
+  revoked_at TIMESTAMP,
+  revoked_by VARCHAR(200),
+  last_seen_at TIMESTAMP
+);
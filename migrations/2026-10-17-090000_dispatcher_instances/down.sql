@@ -0,0 +1,3 @@
+drop index if exists leased_dispatcher_index;
+ALTER TABLE tasks DROP COLUMN dispatcher_id;
+DROP TABLE dispatcher_instances;
@@ -0,0 +1,32 @@
+-- Multiple active dispatchers. Each running dispatcher registers one `dispatcher_instances` row at
+-- start and freshens its `heartbeat_at` every `dispatcher.instance_heartbeat_seconds`; a clean stop
+-- sets `stopped_at`. An instance whose heartbeat is older than `dispatcher.instance_timeout_seconds`
+-- (judged by the database clock, so peers on different hosts agree) is presumed dead.
+--
+-- `tasks.dispatcher_id` tags each lease with the instance that leased it. It is only meaningful
+-- while the task is leased (`status > 0`); limbo recovery resets the leases of dead instances
+-- only, never a live peer's, so several dispatchers (one per storage node, or an old and a new one
+-- during a rolling restart) can share the task store. A NULL tag is a lease taken before this
+-- migration. No foreign key: instance rows are bookkeeping, and the lease UPDATE on the hot path
+-- should not pay for a referential check.
+CREATE TABLE dispatcher_instances (
+  id SERIAL PRIMARY KEY,
+  host VARCHAR(255) NOT NULL DEFAULT '',
+  pid INTEGER NOT NULL DEFAULT 0,
+  source_port INTEGER NOT NULL DEFAULT 0,
+  result_port INTEGER NOT NULL DEFAULT 0,
+  started_at TIMESTAMP NOT NULL DEFAULT now(),
+  heartbeat_at TIMESTAMP NOT NULL DEFAULT now(),
+  stopped_at TIMESTAMP
+);
+
+-- Adding a nullable column without a default is a catalog-only change (no table rewrite).
+ALTER TABLE tasks ADD COLUMN dispatcher_id INTEGER;
+
+-- Limbo recovery only ever looks at leased rows, grouped by the instance that leased them.
+--
+-- NOTE (production): as with `todo_index`, build it CONCURRENTLY out-of-band on the live table
+-- first and let this migration no-op:
+--   CREATE INDEX CONCURRENTLY IF NOT EXISTS leased_dispatcher_index
+--     ON tasks(dispatcher_id) WHERE status > 0;
+create index if not exists leased_dispatcher_index on tasks(dispatcher_id) where status > 0;
@@ -0,0 +1,15 @@
+-- Per-document metadata carried in from an import manifest (`cortex import --manifest`, or the
+-- `manifest` field of `POST /api/corpora`). Curated corpora — PubMed Central dumps, conference
+-- proceedings, hand-picked regression sets — come with a list of documents and facts about each
+-- (a DOI, a venue, why it is in the set) rather than an arXiv directory layout. The import creates
+-- the usual import task per listed entry, and keeps the rest of the manifest row here.
+--
+-- Keyed by (corpus, entry) rather than by task: every service's task of the entry shares it, and a
+-- re-import of the same manifest updates it in place. A sandbox reads its parent's rows.
+CREATE TABLE entry_metadata (
+  corpus_id INTEGER NOT NULL REFERENCES corpora (id) ON DELETE CASCADE,
+  entry VARCHAR(4096) NOT NULL,
+  metadata JSONB NOT NULL DEFAULT '{}',
+  imported_at TIMESTAMP NOT NULL DEFAULT now(),
+  PRIMARY KEY (corpus_id, entry)
+);
@@ -0,0 +1,27 @@
+-- Per-document facts, extracted by the importer so reports, sandbox carves and dataset exports can
+-- filter and group documents without string-hacking `tasks.entry` (the `entry LIKE '%/2605/%'`
+-- month carve, `entry_document_name`). One row per (corpus, entry), written with the import task;
+-- a sandbox gets copies of its carved entries' rows.
+--
+--   name              - the document name, as in reports (`0801.1234`, `cond-mat0302605`)
+--   yymm              - the arXiv submission month, from the path or the arXiv id (NULL if neither)
+--   source_bytes      - size of the source file (the .zip of a complex corpus, else the .tex)
+--   file_count        - files in the source archive (1 for a single .tex)
+--   main_tex          - the archive member that declares \documentclass (or \documentstyle)
+--   primary_category  - the arXiv primary subject category (`hep-th`, `math.AG`), from an optional
+--                       metadata file or the import manifest
+CREATE TABLE documents (
+  corpus_id INTEGER NOT NULL REFERENCES corpora (id) ON DELETE CASCADE,
+  entry VARCHAR(4096) NOT NULL,
+  name VARCHAR(255) NOT NULL DEFAULT '',
+  yymm VARCHAR(4),
+  source_bytes BIGINT,
+  file_count INTEGER,
+  main_tex VARCHAR(1024),
+  primary_category VARCHAR(64),
+  inspected_at TIMESTAMP NOT NULL DEFAULT now(),
+  PRIMARY KEY (corpus_id, entry)
+);
+CREATE INDEX documents_name_idx ON documents (corpus_id, name);
+CREATE INDEX documents_yymm_idx ON documents (corpus_id, yymm);
+CREATE INDEX documents_category_idx ON documents (corpus_id, primary_category);
@@ -0,0 +1,5 @@
+DROP INDEX documents_vanished_idx;
+ALTER TABLE documents
+  DROP COLUMN content_hash,
+  DROP COLUMN source_modified,
+  DROP COLUMN vanished_at;
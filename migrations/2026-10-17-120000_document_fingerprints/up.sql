@@ -0,0 +1,12 @@
+-- Source fingerprints, so `extend` can tell new, changed and vanished entries apart (arXiv replaces
+-- a paper's source with a new version in place, under the same entry path).
+--
+--   content_hash     - hex SHA-256 of the source file (NULL for rows inspected before this column)
+--   source_modified  - the source file's modification time when it was hashed; an unchanged size
+--                      and time lets `extend` skip re-hashing the file
+--   vanished_at      - when `extend` last found the source file missing (NULL while present)
+ALTER TABLE documents
+  ADD COLUMN content_hash VARCHAR(64),
+  ADD COLUMN source_modified TIMESTAMP,
+  ADD COLUMN vanished_at TIMESTAMP;
+CREATE INDEX documents_vanished_idx ON documents (corpus_id) WHERE vanished_at IS NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE corpora DROP COLUMN unpacker;
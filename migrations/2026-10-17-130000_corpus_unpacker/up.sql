@@ -0,0 +1,4 @@
+-- The source layout of a complex corpus: the key of the unpacker (`importer::unpackers`) that brings
+-- its sources into the `<name>/<name>.zip` layout before the import walk. NULL is arXiv's bulk
+-- layout (`arxiv`), which every corpus used before unpackers were pluggable.
+ALTER TABLE corpora ADD COLUMN unpacker VARCHAR(32);
@@ -0,0 +1,15 @@
+-- The size and SHA-256 of each task's current result archive, recorded by the sink when it commits
+-- the archive (written on the finalize path, `mark_done`). `cortex fsck` compares the stored
+-- archives against them to find truncated or corrupt ones.
+--
+--   path        - where the archive was written (`helpers::result_archive_path`)
+--   size_bytes  - its length in bytes
+--   sha256      - hex SHA-256 of its bytes
+--   written_at  - when the sink committed it
+create table result_archives (
+  task_id    bigint      primary key references tasks(id) on delete cascade,
+  path       varchar     not null,
+  size_bytes bigint      not null,
+  sha256     varchar(64) not null,
+  written_at timestamp   not null default now()
+);
@@ -0,0 +1,4 @@
+DROP INDEX result_archives_path_idx;
+DELETE FROM result_archives WHERE task_id NOT IN (SELECT id FROM tasks);
+ALTER TABLE result_archives
+  ADD CONSTRAINT result_archives_task_id_fkey FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE;
@@ -0,0 +1,6 @@
+-- Keep a result archive's record after its task is deleted (with its corpus, service or sandbox):
+-- the orphaned row is how the archive GC (`backend::gc`) finds the file to delete, since the task
+-- that pointed at it is gone. Task ids are never reused, so a stale row cannot shadow a new task.
+ALTER TABLE result_archives DROP CONSTRAINT result_archives_task_id_fkey;
+-- The GC skips an orphaned record whose path a live task has since written again.
+CREATE INDEX result_archives_path_idx ON result_archives (path);
@@ -0,0 +1,2 @@
+DROP TABLE result_archive_versions;
+ALTER TABLE services DROP COLUMN keep_result_versions;
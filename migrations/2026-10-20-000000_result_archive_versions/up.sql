@@ -0,0 +1,27 @@
+-- Opt-in retention of a task's previous result archives. With `keep_result_versions` = N > 0, the
+-- sink moves a task's current `<service>.zip` aside to `<service>.v<stamp>.zip` before writing the
+-- new one, and keeps the N newest of those; 0 (the default) overwrites in place, as before.
+ALTER TABLE services ADD COLUMN keep_result_versions integer NOT NULL DEFAULT 0;
+
+-- One row per kept previous result archive, recorded on the finalize path (`mark_done`).
+--
+--   task_id     - the task it was a result of (no foreign key: like `result_archives`, the row
+--                 outlives its task so the archive GC can find the file)
+--   run_id      - the historical run it was produced in, if one was open when it was written
+--   path        - where it was moved to (`helpers::result_version_path`)
+--   size_bytes  - its length in bytes
+--   sha256      - hex SHA-256 of its bytes, when recorded
+--   written_at  - when the sink committed it (as the then-current archive)
+--   saved_at    - when it was moved aside; retention prunes by this, with `historical_tasks`
+create table result_archive_versions (
+  id         bigserial   primary key,
+  task_id    bigint      not null,
+  run_id     integer     references historical_runs(id) on delete set null,
+  path       varchar     not null,
+  size_bytes bigint      not null,
+  sha256     varchar(64),
+  written_at timestamp   not null,
+  saved_at   timestamp   not null default now()
+);
+CREATE INDEX result_archive_versions_task_idx ON result_archive_versions (task_id, id);
+CREATE INDEX result_archive_versions_saved_idx ON result_archive_versions (saved_at);
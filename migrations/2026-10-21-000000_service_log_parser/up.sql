@@ -0,0 +1,4 @@
+-- The format of a service's worker log: the key of the log parser (`helpers::log_parsers`) that
+-- turns its `cortex.log` into a status and messages. NULL is LaTeXML's message convention
+-- (`latexml`), which every service used before log parsers were pluggable.
+ALTER TABLE services ADD COLUMN log_parser VARCHAR(32);
@@ -0,0 +1,5 @@
+ALTER TABLE log_infos    DROP COLUMN fingerprint;
+ALTER TABLE log_warnings DROP COLUMN fingerprint;
+ALTER TABLE log_errors   DROP COLUMN fingerprint;
+ALTER TABLE log_fatals   DROP COLUMN fingerprint;
+ALTER TABLE log_invalids DROP COLUMN fingerprint;
@@ -0,0 +1,14 @@
+-- Message clusters (the `/clusters` rung below `what` in the report ladder): every log message
+-- stores the fingerprint of its `details` with line numbers, paths, file names, quoted tokens and
+-- macro arguments masked (`helpers::fingerprint`), so a `what`'s near-identical long tail groups by
+-- root cause with one `GROUP BY fingerprint`.
+--
+-- ONLINE-SAFE on the large production tables: `ADD COLUMN … BIGINT` (nullable, no default) is a
+-- catalog-only change — instant, no table rewrite. The dispatcher fingerprints new messages as it
+-- parses them; pre-existing rows stay NULL until `cortex backfill-fingerprints` fills them in
+-- batches (the clusters report counts them as unfingerprinted meanwhile).
+ALTER TABLE log_infos    ADD COLUMN fingerprint BIGINT;
+ALTER TABLE log_warnings ADD COLUMN fingerprint BIGINT;
+ALTER TABLE log_errors   ADD COLUMN fingerprint BIGINT;
+ALTER TABLE log_fatals   ADD COLUMN fingerprint BIGINT;
+ALTER TABLE log_invalids ADD COLUMN fingerprint BIGINT;
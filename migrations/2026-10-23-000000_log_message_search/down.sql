@@ -0,0 +1,5 @@
+drop index if exists log_infos_text_idx;
+drop index if exists log_warnings_text_idx;
+drop index if exists log_errors_text_idx;
+drop index if exists log_fatals_text_idx;
+drop index if exists log_invalids_text_idx;
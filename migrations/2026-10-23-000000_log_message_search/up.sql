@@ -0,0 +1,21 @@
+-- Full-text message search (`GET /api/search/messages`, `/search`, `cortex search`): one GIN index
+-- per log table over the `what` and `details` of its messages, under the `simple` text search
+-- configuration (no stemming or stop words — TeX tokens are not English). The search, sandbox and
+-- rerun queries use exactly this expression (`backend::search::MESSAGE_TEXT_VECTOR`), so the
+-- planner matches them to these indexes.
+--
+-- NOTE (production): a plain CREATE INDEX takes a SHARE lock (blocks writes) while it builds, and a
+-- GIN build over the ~530M-row `log_infos` takes hours. Build them CONCURRENTLY out-of-band first
+-- and let this migration no-op (the `IF NOT EXISTS` makes a pre-built index a no-op), e.g.:
+--   CREATE INDEX CONCURRENTLY IF NOT EXISTS log_infos_text_idx ON log_infos
+--     USING gin (to_tsvector('simple', coalesce(what, '') || ' ' || coalesce(details, '')));
+create index if not exists log_infos_text_idx on log_infos
+  using gin (to_tsvector('simple', coalesce(what, '') || ' ' || coalesce(details, '')));
+create index if not exists log_warnings_text_idx on log_warnings
+  using gin (to_tsvector('simple', coalesce(what, '') || ' ' || coalesce(details, '')));
+create index if not exists log_errors_text_idx on log_errors
+  using gin (to_tsvector('simple', coalesce(what, '') || ' ' || coalesce(details, '')));
+create index if not exists log_fatals_text_idx on log_fatals
+  using gin (to_tsvector('simple', coalesce(what, '') || ' ' || coalesce(details, '')));
+create index if not exists log_invalids_text_idx on log_invalids
+  using gin (to_tsvector('simple', coalesce(what, '') || ' ' || coalesce(details, '')));
@@ -0,0 +1,2 @@
+DROP TABLE run_message_classes;
+ALTER TABLE historical_runs DROP COLUMN regressions;
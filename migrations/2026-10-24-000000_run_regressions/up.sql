@@ -0,0 +1,22 @@
+-- Regression detection between consecutive runs: when a run closes on drain
+-- (`Backend::complete_run_if_drained`), its message classes are snapshotted and compared with the
+-- run before it, and the summary is stored on the run.
+--
+-- `regressions` holds the summary as JSON (`backend::RunRegressions`): the documents whose status
+-- got worse, the `(severity, category, what)` classes new since the previous run, and the classes
+-- whose task counts jumped past `dispatcher.regression_jump_percent`. NULL for open runs and for
+-- runs closed before this existed. Catalog-only (nullable, no default) — instant on a live table.
+ALTER TABLE historical_runs ADD COLUMN regressions jsonb;
+
+-- One row per message class of a closed run, with the tasks and messages it counted — the report
+-- ladder's `what` grain (warning/error/fatal/invalid, by task status as the reports count them),
+-- frozen at the run boundary. A run's classes go with it.
+create table run_message_classes (
+  run_id        integer      not null references historical_runs(id) on delete cascade,
+  severity      varchar(10)  not null,
+  category      varchar(100) not null,
+  what          varchar(200) not null,
+  task_count    bigint       not null,
+  message_count bigint       not null,
+  primary key (run_id, severity, category, what)
+);
@@ -0,0 +1,12 @@
+-- Message-class trends across runs: the category grain of the per-run rollup. `run_message_classes`
+-- holds each closed run's `(severity, category, what)` task counts; a category's task count cannot
+-- be summed from its whats (one task may carry several), so it is frozen alongside, in the same
+-- pass, as its own row. A run's categories go with it.
+create table run_message_categories (
+  run_id        integer      not null references historical_runs(id) on delete cascade,
+  severity      varchar(10)  not null,
+  category      varchar(100) not null,
+  task_count    bigint       not null,
+  message_count bigint       not null,
+  primary key (run_id, severity, category)
+);
@@ -0,0 +1,2 @@
+ALTER INDEX poison_entries_held_idx RENAME TO poison_entries_quarantined_idx;
+ALTER TABLE poison_entries RENAME COLUMN held_at TO quarantined_at;
@@ -0,0 +1,6 @@
+-- A poison document is "held", not "quarantined": `TaskStatus::QUARANTINED` (-8) already names the
+-- operator's parking of a dead-lettered task, a different mechanism (one task, a status) from the
+-- per-entry lease hold kept here (every service's tasks of the entry, status untouched). Strikes
+-- now also count worker-reported crashes and timeouts, not only expired leases.
+ALTER TABLE poison_entries RENAME COLUMN quarantined_at TO held_at;
+ALTER INDEX poison_entries_quarantined_idx RENAME TO poison_entries_held_idx;
@@ -0,0 +1,2 @@
+DROP INDEX historical_runs_regressions_pending_idx;
+ALTER TABLE historical_runs DROP COLUMN regressions_pending;
@@ -0,0 +1,8 @@
+-- A run closing on drain freezes its message classes and its tasks' statuses at once, and leaves
+-- the comparison with the previous run to a background job. `regressions_pending` marks a run
+-- whose summary is still owed, so the dispatcher retries the job until one stores it (a job lost
+-- to a restart or a failure would otherwise leave the run without a summary for good). Runs closed
+-- before this existed, or by the next rerun starting, owe none.
+ALTER TABLE historical_runs ADD COLUMN regressions_pending boolean NOT NULL DEFAULT false;
+CREATE INDEX historical_runs_regressions_pending_idx ON historical_runs (id)
+  WHERE regressions_pending;
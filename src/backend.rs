@@ -8,24 +8,59 @@
 //! All aggregate operations over the CorTeX PostgresQL store are accessed through the connection of
 //! a `Backend` object.
 
+mod archive_diff;
+mod class_trends;
+mod clusters;
+mod control;
 mod corpora_aggregate;
+mod documents;
 mod export;
+mod fsck;
+mod gc;
 mod mark;
+mod pipelines;
+mod regressions;
 mod reports;
 mod rollup;
 mod sandbox;
+mod search;
 mod services_aggregate;
 mod tasks_aggregate;
+mod versions;
+pub use archive_diff::{
+  ArchiveDiff, DiffLine, MemberDelta, MessageDelta, TEXT_DIFF_LINE_LIMIT, TextDiffReport,
+  diff_archives,
+};
+pub use class_trends::{
+  ClassGrain, ClassMove, ClassMovers, ClassTrendPoint, DEFAULT_TREND_RUNS, MAX_TREND_RUNS,
+  TrendRun, class_movers, class_trend, trend_runs,
+};
+pub use clusters::{
+  CLUSTER_QUERY_BUDGET_MS, ClusterExample, ClusterReport, FingerprintBackfill, MessageCluster,
+  backfill_fingerprints, message_clusters,
+};
+pub use control::{
+  CONTROL_CHANNEL, ControlKind, ControlSignal, listen_control, notify_control, poll_control,
+  wake_dispatcher,
+};
+pub use documents::{DocumentFacet, DocumentFilter, FacetRow, MessageScope, facet_breakdown};
 pub use export::{DatasetExportOutcome, GroupBy, export_html_dataset};
+pub use fsck::{FsckOptions, FsckReport, OrphanArchive, fsck_archives};
+pub use gc::{ArchiveGcReport, CollectibleArchive, GcReason, collect_archives};
 pub(crate) use mark::{
-  mark_all_blocked, mark_blocked, mark_rerun, resume_all_blocked, resume_blocked,
-  save_historical_tasks,
+  mark_all_blocked, mark_blocked, mark_rerun, resolve_dead_letter, resume_all_blocked,
+  resume_blocked, save_historical_tasks,
 };
 // `pub` (not `pub(crate)`): the `cortex diff --tasks` subcommand calls it directly, giving the
 // per-task changed-tasks drill a third (CLI) surface alongside the agent `/api/runs/<c>/<s>/tasks`
 // and the web screen.
+pub use regressions::{
+  CLASS_SEVERITIES, ClassChange, REGRESSION_QUERY_BUDGET_MS, RegressionThresholds, RunRegressions,
+  WorseTransition, pending_regression_runs, record_run_regressions,
+};
 pub use reports::list_task_diffs;
 pub(crate) use reports::live_run_diff;
+pub(crate) use reports::priority_backlog;
 pub(crate) use reports::progress_report;
 pub(crate) use reports::report_uses_rollup;
 // `pub` (not `pub(crate)`): the `cortex` CLI's `diff` subcommand calls it directly, so the run-diff
@@ -41,6 +76,13 @@ pub(crate) use rollup::{
   populate_scope_bounded, report_cache_computed_at, scope_cached, severity_total, what_rollup,
 };
 pub use sandbox::{SandboxOutcome, SandboxSelection, create_sandbox};
+pub use search::{
+  Highlight, MAX_MESSAGE_QUERY_CHARS, MessageHit, MessageSearch, MessageSearchReport,
+  SEARCH_QUERY_BUDGET_MS, highlights, search_messages, validate_message_text,
+};
+pub use versions::{
+  ArchiveMember, ArchiveView, MAIN_HTML_LIMIT, prune_versions_before, read_archive_view,
+};
 
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use diesel::result::Error;
@@ -51,7 +93,10 @@ use std::fmt;
 use crate::concerns::{CortexDeletable, CortexInsertable};
 use crate::config::config;
 use crate::helpers::{TaskReport, TaskStatus};
-use crate::models::{Corpus, NewTask, Service, Task};
+use crate::models::{
+  ActivationWeight, Corpus, DeadLetter, DeadLetterResolution, NewDeadLetter, NewDocument,
+  NewEntryMetadata, NewTask, Service, Task,
+};
 
 /// The production database postgresql address, from the runtime [`crate::config`] configuration
 pub fn default_db_address() -> &'static str { &config().database.url }
@@ -161,6 +206,12 @@ pub struct RerunOptions<'a> {
   pub owner_opt: Option<String>,
   /// optionally, description of the rerun (default is "rerun")
   pub description_opt: Option<String>,
+  /// optionally, the leasing priority to give the rerun tasks (higher is leased sooner); `None`
+  /// returns them to the ordinary backlog (priority 0)
+  pub priority_opt: Option<i32>,
+  /// optionally, a full-text message query (see [`search_messages`]): only the tasks that emitted
+  /// a matching message are rerun, `severity_opt` then naming the message severity searched
+  pub text_opt: Option<String>,
 }
 
 /// Instance methods
@@ -170,6 +221,27 @@ impl Backend {
   pub fn mark_imported(&mut self, imported_tasks: &[NewTask]) -> Result<usize, Error> {
     mark::mark_imported(&mut self.connection, imported_tasks)
   }
+  /// Insert import tasks with the facts inspected about their entries (see `importer::inspect`)
+  /// and any manifest metadata (see `importer::manifest`), in one transaction; tasks and document
+  /// rows insert only if new, metadata replaces an earlier import's.
+  pub fn mark_imported_documents(
+    &mut self,
+    imported_tasks: &[NewTask],
+    documents: &[NewDocument],
+    metadata: &[NewEntryMetadata],
+  ) -> Result<usize, Error> {
+    mark::mark_imported_documents(&mut self.connection, imported_tasks, documents, metadata)
+  }
+  /// Records the re-inspected facts of `corpus` entries whose source changed in place, and resets
+  /// their finished conversions to TODO for every activated service, on the corpus and its
+  /// sandboxes (see [`mark::mark_changed_sources`]). Returns the number of tasks reset.
+  pub fn mark_changed_sources(
+    &mut self,
+    corpus: &Corpus,
+    documents: &[NewDocument],
+  ) -> Result<usize, Error> {
+    mark::mark_changed_sources(&mut self.connection, corpus.id, documents)
+  }
   /// Insert a vector of `TaskReport` reports into the Task store, also marking their tasks as
   /// completed with the correct status code.
   pub fn mark_done(&mut self, reports: &[TaskReport]) -> Result<(), Error> {
@@ -188,13 +260,53 @@ impl Backend {
     mark::mark_blocked(&mut self.connection, corpus_id, service_id)
   }
 
-  /// **Resume** a paused `(corpus, service)` run: return every Blocked task (`status < -5`) to TODO
-  /// so the dispatcher picks them up. Returns the number resumed. The inverse of
-  /// [`Backend::pause_run`].
+  /// **Resume** a paused `(corpus, service)` run: return every paused task to TODO so the
+  /// dispatcher picks them up (pipeline tasks awaiting an upstream stay Blocked). Returns the
+  /// number resumed. The inverse of [`Backend::pause_run`].
   pub fn resume_run(&mut self, corpus_id: i32, service_id: i32) -> Result<usize, Error> {
     mark::resume_blocked(&mut self.connection, corpus_id, service_id)
   }
 
+  /// Records the dispatch dead-letters of one reaping pass (tasks given up on after exhausting
+  /// their retries, with their lease history). Returns the number recorded.
+  pub fn record_dead_letters(&mut self, letters: &[NewDeadLetter]) -> Result<usize, Error> {
+    DeadLetter::record(&mut self.connection, letters)
+  }
+
+  /// Records the lease incidents of one reaping pass or finalize batch in the poison-document
+  /// history, holding every entry whose strikes reach `threshold` (`<= 0` disables holding).
+  /// Returns the entries this pass held.
+  pub fn record_lease_incidents(
+    &mut self,
+    incidents: &[LeaseIncident],
+    threshold: i64,
+  ) -> Result<Vec<HeldEntry>, Error> {
+    PoisonEntry::record_incidents(&mut self.connection, incidents, threshold)
+  }
+
+  /// Returns leased (Queued) tasks to TODO without dispatching them, e.g. the queued tasks of a
+  /// just-held poison entry. Completed tasks are left alone.
+  pub fn unlease_tasks(&mut self, task_ids: &[i64]) -> Result<usize, Error> {
+    tasks_aggregate::unlease_tasks(&mut self.connection, task_ids)
+  }
+
+  /// Releases a held poison entry on behalf of `actor`, so its tasks are leased again.
+  /// `NotFound` unless the entry is held.
+  pub fn release_poison_entry(&mut self, entry: &str, actor: &str) -> Result<PoisonEntry, Error> {
+    PoisonEntry::release(&mut self.connection, entry, actor)
+  }
+
+  /// Requeues or quarantines dispatch dead-letter `id` on behalf of `actor`, returning the updated
+  /// entry. See [`mark::resolve_dead_letter`] for the allowed transitions.
+  pub fn resolve_dead_letter(
+    &mut self,
+    id: i64,
+    resolution: DeadLetterResolution,
+    actor: &str,
+  ) -> Result<DeadLetter, Error> {
+    mark::resolve_dead_letter(&mut self.connection, id, resolution, actor)
+  }
+
   /// While not changing any status information for Tasks, add a new historical run bookmark
   pub fn mark_new_run(
     &mut self,
@@ -234,22 +346,42 @@ impl Backend {
     object.create(&mut self.connection)
   }
 
-  /// Fetches no more than `limit` queued tasks for a given `Service`
-  pub fn fetch_tasks(&mut self, service: &Service, limit: usize) -> Result<Vec<Task>, Error> {
-    tasks_aggregate::fetch_tasks(&mut self.connection, service, limit)
+  /// Fetches no more than `limit` queued tasks for a given `Service`, tagging the leases with the
+  /// leasing dispatcher `instance` (`models::DispatcherInstance`), so peers never recover them
+  /// while it is alive.
+  pub fn fetch_tasks(
+    &mut self,
+    service: &Service,
+    limit: usize,
+    instance: i32,
+  ) -> Result<Vec<Task>, Error> {
+    tasks_aggregate::fetch_tasks(&mut self.connection, service, limit, instance)
   }
-  /// Globally resets any "in progress" tasks back to "queued".
-  /// Particularly useful for dispatcher restarts, when all "in progress" tasks need to be
-  /// invalidated
+  /// Globally resets any "in progress" tasks back to "queued", whichever dispatcher leased them.
+  /// Only safe while no dispatcher runs; a starting dispatcher uses
+  /// [`Self::clear_limbo_tasks_except`] instead.
   pub fn clear_limbo_tasks(&mut self) -> Result<usize, Error> {
     tasks_aggregate::clear_limbo_tasks(&mut self.connection)
   }
 
-  /// Like [`Self::clear_limbo_tasks`], but preserves the given in-flight task ids (the live
-  /// `progress_queue` on a ventilator restart) so tasks a worker is actively processing are not
-  /// reset to `TODO` and double-dispatched. See `tasks_aggregate::clear_limbo_tasks_except`.
-  pub fn clear_limbo_tasks_except(&mut self, in_flight: &[i64]) -> Result<usize, Error> {
-    tasks_aggregate::clear_limbo_tasks_except(&mut self.connection, in_flight)
+  /// Resets the "in progress" tasks dispatcher `instance` may recover — its own and those of dead
+  /// instances, never a live peer's, and untagged ones only under
+  /// `dispatcher.recover_untagged_leases` — but preserves the given in-flight task ids
+  /// (the live `progress_queue` on a ventilator restart) so tasks a worker is actively processing
+  /// are not reset to `TODO` and double-dispatched. See
+  /// `tasks_aggregate::clear_limbo_tasks_except`.
+  pub fn clear_limbo_tasks_except(
+    &mut self,
+    instance: i32,
+    in_flight: &[i64],
+  ) -> Result<usize, Error> {
+    tasks_aggregate::clear_limbo_tasks_except(
+      &mut self.connection,
+      instance,
+      in_flight,
+      config().dispatcher.instance_timeout_seconds,
+      config().dispatcher.recover_untagged_leases,
+    )
   }
 
   /// Activates an existing service on the given corpus.
@@ -276,6 +408,18 @@ impl Backend {
     services_aggregate::extend_service(&mut self.connection, service, corpus)
   }
 
+  /// Sets the fair-share weight of `service`'s activation on `corpus`: the relative slice of the
+  /// service's lease batches this corpus receives while other corpora have work for it too (see
+  /// `tasks_aggregate::fetch_tasks`). Weights below 1 are clamped to 1.
+  pub fn set_activation_weight(
+    &mut self,
+    corpus: &Corpus,
+    service: &Service,
+    weight: i32,
+  ) -> Result<usize, Error> {
+    ActivationWeight::set(&mut self.connection, corpus.id, service.id, weight)
+  }
+
   /// Permanently destroys a service by name — its definition plus all of its tasks + `log_*` rows
   /// across every corpus, in one transaction (orphan-free + crash-consistent; closes R-6). Refuses
   /// the magic `init`/`import` services. See [`services_aggregate::destroy_service_by_name`].
@@ -314,6 +458,11 @@ impl Backend {
   pub fn progress_report(&mut self, corpus: &Corpus, service: &Service) -> HashMap<String, f64> {
     reports::progress_report(&mut self.connection, corpus.id, service.id)
   }
+  /// The outstanding (TODO + Queued) backlog of a `Corpus` and `Service` pair per leasing
+  /// priority, as `(priority, tasks)` highest priority first
+  pub fn priority_backlog(&mut self, corpus: &Corpus, service: &Service) -> Vec<(i32, i64)> {
+    reports::priority_backlog(&mut self.connection, corpus.id, service.id)
+  }
 
   /// Recomputes the `report_summary` rollup (Arm 14 #6); call on the run-completion path so the
   /// cheap category/what report reads stay fresh.
@@ -329,33 +478,60 @@ impl Backend {
   /// Run-completion-on-drain: close the `(corpus, service)` pair's open historical run iff its work
   /// is exhausted (every task terminal). Delegates to
   /// [`HistoricalRun::complete_if_drained`](crate::models::HistoricalRun::complete_if_drained);
-  /// returns `true` iff a run was closed. Called per touched scope from the dispatcher's finalize
-  /// loop when the queue idles, so a finished run is closed at once rather than at the next rerun.
+  /// returns the id of the run it closed, if it closed one. Called per touched scope from the
+  /// dispatcher's finalize loop when the queue idles, so a finished run is closed at once rather
+  /// than at the next rerun. The closed run's tasks and message classes are frozen here, at the
+  /// drain point; only the comparison with the previous run is left to a background job
+  /// ([`crate::jobs::spawn_run_regressions`]).
   pub fn complete_run_if_drained(
     &mut self,
     corpus_id: i32,
     service_id: i32,
-  ) -> Result<bool, Error> {
+  ) -> Result<Option<i32>, Error> {
     let closed = crate::models::HistoricalRun::complete_if_drained(
       corpus_id,
       service_id,
       &mut self.connection,
     )?;
-    if closed {
-      // The run just closed on drain — its per-task outcomes are settled. Snapshot them into
-      // `historical_tasks` as the BASELINE for the NEXT run's live run-diff: captured once, here,
-      // at the natural completion point (in the finalize thread, off the request path). Best-effort
-      // — a snapshot failure must never undo the (critical) run close, so log and carry on.
-      if let Err(e) = mark::snapshot_tasks(&mut self.connection, corpus_id, service_id) {
-        tracing::warn!(
-          corpus_id,
-          service_id,
-          error = ?e,
-          "run-completion-on-drain: baseline snapshot failed (non-fatal)"
-        );
-      }
+    if !closed {
+      return Ok(None);
+    }
+    // The run just closed on drain — its per-task outcomes are settled. Snapshot them into
+    // `historical_tasks` as the BASELINE for the NEXT run's live run-diff (and as this run's
+    // closing statuses for its regression summary): captured once, here, at the natural completion
+    // point (in the finalize thread, off the request path). Best-effort — a snapshot failure must
+    // never undo the (critical) run close, so log and carry on.
+    if let Err(e) = mark::snapshot_tasks(&mut self.connection, corpus_id, service_id) {
+      tracing::warn!(
+        corpus_id,
+        service_id,
+        error = ?e,
+        "run-completion-on-drain: baseline snapshot failed (non-fatal)"
+      );
+    }
+    // The pair's newest closed run is the one just closed (a pair has one open run at a time).
+    use crate::schema::historical_runs;
+    let run_id: Option<i32> = historical_runs::table
+      .filter(historical_runs::corpus_id.eq(corpus_id))
+      .filter(historical_runs::service_id.eq(service_id))
+      .filter(historical_runs::end_time.is_not_null())
+      .order(historical_runs::start_time.desc())
+      .select(historical_runs::id)
+      .first(&mut self.connection)
+      .optional()?;
+    // Freeze its message classes now too, before a rerun can clear the logs they come from; the
+    // run then owes a regression summary. Best-effort like the snapshot above.
+    if let Some(run_id) = run_id
+      && let Err(e) =
+        regressions::freeze_run_classes(&mut self.connection, run_id, corpus_id, service_id)
+    {
+      tracing::warn!(
+        run_id,
+        error = ?e,
+        "run-completion-on-drain: message-class snapshot failed, no regression summary (non-fatal)"
+      );
     }
-    Ok(closed)
+    Ok(run_id)
   }
   /// Category-grain report for `(corpus, service, severity)`, read from the `report_summary`
   /// rollup, windowed to `[offset, offset + limit)` (ordered by descending task count).
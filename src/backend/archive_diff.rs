@@ -0,0 +1,398 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Document-level diff** of two result archives of the same entry — what changed in the output
+//! between two runs (a kept result version against the current one), or between a sandbox and its
+//! parent corpus. Where the run-diff screens say *which* documents changed severity, this says
+//! *what* changed in one of them: the messages of the parsed `cortex.log`, the archive members, and
+//! the text of the main HTML document.
+//!
+//! Both sides are [`ArchiveView`]s (`backend::read_archive_view`); the diff itself is pure.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+use crate::backend::ArchiveView;
+use crate::models::LogRecord;
+
+/// The most diff lines (context included) kept of the main HTML's text diff.
+pub const TEXT_DIFF_LINE_LIMIT: usize = 2_000;
+
+/// Unchanged lines of context around each change of the main HTML's text diff.
+const TEXT_DIFF_CONTEXT: usize = 3;
+
+/// How long the main HTML's line diff may search for the minimal diff before settling for a
+/// coarser one.
+const TEXT_DIFF_DEADLINE: Duration = Duration::from_secs(2);
+
+/// A message whose occurrences differ between the two sides, keyed by its severity, category, what
+/// and details.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, schemars::JsonSchema)]
+pub struct MessageDelta {
+  /// `info` | `warning` | `error` | `fatal` | `invalid`
+  pub severity: String,
+  /// mid-level description
+  pub category: String,
+  /// low-level description
+  pub what: String,
+  /// technical details
+  pub details: String,
+  /// occurrences on the left (older) side
+  pub left: usize,
+  /// occurrences on the right (newer) side
+  pub right: usize,
+}
+
+/// An archive member added, removed or resized between the two sides.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, schemars::JsonSchema)]
+pub struct MemberDelta {
+  /// its path inside the archive
+  pub name: String,
+  /// its size on the left side, if it is there
+  pub left_size: Option<u64>,
+  /// its size on the right side, if it is there
+  pub right_size: Option<u64>,
+}
+
+/// One line of the main HTML's text diff.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, schemars::JsonSchema)]
+pub struct DiffLine {
+  /// `equal` (context), `delete` (left only) or `insert` (right only)
+  pub tag: &'static str,
+  /// its 1-based line number on the left side, unless inserted
+  pub left_line: Option<usize>,
+  /// its 1-based line number on the right side, unless deleted
+  pub right_line: Option<usize>,
+  /// the line, without its line break
+  pub text: String,
+}
+
+/// The text diff of the two sides' main HTML documents, as hunks of changed lines with
+/// [`TEXT_DIFF_CONTEXT`] lines of context.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, schemars::JsonSchema)]
+pub struct TextDiffReport {
+  /// the left side's main HTML member
+  pub left_name: Option<String>,
+  /// the right side's main HTML member
+  pub right_name: Option<String>,
+  /// the hunks, in document order (empty when the texts are identical)
+  pub hunks: Vec<Vec<DiffLine>>,
+  /// lines deleted in total
+  pub deleted: usize,
+  /// lines inserted in total
+  pub inserted: usize,
+  /// how similar the texts are, from `0.0` to `1.0`
+  pub similarity: f32,
+  /// whether the hunks were cut at [`TEXT_DIFF_LINE_LIMIT`] lines, or either text was cut at
+  /// `backend::MAIN_HTML_LIMIT` before diffing
+  pub truncated: bool,
+}
+
+/// What changed between two result archives of one document.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, schemars::JsonSchema)]
+pub struct ArchiveDiff {
+  /// the messages that occur a different number of times, most severe first
+  pub messages: Vec<MessageDelta>,
+  /// the distinct messages that occur equally often on both sides
+  pub unchanged_messages: usize,
+  /// the members added, removed or resized, by name
+  pub members: Vec<MemberDelta>,
+  /// the members present with the same size on both sides
+  pub unchanged_members: usize,
+  /// the text diff of the main HTML, when either side has one
+  pub main_html: Option<TextDiffReport>,
+}
+
+/// Diffs the `left` (older) result archive against the `right` (newer) one.
+pub fn diff_archives(left: &ArchiveView, right: &ArchiveView) -> ArchiveDiff {
+  let (messages, unchanged_messages) = diff_messages(left, right);
+  let (members, unchanged_members) = diff_members(left, right);
+  let main_html = if left.main_html.is_some() || right.main_html.is_some() {
+    let mut report = diff_text(
+      left.main_html.as_deref().unwrap_or_default(),
+      right.main_html.as_deref().unwrap_or_default(),
+    );
+    report.left_name = left.main_html_name.clone();
+    report.right_name = right.main_html_name.clone();
+    report.truncated |= left.main_html_truncated || right.main_html_truncated;
+    Some(report)
+  } else {
+    None
+  };
+  ArchiveDiff {
+    messages,
+    unchanged_messages,
+    members,
+    unchanged_members,
+    main_html,
+  }
+}
+
+/// The rank of a severity, most severe first.
+fn severity_rank(severity: &str) -> u8 {
+  match severity {
+    "fatal" => 0,
+    "invalid" => 1,
+    "error" => 2,
+    "warning" => 3,
+    _ => 4,
+  }
+}
+
+/// The messages occurring a different number of times on the two sides, and how many distinct
+/// messages occur equally often. Messages are compared as a multiset: a message that merely moved
+/// within the log is unchanged.
+fn diff_messages(left: &ArchiveView, right: &ArchiveView) -> (Vec<MessageDelta>, usize) {
+  type Key = (u8, String, String, String, String);
+  let mut counts: BTreeMap<Key, (usize, usize)> = BTreeMap::new();
+  let key = |message: &dyn LogRecord| -> Key {
+    (
+      severity_rank(message.severity()),
+      message.severity().to_string(),
+      message.category().to_string(),
+      message.what().to_string(),
+      message.details().to_string(),
+    )
+  };
+  for message in &left.messages {
+    counts.entry(key(message)).or_default().0 += 1;
+  }
+  for message in &right.messages {
+    counts.entry(key(message)).or_default().1 += 1;
+  }
+  let mut unchanged = 0;
+  let mut deltas = Vec::new();
+  for ((_, severity, category, what, details), (left, right)) in counts {
+    if left == right {
+      unchanged += 1;
+    } else {
+      deltas.push(MessageDelta {
+        severity,
+        category,
+        what,
+        details,
+        left,
+        right,
+      });
+    }
+  }
+  (deltas, unchanged)
+}
+
+/// The members added, removed or resized between the two sides, and how many are unchanged.
+fn diff_members(left: &ArchiveView, right: &ArchiveView) -> (Vec<MemberDelta>, usize) {
+  let mut sizes: BTreeMap<&str, (Option<u64>, Option<u64>)> = BTreeMap::new();
+  for member in &left.members {
+    sizes.entry(&member.name).or_default().0 = Some(member.size);
+  }
+  for member in &right.members {
+    sizes.entry(&member.name).or_default().1 = Some(member.size);
+  }
+  let mut unchanged = 0;
+  let mut deltas = Vec::new();
+  for (name, (left_size, right_size)) in sizes {
+    if left_size == right_size {
+      unchanged += 1;
+    } else {
+      deltas.push(MemberDelta {
+        name: name.to_string(),
+        left_size,
+        right_size,
+      });
+    }
+  }
+  (deltas, unchanged)
+}
+
+/// The line diff of `left` against `right`, in hunks with context, capped at
+/// [`TEXT_DIFF_LINE_LIMIT`] lines.
+fn diff_text(left: &str, right: &str) -> TextDiffReport {
+  let diff = TextDiff::configure()
+    .timeout(TEXT_DIFF_DEADLINE)
+    .diff_lines(left, right);
+  let mut report = TextDiffReport {
+    similarity: diff.ratio(),
+    ..TextDiffReport::default()
+  };
+  let mut kept = 0;
+  for group in diff.grouped_ops(TEXT_DIFF_CONTEXT) {
+    let mut hunk = Vec::new();
+    for op in &group {
+      for change in diff.iter_changes(op) {
+        let tag = match change.tag() {
+          ChangeTag::Equal => "equal",
+          ChangeTag::Delete => {
+            report.deleted += 1;
+            "delete"
+          },
+          ChangeTag::Insert => {
+            report.inserted += 1;
+            "insert"
+          },
+        };
+        if kept < TEXT_DIFF_LINE_LIMIT {
+          kept += 1;
+          hunk.push(DiffLine {
+            tag,
+            left_line: change.old_index().map(|index| index + 1),
+            right_line: change.new_index().map(|index| index + 1),
+            text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+          });
+        } else {
+          report.truncated = true;
+        }
+      }
+    }
+    if !hunk.is_empty() {
+      report.hunks.push(hunk);
+    }
+  }
+  report
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::backend::ArchiveMember;
+  use crate::helpers::{NewTaskMessage, TaskStatus};
+
+  fn view(messages: &[(&str, &str, &str)], members: &[(&str, u64)], html: &str) -> ArchiveView {
+    ArchiveView {
+      status: TaskStatus::Warning,
+      messages: messages
+        .iter()
+        .map(|(severity, category, what)| {
+          NewTaskMessage::new(
+            1,
+            severity,
+            category.to_string(),
+            what.to_string(),
+            String::new(),
+          )
+        })
+        .collect(),
+      members: members
+        .iter()
+        .map(|(name, size)| ArchiveMember {
+          name: name.to_string(),
+          size: *size,
+        })
+        .collect(),
+      main_html_name: Some("doc.html".to_string()),
+      main_html: Some(html.to_string()),
+      main_html_truncated: false,
+    }
+  }
+
+  #[test]
+  fn diffs_messages_as_a_multiset_most_severe_first() {
+    let left = view(
+      &[
+        ("warning", "undefined", "\\foo"),
+        ("warning", "undefined", "\\foo"),
+        ("error", "missing_file", "x.sty"),
+        ("info", "conversion", "done"),
+      ],
+      &[],
+      "",
+    );
+    let right = view(
+      &[
+        ("info", "conversion", "done"),
+        ("warning", "undefined", "\\foo"),
+        ("fatal", "timeout", "killed"),
+      ],
+      &[],
+      "",
+    );
+    let (deltas, unchanged) = diff_messages(&left, &right);
+    assert_eq!(unchanged, 1, "the info message only moved");
+    let summary: Vec<(&str, &str, usize, usize)> = deltas
+      .iter()
+      .map(|delta| {
+        (
+          delta.severity.as_str(),
+          delta.what.as_str(),
+          delta.left,
+          delta.right,
+        )
+      })
+      .collect();
+    assert_eq!(
+      summary,
+      [
+        ("fatal", "killed", 0, 1),
+        ("error", "x.sty", 1, 0),
+        ("warning", "\\foo", 2, 1)
+      ]
+    );
+  }
+
+  #[test]
+  fn diffs_members_by_name_and_size() {
+    let left = view(
+      &[],
+      &[("doc.html", 10), ("x1.png", 5), ("cortex.log", 3)],
+      "",
+    );
+    let right = view(
+      &[],
+      &[("doc.html", 12), ("x2.png", 5), ("cortex.log", 3)],
+      "",
+    );
+    let (deltas, unchanged) = diff_members(&left, &right);
+    assert_eq!(unchanged, 1);
+    assert_eq!(
+      deltas,
+      [
+        MemberDelta {
+          name: "doc.html".to_string(),
+          left_size: Some(10),
+          right_size: Some(12)
+        },
+        MemberDelta {
+          name: "x1.png".to_string(),
+          left_size: Some(5),
+          right_size: None
+        },
+        MemberDelta {
+          name: "x2.png".to_string(),
+          left_size: None,
+          right_size: Some(5)
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn diffs_the_main_html_in_hunks_with_context() {
+    let left: String = (1..=20).map(|line| format!("<p>{line}</p>\n")).collect();
+    let right = left.replace("<p>10</p>", "<p>ten</p>");
+    let diff = diff_archives(&view(&[], &[], &left), &view(&[], &[], &right));
+    let text = diff.main_html.expect("both sides have HTML");
+    assert_eq!((text.deleted, text.inserted), (1, 1));
+    assert_eq!(text.hunks.len(), 1);
+    let hunk = &text.hunks[0];
+    assert_eq!(hunk.len(), 2 * TEXT_DIFF_CONTEXT + 2);
+    assert_eq!(hunk[0].left_line, Some(10 - TEXT_DIFF_CONTEXT));
+    assert!(
+      hunk
+        .iter()
+        .any(|line| line.tag == "delete" && line.text == "<p>10</p>" && line.left_line == Some(10))
+    );
+    assert!(hunk.iter().any(|line| line.tag == "insert"
+      && line.text == "<p>ten</p>"
+      && line.right_line == Some(10)));
+    assert!(!text.truncated);
+
+    let same = diff_archives(&view(&[], &[], &left), &view(&[], &[], &left));
+    assert!(same.main_html.expect("HTML").hunks.is_empty());
+  }
+}
@@ -0,0 +1,272 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Message-class trends across runs.** Every run that closes on drain freezes its message classes
+//! into the per-run rollup tables (see [`super::regressions`]): `run_message_categories` at the
+//! `(severity, category)` grain, `run_message_classes` at `(severity, category, what)`. Read back
+//! over a window of recent runs they answer "how did `undefined/\foo` evolve over the last 20
+//! releases" ([`class_trend`]) and "which classes grew or shrank the most" ([`class_movers`]).
+//!
+//! Only snapshotted runs take part — those carrying a regression summary, which is written in the
+//! same transaction as their classes — so a class absent from such a run had no messages then,
+//! rather than no data.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Integer, Nullable, Text};
+
+use crate::schema::historical_runs;
+
+/// Runs in a trend window by default.
+pub const DEFAULT_TREND_RUNS: i64 = 20;
+
+/// The most runs a trend window may span.
+pub const MAX_TREND_RUNS: i64 = 200;
+
+/// The grain a class trend is read at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClassGrain {
+  /// `(severity, category)`, from `run_message_categories`.
+  Category,
+  /// `(severity, category, what)`, from `run_message_classes`.
+  What,
+}
+
+impl ClassGrain {
+  /// Parse the CLI/string form (`category` / `what`).
+  pub fn from_key(key: &str) -> Option<Self> {
+    match key {
+      "category" => Some(ClassGrain::Category),
+      "what" => Some(ClassGrain::What),
+      _ => None,
+    }
+  }
+
+  /// The CLI/string form.
+  pub fn to_key(self) -> &'static str {
+    match self {
+      ClassGrain::Category => "category",
+      ClassGrain::What => "what",
+    }
+  }
+
+  /// The rollup table holding this grain.
+  fn table(self) -> &'static str {
+    match self {
+      ClassGrain::Category => "run_message_categories",
+      ClassGrain::What => "run_message_classes",
+    }
+  }
+}
+
+/// A snapshotted run of a trend window.
+#[derive(Debug, Clone)]
+pub struct TrendRun {
+  /// internal run id
+  pub id: i32,
+  /// the run's stable external handle
+  pub public_id: String,
+  /// when the run started
+  pub start_time: NaiveDateTime,
+  /// why the run was initiated
+  pub description: String,
+}
+
+/// One run's counts of a class.
+#[derive(Debug, Clone)]
+pub struct ClassTrendPoint {
+  /// the run
+  pub run: TrendRun,
+  /// tasks with the class at the end of the run (0 when absent)
+  pub task_count: i64,
+  /// messages of the class at the end of the run (0 when absent)
+  pub message_count: i64,
+}
+
+/// A class whose task count moved across a trend window, from its first run to its last.
+#[derive(QueryableByName, Debug, Clone, PartialEq, Eq)]
+pub struct ClassMove {
+  /// message severity
+  #[diesel(sql_type = Text)]
+  pub severity: String,
+  /// message category
+  #[diesel(sql_type = Text)]
+  pub category: String,
+  /// message `what`; `None` at the category grain
+  #[diesel(sql_type = Nullable<Text>)]
+  pub what: Option<String>,
+  /// tasks with the class in the window's first run
+  #[diesel(sql_type = BigInt)]
+  pub first_tasks: i64,
+  /// tasks with the class in the window's last run
+  #[diesel(sql_type = BigInt)]
+  pub last_tasks: i64,
+}
+
+impl ClassMove {
+  /// The change in tasks across the window.
+  pub fn change(&self) -> i64 { self.last_tasks - self.first_tasks }
+}
+
+/// The fastest-growing and fastest-shrinking classes of a trend window.
+#[derive(Debug, Clone, Default)]
+pub struct ClassMovers {
+  /// the window's snapshotted runs, oldest first; the movers compare the first with the last
+  pub runs: Vec<TrendRun>,
+  /// classes that grew the most, largest growth first
+  pub growing: Vec<ClassMove>,
+  /// classes that shrank the most, largest drop first
+  pub shrinking: Vec<ClassMove>,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+  #[diesel(sql_type = Integer)]
+  run_id: i32,
+  #[diesel(sql_type = BigInt)]
+  task_count: i64,
+  #[diesel(sql_type = BigInt)]
+  message_count: i64,
+}
+
+/// The last `runs` snapshotted runs of a `(corpus, service)`, oldest first.
+pub fn trend_runs(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service_id: i32,
+  runs: i64,
+) -> QueryResult<Vec<TrendRun>> {
+  let mut rows: Vec<(i32, uuid::Uuid, NaiveDateTime, String)> = historical_runs::table
+    .filter(historical_runs::corpus_id.eq(corpus_id))
+    .filter(historical_runs::service_id.eq(service_id))
+    .filter(historical_runs::regressions.is_not_null())
+    .order(historical_runs::start_time.desc())
+    .limit(runs.clamp(1, MAX_TREND_RUNS))
+    .select((
+      historical_runs::id,
+      historical_runs::public_id,
+      historical_runs::start_time,
+      historical_runs::description,
+    ))
+    .load(connection)?;
+  rows.reverse();
+  Ok(
+    rows
+      .into_iter()
+      .map(|(id, public_id, start_time, description)| TrendRun {
+        id,
+        public_id: public_id.to_string(),
+        start_time,
+        description,
+      })
+      .collect(),
+  )
+}
+
+/// The counts of one class over the last `runs` snapshotted runs of a `(corpus, service)`, oldest
+/// first. `what: None` reads the category grain.
+pub fn class_trend(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service_id: i32,
+  severity: &str,
+  category: &str,
+  what: Option<&str>,
+  runs: i64,
+) -> QueryResult<Vec<ClassTrendPoint>> {
+  let window = trend_runs(connection, corpus_id, service_id, runs)?;
+  let run_ids: Vec<i32> = window.iter().map(|run| run.id).collect();
+  let counts: Vec<CountRow> = match what {
+    Some(what) => sql_query(
+      "SELECT run_id, task_count, message_count FROM run_message_classes \
+       WHERE run_id = ANY($1) AND severity = $2 AND category = $3 AND what = $4",
+    )
+    .bind::<Array<Integer>, _>(&run_ids)
+    .bind::<Text, _>(severity)
+    .bind::<Text, _>(category)
+    .bind::<Text, _>(what)
+    .load(connection)?,
+    None => sql_query(
+      "SELECT run_id, task_count, message_count FROM run_message_categories \
+       WHERE run_id = ANY($1) AND severity = $2 AND category = $3",
+    )
+    .bind::<Array<Integer>, _>(&run_ids)
+    .bind::<Text, _>(severity)
+    .bind::<Text, _>(category)
+    .load(connection)?,
+  };
+  Ok(
+    window
+      .into_iter()
+      .map(|run| {
+        let count = counts.iter().find(|row| row.run_id == run.id);
+        ClassTrendPoint {
+          task_count: count.map_or(0, |row| row.task_count),
+          message_count: count.map_or(0, |row| row.message_count),
+          run,
+        }
+      })
+      .collect(),
+  )
+}
+
+/// The classes of a `(corpus, service)` whose task counts grew and shrank the most between the
+/// first and the last of its last `runs` snapshotted runs (a class absent from one side counts 0),
+/// at most `limit` each, optionally narrowed to one `severity`. Empty below two snapshotted runs.
+pub fn class_movers(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service_id: i32,
+  grain: ClassGrain,
+  severity: Option<&str>,
+  runs: i64,
+  limit: i64,
+) -> QueryResult<ClassMovers> {
+  let window = trend_runs(connection, corpus_id, service_id, runs)?;
+  let (Some(first), Some(last)) = (window.first(), window.last()) else {
+    return Ok(ClassMovers::default());
+  };
+  if first.id == last.id {
+    return Ok(ClassMovers {
+      runs: window,
+      ..ClassMovers::default()
+    });
+  }
+  let (what_column, what_join) = match grain {
+    ClassGrain::Category => ("NULL::text", ""),
+    ClassGrain::What => ("COALESCE(a.what, b.what)", " AND a.what = b.what"),
+  };
+  let mut moved = |direction: &str| -> QueryResult<Vec<ClassMove>> {
+    sql_query(format!(
+      "SELECT COALESCE(a.severity, b.severity) AS severity, \
+         COALESCE(a.category, b.category) AS category, {what_column} AS what, \
+         COALESCE(a.task_count, 0) AS first_tasks, COALESCE(b.task_count, 0) AS last_tasks \
+       FROM (SELECT * FROM {table} WHERE run_id = $1) a \
+       FULL JOIN (SELECT * FROM {table} WHERE run_id = $2) b \
+         ON a.severity = b.severity AND a.category = b.category{what_join} \
+       WHERE ($3::text IS NULL OR COALESCE(a.severity, b.severity) = $3) \
+         AND COALESCE(b.task_count, 0) {direction} COALESCE(a.task_count, 0) \
+       ORDER BY abs(COALESCE(b.task_count, 0) - COALESCE(a.task_count, 0)) DESC, \
+         severity, category, what \
+       LIMIT $4",
+      table = grain.table(),
+    ))
+    .bind::<Integer, _>(first.id)
+    .bind::<Integer, _>(last.id)
+    .bind::<Nullable<Text>, _>(severity)
+    .bind::<BigInt, _>(limit)
+    .load(connection)
+  };
+  let growing = moved(">")?;
+  let shrinking = moved("<")?;
+  Ok(ClassMovers {
+    runs: window,
+    growing,
+    shrinking,
+  })
+}
@@ -0,0 +1,273 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Message clusters**: the rung below `what` in the report ladder. The messages of one
+//! `(corpus, service, severity, category, what)` grouped by the fingerprint of their `details`
+//! (see [`crate::helpers::fingerprint`]), so a `what` with thousands of near-identical rows reads
+//! as its handful of distinct root causes — each with its task and message counts and a few
+//! example documents.
+//!
+//! Clusters are aggregated live (they are one `GROUP BY` over a single `what`'s rows, not a whole
+//! severity slice), under [`CLUSTER_QUERY_BUDGET_MS`] so an outsized `what` cannot pin a pooled
+//! connection. Messages stored before fingerprints existed carry none and are counted apart until
+//! [`backfill_fingerprints`] fills them in.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Integer, Nullable, Text};
+
+use super::rollup::severity_scope;
+use crate::helpers::fingerprint::{details_fingerprint, normalize_details};
+
+/// How long the cluster queries of one report may run before Postgres cancels them.
+pub const CLUSTER_QUERY_BUDGET_MS: u32 = 20_000;
+
+/// One example document of a cluster.
+#[derive(QueryableByName, Debug, Clone)]
+pub struct ClusterExample {
+  /// The cluster it belongs to.
+  #[diesel(sql_type = BigInt)]
+  pub fingerprint: i64,
+  /// The task id.
+  #[diesel(sql_type = BigInt)]
+  pub task_id: i64,
+  /// The task's entry path.
+  #[diesel(sql_type = Text)]
+  pub entry: String,
+  /// The message's own details, unmasked.
+  #[diesel(sql_type = Text)]
+  pub details: String,
+}
+
+/// One cluster of a `what`'s messages: those sharing a details fingerprint.
+#[derive(Debug, Clone)]
+pub struct MessageCluster {
+  /// The shared fingerprint.
+  pub fingerprint: i64,
+  /// The masked details the cluster's messages share (see
+  /// [`crate::helpers::fingerprint::normalize_details`]).
+  pub pattern: String,
+  /// Distinct tasks with a message in the cluster.
+  pub task_count: i64,
+  /// Messages in the cluster.
+  pub message_count: i64,
+  /// Up to the requested number of example documents, by entry.
+  pub examples: Vec<ClusterExample>,
+}
+
+/// The clusters of one `(corpus, service, severity, category, what)`, a page of them, with the
+/// totals they are drawn from.
+#[derive(Debug, Clone, Default)]
+pub struct ClusterReport {
+  /// Messages under the `what` in total.
+  pub total_messages: i64,
+  /// Of those, messages stored before fingerprints (in no cluster until backfilled).
+  pub unfingerprinted_messages: i64,
+  /// Distinct clusters in total.
+  pub cluster_count: i64,
+  /// The clusters of the requested page, by descending task count.
+  pub clusters: Vec<MessageCluster>,
+}
+
+#[derive(QueryableByName)]
+struct ClusterTotals {
+  #[diesel(sql_type = BigInt)]
+  total_messages: i64,
+  #[diesel(sql_type = BigInt)]
+  unfingerprinted_messages: i64,
+  #[diesel(sql_type = BigInt)]
+  cluster_count: i64,
+}
+
+#[derive(QueryableByName)]
+struct ClusterRow {
+  #[diesel(sql_type = BigInt)]
+  fingerprint: i64,
+  #[diesel(sql_type = BigInt)]
+  task_count: i64,
+  #[diesel(sql_type = BigInt)]
+  message_count: i64,
+}
+
+/// The message clusters of a `(corpus, service, severity, category, what)`: a page of
+/// `[offset, offset + limit)` clusters by descending task count (ties by fingerprint, for a stable
+/// paging order), each with up to `examples` example documents. `None` for a severity outside the
+/// report ladder; an `Err` if the queries overrun [`CLUSTER_QUERY_BUDGET_MS`].
+#[allow(clippy::too_many_arguments)]
+pub fn message_clusters(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service_id: i32,
+  severity: &str,
+  category: &str,
+  what: &str,
+  limit: i64,
+  offset: i64,
+  examples: i64,
+) -> QueryResult<Option<ClusterReport>> {
+  let Some((table, status_pred)) = severity_scope(severity) else {
+    return Ok(None);
+  };
+  // `table` and `status_pred` are compile-time constants (see `severity_scope`), so interpolating
+  // them is injection-safe; everything else is bound.
+  let scope = format!(
+    "FROM tasks t JOIN {table} l ON l.task_id = t.id \
+     WHERE t.corpus_id = $1 AND t.service_id = $2 AND {status_pred} \
+       AND COALESCE(l.category, '') = $3 AND COALESCE(l.what, '') = $4"
+  );
+  connection.transaction::<_, diesel::result::Error, _>(|conn| {
+    sql_query(format!(
+      "SET LOCAL statement_timeout = {CLUSTER_QUERY_BUDGET_MS}"
+    ))
+    .execute(conn)?;
+    let totals: ClusterTotals = sql_query(format!(
+      "SELECT COUNT(*)::bigint AS total_messages, \
+         (COUNT(*) FILTER (WHERE l.fingerprint IS NULL))::bigint AS unfingerprinted_messages, \
+         COUNT(DISTINCT l.fingerprint)::bigint AS cluster_count {scope}"
+    ))
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Integer, _>(service_id)
+    .bind::<Text, _>(category)
+    .bind::<Text, _>(what)
+    .get_result(conn)?;
+    let rows: Vec<ClusterRow> = sql_query(format!(
+      "SELECT l.fingerprint, COUNT(DISTINCT l.task_id)::bigint AS task_count, \
+         COUNT(*)::bigint AS message_count {scope} AND l.fingerprint IS NOT NULL \
+       GROUP BY l.fingerprint ORDER BY task_count DESC, l.fingerprint LIMIT $5 OFFSET $6"
+    ))
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Integer, _>(service_id)
+    .bind::<Text, _>(category)
+    .bind::<Text, _>(what)
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .load(conn)?;
+    let fingerprints: Vec<i64> = rows.iter().map(|row| row.fingerprint).collect();
+    let sample: Vec<ClusterExample> = if fingerprints.is_empty() {
+      Vec::new()
+    } else {
+      sql_query(format!(
+        "SELECT fingerprint, task_id, entry, details FROM ( \
+           SELECT l.fingerprint, t.id AS task_id, t.entry, COALESCE(l.details, '') AS details, \
+             ROW_NUMBER() OVER (PARTITION BY l.fingerprint ORDER BY t.entry, l.id) AS rank \
+           {scope} AND l.fingerprint = ANY($5)) ranked \
+         WHERE rank <= $6 ORDER BY fingerprint, rank"
+      ))
+      .bind::<Integer, _>(corpus_id)
+      .bind::<Integer, _>(service_id)
+      .bind::<Text, _>(category)
+      .bind::<Text, _>(what)
+      .bind::<Array<BigInt>, _>(fingerprints.as_slice())
+      .bind::<BigInt, _>(examples.max(1))
+      .load(conn)?
+    };
+    let mut by_fingerprint: HashMap<i64, Vec<ClusterExample>> = HashMap::new();
+    for example in sample {
+      by_fingerprint
+        .entry(example.fingerprint)
+        .or_default()
+        .push(example);
+    }
+    let clusters = rows
+      .into_iter()
+      .map(|row| {
+        let examples = by_fingerprint.remove(&row.fingerprint).unwrap_or_default();
+        MessageCluster {
+          fingerprint: row.fingerprint,
+          // Every member normalizes to the same text (short of a hash collision), so any example
+          // shows the pattern.
+          pattern: examples
+            .first()
+            .map(|example| normalize_details(&example.details))
+            .unwrap_or_default(),
+          task_count: row.task_count,
+          message_count: row.message_count,
+          examples,
+        }
+      })
+      .collect();
+    Ok(Some(ClusterReport {
+      total_messages: totals.total_messages,
+      unfingerprinted_messages: totals.unfingerprinted_messages,
+      cluster_count: totals.cluster_count,
+      clusters,
+    }))
+  })
+}
+
+/// The messages of one log table given fingerprints by [`backfill_fingerprints`].
+#[derive(Debug, Clone)]
+pub struct FingerprintBackfill {
+  /// The log table.
+  pub table: &'static str,
+  /// Messages fingerprinted.
+  pub messages: usize,
+}
+
+#[derive(QueryableByName)]
+struct UnfingerprintedRow {
+  #[diesel(sql_type = BigInt)]
+  id: i64,
+  #[diesel(sql_type = Nullable<Text>)]
+  details: Option<String>,
+}
+
+/// Fingerprints the messages stored before fingerprints existed, `batch` rows per statement (one
+/// short transaction each, so the backfill never holds long locks on the log tables and can be
+/// interrupted and resumed). `progress` is called after each batch with the table and its running
+/// count.
+pub fn backfill_fingerprints(
+  connection: &mut PgConnection,
+  batch: i64,
+  mut progress: impl FnMut(&str, usize),
+) -> QueryResult<Vec<FingerprintBackfill>> {
+  let mut report = Vec::new();
+  for table in [
+    "log_infos",
+    "log_warnings",
+    "log_errors",
+    "log_fatals",
+    "log_invalids",
+  ] {
+    let mut messages = 0;
+    let mut after = 0i64;
+    loop {
+      // Walk the primary key so each batch starts where the last stopped, instead of rescanning
+      // the rows already filled in.
+      let rows: Vec<UnfingerprintedRow> = sql_query(format!(
+        "SELECT id, details FROM {table} WHERE id > $1 AND fingerprint IS NULL \
+         ORDER BY id LIMIT $2"
+      ))
+      .bind::<BigInt, _>(after)
+      .bind::<BigInt, _>(batch.max(1))
+      .load(connection)?;
+      let Some(last) = rows.last() else {
+        break;
+      };
+      after = last.id;
+      let ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+      let fingerprints: Vec<i64> = rows
+        .iter()
+        .map(|row| details_fingerprint(row.details.as_deref().unwrap_or_default()))
+        .collect();
+      sql_query(format!(
+        "UPDATE {table} SET fingerprint = v.fingerprint \
+         FROM (SELECT UNNEST($1) AS id, UNNEST($2) AS fingerprint) v \
+         WHERE {table}.id = v.id"
+      ))
+      .bind::<Array<BigInt>, _>(ids.as_slice())
+      .bind::<Array<BigInt>, _>(fingerprints.as_slice())
+      .execute(connection)?;
+      messages += rows.len();
+      progress(table, messages);
+    }
+    report.push(FingerprintBackfill { table, messages });
+  }
+  Ok(report)
+}
@@ -0,0 +1,130 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Run-control **wake signals** over Postgres `LISTEN`/`NOTIFY` (docs/ORCHESTRATION_PLAN.md §4).
+//!
+//! The `tasks` table stays the single source of truth: a pause/resume/rerun/activate has already
+//! committed its status changes before a signal is sent, and the dispatcher would observe them on
+//! its next poll regardless. A signal only tells the dispatcher *now* rather than on the next
+//! refetch — drop queued work of a paused scope before it is dispatched, top up a queue the moment
+//! new TODO rows exist, re-read cached `Service` rows after a service setting changed. A lost
+//! notification (a listener reconnecting, the dispatcher down) is therefore harmless: polling
+//! covers it.
+
+use diesel::pg::PgConnection;
+use diesel::result::Error;
+use diesel::sql_types::Text;
+use diesel::*;
+use serde::{Deserialize, Serialize};
+
+/// The Postgres notification channel carrying [`ControlSignal`] payloads.
+pub const CONTROL_CHANNEL: &str = "cortex_control";
+
+/// What changed. Each kind maps to one dispatcher reaction (see `dispatcher::ventilator`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlKind {
+  /// A scope was paused — its TODO/queued tasks are now Blocked; drop them from dispatch queues.
+  Pause,
+  /// A scope was resumed — Blocked tasks are TODO again; refetch.
+  Resume,
+  /// A scope was marked for rerun — fresh TODO tasks; refetch.
+  Rerun,
+  /// A service was activated (or extended) on a corpus — fresh TODO tasks; refetch.
+  Activate,
+  /// A service's settings (lease timeout, renewal cap, kept versions, log parser, pipeline policy)
+  /// changed — re-read the cached `Service` rows.
+  Service,
+}
+
+/// One run-control wake signal, serialized as the JSON `NOTIFY` payload. A `None` corpus/service
+/// widens the scope to *every* corpus/service (the dashboard's global pause/resume; a service
+/// setting change, which spans every corpus).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlSignal {
+  /// What changed.
+  pub kind: ControlKind,
+  /// The affected corpus, or `None` for all corpora.
+  pub corpus_id: Option<i32>,
+  /// The affected service, or `None` for all services.
+  pub service_id: Option<i32>,
+}
+
+impl ControlSignal {
+  /// A signal scoped to one `(corpus, service)` pair.
+  pub fn scoped(kind: ControlKind, corpus_id: i32, service_id: i32) -> Self {
+    ControlSignal {
+      kind,
+      corpus_id: Some(corpus_id),
+      service_id: Some(service_id),
+    }
+  }
+  /// A fleet-wide signal (every corpus, every service).
+  pub fn global(kind: ControlKind) -> Self {
+    ControlSignal {
+      kind,
+      corpus_id: None,
+      service_id: None,
+    }
+  }
+  /// Whether a task of `(corpus_id, service_id)` falls in this signal's scope.
+  pub fn covers(&self, corpus_id: i32, service_id: i32) -> bool {
+    self.corpus_id.is_none_or(|id| id == corpus_id)
+      && self.service_id.is_none_or(|id| id == service_id)
+  }
+}
+
+/// Sends `signal` on [`CONTROL_CHANNEL`]. Postgres delivers a `NOTIFY` only when the surrounding
+/// transaction commits, so issued after (or inside) the mutating transaction a listener can never
+/// observe a signal for a change that rolled back.
+pub fn notify_control(connection: &mut PgConnection, signal: &ControlSignal) -> Result<(), Error> {
+  let payload =
+    serde_json::to_string(signal).map_err(|e| Error::SerializationError(Box::new(e)))?;
+  sql_query("SELECT pg_notify($1, $2)")
+    .bind::<Text, _>(CONTROL_CHANNEL)
+    .bind::<Text, _>(payload)
+    .execute(connection)?;
+  Ok(())
+}
+
+/// Best-effort [`notify_control`] for the mutation paths: the status change has already committed
+/// and polling picks it up anyway, so a failed wake is logged, never surfaced as a failed action.
+pub fn wake_dispatcher(connection: &mut PgConnection, signal: ControlSignal) {
+  if let Err(error) = notify_control(connection, &signal) {
+    tracing::warn!(?signal, %error, "control: dispatcher wake-up NOTIFY failed (polling covers it)");
+  }
+}
+
+/// Subscribes `connection` to [`CONTROL_CHANNEL`]. Use a dedicated connection: the subscription
+/// lives as long as the session does.
+pub fn listen_control(connection: &mut PgConnection) -> Result<(), Error> {
+  sql_query(format!("LISTEN {CONTROL_CHANNEL}")).execute(connection)?;
+  Ok(())
+}
+
+/// Drains the control signals that have arrived on a [`listen_control`]-subscribed `connection`,
+/// **without blocking** (diesel's `notifications_iter` only consumes input already on the socket),
+/// so it is cheap enough to call on every dispatcher loop tick. Payloads that don't parse are
+/// skipped. Returns `Err` if the listener connection itself failed — the caller falls back to
+/// polling.
+pub fn poll_control(connection: &mut PgConnection) -> Result<Vec<ControlSignal>, Error> {
+  let mut signals = Vec::new();
+  for notification in connection.notifications_iter() {
+    let notification = notification?;
+    if notification.channel != CONTROL_CHANNEL {
+      continue;
+    }
+    match serde_json::from_str::<ControlSignal>(&notification.payload) {
+      Ok(signal) => signals.push(signal),
+      Err(_) => tracing::debug!(
+        payload = %notification.payload,
+        "control: ignoring an unparseable control payload"
+      ),
+    }
+  }
+  Ok(signals)
+}
@@ -0,0 +1,420 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Filtering and grouping a corpus's tasks by the facts of their documents (`models::Document`):
+//! submission month, arXiv primary category, source size and file count. The one [`DocumentFilter`]
+//! narrows a sandbox carve, a dataset export and the faceted report alike; [`facet_breakdown`] is
+//! that report — a `(corpus, service)`'s task statuses per month / category / size / file count,
+//! optionally among the tasks that emitted a given message.
+//!
+//! Tasks of entries never inspected (a corpus imported before the `documents` table, until
+//! `cortex index-documents` runs) have no facts: a filter excludes them, a breakdown groups them
+//! as unknown.
+
+use diesel::pg::Pg;
+use diesel::result::Error;
+use diesel::sql_types::{BigInt, Nullable, Text};
+use diesel::*;
+use serde::{Deserialize, Serialize};
+
+use super::sandbox::message_log_table;
+use crate::helpers::TaskStatus;
+
+/// Most groups a breakdown returns (months since 1991 and arXiv categories both stay well below).
+const MAX_FACET_GROUPS: i64 = 1000;
+
+/// How long a breakdown's grouped scan may run before Postgres cancels it.
+pub const BREAKDOWN_QUERY_BUDGET_MS: u32 = 20_000;
+
+/// A predicate over the facts of a task's document. Every set field must hold; an empty filter
+/// matches every task. Serialized flat into a sandbox's `selection`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DocumentFilter {
+  /// arXiv submission month (`yymm`, e.g. `2605`)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub yymm: Option<String>,
+  /// arXiv primary category (`hep-th`, `math.AG`); an archive name alone (`math`) matches all of
+  /// its categories
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub primary_category: Option<String>,
+  /// smallest source size, in bytes
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub min_source_bytes: Option<i64>,
+  /// largest source size, in bytes
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_source_bytes: Option<i64>,
+  /// fewest files in the source
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub min_files: Option<i32>,
+  /// most files in the source
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_files: Option<i32>,
+}
+
+impl DocumentFilter {
+  /// Whether no field is set.
+  pub fn is_empty(&self) -> bool { self == &DocumentFilter::default() }
+
+  /// Blank strings count as unset (an HTML form submits empty fields).
+  pub fn normalized(mut self) -> Self {
+    for field in [&mut self.yymm, &mut self.primary_category] {
+      *field = field
+        .take()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    }
+    self
+  }
+
+  /// Checks the fields, or returns a human-readable reason: a four-digit month, a well-formed
+  /// category, non-negative bounds with the minimum not above the maximum.
+  pub fn validate(&self) -> Result<(), String> {
+    if let Some(yymm) = &self.yymm
+      && !(yymm.len() == 4 && yymm.bytes().all(|byte| byte.is_ascii_digit()))
+    {
+      return Err(format!("month '{yymm}' is not a four-digit yymm"));
+    }
+    if let Some(category) = &self.primary_category
+      && crate::importer::inspect::primary_category(category).as_deref() != Some(category.as_str())
+    {
+      return Err(format!("'{category}' is not an arXiv category"));
+    }
+    let bounds = [
+      (self.min_source_bytes, self.max_source_bytes, "source size"),
+      (
+        self.min_files.map(i64::from),
+        self.max_files.map(i64::from),
+        "file count",
+      ),
+    ];
+    for (min, max, what) in bounds {
+      if min.is_some_and(|min| min < 0) || max.is_some_and(|max| max < 0) {
+        return Err(format!("{what} bounds must not be negative"));
+      }
+      if let (Some(min), Some(max)) = (min, max)
+        && min > max
+      {
+        return Err(format!("the {what} minimum exceeds its maximum"));
+      }
+    }
+    Ok(())
+  }
+
+  /// A compact summary, e.g. `month=2605, category=hep-th, bytes=0..1048576, files=2..` (empty for
+  /// an empty filter).
+  pub fn summary(&self) -> String {
+    let range = |min: Option<i64>, max: Option<i64>| match (min, max) {
+      (None, None) => None,
+      (min, max) => Some(format!(
+        "{}..{}",
+        min.map(|min| min.to_string()).unwrap_or_default(),
+        max.map(|max| max.to_string()).unwrap_or_default()
+      )),
+    };
+    let mut parts = Vec::new();
+    if let Some(yymm) = &self.yymm {
+      parts.push(format!("month={yymm}"));
+    }
+    if let Some(category) = &self.primary_category {
+      parts.push(format!("category={category}"));
+    }
+    if let Some(bytes) = range(self.min_source_bytes, self.max_source_bytes) {
+      parts.push(format!("bytes={bytes}"));
+    }
+    if let Some(files) = range(self.min_files.map(i64::from), self.max_files.map(i64::from)) {
+      parts.push(format!("files={files}"));
+    }
+    parts.join(", ")
+  }
+
+  /// The filter as an SQL condition over the `tasks` row aliased `t` — `AND EXISTS (…)` over its
+  /// document, or an empty string for an empty filter — with its string values as the bind
+  /// placeholders `$first_param…`, returned in order. The numeric bounds are typed integers and are
+  /// inlined; callers [`validate`](Self::validate) first.
+  pub(crate) fn sql_condition(&self, first_param: usize) -> (String, Vec<String>) {
+    if self.is_empty() {
+      return (String::new(), Vec::new());
+    }
+    let mut clauses = Vec::new();
+    let mut binds = Vec::new();
+    if let Some(yymm) = &self.yymm {
+      binds.push(yymm.clone());
+      clauses.push(format!("d.yymm = ${}", first_param + binds.len() - 1));
+    }
+    if let Some(category) = &self.primary_category {
+      binds.push(category.clone());
+      let param = first_param + binds.len() - 1;
+      clauses.push(format!(
+        "(d.primary_category = ${param} OR d.primary_category LIKE ${param} || '.%')"
+      ));
+    }
+    let bounds = [
+      ("d.source_bytes >=", self.min_source_bytes),
+      ("d.source_bytes <=", self.max_source_bytes),
+      ("d.file_count >=", self.min_files.map(i64::from)),
+      ("d.file_count <=", self.max_files.map(i64::from)),
+    ];
+    for (comparison, bound) in bounds {
+      if let Some(bound) = bound {
+        clauses.push(format!("{comparison} {bound}"));
+      }
+    }
+    (
+      format!(
+        " AND EXISTS (SELECT 1 FROM documents d WHERE d.corpus_id = t.corpus_id \
+         AND d.entry = t.entry AND {})",
+        clauses.join(" AND ")
+      ),
+      binds,
+    )
+  }
+}
+
+/// A document fact to group tasks by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentFacet {
+  /// the arXiv submission month
+  Month,
+  /// the arXiv primary category
+  Category,
+  /// the source size, in decade buckets
+  Size,
+  /// the number of source files, in buckets
+  Files,
+}
+
+impl DocumentFacet {
+  /// Parses the query-string form (`month` / `category` / `size` / `files`).
+  pub fn from_key(key: &str) -> Option<Self> {
+    match key {
+      "month" => Some(DocumentFacet::Month),
+      "category" => Some(DocumentFacet::Category),
+      "size" => Some(DocumentFacet::Size),
+      "files" => Some(DocumentFacet::Files),
+      _ => None,
+    }
+  }
+
+  /// The query-string form.
+  pub fn to_key(self) -> &'static str {
+    match self {
+      DocumentFacet::Month => "month",
+      DocumentFacet::Category => "category",
+      DocumentFacet::Size => "size",
+      DocumentFacet::Files => "files",
+    }
+  }
+
+  /// The SQL group label over the document aliased `d` (NULL when unknown), and the key groups
+  /// are ordered by: months chronologically (arXiv began in 1991, so `91`–`99` precede `00`),
+  /// buckets from small to large.
+  fn sql(self) -> (&'static str, &'static str) {
+    match self {
+      DocumentFacet::Month => (
+        "d.yymm",
+        "CASE WHEN d.yymm >= '91' THEN '19' ELSE '20' END || d.yymm",
+      ),
+      DocumentFacet::Category => ("d.primary_category", "d.primary_category"),
+      DocumentFacet::Size => (
+        "CASE WHEN d.source_bytes IS NULL THEN NULL \
+         WHEN d.source_bytes < 10240 THEN '< 10 KiB' \
+         WHEN d.source_bytes < 102400 THEN '10–100 KiB' \
+         WHEN d.source_bytes < 1048576 THEN '100 KiB–1 MiB' \
+         WHEN d.source_bytes < 10485760 THEN '1–10 MiB' ELSE '≥ 10 MiB' END",
+        "lpad(width_bucket(d.source_bytes, ARRAY[10240, 102400, 1048576, 10485760]::bigint[])\
+         ::text, 2, '0')",
+      ),
+      DocumentFacet::Files => (
+        "CASE WHEN d.file_count IS NULL THEN NULL \
+         WHEN d.file_count <= 1 THEN '1' \
+         WHEN d.file_count <= 5 THEN '2–5' \
+         WHEN d.file_count <= 20 THEN '6–20' \
+         WHEN d.file_count <= 100 THEN '21–100' ELSE '> 100' END",
+        "lpad(width_bucket(d.file_count, ARRAY[2, 6, 21, 101])::text, 2, '0')",
+      ),
+    }
+  }
+}
+
+/// Narrows a breakdown to the tasks that emitted a message: a message severity (`info` |
+/// `warning` | `error` | `fatal` | `invalid`), optionally a category, and a `what` within it.
+#[derive(Clone, Debug, Default)]
+pub struct MessageScope {
+  /// the message severity
+  pub severity: String,
+  /// optional category within it
+  pub category: Option<String>,
+  /// optional `what` within the category (needs `category`)
+  pub what: Option<String>,
+}
+
+/// One group of a [`facet_breakdown`]: its label and its tasks' statuses.
+#[derive(QueryableByName, Clone, Debug, PartialEq, Eq)]
+pub struct FacetRow {
+  /// the group label (`None` for tasks whose document fact is unknown)
+  #[diesel(sql_type = Nullable<Text>)]
+  pub facet: Option<String>,
+  /// tasks in the group
+  #[diesel(sql_type = BigInt)]
+  pub tasks: i64,
+  /// not yet converted (TODO or leased)
+  #[diesel(sql_type = BigInt)]
+  pub todo: i64,
+  /// converted without a problem
+  #[diesel(sql_type = BigInt)]
+  pub no_problem: i64,
+  /// converted with warnings
+  #[diesel(sql_type = BigInt)]
+  pub warning: i64,
+  /// converted with errors
+  #[diesel(sql_type = BigInt)]
+  pub error: i64,
+  /// failed fatally
+  #[diesel(sql_type = BigInt)]
+  pub fatal: i64,
+  /// invalid input
+  #[diesel(sql_type = BigInt)]
+  pub invalid: i64,
+  /// paused, quarantined or awaiting an upstream service
+  #[diesel(sql_type = BigInt)]
+  pub blocked: i64,
+}
+
+/// A `(corpus, service)`'s task statuses grouped by a document fact, over the tasks matching
+/// `filter` (and, with `message`, that emitted such a message) — live, one grouped scan of the
+/// pair's tasks, under [`BREAKDOWN_QUERY_BUDGET_MS`]. `Err(QueryBuilderError)` for an invalid
+/// filter or message scope.
+pub fn facet_breakdown(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service_id: i32,
+  facet: DocumentFacet,
+  filter: &DocumentFilter,
+  message: Option<&MessageScope>,
+) -> Result<Vec<FacetRow>, Error> {
+  filter
+    .validate()
+    .map_err(|reason| Error::QueryBuilderError(reason.into()))?;
+  let mut binds = Vec::new();
+  let message_clause = match message {
+    None => String::new(),
+    Some(scope) => {
+      let table = message_log_table(&scope.severity).ok_or_else(|| {
+        Error::QueryBuilderError(format!("unknown message severity '{}'", scope.severity).into())
+      })?;
+      let mut clause = format!(" AND EXISTS (SELECT 1 FROM {table} l WHERE l.task_id = t.id");
+      if let Some(category) = &scope.category {
+        binds.push(category.clone());
+        clause.push_str(&format!(" AND l.category = ${}", binds.len()));
+      }
+      if let Some(what) = &scope.what {
+        if scope.category.is_none() {
+          return Err(Error::QueryBuilderError("what needs a category".into()));
+        }
+        binds.push(what.clone());
+        clause.push_str(&format!(" AND l.what = ${}", binds.len()));
+      }
+      clause.push(')');
+      clause
+    },
+  };
+  let (filter_clause, filter_binds) = filter.sql_condition(binds.len() + 1);
+  binds.extend(filter_binds);
+  let (label, order) = facet.sql();
+  let status = |status: TaskStatus| status.raw();
+  let query = format!(
+    "SELECT facet, count(*) AS tasks,
+            count(*) FILTER (WHERE status >= {todo}) AS todo,
+            count(*) FILTER (WHERE status = {no_problem}) AS no_problem,
+            count(*) FILTER (WHERE status = {warning}) AS warning,
+            count(*) FILTER (WHERE status = {error}) AS error,
+            count(*) FILTER (WHERE status = {fatal}) AS fatal,
+            count(*) FILTER (WHERE status = {invalid}) AS invalid,
+            count(*) FILTER (WHERE status < {invalid}) AS blocked
+     FROM (SELECT {label} AS facet, {order} AS facet_order, t.status
+           FROM tasks t LEFT JOIN documents d ON d.corpus_id = t.corpus_id AND d.entry = t.entry
+           WHERE t.corpus_id = {corpus_id} AND t.service_id = {service_id}\
+           {message_clause}{filter_clause}) grouped
+     GROUP BY facet, facet_order
+     ORDER BY facet IS NULL, facet_order
+     LIMIT {MAX_FACET_GROUPS}",
+    todo = status(TaskStatus::TODO),
+    no_problem = status(TaskStatus::NoProblem),
+    warning = status(TaskStatus::Warning),
+    error = status(TaskStatus::Error),
+    fatal = status(TaskStatus::Fatal),
+    invalid = status(TaskStatus::Invalid),
+  );
+  let mut query = sql_query(query).into_boxed::<Pg>();
+  for bind in binds {
+    query = query.bind::<Text, _>(bind);
+  }
+  connection.transaction(|conn| {
+    sql_query(format!(
+      "SET LOCAL statement_timeout = {BREAKDOWN_QUERY_BUDGET_MS}"
+    ))
+    .execute(conn)?;
+    query.load(conn)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn filters_validate_and_render_placeholders_in_order() {
+    let filter = DocumentFilter {
+      yymm: Some("2605".into()),
+      primary_category: Some("math".into()),
+      min_source_bytes: Some(1024),
+      max_files: Some(3),
+      ..DocumentFilter::default()
+    };
+    assert!(filter.validate().is_ok());
+    assert_eq!(
+      filter.summary(),
+      "month=2605, category=math, bytes=1024.., files=..3"
+    );
+    let (sql, binds) = filter.sql_condition(3);
+    assert_eq!(binds, vec!["2605".to_string(), "math".to_string()]);
+    assert!(sql.contains("d.yymm = $3"), "{sql}");
+    assert!(sql.contains("d.primary_category LIKE $4 || '.%'"), "{sql}");
+    assert!(sql.contains("d.source_bytes >= 1024") && sql.contains("d.file_count <= 3"));
+    assert_eq!(
+      DocumentFilter::default().sql_condition(1),
+      (String::new(), Vec::new())
+    );
+
+    let bad = |filter: DocumentFilter| filter.validate().is_err();
+    assert!(bad(DocumentFilter {
+      yymm: Some("26-05".into()),
+      ..DocumentFilter::default()
+    }));
+    assert!(bad(DocumentFilter {
+      primary_category: Some("hep-th' OR 1=1".into()),
+      ..DocumentFilter::default()
+    }));
+    assert!(bad(DocumentFilter {
+      min_files: Some(5),
+      max_files: Some(2),
+      ..DocumentFilter::default()
+    }));
+  }
+
+  #[test]
+  fn blank_form_fields_are_unset() {
+    let filter = DocumentFilter {
+      yymm: Some("  ".into()),
+      primary_category: Some(" hep-th ".into()),
+      ..DocumentFilter::default()
+    }
+    .normalized();
+    assert_eq!(filter.yymm, None);
+    assert_eq!(filter.primary_category.as_deref(), Some("hep-th"));
+  }
+}
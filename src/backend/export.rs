@@ -20,13 +20,17 @@
 
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use diesel::PgConnection;
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text};
 
+use super::documents::DocumentFilter;
 use crate::helpers::{TaskStatus, result_archive_path};
 use crate::models::{Corpus, Service};
+use crate::storage::Storage;
 
 /// How the per-paper HTML files are bucketed into output archives — the sole difference between the
 /// two scripts this replaces.
@@ -38,6 +42,9 @@ pub enum GroupBy {
   /// One archive per severity (`<corpus>-<severity>.zip`, papers at
   /// `<severity>/<yymm>/<paper>.html`) — the `bundle-html-dataset-by-severity.sh` layout.
   Severity,
+  /// One archive per arXiv primary category (`<corpus>-<category>.zip`, papers at
+  /// `<category>/<yymm>/<paper>.html`); papers with no recorded category go to `uncategorized`.
+  Category,
 }
 
 impl GroupBy {
@@ -46,11 +53,24 @@ impl GroupBy {
     match key {
       "month" => Some(GroupBy::Month),
       "severity" => Some(GroupBy::Severity),
+      "category" => Some(GroupBy::Category),
       _ => None,
     }
   }
+
+  /// The CLI/string form.
+  pub fn to_key(self) -> &'static str {
+    match self {
+      GroupBy::Month => "month",
+      GroupBy::Severity => "severity",
+      GroupBy::Category => "category",
+    }
+  }
 }
 
+/// The archive key of the papers whose document has no primary category, in [`GroupBy::Category`].
+const UNCATEGORIZED: &str = "uncategorized";
+
 /// One produced dataset archive and how many papers it holds.
 #[derive(Debug, serde::Serialize)]
 pub struct DatasetArchive {
@@ -68,8 +88,11 @@ pub struct DatasetExportOutcome {
   pub corpus: String,
   /// service whose HTML output was bundled
   pub service: String,
-  /// `month` or `severity`
+  /// `month`, `severity` or `category`
   pub group_by: String,
+  /// the document filter the papers were narrowed by (see `DocumentFilter::summary`), if any
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub documents: Option<String>,
   /// per-archive size cap (MB) if chunking was enabled, else `None` (one archive per bucket).
   /// Recorded so a resumer uses the same limit (boundaries are limit-dependent).
   pub max_archive_mb: Option<u64>,
@@ -102,26 +125,31 @@ fn zip_options() -> zip::write::FileOptions<'static, ()> {
 
 /// Export a corpus/service's converted HTML into ZIP archives bucketed by [`GroupBy`].
 ///
-/// Reads existing result archives off the shared filesystem (no conversion is run); the DB is only
-/// queried for the matching task `entry` paths. `progress` is called with human-readable milestone
-/// lines (the CLI prints them; a future web/job caller can stream them). Resumable: an archive
-/// whose `.zip` already exists in `out_dir` is left untouched (matching the scripts' resume
-/// behaviour).
+/// Reads existing result archives through `storage` (no conversion is run); the DB is only queried
+/// for the matching task `entry` paths. The dataset itself is written to the local `out_dir`.
+/// `progress` is called with human-readable milestone lines (the CLI prints them; a future web/job
+/// caller can stream them). Resumable: an archive whose `.zip` already exists in `out_dir` is left
+/// untouched (matching the scripts' resume behaviour).
 ///
 /// Severities are taken in the given order; the canonical [`TaskStatus::to_key`] spelling is used
-/// throughout (no `no-problem`/`no_problem` ambiguity).
+/// throughout (no `no-problem`/`no_problem` ambiguity). A non-empty `documents` filter keeps only
+/// the papers whose document facts match it.
 #[allow(clippy::too_many_arguments)] // config-heavy export knobs; a params struct is ceremony here
 pub fn export_html_dataset(
   connection: &mut PgConnection,
   corpus: &Corpus,
   service: &Service,
   severities: &[TaskStatus],
+  documents: &DocumentFilter,
   group_by: GroupBy,
   max_archive_mb: Option<u64>,
   out_dir: &PathBuf,
+  storage: &dyn Storage,
   mut progress: impl FnMut(&str),
 ) -> Result<DatasetExportOutcome, String> {
+  documents.validate()?;
   fs::create_dir_all(out_dir).map_err(|e| format!("cannot create {}: {e}", out_dir.display()))?;
+  let condition = documents.sql_condition(1);
 
   progress("Streaming HTML dataset export...");
   // Stream the matching papers straight into per-archive ZIPs, holding at most ONE archive open at
@@ -130,7 +158,8 @@ pub fn export_html_dataset(
   // entries contiguous, which is the streamer's one requirement.
   // MB (binary) → bytes; `saturating_mul` so an absurd value can't overflow the cap.
   let max_bytes = max_archive_mb.map(|mb| mb.saturating_mul(1024 * 1024));
-  let mut streamer = ArchiveStreamer::new(out_dir, corpus, &service.name, group_by, max_bytes);
+  let mut streamer =
+    ArchiveStreamer::new(out_dir, corpus, &service.name, group_by, max_bytes, storage);
 
   match group_by {
     // Month: ONE keyset-paginated pass over *all* requested severities, ordered by `entry`. Since
@@ -140,7 +169,14 @@ pub fn export_html_dataset(
       let raws: Vec<i32> = severities.iter().map(|s| s.raw()).collect();
       let mut after: Option<String> = None;
       loop {
-        let page = fetch_entry_page(connection, corpus.id, service.id, &raws, after.as_deref())?;
+        let page = fetch_entry_page(
+          connection,
+          corpus.id,
+          service.id,
+          &raws,
+          &condition,
+          after.as_deref(),
+        )?;
         let full = page.len() as i64 == EXPORT_PAGE_SIZE;
         for entry in &page {
           streamer.feed(entry, None, &mut progress)?;
@@ -159,7 +195,57 @@ pub fn export_html_dataset(
         let raws = [status.raw()];
         let mut after: Option<String> = None;
         loop {
-          let page = fetch_entry_page(connection, corpus.id, service.id, &raws, after.as_deref())?;
+          let page = fetch_entry_page(
+            connection,
+            corpus.id,
+            service.id,
+            &raws,
+            &condition,
+            after.as_deref(),
+          )?;
+          let full = page.len() as i64 == EXPORT_PAGE_SIZE;
+          for entry in &page {
+            streamer.feed(entry, Some(&key), &mut progress)?;
+          }
+          after = page.into_iter().next_back();
+          if !full {
+            break;
+          }
+        }
+      }
+    },
+    // Category: one archive per primary category, processed a category at a time like severities
+    // (the filtered pass *is* the contiguity) — the categories present come from one grouped scan.
+    GroupBy::Category => {
+      let raws: Vec<i32> = severities.iter().map(|s| s.raw()).collect();
+      for category in export_categories(connection, corpus.id, service.id, &raws, &condition)? {
+        let (mut clause, mut binds) = condition.clone();
+        match &category {
+          Some(category) => {
+            binds.push(category.clone());
+            clause.push_str(&format!(
+              " AND EXISTS (SELECT 1 FROM documents dc WHERE dc.corpus_id = t.corpus_id \
+               AND dc.entry = t.entry AND dc.primary_category = ${})",
+              binds.len()
+            ));
+          },
+          None => clause.push_str(
+            " AND NOT EXISTS (SELECT 1 FROM documents dc WHERE dc.corpus_id = t.corpus_id \
+             AND dc.entry = t.entry AND dc.primary_category IS NOT NULL)",
+          ),
+        }
+        let scoped = (clause, binds);
+        let key = category.unwrap_or_else(|| UNCATEGORIZED.to_string());
+        let mut after: Option<String> = None;
+        loop {
+          let page = fetch_entry_page(
+            connection,
+            corpus.id,
+            service.id,
+            &raws,
+            &scoped,
+            after.as_deref(),
+          )?;
           let full = page.len() as i64 == EXPORT_PAGE_SIZE;
           for entry in &page {
             streamer.feed(entry, Some(&key), &mut progress)?;
@@ -178,11 +264,8 @@ pub fn export_html_dataset(
   let outcome = DatasetExportOutcome {
     corpus: corpus.name.clone(),
     service: service.name.clone(),
-    group_by: match group_by {
-      GroupBy::Month => "month",
-      GroupBy::Severity => "severity",
-    }
-    .to_string(),
+    group_by: group_by.to_key().to_string(),
+    documents: Some(documents.summary()).filter(|summary| !summary.is_empty()),
     max_archive_mb,
     severities: severities.iter().map(|s| s.to_key()).collect(),
     generated_at: chrono::Utc::now().to_rfc3339(),
@@ -213,25 +296,77 @@ fn fetch_entry_page(
   corpus_id: i32,
   service_id: i32,
   status_raws: &[i32],
+  (condition, binds): &(String, Vec<String>),
   after: Option<&str>,
 ) -> Result<Vec<String>, String> {
-  use crate::schema::tasks::dsl as t;
-  let mut query = t::tasks
-    .filter(t::corpus_id.eq(corpus_id))
-    .filter(t::service_id.eq(service_id))
-    .filter(t::status.eq_any(status_raws.to_vec()))
-    .select(t::entry)
-    .order(t::entry.asc())
-    .into_boxed();
-  if let Some(cursor) = after {
-    query = query.filter(t::entry.gt(cursor.to_string()));
+  // Raw SQL so the document condition (an `EXISTS` over `documents`, its strings bound from `$1`)
+  // composes; the ids and statuses are typed integers, inlined.
+  let statuses = status_raws
+    .iter()
+    .map(i32::to_string)
+    .collect::<Vec<_>>()
+    .join(", ");
+  let cursor = match after {
+    Some(_) => format!(" AND t.entry > ${}", binds.len() + 1),
+    None => String::new(),
+  };
+  let mut query = diesel::sql_query(format!(
+    "SELECT t.entry FROM tasks t \
+     WHERE t.corpus_id = {corpus_id} AND t.service_id = {service_id} AND t.status IN ({statuses})\
+     {condition}{cursor} ORDER BY t.entry LIMIT {EXPORT_PAGE_SIZE}"
+  ))
+  .into_boxed::<Pg>();
+  for bind in binds.iter().map(String::as_str).chain(after) {
+    query = query.bind::<Text, _>(bind.to_string());
   }
   query
-    .limit(EXPORT_PAGE_SIZE)
-    .load(connection)
+    .load::<EntryRow>(connection)
+    .map(|rows| rows.into_iter().map(|row| row.entry).collect())
     .map_err(|e| format!("querying export tasks failed: {e}"))
 }
 
+/// The distinct primary categories (`None` = no recorded category) of the matching papers, in name
+/// order with the uncategorized last — the archives of a [`GroupBy::Category`] export.
+fn export_categories(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service_id: i32,
+  status_raws: &[i32],
+  (condition, binds): &(String, Vec<String>),
+) -> Result<Vec<Option<String>>, String> {
+  let statuses = status_raws
+    .iter()
+    .map(i32::to_string)
+    .collect::<Vec<_>>()
+    .join(", ");
+  let mut query = diesel::sql_query(format!(
+    "SELECT DISTINCT d.primary_category AS category FROM tasks t \
+     LEFT JOIN documents d ON d.corpus_id = t.corpus_id AND d.entry = t.entry \
+     WHERE t.corpus_id = {corpus_id} AND t.service_id = {service_id} AND t.status IN ({statuses})\
+     {condition} ORDER BY category NULLS LAST"
+  ))
+  .into_boxed::<Pg>();
+  for bind in binds {
+    query = query.bind::<Text, _>(bind.clone());
+  }
+  query
+    .load::<CategoryRow>(connection)
+    .map(|rows| rows.into_iter().map(|row| row.category).collect())
+    .map_err(|e| format!("querying export categories failed: {e}"))
+}
+
+#[derive(QueryableByName)]
+struct EntryRow {
+  #[diesel(sql_type = Text)]
+  entry: String,
+}
+
+#[derive(QueryableByName)]
+struct CategoryRow {
+  #[diesel(sql_type = Nullable<Text>)]
+  category: Option<String>,
+}
+
 /// The one archive currently being written by the [`ArchiveStreamer`].
 struct OpenArchive {
   /// archive key (a `yymm` in month mode, a severity in severity mode)
@@ -257,6 +392,8 @@ struct ArchiveStreamer<'a> {
   corpus: &'a Corpus,
   service_name: &'a str,
   group_by: GroupBy,
+  /// where the result archives are read from
+  storage: &'a dyn Storage,
   /// Optional per-archive byte cap (configurable chunking): when `Some`, a bucket that exceeds it
   /// rolls into numbered chunks `<corpus>-<key>-NNN.zip`. Measured on **uncompressed** HTML bytes
   /// — deterministic, so resume re-derives identical boundaries (the published `.zip` is
@@ -284,12 +421,14 @@ impl<'a> ArchiveStreamer<'a> {
     service_name: &'a str,
     group_by: GroupBy,
     max_bytes: Option<u64>,
+    storage: &'a dyn Storage,
   ) -> Self {
     ArchiveStreamer {
       out_dir,
       corpus,
       service_name,
       group_by,
+      storage,
       max_bytes,
       cur_base_key: None,
       cur_chunk: 1,
@@ -365,9 +504,9 @@ impl<'a> ArchiveStreamer<'a> {
     // in.
     let internal = match self.group_by {
       GroupBy::Month => format!("{yymm}/{paper}.html"),
-      GroupBy::Severity => format!("{base_key}/{yymm}/{paper}.html"),
+      GroupBy::Severity | GroupBy::Category => format!("{base_key}/{yymm}/{paper}.html"),
     };
-    let html = match extract_main_html(&result_zip, &paper) {
+    let html = match extract_main_html(self.storage, &result_zip, &paper) {
       Some(bytes) => bytes,
       None => {
         // Only count an HTML miss when we'd actually be writing (not while resume-skipping a
@@ -484,8 +623,8 @@ impl<'a> ArchiveStreamer<'a> {
 /// any other root-level `*.html`; returns `None` if the archive is unreadable or HTML-free (the
 /// caller counts it as skipped). HTML-only by design — the arXMLiv datasets bundle the document,
 /// not its assets, exactly as the scripts did (`unzip *.html`).
-fn extract_main_html(result_zip: &PathBuf, paper: &str) -> Option<Vec<u8>> {
-  let file = File::open(result_zip).ok()?;
+fn extract_main_html(storage: &dyn Storage, result_zip: &Path, paper: &str) -> Option<Vec<u8>> {
+  let file = storage.open(result_zip).ok()?;
   let mut archive = zip::ZipArchive::new(file).ok()?;
   // Find the best HTML index first (an immutable scan), then read it — `by_index` needs `&mut`.
   let preferred = format!("{paper}.html");
@@ -512,6 +651,7 @@ fn extract_main_html(result_zip: &PathBuf, paper: &str) -> Option<Vec<u8>> {
 #[cfg(test)]
 mod tests {
   use super::{GroupBy, extract_main_html};
+  use crate::storage::{LocalStorage, MemoryStorage};
   use std::io::Write;
 
   fn write_zip(path: &std::path::Path, files: &[(&str, &str)]) {
@@ -548,7 +688,7 @@ mod tests {
       ],
     );
     assert_eq!(
-      extract_main_html(&z1, "0801.1234").as_deref(),
+      extract_main_html(&LocalStorage, &z1, "0801.1234").as_deref(),
       Some(b"MAIN".as_slice()),
       "the paper-named root html wins"
     );
@@ -557,7 +697,7 @@ mod tests {
     let z2 = dir.join("fallback.zip");
     write_zip(&z2, &[("index.html", "ROOT"), ("sub/deep.html", "DEEP")]);
     assert_eq!(
-      extract_main_html(&z2, "9999.0001").as_deref(),
+      extract_main_html(&LocalStorage, &z2, "9999.0001").as_deref(),
       Some(b"ROOT".as_slice()),
       "a root-level html is used when the paper-named one is absent"
     );
@@ -565,11 +705,29 @@ mod tests {
     // No root-level html at all ⇒ skipped (None), never a panic.
     let z3 = dir.join("htmlless.zip");
     write_zip(&z3, &[("sub/only.html", "DEEP"), ("cortex.log", "x")]);
-    assert!(extract_main_html(&z3, "p").is_none());
+    assert!(extract_main_html(&LocalStorage, &z3, "p").is_none());
 
     // A non-existent / unreadable archive ⇒ None, not an error.
-    assert!(extract_main_html(&dir.join("nope.zip"), "p").is_none());
+    assert!(extract_main_html(&LocalStorage, &dir.join("nope.zip"), "p").is_none());
 
     std::fs::remove_dir_all(&dir).ok();
   }
+
+  #[test]
+  fn extract_main_html_reads_through_the_storage_backend() {
+    // The archive exists only as an object of the storage backend, not on disk.
+    let storage = MemoryStorage::default();
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let opts: zip::write::FileOptions<'_, ()> = zip::write::FileOptions::default();
+    zip.start_file("2401.00001.html", opts).unwrap();
+    zip.write_all(b"MAIN").unwrap();
+    let bytes = zip.finish().unwrap().into_inner();
+    let result_zip = std::path::Path::new("/objects/2401/2401.00001/tex_to_html.zip");
+    storage.insert(result_zip, bytes);
+    assert_eq!(
+      extract_main_html(&storage, result_zip, "2401.00001").as_deref(),
+      Some(b"MAIN".as_slice())
+    );
+    assert!(extract_main_html(&LocalStorage, result_zip, "2401.00001").is_none());
+  }
 }
@@ -0,0 +1,577 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Result archive integrity — `cortex fsck`. Cross-checks a `(corpus, service)` pair's task store
+//! against its result archives in storage:
+//!
+//! * a **missing** archive: a task whose recorded archive, or whose successful result, is gone;
+//! * a **checksum mismatch**: the archive no longer has the size and SHA-256 the sink recorded at
+//!   commit (`result_archives`), i.e. it was truncated or rewritten;
+//! * **no `cortex.log`**: the archive is not a readable zip, or has no `cortex.log` to report from;
+//! * an **orphan**: a `<service>.zip` or `<service>.sandbox-<id>.zip` in one of the corpus's entry
+//!   directories whose task — or whole sandbox — was deleted.
+//!
+//! Archives written before checksums were recorded are still checked for presence and a
+//! `cortex.log`, and counted as unrecorded. The scan is read-only unless asked to repair: damaged
+//! tasks are reset to TODO (rerun-style, [`super::mark::mark_damaged`]) and orphans removed; every
+//! repair is recorded in the audit log.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::control::{ControlKind, ControlSignal, wake_dispatcher};
+use crate::helpers::{TaskStatus, result_archive_path};
+use crate::models::{Corpus, NewAuditEntry, ResultArchive, Service};
+use crate::schema::{corpora, result_archives, tasks};
+use crate::storage::{ReadSeek, Storage, StoredObject};
+
+/// Tasks (and entries) examined per page of the scan.
+const FSCK_PAGE_SIZE: i64 = 5_000;
+/// At most this many paths are listed per finding; the counts are always complete.
+const FSCK_SAMPLE_CAP: usize = 1_000;
+
+/// What `fsck` may repair. The default repairs nothing — a dry run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FsckOptions {
+  /// reset the tasks with a missing or damaged archive to TODO
+  pub reset_damaged: bool,
+  /// remove the orphan archives
+  pub remove_orphans: bool,
+}
+
+impl FsckOptions {
+  /// Whether this is a read-only scan.
+  pub fn dry_run(&self) -> bool { !self.reset_damaged && !self.remove_orphans }
+}
+
+/// An archive with no task behind it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OrphanArchive {
+  /// the archive's storage path
+  pub path: String,
+  /// its size, in bytes
+  pub bytes: u64,
+}
+
+/// The findings of an [`fsck_archives`] scan, and what was repaired.
+#[derive(Debug, Default, Serialize)]
+pub struct FsckReport {
+  /// corpus scanned
+  pub corpus: String,
+  /// service whose archives were checked
+  pub service: String,
+  /// `true` if nothing was changed
+  pub dry_run: bool,
+  /// finished tasks examined
+  pub checked: usize,
+  /// archives present and intact but written before checksums were recorded
+  pub unrecorded: usize,
+  /// tasks whose archive is missing (paths, capped)
+  pub missing: Vec<String>,
+  /// total tasks with a missing archive
+  pub missing_count: usize,
+  /// archives whose size or SHA-256 differs from the recorded one (paths, capped)
+  pub checksum_mismatch: Vec<String>,
+  /// total checksum mismatches
+  pub checksum_mismatch_count: usize,
+  /// archives that are unreadable or hold no `cortex.log` (paths, capped)
+  pub no_cortex_log: Vec<String>,
+  /// total archives without a `cortex.log`
+  pub no_cortex_log_count: usize,
+  /// archives with no task behind them (capped)
+  pub orphans: Vec<OrphanArchive>,
+  /// total orphan archives
+  pub orphan_count: usize,
+  /// total size of the orphan archives, in bytes
+  pub orphan_bytes: u64,
+  /// damaged tasks reset to TODO
+  pub reset: usize,
+  /// orphan archives removed
+  pub removed: usize,
+}
+
+impl FsckReport {
+  /// Tasks with a missing or damaged archive.
+  pub fn damaged(&self) -> usize {
+    self.missing_count + self.checksum_mismatch_count + self.no_cortex_log_count
+  }
+
+  /// Whether the scan found nothing wrong.
+  pub fn is_clean(&self) -> bool { self.damaged() == 0 && self.orphan_count == 0 }
+}
+
+/// Pushes `item` onto a capped sample list and bumps its count.
+pub(super) fn note<T>(sample: &mut Vec<T>, count: &mut usize, item: T) {
+  *count += 1;
+  if sample.len() < FSCK_SAMPLE_CAP {
+    sample.push(item);
+  }
+}
+
+/// What is wrong with one stored archive.
+#[derive(Debug, PartialEq, Eq)]
+enum Damage {
+  Missing,
+  ChecksumMismatch,
+  NoCortexLog,
+}
+
+/// Checks the archive at `path` against its `record`, if any. `Ok(None)` is an intact archive;
+/// `Err` a storage failure other than a missing object (which aborts the scan instead of resetting
+/// tasks over a flaky mount).
+fn check_archive(
+  storage: &dyn Storage,
+  path: &Path,
+  record: Option<&ResultArchive>,
+) -> io::Result<Option<Damage>> {
+  let mut reader = match storage.open(path) {
+    Ok(reader) => reader,
+    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Some(Damage::Missing)),
+    Err(e) => return Err(e),
+  };
+  if let Some(record) = record {
+    let (size, sha256) = digest(&mut reader)?;
+    if size as i64 != record.size_bytes || sha256 != record.sha256 {
+      return Ok(Some(Damage::ChecksumMismatch));
+    }
+    reader.seek(SeekFrom::Start(0))?;
+  }
+  Ok(if has_cortex_log(reader) {
+    None
+  } else {
+    Some(Damage::NoCortexLog)
+  })
+}
+
+/// The size and hex SHA-256 of a stream, read to its end.
+fn digest(reader: &mut impl Read) -> io::Result<(u64, String)> {
+  let mut hasher = Sha256::new();
+  let mut buffer = [0u8; 64 * 1024];
+  let mut size = 0u64;
+  loop {
+    let read = reader.read(&mut buffer)?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buffer[..read]);
+    size += read as u64;
+  }
+  let sha256 = hasher
+    .finalize()
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect();
+  Ok((size, sha256))
+}
+
+/// Whether `reader` is a zip holding a `cortex.log`.
+fn has_cortex_log(reader: Box<dyn ReadSeek>) -> bool {
+  zip::ZipArchive::new(reader)
+    .map(|mut archive| archive.by_name("cortex.log").is_ok())
+    .unwrap_or(false)
+}
+
+/// The owner a file named `file_name` would be the result archive of, for service `service_name`:
+/// `Some(None)` for the plain `<service>.zip`, `Some(Some(id))` for `<service>.sandbox-<id>.zip`,
+/// `None` for any other file.
+pub(super) fn archive_owner(file_name: &str, service_name: &str) -> Option<Option<i32>> {
+  let stem = file_name.strip_suffix(".zip")?.strip_prefix(service_name)?;
+  if stem.is_empty() {
+    return Some(None);
+  }
+  let id = stem.strip_prefix(".sandbox-")?;
+  if id.is_empty() || !id.bytes().all(|byte| byte.is_ascii_digit()) {
+    return None;
+  }
+  id.parse().ok().map(Some)
+}
+
+/// Scans the result archives of `service` on `corpus`, reporting missing, damaged and orphan
+/// archives, and — when `options` asks — resets the damaged tasks to TODO and removes the orphans,
+/// recording the repair as `actor` in the audit log. `progress` is called with human-readable
+/// milestone lines.
+pub fn fsck_archives(
+  connection: &mut PgConnection,
+  corpus: &Corpus,
+  service: &Service,
+  storage: &dyn Storage,
+  options: FsckOptions,
+  actor: &str,
+  mut progress: impl FnMut(&str),
+) -> Result<FsckReport, String> {
+  let mut report = FsckReport {
+    corpus: corpus.name.clone(),
+    service: service.name.clone(),
+    dry_run: options.dry_run(),
+    ..FsckReport::default()
+  };
+  let damaged = verify_tasks(
+    connection,
+    corpus,
+    service,
+    storage,
+    &mut report,
+    &mut progress,
+  )?;
+  let orphans = find_orphans(
+    connection,
+    corpus,
+    service,
+    storage,
+    &mut report,
+    &mut progress,
+  )?;
+
+  if options.reset_damaged && !damaged.is_empty() {
+    report.reset = super::mark::mark_damaged(connection, corpus, service, &damaged)
+      .map_err(|e| format!("resetting damaged tasks failed: {e}"))?;
+    wake_dispatcher(
+      connection,
+      ControlSignal::scoped(ControlKind::Rerun, corpus.id, service.id),
+    );
+    progress(&format!("reset {} damaged tasks to TODO", report.reset));
+  }
+  if options.remove_orphans {
+    for orphan in &orphans {
+      storage
+        .remove(orphan)
+        .map_err(|e| format!("removing {}: {e}", orphan.display()))?;
+      report.removed += 1;
+    }
+    progress(&format!("removed {} orphan archives", report.removed));
+  }
+  if report.reset > 0 || report.removed > 0 {
+    let entry = NewAuditEntry::new(
+      actor,
+      "fsck_repair",
+      format!("{}/{}", corpus.name, service.name),
+    )
+    .outcome("ok")
+    .details(format!(
+      "reset {} damaged tasks, removed {} orphan archives ({} bytes)",
+      report.reset, report.removed, report.orphan_bytes
+    ));
+    if let Err(error) = entry.record(connection) {
+      tracing::warn!(%error, "fsck: failed to record the audit entry");
+    }
+  }
+  Ok(report)
+}
+
+/// Checks the archive of every finished task; returns the ids of the damaged ones.
+fn verify_tasks(
+  connection: &mut PgConnection,
+  corpus: &Corpus,
+  service: &Service,
+  storage: &dyn Storage,
+  report: &mut FsckReport,
+  progress: &mut impl FnMut(&str),
+) -> Result<Vec<i64>, String> {
+  let mut damaged = Vec::new();
+  let mut last_id = 0i64;
+  loop {
+    let page: Vec<(i64, String, i32)> = tasks::table
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(tasks::service_id.eq(service.id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
+      .filter(tasks::id.gt(last_id))
+      .order(tasks::id)
+      .limit(FSCK_PAGE_SIZE)
+      .select((tasks::id, tasks::entry, tasks::status))
+      .load(connection)
+      .map_err(|e| format!("listing tasks failed: {e}"))?;
+    let Some((page_last, ..)) = page.last() else {
+      break;
+    };
+    last_id = *page_last;
+    let ids: Vec<i64> = page.iter().map(|(task_id, ..)| *task_id).collect();
+    let mut records: HashMap<i64, ResultArchive> = result_archives::table
+      .filter(result_archives::task_id.eq_any(&ids))
+      .load::<ResultArchive>(connection)
+      .map_err(|e| format!("listing archive records failed: {e}"))?
+      .into_iter()
+      .map(|record| (record.task_id, record))
+      .collect();
+    for (task_id, entry, status) in page {
+      let record = records.remove(&task_id);
+      let path = match &record {
+        Some(record) => PathBuf::from(&record.path),
+        None => match result_archive_path(&entry, &service.name, corpus.sandbox_id()) {
+          Some(path) => path,
+          None => continue,
+        },
+      };
+      // A Fatal or Invalid verdict may have come without a result (a rejected or never-completed
+      // task), so its archive is only expected when one was recorded.
+      let expected = record.is_some() || TaskStatus::raw_is_success(status);
+      if !expected && !storage.exists(&path) {
+        continue;
+      }
+      report.checked += 1;
+      let shown = path.display().to_string();
+      match check_archive(storage, &path, record.as_ref())
+        .map_err(|e| format!("reading {shown}: {e}"))?
+      {
+        None => {
+          if record.is_none() {
+            report.unrecorded += 1;
+          }
+          continue;
+        },
+        Some(Damage::Missing) => note(&mut report.missing, &mut report.missing_count, shown),
+        Some(Damage::ChecksumMismatch) => note(
+          &mut report.checksum_mismatch,
+          &mut report.checksum_mismatch_count,
+          shown,
+        ),
+        Some(Damage::NoCortexLog) => note(
+          &mut report.no_cortex_log,
+          &mut report.no_cortex_log_count,
+          shown,
+        ),
+      }
+      damaged.push(task_id);
+    }
+    progress(&format!(
+      "checked {} archives ({} damaged)",
+      report.checked,
+      damaged.len()
+    ));
+  }
+  Ok(damaged)
+}
+
+/// Reports the service's orphan archives on the corpus (see [`scan_orphans`]); returns their paths.
+fn find_orphans(
+  connection: &mut PgConnection,
+  corpus: &Corpus,
+  service: &Service,
+  storage: &dyn Storage,
+  report: &mut FsckReport,
+  progress: &mut impl FnMut(&str),
+) -> Result<Vec<PathBuf>, String> {
+  let mut orphans = Vec::new();
+  scan_orphans(
+    connection,
+    corpus,
+    std::slice::from_ref(service),
+    storage,
+    |object| {
+      report.orphan_bytes += object.size;
+      note(
+        &mut report.orphans,
+        &mut report.orphan_count,
+        OrphanArchive {
+          path: object.path.display().to_string(),
+          bytes: object.size,
+        },
+      );
+      orphans.push(object.path);
+    },
+    progress,
+  )?;
+  progress(&format!("found {} orphan archives", report.orphan_count));
+  Ok(orphans)
+}
+
+/// Lists the result archives of `services` in every entry directory of the corpus, and passes
+/// `orphan` those whose owner — the root corpus for `<service>.zip`, sandbox `<id>` for
+/// `<service>.sandbox-<id>.zip` — has no task of the service there, or no longer exists. Scanning a
+/// sandbox only considers its own archives.
+pub(super) fn scan_orphans(
+  connection: &mut PgConnection,
+  corpus: &Corpus,
+  services: &[Service],
+  storage: &dyn Storage,
+  mut orphan: impl FnMut(StoredObject),
+  progress: &mut impl FnMut(&str),
+) -> Result<(), String> {
+  let db_error = |e: diesel::result::Error| format!("listing entries failed: {e}");
+  // Every corpus id still in the store, and the owners whose tasks this scan can see.
+  let all_corpora: HashMap<i32, Option<i32>> = corpora::table
+    .select((corpora::id, corpora::parent_corpus_id))
+    .load::<(i32, Option<i32>)>(connection)
+    .map_err(db_error)?
+    .into_iter()
+    .collect();
+  let owners: HashSet<i32> = match corpus.parent_corpus_id {
+    Some(_) => HashSet::from([corpus.id]),
+    None => all_corpora
+      .iter()
+      .filter(|(id, parent)| **id == corpus.id || **parent == Some(corpus.id))
+      .map(|(id, _)| *id)
+      .collect(),
+  };
+  let owner_ids: Vec<i32> = owners.iter().copied().collect();
+  let service_ids: Vec<i32> = services.iter().map(|service| service.id).collect();
+
+  let mut last_entry = String::new();
+  loop {
+    let entries: Vec<String> = tasks::table
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(tasks::entry.gt(&last_entry))
+      .select(tasks::entry)
+      .distinct()
+      .order(tasks::entry)
+      .limit(FSCK_PAGE_SIZE)
+      .load(connection)
+      .map_err(db_error)?;
+    let Some(page_last) = entries.last() else {
+      break;
+    };
+    last_entry = page_last.clone();
+    // Which owners have a task of which service in which of these directories.
+    let owned: HashSet<(i32, i32, PathBuf)> = tasks::table
+      .filter(tasks::service_id.eq_any(&service_ids))
+      .filter(tasks::corpus_id.eq_any(&owner_ids))
+      .filter(tasks::entry.eq_any(&entries))
+      .select((tasks::corpus_id, tasks::service_id, tasks::entry))
+      .load::<(i32, i32, String)>(connection)
+      .map_err(db_error)?
+      .into_iter()
+      .filter_map(|(owner, service_id, entry)| Some((owner, service_id, entry_dir(&entry)?)))
+      .collect();
+    let sources: HashSet<PathBuf> = entries
+      .iter()
+      .map(|entry| PathBuf::from(entry.trim_end()))
+      .collect();
+    let dirs: HashSet<PathBuf> = entries
+      .iter()
+      .filter_map(|entry| entry_dir(entry))
+      .collect();
+    let mut dirs: Vec<PathBuf> = dirs.into_iter().collect();
+    dirs.sort();
+    for dir in dirs {
+      let objects = storage
+        .list(&dir)
+        .map_err(|e| format!("listing {}: {e}", dir.display()))?;
+      for object in objects {
+        if sources.contains(&object.path) {
+          continue;
+        }
+        let Some(name) = object.path.file_name().and_then(|name| name.to_str()) else {
+          continue;
+        };
+        let Some((service_id, owner)) = services
+          .iter()
+          .find_map(|service| archive_owner(name, &service.name).map(|owner| (service.id, owner)))
+        else {
+          continue;
+        };
+        let is_orphan = match owner {
+          // A plain archive belongs to the root corpus; a sandbox scan leaves it to the root's.
+          None if corpus.parent_corpus_id.is_none() => {
+            !owned.contains(&(corpus.id, service_id, dir.clone()))
+          },
+          None => false,
+          // A sandbox archive is an orphan once the sandbox is gone, or has no task here.
+          Some(id) if owners.contains(&id) => !owned.contains(&(id, service_id, dir.clone())),
+          Some(id) => !all_corpora.contains_key(&id) && corpus.parent_corpus_id.is_none(),
+        };
+        if is_orphan {
+          orphan(object);
+        }
+      }
+    }
+    progress(&format!(
+      "scanned {} entries up to {last_entry}",
+      corpus.name
+    ));
+  }
+  Ok(())
+}
+
+/// The directory holding an entry (and its result archives).
+fn entry_dir(entry: &str) -> Option<PathBuf> {
+  Path::new(entry.trim_end()).parent().map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::MemoryStorage;
+  use std::io::Write;
+
+  fn zip_with(name: &str) -> Vec<u8> {
+    let mut buffer = io::Cursor::new(Vec::new());
+    let mut zip = zip::ZipWriter::new(&mut buffer);
+    let opts: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+    zip.start_file(name, opts).unwrap();
+    zip.write_all(b"Info:conversion:0\n").unwrap();
+    zip.finish().unwrap();
+    buffer.into_inner()
+  }
+
+  fn record_of(path: &str, bytes: &[u8]) -> ResultArchive {
+    let (size, sha256) = digest(&mut io::Cursor::new(bytes)).unwrap();
+    ResultArchive {
+      task_id: 1,
+      path: path.to_string(),
+      size_bytes: size as i64,
+      sha256,
+      written_at: chrono::NaiveDateTime::default(),
+    }
+  }
+
+  #[test]
+  fn names_the_owner_of_a_result_archive() {
+    assert_eq!(archive_owner("tex_to_html.zip", "tex_to_html"), Some(None));
+    assert_eq!(
+      archive_owner("tex_to_html.sandbox-12.zip", "tex_to_html"),
+      Some(Some(12))
+    );
+    assert_eq!(
+      archive_owner("tex_to_html.sandbox-.zip", "tex_to_html"),
+      None
+    );
+    assert_eq!(
+      archive_owner("tex_to_html.sandbox-1x.zip", "tex_to_html"),
+      None
+    );
+    assert_eq!(archive_owner("tex_to_html_v2.zip", "tex_to_html"), None);
+    assert_eq!(archive_owner("tex_to_html.log", "tex_to_html"), None);
+    assert_eq!(archive_owner("0801.1234.zip", "tex_to_html"), None);
+  }
+
+  #[test]
+  fn tells_intact_archives_from_damaged_ones() {
+    let storage = MemoryStorage::default();
+    let good = zip_with("cortex.log");
+    let path = Path::new("/data/0801/0801.1234/tex_to_html.zip");
+    storage.insert(path, good.clone());
+    let record = record_of(&path.display().to_string(), &good);
+    assert_eq!(check_archive(&storage, path, Some(&record)).unwrap(), None);
+    assert_eq!(check_archive(&storage, path, None).unwrap(), None);
+
+    storage.insert(path, good[..good.len() / 2].to_vec());
+    assert_eq!(
+      check_archive(&storage, path, Some(&record)).unwrap(),
+      Some(Damage::ChecksumMismatch)
+    );
+    assert_eq!(
+      check_archive(&storage, path, None).unwrap(),
+      Some(Damage::NoCortexLog),
+      "without a record, a truncated zip shows as unreadable"
+    );
+
+    storage.insert(path, zip_with("result.html"));
+    assert_eq!(
+      check_archive(&storage, path, None).unwrap(),
+      Some(Damage::NoCortexLog)
+    );
+    storage.remove(path).unwrap();
+    assert_eq!(
+      check_archive(&storage, path, Some(&record)).unwrap(),
+      Some(Damage::Missing)
+    );
+  }
+}
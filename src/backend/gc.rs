@@ -0,0 +1,425 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Result archive **garbage collection**. Deleting a corpus, a service or a sandbox removes its
+//! task rows, but the `<service>.zip` / `<service>.sandbox-<id>.zip` archives the sink wrote next
+//! to each entry stay in storage. The collector finds them two ways:
+//!
+//! * **deleted tasks** — the `result_archives` records whose task no longer exists (a record
+//!   outlives its task for exactly this), unless a live task has since written the same path or has
+//!   its entry there, for the same service — and likewise the kept result versions
+//!   (`result_archive_versions`) of deleted tasks;
+//! * **orphans** — archives of a registered service in a live corpus's entry directories with no
+//!   task behind them (see `fsck::scan_orphans`), which also covers archives written before records
+//!   were kept, and those of deleted sandboxes.
+//!
+//! Archives of a deleted corpus or service written before records were kept cannot be told apart
+//! from other files and are left alone. A run is a size-accounted dry run unless asked to delete;
+//! a deleting run removes the archives and their records and writes an audit entry. Each archive
+//! is re-sized right before its removal, and kept if it changed since it was found.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use diesel::dsl::{exists, not};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::fsck::{archive_owner, note, scan_orphans};
+use crate::models::{
+  Corpus, IMPORT_SERVICE_ID, NewAuditEntry, ResultArchive, ResultArchiveVersion, Service,
+};
+use crate::schema::{corpora, result_archive_versions, result_archives, services, tasks};
+use crate::storage::Storage;
+
+/// Records examined per page of the ledger scan.
+const GC_PAGE_SIZE: i64 = 5_000;
+
+/// Why an archive is collectible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GcReason {
+  /// its recorded task was deleted (with its corpus, service or sandbox)
+  DeletedTask,
+  /// it sits in a live corpus with no task (or sandbox) behind it
+  Orphan,
+}
+
+/// One collectible archive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CollectibleArchive {
+  /// the archive's storage path
+  pub path: String,
+  /// its size, in bytes
+  pub bytes: u64,
+  /// why it is collectible
+  pub reason: GcReason,
+}
+
+/// The findings of a [`collect_archives`] run, and what was deleted.
+#[derive(Debug, Default, Serialize)]
+pub struct ArchiveGcReport {
+  /// `true` if nothing was deleted
+  pub dry_run: bool,
+  /// records of deleted tasks examined (their archives' and kept versions')
+  pub records_checked: usize,
+  /// records of deleted tasks whose archive was already gone (dropped by a deleting run)
+  pub stale_records: usize,
+  /// live root corpora whose entry directories were scanned for orphans
+  pub corpora_scanned: usize,
+  /// collectible archives (capped)
+  pub archives: Vec<CollectibleArchive>,
+  /// total collectible archives
+  pub archive_count: usize,
+  /// total size of the collectible archives, in bytes
+  pub total_bytes: u64,
+  /// collectible archives of deleted tasks
+  pub deleted_task_archives: usize,
+  /// their size, in bytes
+  pub deleted_task_bytes: u64,
+  /// collectible orphan archives
+  pub orphan_archives: usize,
+  /// their size, in bytes
+  pub orphan_bytes: u64,
+  /// archives deleted
+  pub removed: usize,
+  /// collectible archives left alone because they changed (or vanished) before their removal
+  pub changed: usize,
+  /// bytes freed by the deleted archives
+  pub freed_bytes: u64,
+}
+
+impl ArchiveGcReport {
+  /// Counts a collectible archive, and its bytes under its reason.
+  fn found(&mut self, path: &Path, bytes: u64, reason: GcReason) {
+    self.total_bytes += bytes;
+    match reason {
+      GcReason::DeletedTask => {
+        self.deleted_task_archives += 1;
+        self.deleted_task_bytes += bytes;
+      },
+      GcReason::Orphan => {
+        self.orphan_archives += 1;
+        self.orphan_bytes += bytes;
+      },
+    }
+    note(
+      &mut self.archives,
+      &mut self.archive_count,
+      CollectibleArchive {
+        path: path.display().to_string(),
+        bytes,
+        reason,
+      },
+    );
+  }
+}
+
+/// Finds the result archives no live task corresponds to, across every corpus and service — and,
+/// with `delete`, removes them (and the records of deleted tasks), recording the collection as
+/// `actor` in the audit log. `progress` is called with human-readable milestone lines.
+pub fn collect_archives(
+  connection: &mut PgConnection,
+  storage: &dyn Storage,
+  delete: bool,
+  actor: &str,
+  mut progress: impl FnMut(&str),
+) -> Result<ArchiveGcReport, String> {
+  let mut report = ArchiveGcReport {
+    dry_run: !delete,
+    ..ArchiveGcReport::default()
+  };
+  let mut seen = HashSet::new();
+  collect_deleted_tasks(
+    connection,
+    storage,
+    delete,
+    &mut report,
+    &mut seen,
+    &mut progress,
+  )?;
+  collect_deleted_task_versions(connection, storage, delete, &mut report, &mut progress)?;
+  collect_orphans(
+    connection,
+    storage,
+    delete,
+    &mut report,
+    &mut seen,
+    &mut progress,
+  )?;
+
+  if report.removed > 0 || (delete && report.stale_records > 0) {
+    let entry = NewAuditEntry::new(actor, "archive_gc", "result_archives")
+      .outcome("ok")
+      .details(format!(
+        "removed {} archives ({} bytes), dropped {} stale records",
+        report.removed, report.freed_bytes, report.stale_records
+      ));
+    if let Err(error) = entry.record(connection) {
+      tracing::warn!(%error, "archive gc: failed to record the audit entry");
+    }
+  }
+  Ok(report)
+}
+
+/// The ledger pass: the archives recorded for tasks that no longer exist.
+fn collect_deleted_tasks(
+  connection: &mut PgConnection,
+  storage: &dyn Storage,
+  delete: bool,
+  report: &mut ArchiveGcReport,
+  seen: &mut HashSet<PathBuf>,
+  progress: &mut impl FnMut(&str),
+) -> Result<(), String> {
+  let db_error = |e: diesel::result::Error| format!("reading archive records failed: {e}");
+  let services: Vec<Service> = services::table.load(connection).map_err(db_error)?;
+  let mut last_id = 0i64;
+  loop {
+    let page: Vec<ResultArchive> = result_archives::table
+      .filter(not(exists(
+        tasks::table.filter(tasks::id.eq(result_archives::task_id)),
+      )))
+      .filter(result_archives::task_id.gt(last_id))
+      .order(result_archives::task_id)
+      .limit(GC_PAGE_SIZE)
+      .load(connection)
+      .map_err(db_error)?;
+    let Some(page_last) = page.last() else {
+      break;
+    };
+    last_id = page_last.task_id;
+    report.records_checked += page.len();
+    // A path a live task has written since belongs to that task now.
+    let paths: Vec<&str> = page.iter().map(|record| record.path.as_str()).collect();
+    let live: HashSet<String> = result_archives::table
+      .filter(result_archives::path.eq_any(&paths))
+      .filter(exists(
+        tasks::table.filter(tasks::id.eq(result_archives::task_id)),
+      ))
+      .select(result_archives::path)
+      .load(connection)
+      .map_err(db_error)?
+      .into_iter()
+      .collect();
+    let mut dropped = Vec::new();
+    for record in &page {
+      if live.contains(&record.path) {
+        dropped.push(record.task_id);
+        continue;
+      }
+      let path = PathBuf::from(&record.path);
+      if !seen.insert(path.clone()) {
+        dropped.push(record.task_id);
+        continue;
+      }
+      // So does a path a live task of the same service is yet to write (or whose result is still
+      // on its way to its record): a corpus re-imported over the same entries, say.
+      if has_live_task_for(connection, &path, &services).map_err(db_error)? {
+        dropped.push(record.task_id);
+        continue;
+      }
+      match storage.size(&path) {
+        Ok(bytes) => {
+          report.found(&path, bytes, GcReason::DeletedTask);
+          if delete {
+            remove(storage, &path, bytes, report)?;
+            dropped.push(record.task_id);
+          }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+          report.stale_records += 1;
+          dropped.push(record.task_id);
+        },
+        Err(e) => return Err(format!("reading {}: {e}", path.display())),
+      }
+    }
+    if delete {
+      diesel::delete(result_archives::table.filter(result_archives::task_id.eq_any(&dropped)))
+        .execute(connection)
+        .map_err(db_error)?;
+    }
+    progress(&format!(
+      "checked {} records of deleted tasks ({} archives, {} bytes)",
+      report.records_checked, report.deleted_task_archives, report.deleted_task_bytes
+    ));
+  }
+  Ok(())
+}
+
+/// Whether some task — of any corpus, in any status — of the service a result archive at `path` is
+/// named for has its entry in the archive's directory, and so may write `path` next.
+fn has_live_task_for(
+  connection: &mut PgConnection,
+  path: &Path,
+  services: &[Service],
+) -> QueryResult<bool> {
+  let (Some(dir), Some(name)) = (
+    path.parent().and_then(Path::to_str),
+    path.file_name().and_then(|name| name.to_str()),
+  ) else {
+    return Ok(false);
+  };
+  // An archive of a deleted service has no task left to write it.
+  let Some(service) = services
+    .iter()
+    .find(|service| archive_owner(name, &service.name).is_some())
+  else {
+    return Ok(false);
+  };
+  let dir = dir
+    .replace('\\', "\\\\")
+    .replace('%', "\\%")
+    .replace('_', "\\_");
+  diesel::select(exists(
+    tasks::table
+      .filter(tasks::service_id.eq(service.id))
+      .filter(tasks::entry.like(format!("{dir}/%")))
+      .filter(tasks::entry.not_like(format!("{dir}/%/%"))),
+  ))
+  .get_result(connection)
+}
+
+/// The ledger pass over kept result versions: those of tasks that no longer exist.
+fn collect_deleted_task_versions(
+  connection: &mut PgConnection,
+  storage: &dyn Storage,
+  delete: bool,
+  report: &mut ArchiveGcReport,
+  progress: &mut impl FnMut(&str),
+) -> Result<(), String> {
+  let db_error = |e: diesel::result::Error| format!("reading result version records failed: {e}");
+  let mut last_id = 0i64;
+  loop {
+    let page: Vec<ResultArchiveVersion> = result_archive_versions::table
+      .filter(not(exists(
+        tasks::table.filter(tasks::id.eq(result_archive_versions::task_id)),
+      )))
+      .filter(result_archive_versions::id.gt(last_id))
+      .order(result_archive_versions::id)
+      .limit(GC_PAGE_SIZE)
+      .load(connection)
+      .map_err(db_error)?;
+    let Some(page_last) = page.last() else {
+      break;
+    };
+    last_id = page_last.id;
+    report.records_checked += page.len();
+    let mut dropped = Vec::new();
+    for version in &page {
+      let path = PathBuf::from(&version.path);
+      match storage.size(&path) {
+        Ok(bytes) => {
+          report.found(&path, bytes, GcReason::DeletedTask);
+          if delete {
+            remove(storage, &path, bytes, report)?;
+            dropped.push(version.id);
+          }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+          report.stale_records += 1;
+          dropped.push(version.id);
+        },
+        Err(e) => return Err(format!("reading {}: {e}", path.display())),
+      }
+    }
+    if delete {
+      diesel::delete(
+        result_archive_versions::table.filter(result_archive_versions::id.eq_any(&dropped)),
+      )
+      .execute(connection)
+      .map_err(db_error)?;
+    }
+    progress(&format!(
+      "checked {} records of deleted tasks ({} archives, {} bytes)",
+      report.records_checked, report.deleted_task_archives, report.deleted_task_bytes
+    ));
+  }
+  Ok(())
+}
+
+/// The scan pass: orphan archives of every registered service in every live root corpus.
+fn collect_orphans(
+  connection: &mut PgConnection,
+  storage: &dyn Storage,
+  delete: bool,
+  report: &mut ArchiveGcReport,
+  seen: &mut HashSet<PathBuf>,
+  progress: &mut impl FnMut(&str),
+) -> Result<(), String> {
+  let db_error = |e: diesel::result::Error| format!("listing corpora and services failed: {e}");
+  // Real conversion services only: init and import write no result archives.
+  let services: Vec<Service> = services::table
+    .filter(services::id.gt(IMPORT_SERVICE_ID))
+    .order(services::id)
+    .load(connection)
+    .map_err(db_error)?;
+  let roots: Vec<Corpus> = corpora::table
+    .filter(corpora::parent_corpus_id.is_null())
+    .order(corpora::id)
+    .load(connection)
+    .map_err(db_error)?;
+  if services.is_empty() {
+    return Ok(());
+  }
+  for corpus in &roots {
+    let mut orphans = Vec::new();
+    scan_orphans(
+      connection,
+      corpus,
+      &services,
+      storage,
+      |object| {
+        if seen.insert(object.path.clone()) {
+          orphans.push(object);
+        }
+      },
+      progress,
+    )?;
+    for object in orphans {
+      report.found(&object.path, object.size, GcReason::Orphan);
+      if delete {
+        remove(storage, &object.path, object.size, report)?;
+      }
+    }
+    report.corpora_scanned += 1;
+    progress(&format!(
+      "scanned {} ({} orphan archives, {} bytes so far)",
+      corpus.name, report.orphan_archives, report.orphan_bytes
+    ));
+  }
+  Ok(())
+}
+
+/// Deletes one collectible archive, accounting for it.
+fn remove(
+  storage: &dyn Storage,
+  path: &Path,
+  bytes: u64,
+  report: &mut ArchiveGcReport,
+) -> Result<(), String> {
+  // Re-stat right before removing: an archive rewritten (or removed) since it was found has a new
+  // owner, or none to free, and is left alone.
+  match storage.size(path) {
+    Ok(now) if now == bytes => {},
+    Ok(_) => {
+      report.changed += 1;
+      return Ok(());
+    },
+    Err(e) if e.kind() == io::ErrorKind::NotFound => {
+      report.changed += 1;
+      return Ok(());
+    },
+    Err(e) => return Err(format!("reading {}: {e}", path.display())),
+  }
+  storage
+    .remove(path)
+    .map_err(|e| format!("removing {}: {e}", path.display()))?;
+  report.removed += 1;
+  report.freed_bytes += bytes;
+  Ok(())
+}
@@ -1,19 +1,22 @@
 use std::collections::HashMap;
 
 use crate::schema::{
-  historical_tasks, log_errors, log_fatals, log_infos, log_invalids, log_warnings, task_runtimes,
-  tasks,
+  dead_letters, historical_runs, historical_tasks, log_errors, log_fatals, log_infos, log_invalids,
+  log_warnings, result_archive_versions, result_archives, services, task_runtimes, tasks,
 };
 use diesel::result::Error;
 use diesel::*;
 
 use super::RerunOptions;
+use super::control::{ControlKind, ControlSignal, wake_dispatcher};
 use crate::concerns::{CortexInsertable, MarkRerun};
 use crate::helpers::{NewTaskMessage, TaskReport, TaskStatus, rerun_mark};
 use crate::models::{
-  Corpus, HistoricalRun, LogError, LogFatal, LogInfo, LogInvalid, LogRecord, LogWarning,
-  NewHistoricalRun, NewLogError, NewLogFatal, NewLogInfo, NewLogInvalid, NewLogWarning, NewTask,
-  NewTaskRuntime, Service,
+  Corpus, DeadLetter, DeadLetterResolution, Document, EntryMetadata, HistoricalRun,
+  IMPORT_SERVICE_ID, LogError, LogFatal, LogInfo, LogInvalid, LogRecord, LogWarning, NewDocument,
+  NewEntryMetadata, NewHistoricalRun, NewLogError, NewLogFatal, NewLogInfo, NewLogInvalid,
+  NewLogWarning, NewResultArchive, NewResultArchiveVersion, NewTask, NewTaskRuntime, ResultArchive,
+  Service, Task,
 };
 
 pub(crate) fn mark_imported(
@@ -27,59 +30,252 @@ pub(crate) fn mark_imported(
     .execute(connection)
 }
 
+/// Insert import tasks together with the facts inspected about their entries and, for a manifest
+/// import, their manifest metadata — in one transaction, so a task is never left without the
+/// document row it was imported with. Tasks and document rows insert only if new, as in
+/// [`mark_imported`]; metadata replaces that of an earlier import of the entry, and an empty object
+/// clears it. Returns the number of new tasks.
+pub(crate) fn mark_imported_documents(
+  connection: &mut PgConnection,
+  imported_tasks: &[NewTask],
+  documents: &[NewDocument],
+  metadata: &[NewEntryMetadata],
+) -> Result<usize, Error> {
+  connection.transaction(|connection| {
+    let inserted = mark_imported(connection, imported_tasks)?;
+    Document::insert_new(connection, documents)?;
+    EntryMetadata::upsert(connection, metadata)?;
+    Ok(inserted)
+  })
+}
+
+/// Records the re-inspected facts of entries whose source changed in place (see
+/// `importer::changes`) and sends their finished conversions back to TODO, for every service
+/// activated on corpus `corpus_id` and on the sandboxes carved from it (which read the same sources
+/// in place) — one transaction, so a refreshed document never keeps its stale results. As in a
+/// rerun, the reset tasks' logs and runtimes are deleted, a pipeline service's tasks wait again for
+/// their upstream result, and the touched scopes' cached report grains are dropped. Tasks still
+/// pending (TODO, leased, paused, awaiting upstream) will read the new source anyway and are left
+/// alone, and so are quarantined ones. Returns the number of tasks reset.
+pub(crate) fn mark_changed_sources(
+  connection: &mut PgConnection,
+  corpus_id_val: i32,
+  documents: &[NewDocument],
+) -> Result<usize, Error> {
+  use crate::schema::{corpora, services};
+  if documents.is_empty() {
+    return Ok(0);
+  }
+  connection.transaction(|connection| {
+    let sandbox_ids: Vec<i32> = corpora::table
+      .filter(corpora::parent_corpus_id.eq(corpus_id_val))
+      .select(corpora::id)
+      .load(connection)?;
+    for document in documents {
+      Document::refresh(connection, document)?;
+      // A sandbox carries its own copy of the entry's facts (see `Document::copy_to_sandbox`).
+      for &sandbox_id in &sandbox_ids {
+        let copy = NewDocument {
+          corpus_id: sandbox_id,
+          ..document.clone()
+        };
+        Document::refresh(connection, &copy)?;
+      }
+    }
+    let entries: Vec<&str> = documents
+      .iter()
+      .map(|document| document.entry.as_str())
+      .collect();
+    let scope: Vec<i32> = std::iter::once(corpus_id_val).chain(sandbox_ids).collect();
+    // The temporary `mark` keeps the reset scope selectable while it is rewired (see mark_rerun).
+    let mark: i32 = rerun_mark();
+    let mut touched: Vec<(i32, i32)> = update(tasks::table)
+      .filter(tasks::corpus_id.eq_any(&scope))
+      .filter(tasks::service_id.gt(IMPORT_SERVICE_ID))
+      .filter(tasks::entry.eq_any(&entries))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
+      .set(tasks::status.eq(mark))
+      .returning((tasks::corpus_id, tasks::service_id))
+      .get_results(connection)?;
+    let reset = touched.len();
+    touched.sort_unstable();
+    touched.dedup();
+    for (corpus_id, service_id_val) in touched {
+      let service: Service = services::table.find(service_id_val).first(connection)?;
+      reset_marked_scope(connection, corpus_id, &service, mark)?;
+    }
+    Ok(reset)
+  })
+}
+
+/// Sends the finished tasks `task_ids` of a `(corpus, service)` pair back to TODO because their
+/// result archive is missing or damaged (see `backend::fsck`) — one transaction, rerun-style: their
+/// logs and archive records are deleted, a pipeline service's tasks wait again for their upstream
+/// result, and the scope's cached report grains are dropped. Tasks no longer finished are left
+/// alone. Returns the number of tasks reset.
+pub(crate) fn mark_damaged(
+  connection: &mut PgConnection,
+  corpus: &Corpus,
+  service: &Service,
+  task_ids: &[i64],
+) -> Result<usize, Error> {
+  if task_ids.is_empty() {
+    return Ok(0);
+  }
+  connection.transaction(|connection| {
+    let mark: i32 = rerun_mark();
+    let reset = update(tasks::table)
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(tasks::service_id.eq(service.id))
+      .filter(tasks::id.eq_any(task_ids))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
+      .set(tasks::status.eq(mark))
+      .execute(connection)?;
+    // Only here is the archive really gone, so only here does its record go too.
+    let marked_ids = tasks::table
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(tasks::service_id.eq(service.id))
+      .filter(tasks::status.eq(mark))
+      .select(tasks::id);
+    delete(result_archives::table.filter(result_archives::task_id.eq_any(marked_ids)))
+      .execute(connection)?;
+    reset_marked_scope(connection, corpus.id, service, mark)?;
+    Ok(reset)
+  })
+}
+
+/// The shared tail of every reset (rerun, changed sources, damaged archives): the tasks of
+/// `(corpus_id, service)` currently set to the temporary `mark` lose their logs and runtimes, a
+/// pipeline service's tasks wait again for their upstream result, the rest go back to TODO, and the
+/// scope's cached report grains are dropped. Their archive records stay: the archive is still
+/// stored until the next result replaces it (and its record) on commit, and is then kept as a
+/// version with the record's checksum and run. Runs inside the caller's transaction, so the `mark`
+/// is never observable. Returns the number of tasks set to TODO.
+fn reset_marked_scope(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service: &Service,
+  mark: i32,
+) -> Result<usize, Error> {
+  // With a positive blocking mark, these deletes are index scans on each table's `task_id`.
+  let affected_tasks = tasks::table
+    .filter(tasks::corpus_id.eq(corpus_id))
+    .filter(tasks::service_id.eq(service.id))
+    .filter(tasks::status.eq(mark));
+  let affected_tasks_ids = affected_tasks.select(tasks::id);
+  delete(log_infos::table.filter(log_infos::task_id.eq_any(affected_tasks_ids)))
+    .execute(connection)?;
+  delete(log_warnings::table.filter(log_warnings::task_id.eq_any(affected_tasks_ids)))
+    .execute(connection)?;
+  delete(log_errors::table.filter(log_errors::task_id.eq_any(affected_tasks_ids)))
+    .execute(connection)?;
+  delete(log_fatals::table.filter(log_fatals::task_id.eq_any(affected_tasks_ids)))
+    .execute(connection)?;
+  delete(log_invalids::table.filter(log_invalids::task_id.eq_any(affected_tasks_ids)))
+    .execute(connection)?;
+  delete(task_runtimes::table.filter(task_runtimes::task_id.eq_any(affected_tasks_ids)))
+    .execute(connection)?;
+
+  super::pipelines::hold_for_upstream(connection, corpus_id, service, mark)?;
+  let reset = update(affected_tasks)
+    .set(tasks::status.eq(TaskStatus::TODO.raw()))
+    .execute(connection)?;
+  super::rollup::invalidate_scope(connection, corpus_id, service.id)?;
+  Ok(reset)
+}
+
 /// **Pause** a `(corpus, service)` run: transition every **in-progress** task (`status >= 0` — i.e.
 /// TODO plus any leased/Queued mark) to **Blocked**, so the ventilator stops handing them out (it
-/// only fetches `status = TODO`). Completed tasks (`status < 0`) are left alone. Returns the number
-/// paused. The exact inverse of [`resume_blocked`] (Arm 7 run-lifecycle control).
+/// only fetches `status = TODO`), and park the pipeline tasks still awaiting their upstream as
+/// [`TaskStatus::PAUSED_AWAITING_UPSTREAM`], so an upstream finishing during the pause does not
+/// release them. Completed tasks (`status < 0`) are left alone. Returns the number paused. The
+/// exact inverse of [`resume_blocked`] (Arm 7 run-lifecycle control).
 pub(crate) fn mark_blocked(
   connection: &mut PgConnection,
   corpus_id_val: i32,
   service_id_val: i32,
 ) -> Result<usize, Error> {
-  update(tasks::table)
-    .filter(tasks::corpus_id.eq(corpus_id_val))
-    .filter(tasks::service_id.eq(service_id_val))
-    .filter(tasks::status.ge(0))
-    .set(tasks::status.eq(TaskStatus::Blocked(-6).raw()))
-    .execute(connection)
+  connection.transaction(|connection| {
+    let paused = update(tasks::table)
+      .filter(tasks::corpus_id.eq(corpus_id_val))
+      .filter(tasks::service_id.eq(service_id_val))
+      .filter(tasks::status.ge(0))
+      .set(tasks::status.eq(TaskStatus::PAUSED.raw()))
+      .execute(connection)?;
+    let parked = update(tasks::table)
+      .filter(tasks::corpus_id.eq(corpus_id_val))
+      .filter(tasks::service_id.eq(service_id_val))
+      .filter(tasks::status.eq(TaskStatus::AWAITING_UPSTREAM.raw()))
+      .set(tasks::status.eq(TaskStatus::PAUSED_AWAITING_UPSTREAM.raw()))
+      .execute(connection)?;
+    Ok(paused + parked)
+  })
 }
 
-/// **Resume** a paused `(corpus, service)` run: transition every **Blocked** task (`status < -5`)
-/// back to **TODO** (`0`), so the ventilator re-leases it on the next fetch. Returns the number
-/// resumed. The exact inverse of [`mark_blocked`]; completed and already-in-progress tasks are left
-/// alone (Invalid is `-5`, so the `< -5` filter never touches it).
+/// **Resume** a paused `(corpus, service)` run: transition every **paused** task
+/// ([`TaskStatus::PAUSED`]) back to **TODO** (`0`), so the ventilator re-leases it on the next
+/// fetch, and settle the parked pipeline tasks against their upstream as it stands now (see
+/// [`super::pipelines::release_parked`]). Returns the number resumed. The exact inverse of
+/// [`mark_blocked`]; completed and already-in-progress tasks are left alone.
 pub(crate) fn resume_blocked(
   connection: &mut PgConnection,
   corpus_id_val: i32,
   service_id_val: i32,
 ) -> Result<usize, Error> {
-  update(tasks::table)
-    .filter(tasks::corpus_id.eq(corpus_id_val))
-    .filter(tasks::service_id.eq(service_id_val))
-    .filter(tasks::status.lt(-5))
-    .set(tasks::status.eq(TaskStatus::TODO.raw()))
-    .execute(connection)
+  connection.transaction(|connection| {
+    let resumed = update(tasks::table)
+      .filter(tasks::corpus_id.eq(corpus_id_val))
+      .filter(tasks::service_id.eq(service_id_val))
+      .filter(tasks::status.eq(TaskStatus::PAUSED.raw()))
+      .set(tasks::status.eq(TaskStatus::TODO.raw()))
+      .execute(connection)?;
+    let service: Service = services::table.find(service_id_val).first(connection)?;
+    let released = super::pipelines::release_parked(connection, corpus_id_val, &service)?;
+    Ok(resumed + released)
+  })
 }
 
 /// **Pause ALL conversions** (the dashboard's global control): block every in-progress task
 /// (`status >= 0`) across **every** `(corpus, service)`, fleet-wide, so the ventilator stops
-/// leasing new work everywhere. The global twin of [`mark_blocked`]; returns the number paused.
-/// Reversible with [`resume_all_blocked`]. (In-flight tasks already dispatched still land their
-/// results — pause only stops *new* leasing, exactly like the per-run pause.)
+/// leasing new work everywhere, parking awaiting pipeline tasks as [`mark_blocked`] does. The
+/// global twin of [`mark_blocked`]; returns the number paused. Reversible with
+/// [`resume_all_blocked`]. (In-flight tasks already dispatched still land their results — pause
+/// only stops *new* leasing, exactly like the per-run pause.)
 pub(crate) fn mark_all_blocked(connection: &mut PgConnection) -> Result<usize, Error> {
-  update(tasks::table)
-    .filter(tasks::status.ge(0))
-    .set(tasks::status.eq(TaskStatus::Blocked(-6).raw()))
-    .execute(connection)
+  connection.transaction(|connection| {
+    let paused = update(tasks::table)
+      .filter(tasks::status.ge(0))
+      .set(tasks::status.eq(TaskStatus::PAUSED.raw()))
+      .execute(connection)?;
+    let parked = update(tasks::table)
+      .filter(tasks::status.eq(TaskStatus::AWAITING_UPSTREAM.raw()))
+      .set(tasks::status.eq(TaskStatus::PAUSED_AWAITING_UPSTREAM.raw()))
+      .execute(connection)?;
+    Ok(paused + parked)
+  })
 }
 
-/// **Resume ALL conversions**: return every Blocked task (`status < -5`) across every pair to TODO
-/// — the exact inverse of [`mark_all_blocked`]. Returns the number resumed.
+/// **Resume ALL conversions**: return every paused task across every pair to TODO, and settle every
+/// parked pipeline task against its upstream — the exact inverse of [`mark_all_blocked`]. Returns
+/// the number resumed.
 pub(crate) fn resume_all_blocked(connection: &mut PgConnection) -> Result<usize, Error> {
-  update(tasks::table)
-    .filter(tasks::status.lt(-5))
-    .set(tasks::status.eq(TaskStatus::TODO.raw()))
-    .execute(connection)
+  connection.transaction(|connection| {
+    let resumed = update(tasks::table)
+      .filter(tasks::status.eq(TaskStatus::PAUSED.raw()))
+      .set(tasks::status.eq(TaskStatus::TODO.raw()))
+      .execute(connection)?;
+    let parked_pairs: Vec<(i32, i32)> = tasks::table
+      .filter(tasks::status.eq(TaskStatus::PAUSED_AWAITING_UPSTREAM.raw()))
+      .select((tasks::corpus_id, tasks::service_id))
+      .distinct()
+      .load(connection)?;
+    let mut released = 0;
+    for (corpus_id_val, service_id_val) in parked_pairs {
+      let service: Service = services::table.find(service_id_val).first(connection)?;
+      released += super::pipelines::release_parked(connection, corpus_id_val, &service)?;
+    }
+    Ok(resumed + released)
+  })
 }
 
 pub(crate) fn mark_done(
@@ -99,11 +295,13 @@ pub(crate) fn mark_done(
   // that cap — which made the whole statement fail (`number of parameters must be
   // between 0 and 65535`), the finalize thread panic, and the dispatcher wedge
   // (observed on a 64-worker run, 2026-06-17). Chunk every batched statement to stay
-  // under the cap: `eq_any` binds 1 param per id; a log row binds 4 columns
-  // (task_id, category, what, details), so 16k rows ≈ 64k params.
+  // under the cap: `eq_any` binds 1 param per id; a log row binds 5 columns
+  // (task_id, category, what, details, fingerprint), so 13k rows ≈ 65k params.
   const ID_CHUNK: usize = 50_000;
-  const LOG_INSERT_CHUNK: usize = 16_000;
+  const LOG_INSERT_CHUNK: usize = 13_000;
   connection.transaction::<(), Error, _>(|t_connection| {
+    // Record the previous archives the sink kept as versions, while their records still exist.
+    record_versions(t_connection, reports)?;
     // Clear the prior log messages for every finalized task (one statement per severity
     // table), chunked over the task-id list to respect the bind-parameter cap.
     for ids in task_ids.chunks(ID_CHUNK) {
@@ -116,6 +314,8 @@ pub(crate) fn mark_done(
         .execute(t_connection)?;
       delete(task_runtimes::table.filter(task_runtimes::task_id.eq_any(ids)))
         .execute(t_connection)?;
+      delete(result_archives::table.filter(result_archives::task_id.eq_any(ids)))
+        .execute(t_connection)?;
     }
     // Group the finalized task ids by their target status, and partition the new messages by
     // severity table, in one pass — so both the status UPDATEs and the message INSERTs become a
@@ -174,7 +374,6 @@ pub(crate) fn mark_done(
           NewTaskMessage::Invalid(record) => new_invalids.push(record.clone()),
         }
       }
-      // TODO: Update dependenct services, when integrated in DB
     }
     // Apply the status updates: one batched UPDATE per *distinct* terminal status (a small fixed
     // set — NoProblem/Warning/Error/Fatal/Invalid), each over the disjoint id set that resolved
@@ -187,6 +386,9 @@ pub(crate) fn mark_done(
           .execute(t_connection)?;
       }
     }
+    // Pipelines: release (or settle Invalid) the downstream tasks that were awaiting these
+    // upstream results, now that their statuses are final.
+    super::pipelines::advance_downstream(t_connection, &task_ids)?;
     // Batched INSERT per severity table, chunked to respect the bind-parameter cap.
     // (`chunks` over an empty Vec yields nothing, so no `is_empty` guard is needed.)
     for chunk in new_infos.chunks(LOG_INSERT_CHUNK) {
@@ -215,17 +417,96 @@ pub(crate) fn mark_done(
         .execute(t_connection)?;
     }
     // Denormalized runtimes: the prior runtime rows for these tasks were cleared in the delete loop
-    // above, so a plain insert is correct (3 columns/row → 16k rows ≈ 48k binds, under the cap).
+    // above, so a plain insert is correct (3 columns/row → 13k rows ≈ 39k binds, under the cap).
     for chunk in new_runtimes.chunks(LOG_INSERT_CHUNK) {
       insert_into(task_runtimes::table)
         .values(chunk)
         .execute(t_connection)?;
     }
+    // The committed archives' sizes and checksums (4 columns/row), replacing the records of any
+    // earlier result — what `cortex fsck` verifies the stored archives against.
+    let new_archives: Vec<NewResultArchive> = reports
+      .iter()
+      .filter_map(|report| report.archive.clone())
+      .collect();
+    for chunk in new_archives.chunks(LOG_INSERT_CHUNK) {
+      insert_into(result_archives::table)
+        .values(chunk)
+        .execute(t_connection)?;
+    }
     Ok(())
   })?;
   Ok(())
 }
 
+/// Records the previous result archives the sink moved aside for `reports` (see
+/// `TaskReport::previous_archive`): each takes the checksum and commit time of the archive's
+/// `result_archives` record, and is tagged with the historical run of its `(corpus, service)` that
+/// was open when it was written. Then drops the records beyond each service's
+/// `keep_result_versions`, newest kept — the sink has already removed those files.
+fn record_versions(connection: &mut PgConnection, reports: &[TaskReport]) -> Result<(), Error> {
+  let kept: Vec<(&Task, &NewResultArchiveVersion)> = reports
+    .iter()
+    .filter_map(|report| Some((&report.task, report.previous_archive.as_ref()?)))
+    .collect();
+  if kept.is_empty() {
+    return Ok(());
+  }
+  let task_ids: Vec<i64> = kept.iter().map(|(task, _)| task.id).collect();
+  let records: HashMap<i64, ResultArchive> = result_archives::table
+    .filter(result_archives::task_id.eq_any(&task_ids))
+    .load::<ResultArchive>(connection)?
+    .into_iter()
+    .map(|record| (record.task_id, record))
+    .collect();
+  let mut versions = Vec::with_capacity(kept.len());
+  for (task, version) in &kept {
+    let mut version = (*version).clone();
+    // An archive committed before records were kept has no checksum, nor a known commit time.
+    if let Some(record) = records.get(&task.id) {
+      version.sha256 = Some(record.sha256.clone());
+      version.written_at = record.written_at;
+    }
+    version.run_id = historical_runs::table
+      .filter(historical_runs::corpus_id.eq(task.corpus_id))
+      .filter(historical_runs::service_id.eq(task.service_id))
+      .filter(historical_runs::start_time.le(version.written_at))
+      .order(historical_runs::start_time.desc())
+      .select(historical_runs::id)
+      .first(connection)
+      .optional()?;
+    versions.push(version);
+  }
+  insert_into(result_archive_versions::table)
+    .values(&versions)
+    .execute(connection)?;
+
+  let mut service_ids: Vec<i32> = kept.iter().map(|(task, _)| task.service_id).collect();
+  service_ids.sort_unstable();
+  service_ids.dedup();
+  let keep: HashMap<i32, i32> = services::table
+    .filter(services::id.eq_any(&service_ids))
+    .select((services::id, services::keep_result_versions))
+    .load::<(i32, i32)>(connection)?
+    .into_iter()
+    .collect();
+  for (task, _) in &kept {
+    // At least the version just kept: the sink kept it, so the service kept versions a moment ago.
+    let keep = i64::from(keep.get(&task.service_id).copied().unwrap_or(0).max(1));
+    let stale: Vec<i64> = result_archive_versions::table
+      .filter(result_archive_versions::task_id.eq(task.id))
+      .order(result_archive_versions::id.desc())
+      .offset(keep)
+      .select(result_archive_versions::id)
+      .load(connection)?;
+    if !stale.is_empty() {
+      delete(result_archive_versions::table.filter(result_archive_versions::id.eq_any(&stale)))
+        .execute(connection)?;
+    }
+  }
+  Ok(())
+}
+
 pub(crate) fn mark_rerun<'a>(
   connection: &'a mut PgConnection,
   options: RerunOptions<'a>,
@@ -238,13 +519,15 @@ pub(crate) fn mark_rerun<'a>(
     what_opt,
     owner_opt,
     description_opt,
+    priority_opt,
+    text_opt,
   } = options;
   use crate::schema::tasks::{corpus_id, service_id, status};
   // We are starting a new run, first catalog the current metadata in our historical records.
   let mut description = description_opt.unwrap_or_else(|| String::from("mark for rerun "));
   // auto-generate a report message from the selected filters
   description.push_str("(filters:");
-  if severity_opt.is_none() && category_opt.is_none() && what_opt.is_none() {
+  if severity_opt.is_none() && category_opt.is_none() && what_opt.is_none() && text_opt.is_none() {
     description.push_str(" entire corpus");
   } else {
     if let Some(ref severity) = severity_opt {
@@ -259,6 +542,12 @@ pub(crate) fn mark_rerun<'a>(
       description.push_str(" what=");
       description.push_str(what);
     }
+    if let Some(ref text) = text_opt {
+      description.push_str(&format!(" text={text:?}"));
+    }
+  }
+  if let Some(priority) = priority_opt {
+    description.push_str(&format!(" priority={priority}"));
   }
   description.push(')');
 
@@ -284,109 +573,184 @@ pub(crate) fn mark_rerun<'a>(
     // (0) — else an unrelated task would be swept into the rerun and double-dispatched.
     let mark: i32 = rerun_mark();
 
-    // First, mark as blocked all of the tasks in the chosen scope, using a special mark
-    match severity_opt {
-      Some(severity) => match category_opt {
-        Some(category) => match what_opt {
-          // All tasks in a "what" class
-          Some(what) => match severity.to_lowercase().as_str() {
-            "warning" => LogWarning::mark_rerun_by_what(
-              mark, corpus.id, service.id, &category, &what, connection,
-            ),
-            "error" => LogError::mark_rerun_by_what(
-              mark, corpus.id, service.id, &category, &what, connection,
-            ),
-            "fatal" => LogFatal::mark_rerun_by_what(
-              mark, corpus.id, service.id, &category, &what, connection,
-            ),
-            "invalid" => LogInvalid::mark_rerun_by_what(
-              mark, corpus.id, service.id, &category, &what, connection,
-            ),
-            _ => {
-              LogInfo::mark_rerun_by_what(mark, corpus.id, service.id, &category, &what, connection)
-            },
-          }?,
-          // None: All tasks in a category
-          None => match severity.to_lowercase().as_str() {
-            "warning" => {
-              LogWarning::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
-            },
-            "error" => {
-              LogError::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
-            },
-            "fatal" => {
-              LogFatal::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
-            },
-            "invalid" => {
-              LogInvalid::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
-            },
-            _ => {
-              LogInfo::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
-            },
-          }?,
+    // First, mark as blocked all of the tasks in the chosen scope, using a special mark. A text
+    // query scopes by message, so its severity names the messages searched, not a task status.
+    if let Some(text) = text_opt {
+      super::search::mark_rerun_by_text(
+        connection,
+        mark,
+        corpus.id,
+        service.id,
+        severity_opt.as_deref(),
+        category_opt.as_deref(),
+        what_opt.as_deref(),
+        &text,
+      )?;
+    } else {
+      match severity_opt {
+        Some(severity) => match category_opt {
+          Some(category) => match what_opt {
+            // All tasks in a "what" class
+            Some(what) => match severity.to_lowercase().as_str() {
+              "warning" => LogWarning::mark_rerun_by_what(
+                mark, corpus.id, service.id, &category, &what, connection,
+              ),
+              "error" => LogError::mark_rerun_by_what(
+                mark, corpus.id, service.id, &category, &what, connection,
+              ),
+              "fatal" => LogFatal::mark_rerun_by_what(
+                mark, corpus.id, service.id, &category, &what, connection,
+              ),
+              "invalid" => LogInvalid::mark_rerun_by_what(
+                mark, corpus.id, service.id, &category, &what, connection,
+              ),
+              _ => LogInfo::mark_rerun_by_what(
+                mark, corpus.id, service.id, &category, &what, connection,
+              ),
+            }?,
+            // None: All tasks in a category
+            None => match severity.to_lowercase().as_str() {
+              "warning" => LogWarning::mark_rerun_by_category(
+                mark, corpus.id, service.id, &category, connection,
+              ),
+              "error" => {
+                LogError::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
+              },
+              "fatal" => {
+                LogFatal::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
+              },
+              "invalid" => LogInvalid::mark_rerun_by_category(
+                mark, corpus.id, service.id, &category, connection,
+              ),
+              _ => {
+                LogInfo::mark_rerun_by_category(mark, corpus.id, service.id, &category, connection)
+              },
+            }?,
+          },
+          None => {
+            // All finished tasks in a certain status/severity (never in-flight or quarantined
+            // ones, as for the message-scoped reruns)
+            let status_to_rerun: i32 = TaskStatus::from_key(&severity)
+              .unwrap_or(TaskStatus::NoProblem)
+              .raw();
+            update(tasks::table)
+              .filter(corpus_id.eq(corpus.id))
+              .filter(service_id.eq(service.id))
+              .filter(status.eq(status_to_rerun))
+              .filter(status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
+              .set(status.eq(mark))
+              .execute(connection)?
+          },
         },
         None => {
-          // All tasks in a certain status/severity
-          let status_to_rerun: i32 = TaskStatus::from_key(&severity)
-            .unwrap_or(TaskStatus::NoProblem)
-            .raw();
+          // Entire corpus (quarantined dead-letters stay parked until requeued explicitly)
           update(tasks::table)
             .filter(corpus_id.eq(corpus.id))
             .filter(service_id.eq(service.id))
-            .filter(status.eq(status_to_rerun))
+            .filter(status.lt(0))
+            .filter(status.ne(TaskStatus::QUARANTINED.raw()))
             .set(status.eq(mark))
             .execute(connection)?
         },
-      },
-      None => {
-        // Entire corpus
-        update(tasks::table)
-          .filter(corpus_id.eq(corpus.id))
-          .filter(service_id.eq(service.id))
-          .filter(status.lt(0))
-          .set(status.eq(mark))
-          .execute(connection)?
-      },
-    };
+      };
+    }
+
+    // Pipelines: the scope's results are about to change, so the same entries of the downstream
+    // services that follow reruns go back to awaiting this service (logs cleared).
+    super::pipelines::invalidate_downstream(connection, corpus.id, service, mark)?;
 
-    // Next, delete all logs for the blocked tasks.
-    // Note that if we are using a negative blocking status, this query should get sped up via an
-    // "Index Scan using log_taskid on logs"
-    let affected_tasks = tasks::table
+    // A prioritized rerun stamps its priority on the scope, so the dispatcher leases it ahead of
+    // the ordinary backlog (including tasks that first await their upstream). An ordinary rerun
+    // returns the scope to that backlog (priority 0), so an earlier prioritized rerun's stamp does
+    // not keep jumping the queue.
+    update(tasks::table)
       .filter(corpus_id.eq(corpus.id))
       .filter(service_id.eq(service.id))
-      .filter(status.eq(mark));
-    let affected_tasks_ids = affected_tasks.select(tasks::id);
-
-    let affected_log_infos = log_infos::table.filter(log_infos::task_id.eq_any(affected_tasks_ids));
-    delete(affected_log_infos).execute(connection)?;
-    let affected_log_warnings =
-      log_warnings::table.filter(log_warnings::task_id.eq_any(affected_tasks_ids));
-    delete(affected_log_warnings).execute(connection)?;
-    let affected_log_errors =
-      log_errors::table.filter(log_errors::task_id.eq_any(affected_tasks_ids));
-    delete(affected_log_errors).execute(connection)?;
-    let affected_log_fatals =
-      log_fatals::table.filter(log_fatals::task_id.eq_any(affected_tasks_ids));
-    delete(affected_log_fatals).execute(connection)?;
-    let affected_log_invalids =
-      log_invalids::table.filter(log_invalids::task_id.eq_any(affected_tasks_ids));
-    delete(affected_log_invalids).execute(connection)?;
-
-    // Lastly, switch all blocked tasks to TODO, and complete the rerun mark pass.
-    update(affected_tasks)
-      .set(status.eq(TaskStatus::TODO.raw()))
+      .filter(status.eq(mark))
+      .set(tasks::priority.eq(priority_opt.unwrap_or(0)))
       .execute(connection)?;
-
-    // The reran scope's reports are now stale (logs deleted, statuses reset). Drop its cached
-    // report grains inside the same transaction so the next report view repopulates from the
-    // fresh data — scoped to exactly this (corpus, service), never the global cube.
-    super::rollup::invalidate_scope(connection, corpus.id, service.id)?;
+    // Lastly, clear the scope's old logs and records and switch it to TODO (or awaiting upstream),
+    // dropping its now-stale cached report grains in the same transaction.
+    reset_marked_scope(connection, corpus.id, service, mark)?;
 
     Ok(())
   })
 }
 
+/// Resolves dispatch dead-letter `id` on behalf of `actor`: **requeue** resets its task to TODO for
+/// another attempt (allowed from `open`, or to release a `quarantined` entry), **quarantine** parks
+/// the task permanently as [`TaskStatus::QUARANTINED`] (allowed from `open`). The task must still
+/// be the dead-lettered Fatal (or, for a release, still quarantined) — a task since rerun is left
+/// alone. A requeue resets the task like a rerun (its `never_completed_with_retries` fatal and
+/// other logs cleared, a pipeline task awaiting its upstream again) and wakes the dispatcher;
+/// either way the task's cached report grains are dropped in the same transaction. `NotFound` for
+/// an unknown id; an entry already resolved this way, or whose task moved on, is a
+/// [`Error::QueryBuilderError`].
+pub(crate) fn resolve_dead_letter(
+  connection: &mut PgConnection,
+  id: i64,
+  resolution: DeadLetterResolution,
+  actor: &str,
+) -> Result<DeadLetter, Error> {
+  let (letter, task) = connection.transaction(|connection| {
+    let letter = DeadLetter::find(connection, id)?;
+    if !resolution.applies_to(&letter.state) {
+      return Err(Error::QueryBuilderError(
+        format!(
+          "dead-letter {id} is {}; it cannot be {}",
+          letter.state,
+          resolution.state()
+        )
+        .into(),
+      ));
+    }
+    let task: Task = tasks::table.find(letter.task_id).first(connection)?;
+    let (expected, expected_name) = if letter.state == "quarantined" {
+      (TaskStatus::QUARANTINED, "quarantined")
+    } else {
+      (TaskStatus::Fatal, "fatal")
+    };
+    let target = match resolution {
+      DeadLetterResolution::Requeue => rerun_mark(),
+      DeadLetterResolution::Quarantine => TaskStatus::QUARANTINED.raw(),
+    };
+    let moved = update(tasks::table.find(task.id))
+      .filter(tasks::status.eq(expected.raw()))
+      .set(tasks::status.eq(target))
+      .execute(connection)?;
+    if moved == 0 {
+      return Err(Error::QueryBuilderError(
+        format!(
+          "task {} of dead-letter {id} is no longer {expected_name}; it was handled since",
+          task.id
+        )
+        .into(),
+      ));
+    }
+    if resolution == DeadLetterResolution::Requeue {
+      let service: Service = services::table.find(task.service_id).first(connection)?;
+      reset_marked_scope(connection, task.corpus_id, &service, target)?;
+    } else {
+      super::rollup::invalidate_scope(connection, task.corpus_id, task.service_id)?;
+    }
+    let letter: DeadLetter = update(dead_letters::table.find(id))
+      .set((
+        dead_letters::state.eq(resolution.state()),
+        dead_letters::resolved_at.eq(Some(chrono::Utc::now().naive_utc())),
+        dead_letters::resolved_by.eq(Some(actor)),
+      ))
+      .get_result(connection)?;
+    Ok((letter, task))
+  })?;
+  if resolution == DeadLetterResolution::Requeue {
+    wake_dispatcher(
+      connection,
+      ControlSignal::scoped(ControlKind::Rerun, task.corpus_id, task.service_id),
+    );
+  }
+  Ok(letter)
+}
+
 pub(crate) fn mark_new_run(
   connection: &mut PgConnection,
   corpus: &Corpus,
@@ -0,0 +1,325 @@
+//! **Service pipelines**: scheduling across services chained by `inputconverter`.
+//!
+//! A service whose `inputconverter` names another registered service (its *upstream*, see
+//! [`Service::upstream`]) consumes that service's result archive for the same document instead of
+//! the document source. The chain forms a DAG over `services`; scheduling follows it per
+//! `(corpus, entry)`:
+//!
+//! * a downstream task starts in [`TaskStatus::AWAITING_UPSTREAM`] until its upstream task for the
+//!   same entry has a successful result ([`hold_for_upstream`]);
+//! * finalizing an upstream task releases its waiting downstream tasks to TODO on success, or
+//!   settles them as Invalid (`cortex`/`upstream_failed`) on a Fatal/Invalid upstream
+//!   ([`advance_downstream`]);
+//! * rerunning an upstream scope resets the same entries of every downstream service whose
+//!   `on_upstream_rerun` policy is `rerun` — transitively — back to awaiting
+//!   ([`invalidate_downstream`]); a `keep` service retains its results and stops the cascade.
+//!
+//! A sandbox corpus reads its parent's upstream results unless it ran the upstream itself, so an
+//! upstream success on either counts, and finalizing a parent's upstream task also releases the
+//! sandbox's waiting tasks.
+//!
+//! Pausing a downstream pair parks its awaiting tasks as
+//! [`TaskStatus::PAUSED_AWAITING_UPSTREAM`], which [`advance_downstream`] leaves alone; resuming
+//! settles them against their upstream as it stands then ([`release_parked`]). An upstream rerun
+//! resets a paused pair's finished tasks straight to that parked status.
+//!
+//! All helpers run inside the caller's transaction.
+use diesel::result::Error;
+use diesel::sql_types::{Array, BigInt, Integer};
+use diesel::*;
+
+use crate::helpers::TaskStatus;
+use crate::helpers::fingerprint::details_fingerprint;
+use crate::models::{NewLogInvalid, Service};
+use crate::schema::{log_errors, log_fatals, log_infos, log_invalids, log_warnings, task_runtimes};
+
+/// The Invalid message category/what recorded on a downstream task whose upstream failed.
+const UPSTREAM_FAILED: (&str, &str) = ("cortex", "upstream_failed");
+/// Chunk size for the `= ANY($1)` id lists (one array bind each, but keep statements bounded).
+const ID_CHUNK: usize = 50_000;
+/// The corpora whose upstream tasks serve corpus `$1`: itself and, for a sandbox, its parent.
+const UPSTREAM_CORPORA: &str = "SELECT id FROM corpora WHERE id = $1 \
+   UNION SELECT parent_corpus_id FROM corpora WHERE id = $1";
+
+#[derive(QueryableByName)]
+struct SettledRow {
+  #[diesel(sql_type = BigInt)]
+  id: i64,
+  #[diesel(sql_type = Integer)]
+  status: i32,
+  #[diesel(sql_type = diesel::sql_types::Text)]
+  upstream: String,
+}
+
+#[derive(QueryableByName)]
+struct ResetRow {
+  #[diesel(sql_type = BigInt)]
+  id: i64,
+  #[diesel(sql_type = Integer)]
+  service_id: i32,
+}
+
+/// Refuses to activate a pipeline service on a corpus its upstream is not activated on (nor, for a
+/// sandbox, its parent) — its tasks would await an upstream result that can never arrive. `Ok` for
+/// a non-pipeline service.
+pub(crate) fn check_upstream_activated(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service: &Service,
+) -> Result<(), Error> {
+  use crate::schema::{corpora, tasks};
+  let Some(upstream_name) = service.upstream() else {
+    return Ok(());
+  };
+  let upstream = Service::find_by_name(upstream_name, connection).map_err(|_| {
+    Error::QueryBuilderError(
+      format!(
+        "service {:?} takes its input from {upstream_name:?}, which is not a registered service",
+        service.name
+      )
+      .into(),
+    )
+  })?;
+  // A sandbox may consume its parent corpus's upstream results.
+  let parent_id: Option<i32> = corpora::table
+    .find(corpus_id)
+    .select(corpora::parent_corpus_id)
+    .first(connection)?;
+  let candidates: Vec<i32> = std::iter::once(corpus_id).chain(parent_id).collect();
+  let activated: bool = select(exists(
+    tasks::table
+      .filter(tasks::service_id.eq(upstream.id))
+      .filter(tasks::corpus_id.eq_any(candidates)),
+  ))
+  .get_result(connection)?;
+  if activated {
+    Ok(())
+  } else {
+    Err(Error::QueryBuilderError(
+      format!(
+        "service {:?} takes its input from {:?}, which is not activated on this corpus — \
+         activate {:?} first",
+        service.name, upstream.name, upstream.name
+      )
+      .into(),
+    ))
+  }
+}
+
+/// Moves `service`'s tasks on `corpus_id` currently in `from_status` to
+/// [`TaskStatus::AWAITING_UPSTREAM`] unless their upstream task for the same entry already has a
+/// successful result; entries whose upstream already failed are settled Invalid straight away.
+/// A no-op for a non-pipeline service (or an upstream that is no longer registered). Returns the
+/// number of tasks held back.
+pub(crate) fn hold_for_upstream(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service: &Service,
+  from_status: i32,
+) -> Result<usize, Error> {
+  let Some(upstream_name) = service.upstream() else {
+    return Ok(0);
+  };
+  let Ok(upstream) = Service::find_by_name(upstream_name, connection) else {
+    return Ok(0);
+  };
+  // Rides the `(entry, service_id, corpus_id)` unique index for the per-entry upstream probe.
+  let held = sql_query(format!(
+    "UPDATE tasks d SET status = $4 \
+     WHERE d.corpus_id = $1 AND d.service_id = $2 AND d.status = $3 \
+       AND NOT EXISTS ( \
+         SELECT 1 FROM tasks u \
+         WHERE u.entry = d.entry AND u.service_id = $5 AND u.corpus_id IN ({UPSTREAM_CORPORA}) \
+           AND u.status BETWEEN $6 AND $7)"
+  ))
+  .bind::<Integer, _>(corpus_id)
+  .bind::<Integer, _>(service.id)
+  .bind::<Integer, _>(from_status)
+  .bind::<Integer, _>(TaskStatus::AWAITING_UPSTREAM.raw())
+  .bind::<Integer, _>(upstream.id)
+  .bind::<Integer, _>(TaskStatus::Error.raw())
+  .bind::<Integer, _>(TaskStatus::NoProblem.raw())
+  .execute(connection)?;
+  if held > 0 {
+    // Entries whose upstream task already failed will never get an input: settle them now. The
+    // same corpora are searched as for the hold, so a sandbox whose parent's upstream failed
+    // settles too — unless the sandbox still has an upstream task of its own pending (TODO,
+    // leased or itself awaiting), whose finalize releases the entry instead.
+    let settled: Vec<SettledRow> = sql_query(format!(
+      "UPDATE tasks d SET status = $5 \
+       WHERE d.corpus_id = $1 AND d.service_id = $2 AND d.status = $3 \
+         AND EXISTS ( \
+           SELECT 1 FROM tasks u \
+           WHERE u.entry = d.entry AND u.service_id = $4 AND u.corpus_id IN ({UPSTREAM_CORPORA}) \
+             AND u.status BETWEEN $5 AND $7) \
+         AND NOT EXISTS ( \
+           SELECT 1 FROM tasks u \
+           WHERE u.entry = d.entry AND u.service_id = $4 AND u.corpus_id IN ({UPSTREAM_CORPORA}) \
+             AND (u.status >= $8 OR u.status = $3)) \
+       RETURNING d.id, d.status, $6::text AS upstream"
+    ))
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Integer, _>(service.id)
+    .bind::<Integer, _>(TaskStatus::AWAITING_UPSTREAM.raw())
+    .bind::<Integer, _>(upstream.id)
+    .bind::<Integer, _>(TaskStatus::Invalid.raw())
+    .bind::<diesel::sql_types::Text, _>(&upstream.name)
+    .bind::<Integer, _>(TaskStatus::Fatal.raw())
+    .bind::<Integer, _>(TaskStatus::TODO.raw())
+    .load(connection)?;
+    record_upstream_failures(connection, &settled)?;
+  }
+  Ok(held)
+}
+
+/// Releases the downstream tasks waiting on the just-finalized upstream tasks `upstream_task_ids`:
+/// to TODO where the upstream succeeded, to Invalid (with an `upstream_failed` message) where it
+/// ended Fatal/Invalid. Called by the finalize path in the same transaction as the status update;
+/// one statement per id chunk, so a non-pipeline burst costs a single index probe per chunk.
+pub(crate) fn advance_downstream(
+  connection: &mut PgConnection,
+  upstream_task_ids: &[i64],
+) -> Result<usize, Error> {
+  let mut advanced = 0;
+  for ids in upstream_task_ids.chunks(ID_CHUNK) {
+    let settled: Vec<SettledRow> = sql_query(
+      "UPDATE tasks d \
+       SET status = CASE WHEN u.status BETWEEN $5 AND $6 THEN $2 ELSE $3 END \
+       FROM tasks u \
+       JOIN services us ON us.id = u.service_id \
+       JOIN services ds ON ds.inputconverter = us.name AND ds.id <> us.id \
+       WHERE u.id = ANY($1) AND u.status BETWEEN $3 AND $6 \
+         AND d.entry = u.entry AND d.service_id = ds.id \
+         AND (d.corpus_id = u.corpus_id \
+              OR d.corpus_id IN (SELECT id FROM corpora WHERE parent_corpus_id = u.corpus_id)) \
+         AND d.status = $4 \
+       RETURNING d.id, d.status, us.name AS upstream",
+    )
+    .bind::<Array<BigInt>, _>(ids)
+    .bind::<Integer, _>(TaskStatus::TODO.raw())
+    .bind::<Integer, _>(TaskStatus::Invalid.raw())
+    .bind::<Integer, _>(TaskStatus::AWAITING_UPSTREAM.raw())
+    .bind::<Integer, _>(TaskStatus::Error.raw())
+    .bind::<Integer, _>(TaskStatus::NoProblem.raw())
+    .load(connection)?;
+    advanced += settled.len();
+    record_upstream_failures(connection, &settled)?;
+  }
+  Ok(advanced)
+}
+
+/// Settles `service`'s tasks on `corpus_id` parked by a pause
+/// ([`TaskStatus::PAUSED_AWAITING_UPSTREAM`]) when their run resumes: back to awaiting where the
+/// upstream still has no successful result (settled Invalid where it failed meanwhile), to TODO
+/// where it has one. Returns the number released to TODO.
+pub(crate) fn release_parked(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  service: &Service,
+) -> Result<usize, Error> {
+  use crate::schema::tasks;
+  let parked = TaskStatus::PAUSED_AWAITING_UPSTREAM.raw();
+  hold_for_upstream(connection, corpus_id, service, parked)?;
+  update(tasks::table)
+    .filter(tasks::corpus_id.eq(corpus_id))
+    .filter(tasks::service_id.eq(service.id))
+    .filter(tasks::status.eq(parked))
+    .set(tasks::status.eq(TaskStatus::TODO.raw()))
+    .execute(connection)
+}
+
+/// Resets the downstream closure of a rerun: every task of a service downstream of `upstream`
+/// (transitively, through services whose `on_upstream_rerun` is `rerun`) on the same corpus and
+/// entries as `upstream`'s tasks currently in `mark` goes back to
+/// [`TaskStatus::AWAITING_UPSTREAM`] — [`TaskStatus::PAUSED_AWAITING_UPSTREAM`] on a paused pair,
+/// one holding paused or parked tasks — with its logs and runtime rows cleared and its cached
+/// report grains invalidated. In-flight downstream leases are left alone (their finalize re-sets
+/// the status; the reset happens again on the next rerun). Returns the number of tasks reset.
+pub(crate) fn invalidate_downstream(
+  connection: &mut PgConnection,
+  corpus_id: i32,
+  upstream: &Service,
+  mark: i32,
+) -> Result<usize, Error> {
+  // `UNION` (not `UNION ALL`) deduplicates the recursive frontier, so a misconfigured cycle in
+  // the `inputconverter` graph terminates instead of recursing forever.
+  let reset: Vec<ResetRow> = sql_query(
+    "WITH RECURSIVE downstream(id, name) AS ( \
+       SELECT s.id, s.name FROM services s \
+       WHERE s.inputconverter = $1 AND s.id <> $2 AND s.on_upstream_rerun = 'rerun' \
+       UNION \
+       SELECT s.id, s.name FROM services s JOIN downstream p ON s.inputconverter = p.name \
+       WHERE s.on_upstream_rerun = 'rerun' \
+     ) \
+     UPDATE tasks d \
+     SET status = CASE WHEN EXISTS ( \
+         SELECT 1 FROM tasks p \
+         WHERE p.corpus_id = d.corpus_id AND p.service_id = d.service_id \
+           AND p.status IN ($8, $9)) \
+       THEN $9 ELSE $5 END \
+     FROM tasks u \
+     WHERE u.corpus_id = $3 AND u.service_id = $2 AND u.status = $4 \
+       AND d.entry = u.entry AND d.corpus_id = u.corpus_id \
+       AND d.service_id IN (SELECT id FROM downstream) AND d.service_id <> $2 \
+       AND d.status BETWEEN $6 AND $7 \
+     RETURNING d.id, d.service_id",
+  )
+  .bind::<diesel::sql_types::Text, _>(&upstream.name)
+  .bind::<Integer, _>(upstream.id)
+  .bind::<Integer, _>(corpus_id)
+  .bind::<Integer, _>(mark)
+  .bind::<Integer, _>(TaskStatus::AWAITING_UPSTREAM.raw())
+  .bind::<Integer, _>(TaskStatus::Invalid.raw())
+  .bind::<Integer, _>(TaskStatus::TODO.raw())
+  .bind::<Integer, _>(TaskStatus::PAUSED.raw())
+  .bind::<Integer, _>(TaskStatus::PAUSED_AWAITING_UPSTREAM.raw())
+  .load(connection)?;
+  let ids: Vec<i64> = reset.iter().map(|row| row.id).collect();
+  for chunk in ids.chunks(ID_CHUNK) {
+    delete(log_infos::table.filter(log_infos::task_id.eq_any(chunk))).execute(connection)?;
+    delete(log_warnings::table.filter(log_warnings::task_id.eq_any(chunk))).execute(connection)?;
+    delete(log_errors::table.filter(log_errors::task_id.eq_any(chunk))).execute(connection)?;
+    delete(log_fatals::table.filter(log_fatals::task_id.eq_any(chunk))).execute(connection)?;
+    delete(log_invalids::table.filter(log_invalids::task_id.eq_any(chunk))).execute(connection)?;
+    delete(task_runtimes::table.filter(task_runtimes::task_id.eq_any(chunk)))
+      .execute(connection)?;
+  }
+  let mut services: Vec<i32> = reset.iter().map(|row| row.service_id).collect();
+  services.sort_unstable();
+  services.dedup();
+  for service_id in services {
+    super::rollup::invalidate_scope(connection, corpus_id, service_id)?;
+  }
+  Ok(reset.len())
+}
+
+/// Records the `upstream_failed` Invalid message for every settled row that ended Invalid.
+fn record_upstream_failures(
+  connection: &mut PgConnection,
+  settled: &[SettledRow],
+) -> Result<(), Error> {
+  let invalid = TaskStatus::Invalid.raw();
+  let messages: Vec<NewLogInvalid> = settled
+    .iter()
+    .filter(|row| row.status == invalid)
+    .map(|row| {
+      let details = format!(
+        "upstream service {:?} produced no usable result for this document",
+        row.upstream
+      );
+      NewLogInvalid {
+        task_id: row.id,
+        category: UPSTREAM_FAILED.0.to_string(),
+        what: UPSTREAM_FAILED.1.to_string(),
+        fingerprint: details_fingerprint(&details),
+        details,
+      }
+    })
+    .collect();
+  for chunk in messages.chunks(13_000) {
+    insert_into(log_invalids::table)
+      .values(chunk)
+      .execute(connection)?;
+  }
+  Ok(())
+}
@@ -0,0 +1,515 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Regression detection between consecutive runs.** When a run closes on drain
+//! ([`super::Backend::complete_run_if_drained`]), its message classes — the report ladder's
+//! `(severity, category, what)` grain — are frozen into `run_message_classes` (their categories
+//! into `run_message_categories`; both feed the trends of [`super::class_trends`]) right there,
+//! next to its tasks' closing statuses, and the run is flagged `regressions_pending`. A background
+//! job ([`crate::jobs::spawn_run_regressions`], retried by the dispatcher while the flag is set)
+//! then compares the frozen run with the one before it:
+//!
+//! * **worse documents** — tasks whose status at the close is worse than at the previous run's
+//!   baseline snapshot (the same baseline as the live run-diff: each task's last snapshot taken
+//!   before this run started), with the transitions they made and a few example documents;
+//! * **new classes** — classes this run has that the previous run did not;
+//! * **jumped classes** — classes whose task count grew past [`RegressionThresholds`].
+//!
+//! The summary is stored on the run (`historical_runs.regressions`) as a [`RunRegressions`], so the
+//! runs page, `GET /api/runs/<c>/<s>` and `cortex runs --check` read it without recomputing. The
+//! `info` slice is left out: informational messages are not regressions, and it is by far the
+//! heaviest slice to aggregate.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Integer, Text, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use super::rollup::severity_scope;
+use crate::helpers::{TaskStatus, entry_document_name};
+use crate::models::HistoricalRun;
+use crate::schema::historical_runs;
+
+/// How long the class snapshot, or the comparison, of one closing run may run before Postgres
+/// cancels it (the run stays closed either way; a cancelled comparison is retried, a cancelled
+/// snapshot leaves the run without a summary).
+pub const REGRESSION_QUERY_BUDGET_MS: u32 = 120_000;
+
+/// The message severities whose classes are snapshotted and compared (and charted as trends).
+pub const CLASS_SEVERITIES: [&str; 4] = ["warning", "error", "fatal", "invalid"];
+
+/// New and jumped classes listed per summary (the counts cover them all).
+const LISTED_CLASSES: i64 = 50;
+
+/// Example documents listed for the worse documents.
+const LISTED_EXAMPLES: i64 = 20;
+
+/// When a class's growth between two runs is a regression: it must grow by at least
+/// `jump_percent` percent of its previous task count **and** by at least `jump_min_tasks` tasks.
+/// Configured as `dispatcher.regression_jump_percent` / `dispatcher.regression_jump_min_tasks`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegressionThresholds {
+  /// Minimum growth, in percent of the previous task count.
+  pub jump_percent: i64,
+  /// Minimum growth, in tasks.
+  pub jump_min_tasks: i64,
+}
+
+impl RegressionThresholds {
+  /// The thresholds of the running configuration.
+  pub fn from_config() -> Self {
+    let dispatcher = &crate::config::config().dispatcher;
+    RegressionThresholds {
+      jump_percent: dispatcher.regression_jump_percent,
+      jump_min_tasks: dispatcher.regression_jump_min_tasks,
+    }
+  }
+
+  /// Whether a class going from `previous` to `current` tasks jumped. The comparison query applies
+  /// the same rule in SQL.
+  pub fn is_jump(&self, previous: i64, current: i64) -> bool {
+    let growth = current - previous;
+    growth > 0 && growth >= self.jump_min_tasks && growth * 100 >= previous * self.jump_percent
+  }
+}
+
+/// How many documents made one worsening status transition.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct WorseTransition {
+  /// Status at the previous run's baseline.
+  pub previous_status: String,
+  /// Status at the end of this run.
+  pub current_status: String,
+  /// Documents making the transition.
+  pub task_count: i64,
+}
+
+/// A message class that is new in a run, or whose task count jumped.
+#[derive(
+  Clone,
+  Debug,
+  Default,
+  PartialEq,
+  Eq,
+  Serialize,
+  Deserialize,
+  QueryableByName,
+  schemars::JsonSchema,
+)]
+pub struct ClassChange {
+  /// Message severity.
+  #[diesel(sql_type = Text)]
+  pub severity: String,
+  /// Message category.
+  #[diesel(sql_type = Text)]
+  pub category: String,
+  /// Message `what`.
+  #[diesel(sql_type = Text)]
+  pub what: String,
+  /// Tasks with the class in the previous run (0 for a new class).
+  #[diesel(sql_type = BigInt)]
+  pub previous_tasks: i64,
+  /// Tasks with the class in this run.
+  #[diesel(sql_type = BigInt)]
+  pub current_tasks: i64,
+}
+
+/// The regression summary of a closed run against the run before it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct RunRegressions {
+  /// The `public_id` of the run whose classes this one was compared with; `None` if no earlier run
+  /// has a class snapshot (the first run, or one closed before snapshots existed), in which case
+  /// no class can be new or jumped.
+  pub baseline_run: Option<String>,
+  /// Documents with a status at both the baseline and the end of this run.
+  pub compared_documents: i64,
+  /// Of those, documents whose status got worse (invalid is a reclassification, not counted).
+  pub worse_documents: i64,
+  /// The worsening transitions, by document count.
+  pub worse_transitions: Vec<WorseTransition>,
+  /// A few of the worse documents, by name.
+  pub worse_examples: Vec<String>,
+  /// Classes this run has that the baseline run did not.
+  pub new_class_count: i64,
+  /// The largest of them, by task count.
+  pub new_classes: Vec<ClassChange>,
+  /// Classes whose task count jumped past the thresholds.
+  pub jumped_class_count: i64,
+  /// The largest jumps, by growth.
+  pub jumped_classes: Vec<ClassChange>,
+  /// The `jump_percent` threshold applied.
+  pub jump_percent: i64,
+  /// The `jump_min_tasks` threshold applied.
+  pub jump_min_tasks: i64,
+}
+
+impl RunRegressions {
+  /// Whether the run regressed in any of the three ways.
+  pub fn regressed(&self) -> bool {
+    self.worse_documents > 0 || self.new_class_count > 0 || self.jumped_class_count > 0
+  }
+
+  /// The summary stored on a run, if it has one.
+  pub fn of(run: &HistoricalRun) -> Option<RunRegressions> {
+    run
+      .regressions
+      .clone()
+      .and_then(|value| serde_json::from_value(value).ok())
+  }
+}
+
+#[derive(QueryableByName)]
+struct TransitionRow {
+  #[diesel(sql_type = Integer)]
+  previous_status: i32,
+  #[diesel(sql_type = Integer)]
+  current_status: i32,
+  #[diesel(sql_type = BigInt)]
+  task_count: i64,
+}
+
+#[derive(QueryableByName)]
+struct EntryRow {
+  #[diesel(sql_type = Text)]
+  entry: String,
+}
+
+#[derive(QueryableByName)]
+struct BaselineRun {
+  #[diesel(sql_type = Integer)]
+  id: i32,
+  #[diesel(sql_type = Text)]
+  public_id: String,
+}
+
+#[derive(QueryableByName)]
+struct ListedClass {
+  #[diesel(sql_type = BigInt)]
+  total: i64,
+  #[diesel(embed)]
+  change: ClassChange,
+}
+
+/// Each task of the scope `($1, $2)` with its status at the run's close — its first snapshot taken
+/// at or after `$4` (the run's end: the one taken on drain) — paired with its baseline status: its
+/// last snapshot taken before `$3` (the run's start), as in the live run-diff. Reading the close
+/// from the snapshot keeps a rerun started before the comparison runs out of it. Both sides are
+/// limited to the graded terminal statuses (no problem … fatal); `e` is the task, for its entry.
+const BASELINE_JOIN: &str = "FROM ( \
+     SELECT DISTINCT ON (h.task_id) h.task_id, h.status \
+     FROM historical_tasks h JOIN tasks tt ON tt.id = h.task_id \
+     WHERE tt.corpus_id = $1 AND tt.service_id = $2 AND h.saved_at >= $4 \
+     ORDER BY h.task_id, h.saved_at \
+   ) t \
+   JOIN ( \
+     SELECT DISTINCT ON (h.task_id) h.task_id, h.status \
+     FROM historical_tasks h JOIN tasks tt ON tt.id = h.task_id \
+     WHERE tt.corpus_id = $1 AND tt.service_id = $2 AND h.saved_at < $3 \
+     ORDER BY h.task_id, h.saved_at DESC \
+   ) b ON b.task_id = t.task_id \
+   JOIN tasks e ON e.id = t.task_id \
+   WHERE t.status BETWEEN -4 AND -1 AND b.status BETWEEN -4 AND -1";
+
+/// Freezes the message classes of a `(corpus, service)` for `run_id`, replacing any it had: each
+/// `(severity, category, what)` into `run_message_classes` and each `(severity, category)` into
+/// `run_message_categories`, from one grouping-sets scan per severity.
+fn snapshot_classes(
+  conn: &mut PgConnection,
+  run_id: i32,
+  corpus_id: i32,
+  service_id: i32,
+) -> QueryResult<()> {
+  for table in ["run_message_classes", "run_message_categories"] {
+    sql_query(format!("DELETE FROM {table} WHERE run_id = $1"))
+      .bind::<Integer, _>(run_id)
+      .execute(conn)?;
+  }
+  for severity in CLASS_SEVERITIES {
+    let Some((table, status_pred)) = severity_scope(severity) else {
+      continue;
+    };
+    // `table` and `status_pred` are compile-time constants (see `severity_scope`). The category
+    // grain is its own grouping set: a task carrying several whats of a category counts once.
+    sql_query(format!(
+      "WITH grouped AS ( \
+         SELECT COALESCE(l.category, '') AS category, COALESCE(l.what, '') AS what, \
+           GROUPING(COALESCE(l.what, '')) AS category_row, \
+           COUNT(DISTINCT l.task_id)::bigint AS task_count, COUNT(*)::bigint AS message_count \
+         FROM tasks t JOIN {table} l ON l.task_id = t.id \
+         WHERE t.corpus_id = $3 AND t.service_id = $4 AND {status_pred} \
+         GROUP BY GROUPING SETS ( \
+           (COALESCE(l.category, ''), COALESCE(l.what, '')), (COALESCE(l.category, ''))) \
+       ), classes AS ( \
+         INSERT INTO run_message_classes \
+           (run_id, severity, category, what, task_count, message_count) \
+         SELECT $1, $2, category, what, task_count, message_count \
+         FROM grouped WHERE category_row = 0 \
+       ) \
+       INSERT INTO run_message_categories \
+         (run_id, severity, category, task_count, message_count) \
+       SELECT $1, $2, category, task_count, message_count FROM grouped WHERE category_row = 1"
+    ))
+    .bind::<Integer, _>(run_id)
+    .bind::<Text, _>(severity)
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Integer, _>(service_id)
+    .execute(conn)?;
+  }
+  Ok(())
+}
+
+/// Compares the run `run_id`, spanning `(start_time, end_time)`, of a `(corpus, service)` with the
+/// run before it. Its classes and closing statuses must already be snapshotted.
+fn summarize(
+  conn: &mut PgConnection,
+  run_id: i32,
+  (start_time, end_time): (NaiveDateTime, NaiveDateTime),
+  corpus_id: i32,
+  service_id: i32,
+  thresholds: RegressionThresholds,
+) -> QueryResult<RunRegressions> {
+  let mut summary = RunRegressions {
+    jump_percent: thresholds.jump_percent,
+    jump_min_tasks: thresholds.jump_min_tasks,
+    ..RunRegressions::default()
+  };
+
+  // Worse documents: the transitions to a lower (worse) status since the baseline.
+  let transitions: Vec<TransitionRow> = sql_query(format!(
+    "SELECT b.status AS previous_status, t.status AS current_status, \
+       COUNT(*)::bigint AS task_count {BASELINE_JOIN} \
+     GROUP BY b.status, t.status"
+  ))
+  .bind::<Integer, _>(corpus_id)
+  .bind::<Integer, _>(service_id)
+  .bind::<Timestamp, _>(start_time)
+  .bind::<Timestamp, _>(end_time)
+  .load(conn)?;
+  summary.compared_documents = transitions.iter().map(|row| row.task_count).sum();
+  let mut worse: Vec<&TransitionRow> = transitions
+    .iter()
+    .filter(|row| row.current_status < row.previous_status)
+    .collect();
+  worse.sort_by(|a, b| b.task_count.cmp(&a.task_count));
+  summary.worse_documents = worse.iter().map(|row| row.task_count).sum();
+  summary.worse_transitions = worse
+    .into_iter()
+    .map(|row| WorseTransition {
+      previous_status: TaskStatus::from_raw(row.previous_status).to_key(),
+      current_status: TaskStatus::from_raw(row.current_status).to_key(),
+      task_count: row.task_count,
+    })
+    .collect();
+  if summary.worse_documents > 0 {
+    let examples: Vec<EntryRow> = sql_query(format!(
+      "SELECT e.entry {BASELINE_JOIN} AND t.status < b.status ORDER BY e.entry LIMIT $5"
+    ))
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Integer, _>(service_id)
+    .bind::<Timestamp, _>(start_time)
+    .bind::<Timestamp, _>(end_time)
+    .bind::<BigInt, _>(LISTED_EXAMPLES)
+    .load(conn)?;
+    summary.worse_examples = examples
+      .iter()
+      .map(|row| entry_document_name(&row.entry))
+      .collect();
+  }
+
+  // New and jumped classes, against the latest earlier run with a class snapshot.
+  let baseline: Option<BaselineRun> = sql_query(
+    "SELECT r.id, r.public_id::text AS public_id FROM historical_runs r \
+     WHERE r.corpus_id = $1 AND r.service_id = $2 AND r.id <> $3 AND r.start_time < $4 \
+       AND EXISTS (SELECT 1 FROM run_message_classes c WHERE c.run_id = r.id) \
+     ORDER BY r.start_time DESC LIMIT 1",
+  )
+  .bind::<Integer, _>(corpus_id)
+  .bind::<Integer, _>(service_id)
+  .bind::<Integer, _>(run_id)
+  .bind::<Timestamp, _>(start_time)
+  .get_result(conn)
+  .optional()?;
+  let Some(baseline) = baseline else {
+    return Ok(summary);
+  };
+  summary.baseline_run = Some(baseline.public_id);
+
+  let new_classes: Vec<ListedClass> = sql_query(
+    "SELECT COUNT(*) OVER ()::bigint AS total, c.severity, c.category, c.what, \
+       0::bigint AS previous_tasks, c.task_count AS current_tasks \
+     FROM run_message_classes c \
+     WHERE c.run_id = $1 AND NOT EXISTS ( \
+       SELECT 1 FROM run_message_classes p WHERE p.run_id = $2 \
+         AND p.severity = c.severity AND p.category = c.category AND p.what = c.what) \
+     ORDER BY c.task_count DESC, c.severity, c.category, c.what LIMIT $3",
+  )
+  .bind::<Integer, _>(run_id)
+  .bind::<Integer, _>(baseline.id)
+  .bind::<BigInt, _>(LISTED_CLASSES)
+  .load(conn)?;
+  summary.new_class_count = new_classes.first().map_or(0, |row| row.total);
+  summary.new_classes = new_classes.into_iter().map(|row| row.change).collect();
+
+  // The SQL twin of `RegressionThresholds::is_jump`.
+  let jumped_classes: Vec<ListedClass> = sql_query(
+    "SELECT COUNT(*) OVER ()::bigint AS total, c.severity, c.category, c.what, \
+       p.task_count AS previous_tasks, c.task_count AS current_tasks \
+     FROM run_message_classes c \
+     JOIN run_message_classes p ON p.run_id = $2 \
+       AND p.severity = c.severity AND p.category = c.category AND p.what = c.what \
+     WHERE c.run_id = $1 AND c.task_count > p.task_count \
+       AND c.task_count - p.task_count >= $3 \
+       AND (c.task_count - p.task_count) * 100 >= p.task_count * $4 \
+     ORDER BY c.task_count - p.task_count DESC, c.severity, c.category, c.what LIMIT $5",
+  )
+  .bind::<Integer, _>(run_id)
+  .bind::<Integer, _>(baseline.id)
+  .bind::<BigInt, _>(thresholds.jump_min_tasks)
+  .bind::<BigInt, _>(thresholds.jump_percent)
+  .bind::<BigInt, _>(LISTED_CLASSES)
+  .load(conn)?;
+  summary.jumped_class_count = jumped_classes.first().map_or(0, |row| row.total);
+  summary.jumped_classes = jumped_classes.into_iter().map(|row| row.change).collect();
+  Ok(summary)
+}
+
+/// Freezes the closed run `run_id` of `(corpus_id, service_id)` for its regression summary: its
+/// message classes as they stand at the close, under [`REGRESSION_QUERY_BUDGET_MS`], and flags the
+/// run `regressions_pending` for [`record_run_regressions`] — in one transaction, so a run is never
+/// flagged without its classes. Called on drain, right after the tasks' closing snapshot.
+pub(crate) fn freeze_run_classes(
+  connection: &mut PgConnection,
+  run_id: i32,
+  corpus_id: i32,
+  service_id: i32,
+) -> QueryResult<()> {
+  connection.transaction::<_, diesel::result::Error, _>(|conn| {
+    sql_query(format!(
+      "SET LOCAL statement_timeout = {REGRESSION_QUERY_BUDGET_MS}"
+    ))
+    .execute(conn)?;
+    snapshot_classes(conn, run_id, corpus_id, service_id)?;
+    diesel::update(historical_runs::table.filter(historical_runs::id.eq(run_id)))
+      .set(historical_runs::regressions_pending.eq(true))
+      .execute(conn)?;
+    Ok(())
+  })
+}
+
+/// The closed runs whose regression summary is still owed, oldest first — what the dispatcher
+/// retries [`crate::jobs::spawn_run_regressions`] for.
+pub fn pending_regression_runs(connection: &mut PgConnection) -> QueryResult<Vec<i32>> {
+  historical_runs::table
+    .filter(historical_runs::regressions_pending.eq(true))
+    .filter(historical_runs::end_time.is_not_null())
+    .order(historical_runs::id.asc())
+    .select(historical_runs::id)
+    .load(connection)
+}
+
+/// Compares the closed run `run_id`, as frozen on drain ([`freeze_run_classes`] and the tasks'
+/// closing snapshot), with the run before it, and stores the summary on the run, clearing its
+/// `regressions_pending` flag — in one transaction under [`REGRESSION_QUERY_BUDGET_MS`]. Reads
+/// only frozen state, so it may run (and be retried) any time after the close. `None` if there is
+/// no such run, or it is still open.
+pub fn record_run_regressions(
+  connection: &mut PgConnection,
+  run_id: i32,
+  thresholds: RegressionThresholds,
+) -> QueryResult<Option<RunRegressions>> {
+  let run: Option<(i32, i32, NaiveDateTime, Option<NaiveDateTime>)> = historical_runs::table
+    .filter(historical_runs::id.eq(run_id))
+    .select((
+      historical_runs::corpus_id,
+      historical_runs::service_id,
+      historical_runs::start_time,
+      historical_runs::end_time,
+    ))
+    .first(connection)
+    .optional()?;
+  let Some((corpus_id, service_id, start_time, Some(end_time))) = run else {
+    return Ok(None);
+  };
+  connection.transaction::<_, diesel::result::Error, _>(|conn| {
+    sql_query(format!(
+      "SET LOCAL statement_timeout = {REGRESSION_QUERY_BUDGET_MS}"
+    ))
+    .execute(conn)?;
+    let summary = summarize(
+      conn,
+      run_id,
+      (start_time, end_time),
+      corpus_id,
+      service_id,
+      thresholds,
+    )?;
+    let value = serde_json::to_value(&summary)
+      .map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?;
+    diesel::update(historical_runs::table.filter(historical_runs::id.eq(run_id)))
+      .set((
+        historical_runs::regressions.eq(value),
+        historical_runs::regressions_pending.eq(false),
+      ))
+      .execute(conn)?;
+    Ok(Some(summary))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{RegressionThresholds, RunRegressions};
+
+  #[test]
+  fn a_jump_needs_both_the_percentage_and_the_floor() {
+    let thresholds = RegressionThresholds {
+      jump_percent: 50,
+      jump_min_tasks: 10,
+    };
+    assert!(thresholds.is_jump(20, 30), "+10 tasks is +50%");
+    assert!(!thresholds.is_jump(20, 29), "+9 tasks is under the floor");
+    assert!(
+      !thresholds.is_jump(100, 140),
+      "+40% is under the percentage"
+    );
+    assert!(
+      thresholds.is_jump(0, 10),
+      "a class from nothing grows by any percentage"
+    );
+    assert!(!thresholds.is_jump(30, 30), "no growth is no jump");
+    let lenient = RegressionThresholds {
+      jump_percent: 0,
+      jump_min_tasks: 0,
+    };
+    assert!(
+      !lenient.is_jump(5, 5),
+      "even at zero thresholds, a jump must grow"
+    );
+    assert!(lenient.is_jump(5, 6));
+  }
+
+  #[test]
+  fn regressed_is_any_of_the_three_signals() {
+    assert!(!RunRegressions::default().regressed());
+    for summary in [
+      RunRegressions {
+        worse_documents: 1,
+        ..Default::default()
+      },
+      RunRegressions {
+        new_class_count: 1,
+        ..Default::default()
+      },
+      RunRegressions {
+        jumped_class_count: 1,
+        ..Default::default()
+      },
+    ] {
+      assert!(summary.regressed());
+    }
+  }
+}
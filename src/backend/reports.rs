@@ -284,6 +284,27 @@ pub(crate) fn progress_report(
   stats_hash
 }
 
+/// The outstanding backlog of a `(corpus, service)` per leasing priority: `(priority, tasks)` over
+/// the TODO + Queued tasks (`status >= 0`; Blocked is paused, not backlog), highest priority first
+/// — the order the dispatcher works through them. Empty once the run has drained.
+pub(crate) fn priority_backlog(
+  connection: &mut PgConnection,
+  corpus: i32,
+  service: i32,
+) -> Vec<(i32, i64)> {
+  use crate::schema::tasks::{corpus_id, priority, service_id, status};
+  use diesel::dsl::count_star;
+  tasks::table
+    .filter(service_id.eq(service))
+    .filter(corpus_id.eq(corpus))
+    .filter(status.ge(TaskStatus::TODO.raw()))
+    .group_by(priority)
+    .select((priority, count_star()))
+    .order(priority.desc())
+    .load(connection)
+    .unwrap_or_default()
+}
+
 /// Computes a `(corpus, service)` progress report at the granularity implied by the optional
 /// `severity`/`category`/`what` selectors.
 ///
@@ -74,7 +74,7 @@ pub struct ReportSummaryRow {
 /// non-drill-down key, so the report yields no rows — exactly what the matview lookup did for an
 /// absent severity. Both returned strings are compile-time constants (never user input), so they
 /// are safe to interpolate into the query text below.
-fn severity_scope(severity: &str) -> Option<(&'static str, &'static str)> {
+pub(super) fn severity_scope(severity: &str) -> Option<(&'static str, &'static str)> {
   match severity {
     "warning" => Some(("log_warnings", "t.status = -2")),
     "error" => Some(("log_errors", "t.status = -3")),
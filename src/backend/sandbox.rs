@@ -15,14 +15,17 @@
 //! paths; nothing is copied) and the carved set is a **one-time snapshot** evaluated at creation.
 //! See `docs/archive/SANDBOX_CORPORA.md`.
 
+use diesel::pg::Pg;
 use diesel::result::Error;
 use diesel::sql_types::Text;
 use diesel::*;
 use serde::{Deserialize, Serialize};
 
+use super::documents::DocumentFilter;
+use super::search::{message_exists_clause, message_tables, validate_message_text};
 use crate::concerns::CortexInsertable;
 use crate::helpers::TaskStatus;
-use crate::models::{Corpus, NewSandboxCorpus};
+use crate::models::{ActivationWeight, Corpus, Document, NewSandboxCorpus};
 
 /// The filter that defines a sandbox: a slice of the parent corpus addressed by independent,
 /// **intersected** task-status and message (`severity`/`category`/`what`) dimensions (Model C),
@@ -48,6 +51,11 @@ pub struct SandboxSelection {
   /// optional `what` narrowing within the category (needs `category`)
   #[serde(default)]
   pub what: Option<String>,
+  /// optional full-text message query (see [`super::search_messages`]): the carve keeps tasks that
+  /// emitted a message matching it — of `message_severity` if set, else of any severity — so a
+  /// search's results can be carved as they are
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub text: Option<String>,
   /// optional substring the parent `entry` must contain, matched at any position (`entry LIKE
   /// '%…%'`). Include slashes for precision — `/2605/` carves exactly the arXiv month (a bare
   /// `2605.` also matches mid-id paths like `…0302605.zip`). `None`/empty = no narrowing.
@@ -61,11 +69,15 @@ pub struct SandboxSelection {
   /// the status/message split still render their provenance. Never set by new carves.
   #[serde(default, skip_serializing_if = "Option::is_none")]
   pub severity: Option<String>,
+  /// optional narrowing by the facts of the entry's document (month, primary category, source
+  /// size, file count) — stored flat alongside the fields above
+  #[serde(flatten)]
+  pub document: DocumentFilter,
 }
 
 /// The message-severity → `log_*` table map (includes `info`, which has no [`TaskStatus`]). The
 /// only thing the carve interpolates into SQL, and it comes from this fixed map — never user input.
-fn message_log_table(message_severity: &str) -> Option<&'static str> {
+pub(crate) fn message_log_table(message_severity: &str) -> Option<&'static str> {
   match message_severity {
     "info" => Some("log_infos"),
     "warning" => Some("log_warnings"),
@@ -77,12 +89,13 @@ fn message_log_table(message_severity: &str) -> Option<&'static str> {
 }
 
 /// A validated, resolved sandbox filter: the task-status raw int to match (if any) and the message
-/// `log_*` table to look in (if any). The carve uses both; the pre-flight just checks for `Err`.
+/// `log_*` tables to look in (if any). The carve uses both; the pre-flight just checks for `Err`.
 pub struct ResolvedFilter {
   /// raw task-status int to match `t.status` against, or `None` for "any status"
   pub status_raw: Option<i32>,
-  /// the `log_*` table a message filter looks in, or `None` for "no message filter"
-  pub message_table: Option<&'static str>,
+  /// the `(severity, log_* table)` pairs a message filter looks in — the `message_severity`'s, or
+  /// all five for a text query without one — or `None` for "no message filter"
+  pub message_tables: Option<Vec<(&'static str, &'static str)>>,
 }
 
 impl SandboxSelection {
@@ -106,6 +119,9 @@ impl SandboxSelection {
       }
       parts.push(message);
     }
+    if let Some(text) = &self.text {
+      parts.push(format!("text={text:?}"));
+    }
     // Legacy stored selections only had the overloaded `severity` (+ its category/what).
     if parts.is_empty()
       && let Some(severity) = &self.severity
@@ -126,6 +142,9 @@ impl SandboxSelection {
     {
       parts.push(format!("entry~{entry}"));
     }
+    if !self.document.is_empty() {
+      parts.push(self.document.summary());
+    }
     if let Some(n) = self.max_entries.filter(|n| *n > 0) {
       parts.push(format!("limit={n}"));
     }
@@ -137,8 +156,8 @@ impl SandboxSelection {
   /// `status` matches the task's outcome; `message_severity` (+ `category`/`what`) matches a log
   /// message the task emitted, **at any task status** — so `info`+category collects every task
   /// that emitted that info message. `category` needs a `message_severity`; `what` needs a
-  /// `category`; at least one of the two dimensions must be present. The **pre-flight twin** of
-  /// the carve (`start_sandbox` calls it for an immediate `422`).
+  /// `category`; at least one of the two dimensions — or a document filter — must be present. The
+  /// **pre-flight twin** of the carve (`start_sandbox` calls it for an immediate `422`).
   pub fn validate(&self) -> Result<ResolvedFilter, String> {
     let status_raw = match self.status.as_deref() {
       Some(key) => Some(
@@ -164,14 +183,30 @@ impl SandboxSelection {
     if self.what.is_some() && self.category.is_none() {
       return Err("what needs a category".to_string());
     }
-    if status_raw.is_none() && message_table.is_none() {
+    if let Some(text) = &self.text {
+      validate_message_text(text)?;
+    }
+    self.document.validate()?;
+    if status_raw.is_none()
+      && message_table.is_none()
+      && self.text.is_none()
+      && self.document.is_empty()
+    {
       return Err(
-        "a sandbox needs at least a task-status or a message-severity filter".to_string(),
+        "a sandbox needs at least a task-status, a message-severity, a message-text or a document \
+         filter"
+          .to_string(),
       );
     }
+    // A text query without a severity searches the messages of every severity.
+    let message_tables = if message_table.is_some() || self.text.is_some() {
+      message_tables(self.message_severity.as_deref())
+    } else {
+      None
+    };
     Ok(ResolvedFilter {
       status_raw,
-      message_table,
+      message_tables,
     })
   }
 }
@@ -197,7 +232,8 @@ pub struct SandboxOutcome {
 /// severity's `log_*` table (the table name comes from the fixed [`TaskStatus::to_table`] map, so
 /// it is never user-controlled; ids/`category`/`what` are bound parameters). `SELECT DISTINCT`
 /// collapses a parent task carrying several matching messages to a single carved entry — which also
-/// satisfies the `tasks` `UNIQUE(entry, service_id, corpus_id)` constraint.
+/// satisfies the `tasks` `UNIQUE(entry, service_id, corpus_id)` constraint. A `text` query is
+/// matched in the same `EXISTS`, over every `log_*` table when no message severity is given.
 ///
 /// **Output-isolation note:** the sandbox is its own `corpus_id` (own tasks, runs, reports).
 /// Running a *conversion* on it would, today, write result archives to the shared
@@ -214,7 +250,7 @@ pub fn create_sandbox(
   // the `start_sandbox` pre-flight reject the identical set.
   let ResolvedFilter {
     status_raw,
-    message_table,
+    message_tables,
   } = selection
     .validate()
     .map_err(|message| Error::QueryBuilderError(message.into()))?;
@@ -224,8 +260,8 @@ pub fn create_sandbox(
   // slot (`entry LIKE '%…%'`). Include slashes for precision: `/2605/` carves exactly the arXiv
   // month directory, whereas a bare `2605.` also matches mid-id paths like
   // `.../cond-mat0302605/cond-mat0302605.zip` (`0302605.zip` literally contains `2605.`). Always
-  // bound (default `%` = match every entry) so the three SQL branches share one extra bind slot.
-  // Trimmed; blank = no narrowing.
+  // bound (default `%` = match every entry), after the message binds. Trimmed; blank = no
+  // narrowing.
   let entry_pattern = selection
     .entry
     .as_deref()
@@ -268,8 +304,8 @@ pub fn create_sandbox(
     // INTERSECTED: an optional `t.status = <status>` and, independently, an optional EXISTS over
     // the message `log_*` table — so a message filter matches a task **at any status**.
     // Validated ints (ids, status, limit) are inlined (no injection surface); the only user
-    // strings (category / what / entry) are bound; the only interpolated identifier is the
-    // fixed-map `log_*` table name.
+    // strings (category / what / entry / document month and category) are bound, in placeholder
+    // order; the only interpolated identifier is the fixed-map `log_*` table name.
     let sandbox_id = sandbox.id;
     let status_clause = match status_raw {
       Some(status) => format!(" AND t.status = {status}"),
@@ -280,40 +316,43 @@ pub fn create_sandbox(
        SELECT {service_id}, {sandbox_id}, {todo}, t.entry FROM tasks t \
        WHERE t.corpus_id = {parent_id} AND t.service_id = {service_id}{status_clause}"
     );
-    let entry_count = match (
-      message_table,
-      selection.category.as_deref(),
-      selection.what.as_deref(),
-    ) {
-      // No message filter (status-only / entry-only): a plain status + entry scan.
-      (None, _, _) => sql_query(format!("{base} AND t.entry LIKE $1{limit_clause}"))
-        .bind::<Text, _>(&entry_pattern)
-        .execute(t_connection)?,
-      // Message severity, no category: tasks that emitted ANY message of that severity.
-      (Some(table), None, _) => sql_query(format!(
-        "{base} AND EXISTS (SELECT 1 FROM {table} l WHERE l.task_id = t.id) \
-         AND t.entry LIKE $1{limit_clause}"
-      ))
-      .bind::<Text, _>(&entry_pattern)
-      .execute(t_connection)?,
-      // Message severity + category.
-      (Some(table), Some(category), None) => sql_query(format!(
-        "{base} AND EXISTS (SELECT 1 FROM {table} l WHERE l.task_id = t.id AND l.category = $1) \
-         AND t.entry LIKE $2{limit_clause}"
-      ))
-      .bind::<Text, _>(category)
-      .bind::<Text, _>(&entry_pattern)
-      .execute(t_connection)?,
-      // Message severity + category + what.
-      (Some(table), Some(category), Some(what)) => sql_query(format!(
-        "{base} AND EXISTS (SELECT 1 FROM {table} l WHERE l.task_id = t.id AND l.category = $1 \
-         AND l.what = $2) AND t.entry LIKE $3{limit_clause}"
-      ))
-      .bind::<Text, _>(category)
-      .bind::<Text, _>(what)
-      .bind::<Text, _>(&entry_pattern)
-      .execute(t_connection)?,
+    let mut binds: Vec<String> = Vec::new();
+    let message_clause = match &message_tables {
+      None => String::new(),
+      Some(tables) => {
+        let mut bind = |value: &Option<String>| {
+          value.as_ref().map(|value| {
+            binds.push(value.clone());
+            binds.len()
+          })
+        };
+        let category_param = bind(&selection.category);
+        let what_param = bind(&selection.what);
+        let text_param = bind(&selection.text);
+        format!(
+          " AND {}",
+          message_exists_clause(tables, category_param, what_param, text_param)
+        )
+      },
     };
+    binds.push(entry_pattern);
+    let entry_param = binds.len();
+    let (document_clause, document_binds) = selection.document.sql_condition(entry_param + 1);
+    binds.extend(document_binds);
+    let mut carve = sql_query(format!(
+      "{base}{message_clause} AND t.entry LIKE ${entry_param}{document_clause}{limit_clause}"
+    ))
+    .into_boxed::<Pg>();
+    for bind in binds {
+      carve = carve.bind::<Text, _>(bind);
+    }
+    let entry_count = carve.execute(t_connection)?;
+    // The sandbox inherits the facts of its entries, so its own reports group by them too.
+    Document::copy_to_sandbox(t_connection, parent_id, sandbox_id)?;
+
+    // The sandbox's own fair-share weight row, so its campaign is leased alongside (not behind) a
+    // full-corpus run of the same service.
+    ActivationWeight::set(t_connection, sandbox_id, service_id, 1)?;
 
     Ok(SandboxOutcome {
       sandbox,
@@ -338,9 +377,11 @@ mod tests {
       message_severity: message_severity.map(String::from),
       category: category.map(String::from),
       what: what.map(String::from),
+      text: None,
       entry: None,
       max_entries: None,
       severity: None,
+      document: DocumentFilter::default(),
     }
   }
 
@@ -382,6 +423,28 @@ mod tests {
     assert!(sel(Some("info"), None, None, None).validate().is_err());
     // an empty selection (no dimension) is rejected.
     assert!(sel(None, None, None, None).validate().is_err());
+    // a document filter alone is a dimension, and is itself validated.
+    let mut by_month = sel(None, None, None, None);
+    by_month.document.yymm = Some("2605".to_string());
+    assert!(by_month.validate().is_ok());
+    by_month.document.yymm = Some("May 2026".to_string());
+    assert!(by_month.validate().is_err());
+  }
+
+  #[test]
+  fn message_text_is_a_dimension_of_its_own() {
+    // a text query alone searches every severity; with one, only that severity's table.
+    let mut by_text = sel(None, None, None, None);
+    by_text.text = Some("\\usepackage{foo}".to_string());
+    let resolved = by_text.validate().expect("text alone is a valid carve");
+    assert_eq!(resolved.message_tables.map(|tables| tables.len()), Some(5));
+    assert!(by_text.filter_summary().contains("text="));
+    by_text.message_severity = Some("error".to_string());
+    let resolved = by_text.validate().expect("text within a severity is valid");
+    assert_eq!(resolved.message_tables, Some(vec![("error", "log_errors")]));
+    // a blank query is no filter at all.
+    by_text.text = Some("   ".to_string());
+    assert!(by_text.validate().is_err());
   }
 
   #[test]
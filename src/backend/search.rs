@@ -0,0 +1,378 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Message search**: full-text search over the `what` and `details` of the conversion messages in
+//! the five `log_*` tables, so "which documents mention `\usepackage{foo}` in an error" is a query,
+//! not raw SQL. Each table carries a GIN index over [`MESSAGE_TEXT_VECTOR`] (the `simple` text
+//! search configuration — no stemming or stop words, as TeX tokens are not English); a query is
+//! read by Postgres's `websearch_to_tsquery` (`"quoted phrases"`, `or`, `-excluded`).
+//!
+//! The same match scopes a sandbox carve ([`super::SandboxSelection::text`]) and a rerun
+//! ([`super::RerunOptions::text_opt`]) through [`message_exists_clause`], so a search's results can
+//! be carved or reconverted as they are.
+
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
+
+use super::sandbox::message_log_table;
+use crate::helpers::TaskStatus;
+
+/// The text a message is searched by: its `what` and `details`. The migration indexes exactly this
+/// expression, so a query using it is served by the index.
+pub(crate) const MESSAGE_TEXT_VECTOR: &str =
+  "to_tsvector('simple', COALESCE(l.what, '') || ' ' || COALESCE(l.details, ''))";
+
+/// The message severities, in report order, each searched in its own `log_*` table.
+const MESSAGE_SEVERITIES: [&str; 5] = ["invalid", "fatal", "error", "warning", "info"];
+
+/// The longest accepted search query, in characters.
+pub const MAX_MESSAGE_QUERY_CHARS: usize = 256;
+
+/// How long the queries of one search may run before Postgres cancels them.
+pub const SEARCH_QUERY_BUDGET_MS: u32 = 20_000;
+
+/// Opens a highlighted match in a [`MessageHit::headline`].
+const HIT_START: char = '\u{E000}';
+/// Closes a highlighted match in a [`MessageHit::headline`].
+const HIT_STOP: char = '\u{E001}';
+
+/// The trimmed search text, or the reason it cannot be searched for (blank, or longer than
+/// [`MAX_MESSAGE_QUERY_CHARS`]).
+pub fn validate_message_text(text: &str) -> Result<&str, String> {
+  let text = text.trim();
+  if text.is_empty() {
+    Err("a message search needs some text".to_string())
+  } else if text.chars().count() > MAX_MESSAGE_QUERY_CHARS {
+    Err(format!(
+      "a message search is at most {MAX_MESSAGE_QUERY_CHARS} characters"
+    ))
+  } else {
+    Ok(text)
+  }
+}
+
+/// The `(severity, log_* table)` pairs a message filter of `severity` looks in: its own, or all
+/// five without one. `None` for an unknown severity.
+pub(crate) fn message_tables(severity: Option<&str>) -> Option<Vec<(&'static str, &'static str)>> {
+  MESSAGE_SEVERITIES
+    .iter()
+    .filter(|known| severity.is_none_or(|severity| severity == **known))
+    .map(|known| message_log_table(known).map(|table| (*known, table)))
+    .collect::<Option<Vec<_>>>()
+    .filter(|tables| !tables.is_empty())
+}
+
+/// An SQL condition on a task aliased `t`: it emitted a message in one of `tables` matching the
+/// given bind parameters — `category_param`/`what_param` compared exactly, `text_param` searched
+/// for. The table names come from [`message_tables`], never user input.
+pub(crate) fn message_exists_clause(
+  tables: &[(&'static str, &'static str)],
+  category_param: Option<usize>,
+  what_param: Option<usize>,
+  text_param: Option<usize>,
+) -> String {
+  let mut condition = String::new();
+  if let Some(param) = category_param {
+    condition.push_str(&format!(" AND l.category = ${param}"));
+  }
+  if let Some(param) = what_param {
+    condition.push_str(&format!(" AND l.what = ${param}"));
+  }
+  if let Some(param) = text_param {
+    condition.push_str(&format!(
+      " AND {MESSAGE_TEXT_VECTOR} @@ websearch_to_tsquery('simple', ${param})"
+    ));
+  }
+  let exists: Vec<String> = tables
+    .iter()
+    .map(|(_, table)| format!("EXISTS (SELECT 1 FROM {table} l WHERE l.task_id = t.id{condition})"))
+    .collect();
+  format!("({})", exists.join(" OR "))
+}
+
+/// A message search: the text and the optional scope it is narrowed to.
+#[derive(Debug, Clone, Default)]
+pub struct MessageSearch {
+  /// The search text, in `websearch_to_tsquery` syntax.
+  pub text: String,
+  /// Only messages of this corpus's tasks.
+  pub corpus_id: Option<i32>,
+  /// Only messages of this service's tasks.
+  pub service_id: Option<i32>,
+  /// Only messages of this severity (`info`, `warning`, `error`, `fatal` or `invalid`).
+  pub severity: Option<String>,
+  /// Only messages of this category.
+  pub category: Option<String>,
+}
+
+/// One message matching a search.
+#[derive(QueryableByName, Debug, Clone)]
+pub struct MessageHit {
+  /// The task's corpus.
+  #[diesel(sql_type = Text)]
+  pub corpus: String,
+  /// The task's service.
+  #[diesel(sql_type = Text)]
+  pub service: String,
+  /// The task id.
+  #[diesel(sql_type = BigInt)]
+  pub task_id: i64,
+  /// The task's entry path.
+  #[diesel(sql_type = Text)]
+  pub entry: String,
+  /// The message severity.
+  #[diesel(sql_type = Text)]
+  pub severity: String,
+  /// The message category.
+  #[diesel(sql_type = Text)]
+  pub category: String,
+  /// The message `what`.
+  #[diesel(sql_type = Text)]
+  pub what: String,
+  /// The fragments of the `details` around the matches, marked up for [`highlights`].
+  #[diesel(sql_type = Text)]
+  pub headline: String,
+}
+
+/// A stretch of a [`MessageHit::headline`]: matched text, or the text around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+  /// The text.
+  pub text: String,
+  /// Whether it matched the search.
+  pub matched: bool,
+}
+
+/// The stretches of a headline, alternating between the text around the matches and the matches.
+pub fn highlights(headline: &str) -> Vec<Highlight> {
+  let mut stretches = Vec::new();
+  let mut rest = headline;
+  while let Some(start) = rest.find(HIT_START) {
+    if start > 0 {
+      stretches.push(Highlight {
+        text: rest[..start].to_string(),
+        matched: false,
+      });
+    }
+    rest = &rest[start + HIT_START.len_utf8()..];
+    let stop = rest.find(HIT_STOP).unwrap_or(rest.len());
+    if stop > 0 {
+      stretches.push(Highlight {
+        text: rest[..stop].to_string(),
+        matched: true,
+      });
+    }
+    rest = rest.get(stop + HIT_STOP.len_utf8()..).unwrap_or_default();
+  }
+  if !rest.is_empty() {
+    stretches.push(Highlight {
+      text: rest.to_string(),
+      matched: false,
+    });
+  }
+  stretches
+}
+
+/// A page of search results, with the totals they are drawn from.
+#[derive(Debug, Clone, Default)]
+pub struct MessageSearchReport {
+  /// Matching messages in total.
+  pub total_messages: i64,
+  /// Distinct tasks with a matching message.
+  pub total_tasks: i64,
+  /// The messages of the requested page, by entry.
+  pub hits: Vec<MessageHit>,
+}
+
+#[derive(QueryableByName)]
+struct SearchTotals {
+  #[diesel(sql_type = BigInt)]
+  total_messages: i64,
+  #[diesel(sql_type = BigInt)]
+  total_tasks: i64,
+}
+
+/// The messages matching `search`: a page of `[offset, offset + limit)` by entry (then severity,
+/// then message), each with its highlighted `details`. `None` for an unknown severity; an `Err` if
+/// the queries overrun [`SEARCH_QUERY_BUDGET_MS`]. The text is expected to have passed
+/// [`validate_message_text`].
+pub fn search_messages(
+  connection: &mut PgConnection,
+  search: &MessageSearch,
+  limit: i64,
+  offset: i64,
+) -> QueryResult<Option<MessageSearchReport>> {
+  let Some(tables) = message_tables(search.severity.as_deref()) else {
+    return Ok(None);
+  };
+  // The table names and their severities come from the fixed map; everything else is bound, in
+  // the same placeholder order for both queries ($1 text, $2 corpus, $3 service, $4 category).
+  let branches = tables
+    .iter()
+    .map(|(severity, table)| {
+      format!(
+        "SELECT '{severity}'::text AS severity, l.id AS message_id, l.task_id, t.entry, \
+           l.category, l.what, l.details \
+         FROM {table} l JOIN tasks t ON t.id = l.task_id \
+         WHERE {MESSAGE_TEXT_VECTOR} @@ websearch_to_tsquery('simple', $1) \
+           AND ($2::integer IS NULL OR t.corpus_id = $2) \
+           AND ($3::integer IS NULL OR t.service_id = $3) \
+           AND ($4::text IS NULL OR l.category = $4)"
+      )
+    })
+    .collect::<Vec<_>>()
+    .join(" UNION ALL ");
+  let scoped = |query: String| {
+    sql_query(query)
+      .into_boxed::<Pg>()
+      .bind::<Text, _>(search.text.clone())
+      .bind::<Nullable<Integer>, _>(search.corpus_id)
+      .bind::<Nullable<Integer>, _>(search.service_id)
+      .bind::<Nullable<Text>, _>(search.category.clone())
+  };
+  let options = format!(
+    "StartSel={HIT_START}, StopSel={HIT_STOP}, MaxFragments=2, MaxWords=30, MinWords=10, \
+     FragmentDelimiter=\" … \""
+  );
+  connection.transaction::<_, diesel::result::Error, _>(|conn| {
+    sql_query(format!(
+      "SET LOCAL statement_timeout = {SEARCH_QUERY_BUDGET_MS}"
+    ))
+    .execute(conn)?;
+    let totals: SearchTotals = scoped(format!(
+      "SELECT COUNT(*)::bigint AS total_messages, \
+         COUNT(DISTINCT task_id)::bigint AS total_tasks FROM ({branches}) hits"
+    ))
+    .get_result(conn)?;
+    // The page is cut before its headlines are built, so only the shown messages pay for them.
+    let hits: Vec<MessageHit> = scoped(format!(
+      "SELECT c.name AS corpus, s.name AS service, t.id AS task_id, t.entry, page.severity, \
+         COALESCE(page.category, '') AS category, COALESCE(page.what, '') AS what, \
+         ts_headline('simple', COALESCE(page.details, ''), websearch_to_tsquery('simple', $1), $7) \
+           AS headline \
+       FROM (SELECT * FROM ({branches}) hits \
+             ORDER BY entry, severity, message_id LIMIT $5 OFFSET $6) page \
+       JOIN tasks t ON t.id = page.task_id \
+       JOIN corpora c ON c.id = t.corpus_id \
+       JOIN services s ON s.id = t.service_id \
+       ORDER BY page.entry, page.severity, page.message_id"
+    ))
+    .bind::<BigInt, _>(limit)
+    .bind::<BigInt, _>(offset)
+    .bind::<Text, _>(options.clone())
+    .load(conn)?;
+    Ok(Some(MessageSearchReport {
+      total_messages: totals.total_messages,
+      total_tasks: totals.total_tasks,
+      hits,
+    }))
+  })
+}
+
+/// Marks for a rerun (sets to `mark`) the `(corpus, service)` tasks that emitted a message matching
+/// `text` — of `severity` if given (else of any), narrowed to `category` and `what` if given. The
+/// text-scoped branch of [`super::mark::mark_rerun`]. `severity` must be a message severity.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn mark_rerun_by_text(
+  connection: &mut PgConnection,
+  mark: i32,
+  corpus_id: i32,
+  service_id: i32,
+  severity: Option<&str>,
+  category: Option<&str>,
+  what: Option<&str>,
+  text: &str,
+) -> QueryResult<usize> {
+  let tables = message_tables(severity).ok_or_else(|| {
+    diesel::result::Error::QueryBuilderError(
+      format!(
+        "unknown message severity '{}'",
+        severity.unwrap_or_default()
+      )
+      .into(),
+    )
+  })?;
+  // $1 is the text; category and what follow only when given.
+  let mut binds: Vec<&str> = vec![text];
+  let category_param = category.map(|category| {
+    binds.push(category);
+    binds.len()
+  });
+  let what_param = what.map(|what| {
+    binds.push(what);
+    binds.len()
+  });
+  let exists = message_exists_clause(&tables, category_param, what_param, Some(1));
+  // Finished tasks only, as for every message-scoped rerun (see `MarkRerun`): a quarantined task
+  // keeps the message that parked it.
+  let mut update = sql_query(format!(
+    "UPDATE tasks t SET status = {mark} \
+     WHERE t.corpus_id = {corpus_id} AND t.service_id = {service_id} \
+       AND t.status BETWEEN {invalid} AND {no_problem} AND {exists}",
+    invalid = TaskStatus::Invalid.raw(),
+    no_problem = TaskStatus::NoProblem.raw(),
+  ))
+  .into_boxed::<Pg>();
+  for bind in binds {
+    update = update.bind::<Text, _>(bind);
+  }
+  update.execute(connection)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn splits_headlines_into_highlights() {
+    let headline = format!("missing {HIT_START}foo.sty{HIT_STOP} in {HIT_START}paper{HIT_STOP}");
+    let stretches = highlights(&headline);
+    assert_eq!(
+      stretches
+        .iter()
+        .map(|stretch| (stretch.text.as_str(), stretch.matched))
+        .collect::<Vec<_>>(),
+      [
+        ("missing ", false),
+        ("foo.sty", true),
+        (" in ", false),
+        ("paper", true)
+      ]
+    );
+    assert_eq!(
+      highlights("no match at all"),
+      [Highlight {
+        text: "no match at all".to_string(),
+        matched: false
+      }]
+    );
+    // An unclosed match (a headline cut mid-fragment) runs to the end.
+    assert_eq!(highlights(&format!("{HIT_START}open")).len(), 1);
+  }
+
+  #[test]
+  fn scopes_messages_to_known_severities() {
+    assert_eq!(message_tables(None).map(|tables| tables.len()), Some(5));
+    assert_eq!(
+      message_tables(Some("error")),
+      Some(vec![("error", "log_errors")])
+    );
+    assert!(message_tables(Some("no_problem")).is_none());
+    let clause = message_exists_clause(&[("error", "log_errors")], Some(2), None, Some(1));
+    assert!(clause.starts_with("(EXISTS (SELECT 1 FROM log_errors l WHERE l.task_id = t.id"));
+    assert!(clause.contains("l.category = $2"));
+    assert!(clause.contains("websearch_to_tsquery('simple', $1)"));
+    assert!(validate_message_text("  ").is_err());
+    assert_eq!(
+      validate_message_text(" \\usepackage{foo} "),
+      Ok("\\usepackage{foo}")
+    );
+    assert!(validate_message_text(&"x".repeat(MAX_MESSAGE_QUERY_CHARS + 1)).is_err());
+  }
+}
@@ -1,6 +1,6 @@
 use super::mark;
 use crate::helpers::TaskStatus;
-use crate::models::{Corpus, Service};
+use crate::models::{Corpus, IMPORT_SERVICE_ID, Service};
 use diesel::IntoSql;
 use diesel::result::Error;
 use diesel::sql_types::Integer;
@@ -14,7 +14,9 @@ pub(crate) fn register_service(
   description: String,
 ) -> Result<(), Error> {
   use crate::schema::tasks::dsl::*;
-  use crate::schema::{log_errors, log_fatals, log_infos, log_invalids, log_warnings};
+  use crate::schema::{
+    activation_weights, log_errors, log_fatals, log_infos, log_invalids, log_warnings,
+  };
   // The caller resolves the exact corpus (by name) and passes it in — we must NOT re-resolve by
   // path, because a sandbox shares its parent's path (`find_by_path` would return the parent and
   // we'd register/check against the wrong corpus). See KNOWN_ISSUES — sandbox activation.
@@ -46,6 +48,9 @@ pub(crate) fn register_service(
     ));
   }
 
+  // A pipeline service consumes its upstream's results, so the upstream must be activated here.
+  super::pipelines::check_upstream_activated(connection, corpus_id_val, service)?;
+
   // The magic `import` service holds one entry per document; the new service activates over all of
   // them.
   let import_service = Service::find_by_name("import", connection)?;
@@ -94,6 +99,17 @@ pub(crate) fn register_service(
       )
       .into_columns((entry, service_id, corpus_id, status))
       .execute(t_connection)?;
+    // Pipeline tasks wait for their upstream result for the same document.
+    super::pipelines::hold_for_upstream(t_connection, corpus_id_val, service, todo_raw)?;
+    // The activation's fair-share weight row (default weight 1; a weight chosen at activation is
+    // set by the caller afterwards). `DO NOTHING` keeps a weight set before a re-registration.
+    insert_into(activation_weights::table)
+      .values((
+        activation_weights::corpus_id.eq(corpus_id_val),
+        activation_weights::service_id.eq(service_id_val),
+      ))
+      .on_conflict_do_nothing()
+      .execute(t_connection)?;
     // Register the activation run **inside** the same transaction (R-11 sibling), so a crash or a
     // `mark_new_run` error rolls the freshly-created tasks back too — never
     // tasks-created-but-no-run, nor a "failed" activate job that in fact created the tasks.
@@ -121,7 +137,7 @@ pub(crate) fn extend_service(
   // import entry list is materialized in RAM and there is no per-document round-trip, so the work
   // is bounded regardless of corpus size (KNOWN_ISSUES I-2 — the old path loaded every import
   // entry into a `Vec` and issued one `create_if_new` per entry, ~1.5M for arxmliv). A single
-  // statement is atomic, so no explicit transaction is needed.
+  // statement is atomic; the pipeline hold below is a follow-up that is idempotent on retry.
   insert_into(tasks)
     .values(
       tasks
@@ -137,6 +153,9 @@ pub(crate) fn extend_service(
     .into_columns((entry, service_id, corpus_id, status))
     .on_conflict_do_nothing()
     .execute(connection)?;
+  // Pipeline tasks wait for their upstream result for the same document (existing TODO tasks whose
+  // upstream already succeeded are unaffected).
+  super::pipelines::hold_for_upstream(connection, corpus.id, service, todo_raw)?;
   Ok(())
 }
 
@@ -153,8 +172,8 @@ pub(crate) fn destroy_service_by_name(
 ) -> Result<usize, Error> {
   let service = Service::find_by_name(name, connection)?;
   // Defense-in-depth: the frontend already rejects these with 403, but the data layer must never
-  // let a careless caller wipe init/import. (Magic ids: 1=init, 2=import, >2=real services.)
-  if service.id <= 2 {
+  // let a careless caller wipe init/import.
+  if service.id <= IMPORT_SERVICE_ID {
     return Err(Error::QueryBuilderError(
       format!(
         "refusing to destroy the infrastructure service {:?} (id {})",
@@ -1,17 +1,34 @@
 //! Aggregate methods, to be used by backend
 use crate::helpers::TaskStatus;
-use crate::models::{Service, Task};
+use crate::models::{ActivationWeight, Service, Task};
 use crate::schema::tasks;
+use diesel::dsl::sql;
 use diesel::result::Error;
+use diesel::sql_types::Bool;
 use diesel::*;
 use rand::RngExt;
+use std::collections::HashMap;
 
+/// Leases up to `queue_size` of `service`'s TODO tasks and returns them in dispatch order. The
+/// claimed rows are marked Queued with a fresh lease mark in the same transaction.
+///
+/// Tasks are claimed **highest `priority` first** across every corpus (ties by id, i.e. import
+/// order). When the service is activated on several corpora, each priority tier is **fair-shared**
+/// in turn: each corpus is entitled to a slice of the tier proportional to its
+/// `activation_weights.weight` ([`apportion`]), and a corpus with less work in the tier than its
+/// slice hands the remainder back to the others — so one giant corpus can no longer starve a small
+/// sandbox campaign on the same service, while a prioritized rerun on any corpus still goes ahead
+/// of every corpus's backlog. The returned batch is ordered by priority, then interleaved across
+/// corpora by weight. What each corpus received is recorded on its weight row
+/// ([`ActivationWeight::record_leases`]) for the achieved-share report.
+/// Tasks of held poison entries (`models::PoisonEntry`) are never leased. The leases are
+/// tagged with the leasing dispatcher `instance` (`models::DispatcherInstance`).
 pub(crate) fn fetch_tasks(
   connection: &mut PgConnection,
   service: &Service,
   queue_size: usize,
+  instance: i32,
 ) -> Result<Vec<Task>, Error> {
-  use crate::schema::tasks::dsl::{id, service_id, status};
   let mut rng = rand::rng();
   // Temporary lease mark: a positive sentinel in the live-lease value space `[1, 65536]` — disjoint
   // from `TODO` (0), completed (`< 0`), and the rerun sentinel (`> 65536`, R-13). Computed in `i32`
@@ -20,49 +37,295 @@ pub(crate) fn fetch_tasks(
   let mark: i32 = 1 + i32::from(rng.random::<u16>());
 
   connection.transaction::<Vec<Task>, Error, _>(|t_connection| {
-    // Claim up to `queue_size` of this service's TODO tasks, locking the rows for the txn so a
-    // concurrent (or future second) dispatcher can't grab the same ones; `SKIP LOCKED` makes such a
-    // dispatcher take the next disjoint batch instead of blocking on ours.
-    let claimed: Vec<i64> = tasks::table
-      .filter(service_id.eq(service.id))
-      .filter(status.eq(TaskStatus::TODO.raw()))
-      .limit(queue_size as i64)
+    let weights = ActivationWeight::for_service(t_connection, service.id)?;
+    let mut leased: Vec<Task> = Vec::new();
+    let mut remaining = queue_size;
+    // Fair-share tier by tier, from the highest priority with TODO work down. Rerun priorities
+    // are a handful of distinct values, so this is a few tiers per batch at most.
+    let mut below = None;
+    while weights.len() > 1 && remaining > 0 {
+      let Some(tier) = top_priority(t_connection, service.id, below)? else {
+        break;
+      };
+      below = Some(tier);
+      // Fair-share rounds over the corpora that may still have TODO work in the tier. Each round
+      // apportions what is left of the batch; a corpus that comes up short of its slice is drained
+      // and drops out, its unused slice re-apportioned next round. Every round either fills the
+      // batch or retires a corpus, so this is at most `weights.len()` rounds of small indexed
+      // claims.
+      let mut open: Vec<(i32, i32)> = weights.iter().map(|w| (w.corpus_id, w.weight)).collect();
+      while remaining > 0 && !open.is_empty() {
+        let mut still_open = Vec::with_capacity(open.len());
+        for ((corpus, weight), slice) in open.iter().zip(apportion(remaining, &open)) {
+          if slice == 0 {
+            still_open.push((*corpus, *weight));
+            continue;
+          }
+          let batch = claim(
+            t_connection,
+            service.id,
+            Some(*corpus),
+            Some(tier),
+            slice,
+            mark,
+            instance,
+          )?;
+          if batch.len() == slice {
+            still_open.push((*corpus, *weight));
+          }
+          remaining -= batch.len();
+          leased.extend(batch);
+        }
+        open = still_open;
+      }
+      // The tier's work in any corpus lacking a weight row (activated before weights existed and
+      // not backfilled), so no TODO work is ever unleasable.
+      if remaining > 0 {
+        let batch = claim(
+          t_connection,
+          service.id,
+          None,
+          Some(tier),
+          remaining,
+          mark,
+          instance,
+        )?;
+        remaining -= batch.len();
+        leased.extend(batch);
+      }
+    }
+    // A single-corpus service (the common case) takes the plain service-wide claim.
+    if remaining > 0 {
+      leased.extend(claim(
+        t_connection,
+        service.id,
+        None,
+        None,
+        remaining,
+        mark,
+        instance,
+      )?);
+    }
+    if leased.is_empty() {
+      return Ok(leased);
+    }
+
+    // Dispatch order: priority first, then interleave the corpora in proportion to their weights
+    // (a corpus's k-th task of a tier is due at `k / weight`), so the batch is fair over time as
+    // well as in total. `leased` holds each corpus's tasks in claim order, which ranks them.
+    let weight_of = |corpus: i32| {
+      weights
+        .iter()
+        .find(|w| w.corpus_id == corpus)
+        .map_or(1.0, |w| f64::from(w.weight))
+    };
+    let mut per_corpus: Vec<(i32, i64)> = Vec::new();
+    let mut per_tier: HashMap<(i32, i32), i64> = HashMap::new();
+    let mut keyed: Vec<(f64, Task)> = Vec::with_capacity(leased.len());
+    for task in leased {
+      match per_corpus.iter_mut().find(|(c, _)| *c == task.corpus_id) {
+        Some((_, count)) => *count += 1,
+        None => per_corpus.push((task.corpus_id, 1)),
+      }
+      let rank = per_tier.entry((task.corpus_id, task.priority)).or_default();
+      *rank += 1;
+      keyed.push((*rank as f64 / weight_of(task.corpus_id), task));
+    }
+    keyed.sort_by(|(a_due, a), (b_due, b)| {
+      b.priority
+        .cmp(&a.priority)
+        .then(a_due.total_cmp(b_due))
+        .then(a.id.cmp(&b.id))
+    });
+    ActivationWeight::record_leases(t_connection, service.id, &per_corpus)?;
+    Ok(keyed.into_iter().map(|(_, task)| task).collect())
+  })
+}
+
+/// The highest `priority` among `service`'s leasable TODO tasks, below `below` if given; `None`
+/// once there are none. A read of the front of `todo_priority_index`.
+fn top_priority(
+  connection: &mut PgConnection,
+  service: i32,
+  below: Option<i32>,
+) -> Result<Option<i32>, Error> {
+  use crate::schema::tasks::dsl::{priority, service_id, status};
+  let ceiling = match below {
+    None => i32::MAX,
+    Some(tier) => match tier.checked_sub(1) {
+      Some(ceiling) => ceiling,
+      None => return Ok(None),
+    },
+  };
+  tasks::table
+    .filter(service_id.eq(service))
+    .filter(status.eq(TaskStatus::TODO.raw()))
+    .filter(priority.le(ceiling))
+    .filter(sql::<Bool>(NOT_HELD))
+    .select(diesel::dsl::max(priority))
+    .first(connection)
+}
+
+/// Excludes the tasks of entries held as poison documents (their tasks stay TODO, and in reports)
+/// until released; the anti-join only probes the partial index of held entries.
+const NOT_HELD: &str = "NOT EXISTS (SELECT 1 FROM poison_entries p \
+                        WHERE p.entry = tasks.entry AND p.held_at IS NOT NULL)";
+
+/// Claims up to `limit` of `service_id`'s TODO tasks — of one corpus, or of any; of one priority
+/// `tier`, or of all — highest `priority` first (ties by id), and marks them Queued with the lease
+/// `mark`. Locks the rows for
+/// the transaction so a concurrent (or future second) dispatcher can't grab the same ones; `SKIP
+/// LOCKED` makes such a dispatcher take the next disjoint batch instead of blocking on ours. The
+/// order is served by the TODO-only `todo_priority_index` (service-wide) or
+/// `todo_fair_share_index` (per corpus), so the claim reads the front of an index rather than
+/// sorting the backlog. The claimed rows are tagged with the leasing dispatcher `instance`.
+/// Returned in claim order.
+fn claim(
+  connection: &mut PgConnection,
+  service: i32,
+  corpus: Option<i32>,
+  tier: Option<i32>,
+  limit: usize,
+  mark: i32,
+  instance: i32,
+) -> Result<Vec<Task>, Error> {
+  use crate::schema::tasks::dsl::{corpus_id, dispatcher_id, id, priority, service_id, status};
+  let (lowest, highest) = tier.map_or((i32::MIN, i32::MAX), |tier| (tier, tier));
+  let todo = tasks::table
+    .filter(service_id.eq(service))
+    .filter(status.eq(TaskStatus::TODO.raw()))
+    .filter(priority.between(lowest, highest))
+    .filter(sql::<Bool>(NOT_HELD));
+  let claimed: Vec<i64> = match corpus {
+    Some(corpus) => todo
+      .filter(corpus_id.eq(corpus))
+      .order((priority.desc(), id.asc()))
+      .limit(limit as i64)
       .select(id)
       .for_update()
       .skip_locked()
-      .load(t_connection)?;
-    if claimed.is_empty() {
-      return Ok(Vec::new());
-    }
-    // Mark the whole claimed batch Queued in ONE statement (was one `save_changes` UPDATE per row —
-    // the D-8 write-amplification class, here on the lease side: ~`queue_size` round-trips per
-    // refetch, serial on the single ventilator thread, each rewriting every column). `RETURNING *`
-    // hands back the marked tasks the ventilator wraps into its dispatch queue.
-    update(tasks::table.filter(id.eq_any(&claimed)))
-      .set(status.eq(mark))
-      .get_results::<Task>(t_connection)
-  })
+      .load(connection)?,
+    None => todo
+      .order((priority.desc(), id.asc()))
+      .limit(limit as i64)
+      .select(id)
+      .for_update()
+      .skip_locked()
+      .load(connection)?,
+  };
+  if claimed.is_empty() {
+    return Ok(Vec::new());
+  }
+  // Mark the whole claimed batch Queued in ONE statement (was one `save_changes` UPDATE per row —
+  // the D-8 write-amplification class, here on the lease side: ~`queue_size` round-trips per
+  // refetch, serial on the single ventilator thread, each rewriting every column). Marking before
+  // the next claim also keeps a later fair-share round from re-selecting these rows. `RETURNING`
+  // order is unspecified, so restore the claim order before handing the batch over.
+  let mut leased = update(tasks::table.filter(id.eq_any(&claimed)))
+    .set((status.eq(mark), dispatcher_id.eq(instance)))
+    .get_results::<Task>(connection)?;
+  leased.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+  Ok(leased)
+}
+
+/// Splits `total` lease slots across `(corpus_id, weight)` activations in proportion to their
+/// weights, by largest remainder (ties to the earlier entry), returning one slice per input in
+/// input order. The slices always sum to `total`; non-positive weights count as 1.
+pub(crate) fn apportion(total: usize, weights: &[(i32, i32)]) -> Vec<usize> {
+  let weight = |w: i32| w.max(1) as u64;
+  let sum: u64 = weights.iter().map(|(_, w)| weight(*w)).sum();
+  if sum == 0 {
+    return Vec::new();
+  }
+  let mut slices: Vec<usize> = weights
+    .iter()
+    .map(|(_, w)| (total as u64 * weight(*w) / sum) as usize)
+    .collect();
+  let mut order: Vec<usize> = (0..weights.len()).collect();
+  // Largest fractional remainder first (`total * w mod sum`, compared exactly in integers).
+  order.sort_by_key(|&i| std::cmp::Reverse(total as u64 * weight(weights[i].1) % sum));
+  let handed_out: usize = slices.iter().sum();
+  for &i in order.iter().take(total - handed_out) {
+    slices[i] += 1;
+  }
+  slices
 }
 
+/// Reset every Queued (positive-`status` lease mark) task back to `TODO`, whichever dispatcher
+/// leased it. Only safe when no dispatcher is running.
 pub(crate) fn clear_limbo_tasks(connection: &mut PgConnection) -> Result<usize, Error> {
-  clear_limbo_tasks_except(connection, &[])
+  use crate::schema::tasks::dsl::status;
+  update(tasks::table)
+    .filter(status.gt(&TaskStatus::TODO.raw()))
+    .set(status.eq(&TaskStatus::TODO.raw()))
+    .execute(connection)
 }
 
-/// Reset every Queued (positive-`status` lease mark) task back to `TODO`, **except** the given
-/// in-flight task ids. At process start (nothing in flight) pass `&[]` to recover all leftover
-/// Queued tasks from a previously-crashed run. On a ventilator **restart** mid-operation
-/// (KNOWN_ISSUES D-4 band-aid), pass the live `progress_queue` ids so tasks a worker is *currently
-/// processing* are NOT reset — resetting an in-flight task re-leases it while its original result
-/// is still pending (a double-dispatch). With an empty slice this is exactly the old blunt reset
-/// (`x <> ALL('{}')` is vacuously true), so process-start behaviour is unchanged.
+/// Reset the Queued (positive-`status` lease mark) tasks dispatcher `instance` may recover back to
+/// `TODO`, **except** the given in-flight task ids. Recoverable are its own leases and the leases
+/// of dead instances (stopped, or silent for over `timeout_seconds`, see
+/// `models::dispatcher_instances`) — never a live peer's, which would double-dispatch the tasks
+/// that peer's workers are converting. Untagged leases (taken by a dispatcher predating instance
+/// tags, which may still be running) are recovered only with `include_untagged`. At process start
+/// (nothing in flight) pass `&[]`. On a ventilator **restart** mid-operation (KNOWN_ISSUES D-4
+/// band-aid), pass the live `progress_queue` ids so tasks a worker is *currently processing* are
+/// NOT reset — resetting an in-flight task re-leases it while its original result is still pending
+/// (a double-dispatch).
 pub(crate) fn clear_limbo_tasks_except(
   connection: &mut PgConnection,
+  instance: i32,
   in_flight: &[i64],
+  timeout_seconds: i64,
+  include_untagged: bool,
+) -> Result<usize, Error> {
+  use diesel::sql_types::{Array, BigInt, Bool, Integer};
+  sql_query(format!(
+    "UPDATE tasks SET status = $1 WHERE status > $1 AND id <> ALL($2) \
+     AND (dispatcher_id = $3 OR {} OR ($5 AND dispatcher_id IS NULL))",
+    crate::models::dead_lease_holder("$4")
+  ))
+  .bind::<Integer, _>(TaskStatus::TODO.raw())
+  .bind::<Array<BigInt>, _>(in_flight)
+  .bind::<Integer, _>(instance)
+  .bind::<BigInt, _>(timeout_seconds)
+  .bind::<Bool, _>(include_untagged)
+  .execute(connection)
+}
+
+/// Returns leased (Queued) tasks to TODO — the ventilator drops the queued tasks of an entry it has
+/// just held as a poison document, and they must not linger Queued until the next limbo recovery.
+/// Tasks that already completed are left alone.
+pub(crate) fn unlease_tasks(
+  connection: &mut PgConnection,
+  task_ids: &[i64],
 ) -> Result<usize, Error> {
   use crate::schema::tasks::dsl::{id, status};
   update(tasks::table)
+    .filter(id.eq_any(task_ids))
     .filter(status.gt(&TaskStatus::TODO.raw()))
-    .filter(id.ne_all(in_flight))
     .set(status.eq(&TaskStatus::TODO.raw()))
     .execute(connection)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::apportion;
+
+  #[test]
+  fn apportion_splits_by_weight_and_hands_out_every_slot() {
+    assert_eq!(apportion(10, &[(1, 1), (2, 1)]), vec![5, 5]);
+    assert_eq!(apportion(10, &[(1, 3), (2, 1)]), vec![8, 2]);
+    assert_eq!(apportion(7, &[(1, 1), (2, 1), (3, 1)]), vec![3, 2, 2]);
+    assert_eq!(apportion(1, &[(1, 1), (2, 9)]), vec![0, 1]);
+    assert_eq!(apportion(100, &[(1, 0), (2, -4)]), vec![50, 50]);
+    assert_eq!(apportion(5, &[]), Vec::<usize>::new());
+    for total in 0..50 {
+      assert_eq!(
+        apportion(total, &[(1, 2), (2, 3), (3, 7)])
+          .iter()
+          .sum::<usize>(),
+        total
+      );
+    }
+  }
+}
@@ -0,0 +1,260 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Result archive versions**: reading a kept (or the current) result archive back for the
+//! document page's side-by-side comparison of two runs, and aging the kept versions out with the
+//! retention prune of `historical_tasks`.
+//!
+//! A service that keeps versions (`services.keep_result_versions`) has the sink move a task's
+//! previous `<service>.zip` aside instead of overwriting it; `mark_done` records it in
+//! `result_archive_versions`, tagged with the historical run it came from.
+
+use std::io::Read;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::helpers::log_parsers::LogParser;
+use crate::helpers::{NewTaskMessage, TaskStatus, read_cortex_log};
+use crate::models::ResultArchiveVersion;
+use crate::schema::result_archive_versions;
+use crate::storage::Storage;
+
+/// The most of an archive's main HTML document read into memory for display.
+pub const MAIN_HTML_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// Versions aged out per page of a retention prune.
+const PRUNE_PAGE_SIZE: i64 = 1_000;
+
+/// One member of a result archive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ArchiveMember {
+  /// its path inside the archive
+  pub name: String,
+  /// its uncompressed size, in bytes
+  pub size: u64,
+}
+
+/// What a result archive holds, as the document page compares it: the verdict and messages of its
+/// `cortex.log`, its members, and its main HTML document.
+#[derive(Clone, Debug)]
+pub struct ArchiveView {
+  /// the conversion status its `cortex.log` reports
+  pub status: TaskStatus,
+  /// the messages of its `cortex.log`
+  pub messages: Vec<NewTaskMessage>,
+  /// its members, in archive order
+  pub members: Vec<ArchiveMember>,
+  /// the name of its main HTML document, if it has one
+  pub main_html_name: Option<String>,
+  /// that document's text (lossily decoded, at most [`MAIN_HTML_LIMIT`] bytes)
+  pub main_html: Option<String>,
+  /// whether the text was cut at [`MAIN_HTML_LIMIT`]
+  pub main_html_truncated: bool,
+}
+
+/// Reads the result archive at `path` back from `storage`: the status and messages of its
+/// `cortex.log` (read by the service's `parser`, as the sink did when it was committed), its member
+/// list, and its main HTML document — the `.html`/`.xhtml` member named after the document, else
+/// the largest one. `task_id` is only stamped on the messages.
+pub fn read_archive_view(
+  storage: &dyn Storage,
+  path: &Path,
+  document_name: &str,
+  task_id: i64,
+  parser: &dyn LogParser,
+) -> Result<ArchiveView, String> {
+  let log = read_cortex_log(path, storage)?;
+  let (status, messages) = parser.classify(task_id, &log);
+  let file = storage
+    .open(path)
+    .map_err(|e| format!("cannot open result archive: {e}"))?;
+  let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("not a readable zip: {e}"))?;
+  let mut members = Vec::with_capacity(archive.len());
+  for index in 0..archive.len() {
+    let member = archive
+      .by_index_raw(index)
+      .map_err(|e| format!("reading the archive index failed: {e}"))?;
+    if member.is_dir() {
+      continue;
+    }
+    members.push(ArchiveMember {
+      name: member.name().to_string(),
+      size: member.size(),
+    });
+  }
+  let main_html_name = main_html_member(&members, document_name);
+  let mut main_html = None;
+  let mut main_html_truncated = false;
+  if let Some(name) = main_html_name.as_deref() {
+    let member = archive
+      .by_name(name)
+      .map_err(|e| format!("reading {name} failed: {e}"))?;
+    main_html_truncated = member.size() > MAIN_HTML_LIMIT;
+    let mut raw = Vec::new();
+    member
+      .take(MAIN_HTML_LIMIT)
+      .read_to_end(&mut raw)
+      .map_err(|e| format!("reading {name} failed: {e}"))?;
+    main_html = Some(String::from_utf8_lossy(&raw).into_owned());
+  }
+  Ok(ArchiveView {
+    status,
+    messages,
+    members,
+    main_html_name,
+    main_html,
+    main_html_truncated,
+  })
+}
+
+/// The main HTML member of an archive: the `.html`/`.xhtml` document named after the document
+/// (`<name>.html`), else the largest HTML member.
+fn main_html_member(members: &[ArchiveMember], document_name: &str) -> Option<String> {
+  let html: Vec<&ArchiveMember> = members
+    .iter()
+    .filter(|member| {
+      let name = member.name.to_ascii_lowercase();
+      name.ends_with(".html") || name.ends_with(".xhtml")
+    })
+    .collect();
+  let stem = |name: &str| {
+    Path::new(name)
+      .file_stem()
+      .and_then(|stem| stem.to_str())
+      .map(str::to_string)
+  };
+  html
+    .iter()
+    .find(|member| stem(&member.name).as_deref() == Some(document_name))
+    .or_else(|| html.iter().max_by_key(|member| member.size))
+    .map(|member| member.name.clone())
+}
+
+/// Ages out the kept result versions moved aside strictly before `cutoff` — removing their files
+/// from `storage` and their records — alongside the retention prune of the `historical_tasks`
+/// snapshots of the same age. Returns the versions pruned and the bytes freed.
+pub fn prune_versions_before(
+  connection: &mut PgConnection,
+  storage: &dyn Storage,
+  cutoff: NaiveDateTime,
+) -> Result<(usize, u64), String> {
+  let db_error = |e: diesel::result::Error| format!("pruning result versions failed: {e}");
+  let mut pruned = 0;
+  let mut freed = 0u64;
+  loop {
+    let page: Vec<ResultArchiveVersion> = result_archive_versions::table
+      .filter(result_archive_versions::saved_at.lt(cutoff))
+      .order(result_archive_versions::id)
+      .limit(PRUNE_PAGE_SIZE)
+      .load(connection)
+      .map_err(db_error)?;
+    if page.is_empty() {
+      break;
+    }
+    for version in &page {
+      storage
+        .remove(Path::new(&version.path))
+        .map_err(|e| format!("removing {}: {e}", version.path))?;
+      freed += version.size_bytes.max(0) as u64;
+    }
+    let ids: Vec<i64> = page.iter().map(|version| version.id).collect();
+    pruned += diesel::delete(
+      result_archive_versions::table.filter(result_archive_versions::id.eq_any(&ids)),
+    )
+    .execute(connection)
+    .map_err(db_error)?;
+  }
+  Ok((pruned, freed))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::helpers::log_parsers::Latexml;
+  use crate::storage::MemoryStorage;
+  use std::io::Write;
+
+  fn archive(members: &[(&str, &str)]) -> Vec<u8> {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    let mut zip = zip::ZipWriter::new(&mut buffer);
+    let opts: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+    for (name, text) in members {
+      zip.start_file(*name, opts).unwrap();
+      zip.write_all(text.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+    buffer.into_inner()
+  }
+
+  #[test]
+  fn reads_the_verdict_members_and_main_html() {
+    let storage = MemoryStorage::default();
+    storage.insert(
+      "/data/0801.1234/tex_to_html.zip",
+      archive(&[
+        (
+          "cortex.log",
+          "Warning:undefined:\\foo line 3\nInfo:conversion:1\n",
+        ),
+        ("index.html", "<html>a much longer navigation page</html>"),
+        ("0801.1234.html", "<html>paper</html>"),
+        ("x1.png", "png"),
+      ]),
+    );
+    let view = read_archive_view(
+      &storage,
+      Path::new("/data/0801.1234/tex_to_html.zip"),
+      "0801.1234",
+      9,
+      &Latexml,
+    )
+    .expect("readable archive");
+    assert_eq!(view.status, TaskStatus::Warning);
+    assert_eq!(view.messages.len(), 1);
+    assert_eq!(view.messages[0].category(), "undefined");
+    assert_eq!(view.members.len(), 4);
+    assert_eq!(view.main_html_name.as_deref(), Some("0801.1234.html"));
+    assert_eq!(view.main_html.as_deref(), Some("<html>paper</html>"));
+    assert!(!view.main_html_truncated);
+  }
+
+  #[test]
+  fn falls_back_to_the_largest_html_member() {
+    let members = vec![
+      ArchiveMember {
+        name: "small.html".to_string(),
+        size: 10,
+      },
+      ArchiveMember {
+        name: "doc/Main.XHTML".to_string(),
+        size: 900,
+      },
+      ArchiveMember {
+        name: "figure.svg".to_string(),
+        size: 5_000,
+      },
+    ];
+    assert_eq!(
+      main_html_member(&members, "0801.1234").as_deref(),
+      Some("doc/Main.XHTML")
+    );
+    assert_eq!(main_html_member(&members[2..], "0801.1234"), None);
+  }
+
+  #[test]
+  fn an_archive_without_a_log_is_unreadable() {
+    let storage = MemoryStorage::default();
+    storage.insert("/data/x/svc.zip", archive(&[("x.html", "<p/>")]));
+    assert!(read_archive_view(&storage, Path::new("/data/x/svc.zip"), "x", 1, &Latexml).is_err());
+    assert!(
+      read_archive_view(&storage, Path::new("/data/x/missing.zip"), "x", 1, &Latexml).is_err()
+    );
+  }
+}
@@ -5,7 +5,10 @@ use crate::helpers::TaskStatus;
 use crate::models::{LogError, LogFatal, LogInfo, LogInvalid, LogWarning, Task};
 use crate::schema::tasks;
 
-/// Task reruns by a variety of selector granularity
+/// Task reruns by a variety of selector granularity. Only finished tasks (Invalid through
+/// NoProblem) are reached: an in-flight, blocked or quarantined task keeps its old messages (a
+/// quarantined one keeps the very `cortex/never_completed_with_retries` fatal that parked it), so
+/// matching on messages alone would re-dispatch it or silently release it from quarantine.
 pub trait MarkRerun {
   /// Most-specific rerun query, via both category and what filter
   fn mark_rerun_by_what(
@@ -46,6 +49,7 @@ impl MarkRerun for LogInfo {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -67,6 +71,7 @@ impl MarkRerun for LogInfo {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -93,6 +98,7 @@ impl MarkRerun for LogWarning {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -114,6 +120,7 @@ impl MarkRerun for LogWarning {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -140,6 +147,7 @@ impl MarkRerun for LogError {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -161,6 +169,7 @@ impl MarkRerun for LogError {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -186,6 +195,7 @@ impl MarkRerun for LogFatal {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -214,6 +224,7 @@ impl MarkRerun for LogFatal {
       update(tasks::table)
         .filter(tasks::corpus_id.eq(&corpus_id))
         .filter(tasks::service_id.eq(&service_id))
+        .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
         .filter(tasks::id.eq_any(task_ids_to_rerun))
         .set(tasks::status.eq(mark))
         .execute(connection)
@@ -226,6 +237,7 @@ impl MarkRerun for LogFatal {
       update(tasks::table)
         .filter(tasks::corpus_id.eq(&corpus_id))
         .filter(tasks::service_id.eq(&service_id))
+        .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
         .filter(tasks::id.eq_any(task_ids_to_rerun))
         .set(tasks::status.eq(mark))
         .execute(connection)
@@ -253,6 +265,7 @@ impl MarkRerun for LogInvalid {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
@@ -274,6 +287,7 @@ impl MarkRerun for LogInvalid {
     update(tasks::table)
       .filter(tasks::corpus_id.eq(&corpus_id))
       .filter(tasks::service_id.eq(&service_id))
+      .filter(tasks::status.between(TaskStatus::Invalid.raw(), TaskStatus::NoProblem.raw()))
       .filter(tasks::id.eq_any(task_ids_to_rerun))
       .set(tasks::status.eq(mark))
       .execute(connection)
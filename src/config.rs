@@ -145,6 +145,29 @@ pub struct DispatcherConfig {
   /// both this and `lease_timeout_seconds` is what lets a fast chaos test exercise reaper-based
   /// recovery in seconds instead of the hour-scale production timing.)
   pub reap_interval_seconds: i64,
+  /// **Lease renewal cap.** How many times a worker's "still working on task N" heartbeat may
+  /// extend one in-flight lease (each renewal pushes the deadline to one `lease_timeout_seconds`
+  /// past the heartbeat), so a legitimately slow conversion keeps its lease without a huge
+  /// per-service timeout, while a runaway worker still cannot hold a task forever: past the cap
+  /// heartbeats are ignored and the lease lapses normally. A service's `max_lease_renewals`
+  /// overrides it. Default **12** (with the 240 s lease, up to ~50 min of renewed work); `0`
+  /// disables renewal.
+  pub max_lease_renewals: i64,
+  /// **Poison-document hold threshold.** How many leases of one entry may end without a usable
+  /// result — expired (the worker crashed, was OOM-killed or hung — KNOWN_ISSUES W-6) or reported
+  /// by the worker as a crash or timeout — across services and runs, before the entry is held: its
+  /// tasks are skipped by the lease query until an operator releases it, so a rerun campaign stops
+  /// losing workers to the same document. Default **6** (two runs' worth of retried leases); `0`
+  /// disables holding (strikes are still recorded). Formerly `quarantine_threshold`, still read.
+  #[serde(alias = "quarantine_threshold")]
+  pub poison_hold_threshold: i64,
+  /// **Class-jump regression threshold (percent).** When a run closes on drain, a message class
+  /// (`severity/category/what`) present in the previous run is flagged as a regression if its task
+  /// count grew by at least this percentage. Default **50**.
+  pub regression_jump_percent: i64,
+  /// **Class-jump floor (tasks).** A class jump is flagged only if it also grew by at least this
+  /// many tasks, so a class going from 2 tasks to 3 is not a regression. Default **10**.
+  pub regression_jump_min_tasks: i64,
   /// **TCP keepalive idle (seconds) on the worker-facing ZMQ sockets** (ventilator + sink). After
   /// this many idle seconds the OS begins probing the peer; this both keeps idle worker
   /// connections alive across NAT/firewall idle-timeouts — essential when the ~200 remote
@@ -178,6 +201,33 @@ pub struct DispatcherConfig {
   /// cold. The typical batch (~`queue_size` × mean-entry ≈ a few hundred MiB) warms fully well
   /// under this. Default **8192** (8 GiB).
   pub prefetch_budget_mb: usize,
+  /// **Authenticated, encrypted worker transport.** When on, the ventilator is a ZMQ **CURVE**
+  /// server: every worker connection is encrypted, and its handshake is accepted only for a
+  /// public key on the worker key allowlist (`cortex worker-keys`, `/admin/worker-keys`) — on the
+  /// ventilator and the sink alike. Each lease also carries a random token inside the task-id
+  /// frame (`<taskid>:<token>`, echoed back unchanged by the worker); a result is accepted only
+  /// with a token of its task's lease history. Refused handshakes and rejected results are
+  /// recorded in the audit log. Requires a libzmq built with CURVE support and the server key pair
+  /// in `curve_key_file`. Default **off**.
+  pub worker_auth: bool,
+  /// Path of the dispatcher's CURVE key pair (JSON, Z85-encoded `public_key` / `secret_key`),
+  /// created by `cortex worker-keys --server-key`. Kept out of `cortex.toml` like the admin
+  /// tokens; workers are given only its public key. Default `dispatcher_curve.json`.
+  pub curve_key_file: String,
+  /// **Dispatcher instance heartbeat (seconds).** How often a running dispatcher freshens its
+  /// `dispatcher_instances` row and recovers the leases of dead peers, so several dispatchers can
+  /// share the task store. Default **10**.
+  pub instance_heartbeat_seconds: u64,
+  /// **Dispatcher instance timeout (seconds).** A dispatcher whose heartbeat is older than this is
+  /// presumed dead, and its peers (or its own successor) return its leases to TODO. Keep it
+  /// several heartbeats long, so a database hiccup does not declare a live dispatcher dead — its
+  /// leases would be re-leased while its workers still convert them. Default **60**.
+  pub instance_timeout_seconds: i64,
+  /// **Recover untagged leases at start.** Leases carrying no dispatcher instance were taken by a
+  /// dispatcher predating instance tags, which nothing can tell alive or dead. Off, a starting
+  /// dispatcher leaves them alone; turn it on for one restart once every old dispatcher is gone,
+  /// so their leftovers return to TODO. Default **off**.
+  pub recover_untagged_leases: bool,
 }
 impl Default for DispatcherConfig {
   fn default() -> Self {
@@ -194,10 +244,19 @@ impl Default for DispatcherConfig {
       finalize_flush_ms: 300,
       lease_timeout_seconds: 240,
       reap_interval_seconds: 60,
+      max_lease_renewals: 12,
+      poison_hold_threshold: 6,
+      regression_jump_percent: 50,
+      regression_jump_min_tasks: 10,
       tcp_keepalive_idle_seconds: 120,
       input_prefetchers: 8,
       prefetch_max_entry_mb: 50,
       prefetch_budget_mb: 8192,
+      worker_auth: false,
+      curve_key_file: "dispatcher_curve.json".to_string(),
+      instance_heartbeat_seconds: 10,
+      instance_timeout_seconds: 60,
+      recover_untagged_leases: false,
     }
   }
 }
@@ -275,6 +334,42 @@ impl Default for JobsConfig {
   }
 }
 
+/// Where document inputs and result archives are stored (see [`crate::storage`]). Paths keep their
+/// meaning in every backend: a task `entry` and its result archive paths are recorded as before,
+/// and an object store maps each of them to an object key.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct StorageConfig {
+  /// `local` (the default) — plain paths on a POSIX filesystem, shared by every dispatcher and
+  /// frontend — or `s3`, an S3-compatible object store (AWS S3, MinIO, Ceph RGW, …). The object
+  /// store's credentials are read from the usual `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`
+  /// (and optional `AWS_SESSION_TOKEN`) environment variables, never from `cortex.toml`.
+  pub backend: String,
+  /// `s3`: the endpoint URL, e.g. `https://s3.eu-central-1.amazonaws.com` or
+  /// `http://minio.internal:9000`. Requests are path-style (`<endpoint>/<bucket>/<key>`).
+  pub endpoint: String,
+  /// `s3`: the region requests are signed for. Default `us-east-1` (what MinIO expects too).
+  pub region: String,
+  /// `s3`: the bucket holding the inputs and results.
+  pub bucket: String,
+  /// `s3`: the path that maps to the bucket root — a path `<root>/<rest>` is the object
+  /// `<prefix><rest>`; paths outside it are not stored. Default `/`.
+  pub root: String,
+  /// `s3`: a key prefix prepended to every object key (e.g. `cortex/`). Default empty.
+  pub prefix: String,
+}
+impl Default for StorageConfig {
+  fn default() -> Self {
+    StorageConfig {
+      backend: "local".to_string(),
+      endpoint: String::new(),
+      region: "us-east-1".to_string(),
+      bucket: String::new(),
+      root: "/".to_string(),
+      prefix: String::new(),
+    }
+  }
+}
+
 /// Top-level `CorTeX` runtime configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CortexConfig {
@@ -294,6 +389,8 @@ pub struct CortexConfig {
   pub webauthn: WebauthnConfig,
   /// Background-job lifecycle settings.
   pub jobs: JobsConfig,
+  /// Where document inputs and result archives are stored.
+  pub storage: StorageConfig,
 }
 
 impl CortexConfig {
@@ -363,6 +460,7 @@ pub fn to_persisted_toml(config: &CortexConfig) -> Result<String, toml::ser::Err
     assets: &'a AssetsConfig,
     webauthn: &'a WebauthnConfig,
     jobs: &'a JobsConfig,
+    storage: &'a StorageConfig,
   }
   toml::to_string_pretty(&Persisted {
     database: &config.database,
@@ -370,6 +468,7 @@ pub fn to_persisted_toml(config: &CortexConfig) -> Result<String, toml::ser::Err
     assets: &config.assets,
     webauthn: &config.webauthn,
     jobs: &config.jobs,
+    storage: &config.storage,
   })
 }
 
@@ -1,6 +1,7 @@
 use crate::backend;
 use crate::dispatcher::server;
 use crate::helpers::TaskReport;
+use crate::models::LeaseIncident;
 use std::collections::HashSet;
 use std::error::Error;
 use std::sync::mpsc::{Receiver, RecvTimeoutError};
@@ -20,6 +21,15 @@ fn report_refresh_interval() -> Duration {
   )
 }
 
+/// Connections for the background jobs spawned as runs close: a job holds one for its work and
+/// briefly takes another to record its outcome, and a few scopes may close in one tick.
+const RUN_JOBS_POOL_SIZE: u32 = 4;
+
+/// How often an idle finalize loop looks for closed runs still owing a regression summary (one
+/// small indexed query), so a summary lost to a restart or a failed job is recovered without new
+/// work landing first.
+const REGRESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Specifies the binding and operation parameters for a thread that saves finalized tasks to the DB
 pub struct Finalize {
   /// the DB address to bind on
@@ -76,6 +86,9 @@ impl Finalize {
   /// `job_limit` counter, which was the unit that disagreed with the other two threads).
   pub fn start(&self, done_rx: Receiver<TaskReport>) -> Result<(), Box<dyn Error>> {
     let mut backend = backend::from_address(&self.backend_address);
+    // Background jobs spawned for a closed run (its regression summary) check out their own
+    // connections from here, never the finalize one.
+    let jobs_pool = backend::build_pool(&self.backend_address, RUN_JOBS_POOL_SIZE);
     let dispatcher = &crate::config::config().dispatcher;
     let batch_size = dispatcher.finalize_batch_size.max(1);
     let flush_window = Duration::from_millis(dispatcher.finalize_flush_ms);
@@ -88,6 +101,9 @@ impl Finalize {
     // invalidation. On drain / periodic tick we drop ONLY these scopes' cached report grains (a
     // cheap keyed DELETE), so each repopulates lazily on its next view — never a global scan.
     let mut touched: HashSet<(i32, i32)> = HashSet::new();
+    // When the idle loop last retried the owed regression summaries; `None` sweeps on the first
+    // idle tick, recovering those a previous process left behind.
+    let mut last_regression_sweep: Option<Instant> = None;
     loop {
       match done_rx.recv_timeout(Duration::from_secs(1)) {
         Ok(first) => {
@@ -96,6 +112,7 @@ impl Finalize {
           let batch_len = batch.len();
           let persist_start = Instant::now();
           server::mark_done_batch(&mut backend, &batch)?;
+          record_crash_strikes(&mut backend, &batch);
           jobs_count += 1;
           reports_dirty = true;
           for report in &batch {
@@ -116,14 +133,14 @@ impl Finalize {
           );
           // Long runs may never idle, so bound report staleness with a periodic refresh.
           if last_report_refresh.elapsed() >= report_refresh_interval() {
-            settle_touched(&mut backend, &mut touched);
+            settle_touched(&mut backend, &jobs_pool, &mut touched);
             reports_dirty = false;
             last_report_refresh = Instant::now();
           }
           if disconnected {
             // Producers vanished mid-batch: we persisted what we had; make it visible and stop.
             if reports_dirty {
-              settle_touched(&mut backend, &mut touched);
+              settle_touched(&mut backend, &jobs_pool, &mut touched);
             }
             break;
           }
@@ -132,15 +149,20 @@ impl Finalize {
           // The queue just idled: close any historical run whose work has drained, recompute the
           // rollup so finished work shows up in reports immediately, then keep waiting.
           if reports_dirty {
-            settle_touched(&mut backend, &mut touched);
+            settle_touched(&mut backend, &jobs_pool, &mut touched);
             reports_dirty = false;
             last_report_refresh = Instant::now();
+            last_regression_sweep = Some(Instant::now());
+          } else if last_regression_sweep.is_none_or(|at| at.elapsed() >= REGRESSION_SWEEP_INTERVAL)
+          {
+            retry_pending_regressions(&jobs_pool);
+            last_regression_sweep = Some(Instant::now());
           }
         },
         Err(RecvTimeoutError::Disconnected) => {
           // Every producer (sink + ventilator) has gone — clean shutdown. Flush and stop.
           if reports_dirty {
-            settle_touched(&mut backend, &mut touched);
+            settle_touched(&mut backend, &jobs_pool, &mut touched);
           }
           break;
         },
@@ -150,27 +172,78 @@ impl Finalize {
   }
 }
 
+/// Records a poison-document strike for every result of a persisted batch whose worker reported a
+/// crash or timeout ([`LeaseIncident::from_report`]) — the harness kills such a conversion and
+/// returns a `Fatal` result, so its lease never expires for the reaper to count. An entry reaching
+/// the hold threshold is skipped by the lease query from then on (tasks the ventilator already
+/// queued for it are still dispatched once). A small keyed upsert, and only for crashed results; a
+/// failure is logged and dropped, losing only the strikes.
+fn record_crash_strikes(backend: &mut backend::Backend, batch: &[TaskReport]) {
+  let incidents: Vec<LeaseIncident> = batch
+    .iter()
+    .filter_map(LeaseIncident::from_report)
+    .collect();
+  if incidents.is_empty() {
+    return;
+  }
+  let threshold = crate::config::config().dispatcher.poison_hold_threshold;
+  match backend.record_lease_incidents(&incidents, threshold) {
+    Ok(held) => {
+      for poison in &held {
+        warn!(
+          entry = %poison.entry,
+          strikes = poison.strikes,
+          "finalize: held a poison document (its workers keep crashing on it)"
+        );
+      }
+    },
+    Err(error) => warn!(%error, "finalize: failed to record crash strikes (non-fatal)"),
+  }
+}
+
 /// On drain/idle, settle the `(corpus, service)` scopes touched since the last tick: first close
-/// any historical runs whose work has drained (run-completion-on-drain), then drop those scopes'
-/// cached report grains so the next report view repopulates. Order matters — closing the run
-/// freezes its tallies, and we invalidate the report cache *after* so the next view reads the
-/// frozen, completed run rather than the stale open one. Drains `touched`.
-fn settle_touched(backend: &mut backend::Backend, touched: &mut HashSet<(i32, i32)>) {
+/// any historical runs whose work has drained (run-completion-on-drain), start the regression
+/// summaries they (and any earlier run) still owe, then drop those scopes' cached report grains so
+/// the next report view repopulates. Order matters — closing the run freezes its tallies, and we
+/// invalidate the report cache *after* so the next view reads the frozen, completed run rather than
+/// the stale open one. Drains `touched`.
+fn settle_touched(
+  backend: &mut backend::Backend,
+  jobs_pool: &backend::DbPool,
+  touched: &mut HashSet<(i32, i32)>,
+) {
   complete_drained_runs(backend, touched);
+  retry_pending_regressions(jobs_pool);
   invalidate_touched(backend, touched);
 }
 
+/// Starts a background job for every closed run still owing its regression summary — a run just
+/// closed on drain, or one whose job was lost or failed (`jobs::retry_pending_run_regressions`).
+/// A failure is logged; the next sweep retries.
+fn retry_pending_regressions(jobs_pool: &backend::DbPool) {
+  match crate::jobs::retry_pending_run_regressions(jobs_pool, "dispatcher") {
+    Ok(0) => {},
+    Ok(started) => debug!(started, "finalize: started owed regression summaries"),
+    Err(error) => warn!(%error, "finalize: could not start owed regression summaries (non-fatal)"),
+  }
+}
+
 /// Run-completion-on-drain: for each scope we've persisted results for since the last tick, close
 /// its open historical run if the pair's work is now exhausted (no task left `TODO`, `Queued`, or
 /// `Blocked`). This fires the "run ended" event the instant the queue drains, instead of lazily
-/// when the next rerun starts — so a finished run stops showing as "ongoing" right away. Read-only
-/// over `touched` (the following [`invalidate_touched`] drains it). A per-scope failure is logged
-/// and skipped so the finalize thread keeps running; the next idle/periodic tick retries.
+/// when the next rerun starts — so a finished run stops showing as "ongoing" right away. Closing
+/// freezes the run's tasks and message classes; its regression summary follows from
+/// [`retry_pending_regressions`]. Read-only over `touched` (the following [`invalidate_touched`]
+/// drains it). A per-scope failure is logged and skipped so the finalize thread keeps running; the
+/// next idle/periodic tick retries.
 fn complete_drained_runs(backend: &mut backend::Backend, touched: &HashSet<(i32, i32)>) {
   for &(corpus_id, service_id) in touched {
     match backend.complete_run_if_drained(corpus_id, service_id) {
-      Ok(true) => debug!(corpus_id, service_id, "finalize: closed drained run"),
-      Ok(false) => {},
+      Ok(Some(run_id)) => debug!(
+        corpus_id,
+        service_id, run_id, "finalize: closed drained run"
+      ),
+      Ok(None) => {},
       Err(e) => warn!(
         corpus_id,
         service_id,
@@ -213,9 +286,13 @@ mod tests {
         corpus_id: 1,
         status: 0,
         entry: String::new(),
+        priority: 0,
+        dispatcher_id: None,
       },
       status: TaskStatus::NoProblem,
       messages: Vec::new(),
+      archive: None,
+      previous_archive: None,
     }
   }
 
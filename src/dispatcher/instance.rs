@@ -0,0 +1,140 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This dispatcher's **instance heartbeat** (`models::DispatcherInstance`): a background thread
+//! that freshens the instance row every `dispatcher.instance_heartbeat_seconds`, and on the same
+//! tick returns the leases of dead peers to TODO — so a dispatcher that crashed is recovered by
+//! whichever peer is still running, not only by its own restart. The thread only touches the
+//! database; the dispatch loop never waits on it.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tracing::{error, info, warn};
+
+use crate::backend::{self, DbPool, build_pool};
+use crate::config::config;
+use crate::models::DispatcherInstance;
+
+/// How often the heartbeat thread checks whether it was asked to stop.
+const STOP_POLL: Duration = Duration::from_millis(250);
+/// Logged when a clean stop could not release the instance's leases.
+const UNRELEASED: &str =
+  "dispatcher: could not release the instance's leases; peers recover them after the timeout";
+
+/// The running heartbeat of one registered dispatcher instance. Dropping it stops the heartbeat
+/// without releasing the instance's leases (a crash path: peers recover them after the timeout);
+/// [`InstanceHeartbeat::stop`] is the clean shutdown.
+pub struct InstanceHeartbeat {
+  /// the registered instance
+  pub id: i32,
+  pool: DbPool,
+  stop: Arc<AtomicBool>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl InstanceHeartbeat {
+  /// Registers this process as a dispatcher instance serving the given ports, and starts its
+  /// heartbeat.
+  pub fn register(
+    backend_address: &str,
+    source_port: usize,
+    result_port: usize,
+  ) -> Result<InstanceHeartbeat, diesel::result::Error> {
+    let mut connection = backend::connection_at(backend_address);
+    let instance = DispatcherInstance::register(&mut connection, source_port, result_port)?;
+    let pool = build_pool(backend_address, 1);
+    info!(
+      instance = instance.id,
+      host = %instance.host,
+      "dispatcher: registered instance; its leases are recovered only once it stops or goes silent"
+    );
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+      let pool = pool.clone();
+      let stop = Arc::clone(&stop);
+      thread::spawn(move || beat(&pool, instance.id, &stop))
+    };
+    Ok(InstanceHeartbeat {
+      id: instance.id,
+      pool,
+      stop,
+      thread: Some(thread),
+    })
+  }
+
+  /// Clean shutdown, once the in-flight set has drained: stops the heartbeat, marks the instance
+  /// stopped and returns its remaining leases (leased but never answered) to TODO, so peers pick
+  /// them up right away instead of after the heartbeat timeout.
+  pub fn stop(mut self) {
+    self.halt();
+    match self.pool.get() {
+      Ok(mut connection) => match DispatcherInstance::stop(&mut connection, self.id) {
+        Ok(released) => info!(
+          instance = self.id,
+          released, "dispatcher: instance stopped; released its remaining leases"
+        ),
+        Err(e) => warn!(instance = self.id, error = %e, "{UNRELEASED}"),
+      },
+      Err(e) => warn!(instance = self.id, error = %e, "{UNRELEASED}"),
+    }
+  }
+
+  fn halt(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for InstanceHeartbeat {
+  fn drop(&mut self) { self.halt(); }
+}
+
+/// The heartbeat loop: freshen the instance row, then recover dead peers' leases, every
+/// `instance_heartbeat_seconds`. Database errors are logged and retried on the next tick — a
+/// heartbeat missed for longer than `instance_timeout_seconds` gets this instance presumed dead,
+/// which [`DispatcherInstance::heartbeat`] reports once it gets through again.
+fn beat(pool: &DbPool, id: i32, stop: &AtomicBool) {
+  let dispatcher = &config().dispatcher;
+  let interval = Duration::from_secs(dispatcher.instance_heartbeat_seconds.max(1));
+  let mut next = Instant::now() + interval;
+  while !stop.load(Ordering::SeqCst) {
+    if Instant::now() < next {
+      thread::sleep(STOP_POLL.min(next - Instant::now()));
+      continue;
+    }
+    next = Instant::now() + interval;
+    let Ok(mut connection) = pool.get() else {
+      warn!(
+        instance = id,
+        "dispatcher: heartbeat skipped, no database connection"
+      );
+      continue;
+    };
+    match DispatcherInstance::heartbeat(&mut connection, id) {
+      Ok(true) => error!(
+        instance = id,
+        "dispatcher: this instance was presumed dead (heartbeat lapsed) and its leases recovered by \
+         a peer — tasks it still holds may be converted twice; raise instance_timeout_seconds?"
+      ),
+      Ok(false) => {},
+      Err(e) => warn!(instance = id, error = %e, "dispatcher: heartbeat failed"),
+    }
+    match DispatcherInstance::recover_dead(&mut connection, dispatcher.instance_timeout_seconds) {
+      Ok(0) => {},
+      Ok(recovered) => info!(
+        instance = id,
+        recovered, "dispatcher: returned the leases of dead dispatcher instances to TODO"
+      ),
+      Err(e) => warn!(instance = id, error = %e, "dispatcher: dead-instance recovery failed"),
+    }
+  }
+}
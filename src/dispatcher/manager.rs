@@ -16,11 +16,13 @@ use tracing::{error, info};
 use crate::backend::{build_pool, default_db_address};
 use crate::config::config;
 use crate::dispatcher::finalize::Finalize;
+use crate::dispatcher::instance::InstanceHeartbeat;
 use crate::dispatcher::server::{InFlightSet, SandboxCache, ServiceCache};
 use crate::dispatcher::sink::Sink;
 use crate::dispatcher::ventilator::Ventilator;
 use crate::helpers::TaskReport;
 use crate::models::start_metadata_writer;
+use crate::storage::storage;
 use zmq::Error;
 
 /// Manager struct responsible for dispatching and receiving tasks
@@ -89,12 +91,32 @@ impl TaskManager {
       config().database.pool_size,
     ));
 
+    // Register this dispatcher as a running instance before leasing anything: its leases are
+    // tagged with the instance id, so peer dispatchers sharing the task store leave them alone
+    // while our heartbeat is fresh, and recover them if we die. The id survives ventilator
+    // restarts, which is what lets a restarted ventilator recover its own limbo tasks.
+    let instance = match InstanceHeartbeat::register(
+      &self.backend_address,
+      self.source_port,
+      self.result_port,
+    ) {
+      Ok(instance) => instance,
+      Err(e) => {
+        error!("Could not register the dispatcher instance: {e}");
+        return Err(zmq::Error::ETERM);
+      },
+    };
+    let instance_id = instance.id;
+
     // First prepare the source ventilator
     let source_port = self.source_port;
     let source_queue_size = self.queue_size;
     let source_message_size = self.message_size;
     let source_max_in_flight = self.max_in_flight;
     let source_backend_address = self.backend_address.clone();
+    // Inputs and results go through the configured storage backend (a bad `[storage]` section
+    // panics here, at startup, like the rest of the configuration).
+    let storage = storage();
 
     // Next prepare the finalize thread which will persist finished jobs to the DB. It owns the
     // single receiver end of the bounded done channel (moved in here).
@@ -120,6 +142,7 @@ impl TaskManager {
     let sink_done_tx = done_tx.clone();
     let sink_metadata = metadata.clone();
     let sink_dispatch_complete = dispatch_complete.clone();
+    let sink_storage = storage.clone();
     let sink_thread = thread::spawn(move || {
       Sink {
         port: result_port,
@@ -127,6 +150,7 @@ impl TaskManager {
         message_size: result_message_size,
         backend_address: result_backend_address.clone(),
         metadata: sink_metadata,
+        storage: sink_storage,
       }
       .start(
         &sink_services_arc,
@@ -150,6 +174,7 @@ impl TaskManager {
       let vent_backend_address = source_backend_address.clone();
       let vent_metadata = metadata.clone();
       let vent_dispatch_complete = dispatch_complete.clone();
+      let vent_storage = storage.clone();
       let vent_thread = thread::spawn(move || {
         let ventilator = Ventilator {
           port: source_port,
@@ -158,6 +183,8 @@ impl TaskManager {
           max_in_flight: source_max_in_flight,
           backend_address: vent_backend_address,
           metadata: vent_metadata,
+          instance_id,
+          storage: vent_storage,
         };
         ventilator
           .start(
@@ -202,8 +229,8 @@ impl TaskManager {
       // to the drain path below — join the sink (it finishes the in-flight set), drop the
       // done-sender, flush finalize, exit `Ok` — instead of restarting the ventilator. A
       // dead-worker straggler whose result never returns is backstopped by the supervisor's
-      // stop-timeout SIGKILL; its task recovers via `clear_limbo_tasks_except` on the next
-      // start.
+      // stop-timeout SIGKILL; its task recovers when a peer finds this instance dead, or via
+      // `clear_limbo_tasks_except` on the next start.
       if crate::dispatcher::server::shutdown_requested() {
         info!("Graceful shutdown requested — draining in-flight results, then stopping.");
         break;
@@ -243,6 +270,9 @@ impl TaskManager {
       error!("DB thread died unexpectedly!");
       Err(zmq::Error::ETERM)
     } else {
+      // Everything returned is persisted: release what we still hold leased (batched but never
+      // dispatched, or a straggler that never answered) so peers take it over right away.
+      instance.stop();
       info!("Manager successfully terminated!");
       Ok(())
     }
@@ -3,6 +3,8 @@
 
 /// Finalize thread responsible for registering the returned tasks and messages in the database
 pub mod finalize;
+/// Dispatcher instance registration and heartbeat, for several dispatchers sharing a task store
+pub mod instance;
 /// Manager orchestrating all dispatcher threads
 pub mod manager;
 /// Input-archive page-cache prefetcher (D-20)
@@ -13,3 +15,5 @@ pub mod server;
 pub mod sink;
 /// Emitter ZMQ ventilator component
 pub mod ventilator;
+/// CURVE worker authentication and result lease tokens
+pub mod worker_auth;
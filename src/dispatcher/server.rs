@@ -7,9 +7,9 @@ use std::time::{Duration, Instant};
 use dashmap::DashMap;
 use tracing::{debug, error, warn};
 
-use crate::backend::Backend;
+use crate::backend::{Backend, ControlSignal};
 use crate::helpers::{NewTaskMessage, TaskProgress, TaskReport, TaskStatus};
-use crate::models::Service;
+use crate::models::{LeaseIncident, NewDeadLetter, Service, WorkerRegistration};
 
 /// Probe interval (seconds) between TCP keepalive probes once the idle threshold is crossed, and
 /// the number of unanswered probes before the OS declares the peer dead — fixed sane values so only
@@ -217,7 +217,8 @@ impl InFlightSet {
   }
 
   /// Remove and return every in-flight task past its visibility-timeout deadline (the reaper
-  /// sweep).
+  /// sweep). The deadline is re-checked at removal, so a lease renewed between the scan and the
+  /// removal is kept.
   pub fn take_expired(&self) -> Vec<TaskProgress> {
     let now = chrono::Utc::now().timestamp();
     let expired_keys: Vec<i64> = self
@@ -228,7 +229,10 @@ impl InFlightSet {
       .collect();
     let mut expired_tasks = Vec::with_capacity(expired_keys.len());
     for key in expired_keys {
-      if let Some((_, task_progress)) = self.map.remove(&key) {
+      if let Some((_, task_progress)) = self
+        .map
+        .remove_if(&key, |_, progress| progress.expected_at() < now)
+      {
         self.len.fetch_sub(1, Ordering::AcqRel);
         expired_tasks.push(task_progress);
       }
@@ -236,12 +240,224 @@ impl InFlightSet {
     expired_tasks
   }
 
+  /// Remove and return in-flight task `taskid` for a returned result that carries lease `token`
+  /// (the sink draining a result; see [`TaskProgress::admits_token`]). A result whose token does
+  /// not match leaves the task in flight for its rightful worker.
+  pub fn claim(&self, taskid: i64, token: Option<&str>) -> ResultClaim {
+    if taskid < 0 {
+      return ResultClaim::Unknown;
+    }
+    match self
+      .map
+      .remove_if(&taskid, |_, progress| progress.admits_token(token))
+    {
+      Some((_, progress)) => {
+        self.len.fetch_sub(1, Ordering::AcqRel);
+        ResultClaim::Claimed(progress)
+      },
+      None if self.map.contains_key(&taskid) => ResultClaim::Forged,
+      None => ResultClaim::Unknown,
+    }
+  }
+
+  /// Extends the lease of in-flight task `taskid` from a heartbeat of `worker`, echoing lease
+  /// `token`, that is "still working" on it (see [`TaskProgress::renew`]). The task must be leased
+  /// for `service_id` — the service the heartbeat names — and its current lease must be the
+  /// worker's own ([`TaskProgress::held_by`]), so neither a stray id nor another worker of the same
+  /// service can keep the lease alive.
+  pub fn renew(
+    &self,
+    taskid: i64,
+    service_id: i32,
+    worker: &str,
+    token: Option<&str>,
+  ) -> LeaseRenewal {
+    match self.map.get_mut(&taskid) {
+      Some(mut progress) if progress.task.service_id == service_id => {
+        if !progress.held_by(worker, token) {
+          LeaseRenewal::NotHolder
+        } else if progress.renew(chrono::Utc::now().timestamp()) {
+          LeaseRenewal::Renewed(progress.renewals)
+        } else {
+          LeaseRenewal::CapReached
+        }
+      },
+      _ => LeaseRenewal::Unknown,
+    }
+  }
+
+  /// How many in-flight tasks are currently leased to `worker` (their latest lease) — checked
+  /// against a registered worker's declared concurrency. A scan of the in-flight set, so it is only
+  /// taken for workers that declared a cap.
+  pub fn leased_to(&self, worker: &str) -> usize {
+    self
+      .map
+      .iter()
+      .filter(|entry| {
+        entry
+          .value()
+          .leases
+          .last()
+          .is_some_and(|lease| lease.worker == worker)
+      })
+      .count()
+  }
+
   /// Snapshot of the currently in-flight task ids — used on a ventilator restart to **exclude**
   /// these from the `Queued → TODO` crash-recovery reset (`clear_limbo_tasks_except`), so tasks the
   /// sink is still processing are not re-leased mid-flight (KNOWN_ISSUES D-4).
   pub fn ids(&self) -> Vec<i64> { self.map.iter().map(|entry| *entry.key()).collect() }
 }
 
+/// The outcome of a returned result's claim on its task, see [`InFlightSet::claim`].
+#[derive(Debug)]
+pub enum ResultClaim {
+  /// The task was in flight and the result carries a token of its lease history.
+  Claimed(TaskProgress),
+  /// No such task is in flight (already returned or reaped, or a stray id).
+  Unknown,
+  /// The task is in flight, but the result carries none of its lease tokens.
+  Forged,
+}
+
+/// The outcome of a lease-renewal heartbeat, see [`InFlightSet::renew`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaseRenewal {
+  /// The lease was extended; carries how many times this lease has now been renewed.
+  Renewed(i64),
+  /// The lease already used its renewal cap; it lapses at its current deadline.
+  CapReached,
+  /// No such task is in flight for the service (already returned or reaped, or a stray id).
+  Unknown,
+  /// The task is in flight, but its current lease is another worker's (or another token's).
+  NotHolder,
+}
+
+/// The marker frame of a worker's lease-renewal heartbeat on the ventilator socket.
+pub const HEARTBEAT_FRAME: &[u8] = b"heartbeat";
+
+/// Parses the frames trailing the service frame of a ventilator request as a lease-renewal
+/// heartbeat — `[identity, service, "heartbeat", <taskid>[:<token>]]` on the ROUTER, the task-id
+/// frame echoed as leased — returning the task id and the lease token it carries, if any. An
+/// ordinary work request has no trailing frames, so `None` keeps it a work request; any other
+/// trailing shape (the over-long / malformed requests drained for frame alignment) is `None` too.
+pub fn parse_heartbeat(trailing: &[zmq::Message]) -> Option<(i64, Option<String>)> {
+  match trailing {
+    [marker, taskid] if &marker[..] == HEARTBEAT_FRAME => taskid
+      .as_str()
+      .map(|handle| crate::dispatcher::worker_auth::parse_task_handle(handle.trim()))
+      .filter(|(id, _)| *id > 0)
+      .map(|(id, token)| (id, token.map(str::to_string))),
+    _ => None,
+  }
+}
+
+/// The marker frame of a worker's registration on the ventilator socket.
+pub const REGISTER_FRAME: &[u8] = b"register";
+
+/// Upper bound on a registration frame's JSON payload; a bigger one is rejected unparsed.
+const MAX_REGISTRATION_BYTES: usize = 4096;
+
+/// Parses the frames trailing the service frame of a ventilator request as a worker registration —
+/// `[identity, service, "register", <json>]` on the ROUTER. `None` for any other shape (a work
+/// request or a heartbeat); `Some(Err)` for a registration frame whose payload is oversized or not
+/// a valid [`WorkerRegistration`], which the caller discards rather than treating as a request.
+pub fn parse_registration(trailing: &[zmq::Message]) -> Option<Result<WorkerRegistration, String>> {
+  match trailing {
+    [marker, payload] if &marker[..] == REGISTER_FRAME => {
+      Some(if payload.len() > MAX_REGISTRATION_BYTES {
+        Err(format!("{} byte payload", payload.len()))
+      } else {
+        WorkerRegistration::parse(&payload[..]).map_err(|error| error.to_string())
+      })
+    },
+    _ => None,
+  }
+}
+
+/// Most registrations the ventilator remembers; at the bound the oldest is forgotten (that worker
+/// is then leased to as an unregistered one until it registers again).
+const MAX_REGISTERED_WORKERS: usize = 10_000;
+
+/// The ventilator's view of its registered workers, keyed by `(identity, service id)`: each
+/// worker's declaration and whether it is refused leases. Ventilator-local (never shared), and
+/// bounded by [`MAX_REGISTERED_WORKERS`] so identity churn cannot grow it without limit.
+#[derive(Default)]
+pub struct WorkerRegistry {
+  workers: HashMap<(String, i32), RegisteredWorker>,
+}
+
+/// One registered worker, see [`WorkerRegistry`].
+#[derive(Debug, Clone)]
+pub struct RegisteredWorker {
+  /// What the worker declared.
+  pub registration: WorkerRegistration,
+  /// Why it is refused leases, if it is.
+  pub refusal: Option<String>,
+  /// When it (last) registered, for the eviction order.
+  registered_at: Instant,
+}
+
+impl WorkerRegistry {
+  /// Records (or replaces) `worker`'s registration for `service`, returning the refusal verdict.
+  pub fn register(
+    &mut self,
+    worker: &str,
+    service: &Service,
+    registration: WorkerRegistration,
+  ) -> Option<String> {
+    let key = (worker.to_string(), service.id);
+    if self.workers.len() >= MAX_REGISTERED_WORKERS && !self.workers.contains_key(&key) {
+      let oldest = self
+        .workers
+        .iter()
+        .min_by_key(|(_, registered)| registered.registered_at)
+        .map(|(key, _)| key.clone());
+      if let Some(oldest) = oldest {
+        self.workers.remove(&oldest);
+      }
+    }
+    let refusal = registration.refusal(service);
+    self.workers.insert(
+      key,
+      RegisteredWorker {
+        registration,
+        refusal: refusal.clone(),
+        registered_at: Instant::now(),
+      },
+    );
+    refusal
+  }
+
+  /// `worker`'s registration for `service_id`, if it registered.
+  pub fn get(&self, worker: &str, service_id: i32) -> Option<&RegisteredWorker> {
+    self.workers.get(&(worker.to_string(), service_id))
+  }
+}
+
+/// Takes the next task of `task_queue` for a worker whose declared input limit is
+/// `registration.max_input_bytes` — the task `pop()` would hand out, unless it is too big, in which
+/// case the next one in dispatch order that fits. `input_bytes` sizes a task's input (`None` if
+/// unknown, which fits); it is called at most once per queued task, which keeps the size. Tasks
+/// that don't fit stay queued, in order, for a worker that can take them. An undeclared limit is
+/// exactly `pop()`, and sizes nothing.
+pub fn take_fitting_task(
+  task_queue: &mut Vec<TaskProgress>,
+  registration: Option<&WorkerRegistration>,
+  mut input_bytes: impl FnMut(&TaskProgress) -> Option<u64>,
+) -> Option<TaskProgress> {
+  let Some(registration) = registration.filter(|r| r.max_input_bytes.is_some()) else {
+    return task_queue.pop();
+  };
+  let index = task_queue.iter_mut().rposition(|progress| {
+    if progress.input_bytes.is_none() {
+      progress.input_bytes = Some(input_bytes(progress));
+    }
+    registration.accepts_input(progress.input_bytes.flatten())
+  })?;
+  Some(task_queue.remove(index))
+}
+
 /// Persists a **batch** of finished reports to the Task store, with bounded retry on a transient DB
 /// failure. The finalize thread drains the batch off the bounded done channel and hands it here in
 /// one `mark_done` call (amortizing the round-trip). On exhausted retries it returns `Err`, which
@@ -317,26 +533,33 @@ pub const MAX_DISPATCH_RETRIES: i64 = 1;
 pub enum ExpiredOutcome {
   /// Re-dispatch the task — retry budget remains; its retry count is incremented.
   Requeue(TaskProgress),
-  /// Give up — retry budget exhausted; report the task `Fatal`.
-  Fatal(TaskReport),
+  /// Give up — retry budget exhausted; report the task `Fatal`, and dead-letter it with its lease
+  /// history so the give-up stays distinguishable from a genuine conversion fatal.
+  Fatal(TaskReport, NewDeadLetter),
 }
 
 /// Decides what to do with an in-flight task that timed out: retry it (until
-/// [`MAX_DISPATCH_RETRIES`] dispatches) or give up and report it `Fatal`.
+/// [`MAX_DISPATCH_RETRIES`] dispatches) or give up, report it `Fatal` and dead-letter it.
 pub fn classify_expired(expired: TaskProgress) -> ExpiredOutcome {
   if expired.retries > MAX_DISPATCH_RETRIES {
     let task_id = expired.task.id;
-    ExpiredOutcome::Fatal(TaskReport {
-      task: expired.task,
-      status: TaskStatus::Fatal,
-      messages: vec![NewTaskMessage::new(
-        task_id,
-        "fatal",
-        "cortex".to_string(),
-        "never_completed_with_retries".to_string(),
-        String::new(),
-      )],
-    })
+    let dead_letter = NewDeadLetter::from_progress(&expired);
+    ExpiredOutcome::Fatal(
+      TaskReport {
+        task: expired.task,
+        status: TaskStatus::Fatal,
+        messages: vec![NewTaskMessage::new(
+          task_id,
+          "fatal",
+          "cortex".to_string(),
+          "never_completed_with_retries".to_string(),
+          String::new(),
+        )],
+        archive: None,
+        previous_archive: None,
+      },
+      dead_letter,
+    )
   } else {
     ExpiredOutcome::Requeue(TaskProgress {
       task: expired.task,
@@ -344,26 +567,39 @@ pub fn classify_expired(expired: TaskProgress) -> ExpiredOutcome {
       retries: expired.retries + 1,
       // D-17: a re-lease keeps the per-task lease captured at first dispatch.
       lease_timeout_seconds: expired.lease_timeout_seconds,
+      // The next lease starts with a fresh renewal budget.
+      renewals: 0,
+      renewed_at: 0,
+      max_renewals: expired.max_renewals,
+      // The lease history carries over, for the dead-letter should the retries run out.
+      leases: expired.leases,
+      input_bytes: expired.input_bytes,
     })
   }
 }
 
 /// Tally of one reaping pass — the dispatcher's re-lease / dead-letter health signal (Arm 8
 /// observability). Returned by [`reap_expired_into`] so the ventilator can log it without the
-/// reaper itself reaching for tracing.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// reaper itself reaching for tracing — or the database: the pass's dead-letters and lease
+/// incidents are handed back for the ventilator to persist.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ReapSummary {
   /// Timed-out tasks re-queued for another dispatch attempt (retry budget remained).
   pub requeued: usize,
   /// Timed-out tasks given up on and reported `Fatal` (retry budget exhausted) — dead-letters.
   pub dead_lettered: usize,
+  /// The dead-letter record of each task given up on, with its lease history.
+  pub dead_letters: Vec<NewDeadLetter>,
+  /// One incident per expired lease (re-queued or not), for the poison-document history.
+  pub incidents: Vec<LeaseIncident>,
 }
 
 /// Reaps timed-out in-flight tasks and routes each to **its own service's** dispatch queue (a
 /// retry) or the done queue (a `Fatal`). Decoupled from the refetch path so the in-flight set
 /// drains even under sustained backpressure (KNOWN_ISSUES D-6); routing by `task.service_id` rather
 /// than the requesting service fixes the latent cross-service requeue bug. Returns a
-/// [`ReapSummary`] of what it did (re-leases vs dead-letters) for the health log.
+/// [`ReapSummary`] of what it did (re-leases vs dead-letters) for the health log, with the
+/// dead-letters and lease incidents for the ventilator to persist.
 pub fn reap_expired_into(
   queues: &mut HashMap<i32, Vec<TaskProgress>>,
   in_flight: &InFlightSet,
@@ -371,6 +607,9 @@ pub fn reap_expired_into(
 ) -> ReapSummary {
   let mut summary = ReapSummary::default();
   for expired in in_flight.take_expired() {
+    summary
+      .incidents
+      .push(LeaseIncident::from_progress(&expired));
     match classify_expired(expired) {
       ExpiredOutcome::Requeue(task_progress) => {
         queues
@@ -379,9 +618,10 @@ pub fn reap_expired_into(
           .push(task_progress);
         summary.requeued += 1;
       },
-      ExpiredOutcome::Fatal(report) => {
+      ExpiredOutcome::Fatal(report, dead_letter) => {
         send_done(done_tx, report);
         summary.dead_lettered += 1;
+        summary.dead_letters.push(dead_letter);
       },
     }
   }
@@ -437,10 +677,44 @@ pub fn get_sandbox_id(corpus_id: i32, sandboxes: &SandboxCache) -> Option<i32> {
   sandboxes.get(&corpus_id).and_then(|entry| *entry.value())
 }
 
+/// Drops the tasks of a just-**paused** scope from the ventilator's per-service dispatch queues and
+/// returns how many were dropped. The pause already moved those rows to Blocked (it blocks every
+/// `status >= 0` task, leased marks included), so the queued copies are stale: dispatching one
+/// would convert a paused document and its result would overwrite the Blocked status. Dropping is
+/// safe — the rows are Blocked, not leased, and a resume returns them to TODO for a fresh fetch.
+/// In-flight tasks are untouched; their results still land, exactly as before a pause.
+pub fn drop_paused(queues: &mut HashMap<i32, Vec<TaskProgress>>, signal: &ControlSignal) -> usize {
+  let mut dropped = 0;
+  for (service_id, queue) in queues.iter_mut() {
+    if signal.service_id.is_some_and(|id| id != *service_id) {
+      continue;
+    }
+    let before = queue.len();
+    queue.retain(|progress| !signal.covers(progress.task.corpus_id, progress.task.service_id));
+    dropped += before - queue.len();
+  }
+  dropped
+}
+
+/// Re-reads every cached `Service` row in place (the reaction to a service-setting wake-up), so a
+/// new `lease_timeout_seconds` or renewal cap is picked up without a dispatcher restart. Entries
+/// are overwritten, never removed: the sink resolves in-flight results through this cache without
+/// DB access, so a momentarily-missing entry would discard a result. A failed lookup keeps the old
+/// row.
+pub fn refresh_service_cache(services: &ServiceCache, backend: &mut Backend) {
+  let names: Vec<String> = services.iter().map(|entry| entry.key().clone()).collect();
+  for name in names {
+    if let Ok(service) = Service::find_by_name(&name, &mut backend.connection) {
+      services.insert(name, Some(service));
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
   use crate::config::DispatcherConfig;
+  use crate::helpers::LeaseRecord;
   use crate::models::Task;
   use std::sync::Arc;
 
@@ -452,10 +726,36 @@ mod tests {
         corpus_id: 1,
         status: 0,
         entry: String::new(),
+        priority: 0,
+        dispatcher_id: None,
       },
       created_at: 0,
       retries: 0,
       lease_timeout_seconds: 3600,
+      renewals: 0,
+      renewed_at: 0,
+      max_renewals: 2,
+      leases: Vec::new(),
+      input_bytes: None,
+    }
+  }
+
+  fn dummy_service() -> Service {
+    Service {
+      id: 5,
+      name: "tex_to_html".to_string(),
+      version: 0.1,
+      inputformat: "tex".to_string(),
+      outputformat: "html".to_string(),
+      inputconverter: None,
+      complex: true,
+      description: String::new(),
+      public_id: uuid::Uuid::nil(),
+      lease_timeout_seconds: None,
+      on_upstream_rerun: "rerun".to_string(),
+      max_lease_renewals: None,
+      keep_result_versions: 0,
+      log_parser: None,
     }
   }
 
@@ -499,6 +799,20 @@ mod tests {
     let summary = reap_expired_into(&mut queues, &set, &done_tx);
     assert_eq!(summary.requeued, 2, "two within retry budget are re-leased");
     assert_eq!(summary.dead_lettered, 1, "one over budget is dead-lettered");
+    assert_eq!(
+      summary
+        .dead_letters
+        .iter()
+        .map(|dl| dl.task_id)
+        .collect::<Vec<_>>(),
+      [3],
+      "the dead-letter is handed back for persisting"
+    );
+    assert_eq!(
+      summary.incidents.len(),
+      3,
+      "every expired lease is a poison-document strike, retried or not"
+    );
     assert_eq!(set.len(), 0, "the in-flight set fully drained");
     assert_eq!(
       done_rx.try_iter().count(),
@@ -626,16 +940,283 @@ mod tests {
     tp
   }
 
+  #[test]
+  fn heartbeats_extend_a_lease_up_to_the_cap() {
+    let now = chrono::Utc::now().timestamp();
+    let set = InFlightSet::new();
+    let mut slow = dummy_progress(1);
+    slow.task.service_id = 5;
+    slow.created_at = now - 100;
+    slow.lease_timeout_seconds = 60; // expected_at = now - 40 → due for reaping
+    slow.leases.push(LeaseRecord {
+      worker: "w1".to_string(),
+      leased_at: now - 100,
+      token: None,
+    });
+    set.insert(slow);
+    assert_eq!(
+      set.renew(1, 6, "w1", None),
+      LeaseRenewal::Unknown,
+      "another service's worker cannot renew the lease"
+    );
+    assert_eq!(set.renew(2, 5, "w1", None), LeaseRenewal::Unknown);
+    assert_eq!(
+      set.renew(1, 5, "w2", None),
+      LeaseRenewal::NotHolder,
+      "another worker of the service cannot renew the lease"
+    );
+    assert_eq!(set.renew(1, 5, "w1", None), LeaseRenewal::Renewed(1));
+    assert!(
+      set.take_expired().is_empty(),
+      "the renewed lease runs a full timeout past the heartbeat"
+    );
+    assert_eq!(set.renew(1, 5, "w1", None), LeaseRenewal::Renewed(2));
+    assert_eq!(
+      set.renew(1, 5, "w1", None),
+      LeaseRenewal::CapReached,
+      "the cap stops a runaway worker from holding the lease forever"
+    );
+    // A re-lease after expiry starts with a fresh renewal budget.
+    let mut lapsed = set.remove(1).expect("still in flight");
+    lapsed.renewed_at = now - 100;
+    match classify_expired(lapsed) {
+      ExpiredOutcome::Requeue(tp) => assert_eq!((tp.renewals, tp.renewed_at), (0, 0)),
+      ExpiredOutcome::Fatal(..) => panic!("within retry budget -> should requeue"),
+    }
+  }
+
+  #[test]
+  fn heartbeats_renew_only_the_current_lease_token() {
+    let set = InFlightSet::new();
+    let mut guarded = dummy_progress(1);
+    for (worker, token) in [("w1", "first"), ("w2", "second")] {
+      guarded.leases.push(LeaseRecord {
+        worker: worker.to_string(),
+        leased_at: 0,
+        token: Some(token.to_string()),
+      });
+    }
+    set.insert(guarded);
+    assert_eq!(set.renew(1, 1, "w2", None), LeaseRenewal::NotHolder);
+    assert_eq!(
+      set.renew(1, 1, "w2", Some("guess")),
+      LeaseRenewal::NotHolder
+    );
+    assert_eq!(
+      set.renew(1, 1, "w1", Some("first")),
+      LeaseRenewal::NotHolder,
+      "a superseded lease cannot be kept alive"
+    );
+    assert_eq!(
+      set.renew(1, 1, "w2", Some("second")),
+      LeaseRenewal::Renewed(1)
+    );
+  }
+
+  #[test]
+  fn parse_heartbeat_accepts_only_the_heartbeat_shape() {
+    let frames = |parts: &[&str]| -> Vec<zmq::Message> {
+      parts.iter().map(|part| zmq::Message::from(*part)).collect()
+    };
+    assert_eq!(
+      parse_heartbeat(&frames(&["heartbeat", "42"])),
+      Some((42, None))
+    );
+    assert_eq!(
+      parse_heartbeat(&frames(&["heartbeat", "42:0f0f"])),
+      Some((42, Some("0f0f".to_string()))),
+      "the echoed task handle of a token-bearing lease"
+    );
+    assert_eq!(parse_heartbeat(&frames(&[])), None, "a work request");
+    assert_eq!(parse_heartbeat(&frames(&["heartbeat"])), None);
+    assert_eq!(parse_heartbeat(&frames(&["heartbeat", "-1"])), None);
+    assert_eq!(parse_heartbeat(&frames(&["heartbeat", "x"])), None);
+    assert_eq!(parse_heartbeat(&frames(&["renew", "42"])), None);
+  }
+
+  #[test]
+  fn results_claim_their_task_only_with_a_lease_token() {
+    let set = InFlightSet::new();
+    let lease = |worker: &str, token: Option<&str>| LeaseRecord {
+      worker: worker.to_string(),
+      leased_at: 0,
+      token: token.map(str::to_string),
+    };
+    // Without worker auth, leases carry no token and any result completes its task.
+    let mut open = dummy_progress(1);
+    open.leases.push(lease("w1", None));
+    set.insert(open);
+    assert!(matches!(set.claim(1, None), ResultClaim::Claimed(_)));
+
+    let mut guarded = dummy_progress(2);
+    guarded.leases.push(lease("w1", Some("first")));
+    guarded.leases.push(lease("w2", Some("second")));
+    set.insert(guarded);
+    assert!(matches!(set.claim(2, None), ResultClaim::Forged));
+    assert!(matches!(set.claim(2, Some("guess")), ResultClaim::Forged));
+    assert_eq!(set.len(), 1, "a forged result leaves the task in flight");
+    assert!(
+      matches!(set.claim(2, Some("first")), ResultClaim::Claimed(_)),
+      "an earlier lease's result is as good as the latest one's"
+    );
+    assert!(matches!(set.claim(2, Some("first")), ResultClaim::Unknown));
+    assert!(matches!(set.claim(-1, None), ResultClaim::Unknown));
+    assert!(set.is_empty());
+  }
+
+  #[test]
+  fn parse_registration_accepts_only_the_registration_shape() {
+    let frames = |parts: &[&str]| -> Vec<zmq::Message> {
+      parts.iter().map(|part| zmq::Message::from(*part)).collect()
+    };
+    let parsed = parse_registration(&frames(&[
+      "register",
+      r#"{"version": 0.8, "concurrency": 2}"#,
+    ]));
+    match parsed {
+      Some(Ok(registration)) => {
+        assert_eq!(registration.version, Some(0.8));
+        assert_eq!(registration.concurrency, Some(2));
+      },
+      other => panic!("a registration, got {other:?}"),
+    }
+    assert!(matches!(
+      parse_registration(&frames(&["register", "{not json"])),
+      Some(Err(_))
+    ));
+    let oversized = format!(r#"{{"host": "{}"}}"#, "x".repeat(MAX_REGISTRATION_BYTES));
+    assert!(matches!(
+      parse_registration(&frames(&["register", &oversized])),
+      Some(Err(_))
+    ));
+    assert!(parse_registration(&frames(&[])).is_none(), "a work request");
+    assert!(parse_registration(&frames(&["heartbeat", "42"])).is_none());
+    assert!(parse_registration(&frames(&["register"])).is_none());
+  }
+
+  #[test]
+  fn registered_workers_are_held_to_their_declaration() {
+    let mut service = dummy_service();
+    service.version = 0.8;
+    let mut registry = WorkerRegistry::default();
+    let matching = WorkerRegistration {
+      version: Some(0.8),
+      ..WorkerRegistration::default()
+    };
+    assert_eq!(registry.register("w1", &service, matching), None);
+    let stale = WorkerRegistration {
+      version: Some(0.7),
+      ..WorkerRegistration::default()
+    };
+    assert!(registry.register("w2", &service, stale).is_some());
+    assert!(
+      registry
+        .get("w1", service.id)
+        .is_some_and(|w| w.refusal.is_none())
+    );
+    assert!(
+      registry
+        .get("w2", service.id)
+        .is_some_and(|w| w.refusal.is_some())
+    );
+    assert!(
+      registry.get("w1", service.id + 1).is_none(),
+      "registrations are per service"
+    );
+
+    // Concurrency: the in-flight tasks last leased to a worker.
+    let set = InFlightSet::new();
+    for (id, worker) in [(1, "w1"), (2, "w1"), (3, "w3")] {
+      let mut progress = dummy_progress(id);
+      progress.leases.push(LeaseRecord {
+        worker: worker.to_string(),
+        leased_at: 0,
+        token: None,
+      });
+      set.insert(progress);
+    }
+    assert_eq!(set.leased_to("w1"), 2);
+    assert_eq!(set.leased_to("w2"), 0);
+  }
+
+  #[test]
+  fn take_fitting_task_skips_inputs_over_the_declared_limit() {
+    // Popped from the end: 3 is next, then 2, then 1. Task 3's input is too big for the limit.
+    let mut sized = Vec::new();
+    let mut size = |progress: &TaskProgress| {
+      sized.push(progress.task.id);
+      Some(if progress.task.id == 3 { 500 } else { 100 })
+    };
+    let mut queue: Vec<TaskProgress> = (1..=3).map(dummy_progress).collect();
+    let limited = WorkerRegistration {
+      max_input_bytes: Some(200),
+      ..WorkerRegistration::default()
+    };
+    let picked = take_fitting_task(&mut queue, Some(&limited), &mut size).expect("a fitting task");
+    assert_eq!(picked.task.id, 2);
+    let ids: Vec<i64> = queue.iter().map(|progress| progress.task.id).collect();
+    assert_eq!(ids, vec![1, 3], "the big task stays queued, in order");
+    // The big task keeps its size: the next limited pick sizes only the task not yet sized.
+    let picked = take_fitting_task(&mut queue, Some(&limited), &mut size).expect("a fitting task");
+    assert_eq!(picked.task.id, 1);
+    // Without a declared limit it is a plain pop, which sizes nothing.
+    let unlimited = WorkerRegistration::default();
+    let mut queue_unsized = vec![dummy_progress(4)];
+    let picked =
+      take_fitting_task(&mut queue_unsized, Some(&unlimited), &mut size).expect("next task");
+    assert_eq!(picked.task.id, 4);
+    // Nothing fits: nothing is taken, and the big task is still not sized again.
+    assert!(take_fitting_task(&mut queue, Some(&limited), &mut size).is_none());
+    assert_eq!(queue.len(), 1);
+    assert_eq!(
+      sized,
+      vec![3, 2, 1],
+      "each task is sized once, and only for a limited worker"
+    );
+  }
+
   #[test]
   fn classify_expired_retries_then_gives_up() {
     // Budget remains -> requeue with the retry count incremented.
     match classify_expired(expired_progress(1, 3, MAX_DISPATCH_RETRIES)) {
       ExpiredOutcome::Requeue(tp) => assert_eq!(tp.retries, MAX_DISPATCH_RETRIES + 1),
-      ExpiredOutcome::Fatal(_) => panic!("still within retry budget -> should requeue"),
+      ExpiredOutcome::Fatal(..) => panic!("still within retry budget -> should requeue"),
     }
     // Budget exhausted -> Fatal.
     match classify_expired(expired_progress(2, 3, MAX_DISPATCH_RETRIES + 1)) {
-      ExpiredOutcome::Fatal(report) => assert_eq!(report.task.id, 2),
+      ExpiredOutcome::Fatal(report, _) => assert_eq!(report.task.id, 2),
+      ExpiredOutcome::Requeue(_) => panic!("retry budget exhausted -> should be Fatal"),
+    }
+  }
+
+  #[test]
+  fn dead_letters_carry_the_lease_history_across_retries() {
+    let lease = |worker: &str, leased_at: i64| LeaseRecord {
+      worker: worker.to_string(),
+      leased_at,
+      token: None,
+    };
+    let mut progress = expired_progress(4, 3, 0);
+    progress.leases.push(lease("worker-a", 1_000));
+    // Each requeue keeps the leases so far; the ventilator appends the next one on dispatch.
+    for (retry, worker) in (1..=MAX_DISPATCH_RETRIES + 1).zip(["worker-b", "worker-c"]) {
+      progress = match classify_expired(progress) {
+        ExpiredOutcome::Requeue(mut tp) => {
+          assert_eq!(tp.leases.len() as i64, retry);
+          tp.leases.push(lease(worker, 1_000 + retry));
+          tp
+        },
+        ExpiredOutcome::Fatal(..) => panic!("within retry budget -> should requeue"),
+      };
+    }
+    match classify_expired(progress) {
+      ExpiredOutcome::Fatal(_, dead_letter) => {
+        assert_eq!(dead_letter.task_id, 4);
+        assert_eq!(i64::from(dead_letter.retries), MAX_DISPATCH_RETRIES + 1);
+        assert_eq!(dead_letter.workers, ["worker-a", "worker-b", "worker-c"]);
+        assert_eq!(dead_letter.leased_at.len(), 3);
+        assert_eq!(dead_letter.leased_at[2].and_utc().timestamp(), 1_002);
+      },
       ExpiredOutcome::Requeue(_) => panic!("retry budget exhausted -> should be Fatal"),
     }
   }
@@ -680,4 +1261,33 @@ mod tests {
     // The in-flight set is fully drained.
     assert_eq!(progress.len(), 0);
   }
+
+  #[test]
+  fn pause_drops_only_the_paused_scope_from_dispatch_queues() {
+    use crate::backend::ControlKind;
+    let queued = |id: i64, corpus_id: i32, service_id: i32| {
+      let mut progress = dummy_progress(id);
+      progress.task.corpus_id = corpus_id;
+      progress.task.service_id = service_id;
+      progress
+    };
+    let mut queues: HashMap<i32, Vec<TaskProgress>> = HashMap::new();
+    queues.insert(7, vec![queued(1, 1, 7), queued(2, 2, 7), queued(3, 1, 7)]);
+    queues.insert(9, vec![queued(4, 1, 9)]);
+
+    // Pausing (corpus 1, service 7) drops just that pair's queued tasks.
+    let pause = ControlSignal::scoped(ControlKind::Pause, 1, 7);
+    assert_eq!(drop_paused(&mut queues, &pause), 2);
+    assert_eq!(queues[&7].len(), 1);
+    assert_eq!(
+      queues[&7][0].task.id, 2,
+      "another corpus on the same service stays"
+    );
+    assert_eq!(queues[&9].len(), 1, "another service stays");
+
+    // The global pause drains every queue.
+    let pause_all = ControlSignal::global(ControlKind::Pause);
+    assert_eq!(drop_paused(&mut queues, &pause_all), 2);
+    assert!(queues.values().all(Vec::is_empty));
+  }
 }
@@ -1,22 +1,26 @@
 use std::error::Error;
-use std::fs::File;
 use std::io;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
+use diesel::Connection;
+use sha2::{Digest, Sha256};
 use tracing::{info, trace, warn};
 use zeromq::{PullSocket, Socket, SocketRecv, ZmqMessage};
 
 use crate::config::config;
-use crate::dispatcher::server;
+use crate::dispatcher::server::ResultClaim;
+use crate::dispatcher::{server, worker_auth};
 use crate::helpers;
+use crate::helpers::log_parsers::LogParser;
 use crate::helpers::{NewTaskMessage, TaskReport, TaskStatus};
-use crate::models::{Task, WorkerMetadataSender};
+use crate::models::{NewResultArchive, NewResultArchiveVersion, Task, WorkerMetadataSender};
+use crate::storage::{ObjectWriter, Storage};
 
 /// Capacity of each archive-writer's command channel. A small bound keeps resident memory at
 /// O(chunk) per writer (a handful of `message_size` chunks at most) while letting a writer stay a
@@ -45,8 +49,12 @@ enum WriteCommand {
   Begin {
     /// the task this result belongs to (moved to the writer, which reports it on commit)
     task: Box<Task>,
-    /// `<entry-dir>/<service>.zip` — where the result archive is written
+    /// `<entry-dir>/<service>.zip` — where the result archive is written (in the sink's storage)
     recv_path: PathBuf,
+    /// how many previous archives the service keeps (`0`: overwrite `recv_path` in place)
+    keep_versions: usize,
+    /// the service's log parser, which reads the result's `cortex.log` at commit
+    parser: &'static dyn LogParser,
   },
   /// Append a received data chunk to the in-progress result file. The bytes are owned/moved here
   /// and dropped right after the write, so the per-job footprint stays O(chunk), never the whole
@@ -62,6 +70,112 @@ enum WriteCommand {
   },
 }
 
+/// The result a writer is receiving: the task, its result path, the key it is written to
+/// (`recv_path` itself, or [`partial_path`] for a service that keeps versions), the open writer
+/// (`None` if create/write failed — then the result is abandoned and the task is left `Queued` for
+/// the reaper, matching the legacy inline behavior but without the socket desync the old early
+/// `continue` caused), and the running size and SHA-256 of the bytes written, recorded with the
+/// report so `cortex fsck` can later tell a damaged archive from an intact one. `keep_versions` is
+/// how many previous archives to keep, and `parser` the service's log parser.
+struct InProgress {
+  task: Task,
+  recv_path: PathBuf,
+  write_path: PathBuf,
+  file: Option<Box<dyn ObjectWriter>>,
+  digest: Sha256,
+  size: u64,
+  keep_versions: usize,
+  parser: &'static dyn LogParser,
+}
+
+/// Where a service that keeps versions receives a new result for the archive at `recv_path`
+/// (`<service>.zip.partial`): the current archive stays in place until the new one validates at
+/// commit, and the scans that look for `.zip` archives never mistake a leftover for one.
+fn partial_path(recv_path: &Path) -> PathBuf {
+  let mut name = recv_path.as_os_str().to_owned();
+  name.push(".partial");
+  PathBuf::from(name)
+}
+
+/// Moves the task's current result archive at `recv_path` aside to a new version
+/// ([`helpers::result_version_path`]) once a new result has validated. The version's checksum,
+/// commit time and run are completed from the archive's record in `mark_done`. `None` when there is
+/// no current archive (a first conversion), or the move failed — then the new result simply
+/// replaces it.
+fn move_aside(
+  task: &Task,
+  recv_path: &Path,
+  storage: &dyn Storage,
+) -> Option<NewResultArchiveVersion> {
+  let size = storage.size(recv_path).ok()?;
+  let now = chrono::Utc::now();
+  // Stamps only ever grow, so a (same-microsecond) collision takes the next free one.
+  let mut stamp = now.timestamp_micros();
+  let mut version = helpers::result_version_path(recv_path, stamp)?;
+  while storage.exists(&version) {
+    stamp += 1;
+    version = helpers::result_version_path(recv_path, stamp)?;
+  }
+  if let Err(e) = storage.rename(recv_path, &version) {
+    warn!(
+      task_id = task.id,
+      path = ?recv_path,
+      error = ?e,
+      "sink writer: could not keep the previous result archive; replacing it"
+    );
+    return None;
+  }
+  Some(NewResultArchiveVersion {
+    task_id: task.id,
+    run_id: None,
+    path: version.to_string_lossy().into_owned(),
+    size_bytes: size as i64,
+    sha256: None,
+    written_at: now.naive_utc(),
+  })
+}
+
+/// Puts a moved-aside archive back at `recv_path` when the validated result cannot take its place,
+/// so a failed write never costs the task its current output.
+fn restore(previous: Option<NewResultArchiveVersion>, recv_path: &Path, storage: &dyn Storage) {
+  if let Some(previous) = previous
+    && let Err(e) = storage.rename(Path::new(&previous.path), recv_path)
+  {
+    warn!(
+      task_id = previous.task_id,
+      path = %previous.path,
+      error = ?e,
+      "sink writer: could not restore the previous result archive"
+    );
+  }
+}
+
+/// Removes all but the `keep` newest kept versions of the archive at `recv_path` (the files; their
+/// records are pruned to the same count in `mark_done`).
+fn prune_versions(recv_path: &Path, keep: usize, storage: &dyn Storage) {
+  let (Some(dir), Some(archive_name)) = (
+    recv_path.parent(),
+    recv_path.file_name().and_then(|name| name.to_str()),
+  ) else {
+    return;
+  };
+  let Ok(objects) = storage.list(dir) else {
+    return;
+  };
+  let mut versions: Vec<(i64, PathBuf)> = objects
+    .into_iter()
+    .filter_map(|object| {
+      let name = object.path.file_name()?.to_str()?;
+      let stamp = helpers::result_version_stamp(name, archive_name)?;
+      Some((stamp, object.path))
+    })
+    .collect();
+  versions.sort_by(|a, b| b.0.cmp(&a.0));
+  for (_, path) in versions.into_iter().skip(keep) {
+    storage.remove(&path).ok();
+  }
+}
+
 /// One archive-writer thread. It drains [`WriteCommand`]s for the tasks the receive loop assigns
 /// it, performing the blocking `/data` write + `cortex.log` parse + finalize hand-off **off** the
 /// receive loop. Per-task ordering is guaranteed because the receive loop sends a task's
@@ -69,53 +183,133 @@ enum WriteCommand {
 /// *different* tasks. The writer exits when its channel disconnects (the sink shutting down on
 /// `job_limit`), after draining any buffered commands (so the last result's `Commit` still reaches
 /// finalize — loss-free). A panic here is surfaced by the receive loop (its liveness check / a
-/// send error) → the manager aborts the dispatcher (fail-fast preserved).
-fn run_writer(rx: &Receiver<WriteCommand>, done_tx: &SyncSender<TaskReport>) {
-  // In-progress job state: the task, its result path, and the open file (`None` if create/write
-  // failed — then the result is abandoned and the task is left `Queued` for the reaper, matching
-  // the legacy inline behavior but without the socket desync the old early `continue` caused).
-  let mut current: Option<(Task, PathBuf, Option<File>)> = None;
+/// send error) → the manager aborts the dispatcher (fail-fast preserved). Results are written
+/// through `storage` — a local file, or an object uploaded at commit.
+fn run_writer(
+  rx: &Receiver<WriteCommand>,
+  done_tx: &SyncSender<TaskReport>,
+  storage: &dyn Storage,
+) {
+  let mut current: Option<InProgress> = None;
   while let Ok(cmd) = rx.recv() {
     match cmd {
-      WriteCommand::Begin { task, recv_path } => {
-        let file = match File::create(&recv_path) {
+      WriteCommand::Begin {
+        task,
+        recv_path,
+        keep_versions,
+        parser,
+      } => {
+        let write_path = if keep_versions > 0 {
+          partial_path(&recv_path)
+        } else {
+          recv_path.clone()
+        };
+        let file = match storage.create(&write_path) {
           Ok(f) => Some(f),
           Err(e) => {
             warn!(
               task_id = task.id,
-              path = ?recv_path,
+              path = ?write_path,
               error = ?e,
-              "sink writer: File::create failed; task left Queued for the reaper"
+              "sink writer: storage create failed; task left Queued for the reaper"
             );
             None
           },
         };
-        current = Some((*task, recv_path, file));
+        current = Some(InProgress {
+          task: *task,
+          recv_path,
+          write_path,
+          file,
+          digest: Sha256::new(),
+          size: 0,
+          keep_versions,
+          parser,
+        });
       },
       WriteCommand::Chunk(bytes) => {
-        if let Some((task, _, slot)) = current.as_mut()
-          && let Some(file) = slot
-          && let Err(e) = file.write_all(&bytes)
+        if let Some(job) = current.as_mut()
+          && let Some(file) = job.file.as_mut()
         {
-          warn!(
-            task_id = task.id,
-            error = ?e,
-            "sink writer: write to result file failed; abandoning result"
-          );
-          // Stop writing; the (now-partial) file makes the result untrustworthy, so Commit
-          // skips the report and the task is recovered by the reaper.
-          *slot = None;
+          match file.write_all(&bytes) {
+            Ok(()) => {
+              job.digest.update(&bytes);
+              job.size += bytes.len() as u64;
+            },
+            Err(e) => {
+              warn!(
+                task_id = job.task.id,
+                error = ?e,
+                "sink writer: write to result file failed; abandoning result"
+              );
+              // Stop writing; the (now-partial) file makes the result untrustworthy, so Commit
+              // skips the report and the task is recovered by the reaper.
+              job.file = None;
+            },
+          }
         }
         // `bytes` dropped here — tight deallocation, O(chunk) resident.
       },
       WriteCommand::Commit => {
-        if let Some((task, recv_path, file)) = current.take() {
-          match file {
-            Some(f) => {
-              drop(f); // flush + close before generate_report reads the archive back
+        if let Some(InProgress {
+          task,
+          recv_path,
+          write_path,
+          file,
+          digest,
+          size,
+          keep_versions,
+          parser,
+        }) = current.take()
+        {
+          // Flush + close (or upload) before generate_report reads the archive back.
+          let committed = file.map(|f| {
+            f.commit().map_err(|e| {
+              warn!(
+                task_id = task.id,
+                error = ?e,
+                "sink writer: committing the result failed; skipping report (reaper will recover)"
+              );
+            })
+          });
+          match committed {
+            Some(Ok(())) => {
               let task_id = task.id;
-              match helpers::generate_report(task, &recv_path) {
-                Some(report) => server::send_done(done_tx, report),
+              match helpers::generate_report(task, &write_path, storage, parser) {
+                Some(mut report) => {
+                  // Only a result that validated replaces the current archive, which is kept.
+                  let previous = if write_path != recv_path {
+                    let previous = move_aside(&report.task, &recv_path, storage);
+                    if let Err(e) = storage.rename(&write_path, &recv_path) {
+                      warn!(
+                        task_id,
+                        error = ?e,
+                        "sink writer: could not put the result in place; skipping report (reaper will recover)"
+                      );
+                      storage.remove(&write_path).ok();
+                      restore(previous, &recv_path, storage);
+                      continue;
+                    }
+                    previous
+                  } else {
+                    None
+                  };
+                  report.archive = Some(NewResultArchive {
+                    task_id,
+                    path: recv_path.to_string_lossy().into_owned(),
+                    size_bytes: size as i64,
+                    sha256: digest
+                      .finalize()
+                      .iter()
+                      .map(|byte| format!("{byte:02x}"))
+                      .collect(),
+                  });
+                  if previous.is_some() {
+                    prune_versions(&recv_path, keep_versions, storage);
+                  }
+                  report.previous_archive = previous;
+                  server::send_done(done_tx, report)
+                },
                 // Unreadable / empty result archive (0-byte, truncated, no `cortex.log`) — an
                 // infrastructure failure, not a verdict. Skip finalizing it (exactly like the
                 // no-file case below) so the lease reaper recovers the task: retry, then
@@ -123,7 +317,7 @@ fn run_writer(rx: &Receiver<WriteCommand>, done_tx: &SyncSender<TaskReport>) {
                 // silent terminal Fatal (D-18). Drop the stale 0-byte artifact so a
                 // retry writes a clean file.
                 None => {
-                  std::fs::remove_file(&recv_path).ok();
+                  storage.remove(&write_path).ok();
                   warn!(
                     task_id,
                     "sink writer: empty/unreadable result archive; skipping report so the reaper recovers the task (D-18)"
@@ -131,7 +325,15 @@ fn run_writer(rx: &Receiver<WriteCommand>, done_tx: &SyncSender<TaskReport>) {
                 },
               }
             },
+            Some(Err(())) => {
+              if write_path != recv_path {
+                storage.remove(&write_path).ok();
+              }
+            },
             None => {
+              if write_path != recv_path {
+                storage.remove(&write_path).ok();
+              }
               warn!(
                 task_id = task.id,
                 "sink writer: no result file (create/write failed); skipping report (reaper will recover)"
@@ -141,9 +343,23 @@ fn run_writer(rx: &Receiver<WriteCommand>, done_tx: &SyncSender<TaskReport>) {
         }
       },
       WriteCommand::Reject { max_result_bytes } => {
-        if let Some((task, recv_path, file)) = current.take() {
-          drop(file);
-          std::fs::remove_file(&recv_path).ok();
+        if let Some(InProgress {
+          task,
+          recv_path,
+          write_path,
+          file,
+          keep_versions,
+          ..
+        }) = current.take()
+        {
+          drop(file); // an uncommitted writer discards what it had
+          storage.remove(&write_path).ok();
+          // The verdict stands in for a result, so the current archive is kept as a version.
+          let previous = if keep_versions > 0 {
+            move_aside(&task, &recv_path, storage)
+          } else {
+            None
+          };
           let taskid = task.id;
           warn!(
             task_id = taskid,
@@ -159,6 +375,8 @@ fn run_writer(rx: &Receiver<WriteCommand>, done_tx: &SyncSender<TaskReport>) {
               "result_too_large".to_string(),
               format!("worker result exceeded the {max_result_bytes}-byte hard cap"),
             )],
+            archive: None,
+            previous_archive: previous,
           };
           server::send_done(done_tx, report);
         }
@@ -176,6 +394,8 @@ struct ReplyHeader {
   service: String,
   /// the task id this result is for (`-1` if the frame wasn't a valid integer)
   taskid: i64,
+  /// the lease token echoed with the task id (`<taskid>:<token>`), if any
+  token: Option<String>,
 }
 
 /// Validates + parses the reply envelope from one **atomically-received** `zeromq` message. Because
@@ -197,13 +417,162 @@ fn parse_reply_envelope(msg: &ZmqMessage) -> Result<ReplyHeader, &'static str> {
       .unwrap_or(default)
       .to_string()
   };
+  let handle = frame_str(2, "-1");
+  let (taskid, token) = worker_auth::parse_task_handle(&handle);
   Ok(ReplyHeader {
     identity: frame_str(0, "_worker_"),
     service: frame_str(1, "_unknown_"),
-    taskid: frame_str(2, "-1").parse::<i64>().unwrap_or(-1),
+    taskid,
+    token: token.map(str::to_string),
   })
 }
 
+/// Where the sink's receive loop takes results from: the pure-Rust `zeromq` PULL socket, or — under
+/// `dispatcher.worker_auth` — the CURVE relay of [`start_curve_relay`]. Either way a result arrives
+/// as one whole multipart [`ZmqMessage`].
+enum ResultSource {
+  Plain(PullSocket),
+  Curve(tokio::sync::mpsc::Receiver<ZmqMessage>),
+}
+
+impl ResultSource {
+  async fn recv(&mut self) -> Result<ZmqMessage, String> {
+    match self {
+      ResultSource::Plain(pull) => pull.recv().await.map_err(|e| e.to_string()),
+      ResultSource::Curve(relayed) => relayed
+        .recv()
+        .await
+        .ok_or_else(|| "the CURVE relay stopped".to_string()),
+    }
+  }
+}
+
+/// Records one rejected result in the audit log — every one, unlike the rate-limited warnings, so
+/// the log accounts for each result a worker had refused. The connection is opened on the first
+/// rejection and kept; a failure to open it drops the entry, like every best-effort audit write.
+fn audit_rejected_result(
+  connection: &mut Option<diesel::PgConnection>,
+  backend_address: &str,
+  header: &ReplyHeader,
+  reason: &str,
+) {
+  if connection.is_none() {
+    *connection = diesel::PgConnection::establish(backend_address).ok();
+  }
+  if let Some(connection) = connection.as_mut() {
+    worker_auth::audit_rejection(
+      connection,
+      &header.identity,
+      "worker_result_rejected",
+      &format!("task/{}", header.taskid),
+      format!("result for {} rejected: {reason}", header.service),
+    );
+  }
+}
+
+/// The authenticated sink (`dispatcher.worker_auth`). The `zeromq` PULL socket has no CURVE, so
+/// results are received on a libzmq PULL socket instead: a CURVE server whose handshakes its own
+/// [`worker_auth::ZapHandler`] checks against the same worker key allowlist as the ventilator's.
+/// A relay thread hands each received multipart message to the receive loop whole (libzmq
+/// delivers multipart messages atomically too). The key a connection authenticated with is
+/// re-checked against the allowlist for every message, so a worker whose key is revoked after its
+/// handshake has its results dropped (and audited) from the next allowlist refresh on, like its
+/// work requests at the ventilator. A full channel stops the relay receiving — the same
+/// backpressure as a blocked loop on the plain socket — and the relay exits, closing the socket,
+/// once the receive loop drops its end.
+fn start_curve_relay(
+  address: &str,
+  backend_address: &str,
+) -> Result<(tokio::sync::mpsc::Receiver<ZmqMessage>, JoinHandle<()>), Box<dyn Error>> {
+  let secret_key = worker_auth::server_secret_key("sink")?;
+  let context = zmq::Context::new();
+  let zap = worker_auth::ZapHandler::start(&context, backend_address)?;
+  let pull = context.socket(zmq::PULL)?;
+  worker_auth::serve_curve(&pull, &secret_key)?;
+  pull.set_rcvtimeo(SINK_TERMINATION_POLL.as_millis() as i32)?;
+  // The same TIME_WAIT-outlasting bind retry as the plain socket below.
+  const BIND_ATTEMPTS: u32 = 150;
+  let mut attempt = 1u32;
+  while let Err(e) = pull.bind(address) {
+    if attempt >= BIND_ATTEMPTS {
+      return Err(
+        io::Error::other(format!(
+          "sink: CURVE bind {address} failed after {BIND_ATTEMPTS} attempts: {e}"
+        ))
+        .into(),
+      );
+    }
+    warn!(
+      address = %address,
+      attempt,
+      max_attempts = BIND_ATTEMPTS,
+      error = %e,
+      "sink: bind failed; retrying in 500ms (port handover from a restarting dispatcher?)"
+    );
+    thread::sleep(Duration::from_millis(500));
+    attempt += 1;
+  }
+  let (relay_tx, relay_rx) = tokio::sync::mpsc::channel(SINK_WRITER_CHANNEL_CAPACITY);
+  let backend_address = backend_address.to_string();
+  let relay = thread::spawn(move || {
+    let mut revoked_log = server::RateLimitedLog::new(Duration::from_secs(5));
+    let mut audit_connection: Option<diesel::PgConnection> = None;
+    while !relay_tx.is_closed() {
+      // The first frame carries the connection's `User-Id` (the key the ZAP handler admitted);
+      // the rest of the message is already here, as libzmq delivers it whole.
+      let mut first = match pull.recv_msg(0) {
+        Ok(first) => first,
+        Err(zmq::Error::EAGAIN) => continue,
+        Err(error) => {
+          warn!(%error, "sink: CURVE socket failed; no further results are received");
+          break;
+        },
+      };
+      let key = first.gets("User-Id").unwrap_or_default().to_string();
+      let rest = if first.get_more() {
+        match pull.recv_multipart(0) {
+          Ok(rest) => rest,
+          Err(error) => {
+            warn!(%error, "sink: CURVE socket failed; no further results are received");
+            break;
+          },
+        }
+      } else {
+        Vec::new()
+      };
+      let mut msg = ZmqMessage::from(first.to_vec());
+      for frame in rest {
+        msg.push_back(frame.into());
+      }
+      if !zap.admits(&key) {
+        if let Some(n) = revoked_log.record() {
+          warn!(
+            rejected = n,
+            key = %key,
+            "sink: rejected result(s) from a revoked worker key (rate-limited)"
+          );
+        }
+        if let Ok(header) = parse_reply_envelope(&msg) {
+          audit_rejected_result(
+            &mut audit_connection,
+            &backend_address,
+            &header,
+            &format!("worker key {key} is no longer allowlisted"),
+          );
+        }
+        continue;
+      }
+      if relay_tx.blocking_send(msg).is_err() {
+        break;
+      }
+    }
+    // The ZAP handler stops before the socket it guards closes.
+    drop(zap);
+    drop(pull);
+  });
+  Ok((relay_rx, relay))
+}
+
 /// Specifies the binding and operation parameters for a ZMQ sink component
 pub struct Sink {
   /// port to listen on
@@ -218,6 +587,8 @@ pub struct Sink {
   pub backend_address: String,
   /// non-blocking handle to the background worker-metadata writer
   pub metadata: WorkerMetadataSender,
+  /// where result archives are written (see [`crate::storage`])
+  pub storage: Arc<dyn Storage>,
 }
 
 impl Sink {
@@ -239,7 +610,8 @@ impl Sink {
   /// when the disk or DB can't keep up, the loop stops receiving and the workers back off.
   /// **Note:** `zeromq` exposes no TCP-keepalive knob, so `dispatcher.tcp_keepalive_idle_seconds`
   /// no longer applies to the sink PULL socket; keepalive was stability-only (remote-worker NAT
-  /// mappings) and the lease reaper remains the correctness net.
+  /// mappings) and the lease reaper remains the correctness net. Under `dispatcher.worker_auth`
+  /// results are received over CURVE instead, through [`start_curve_relay`].
   pub fn start(
     &self,
     services_arc: &Arc<server::ServiceCache>,
@@ -262,12 +634,23 @@ impl Sink {
     for _ in 0..num_writers {
       let (tx, rx) = sync_channel::<WriteCommand>(SINK_WRITER_CHANNEL_CAPACITY);
       let writer_done_tx = done_tx.clone();
+      let writer_storage = self.storage.clone();
       writer_txs.push(tx);
-      writer_handles.push(thread::spawn(move || run_writer(&rx, &writer_done_tx)));
+      writer_handles.push(thread::spawn(move || {
+        run_writer(&rx, &writer_done_tx, &*writer_storage)
+      }));
     }
     let mut next_writer = 0_usize;
 
     let address = format!("tcp://0.0.0.0:{}", self.port);
+    // Under `dispatcher.worker_auth` results come in over CURVE, from allowlisted workers only.
+    let (curve_relay, relay_handle) = if config().dispatcher.worker_auth {
+      let (relayed, handle) = start_curve_relay(&address, &self.backend_address)?;
+      info!("sink: result transport is CURVE-authenticated against the worker key allowlist");
+      (Some(relayed), Some(handle))
+    } else {
+      (None, None)
+    };
 
     // A current-thread runtime is right: there is one PULL socket and one receive loop, and
     // blocking it on writer/done-channel backpressure is exactly the flow control we want (stop
@@ -283,35 +666,38 @@ impl Sink {
       // TIME_WAIT (~60s = tcp_fin_timeout), not just a few seconds — see the ventilator's
       // note (observed 2026-07-01: the old 7.5s window crash-looped the sink through the
       // full 60s TIME_WAIT until systemd's start-limit gave up).
-      let mut pull = {
-        // 150 * 500ms = 75s > tcp_fin_timeout (60s) TIME_WAIT, with margin.
-        const BIND_ATTEMPTS: u32 = 150;
-        let mut attempt = 1u32;
-        loop {
-          let mut p = PullSocket::new();
-          match p.bind(&address).await {
-            Ok(_) => break p,
-            Err(e) if attempt < BIND_ATTEMPTS => {
-              warn!(
-                address = %address,
-                attempt,
-                max_attempts = BIND_ATTEMPTS,
-                error = %e,
-                "sink: bind failed; retrying in 500ms (port handover from a restarting dispatcher?)"
-              );
-              tokio::time::sleep(Duration::from_millis(500)).await;
-              attempt += 1;
-            },
-            Err(e) => {
-              return Err(
-                io::Error::other(format!(
-                  "sink: zeromq bind {address} failed after {BIND_ATTEMPTS} attempts: {e}"
-                ))
-                .into(),
-              );
-            },
+      let mut results = match curve_relay {
+        Some(relayed) => ResultSource::Curve(relayed),
+        None => ResultSource::Plain({
+          // 150 * 500ms = 75s > tcp_fin_timeout (60s) TIME_WAIT, with margin.
+          const BIND_ATTEMPTS: u32 = 150;
+          let mut attempt = 1u32;
+          loop {
+            let mut p = PullSocket::new();
+            match p.bind(&address).await {
+              Ok(_) => break p,
+              Err(e) if attempt < BIND_ATTEMPTS => {
+                warn!(
+                  address = %address,
+                  attempt,
+                  max_attempts = BIND_ATTEMPTS,
+                  error = %e,
+                  "sink: bind failed; retrying in 500ms (port handover from a restarting dispatcher?)"
+                );
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                attempt += 1;
+              },
+              Err(e) => {
+                return Err(
+                  io::Error::other(format!(
+                    "sink: zeromq bind {address} failed after {BIND_ATTEMPTS} attempts: {e}"
+                  ))
+                  .into(),
+                );
+              },
+            }
           }
-        }
+        }),
       };
 
       let mut sink_job_count: usize = 0;
@@ -319,6 +705,11 @@ impl Sink {
       // sustained bad-reply flood must not turn per-message `stderr` writes into a throughput-DoS
       // (KNOWN_ISSUES D-11) — count, don't narrate.
       let mut discard_log = server::RateLimitedLog::new(Duration::from_secs(5));
+      // Under `dispatcher.worker_auth` every rejected result is also audited, over a connection
+      // opened on the first rejection.
+      let authenticated = config().dispatcher.worker_auth;
+      let mut forged_log = server::RateLimitedLog::new(Duration::from_secs(5));
+      let mut audit_connection: Option<diesel::PgConnection> = None;
 
       loop {
         // Fail-fast: a writer thread that died (e.g. a panic in `generate_report`) must bring down
@@ -341,7 +732,8 @@ impl Sink {
         // (nothing mid-delivery), and `recv()` is cancel-safe, so a dropped timed-out
         // future loses no message (KNOWN_ISSUES D-5 + the D-16 idle-shutdown
         // hang this closes: a production sink no longer blocks past the shutdown signal).
-        let recv_outcome = match tokio::time::timeout(SINK_TERMINATION_POLL, pull.recv()).await {
+        let polled = tokio::time::timeout(SINK_TERMINATION_POLL, results.recv()).await;
+        let recv_outcome = match polled {
           Ok(inner) => inner,
           Err(_elapsed) => {
             if dispatch_complete.load(Ordering::Acquire) && progress_queue_arc.is_empty() {
@@ -385,7 +777,8 @@ impl Sink {
           "sink: incoming result"
         );
 
-        if let Some(task_progress) = progress_queue_arc.remove(taskid) {
+        let claim = progress_queue_arc.claim(taskid, header.token.as_deref());
+        if let ResultClaim::Claimed(task_progress) = claim {
           let task = task_progress.task;
           match server::get_service(&header.service, services_arc) {
             None => {
@@ -404,6 +797,8 @@ impl Sink {
                       task,
                       status: TaskStatus::NoProblem,
                       messages: Vec::new(),
+                      archive: None,
+                      previous_archive: None,
                     },
                   );
                 } else {
@@ -413,6 +808,11 @@ impl Sink {
                   let sandbox_id = server::get_sandbox_id(task.corpus_id, sandboxes_arc);
                   let recv_path_opt =
                     helpers::result_archive_path(&task.entry, &service.name, sandbox_id);
+                  // A service may keep its previous results as versions; a sandbox's never are.
+                  let keep_versions = match sandbox_id {
+                    None => usize::try_from(service.keep_result_versions).unwrap_or(0),
+                    Some(_) => 0,
+                  };
 
                   // Hard size cap (disk protection): sum the data frames; an over-cap result is
                   // rejected (Invalid) without being written. The whole multipart message is
@@ -432,6 +832,8 @@ impl Sink {
                         .send(WriteCommand::Begin {
                           task: Box::new(task),
                           recv_path,
+                          keep_versions,
+                          parser: service.log_parser(),
                         })
                         .is_err()
                       {
@@ -491,13 +893,41 @@ impl Sink {
               }
             },
           };
-        } else if let Some(n) = discard_log.record() {
-          // No such in-flight task — drop the message (already fully received; nothing to drain).
-          warn!(
-            discarded = n,
-            task_id = taskid,
-            "sink: discarded reply(ies) for unknown task id(s) (rate-limited)"
+        } else if let ResultClaim::Forged = claim {
+          // The task is in flight, but this result does not carry one of its lease tokens: not
+          // from a worker the task was leased to over the authenticated ventilator. Drop it and
+          // leave the task to its rightful worker (or the reaper).
+          if let Some(n) = forged_log.record() {
+            warn!(
+              rejected = n,
+              task_id = taskid,
+              worker = ?header.identity,
+              "sink: rejected result(s) without a valid lease token (rate-limited)"
+            );
+          }
+          audit_rejected_result(
+            &mut audit_connection,
+            &self.backend_address,
+            &header,
+            "no valid lease token",
           );
+        } else {
+          // No such in-flight task — drop the message (already fully received; nothing to drain).
+          if let Some(n) = discard_log.record() {
+            warn!(
+              discarded = n,
+              task_id = taskid,
+              "sink: discarded reply(ies) for unknown task id(s) (rate-limited)"
+            );
+          }
+          if authenticated {
+            audit_rejected_result(
+              &mut audit_connection,
+              &self.backend_address,
+              &header,
+              "not an in-flight task",
+            );
+          }
         }
 
         let request_duration = (chrono::Utc::now() - request_time).num_milliseconds();
@@ -532,14 +962,25 @@ impl Sink {
     for handle in writer_handles {
       let _ = handle.join();
     }
+    // The receive loop dropped its end of the relay channel, so the relay stops within one poll.
+    if let Some(handle) = relay_handle {
+      let _ = handle.join();
+    }
     recv_result
   }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::parse_reply_envelope;
+  use super::{WriteCommand, parse_reply_envelope, run_writer};
+  use crate::helpers::TaskStatus;
+  use crate::helpers::log_parsers::default_parser;
+  use crate::models::Task;
+  use crate::storage::{MemoryStorage, Storage};
   use bytes::Bytes;
+  use std::io::Write;
+  use std::path::{Path, PathBuf};
+  use std::sync::mpsc::sync_channel;
   use zeromq::ZmqMessage;
 
   /// Build a `ZmqMessage` from frame byte-slices (the test analogue of a received reply).
@@ -561,6 +1002,15 @@ mod tests {
     assert_eq!(header.identity, "worker-7");
     assert_eq!(header.service, "tex_to_html");
     assert_eq!(header.taskid, 4242);
+    assert_eq!(header.token, None);
+  }
+
+  #[test]
+  fn parses_the_lease_token_of_a_task_handle() {
+    let msg = message(&[b"worker-7", b"tex_to_html", b"4242:9a3f", b"<zip bytes>"]);
+    let header = parse_reply_envelope(&msg).expect("valid envelope");
+    assert_eq!(header.taskid, 4242);
+    assert_eq!(header.token.as_deref(), Some("9a3f"));
   }
 
   #[test]
@@ -596,4 +1046,167 @@ mod tests {
     assert_eq!(header.identity, "_worker_");
     assert_eq!(header.taskid, 7);
   }
+
+  /// A minimal result zip: a `cortex.log` with a conversion status line.
+  fn result_zip(marker: &str) -> Vec<u8> {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    let mut zip = zip::ZipWriter::new(&mut buffer);
+    let opts: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+    zip.start_file("cortex.log", opts).unwrap();
+    writeln!(zip, "Info:conversion:0 {marker}").unwrap();
+    zip.finish().unwrap();
+    buffer.into_inner()
+  }
+
+  /// Runs one writer over `results` (each a `Begin → Chunk → Commit`), returning the reports.
+  fn write_results(
+    storage: &MemoryStorage,
+    keep_versions: usize,
+    results: &[Vec<u8>],
+  ) -> Vec<crate::helpers::TaskReport> {
+    let (tx, rx) = sync_channel(16);
+    let (done_tx, done_rx) = sync_channel(16);
+    for bytes in results {
+      let task = Task {
+        id: 7,
+        service_id: 3,
+        corpus_id: 1,
+        status: 1,
+        entry: "/data/paper/paper.zip".to_string(),
+        priority: 0,
+        dispatcher_id: None,
+      };
+      tx.send(WriteCommand::Begin {
+        task: Box::new(task),
+        recv_path: PathBuf::from("/data/paper/svc.zip"),
+        keep_versions,
+        parser: default_parser(),
+      })
+      .unwrap();
+      tx.send(WriteCommand::Chunk(bytes.clone())).unwrap();
+      tx.send(WriteCommand::Commit).unwrap();
+    }
+    drop(tx);
+    run_writer(&rx, &done_tx, storage);
+    drop(done_tx);
+    done_rx.into_iter().collect()
+  }
+
+  #[test]
+  fn keeps_the_newest_previous_results_as_versions() {
+    let storage = MemoryStorage::default();
+    let results: Vec<Vec<u8>> = ["one", "two", "three", "four"]
+      .iter()
+      .map(|marker| result_zip(marker))
+      .collect();
+    let reports = write_results(&storage, 2, &results);
+    assert_eq!(reports.len(), 4);
+    assert!(
+      reports[0].previous_archive.is_none(),
+      "a first result has nothing to keep"
+    );
+    let kept: Vec<&str> = reports[1..]
+      .iter()
+      .map(|report| {
+        report
+          .previous_archive
+          .as_ref()
+          .expect("moved aside")
+          .path
+          .as_str()
+      })
+      .collect();
+    assert!(
+      kept
+        .iter()
+        .all(|path| path.starts_with("/data/paper/svc.v"))
+    );
+    // The current archive is the last result; only the two newest previous ones are left.
+    let current = crate::storage::read_all(&storage, Path::new("/data/paper/svc.zip")).unwrap();
+    assert_eq!(current, results[3]);
+    let mut versions: Vec<PathBuf> = storage
+      .list(Path::new("/data/paper"))
+      .unwrap()
+      .into_iter()
+      .map(|object| object.path)
+      .filter(|path| path != Path::new("/data/paper/svc.zip"))
+      .collect();
+    versions.sort();
+    assert_eq!(
+      versions,
+      vec![PathBuf::from(kept[1]), PathBuf::from(kept[2])]
+    );
+    assert_eq!(
+      crate::storage::read_all(&storage, Path::new(kept[2])).unwrap(),
+      results[2]
+    );
+  }
+
+  #[test]
+  fn overwrites_in_place_without_versions_and_restores_after_a_bad_result() {
+    let storage = MemoryStorage::default();
+    let good = result_zip("good");
+    let reports = write_results(&storage, 0, &[good.clone(), result_zip("next")]);
+    assert!(
+      reports
+        .iter()
+        .all(|report| report.previous_archive.is_none())
+    );
+    assert_eq!(storage.list(Path::new("/data/paper")).unwrap().len(), 1);
+
+    // An unreadable result is abandoned: the previous archive is back in place, no version kept.
+    let storage = MemoryStorage::default();
+    let reports = write_results(&storage, 3, &[good.clone(), b"not a zip".to_vec()]);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(
+      crate::storage::read_all(&storage, Path::new("/data/paper/svc.zip")).unwrap(),
+      good
+    );
+    assert_eq!(storage.list(Path::new("/data/paper")).unwrap().len(), 1);
+  }
+
+  #[test]
+  fn keeps_the_current_archive_as_a_version_of_a_rejected_result() {
+    let storage = MemoryStorage::default();
+    let good = result_zip("good");
+    write_results(&storage, 2, std::slice::from_ref(&good));
+    let (tx, rx) = sync_channel(16);
+    let (done_tx, done_rx) = sync_channel(16);
+    tx.send(WriteCommand::Begin {
+      task: Box::new(Task {
+        id: 7,
+        service_id: 3,
+        corpus_id: 1,
+        status: 1,
+        entry: "/data/paper/paper.zip".to_string(),
+        priority: 0,
+        dispatcher_id: None,
+      }),
+      recv_path: PathBuf::from("/data/paper/svc.zip"),
+      keep_versions: 2,
+      parser: default_parser(),
+    })
+    .unwrap();
+    tx.send(WriteCommand::Chunk(vec![0; 64])).unwrap();
+    tx.send(WriteCommand::Reject {
+      max_result_bytes: 32,
+    })
+    .unwrap();
+    drop(tx);
+    run_writer(&rx, &done_tx, &storage);
+    drop(done_tx);
+    let reports: Vec<crate::helpers::TaskReport> = done_rx.into_iter().collect();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].status, TaskStatus::Invalid);
+    let version = reports[0]
+      .previous_archive
+      .as_ref()
+      .expect("the current archive is kept");
+    assert_eq!(
+      crate::storage::read_all(&storage, Path::new(&version.path)).unwrap(),
+      good
+    );
+    // Nothing else is left: no current archive, and no partial result.
+    assert_eq!(storage.list(Path::new("/data/paper")).unwrap().len(), 1);
+  }
 }
@@ -6,11 +6,13 @@ use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 
 use crate::backend;
+use crate::backend::{ControlKind, ControlSignal};
 use crate::dispatcher::prefetch::Prefetcher;
-use crate::dispatcher::server;
+use crate::dispatcher::{server, worker_auth};
 use crate::helpers;
 use crate::helpers::{TaskProgress, TaskReport};
-use crate::models::WorkerMetadataSender;
+use crate::models::{Service, WorkerMetadataSender};
+use crate::storage::Storage;
 use std::error::Error;
 use tracing::{debug, info, trace, warn};
 use zmq::SNDMORE;
@@ -33,6 +35,10 @@ pub struct Ventilator {
   pub max_in_flight: usize,
   /// non-blocking handle to the background worker-metadata writer
   pub metadata: WorkerMetadataSender,
+  /// this dispatcher's registered instance (`models::DispatcherInstance`), which tags its leases
+  pub instance_id: i32,
+  /// where task inputs are read from (see [`crate::storage`])
+  pub storage: Arc<dyn Storage>,
 }
 
 impl Ventilator {
@@ -56,13 +62,30 @@ impl Ventilator {
     // requesting).
     let mut queues: HashMap<i32, Vec<TaskProgress>> = HashMap::new();
     // Recover leftover Queued tasks from a previously-crashed run — but NOT the ones currently in
-    // flight. On a ventilator *restart* (KNOWN_ISSUES D-4) the sink is still processing dispatched
-    // tasks held in `progress_queue`; resetting those to TODO would re-lease them while their
-    // original results are still pending (a double-dispatch). On first start `progress_queue` is
-    // empty, so this recovers all leftover Queued tasks exactly as before.
+    // flight, and never a live peer dispatcher's. On a ventilator *restart* (KNOWN_ISSUES D-4) the
+    // sink is still processing dispatched tasks held in `progress_queue`; resetting those to TODO
+    // would re-lease them while their original results are still pending (a double-dispatch). On
+    // first start `progress_queue` is empty, so this recovers our own instance's leftovers and
+    // those of dead instances; a live peer's leases stay with it, and so do untagged ones
+    // unless `dispatcher.recover_untagged_leases` says every dispatcher predating instance tags
+    // is gone.
     let mut backend = backend::from_address(&self.backend_address);
     let in_flight_ids: Vec<i64> = progress_queue_arc.ids();
-    backend.clear_limbo_tasks_except(&in_flight_ids)?;
+    backend.clear_limbo_tasks_except(self.instance_id, &in_flight_ids)?;
+    // Run-control wake-ups (docs/ORCHESTRATION_PLAN.md §4): a dedicated connection `LISTEN`ing on
+    // the control channel, drained without blocking once per loop tick. Purely an accelerator —
+    // the `tasks` table stays authoritative and the request-driven refetch still polls it — so a
+    // failure to subscribe (or a listener that dies later) just leaves us on polling alone.
+    let mut control_listener = {
+      let mut connection = backend::connection_at(&self.backend_address);
+      match backend::listen_control(&mut connection) {
+        Ok(()) => Some(connection),
+        Err(error) => {
+          warn!(%error, "ventilator: control LISTEN failed; falling back to polling only");
+          None
+        },
+      }
+    };
     // Ok, let's bind to a port and start broadcasting
     let context = zmq::Context::new();
     let ventilator = context.socket(zmq::ROUTER)?;
@@ -92,6 +115,22 @@ impl Ventilator {
         .tcp_keepalive_idle_seconds,
     )?;
 
+    // Authenticated worker transport (`dispatcher.worker_auth`): encrypt every connection with
+    // CURVE and only accept handshakes from allowlisted worker keys. The ZAP handler must be
+    // answering before the socket binds; it is declared after the socket, so it stops (and closes
+    // its own socket) before the socket and context go away. A missing key file or a libzmq
+    // without CURVE fails the start loudly rather than silently serving an open transport.
+    let worker_auth = crate::config::config().dispatcher.worker_auth;
+    let zap = if worker_auth {
+      let keys = worker_auth::server_secret_key("ventilator")?;
+      let zap = worker_auth::ZapHandler::start(&context, &self.backend_address)?;
+      worker_auth::serve_curve(&ventilator, &keys)?;
+      info!("ventilator: worker transport is CURVE-authenticated against the worker key allowlist");
+      Some(zap)
+    } else {
+      None
+    };
+
     let address = format!("tcp://*:{}", self.port);
     // Propagate a bind failure (e.g. `EADDRINUSE` when the port is still held by a just-restarted
     // dispatcher in TIME_WAIT, or a second instance) instead of an opaque `.unwrap()` panic — same
@@ -161,6 +200,9 @@ impl Ventilator {
     // time (D-22). A dying-worker or restart storm can make this fire per-request, so aggregate it
     // (like `discard_log`) — count, don't narrate — so it can never become a throughput-DoS (D-11).
     let mut unroutable_log = server::RateLimitedLog::new(Duration::from_secs(5));
+    // Declarations of the workers that opened with a registration frame: refused workers get only
+    // mock-replies, and a declared concurrency / input-size limit shapes what the worker is leased.
+    let mut registry = server::WorkerRegistry::default();
 
     // Input-archive prefetcher (D-20): warm each fetched batch's archives into the OS page cache
     // ahead of dispatch, so the inline `/data` read below is served from RAM (~0.02 ms) instead of
@@ -214,6 +256,63 @@ impl Ventilator {
             "dispatcher: reaped timed-out in-flight tasks"
           );
         }
+        // Persist the pass's dead-letters with their lease history. Best-effort: the tasks are
+        // already reported Fatal on the done channel, so a failed write loses only the forensics.
+        if let Err(error) = backend.record_dead_letters(&reaped.dead_letters) {
+          warn!(%error, count = reaped.dead_letters.len(), "ventilator: failed to record dead-letters");
+        }
+        // Every expired lease is a strike against its entry (the worker crashed, was OOM-killed or
+        // hung on it). An entry that reaches the hold threshold is skipped by the lease query from
+        // now on — and its tasks already queued here are handed back as TODO rather than
+        // dispatched to lose another worker.
+        let threshold = crate::config::config().dispatcher.poison_hold_threshold;
+        match backend.record_lease_incidents(&reaped.incidents, threshold) {
+          Ok(held) if !held.is_empty() => {
+            for poison in &held {
+              warn!(
+                entry = %poison.entry,
+                strikes = poison.strikes,
+                "ventilator: held a poison document (leases keep failing on it)"
+              );
+            }
+            let entries: Vec<&str> = held.iter().map(|poison| poison.entry.as_str()).collect();
+            let mut dropped: Vec<i64> = Vec::new();
+            for task_queue in queues.values_mut() {
+              task_queue.retain(|progress| {
+                let poisoned = entries.contains(&progress.task.entry.as_str());
+                if poisoned {
+                  dropped.push(progress.task.id);
+                }
+                !poisoned
+              });
+            }
+            if let Err(error) = backend.unlease_tasks(&dropped) {
+              warn!(%error, "ventilator: failed to return held tasks to TODO");
+            }
+          },
+          Ok(_) => {},
+          Err(error) => warn!(%error, "ventilator: failed to record lease incidents"),
+        }
+      }
+      // Drain any run-control wake-ups (non-blocking; also reached on every idle RCVTIMEO tick).
+      if let Some(listener) = control_listener.as_mut() {
+        match backend::poll_control(listener) {
+          Ok(signals) => {
+            for signal in signals {
+              self.apply_control(
+                &signal,
+                &mut queues,
+                services_arc,
+                &mut backend,
+                &prefetcher,
+              );
+            }
+          },
+          Err(error) => {
+            warn!(%error, "ventilator: control listener failed; falling back to polling only");
+            control_listener = None;
+          },
+        }
       }
       let mut identity = zmq::Message::new();
       let mut msg = zmq::Message::new();
@@ -237,6 +336,11 @@ impl Ventilator {
         Err(zmq::Error::EAGAIN) => continue,
         Err(e) => return Err(e.into()),
       }
+      // The key this connection authenticated with (the ZAP handler's `User-Id`), read off the
+      // first frame before it is consumed by the reply.
+      let peer_key = zap
+        .as_ref()
+        .map(|_| identity.gets("User-Id").unwrap_or_default().to_string());
       if !ventilator.get_rcvmore().unwrap_or(false) {
         // `[identity]` with no service frame — truncated. Skipping consumes nothing further, so the
         // next request's frames are left intact (no desync).
@@ -248,14 +352,20 @@ impl Ventilator {
         continue;
       }
       ventilator.recv(&mut msg, 0)?;
-      // A well-formed request ends at the service frame; drain anything beyond it (an over-long /
-      // malformed request) so it can't bleed into the next request — frame-alignment is exactly
-      // what D-4 lost.
+      // A well-formed work request ends at the service frame; drain anything beyond it (an
+      // over-long / malformed request) so it can't bleed into the next request — frame-alignment
+      // is exactly what D-4 lost. The trailing frames are kept (a few, bounded) only to recognize
+      // a lease-renewal heartbeat, `[identity, service, "heartbeat", taskid]`, or a registration,
+      // `[identity, service, "register", json]`.
+      let mut trailing: Vec<zmq::Message> = Vec::new();
       while ventilator.get_rcvmore().unwrap_or(false) {
         let mut extra = zmq::Message::new();
         if ventilator.recv(&mut extra, 0).is_err() {
           break;
         }
+        if trailing.len() <= 2 {
+          trailing.push(extra);
+        }
       }
       let identity_str = identity.as_str().unwrap_or_default().to_string();
       let service_name = msg.as_str().unwrap_or_default().to_string();
@@ -268,6 +378,101 @@ impl Ventilator {
         }
         continue;
       }
+      // A worker whose key was revoked since its handshake keeps its connection until it drops,
+      // but is no longer served: a work request gets the back-off mock-reply, anything else is
+      // dropped.
+      if let (Some(zap), Some(key)) = (zap.as_ref(), peer_key.as_deref())
+        && !zap.admits(key)
+      {
+        if let Some(n) = discard_log.record() {
+          warn!(
+            "ventilator: discarded {n} request(s) [latest: revoked worker key {key:?} from {identity_str:?}] (rate-limited)"
+          );
+        }
+        if trailing.is_empty()
+          && route_worker_frame(&ventilator, identity, &identity_str, &mut unroutable_log)?
+        {
+          ventilator.send("0", SNDMORE)?;
+          ventilator.send(Vec::new(), 0)?;
+        }
+        continue;
+      }
+      // A registration frame: remember what the worker declared and whether it may be leased to
+      // at all (its service version must match the registered one). Fire-and-forget like the
+      // heartbeat below — the worker follows up with ordinary work requests.
+      if let Some(parsed) = server::parse_registration(&trailing) {
+        let service = server::get_sync_service(&service_name, services_arc, &mut backend);
+        match (parsed, service) {
+          (Ok(registration), Some(service)) => {
+            let refusal = registry.register(&identity_str, &service, registration.clone());
+            match &refusal {
+              Some(reason) => warn!(
+                worker = %identity_str,
+                service = %service.name,
+                %reason,
+                "ventilator: registered worker refused leases"
+              ),
+              None => info!(
+                worker = %identity_str,
+                service = %service.name,
+                host = registration.host.as_deref().unwrap_or("-"),
+                "ventilator: worker registered"
+              ),
+            }
+            self
+              .metadata
+              .registered(identity_str, service.id, registration, refusal);
+          },
+          (parsed, _) => {
+            if let Some(n) = discard_log.record() {
+              let reason = parsed
+                .err()
+                .unwrap_or_else(|| "unknown service".to_string());
+              warn!(
+                "ventilator: discarded {n} registration(s) [latest: {reason} for {service_name:?} from {identity_str:?}] (rate-limited)"
+              );
+            }
+          },
+        }
+        continue;
+      }
+      // A lease-renewal heartbeat from a worker still converting a task: extend that task's
+      // in-flight lease (up to its renewal cap). Fire-and-forget — the worker's DEALER is awaiting
+      // nothing, so a reply would be misread as a task; an unknown service or task id is dropped.
+      if let Some((task_id, token)) = server::parse_heartbeat(&trailing) {
+        let renewal =
+          server::get_sync_service(&service_name, services_arc, &mut backend).map(|service| {
+            let renewal =
+              progress_queue_arc.renew(task_id, service.id, &identity_str, token.as_deref());
+            (service.id, renewal)
+          });
+        match renewal {
+          Some((service_id, server::LeaseRenewal::Renewed(count))) => {
+            trace!(task_id, worker = %identity_str, renewals = count, "ventilator: lease renewed");
+            self.metadata.renewed(identity_str, service_id);
+          },
+          Some((_, server::LeaseRenewal::CapReached)) => debug!(
+            task_id,
+            worker = %identity_str,
+            "ventilator: lease renewal cap reached; the lease lapses at its deadline"
+          ),
+          Some((_, server::LeaseRenewal::NotHolder)) => {
+            if let Some(n) = discard_log.record() {
+              warn!(
+                "ventilator: discarded {n} heartbeat(s) [latest: task {task_id} of {service_name:?} from {identity_str:?}, which does not hold its lease] (rate-limited)"
+              );
+            }
+          },
+          _ => {
+            if let Some(n) = discard_log.record() {
+              warn!(
+                "ventilator: discarded {n} heartbeat(s) [latest: task {task_id} of {service_name:?} from {identity_str:?} not in flight] (rate-limited)"
+              );
+            }
+          },
+        }
+        continue;
+      }
 
       let request_time = chrono::Utc::now();
       source_job_count += 1;
@@ -313,6 +518,29 @@ impl Ventilator {
           ventilator.send(Vec::new(), 0)?;
           continue;
         }
+        // A registered worker is held to its declaration: a refused one (service version or input
+        // format mismatch) is never leased to, and one at its declared concurrency is not leased
+        // more until it returns a result. Either way it gets the back-off mock-reply.
+        let registered = registry.get(&identity_str, service.id);
+        let held_back = registered.and_then(|worker| match &worker.refusal {
+          Some(reason) => Some(reason.as_str()),
+          None => worker
+            .registration
+            .concurrency
+            .filter(|limit| progress_queue_arc.leased_to(&identity_str) >= *limit as usize)
+            .map(|_| "at its declared concurrency"),
+        });
+        if let Some(reason) = held_back {
+          trace!(worker = %identity_str, reason, "ventilator: worker held back; mock-replying");
+          if !route_worker_frame(&ventilator, identity, &identity_str, &mut unroutable_log)? {
+            continue;
+          }
+          ventilator.send("0", SNDMORE)?;
+          ventilator.send(Vec::new(), 0)?;
+          self.metadata.heartbeat(identity_str, service.id);
+          continue;
+        }
+        let registration = registered.map(|worker| &worker.registration);
         let task_queue: &mut Vec<TaskProgress> = queues.entry(service.id).or_default();
         if task_queue.is_empty() {
           debug!(
@@ -320,29 +548,15 @@ impl Ventilator {
             service_name, self.queue_size
           );
           // Refetch a new batch of tasks
-          let now = chrono::Utc::now().timestamp();
-          let fetched_tasks = backend
-            .fetch_tasks(&service, self.queue_size)
-            .unwrap_or_default();
-          // D-20: warm this batch's input archives into page cache in DISPATCH order. `task_queue`
-          // is popped LIFO (from the end after the `extend` below), so the batch is dispatched in
-          // reverse of fetch order — feed reversed so the next-to-dispatch archives warm first.
-          // No-op when prefetching is disabled.
-          prefetcher.warm_batch(fetched_tasks.iter().rev().map(|task| task.entry.clone()));
-          // D-17: capture the effective lease for THIS service — its `lease_timeout_seconds`
-          // override, else the global dispatcher default — at dispatch time. `expected_at` uses the
-          // captured value, so one dispatcher serves fast (latexml-oxide) and slow (Perl) services
-          // with the right lease each, and a later config change never re-times an in-flight task.
-          let lease = service
-            .lease_timeout_seconds
-            .map(i64::from)
-            .unwrap_or_else(|| crate::config::config().dispatcher.lease_timeout_seconds);
-          task_queue.extend(fetched_tasks.into_iter().map(|task| TaskProgress {
-            task,
-            created_at: now,
-            retries: 0,
-            lease_timeout_seconds: lease,
-          }));
+          refill_queue(
+            &mut backend,
+            &service,
+            task_queue,
+            self.queue_size,
+            self.instance_id,
+            &prefetcher,
+            &*self.storage,
+          );
         }
 
         // Routing frame under ROUTER_MANDATORY, sent BEFORE the lease (the `pop()` below): if the
@@ -351,7 +565,30 @@ impl Ventilator {
         if !route_worker_frame(&ventilator, identity, &identity_str, &mut unroutable_log)? {
           continue;
         }
-        if let Some(current_task_progress) = task_queue.pop() {
+        // A worker that declared an input-size limit is handed the next task whose input fits it;
+        // bigger ones stay queued for workers without (or with a larger) limit. Inputs are sized
+        // only for such a worker, each at most once while it is queued.
+        let input_bytes = |progress: &TaskProgress| {
+          let sandbox_id =
+            server::get_sync_sandbox_id(progress.task.corpus_id, sandboxes_arc, &mut backend);
+          helpers::task_input_path(&progress.task, &service, sandbox_id, &*self.storage)
+            .and_then(|path| self.storage.size(&path).ok())
+        };
+        let picked = server::take_fitting_task(task_queue, registration, input_bytes);
+        if picked.is_none() && !task_queue.is_empty() && task_queue.len() < 2 * self.queue_size {
+          // Everything queued is too big for this worker: top the queue up (bounded at twice the
+          // batch) so a fleet of size-limited workers still finds work it can take next time.
+          refill_queue(
+            &mut backend,
+            &service,
+            task_queue,
+            self.queue_size,
+            self.instance_id,
+            &prefetcher,
+            &*self.storage,
+          );
+        }
+        if let Some(mut current_task_progress) = picked {
           dispatched_this_iter = true;
           // A `retries == 0` task is entering the pipeline for the first time; a re-leased task
           // (retries > 0, from the reaper requeue) was already counted on its first dispatch, so
@@ -374,7 +611,16 @@ impl Ventilator {
           // component, not as clean proof of this race alone.
           // higher worker concurrency (KNOWN_ISSUES D-4 / docs/DISPATCHER_BENCH.md 8-worker loss).
           // Recording first also leaves a mid-stream send failure correctly in-flight for the
-          // reaper.
+          // reaper. The lease is stamped into the task's history first, for its dead-letter
+          // should every retry time out.
+          // With worker auth on, the lease carries a fresh token, sent in the task-id frame: the
+          // sink accepts the result only with a token of the task's lease history.
+          let token = zap.as_ref().map(|_| worker_auth::new_lease_token());
+          current_task_progress.leases.push(helpers::LeaseRecord {
+            worker: identity_str.clone(),
+            leased_at: chrono::Utc::now().timestamp(),
+            token: token.clone(),
+          });
           progress_queue_arc.insert(current_task_progress.clone());
 
           let current_task = current_task_progress.task;
@@ -382,20 +628,24 @@ impl Ventilator {
           let serviceid = current_task.service_id;
           // Memoise this task's corpus → sandbox id now, before the payload is sent (so before the
           // result can return), so the sink scopes the result archive without its own DB hit (F-6).
-          server::get_sync_sandbox_id(current_task.corpus_id, sandboxes_arc, &mut backend);
+          // A pipeline service also needs it to pick the sandbox's upstream archive as input.
+          let sandbox_id =
+            server::get_sync_sandbox_id(current_task.corpus_id, sandboxes_arc, &mut backend);
           trace!(
             job = source_job_count,
             worker = %identity_str,
             task_id = taskid,
             "ventilator: worker received task"
           );
-          ventilator.send(&taskid.to_string(), SNDMORE)?;
+          ventilator.send(&worker_auth::task_handle(taskid, token.as_deref()), SNDMORE)?;
           if serviceid == 1 {
             // No payload needed for init
             ventilator.send(Vec::new(), 0)?;
           } else {
-            // Regular services fetch the task payload and transfer it to the worker
-            let file_opt = helpers::prepare_input_stream(&current_task);
+            // Regular services fetch the task payload and transfer it to the worker (a pipeline
+            // service's payload is its upstream's result archive)
+            let file_opt =
+              helpers::prepare_input_stream(&current_task, &service, sandbox_id, &*self.storage);
             if file_opt.is_ok() {
               let mut file = file_opt?;
               let mut total_outgoing: usize = 0;
@@ -491,6 +741,119 @@ impl Ventilator {
   }
 }
 
+impl Ventilator {
+  /// Reacts to one run-control wake-up: a **pause** drops the scope's queued (not yet dispatched)
+  /// tasks, a **resume/rerun/activate** tops up the affected services' queues with the fresh TODO
+  /// tasks right away, and a **config** change re-reads the cached `Service` rows. Only services
+  /// this ventilator has already served are refetched — a service no worker has asked for yet is
+  /// fetched on its first request as always.
+  fn apply_control(
+    &self,
+    signal: &ControlSignal,
+    queues: &mut HashMap<i32, Vec<TaskProgress>>,
+    services_arc: &server::ServiceCache,
+    backend: &mut backend::Backend,
+    prefetcher: &Prefetcher,
+  ) {
+    match signal.kind {
+      ControlKind::Pause => {
+        let dropped = server::drop_paused(queues, signal);
+        info!(
+          ?signal,
+          dropped, "ventilator: paused scope dropped from dispatch queues"
+        );
+      },
+      ControlKind::Resume | ControlKind::Rerun | ControlKind::Activate => {
+        let services: Vec<Service> = services_arc
+          .iter()
+          .filter_map(|entry| entry.value().clone())
+          .filter(|service| signal.service_id.is_none_or(|id| id == service.id))
+          .collect();
+        for service in services {
+          let task_queue = queues.entry(service.id).or_default();
+          if task_queue.len() < self.queue_size {
+            let limit = self.queue_size - task_queue.len();
+            refill_queue(
+              backend,
+              &service,
+              task_queue,
+              limit,
+              self.instance_id,
+              prefetcher,
+              &*self.storage,
+            );
+          }
+        }
+        debug!(?signal, "ventilator: refetched after a run-control wake-up");
+      },
+      ControlKind::Service => {
+        server::refresh_service_cache(services_arc, backend);
+        info!(
+          ?signal,
+          "ventilator: reloaded the service cache after a service setting changed"
+        );
+      },
+    }
+  }
+}
+
+/// Leases up to `limit` TODO tasks of `service` from the backend onto its dispatch `task_queue` —
+/// the request-driven refetch on an empty queue, and the eager top-up after a run-control
+/// wake-up. A fetch error leases nothing (the next request or wake-up retries). The leases are
+/// tagged with this dispatcher's `instance`. The queue is kept sorted so the highest-priority task
+/// is always the next one popped.
+fn refill_queue(
+  backend: &mut backend::Backend,
+  service: &Service,
+  task_queue: &mut Vec<TaskProgress>,
+  limit: usize,
+  instance: i32,
+  prefetcher: &Prefetcher,
+  storage: &dyn Storage,
+) {
+  let now = chrono::Utc::now().timestamp();
+  // Fetched highest-priority first — which is also DISPATCH order (see the sort below).
+  let fetched_tasks = backend
+    .fetch_tasks(service, limit, instance)
+    .unwrap_or_default();
+  // D-20: warm this batch's input archives into page cache in dispatch order, so the
+  // next-to-dispatch archives warm first. No-op when prefetching is disabled, and for a storage
+  // backend without local files (there is no page cache to warm).
+  prefetcher.warm_batch(fetched_tasks.iter().filter_map(|task| {
+    helpers::task_input_path(task, service, None, storage)
+      .and_then(|path| storage.local_path(&path))
+      .map(|path| path.to_string_lossy().into_owned())
+  }));
+  // D-17: capture the effective lease for THIS service — its `lease_timeout_seconds` override, else
+  // the global dispatcher default — at dispatch time. `expected_at` uses the captured value, so one
+  // dispatcher serves fast (latexml-oxide) and slow (Perl) services with the right lease each, and
+  // a later config change never re-times an in-flight task.
+  let lease = service
+    .lease_timeout_seconds
+    .map(i64::from)
+    .unwrap_or_else(|| crate::config::config().dispatcher.lease_timeout_seconds);
+  // The renewal cap is captured the same way: the service's override, else the global default.
+  let max_renewals = service
+    .max_lease_renewals
+    .map(i64::from)
+    .unwrap_or_else(|| crate::config::config().dispatcher.max_lease_renewals);
+  task_queue.extend(fetched_tasks.into_iter().rev().map(|task| TaskProgress {
+    task,
+    created_at: now,
+    retries: 0,
+    lease_timeout_seconds: lease,
+    renewals: 0,
+    renewed_at: 0,
+    max_renewals,
+    leases: Vec::new(),
+    input_bytes: None,
+  }));
+  // `task_queue` is popped LIFO (from the end): the batch went in reversed, so the first-fetched
+  // task is popped first, and a stable ascending sort by priority keeps that order within each
+  // priority while moving the highest-priority tasks (of this batch or any already queued) last.
+  task_queue.sort_by_key(|progress| progress.task.priority);
+}
+
 /// Send the ROUTER routing (identity) frame for a worker reply under `ZMQ_ROUTER_MANDATORY`
 /// (KNOWN_ISSUES D-22). Returns `Ok(true)` when the worker routed and the caller may stream the
 /// rest of the reply; `Ok(false)` when the worker was unroutable (`EHOSTUNREACH`) — the caller MUST
@@ -0,0 +1,427 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The **authenticated worker transport** (`dispatcher.worker_auth`).
+//!
+//! Both dispatcher sockets are ZMQ CURVE servers: connections are encrypted, and a ZAP handler
+//! ([`ZapHandler`], RFC 27) accepts a worker's handshake only if its public key is on the
+//! allowlist (`models::WorkerKey`). The ventilator is libzmq already; the sink's pure-Rust
+//! `zeromq` transport has no CURVE, so an authenticated sink receives on a libzmq PULL socket
+//! instead (see `sink::start_curve_relay`). On top of that, every lease carries a random token,
+//! sent inside the task-id frame as a `<taskid>:<token>` handle. Workers echo the task-id frame
+//! back with their result unchanged, and the sink only accepts a result that carries a token of
+//! its task's lease history
+//! ([`InFlightSet::claim`](crate::dispatcher::server::InFlightSet::claim)), so an allowlisted
+//! worker cannot report on a task it was never leased. Both sockets re-check a connection's key
+//! against the refreshed allowlist for every message, so revoking a key also cuts off a worker that
+//! stays connected. Refused handshakes are recorded in the audit log (one entry per burst, like the
+//! discard logs), and so is every rejected result.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use diesel::PgConnection;
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::backend;
+use crate::dispatcher::server::RateLimitedLog;
+use crate::models::{NewAuditEntry, WorkerKey};
+
+/// The ZAP domain the ventilator and sink sockets authenticate in.
+pub const ZAP_DOMAIN: &str = "cortex";
+
+/// How often the ZAP handler reloads the allowlist, so a revoked key stops being leased to (and its
+/// results accepted) within this window even while its worker stays connected.
+const ALLOWLIST_REFRESH: Duration = Duration::from_secs(10);
+
+/// Whether this libzmq was built with CURVE support.
+pub fn curve_available() -> bool { zmq::has("curve").unwrap_or(false) }
+
+/// The path of the dispatcher's CURVE key pair (`dispatcher.curve_key_file`).
+pub fn curve_key_path() -> PathBuf {
+  PathBuf::from(&crate::config::config().dispatcher.curve_key_file)
+}
+
+/// A CURVE key pair, Z85-encoded — the on-disk shape of the dispatcher's key file, and what
+/// `cortex worker-keys --generate` hands out to a new worker.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CurveKeys {
+  /// the public key, safe to share
+  pub public_key: String,
+  /// the secret key, never leaves its owner
+  pub secret_key: String,
+}
+
+impl CurveKeys {
+  /// A fresh key pair.
+  pub fn generate() -> Result<CurveKeys, String> {
+    let pair = zmq::CurveKeyPair::new()
+      .map_err(|e| format!("cannot generate a CURVE key pair (libzmq without CURVE?): {e}"))?;
+    let encode = |bytes: &[u8]| zmq::z85_encode(bytes).map_err(|e| format!("{e:?}"));
+    Ok(CurveKeys {
+      public_key: encode(&pair.public_key)?,
+      secret_key: encode(&pair.secret_key)?,
+    })
+  }
+
+  /// Reads a key pair file.
+  pub fn load(path: &Path) -> Result<CurveKeys, String> {
+    let text =
+      std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("cannot parse {}: {e}", path.display()))
+  }
+
+  /// Reads the key pair file at `path`, creating it (owner-only permissions) if it does not exist
+  /// yet. Returns the keys and whether they were just created; an existing file is never
+  /// overwritten.
+  pub fn load_or_create(path: &Path) -> Result<(CurveKeys, bool), String> {
+    if path.exists() {
+      return Ok((CurveKeys::load(path)?, false));
+    }
+    let keys = CurveKeys::generate()?;
+    let serialized = serde_json::to_string_pretty(&keys).map_err(|e| e.to_string())?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+      .open(path)
+      .map_err(|e| format!("cannot create {}: {e}", path.display()))?;
+    file
+      .write_all(serialized.as_bytes())
+      .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    Ok((keys, true))
+  }
+
+  /// The raw 32-byte secret key, for `set_curve_secretkey`.
+  pub fn secret_key_bytes(&self) -> Result<Vec<u8>, String> {
+    zmq::z85_decode(&self.secret_key)
+      .ok()
+      .filter(|bytes| bytes.len() == 32)
+      .ok_or_else(|| "the secret key is not a Z85-encoded 32-byte key".to_string())
+  }
+}
+
+/// The dispatcher's raw CURVE secret key, for a socket about to serve the authenticated
+/// transport (`component` names that socket in the error). A libzmq without CURVE or an unusable
+/// key file fails the start loudly rather than silently serving an open transport.
+pub fn server_secret_key(component: &str) -> Result<Vec<u8>, std::io::Error> {
+  if !curve_available() {
+    return Err(std::io::Error::other(format!(
+      "{component}: dispatcher.worker_auth needs a libzmq built with CURVE"
+    )));
+  }
+  CurveKeys::load(&curve_key_path())
+    .and_then(|keys| keys.secret_key_bytes())
+    .map_err(|e| {
+      std::io::Error::other(format!(
+        "{component}: dispatcher.worker_auth is on, but the server key is unusable ({e}); \
+         create it with `cortex worker-keys --server-key`"
+      ))
+    })
+}
+
+/// Makes `socket` a CURVE server with `secret_key`, authenticating in [`ZAP_DOMAIN`]. Its
+/// context's [`ZapHandler`] must already be answering, and the socket not yet bound.
+pub fn serve_curve(socket: &zmq::Socket, secret_key: &[u8]) -> Result<(), zmq::Error> {
+  socket.set_curve_server(true)?;
+  socket.set_curve_secretkey(secret_key)?;
+  socket.set_zap_domain(ZAP_DOMAIN)
+}
+
+/// A fresh random lease token (128 bits, hex).
+pub fn new_lease_token() -> String { format!("{:032x}", rand::rng().random::<u128>()) }
+
+/// The task-id frame the ventilator sends with a lease: the bare id, or `<taskid>:<token>` when the
+/// lease carries a token.
+pub fn task_handle(taskid: i64, token: Option<&str>) -> String {
+  match token {
+    Some(token) => format!("{taskid}:{token}"),
+    None => taskid.to_string(),
+  }
+}
+
+/// Parses a task-id frame a worker echoed back into the task id (`-1` if it isn't one) and the
+/// lease token it carries, if any.
+pub fn parse_task_handle(frame: &str) -> (i64, Option<&str>) {
+  let (id, token) = match frame.split_once(':') {
+    Some((id, token)) => (id, Some(token).filter(|token| !token.is_empty())),
+    None => (frame, None),
+  };
+  (id.parse::<i64>().unwrap_or(-1), token)
+}
+
+/// Records a refused handshake or a rejected result in the audit log. Best-effort, like every
+/// audit write: a failure is logged and dropped.
+pub fn audit_rejection(
+  connection: &mut PgConnection,
+  actor: &str,
+  action: &str,
+  target: &str,
+  details: String,
+) {
+  let entry = NewAuditEntry::new(actor, action, target)
+    .outcome("denied")
+    .details(details);
+  if let Err(error) = entry.record(connection) {
+    warn!(%error, action, "worker auth: failed to record an audit entry");
+  }
+}
+
+/// The public keys currently allowed to connect, shared between the ZAP handler (which keeps it
+/// fresh) and the ventilator (which stops leasing to a connection whose key was revoked).
+#[derive(Default)]
+pub struct KeyAllowlist {
+  keys: RwLock<HashSet<String>>,
+}
+
+impl KeyAllowlist {
+  /// Whether `key` is currently allowed.
+  pub fn contains(&self, key: &str) -> bool {
+    self
+      .keys
+      .read()
+      .map(|keys| keys.contains(key))
+      .unwrap_or(false)
+  }
+
+  fn insert(&self, key: String) {
+    if let Ok(mut keys) = self.keys.write() {
+      keys.insert(key);
+    }
+  }
+
+  fn replace(&self, fresh: Vec<String>) {
+    if let Ok(mut keys) = self.keys.write() {
+      *keys = fresh.into_iter().collect();
+    }
+  }
+}
+
+/// A parsed ZAP request (RFC 27): `[version, request_id, domain, address, identity, mechanism,
+/// credentials…]`.
+#[derive(Debug, PartialEq, Eq)]
+struct ZapRequest {
+  request_id: Vec<u8>,
+  address: String,
+  mechanism: String,
+  /// the client's CURVE public key, Z85-encoded (`None` for other mechanisms)
+  client_key: Option<String>,
+}
+
+fn parse_zap_request(frames: &[Vec<u8>]) -> Option<ZapRequest> {
+  if frames.len() < 6 || frames[0] != b"1.0" {
+    return None;
+  }
+  let text = |frame: &[u8]| String::from_utf8_lossy(frame).into_owned();
+  let mechanism = text(&frames[5]);
+  let client_key = match (mechanism.as_str(), frames.get(6)) {
+    ("CURVE", Some(key)) if key.len() == 32 => zmq::z85_encode(key).ok(),
+    _ => None,
+  };
+  Some(ZapRequest {
+    request_id: frames[1].clone(),
+    address: text(&frames[3]),
+    mechanism,
+    client_key,
+  })
+}
+
+/// The ZAP handler of a dispatcher socket's context (the ventilator's, or the authenticated
+/// sink's): a thread answering the handshake requests libzmq sends to `inproc://zeromq.zap.01`. A
+/// CURVE handshake is accepted for an active allowlisted key (its `last_seen_at` stamped), with the
+/// key as the connection's `User-Id`; anything else is refused and audited. Stops and joins on drop
+/// — before the guarded socket's context is torn down.
+pub struct ZapHandler {
+  allowlist: Arc<KeyAllowlist>,
+  stop: Arc<AtomicBool>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl ZapHandler {
+  /// Binds the ZAP endpoint in `context` and starts answering. Must run before the CURVE server
+  /// socket binds, so no handshake goes unanswered.
+  pub fn start(context: &zmq::Context, backend_address: &str) -> Result<ZapHandler, zmq::Error> {
+    let socket = context.socket(zmq::REP)?;
+    socket.set_rcvtimeo(250)?;
+    socket.bind("inproc://zeromq.zap.01")?;
+    let mut connection = backend::connection_at(backend_address);
+    let allowlist = Arc::new(KeyAllowlist::default());
+    allowlist.replace(WorkerKey::active_keys(&mut connection).unwrap_or_default());
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+      let allowlist = Arc::clone(&allowlist);
+      let stop = Arc::clone(&stop);
+      std::thread::spawn(move || serve_zap(&socket, &mut connection, &allowlist, &stop))
+    };
+    Ok(ZapHandler {
+      allowlist,
+      stop,
+      thread: Some(thread),
+    })
+  }
+
+  /// Whether a connection authenticated with `key` may still be leased to, or report results (its
+  /// key was not revoked since the handshake).
+  pub fn admits(&self, key: &str) -> bool { self.allowlist.contains(key) }
+}
+
+impl Drop for ZapHandler {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+fn serve_zap(
+  socket: &zmq::Socket,
+  connection: &mut PgConnection,
+  allowlist: &KeyAllowlist,
+  stop: &AtomicBool,
+) {
+  let mut refreshed = Instant::now();
+  // One audit row per burst of refusals, not one per handshake: a misconfigured fleet reconnecting
+  // in a loop must not flood the audit log.
+  let mut refusals = RateLimitedLog::new(Duration::from_secs(5));
+  while !stop.load(Ordering::SeqCst) {
+    if refreshed.elapsed() >= ALLOWLIST_REFRESH {
+      refreshed = Instant::now();
+      match WorkerKey::active_keys(connection) {
+        Ok(keys) => allowlist.replace(keys),
+        Err(error) => warn!(%error, "zap: failed to reload the worker key allowlist"),
+      }
+    }
+    let frames = match socket.recv_multipart(0) {
+      Ok(frames) => frames,
+      Err(zmq::Error::EAGAIN) => continue,
+      Err(error) => {
+        warn!(%error, "zap: handler socket failed; no further worker handshakes are accepted");
+        return;
+      },
+    };
+    let request = parse_zap_request(&frames);
+    let admitted = request
+      .as_ref()
+      .and_then(|request| request.client_key.as_ref())
+      .map(|key| match WorkerKey::admit(connection, key) {
+        Ok(admitted) => admitted,
+        // A database hiccup falls back to the last loaded allowlist rather than locking the fleet
+        // out.
+        Err(error) => {
+          warn!(%error, "zap: allowlist lookup failed; using the cached allowlist");
+          allowlist.contains(key)
+        },
+      })
+      .unwrap_or(false);
+    let request_id = request
+      .as_ref()
+      .map(|request| request.request_id.clone())
+      .unwrap_or_default();
+    let user_id = request
+      .as_ref()
+      .and_then(|request| request.client_key.clone())
+      .filter(|_| admitted)
+      .unwrap_or_default();
+    if admitted {
+      allowlist.insert(user_id.clone());
+    } else if let Some(n) = refusals.record() {
+      let key = request
+        .as_ref()
+        .and_then(|request| request.client_key.as_deref())
+        .unwrap_or("");
+      let address = request
+        .as_ref()
+        .map(|request| request.address.as_str())
+        .unwrap_or("");
+      let mechanism = request
+        .as_ref()
+        .map(|request| request.mechanism.as_str())
+        .unwrap_or("malformed");
+      info!(
+        refused = n,
+        key, address, mechanism, "zap: refused worker handshake(s) (rate-limited)"
+      );
+      audit_rejection(
+        connection,
+        key,
+        "worker_key_rejected",
+        address,
+        format!(
+          "{n} worker handshake(s) refused since the last entry ({mechanism}, key not allowlisted)"
+        ),
+      );
+    }
+    let (status, text) = if admitted {
+      ("200", "OK")
+    } else {
+      ("400", "worker key not allowlisted")
+    };
+    let reply: [&[u8]; 6] = [
+      b"1.0",
+      &request_id,
+      status.as_bytes(),
+      text.as_bytes(),
+      user_id.as_bytes(),
+      b"",
+    ];
+    if let Err(error) = socket.send_multipart(reply, 0) {
+      warn!(%error, "zap: failed to answer a worker handshake");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn task_handles_round_trip() {
+    assert_eq!(task_handle(42, None), "42");
+    assert_eq!(parse_task_handle("42"), (42, None));
+    let token = new_lease_token();
+    assert_eq!(token.len(), 32);
+    let handle = task_handle(42, Some(&token));
+    assert_eq!(parse_task_handle(&handle), (42, Some(token.as_str())));
+    assert_eq!(
+      parse_task_handle("42:"),
+      (42, None),
+      "an empty token is none"
+    );
+    assert_eq!(parse_task_handle("x:abc"), (-1, Some("abc")));
+  }
+
+  #[test]
+  fn zap_requests_carry_the_curve_key() {
+    let key = [7u8; 32];
+    let frames: Vec<Vec<u8>> = vec![
+      b"1.0".to_vec(),
+      b"1".to_vec(),
+      ZAP_DOMAIN.as_bytes().to_vec(),
+      b"10.0.0.7".to_vec(),
+      Vec::new(),
+      b"CURVE".to_vec(),
+      key.to_vec(),
+    ];
+    let request = parse_zap_request(&frames).expect("a well-formed request");
+    assert_eq!(request.address, "10.0.0.7");
+    assert_eq!(request.client_key, zmq::z85_encode(&key).ok());
+    let mut null = frames[..6].to_vec();
+    null[5] = b"NULL".to_vec();
+    let null = parse_zap_request(&null).expect("a NULL-mechanism request");
+    assert_eq!(null.client_key, None, "no key, nothing to admit");
+    assert_eq!(parse_zap_request(&frames[..3]), None);
+  }
+}
@@ -25,7 +25,9 @@ use crate::backend::DbPool;
 use crate::frontend::actor::{
   ADMIN_COOKIE, Actor, AdminSession, ReturnTo, owner_for_token, safe_next, sign_in_url,
 };
-use crate::models::{Corpus, HistoricalRun, Session, Task, WorkerMetadata};
+use crate::models::{
+  ActivationWeight, Corpus, FairShareDto, HistoricalRun, Session, Task, WorkerMetadata,
+};
 
 /// At-a-glance operational snapshot for the admin **live ops console** — the small-table signals
 /// (plus the one pending-task backlog count) the dashboard polls every few seconds and renders
@@ -60,6 +62,9 @@ pub struct AdminStatusDto {
   pub pool_max: u32,
   /// The most recent conversion run (live tallies overlaid while it is still open), if any.
   pub last_run: Option<LastRunDto>,
+  /// Per-activation fair-share leasing: each `(corpus, service)` pair's configured weight, the
+  /// share it entitles the corpus to, and the share of the service's recent leases it achieved.
+  pub fair_share: Vec<FairShareDto>,
 }
 
 /// The latest run's headline, with live task tallies overlaid while it is still open (so the card
@@ -98,6 +103,7 @@ pub fn admin_status(pool: &DbPool) -> AdminStatusDto {
     pool_in_use: state.connections.saturating_sub(state.idle_connections),
     pool_max: pool.max_size(),
     last_run: None,
+    fair_share: Vec::new(),
   };
   if let Ok(mut connection) = pool.get() {
     status.corpus_count = Corpus::all(&mut connection).map_or(0, |corpora| corpora.len());
@@ -112,6 +118,7 @@ pub fn admin_status(pool: &DbPool) -> AdminStatusDto {
     // Pending-conversion backlog (the unleased work waiting for the fleet) — the one full-table
     // count here, degrading to 0 on error like its siblings.
     status.tasks_todo = Task::count_todo(&mut connection);
+    status.fair_share = ActivationWeight::shares(&mut connection).unwrap_or_default();
     status.last_run = HistoricalRun::recent_all(&mut connection, 1)
       .ok()
       .and_then(|runs| runs.into_iter().next())
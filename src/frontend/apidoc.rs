@@ -34,12 +34,19 @@ use crate::frontend::admin::{
 use crate::frontend::audit::{api_audit, okapi_add_operation_for_api_audit_};
 use crate::frontend::corpora::{
   activate_service, api_corpora, api_corpus, create_sandbox_corpus, deactivate_service,
-  delete_corpus, export_dataset, extend_corpus, import_corpus,
+  delete_corpus, export_dataset, extend_corpus, fsck_corpus_service, import_corpus,
   okapi_add_operation_for_activate_service_, okapi_add_operation_for_api_corpora_,
   okapi_add_operation_for_api_corpus_, okapi_add_operation_for_create_sandbox_corpus_,
   okapi_add_operation_for_deactivate_service_, okapi_add_operation_for_delete_corpus_,
   okapi_add_operation_for_export_dataset_, okapi_add_operation_for_extend_corpus_,
-  okapi_add_operation_for_import_corpus_, okapi_add_operation_for_snapshot_tasks_, snapshot_tasks,
+  okapi_add_operation_for_fsck_corpus_service_, okapi_add_operation_for_import_corpus_,
+  okapi_add_operation_for_preview_corpus_import_, okapi_add_operation_for_set_activation_weight_,
+  okapi_add_operation_for_snapshot_tasks_, preview_corpus_import, set_activation_weight,
+  snapshot_tasks,
+};
+use crate::frontend::dead_letters::{
+  api_dead_letters, api_resolve_dead_letter, okapi_add_operation_for_api_dead_letters_,
+  okapi_add_operation_for_api_resolve_dead_letter_,
 };
 use crate::frontend::jobs::{
   api_job, api_jobs, okapi_add_operation_for_api_job_, okapi_add_operation_for_api_jobs_,
@@ -51,36 +58,50 @@ use crate::frontend::management::{
   okapi_add_operation_for_put_config_, okapi_add_operation_for_reindex_, put_config, reindex,
 };
 use crate::frontend::reports::{
-  api_category_report, api_document, api_entry_list, api_service_overview, api_what_report,
-  okapi_add_operation_for_api_category_report_, okapi_add_operation_for_api_document_,
-  okapi_add_operation_for_api_entry_list_, okapi_add_operation_for_api_service_overview_,
-  okapi_add_operation_for_api_what_report_, okapi_add_operation_for_pause_all_api_,
-  okapi_add_operation_for_pause_run_api_, okapi_add_operation_for_refresh_report_scope_api_,
-  okapi_add_operation_for_refresh_reports_, okapi_add_operation_for_rerun_report_,
+  api_breakdown, api_category_report, api_document, api_document_diff, api_entry_list,
+  api_message_clusters, api_service_overview, api_what_report,
+  okapi_add_operation_for_api_breakdown_, okapi_add_operation_for_api_category_report_,
+  okapi_add_operation_for_api_document_, okapi_add_operation_for_api_document_diff_,
+  okapi_add_operation_for_api_entry_list_, okapi_add_operation_for_api_message_clusters_,
+  okapi_add_operation_for_api_service_overview_, okapi_add_operation_for_api_what_report_,
+  okapi_add_operation_for_pause_all_api_, okapi_add_operation_for_pause_run_api_,
+  okapi_add_operation_for_refresh_report_scope_api_, okapi_add_operation_for_refresh_reports_,
+  okapi_add_operation_for_release_document_api_, okapi_add_operation_for_rerun_report_,
   okapi_add_operation_for_resume_all_api_, okapi_add_operation_for_resume_run_api_, pause_all_api,
-  pause_run_api, refresh_report_scope_api, refresh_reports, rerun_report, resume_all_api,
-  resume_run_api,
+  pause_run_api, refresh_report_scope_api, refresh_reports, release_document_api, rerun_report,
+  resume_all_api, resume_run_api,
 };
 use crate::frontend::retention::{
-  api_historical_stats, okapi_add_operation_for_api_historical_stats_,
+  api_archive_gc, api_historical_stats, okapi_add_operation_for_api_archive_gc_,
+  okapi_add_operation_for_api_historical_stats_,
 };
 use crate::frontend::runs::{
-  api_all_runs, api_run_current, api_run_diff, api_run_task_diffs, api_runs,
-  okapi_add_operation_for_api_all_runs_, okapi_add_operation_for_api_run_current_,
-  okapi_add_operation_for_api_run_diff_, okapi_add_operation_for_api_run_task_diffs_,
-  okapi_add_operation_for_api_runs_,
+  api_all_runs, api_class_movers, api_class_trend, api_run_current, api_run_diff,
+  api_run_task_diffs, api_runs, okapi_add_operation_for_api_all_runs_,
+  okapi_add_operation_for_api_class_movers_, okapi_add_operation_for_api_class_trend_,
+  okapi_add_operation_for_api_run_current_, okapi_add_operation_for_api_run_diff_,
+  okapi_add_operation_for_api_run_task_diffs_, okapi_add_operation_for_api_runs_,
 };
+use crate::frontend::search::{api_search_messages, okapi_add_operation_for_api_search_messages_};
 use crate::frontend::services::{
   api_service_runtimes, api_service_workers, api_services, delete_service,
   okapi_add_operation_for_api_service_runtimes_, okapi_add_operation_for_api_service_workers_,
   okapi_add_operation_for_api_services_, okapi_add_operation_for_delete_service_,
   okapi_add_operation_for_register_service_, okapi_add_operation_for_set_service_lease_,
-  register_service, set_service_lease,
+  okapi_add_operation_for_set_service_log_parser_, okapi_add_operation_for_set_service_pipeline_,
+  okapi_add_operation_for_set_service_renewals_, okapi_add_operation_for_set_service_versions_,
+  register_service, set_service_lease, set_service_log_parser, set_service_pipeline,
+  set_service_renewals, set_service_versions,
 };
 use crate::frontend::sessions::{
   api_revoke_sessions, api_sessions, okapi_add_operation_for_api_revoke_sessions_,
   okapi_add_operation_for_api_sessions_,
 };
+use crate::frontend::worker_keys::{
+  api_add_worker_key, api_revoke_worker_key, api_worker_keys,
+  okapi_add_operation_for_api_add_worker_key_, okapi_add_operation_for_api_revoke_worker_key_,
+  okapi_add_operation_for_api_worker_keys_,
+};
 
 /// The generated OpenAPI document, serialized once at mount time and served verbatim.
 struct SpecJson(String);
@@ -119,6 +140,8 @@ pending-conversion backlog, the latest run).\n\
 - `GET /api/health` — deep health check (connection pool, dispatcher ports, corpus storage).\n\
 - `GET /api/corpora` and `GET /api/reports/<corpus>/<service>/<severity>` — the conversion report \
 hierarchy (paginated).\n\
+- `GET /api/search/messages?q=…` — full-text search over the conversion messages (which documents \
+mention a macro, a package, a file).\n\
 - `GET /api/runs` and `GET /api/runs/<corpus>/<service>/diff` — live and historical run state.\n\
 - `GET /metrics` — Prometheus gauges.\n\
 \n\
@@ -173,11 +196,18 @@ fn routes_and_spec() -> (Vec<rocket::Route>, rocket_okapi::okapi::openapi3::Open
     api_run_current,
     api_run_diff,
     api_run_task_diffs,
+    api_class_trend,
+    api_class_movers,
     api_service_overview,
     api_category_report,
     api_what_report,
     api_entry_list,
+    api_message_clusters,
+    api_search_messages,
     api_document,
+    api_document_diff,
+    api_breakdown,
+    release_document_api,
     api_index,
     api_config,
     healthz,
@@ -185,12 +215,19 @@ fn routes_and_spec() -> (Vec<rocket::Route>, rocket_okapi::okapi::openapi3::Open
     register_service,
     delete_service,
     set_service_lease,
+    set_service_renewals,
+    set_service_versions,
+    set_service_log_parser,
+    set_service_pipeline,
     api_service_runtimes,
     import_corpus,
+    preview_corpus_import,
     extend_corpus,
     export_dataset,
+    fsck_corpus_service,
     create_sandbox_corpus,
     activate_service,
+    set_activation_weight,
     deactivate_service,
     snapshot_tasks,
     delete_corpus,
@@ -209,7 +246,13 @@ fn routes_and_spec() -> (Vec<rocket::Route>, rocket_okapi::okapi::openapi3::Open
     api_audit,
     api_sessions,
     api_revoke_sessions,
+    api_dead_letters,
+    api_resolve_dead_letter,
+    api_worker_keys,
+    api_add_worker_key,
+    api_revoke_worker_key,
     api_historical_stats,
+    api_archive_gc,
   ];
   // Give every operation a short one-line `summary` for the RapiDoc left-nav; the full doc comment
   // stays as the `description` in the detail panel. Without a summary RapiDoc fell back to the long
@@ -1,7 +1,7 @@
 //! Common concerns for frontend routes
 use diesel::PgConnection;
 use rocket::fs::NamedFile;
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::response::status::{Accepted, NotFound};
 use rocket::serde::json::Json;
 use rocket::tokio::sync::{OwnedSemaphorePermit, Semaphore};
@@ -12,14 +12,16 @@ use std::str;
 use std::sync::Arc;
 
 use crate::backend::{
-  DbPool, PooledConn, RerunOptions, live_run_diff, mark_all_blocked, mark_blocked, mark_rerun,
-  progress_report, resume_all_blocked, resume_blocked, save_historical_tasks,
+  ControlKind, ControlSignal, DbPool, PooledConn, RerunOptions, live_run_diff, mark_all_blocked,
+  mark_blocked, mark_rerun, priority_backlog, progress_report, resume_all_blocked, resume_blocked,
+  save_historical_tasks, wake_dispatcher,
 };
 use crate::frontend::actor::AdminSession;
 use crate::frontend::helpers::*;
 use crate::frontend::params::{ReportParams, RerunRequestParams, TemplateContext};
 use crate::frontend::render::task_report;
 use crate::models::{Corpus, HistoricalRun, Service, Task};
+use crate::storage::{read_all, storage};
 
 /// Placeholder word for unknown filters/fields
 pub const UNKNOWN: &str = "_unknown_";
@@ -285,6 +287,22 @@ pub fn serve_report(
             ld.reclassified.to_string(),
           );
         }
+        // The outstanding backlog per leasing priority, in dispatch order — only worth a line when
+        // a prioritized rerun split it (a lone priority-0 bucket is just the "awaiting" count).
+        let backlog = priority_backlog(connection, corpus.id, service.id);
+        if backlog.iter().any(|&(priority, _)| priority != 0) {
+          context.backlog = Some(
+            backlog
+              .into_iter()
+              .map(|(priority, tasks)| {
+                HashMap::from([
+                  ("priority".to_string(), priority.to_string()),
+                  ("tasks".to_string(), tasks.to_string()),
+                ])
+              })
+              .collect(),
+          );
+        }
         // Record the report into the globals
         for (key, val) in report {
           global.insert(key.clone(), val.to_string());
@@ -493,8 +511,11 @@ pub fn serve_report(
 
 /// Rerun a filtered subset of tasks for a <corpus,service> pair, over the caller-supplied (pooled)
 /// `connection`, attributed to the already-authenticated `owner` (the signed-in admin — the route
-/// gates on the [`crate::frontend::actor::AdminSession`] cookie, so there is no token here). `404`
-/// on an unknown corpus/service, `500` if the rerun marking fails.
+/// gates on the [`crate::frontend::actor::AdminSession`] cookie, so there is no token here). A
+/// `priority` stamps the rerun tasks so the dispatcher leases them ahead of the ordinary backlog; a
+/// message `text` query narrows the scope to the documents with a matching message. `400` on an
+/// invalid severity or a blank `text`, `404` on an unknown corpus/service, `500` if the rerun
+/// marking fails.
 #[allow(clippy::too_many_arguments)]
 pub fn serve_rerun(
   connection: &mut PgConnection,
@@ -506,14 +527,24 @@ pub fn serve_rerun(
   what: Option<String>,
   owner: &str,
   description: &str,
+  priority: Option<i32>,
+  text: Option<String>,
 ) -> Result<Accepted<String>, Status> {
   let corpus_name = corpus_name.to_lowercase();
   let service_name = service_name.to_lowercase();
+  if let Some(ref text) = text
+    && crate::backend::validate_message_text(text).is_err()
+  {
+    return Err(Status::BadRequest);
+  }
   // Reject an out-of-scope / typo'd rerun severity (R-9) up front, instead of letting `mark_rerun`
   // silently mis-scope it to `no_problem` — the same guard the agent `rerun_report` applies, so the
   // human and agent surfaces accept/reject the same set.
   if let Some(ref severity) = severity
-    && !crate::frontend::reports::is_valid_rerun_severity(severity, category.is_some())
+    && !crate::frontend::reports::is_valid_rerun_severity(
+      severity,
+      category.is_some() || text.is_some(),
+    )
   {
     return Err(Status::BadRequest);
   }
@@ -526,6 +557,8 @@ pub fn serve_rerun(
     severity = ?severity,
     category = ?category,
     what = ?what,
+    text = ?text,
+    priority = ?priority,
     "rerun requested"
   );
   let corpus = Corpus::find_by_name(&corpus_name, connection).map_err(|_| Status::NotFound)?;
@@ -541,6 +574,8 @@ pub fn serve_rerun(
       what_opt: what,
       description_opt: Some(description.to_string()),
       owner_opt: Some(owner.to_string()),
+      priority_opt: priority,
+      text_opt: text,
     },
   );
   let report_duration = (chrono::Utc::now() - report_start).num_milliseconds();
@@ -555,6 +590,11 @@ pub fn serve_rerun(
         duration_ms = report_duration,
         "rerun committed"
       );
+      // Wake the dispatcher so the fresh TODO tasks are leased now, not on its next refetch.
+      wake_dispatcher(
+        connection,
+        ControlSignal::scoped(ControlKind::Rerun, corpus.id, service.id),
+      );
       // The reran (corpus, service) scope's report cache was already invalidated inside the rerun
       // transaction (`mark_rerun`), so its reports repopulate fresh on the next view — no separate,
       // globally-scoped refresh job needed.
@@ -609,6 +649,17 @@ pub fn serve_pause_resume(
         affected = count,
         "run control committed"
       );
+      // Wake the dispatcher: a pause drops the scope's already-queued tasks before they are handed
+      // out, a resume refetches the restored TODO tasks right away.
+      let kind = if pause {
+        ControlKind::Pause
+      } else {
+        ControlKind::Resume
+      };
+      wake_dispatcher(
+        connection,
+        ControlSignal::scoped(kind, corpus.id, service.id),
+      );
       Ok(count)
     },
   }
@@ -690,6 +741,12 @@ pub fn serve_pause_resume_all(
         affected = count,
         "global run control committed"
       );
+      let kind = if pause {
+        ControlKind::Pause
+      } else {
+        ControlKind::Resume
+      };
+      wake_dispatcher(connection, ControlSignal::global(kind));
       Ok(count)
     },
   }
@@ -752,16 +809,26 @@ pub fn serve_savetasks(
 
 /// A file download served with a `Content-Disposition` filename derived from the document's **entry
 /// name** (e.g. `0811.0417.zip`) instead of the opaque task id in the URL — so corpus curators get
-/// an informative filename (UX request 2026-06-16). Wraps a [`NamedFile`] and adds the header.
+/// an informative filename (UX request 2026-06-16). Wraps the served archive and adds the header.
 pub struct EntryDownload {
-  file: NamedFile,
+  file: EntryBody,
   /// The download filename, already sanitised to a safe character set.
   filename: String,
 }
 
+/// The body of an [`EntryDownload`]: a local file streamed from disk, or an object read out of the
+/// storage backend (an object store keeps no local file to stream).
+enum EntryBody {
+  File(NamedFile),
+  Object(ContentType, Vec<u8>),
+}
+
 impl<'r> rocket::response::Responder<'r, 'static> for EntryDownload {
   fn respond_to(self, request: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
-    let mut response = self.file.respond_to(request)?;
+    let mut response = match self.file {
+      EntryBody::File(file) => file.respond_to(request)?,
+      EntryBody::Object(content_type, bytes) => (content_type, bytes).respond_to(request)?,
+    };
     response.set_raw_header(
       "Content-Disposition",
       format!("attachment; filename=\"{}\"", self.filename),
@@ -845,9 +912,9 @@ pub async fn serve_entry(
       };
       match zip_path {
         Some(path) => {
-          let file = NamedFile::open(&path)
+          let file = open_entry_file(path.clone())
             .await
-            .map_err(|_| NotFound("Invalid Zip at path".to_string()))?;
+            .ok_or_else(|| NotFound("Invalid Zip at path".to_string()))?;
           // Name the download from the served file's real extension (zip / gz / …). The `import`
           // service serves the **source** archive, which keeps the bare document name
           // (`0811.0417.zip`); any **result** archive appends its service so a curator can tell the
@@ -868,6 +935,25 @@ pub async fn serve_entry(
   }
 }
 
+/// Opens an entry's archive through the storage backend: streamed from disk when the backend keeps
+/// a local file, else read whole off the request thread. `None` when it is missing or unreadable.
+async fn open_entry_file(path: std::path::PathBuf) -> Option<EntryBody> {
+  let storage = storage();
+  if let Some(local) = storage.local_path(&path) {
+    return NamedFile::open(&local).await.ok().map(EntryBody::File);
+  }
+  let content_type = path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .and_then(ContentType::from_extension)
+    .unwrap_or(ContentType::Binary);
+  let bytes = rocket::tokio::task::spawn_blocking(move || read_all(&*storage, &path))
+    .await
+    .ok()?
+    .ok()?;
+  Some(EntryBody::Object(content_type, bytes))
+}
+
 /// Serves an entry as a `Template` instance to be preview via a client-side asset renderer, over
 /// the caller-supplied (pooled) `connection`.
 pub fn serve_entry_preview(
@@ -970,9 +1056,10 @@ pub fn preview_entry(
   serve_entry_preview(&mut connection, corpus_name, service_name, entry_name)
 }
 
-/// **Downloads** a converted document's result archive (streamed, so a large archive never loads
-/// into memory). `404` when the task is unknown **or** the archive is missing/unreadable on `/data`
-/// — a hostile or unmounted filesystem yields a clean `404`, never a panic or a `500`.
+/// **Downloads** a converted document's result archive (streamed from disk with the local storage
+/// backend, so a large archive never loads into memory). `404` when the task is unknown **or** the
+/// archive is missing/unreadable in storage — a hostile or unmounted filesystem, or an unreachable
+/// object store, yields a clean `404`, never a panic or a `500`.
 #[post("/entry/<service_name>/<entry_id>")]
 pub async fn entry_fetch(
   service_name: String,
@@ -1013,7 +1100,7 @@ fn human_rerun(
   severity: Option<String>,
   category: Option<String>,
   what: Option<String>,
-  description: &str,
+  rr: &RerunRequestParams,
 ) -> Result<Accepted<String>, Status> {
   let session = session.ok_or(Status::Unauthorized)?;
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
@@ -1026,12 +1113,14 @@ fn human_rerun(
     category,
     what,
     &session.owner,
-    description,
+    &rr.description,
+    rr.priority,
+    rr.text.clone(),
   )
 }
 
 /// Human rerun — whole `(corpus, service)`. Cookie-gated; the rerun modal XHRs a JSON
-/// `{description}`.
+/// `{description, priority?}`.
 #[post(
   "/rerun/<corpus_name>/<service_name>",
   format = "application/json",
@@ -1052,7 +1141,7 @@ pub fn rerun_corpus(
     None,
     None,
     None,
-    &rr.description,
+    &rr,
   )
 }
 
@@ -1078,7 +1167,7 @@ pub fn rerun_severity(
     Some(severity),
     None,
     None,
-    &rr.description,
+    &rr,
   )
 }
 
@@ -1106,7 +1195,7 @@ pub fn rerun_category(
     Some(severity),
     Some(category),
     None,
-    &rr.description,
+    &rr,
   )
 }
 
@@ -1135,7 +1224,7 @@ pub fn rerun_what(
     Some(severity),
     Some(category),
     Some(what),
-    &rr.description,
+    &rr,
   )
 }
 
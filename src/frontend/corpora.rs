@@ -25,8 +25,9 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::backend::{
-  DatabaseUrl, DbPool, GroupBy, SandboxSelection, create_sandbox, export_html_dataset,
-  from_address, progress_report,
+  ControlKind, ControlSignal, DatabaseUrl, DbPool, DocumentFilter, FsckOptions, GroupBy,
+  SandboxSelection, create_sandbox, export_html_dataset, from_address, fsck_archives,
+  progress_report, wake_dispatcher,
 };
 use crate::concerns::CortexInsertable;
 use crate::frontend::actor::{Actor, AdminReject, AdminSession, ReturnTo, require_admin_to};
@@ -35,16 +36,17 @@ use crate::frontend::jobs::JobDto;
 use crate::frontend::params::TemplateContext;
 use crate::helpers::TaskStatus;
 use crate::importer::Importer;
+use crate::importer::preview;
+use crate::importer::unpackers::{self, DEFAULT_UNPACKER};
 use crate::jobs::{self, JobProgress};
-use crate::models::{Corpus, NewCorpus, Service};
+use crate::models::{ActivationWeight, Corpus, IMPORT_SERVICE_ID, NewCorpus, Service};
+use crate::storage::storage;
 use rocket_okapi::openapi;
 use schemars::JsonSchema;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// The magic `import` service id. Service ids `1` (`init`) and `2` (`import`) are infrastructure
-/// (CLAUDE.md: real conversion services have id `> 2`); a service with id `≤` this is never
-/// user-activatable in the picker nor deactivatable from a corpus.
-const IMPORT_SERVICE_ID: i32 = 2;
+/// The job kind of an import preview (see [`start_preview`]).
+const PREVIEW_JOB_KIND: &str = "corpus_import_preview";
 
 /// A corpus as exposed over the API/UI. `name` is the stable external handle used by every route.
 #[derive(Debug, Serialize, JsonSchema)]
@@ -60,6 +62,9 @@ pub struct CorpusDto {
   pub description: String,
   /// Whether documents are multi-file (complex) rather than a single TeX file.
   pub complex: bool,
+  /// Key of the unpacker that lays out a complex corpus's sources for import (`arxiv` unless
+  /// chosen otherwise at import).
+  pub unpacker: String,
   /// Number of ingested documents (import-service tasks) — the corpus's scale at a glance.
   pub document_count: i64,
   /// Name of the parent corpus if this is a **sandbox** (a carved subset), else `null`. Lets a
@@ -76,6 +81,7 @@ impl CorpusDto {
   pub fn build(corpus: Corpus, document_count: i64, parent: Option<String>) -> Self {
     CorpusDto {
       public_id: corpus.public_id.to_string(),
+      unpacker: corpus.unpacker_key().to_string(),
       name: corpus.name,
       path: corpus.path,
       description: corpus.description,
@@ -229,13 +235,31 @@ pub struct ImportRequest {
   pub complex: bool,
   /// Optional human-readable description.
   pub description: Option<String>,
+  /// Optional server-side path of an import manifest (JSON lines or CSV, see
+  /// `importer::manifest`): import exactly the documents it lists, with its metadata attached,
+  /// instead of walking `path`. Relative manifest paths resolve against `path`.
+  #[serde(default)]
+  pub manifest: Option<String>,
+  /// Optional server-side path of an arXiv metadata file (JSON lines with `id` and `categories`,
+  /// as in the public arXiv metadata snapshot): once imported, the documents' primary categories
+  /// are set from it.
+  #[serde(default)]
+  pub arxiv_metadata: Option<String>,
+  /// Optional unpacker key for a complex corpus — how its sources are laid out on disk (`arxiv`,
+  /// `archives`, `flat-zip`, `bundles` or `loose`; see `importer::unpackers`). Defaults to
+  /// `arxiv`, arXiv's bulk layout.
+  #[serde(default)]
+  pub unpacker: Option<String>,
 }
 
 /// Registers a corpus and starts an in-process import job; returns `202 Accepted` + the job handle.
 /// Agents and humans poll `GET /api/jobs/<uuid>` (or the progress page) for completion.
 /// **Token-gated** via the [`Actor`] guard (creating a corpus + a filesystem import job is a
 /// consequential write); `401` without a valid token, `409` if the corpus name already exists,
-/// `422` if the `path` is not a readable directory on the server.
+/// `422` if the `path` is not a readable directory on the server, the `manifest` or
+/// `arxiv_metadata` not a readable file, or the `unpacker` unknown (or given for a non-complex
+/// corpus). A manifest import's job result lists the missing,
+/// unreadable and malformed rows it skipped.
 #[rocket_okapi::openapi(tag = "Corpora")]
 #[post("/api/corpora", format = "json", data = "<request>")]
 pub fn import_corpus(
@@ -244,79 +268,199 @@ pub fn import_corpus(
   pool: &State<DbPool>,
   database_url: &State<DatabaseUrl>,
 ) -> Result<(Status, Json<JobDto>), Status> {
-  let request = request.into_inner();
-  let job_uuid = start_import(
-    pool,
-    &database_url.0,
-    &actor.owner,
-    request.name,
-    request.path,
-    request.complex,
-    request.description.unwrap_or_default(),
-  )?;
+  let job_uuid = start_import(pool, &database_url.0, &actor.owner, request.into_inner())?;
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
   let job = jobs::find_job(&mut connection, job_uuid).ok_or(Status::InternalServerError)?;
   Ok((Status::Accepted, Json(JobDto::from(job))))
 }
 
-/// Registers a corpus and spawns its import job, returning the job uuid. The shared core of the
-/// agent endpoint and the human form. `409` if the name already exists; `422` if the `path` is not
-/// a readable directory on the server (pre-flighted so a doomed import is never started).
-#[allow(clippy::too_many_arguments)]
-fn start_import(
-  pool: &DbPool,
-  database_url: &str,
-  actor: &str,
-  name: String,
-  path: String,
-  complex: bool,
-  description: String,
-) -> Result<Uuid, Status> {
-  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+/// Normalizes an import request (a blank `manifest`/`arxiv_metadata`, and a blank or default
+/// `unpacker`, become `None`) and pre-flights it, so a doomed import is never started: `400` for a
+/// blank name, `409` if the name already exists; `422` if the `path` is not a readable directory,
+/// or the `manifest`/`arxiv_metadata` not a readable file, on the server, or the `unpacker` is not
+/// a registered one for a complex corpus. Shared by the import and its preview.
+fn preflight_import(
+  connection: &mut PgConnection,
+  request: ImportRequest,
+) -> Result<ImportRequest, Status> {
+  let request = ImportRequest {
+    manifest: request
+      .manifest
+      .filter(|manifest| !manifest.trim().is_empty()),
+    arxiv_metadata: request
+      .arxiv_metadata
+      .filter(|metadata| !metadata.trim().is_empty()),
+    unpacker: request
+      .unpacker
+      .filter(|key| !key.trim().is_empty() && key != DEFAULT_UNPACKER),
+    ..request
+  };
   // Reject a blank name on the agent path too (the HTML form enforces `required`, but a raw
   // `POST /api/corpora` bypasses that) — an empty handle is unreachable by every name-keyed route.
-  if name.trim().is_empty() {
+  if request.name.trim().is_empty() {
     return Err(Status::BadRequest);
   }
-  if Corpus::find_by_name(&name, &mut connection).is_ok() {
+  if Corpus::find_by_name(&request.name, connection).is_ok() {
     return Err(Status::Conflict);
   }
   // Pre-flight the source path (the in-process import reads it): reject a path that doesn't exist
   // or isn't a readable directory, so the admin/agent gets immediate feedback instead of a
   // registered corpus whose import silently finds nothing. `422` distinguishes it from the `409`
   // name clash.
-  if !std::fs::metadata(path.trim_end())
+  if !std::fs::metadata(request.path.trim_end())
     .map(|meta| meta.is_dir())
     .unwrap_or(false)
   {
     return Err(Status::UnprocessableEntity);
   }
+  if let Some(ref key) = request.unpacker
+    && (!request.complex || unpackers::find(key).is_none())
+  {
+    return Err(Status::UnprocessableEntity);
+  }
+  for file in [&request.manifest, &request.arxiv_metadata]
+    .into_iter()
+    .flatten()
+  {
+    if !std::fs::metadata(file.trim())
+      .map(|meta| meta.is_file())
+      .unwrap_or(false)
+    {
+      return Err(Status::UnprocessableEntity);
+    }
+  }
+  Ok(request)
+}
+
+/// Registers a corpus and spawns its import job, returning the job uuid. The shared core of the
+/// agent endpoint and the human form; the request is pre-flighted by [`preflight_import`].
+fn start_import(
+  pool: &DbPool,
+  database_url: &str,
+  actor: &str,
+  request: ImportRequest,
+) -> Result<Uuid, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let ImportRequest {
+    name,
+    path,
+    complex,
+    description,
+    manifest,
+    arxiv_metadata,
+    unpacker,
+  } = preflight_import(&mut connection, request)?;
   NewCorpus {
     name: name.clone(),
     path: path.clone(),
     complex,
-    description,
+    description: description.unwrap_or_default(),
   }
   .create(&mut connection)
   .map_err(|_| Status::InternalServerError)?;
-  let corpus =
+  let mut corpus =
     Corpus::find_by_name(&name, &mut connection).map_err(|_| Status::InternalServerError)?;
+  if unpacker.is_some() {
+    corpus
+      .set_unpacker(unpacker.as_deref(), &mut connection)
+      .map_err(|_| Status::InternalServerError)?;
+  }
   drop(connection);
 
   let database_url = database_url.to_string();
-  let params = serde_json::json!({ "name": name, "path": path });
+  let params = serde_json::json!({
+    "name": name,
+    "path": path,
+    "manifest": manifest,
+    "arxiv_metadata": arxiv_metadata,
+    "unpacker": corpus.unpacker_key(),
+  });
   jobs::spawn_job(
     pool.clone(),
     "corpus_import",
     actor,
     params,
-    move |progress| run_import(&database_url, corpus, progress),
+    move |progress| {
+      let arxiv_metadata = arxiv_metadata.as_deref().map(str::trim);
+      match manifest {
+        Some(manifest) => run_manifest_import(
+          &database_url,
+          corpus,
+          manifest.trim(),
+          arxiv_metadata,
+          progress,
+        ),
+        None => run_import(&database_url, corpus, arxiv_metadata, progress),
+      }
+    },
+  )
+  .map_err(|_| Status::InternalServerError)
+}
+
+/// Previews the import a `POST /api/corpora` with the same body would run, without registering,
+/// unpacking or importing anything: walks the `path` (or reads the `manifest`) and sniffs every
+/// source, which on a large corpus takes a while — so it runs as a background job, and the `202`
+/// carries its [`JobDto`]. Poll `GET /api/jobs/<uuid>`: the succeeded job's `result` is the
+/// [`ImportPreview`](preview::ImportPreview) — the projected entry count, the per-month spread,
+/// the skipped paths with their reasons, symlink-loop hits and the largest entries — and a
+/// manifest that cannot be parsed fails the job. **Token-gated** via the [`Actor`] guard (it reads
+/// server-side paths); the same `400`/`409`/`422` pre-flight as [`import_corpus`].
+#[rocket_okapi::openapi(tag = "Corpora")]
+#[post("/api/corpora/preview", format = "json", data = "<request>")]
+pub fn preview_corpus_import(
+  request: Json<ImportRequest>,
+  actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<(Status, Json<JobDto>), Status> {
+  let job_uuid = start_preview(pool, &actor.owner, request.into_inner())?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let job = jobs::find_job(&mut connection, job_uuid).ok_or(Status::InternalServerError)?;
+  Ok((Status::Accepted, Json(JobDto::from(job))))
+}
+
+/// Pre-flights an import `request` and spawns the `corpus_import_preview` job that previews it,
+/// returning the job uuid. The job's params keep the request, so the human form can be re-rendered
+/// from them; its result is the [`ImportPreview`](preview::ImportPreview). Shared by the agent
+/// endpoint and the human form.
+fn start_preview(pool: &DbPool, actor: &str, request: ImportRequest) -> Result<Uuid, Status> {
+  let request = {
+    let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+    preflight_import(&mut connection, request)?
+  };
+  let params = serde_json::json!({
+    "name": request.name,
+    "path": request.path,
+    "complex": request.complex,
+    "description": request.description,
+    "manifest": request.manifest,
+    "unpacker": request.unpacker,
+  });
+  jobs::spawn_job(
+    pool.clone(),
+    PREVIEW_JOB_KIND,
+    actor,
+    params,
+    move |progress| {
+      progress.step(0, None, "walking and sniffing the corpus sources");
+      let report = preview::preview_import(
+        &request.path,
+        request.complex,
+        request.unpacker.as_deref(),
+        request
+          .manifest
+          .as_deref()
+          .map(|manifest| Path::new(manifest.trim())),
+      )
+      .map_err(|error| error.to_string())?;
+      progress.step(1, Some(1), "preview complete");
+      serde_json::to_value(report).map_err(|error| error.to_string())
+    },
   )
   .map_err(|_| Status::InternalServerError)
 }
 
 /// Fields of the human "Add a corpus" form on the admin dashboard.
-#[derive(FromForm)]
+#[derive(Default, FromForm)]
 pub struct ImportForm {
   /// Corpus name (external handle).
   pub name: String,
@@ -326,6 +470,73 @@ pub struct ImportForm {
   pub complex: bool,
   /// Optional description.
   pub description: Option<String>,
+  /// Optional import manifest path (server-side).
+  pub manifest: Option<String>,
+  /// The unpacker of a complex corpus (see `importer::unpackers`).
+  pub unpacker: Option<String>,
+}
+
+impl ImportForm {
+  /// The selected unpacker key ([`DEFAULT_UNPACKER`] when none was chosen).
+  fn unpacker_key(&self) -> &str { self.unpacker.as_deref().unwrap_or(DEFAULT_UNPACKER) }
+
+  /// The [`ImportRequest`] the form submits.
+  fn request(&self) -> ImportRequest {
+    ImportRequest {
+      name: self.name.clone(),
+      path: self.path.clone(),
+      complex: self.complex,
+      description: self.description.clone(),
+      manifest: self.manifest.clone(),
+      arxiv_metadata: None,
+      unpacker: Some(self.unpacker_key().to_string()),
+    }
+  }
+
+  /// The form a preview job was started from, rebuilt from the job's params.
+  fn from_preview_params(params: &Value) -> Self {
+    let text = |key: &str| params[key].as_str().map(str::to_string);
+    ImportForm {
+      name: text("name").unwrap_or_default(),
+      path: text("path").unwrap_or_default(),
+      complex: params["complex"].as_bool().unwrap_or(false),
+      description: text("description"),
+      manifest: text("manifest"),
+      unpacker: text("unpacker"),
+    }
+  }
+
+  /// The friendly message for a submit (or preview) of the form that failed with `status`.
+  fn error_message(&self, status: Status) -> String {
+    let unpacker = self.unpacker_key();
+    let manifest = self.manifest.as_deref().unwrap_or_default();
+    match status.code {
+      409 => format!(
+        "A corpus named “{}” already exists — choose a different name.",
+        self.name
+      ),
+      422 if unpacker != DEFAULT_UNPACKER && !self.complex => format!(
+        "The “{unpacker}” unpacker only applies to multi-file (complex) corpora — tick the \
+         checkbox or keep the arXiv layout."
+      ),
+      422 if unpackers::find(unpacker).is_none() => format!(
+        "“{unpacker}” is not a known unpacker (known: {}).",
+        unpackers::keys()
+      ),
+      422
+        if !manifest.trim().is_empty()
+          && std::fs::metadata(self.path.trim_end()).is_ok_and(|meta| meta.is_dir()) =>
+      {
+        format!("“{manifest}” is not a readable file on the server — check the manifest path.")
+      },
+      422 => format!(
+        "“{}” is not a readable directory on the server — check the path.",
+        self.path
+      ),
+      503 => "The database is temporarily unavailable — please try again.".to_string(),
+      _ => "Could not register the corpus — check the values and try again.".to_string(),
+    }
+  }
 }
 
 /// The human twin of [`import_corpus`]: the admin dashboard's "Add a corpus" form. **Gated by the
@@ -346,46 +557,83 @@ pub fn import_corpus_human(
     return Ok(Redirect::to("/admin/login"));
   };
   let form = form.into_inner();
-  let description = form.description.clone().unwrap_or_default();
-  match start_import(
-    pool,
-    &database_url.0,
-    &session.owner,
-    form.name.clone(),
-    form.path.clone(),
-    form.complex,
-    description.clone(),
-  ) {
+  match start_import(pool, &database_url.0, &session.owner, form.request()) {
     Ok(uuid) => Ok(Redirect::to(format!("/jobs/{uuid}"))),
     // A failed submit re-renders the form with a friendly message + the values preserved, rather
     // than a bare error page that loses the admin's input.
-    Err(status) => {
-      let message = match status.code {
-        409 => format!(
-          "A corpus named “{}” already exists — choose a different name.",
-          form.name
-        ),
-        422 => format!(
-          "“{}” is not a readable directory on the server — check the path.",
-          form.path
-        ),
-        503 => "The database is temporarily unavailable — please try again.".to_string(),
-        _ => "Could not register the corpus — check the values and try again.".to_string(),
-      };
-      Err(render_corpora_new(
-        Some(&message),
-        &form.name,
-        &form.path,
-        &description,
-        form.complex,
-      ))
-    },
+    Err(status) => Err(render_corpora_new(
+      &form,
+      Some(&form.error_message(status)),
+      None,
+    )),
   }
 }
 
+/// The "Preview" button of the "Add a corpus" form — the human twin of [`preview_corpus_import`].
+/// **Gated by the signed-in [`AdminSession`] cookie** (anonymous → sign-in). Starts the preview job
+/// and redirects to [`import_preview_page`], which shows the form with the values kept and the
+/// preview once it is ready; nothing is registered until the admin confirms with "Register &
+/// import". A failed pre-flight re-renders the form with the same messages as a submit.
+#[allow(clippy::result_large_err)] // The Err is the re-rendered form, as for `import_corpus_human`.
+#[post("/corpus/import/preview", data = "<form>")]
+pub fn preview_corpus_import_human(
+  form: Form<ImportForm>,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Redirect, Template> {
+  let Some(session) = session else {
+    return Ok(Redirect::to("/admin/login"));
+  };
+  let form = form.into_inner();
+  match start_preview(pool, &session.owner, form.request()) {
+    Ok(uuid) => Ok(Redirect::to(format!("/corpus/import/preview/{uuid}"))),
+    Err(status) => Err(render_corpora_new(
+      &form,
+      Some(&form.error_message(status)),
+      None,
+    )),
+  }
+}
+
+/// The "Add a corpus" form of a preview job (`GET /corpus/import/preview/<uuid>`), its values
+/// rebuilt from the job: while the preview runs the page says so and refreshes itself; once it
+/// succeeded the preview is shown above the confirm button; a failed preview shows its error.
+/// Signed-in admins only (anonymous → sign-in); `404` for an unknown job or one of another kind.
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[get("/corpus/import/preview/<uuid>")]
+pub fn import_preview_page(
+  uuid: &str,
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Template, AdminReject> {
+  require_admin_to(session, &return_to)?;
+  let parsed = Uuid::parse_str(uuid).map_err(|_| Status::NotFound)?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let job = jobs::find_job(&mut connection, parsed)
+    .filter(|job| job.kind == PREVIEW_JOB_KIND)
+    .ok_or(Status::NotFound)?;
+  let form = ImportForm::from_preview_params(&job.params);
+  Ok(match job.status.as_str() {
+    "succeeded" => render_corpora_new(&form, None, job.result.as_ref()),
+    "failed" | "interrupted" => render_corpora_new(
+      &form,
+      Some(&format!("The preview failed: {}", job.message)),
+      None,
+    ),
+    _ => render_corpora_pending(&form, uuid),
+  })
+}
+
 /// The body of a `corpus_import` job: run the importer in-process against `corpus`, reporting
-/// progress, and return the number of import-service tasks created.
-fn run_import(database_url: &str, corpus: Corpus, progress: &JobProgress) -> Result<Value, String> {
+/// progress, then apply the arXiv metadata file if given. Returns the number of import-service
+/// tasks created (and of documents categorized).
+fn run_import(
+  database_url: &str,
+  corpus: Corpus,
+  arxiv_metadata: Option<&str>,
+  progress: &JobProgress,
+) -> Result<Value, String> {
   let corpus_id = corpus.id;
   let mut importer = Importer {
     corpus,
@@ -395,9 +643,61 @@ fn run_import(database_url: &str, corpus: Corpus, progress: &JobProgress) -> Res
   };
   progress.step(0, None, "importing corpus");
   importer.process().map_err(|error| error.to_string())?;
-  let imported = count_service_tasks(&mut importer.backend.connection, corpus_id, 2);
+  let imported = count_service_tasks(
+    &mut importer.backend.connection,
+    corpus_id,
+    IMPORT_SERVICE_ID,
+  );
+  let mut result = serde_json::json!({ "imported": imported });
+  categorize(&mut importer, arxiv_metadata, &mut result, progress)?;
+  progress.step(imported, Some(imported), "import complete");
+  Ok(result)
+}
+
+/// The body of a manifest `corpus_import` job: import the documents the manifest lists, and return
+/// the [`ManifestReport`](crate::importer::manifest::ManifestReport) — the new task count plus
+/// every skipped row and why.
+fn run_manifest_import(
+  database_url: &str,
+  corpus: Corpus,
+  manifest: &str,
+  arxiv_metadata: Option<&str>,
+  progress: &JobProgress,
+) -> Result<Value, String> {
+  let mut importer = Importer {
+    corpus,
+    backend: from_address(database_url),
+    cwd: Importer::cwd(),
+    active_prefixes: HashSet::new(),
+  };
+  progress.step(0, None, "importing manifest");
+  let report = importer
+    .import_manifest(Path::new(manifest))
+    .map_err(|error| error.to_string())?;
+  let imported = report.imported as i32;
+  let mut result = serde_json::to_value(report).map_err(|error| error.to_string())?;
+  categorize(&mut importer, arxiv_metadata, &mut result, progress)?;
   progress.step(imported, Some(imported), "import complete");
-  Ok(serde_json::json!({ "imported": imported }))
+  Ok(result)
+}
+
+/// Sets the imported documents' arXiv primary categories from `arxiv_metadata`, if given, recording
+/// how many were `categorized` in the job `result`.
+fn categorize(
+  importer: &mut Importer,
+  arxiv_metadata: Option<&str>,
+  result: &mut Value,
+  progress: &JobProgress,
+) -> Result<(), String> {
+  let Some(arxiv_metadata) = arxiv_metadata else {
+    return Ok(());
+  };
+  progress.step(0, None, "applying arXiv metadata");
+  let categorized = importer
+    .apply_arxiv_metadata(Path::new(arxiv_metadata))
+    .map_err(|error| error.to_string())?;
+  result["categorized"] = serde_json::json!(categorized);
+  Ok(())
 }
 
 /// Counts the tasks registered for a `(corpus, service)` pair.
@@ -419,8 +719,8 @@ pub struct ExportRequest {
   /// Server-side output directory for the archives + the `<corpus>-manifest.json` sidecar (created
   /// if missing).
   pub out: String,
-  /// Bucketing: `month` (one archive per year-month) or `severity` (one per severity). Defaults to
-  /// `month`.
+  /// Bucketing: `month` (one archive per year-month), `severity` (one per severity) or `category`
+  /// (one per arXiv primary category). Defaults to `month`.
   #[serde(default)]
   pub group_by: Option<String>,
   /// Severity keys to include (canonical: `no_problem` | `warning` | `error` | `fatal` |
@@ -432,6 +732,10 @@ pub struct ExportRequest {
   /// — the published `.zip` is smaller. Omit for one archive per bucket (no size limit).
   #[serde(default)]
   pub max_archive_mb: Option<u64>,
+  /// Optional narrowing by the papers' document facts (`yymm`, `primary_category`,
+  /// `min_source_bytes`/`max_source_bytes`, `min_files`/`max_files`), given flat.
+  #[serde(flatten)]
+  pub documents: DocumentFilter,
 }
 
 /// The default severity set when a caller omits `severities` — kept identical to the
@@ -502,6 +806,10 @@ fn start_export(
   if severities.is_empty() {
     return Err(Status::UnprocessableEntity);
   }
+  let documents = request.documents.normalized();
+  if documents.validate().is_err() {
+    return Err(Status::UnprocessableEntity);
+  }
 
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
   let corpus = Corpus::find_by_name(&corpus_name.to_lowercase(), &mut connection)
@@ -520,6 +828,7 @@ fn start_export(
     "group_by": group_by_key,
     "severities": severity_keys,
     "max_archive_mb": max_archive_mb,
+    "documents": documents,
   });
   jobs::spawn_job(
     pool.clone(),
@@ -532,6 +841,7 @@ fn start_export(
         corpus,
         service,
         severities,
+        documents,
         group_by,
         max_archive_mb,
         out,
@@ -552,6 +862,7 @@ fn run_export(
   corpus: Corpus,
   service: Service,
   severities: Vec<TaskStatus>,
+  documents: DocumentFilter,
   group_by: GroupBy,
   max_archive_mb: Option<u64>,
   out: PathBuf,
@@ -563,9 +874,11 @@ fn run_export(
     &corpus,
     &service,
     &severities,
+    &documents,
     group_by,
     max_archive_mb,
     &out,
+    &*storage(),
     |line| progress.step(0, None, line),
   )?;
   let total = outcome.total_entries as i32;
@@ -573,24 +886,108 @@ fn run_export(
   serde_json::to_value(&outcome).map_err(|error| error.to_string())
 }
 
+/// Request body for checking a corpus/service's result archives ([`fsck_corpus_service`]). Mirrors
+/// the `cortex fsck` CLI flags; an empty body is a dry run.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct FsckRequest {
+  /// Reset the tasks whose archive is missing or damaged to TODO.
+  #[serde(default)]
+  pub reset_damaged: bool,
+  /// Delete the orphan archives (of deleted tasks or sandboxes).
+  #[serde(default)]
+  pub remove_orphans: bool,
+}
+
+/// Checks a corpus/service's result archives against the task store as an in-process **background
+/// job**: missing archives, size/checksum mismatches against the record the sink kept at commit,
+/// zips without a `cortex.log`, and orphan archives. Returns `202 Accepted` + the job handle; the
+/// job's result is the findings. A dry run unless the body asks to `reset_damaged` and/or
+/// `remove_orphans` (repairs are audited). The agent twin of `cortex fsck`. **Token-gated** via the
+/// [`Actor`] guard; `401` without a valid token, `404` if the corpus or service is unknown.
+#[openapi(tag = "Corpora")]
+#[post(
+  "/api/corpora/<corpus>/services/<service>/fsck",
+  format = "json",
+  data = "<request>"
+)]
+pub fn fsck_corpus_service(
+  corpus: &str,
+  service: &str,
+  request: Json<FsckRequest>,
+  actor: Actor,
+  pool: &State<DbPool>,
+  database_url: &State<DatabaseUrl>,
+) -> Result<(Status, Json<JobDto>), Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let corpus =
+    Corpus::find_by_name(&corpus.to_lowercase(), &mut connection).map_err(|_| Status::NotFound)?;
+  let service = Service::find_by_name(&service.to_lowercase(), &mut connection)
+    .map_err(|_| Status::NotFound)?;
+  drop(connection);
+
+  let request = request.into_inner();
+  let options = FsckOptions {
+    reset_damaged: request.reset_damaged,
+    remove_orphans: request.remove_orphans,
+  };
+  let params = serde_json::json!({
+    "corpus": corpus.name,
+    "service": service.name,
+    "reset_damaged": options.reset_damaged,
+    "remove_orphans": options.remove_orphans,
+  });
+  let database_url = database_url.0.clone();
+  let owner = actor.owner.clone();
+  let job_uuid = jobs::spawn_job(
+    pool.inner().clone(),
+    "fsck",
+    &actor.owner,
+    params,
+    move |progress| {
+      let mut backend = from_address(&database_url);
+      let report = fsck_archives(
+        &mut backend.connection,
+        &corpus,
+        &service,
+        &*storage(),
+        options,
+        &owner,
+        |line| progress.step(0, None, line),
+      )?;
+      let checked = report.checked as i32;
+      progress.step(checked, Some(checked), "fsck complete");
+      serde_json::to_value(&report).map_err(|error| error.to_string())
+    },
+  )
+  .map_err(|_| Status::InternalServerError)?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let job = jobs::find_job(&mut connection, job_uuid).ok_or(Status::InternalServerError)?;
+  Ok((Status::Accepted, Json(JobDto::from(job))))
+}
+
 /// Fields of the human "Export dataset" form (the web twin of [`export_dataset`]).
 #[derive(FromForm)]
 pub struct ExportForm {
   /// Server-side output directory for the archives.
   pub out: String,
-  /// Bucketing: `month` or `severity`.
+  /// Bucketing: `month`, `severity` or `category`.
   pub group_by: String,
   /// The checked severity keys (the multi-value checkbox group; empty = none selected).
   pub severities: Vec<String>,
   /// Optional per-archive size cap in MB (blank = no limit). A string so a blank field parses to
   /// "no limit" instead of a form error.
   pub max_archive_mb: Option<String>,
+  /// Optional arXiv submission month, `yymm` (blank = every month).
+  pub yymm: Option<String>,
+  /// Optional arXiv primary category or archive (blank = every category).
+  pub primary_category: Option<String>,
 }
 
 /// Renders the "Export dataset" form for a `(corpus, service)`. Shared by the GET screen and the
 /// POST error path, so a failed submit re-renders with a friendly `error` and every typed value
 /// preserved (the output path, grouping, and which severities were checked) instead of a bare error
 /// page. Admin-only page (`is_admin`).
+#[allow(clippy::too_many_arguments)] // one per form field, so a re-render keeps every typed value
 fn render_export_form(
   corpus: &str,
   service: &str,
@@ -599,6 +996,7 @@ fn render_export_form(
   group_by: &str,
   selected: &[String],
   max_archive_mb: &str,
+  documents: &DocumentFilter,
 ) -> Template {
   let mut global = HashMap::new();
   global.insert("title".to_string(), "Export dataset".to_string());
@@ -611,6 +1009,14 @@ fn render_export_form(
   global.insert("out".to_string(), out.to_string());
   global.insert("group_by".to_string(), group_by.to_string());
   global.insert("max_archive_mb".to_string(), max_archive_mb.to_string());
+  global.insert(
+    "yymm".to_string(),
+    documents.yymm.clone().unwrap_or_default(),
+  );
+  global.insert(
+    "primary_category".to_string(),
+    documents.primary_category.clone().unwrap_or_default(),
+  );
   if let Some(message) = error {
     global.insert("error".to_string(), message.to_string());
   }
@@ -652,6 +1058,7 @@ pub fn export_dataset_page(
     "month",
     &default_export_severities(),
     "",
+    &DocumentFilter::default(),
   ))
 }
 
@@ -687,6 +1094,11 @@ pub fn export_dataset_human(
       .map(str::trim)
       .filter(|s| !s.is_empty())
       .and_then(|s| s.parse::<u64>().ok()),
+    documents: DocumentFilter {
+      yymm: form.yymm.clone(),
+      primary_category: form.primary_category.clone(),
+      ..DocumentFilter::default()
+    },
   };
   match start_export(
     pool,
@@ -700,7 +1112,9 @@ pub fn export_dataset_human(
     Err(status) => {
       let message = match status.code {
         404 => format!("Corpus “{corpus}” / service “{service}” not found — check the names."),
-        422 => "Pick a grouping and at least one valid severity.".to_string(),
+        422 => "Pick a grouping, at least one valid severity, and a valid month (yymm) / \
+                category if any."
+          .to_string(),
         503 => "The database is temporarily unavailable — please try again.".to_string(),
         _ => "Could not start the export — check the values and try again.".to_string(),
       };
@@ -712,6 +1126,11 @@ pub fn export_dataset_human(
         &form.group_by,
         &form.severities,
         form.max_archive_mb.as_deref().unwrap_or(""),
+        &DocumentFilter {
+          yymm: form.yymm,
+          primary_category: form.primary_category,
+          ..DocumentFilter::default()
+        },
       ))
     },
   }
@@ -740,6 +1159,10 @@ pub struct SandboxRequest {
   /// Optional `what` narrowing within the category (needs `category`).
   #[serde(default)]
   pub what: Option<String>,
+  /// Optional full-text message query (a `GET /api/search/messages` `q`) — matches tasks that
+  /// emitted a matching message, of `message_severity` if given, else of any severity.
+  #[serde(default)]
+  pub text: Option<String>,
   /// Optional substring the parent `entry` path must contain (`entry LIKE '%…%'`, e.g. `/2506/`
   /// for one arXiv month). Empty/absent = no narrowing.
   #[serde(default)]
@@ -748,6 +1171,10 @@ pub struct SandboxRequest {
   /// or non-positive = no cap.
   #[serde(default)]
   pub max_entries: Option<i64>,
+  /// Optional narrowing by the entry's document facts (`yymm`, `primary_category`,
+  /// `min_source_bytes`/`max_source_bytes`, `min_files`/`max_files`), given flat.
+  #[serde(flatten)]
+  pub document: DocumentFilter,
 }
 
 impl From<&SandboxRequest> for SandboxSelection {
@@ -758,9 +1185,11 @@ impl From<&SandboxRequest> for SandboxSelection {
       message_severity: request.message_severity.clone(),
       category: request.category.clone(),
       what: request.what.clone(),
+      text: request.text.clone(),
       entry: request.entry.clone(),
       max_entries: request.max_entries,
       severity: None,
+      document: request.document.clone().normalized(),
     }
   }
 }
@@ -843,10 +1272,16 @@ pub struct SandboxForm {
   pub category: Option<String>,
   /// Optional `what` narrowing (empty string = none).
   pub what: Option<String>,
+  /// Optional message search query (empty string = none).
+  pub text: Option<String>,
   /// Optional `entry` substring filter (empty string = none).
   pub entry: Option<String>,
   /// Optional hard cap on captured entries (empty/zero = none).
   pub max_entries: Option<i64>,
+  /// Optional arXiv submission month, `yymm` (empty string = none).
+  pub yymm: Option<String>,
+  /// Optional arXiv primary category or archive (empty string = none).
+  pub primary_category: Option<String>,
 }
 
 /// The human twin of [`create_sandbox_corpus`]: the corpus page's "Create a sandbox" form. **Gated
@@ -873,9 +1308,16 @@ pub fn create_sandbox_human(
     message_severity: blank_to_none(form.message_severity),
     category: blank_to_none(form.category),
     what: blank_to_none(form.what),
+    text: blank_to_none(form.text),
     entry: blank_to_none(form.entry),
     // A non-positive cap is treated as "no cap" (create_sandbox ignores it); keep the raw value.
     max_entries: form.max_entries.filter(|n| *n > 0),
+    document: DocumentFilter {
+      yymm: form.yymm,
+      primary_category: form.primary_category,
+      ..DocumentFilter::default()
+    }
+    .normalized(),
   };
   // Pre-validate the Model C filter for a friendly inline error — a bad combo (a category without a
   // message severity, no dimension at all, …) would otherwise be a bare 422 from start_sandbox. The
@@ -918,9 +1360,12 @@ fn run_sandbox(
   Ok(serde_json::json!({ "sandbox": outcome.sandbox.name, "entries": outcome.entry_count }))
 }
 
-/// Extends an existing corpus with newly-arrived entries; starts an in-process job and returns
-/// `202 Accepted` + the job handle. **Token-gated** via the [`Actor`] guard; `401` without a valid
-/// token, `404` if the corpus is unknown.
+/// Extends an existing corpus with newly-arrived entries, and re-checks the known ones against
+/// their recorded source hash: an entry replaced in place has its conversions reset to TODO, one
+/// whose source is gone is flagged as vanished. Starts an in-process job and returns `202
+/// Accepted` + the job handle; the job's result reports the `new`, `changed` and `vanished`
+/// entries. **Token-gated** via the [`Actor`] guard; `401` without a valid token, `404` if the
+/// corpus is unknown.
 #[rocket_okapi::openapi(tag = "Corpora")]
 #[post("/api/corpora/<name>/extend")]
 pub fn extend_corpus(
@@ -986,7 +1431,8 @@ pub fn extend_corpus_human(
 }
 
 /// The body of a `corpus_extend` job: import newly-arrived entries and propagate them to the real
-/// (non-init/import) services, returning the resulting import-task count.
+/// (non-init/import) services, returning the resulting import-task count together with the extend
+/// report (new, changed and vanished entries — see `importer::changes::ExtendReport`).
 fn run_extend(database_url: &str, corpus: Corpus, progress: &JobProgress) -> Result<Value, String> {
   let corpus_id = corpus.id;
   let mut importer = Importer {
@@ -996,22 +1442,35 @@ fn run_extend(database_url: &str, corpus: Corpus, progress: &JobProgress) -> Res
     active_prefixes: HashSet::new(),
   };
   progress.step(0, None, "extending corpus");
-  importer
+  let report = importer
     .extend_corpus()
     .map_err(|error| error.to_string())?;
   let services = importer
     .corpus
     .select_services(&mut importer.backend.connection)
     .unwrap_or_default();
-  for service in services.iter().filter(|service| service.id > 2) {
+  for service in services
+    .iter()
+    .filter(|service| service.id > IMPORT_SERVICE_ID)
+  {
     importer
       .backend
       .extend_service(service, &importer.corpus)
       .map_err(|error| error.to_string())?;
+    wake_dispatcher(
+      &mut importer.backend.connection,
+      ControlSignal::scoped(ControlKind::Activate, corpus_id, service.id),
+    );
   }
-  let imported = count_service_tasks(&mut importer.backend.connection, corpus_id, 2);
+  let imported = count_service_tasks(
+    &mut importer.backend.connection,
+    corpus_id,
+    IMPORT_SERVICE_ID,
+  );
   progress.step(imported, Some(imported), "extend complete");
-  Ok(serde_json::json!({ "import_tasks": imported }))
+  let mut result = serde_json::to_value(&report).map_err(|error| error.to_string())?;
+  result["import_tasks"] = serde_json::json!(imported);
+  Ok(result)
 }
 
 /// Activates a registered `service` on a `corpus`: creates a TODO task per imported document so the
@@ -1020,17 +1479,20 @@ fn run_extend(database_url: &str, corpus: Corpus, progress: &JobProgress) -> Res
 /// the pending/done status. `401` without a valid token, `404` on an unknown corpus/service, `409`
 /// if the service is **already registered** on the corpus (registration is idempotent-neutral — no
 /// re-activation wipes existing results; use *extend*/*rerun* instead), `202` with the job handle
-/// on success.
+/// on success, `400` on a `weight` below 1. The optional `?weight=` (default 1) is the activation's
+/// fair-share weight: its relative slice of the service's leases while other corpora are converting
+/// with it too.
 #[rocket_okapi::openapi(tag = "Corpora")]
-#[post("/api/corpora/<corpus>/services/<service>")]
+#[post("/api/corpora/<corpus>/services/<service>?<weight>")]
 pub fn activate_service(
   corpus: &str,
   service: &str,
+  weight: Option<i32>,
   actor: Actor,
   pool: &State<DbPool>,
   database_url: &State<DatabaseUrl>,
 ) -> Result<(Status, Json<JobDto>), Status> {
-  let job_uuid = start_activate(pool, &database_url.0, &actor.owner, corpus, service)?;
+  let job_uuid = start_activate(pool, &database_url.0, &actor.owner, corpus, service, weight)?;
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
   let job = jobs::find_job(&mut connection, job_uuid).ok_or(Status::InternalServerError)?;
   Ok((Status::Accepted, Json(JobDto::from(job))))
@@ -1039,14 +1501,19 @@ pub fn activate_service(
 /// Resolves the `(corpus, service)`, spawns the activation job (attributed to `actor`), and returns
 /// the job uuid. `404` on an unknown corpus/service. Shared by the agent endpoint, the corpus
 /// screen's human form, and the "Add a service" screen (which activates a freshly-defined service
-/// on each checked corpus — see [`crate::frontend::services`]).
+/// on each checked corpus — see [`crate::frontend::services`]). A `weight` (fair-share, see
+/// [`activate_service`]) is applied once the activation has registered; `None` keeps the default.
 pub(crate) fn start_activate(
   pool: &DbPool,
   database_url: &str,
   actor: &str,
   corpus: &str,
   service: &str,
+  weight: Option<i32>,
 ) -> Result<Uuid, Status> {
+  if let Some(weight) = weight {
+    ActivationWeight::validate(weight).map_err(|_| Status::BadRequest)?;
+  }
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
   let corpus_record =
     Corpus::find_by_name(corpus, &mut connection).map_err(|_| Status::NotFound)?;
@@ -1063,7 +1530,7 @@ pub(crate) fn start_activate(
   drop(connection);
   let database_url = database_url.to_string();
   let owner = actor.to_string();
-  let params = serde_json::json!({ "corpus": corpus, "service": service });
+  let params = serde_json::json!({ "corpus": corpus, "service": service, "weight": weight });
   jobs::spawn_job(
     pool.clone(),
     "service_activate",
@@ -1075,6 +1542,7 @@ pub(crate) fn start_activate(
         corpus_record,
         service_record,
         owner,
+        weight,
         progress,
       )
     },
@@ -1087,6 +1555,8 @@ pub(crate) fn start_activate(
 pub struct ActivateForm {
   /// The registered service to activate on this corpus.
   pub service: String,
+  /// Optional fair-share weight (blank = the default 1).
+  pub weight: Option<i32>,
 }
 
 /// The human twin of [`activate_service`]: the corpus screen's "Activate a service" form. **Gated
@@ -1103,17 +1573,73 @@ pub fn activate_service_human(
   let Some(session) = session else {
     return Ok(Redirect::to("/admin/login"));
   };
-  let uuid = start_activate(pool, &database_url.0, &session.owner, corpus, &form.service)?;
+  let uuid = start_activate(
+    pool,
+    &database_url.0,
+    &session.owner,
+    corpus,
+    &form.service,
+    form.weight,
+  )?;
   Ok(Redirect::to(format!("/jobs/{uuid}")))
 }
 
+/// Request body for retuning an activation's fair-share weight.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WeightUpdateRequest {
+  /// The new weight (must be at least 1): the activation's relative slice of the service's leases
+  /// while other corpora are converting with it too.
+  pub weight: i32,
+}
+
+/// Retunes the fair-share weight of `service`'s activation on `corpus` — e.g. raising a sandbox
+/// campaign's share while a full-corpus run of the same service is in progress. **Token-gated** via
+/// the [`Actor`] guard (`401` without a valid token); `400` on a weight below 1, `404` if the
+/// corpus or service is unknown or the service is not activated on the corpus, `204` on success.
+/// Takes effect on the dispatcher's next lease batch (weights are read per batch, not cached).
+#[rocket_okapi::openapi(tag = "Corpora")]
+#[put(
+  "/api/corpora/<corpus>/services/<service>/weight",
+  format = "json",
+  data = "<request>"
+)]
+pub fn set_activation_weight(
+  corpus: &str,
+  service: &str,
+  request: Json<WeightUpdateRequest>,
+  _actor: Actor,
+  pool: &State<DbPool>,
+) -> Status {
+  let Ok(weight) = ActivationWeight::validate(request.into_inner().weight) else {
+    return Status::BadRequest;
+  };
+  let Ok(mut connection) = pool.get() else {
+    return Status::ServiceUnavailable;
+  };
+  let (Ok(corpus_record), Ok(service_record)) = (
+    Corpus::find_by_name(corpus, &mut connection),
+    Service::find_by_name(service, &mut connection),
+  ) else {
+    return Status::NotFound;
+  };
+  if !ActivationWeight::is_activated(&mut connection, corpus_record.id, service_record.id) {
+    return Status::NotFound;
+  }
+  match ActivationWeight::set(&mut connection, corpus_record.id, service_record.id, weight) {
+    Ok(_) => Status::NoContent,
+    Err(_) => Status::InternalServerError,
+  }
+}
+
 /// The body of a `service_activate` job: register `service` on `corpus` (creating a TODO task per
-/// imported document), attributing the new run to `owner`, and return the task count created.
+/// imported document), attributing the new run to `owner`, apply its fair-share `weight` (if
+/// given), and return the task count created.
 fn run_activate(
   database_url: &str,
   corpus: Corpus,
   service: Service,
   owner: String,
+  weight: Option<i32>,
   progress: &JobProgress,
 ) -> Result<Value, String> {
   let (corpus_id, service_id) = (corpus.id, service.id);
@@ -1133,6 +1659,16 @@ fn run_activate(
       format!("Activated service {service_name} on {corpus_name}"),
     )
     .map_err(|error| error.to_string())?;
+  if let Some(weight) = weight {
+    backend
+      .set_activation_weight(&corpus, &service, weight)
+      .map_err(|error| error.to_string())?;
+  }
+  // Wake the dispatcher so the new pair's TODO tasks are leased now, not on its next refetch.
+  wake_dispatcher(
+    &mut backend.connection,
+    ControlSignal::scoped(ControlKind::Activate, corpus_id, service_id),
+  );
   let activated = count_service_tasks(&mut backend.connection, corpus_id, service_id);
   progress.step(
     activated,
@@ -1535,17 +2071,28 @@ pub fn corpus_page(
 /// page, linked from the admin dashboard. **Signed-in admins only** (anonymous → sign-in); the form
 /// posts to the existing `POST /corpus/import`.
 #[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
-/// Renders the "Add a corpus" form. Shared by the GET page and the POST error path, so a failed
-/// submit (e.g. a name collision) re-renders the form with a friendly `error` and the typed values
-/// preserved instead of a bare error page. The `corpus_*` keys carry the form values (the page meta
-/// `description` is separate). Admin-only page, so `is_admin` is set.
-fn render_corpora_new(
-  error: Option<&str>,
-  name: &str,
-  path: &str,
-  description: &str,
-  complex: bool,
-) -> Template {
+/// Renders the "Add a corpus" form. Shared by the GET page, the POST error path and the preview, so
+/// a failed submit (e.g. a name collision) re-renders the form with a friendly `error` and the
+/// typed values of `form` preserved instead of a bare error page. The `corpus_*` keys carry the
+/// form values (the page meta `description` is separate). Admin-only page, so `is_admin` is set.
+/// The unpacker choices are the `entries` (`key`, `description`, `selected`); `preview` is the
+/// serialized [`ImportPreview`](preview::ImportPreview) of the values, when the admin asked for
+/// one and it is ready.
+fn render_corpora_new(form: &ImportForm, error: Option<&str>, preview: Option<&Value>) -> Template {
+  Template::render("corpora-new", corpora_new_context(form, error, preview))
+}
+
+/// The "Add a corpus" form while the preview job `uuid` still runs: the values kept, a note linking
+/// the job, and a self-refresh (the layout's `report_computing` meta refresh) until it is done.
+fn render_corpora_pending(form: &ImportForm, uuid: &str) -> Template {
+  let mut context = corpora_new_context(form, None, None);
+  context["global"]["report_computing"] = Value::from("true");
+  context["global"]["preview_job"] = Value::from(uuid);
+  Template::render("corpora-new", context)
+}
+
+/// The template context of the "Add a corpus" form (see [`render_corpora_new`]).
+fn corpora_new_context(form: &ImportForm, error: Option<&str>, preview: Option<&Value>) -> Value {
   let mut global = HashMap::new();
   global.insert("title".to_string(), "Add a corpus".to_string());
   global.insert(
@@ -1555,18 +2102,31 @@ fn render_corpora_new(
   if let Some(message) = error {
     global.insert("error".to_string(), message.to_string());
   }
-  global.insert("name".to_string(), name.to_string());
-  global.insert("path".to_string(), path.to_string());
-  global.insert("corpus_description".to_string(), description.to_string());
-  global.insert("complex".to_string(), complex.to_string());
-  Template::render(
-    "corpora-new",
-    TemplateContext {
-      global,
-      is_admin: true,
-      ..TemplateContext::default()
-    },
-  )
+  global.insert("name".to_string(), form.name.clone());
+  global.insert("path".to_string(), form.path.clone());
+  global.insert(
+    "corpus_description".to_string(),
+    form.description.clone().unwrap_or_default(),
+  );
+  global.insert(
+    "manifest".to_string(),
+    form.manifest.clone().unwrap_or_default(),
+  );
+  global.insert("complex".to_string(), form.complex.to_string());
+  let entries: Vec<HashMap<String, String>> = unpackers::all()
+    .iter()
+    .map(|choice| {
+      HashMap::from([
+        ("key".to_string(), choice.key().to_string()),
+        ("description".to_string(), choice.description().to_string()),
+        (
+          "selected".to_string(),
+          (choice.key() == form.unpacker_key()).to_string(),
+        ),
+      ])
+    })
+    .collect();
+  serde_json::json!({ "global": global, "entries": entries, "preview": preview, "is_admin": true })
 }
 
 /// The "Add a corpus" form (`GET /corpora/new`). Signed-in admins only (anonymous → sign-in).
@@ -1577,7 +2137,7 @@ pub fn new_corpus_page(
   return_to: ReturnTo,
 ) -> Result<Template, AdminReject> {
   require_admin_to(session, &return_to)?;
-  Ok(render_corpora_new(None, "", "", "", false))
+  Ok(render_corpora_new(&ImportForm::default(), None, None))
 }
 
 /// The route set for the corpus-management capability (API + human screens).
@@ -1587,6 +2147,8 @@ pub fn routes() -> Vec<Route> {
   // are in this plain route group.
   routes![
     import_corpus_human,
+    preview_corpus_import_human,
+    import_preview_page,
     extend_corpus_human,
     delete_corpus_human,
     activate_service_human,
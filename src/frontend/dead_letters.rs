@@ -0,0 +1,297 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The dispatch **dead-letter queue** screen and agent API (`models::DeadLetter`): tasks the
+//! dispatcher gave up on after exhausting their lease retries, with the workers they were leased to
+//! and when. Reported `Fatal` like a converter crash, these are the "infrastructure lost it" cases;
+//! an operator triages each one — **requeue** it for another attempt, or **quarantine** it for
+//! good. Uniform authz — any signed-in admin (or token-holding agent) may list and resolve; both
+//! resolutions are audited by the audit fairing.
+
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use rocket_dyn_templates::{Template, context};
+use serde::Serialize;
+
+use crate::backend::DbPool;
+use crate::frontend::actor::{Actor, AdminReject, AdminSession, ReturnTo, require_admin_to};
+use crate::frontend::helpers::iso_utc;
+use crate::helpers::TaskStatus;
+use crate::models::{DeadLetter, DeadLetterListing, DeadLetterResolution};
+
+/// Entries per dead-letter page (most-recent first, `?page=` walks back).
+const DEAD_LETTER_PAGE_SIZE: i64 = 100;
+
+/// One lease of a dead-lettered task.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct LeaseDto {
+  /// The worker's ZMQ identity.
+  pub worker: String,
+  /// When the task was leased to it, as an RFC 3339 UTC timestamp.
+  pub leased_at: String,
+}
+
+/// A dispatch dead-letter as exposed over the API/UI.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DeadLetterDto {
+  /// The entry id (the handle for requeue / quarantine).
+  pub id: i64,
+  /// The task given up on.
+  pub task_id: i64,
+  /// The task's corpus.
+  pub corpus: String,
+  /// The task's service.
+  pub service: String,
+  /// The task's entry path.
+  pub entry: String,
+  /// The task's current status key (`fatal` until resolved; `todo` once requeued, `blocked` while
+  /// quarantined — or whatever a later run made of it).
+  pub task_status: String,
+  /// The retries the task was granted before it was given up on.
+  pub retries: i32,
+  /// Every lease of the task, oldest first.
+  pub leases: Vec<LeaseDto>,
+  /// When the dispatcher gave up on the task, as an RFC 3339 UTC timestamp.
+  pub dead_at: String,
+  /// The triage state: `open`, `requeued` or `quarantined`.
+  pub state: String,
+  /// When the entry was resolved, as an RFC 3339 UTC timestamp (`null` while open).
+  pub resolved_at: Option<String>,
+  /// Who resolved the entry (`null` while open).
+  pub resolved_by: Option<String>,
+}
+
+impl From<DeadLetterListing> for DeadLetterDto {
+  fn from(listing: DeadLetterListing) -> DeadLetterDto {
+    let DeadLetterListing {
+      letter,
+      corpus,
+      service,
+      entry,
+      status,
+    } = listing;
+    DeadLetterDto {
+      id: letter.id,
+      task_id: letter.task_id,
+      corpus,
+      service,
+      entry,
+      task_status: TaskStatus::from_raw(status).to_key(),
+      retries: letter.retries,
+      leases: letter
+        .workers
+        .into_iter()
+        .zip(letter.leased_at)
+        .map(|(worker, leased_at)| LeaseDto {
+          worker,
+          leased_at: iso_utc(leased_at),
+        })
+        .collect(),
+      dead_at: iso_utc(letter.dead_at),
+      state: letter.state,
+      resolved_at: letter.resolved_at.map(iso_utc),
+      resolved_by: letter.resolved_by,
+    }
+  }
+}
+
+/// One page of dead-letters — the shared shape for the agent endpoint and the human screen.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DeadLetterPage {
+  /// The entries for this page, most-recent first (at most [`DEAD_LETTER_PAGE_SIZE`]).
+  pub entries: Vec<DeadLetterDto>,
+  /// The 0-based page index this response covers.
+  pub page: i64,
+  /// Entries per page (the cap).
+  pub page_size: i64,
+  /// Whether an older page exists.
+  pub has_next: bool,
+}
+
+/// Loads one page of dead-letters (optionally one `state`) as the shared [`DeadLetterPage`]; one
+/// extra row reports `has_next` without a COUNT. An unknown `state` is `400`.
+fn load_dead_letters(
+  pool: &DbPool,
+  state: Option<&str>,
+  page: i64,
+) -> Result<DeadLetterPage, Status> {
+  if !matches!(state, None | Some("open" | "requeued" | "quarantined")) {
+    return Err(Status::BadRequest);
+  }
+  let page = page.max(0);
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let mut entries = DeadLetter::list(
+    &mut connection,
+    state,
+    DEAD_LETTER_PAGE_SIZE + 1,
+    page * DEAD_LETTER_PAGE_SIZE,
+  )
+  .map_err(|_| Status::InternalServerError)?;
+  let has_next = entries.len() as i64 > DEAD_LETTER_PAGE_SIZE;
+  entries.truncate(DEAD_LETTER_PAGE_SIZE as usize);
+  Ok(DeadLetterPage {
+    entries: entries.into_iter().map(DeadLetterDto::from).collect(),
+    page,
+    page_size: DEAD_LETTER_PAGE_SIZE,
+    has_next,
+  })
+}
+
+/// Requeues or quarantines dead-letter `id` as `actor` — the shared core of the agent and human
+/// writes. `404` for an unknown id, `409` if the entry is not in a state the action applies to, or
+/// its task has moved on since (e.g. rerun).
+fn resolve(
+  pool: &DbPool,
+  id: i64,
+  resolution: DeadLetterResolution,
+  actor: &str,
+) -> Result<DeadLetter, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  crate::backend::resolve_dead_letter(&mut connection, id, resolution, actor).map_err(|error| {
+    match error {
+      diesel::result::Error::NotFound => Status::NotFound,
+      diesel::result::Error::QueryBuilderError(_) => Status::Conflict,
+      _ => Status::InternalServerError,
+    }
+  })
+}
+
+/// The dispatch dead-letter queue (agent twin of the `/admin/dead-letters` screen): tasks given up
+/// on after exhausting their lease retries, with their lease history, most-recent first,
+/// optionally only one `state` (`open` | `requeued` | `quarantined`), **paginated** by `page`.
+/// **Token-gated**. `400` for an unknown state, `503` if the pool is exhausted.
+#[rocket_okapi::openapi(tag = "Management")]
+#[get("/api/dead-letters?<state>&<page>")]
+pub fn api_dead_letters(
+  state: Option<String>,
+  page: Option<i64>,
+  _caller: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<DeadLetterPage>, Status> {
+  Ok(Json(load_dead_letters(
+    pool,
+    state.as_deref(),
+    page.unwrap_or(0),
+  )?))
+}
+
+/// Acknowledgement of a dead-letter resolution.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DeadLetterAckDto {
+  /// The entry id.
+  pub id: i64,
+  /// The task it names.
+  pub task_id: i64,
+  /// The entry's new state.
+  pub state: String,
+  /// The actor who resolved it (audit identity).
+  pub actor: String,
+}
+
+/// Resolves dead-letter `id` (agent twin of the screen's buttons): `action` is `requeue` (reset the
+/// task to TODO for another attempt — also releases a quarantined entry) or `quarantine` (park it
+/// permanently: never leased, left out of whole-corpus reruns). **Token-gated** and audited. `400`
+/// for an unknown action, `404` for an unknown id, `409` if the entry was already resolved or its
+/// task has moved on since.
+#[rocket_okapi::openapi(tag = "Management")]
+#[post("/api/dead-letters/<id>/<action>")]
+pub fn api_resolve_dead_letter(
+  id: i64,
+  action: &str,
+  actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<DeadLetterAckDto>, Status> {
+  let resolution = DeadLetterResolution::from_key(action).ok_or(Status::BadRequest)?;
+  let letter = resolve(pool, id, resolution, &actor.owner)?;
+  Ok(Json(DeadLetterAckDto {
+    id: letter.id,
+    task_id: letter.task_id,
+    state: letter.state,
+    actor: actor.owner,
+  }))
+}
+
+/// The dead-letter screen (`GET /admin/dead-letters`): the queue, by default only the `open`
+/// entries (`?state=` picks another, `?state=all` everything), with requeue / quarantine buttons.
+/// Signed-in admins only (unauthenticated → sign-in page, returning here).
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[get("/admin/dead-letters?<state>&<page>")]
+pub fn dead_letters_page(
+  state: Option<String>,
+  page: Option<i64>,
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Template, AdminReject> {
+  let session = require_admin_to(session, &return_to)?;
+  let state = state.unwrap_or_else(|| "open".to_string());
+  let state_filter = Some(state.as_str()).filter(|state| *state != "all");
+  // Best-effort, like the other admin screens: a db hiccup renders an empty table, never a 500.
+  let letters =
+    load_dead_letters(pool, state_filter, page.unwrap_or(0)).unwrap_or(DeadLetterPage {
+      entries: Vec::new(),
+      page: 0,
+      page_size: DEAD_LETTER_PAGE_SIZE,
+      has_next: false,
+    });
+  let counts = pool
+    .get()
+    .ok()
+    .and_then(|mut connection| DeadLetter::state_counts(&mut connection).ok())
+    .unwrap_or_default();
+  let global = serde_json::json!({
+    "title": "Dead letters",
+    "description": "Tasks the dispatcher gave up on after exhausting their lease retries",
+  });
+  Ok(Template::render(
+    "dead-letters",
+    context! {
+      global,
+      owner: session.owner,
+      rows: letters.entries,
+      counts,
+      state,
+      page: letters.page,
+      has_next: letters.has_next,
+      has_prev: letters.page > 0,
+      prev_page: (letters.page - 1).max(0),
+      next_page: letters.page + 1,
+    },
+  ))
+}
+
+/// Fields of the screen's per-entry resolve form.
+#[derive(FromForm)]
+pub struct ResolveDeadLetterForm {
+  /// `requeue` or `quarantine`.
+  pub action: String,
+}
+
+/// Resolves dead-letter `id` from the screen (`POST /admin/dead-letters/<id>`): the human twin of
+/// [`api_resolve_dead_letter`]. Redirects back to the screen. Signed-in admins only; an unknown
+/// action is `400`, an unknown id `404`, an already-resolved entry `409`.
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[post("/admin/dead-letters/<id>", data = "<form>")]
+pub fn resolve_dead_letter_human(
+  id: i64,
+  form: Form<ResolveDeadLetterForm>,
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Redirect, AdminReject> {
+  let session = require_admin_to(session, &return_to)?;
+  let resolution = DeadLetterResolution::from_key(&form.action).ok_or(Status::BadRequest)?;
+  resolve(pool, id, resolution, &session.owner)?;
+  Ok(Redirect::to("/admin/dead-letters"))
+}
+
+/// The human dead-letter screen + resolve form (the agent endpoints are mounted via
+/// `frontend::apidoc`).
+pub fn routes() -> Vec<Route> { routes![dead_letters_page, resolve_dead_letter_human] }
@@ -32,6 +32,8 @@ use crate::config::{AssetsConfig, CortexConfig, DispatcherConfig, JobsConfig, co
 use crate::frontend::actor::{
   Actor, AdminReject, AdminSession, ReturnTo, require_admin, require_admin_to,
 };
+use crate::frontend::helpers::iso_utc;
+use crate::models::{DispatcherInstance, InstanceStatus};
 
 /// Managed state: the path where the write path persists the configuration file.
 pub struct ConfigFile(pub PathBuf);
@@ -125,6 +127,52 @@ pub struct DispatcherHealth {
   pub source_port: usize,
   /// Sink (worker result) port.
   pub result_port: usize,
+  /// Every dispatcher instance sharing this task store — running, or stopped within the last day
+  /// — newest first.
+  pub instances: Vec<DispatcherInstanceHealth>,
+}
+
+/// One registered dispatcher instance (`models::DispatcherInstance`), with its liveness as judged
+/// by the database clock against `dispatcher.instance_timeout_seconds`.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DispatcherInstanceHealth {
+  /// The instance id (the tag on its leases).
+  pub id: i32,
+  /// The host it runs on.
+  pub host: String,
+  /// Its process id.
+  pub pid: i32,
+  /// Its ventilator port.
+  pub source_port: i32,
+  /// Its sink port.
+  pub result_port: i32,
+  /// When it registered, as an RFC 3339 UTC timestamp.
+  pub started_at: String,
+  /// When it stopped or was found dead (`null` while running).
+  pub stopped_at: Option<String>,
+  /// Seconds since its latest heartbeat.
+  pub heartbeat_age_seconds: i64,
+  /// Whether it is running and its heartbeat is fresh.
+  pub alive: bool,
+  /// Tasks it currently holds leased.
+  pub leases: i64,
+}
+
+impl From<InstanceStatus> for DispatcherInstanceHealth {
+  fn from(instance: InstanceStatus) -> DispatcherInstanceHealth {
+    DispatcherInstanceHealth {
+      id: instance.id,
+      host: instance.host,
+      pid: instance.pid,
+      source_port: instance.source_port,
+      result_port: instance.result_port,
+      started_at: iso_utc(instance.started_at),
+      stopped_at: instance.stopped_at.map(iso_utc),
+      heartbeat_age_seconds: instance.heartbeat_age_seconds,
+      alive: instance.alive,
+      leases: instance.leases,
+    }
+  }
 }
 
 /// A corpus whose configured source directory could not be read on disk (missing / unmounted /
@@ -221,6 +269,16 @@ impl HealthDto {
         self.dispatcher.source_port, self.dispatcher.result_port
       ));
     }
+    // A dead instance still holding leases means no live dispatcher has recovered them yet.
+    for instance in &self.dispatcher.instances {
+      if !instance.alive && instance.leases > 0 {
+        hints.push(format!(
+          "dispatcher instance {} ({}, pid {}) is gone but holds {} leased task(s) — they return to \
+           TODO as soon as any dispatcher runs; start one if none is running",
+          instance.id, instance.host, instance.pid, instance.leases
+        ));
+      }
+    }
     if !self.storage.unreadable.is_empty() {
       let names: Vec<&str> = self
         .storage
@@ -345,6 +403,11 @@ fn validate_config_bounds(c: &CortexConfig) -> Result<(), String> {
       "dispatcher.max_result_bytes must be >= 1 (a zero cap rejects every result)".to_string(),
     );
   }
+  if c.dispatcher.regression_jump_percent < 0 || c.dispatcher.regression_jump_min_tasks < 0 {
+    return Err(
+      "dispatcher.regression_jump_percent and regression_jump_min_tasks must be >= 0".to_string(),
+    );
+  }
   if c.jobs.stale_timeout_seconds < 1 {
     return Err(
       "jobs.stale_timeout_seconds must be >= 1 (a non-positive timeout reaps every job \
@@ -453,7 +516,7 @@ fn port_listening(port: usize) -> bool {
 /// same path requests use), samples pool utilization, and probes the co-located dispatcher's ports.
 /// Shared by the agent (`/api/health`) and human (`/health`) routes so both report identical state.
 fn health_report(pool: &DbPool) -> HealthDto {
-  let (reachable, migrations_current, corpora_paths) = match pool.get() {
+  let (reachable, migrations_current, corpora_paths, instances) = match pool.get() {
     Ok(mut connection) => {
       use crate::schema::corpora;
       let reachable = diesel::sql_query("SELECT 1")
@@ -466,9 +529,14 @@ fn health_report(pool: &DbPool) -> HealthDto {
         .select((corpora::name, corpora::path))
         .load(&mut connection)
         .unwrap_or_default();
-      (reachable, migrations_current, corpora_paths)
+      let instances = DispatcherInstance::listing(
+        &mut connection,
+        config().dispatcher.instance_timeout_seconds,
+      )
+      .unwrap_or_default();
+      (reachable, migrations_current, corpora_paths, instances)
     },
-    Err(_) => (false, false, Vec::new()),
+    Err(_) => (false, false, Vec::new(), Vec::new()),
   };
   // Stat each corpus source path (local shared storage; a plain existence check, no read_dir). A
   // corpus with an empty path has no configured location, so it is not a storage fault.
@@ -506,6 +574,10 @@ fn health_report(pool: &DbPool) -> HealthDto {
         reachable: port_listening(dispatcher.source_port) && port_listening(dispatcher.result_port),
         source_port: dispatcher.source_port,
         result_port: dispatcher.result_port,
+        instances: instances
+          .into_iter()
+          .map(DispatcherInstanceHealth::from)
+          .collect(),
       }
     },
     storage: StorageHealth {
@@ -769,6 +841,7 @@ mod tests {
         reachable: true,
         source_port: 51695,
         result_port: 51696,
+        instances: Vec::new(),
       },
       storage: StorageHealth {
         corpora_checked: 2,
@@ -825,6 +898,35 @@ mod tests {
     );
   }
 
+  #[test]
+  fn a_dead_dispatcher_instance_holding_leases_is_flagged() {
+    let mut health = healthy();
+    let instance = |id: i32, alive: bool, leases: i64| DispatcherInstanceHealth {
+      id,
+      host: "node1".to_string(),
+      pid: 4242,
+      source_port: 51695,
+      result_port: 51696,
+      started_at: "2026-10-17T08:00:00Z".to_string(),
+      stopped_at: None,
+      heartbeat_age_seconds: if alive { 3 } else { 900 },
+      alive,
+      leases,
+    };
+    health.dispatcher.instances = vec![instance(3, true, 800), instance(2, false, 0)];
+    assert!(
+      health.remediations().is_empty(),
+      "a live instance and a dead one without leases need no action"
+    );
+    health.dispatcher.instances.push(instance(1, false, 17));
+    let hints = health.remediations();
+    assert_eq!(hints.len(), 1);
+    assert!(
+      hints[0].contains("instance 1") && hints[0].contains("17 leased"),
+      "the dead instance and its stranded leases are named"
+    );
+  }
+
   #[test]
   fn dispatcher_and_storage_warnings_are_actionable() {
     let mut health = healthy();
@@ -11,7 +11,10 @@
 //! **current-state gauges** read on each scrape — connection-pool saturation, background-job
 //! backlog, active admin sessions, registered corpora/services, the dispatcher worker fleet's
 //! size + in-flight backlog, and the **pending-conversion backlog** (`cortex_tasks_todo`, the one
-//! full-table count — bounded ~tens-to-hundreds of ms even at arXiv scale).
+//! full-table count — bounded ~tens-to-hundreds of ms even at arXiv scale). Per-activation
+//! fair-share series (`cortex_fair_share_*{corpus,service}`) report each corpus's target vs
+//! achieved slice of its service's leases, read from the small `activation_weights` table the
+//! dispatcher stamps as it leases.
 //!
 //! It does **not** instrument the hot paths (no dispatcher changes) and does **not** run the
 //! `/healthz` ZMQ/filesystem probes (those are slow and that endpoint's job). Real-time
@@ -24,7 +27,9 @@ use rocket::{Route, State};
 
 use crate::backend::DbPool;
 use crate::frontend::actor::Actor;
-use crate::models::{Corpus, Service, Session, Task, WorkerMetadata};
+use crate::models::{
+  ActivationWeight, Corpus, FairShareDto, Service, Session, Task, WorkerMetadata,
+};
 
 /// Appends one Prometheus gauge (HELP + TYPE + value lines) to `out`.
 fn gauge(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
@@ -33,6 +38,23 @@ fn gauge(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display
   ));
 }
 
+/// Appends one labelled Prometheus metric family (HELP + TYPE once, then one sample per
+/// `(labels, value)`) to `out`. Label values are escaped per the exposition format.
+fn labelled(out: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, String)]) {
+  out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+  for (labels, value) in samples {
+    out.push_str(&format!("{name}{{{labels}}} {value}\n"));
+  }
+}
+
+/// Escapes a Prometheus label value (backslash, double quote, newline).
+fn label_value(value: &str) -> String {
+  value
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+}
+
 /// `GET /metrics` — Prometheus exposition of current-state gauges. **Token-gated** (the [`Actor`]
 /// guard; scrape with `?token=`). Pool gauges are always emitted (in-memory); DB-derived gauges are
 /// best-effort — on a pool/db hiccup they are omitted (and `cortex_db_reachable` is `0`) rather
@@ -150,6 +172,54 @@ pub fn metrics(_caller: Actor, pool: &State<DbPool>) -> (ContentType, String) {
         "Tasks awaiting conversion (status TODO, not yet dispatched) — the pending-work backlog.",
         Task::count_todo(&mut connection),
       );
+      // Fair-share leasing per `(corpus, service)` activation: the configured weight, the share it
+      // entitles the corpus to, the share of the service's recent leases achieved, and the lifetime
+      // lease counter (`rate()` over it gives a windowed share in PromQL). Small-table read.
+      if let Ok(shares) = ActivationWeight::shares(&mut connection) {
+        let series = |value: &dyn Fn(&FairShareDto) -> String| -> Vec<(String, String)> {
+          shares
+            .iter()
+            .map(|share| {
+              (
+                format!(
+                  "corpus=\"{}\",service=\"{}\"",
+                  label_value(&share.corpus),
+                  label_value(&share.service)
+                ),
+                value(share),
+              )
+            })
+            .collect()
+        };
+        labelled(
+          &mut out,
+          "cortex_fair_share_weight",
+          "gauge",
+          "Configured fair-share weight of a (corpus, service) activation.",
+          &series(&|share| share.weight.to_string()),
+        );
+        labelled(
+          &mut out,
+          "cortex_fair_share_target",
+          "gauge",
+          "Share of the service's leases the weights entitle this corpus to (0..1).",
+          &series(&|share| share.target_share.to_string()),
+        );
+        labelled(
+          &mut out,
+          "cortex_fair_share_achieved",
+          "gauge",
+          "Share of the service's recent leases this corpus actually received (0..1).",
+          &series(&|share| share.achieved_share.to_string()),
+        );
+        labelled(
+          &mut out,
+          "cortex_fair_share_leased_total",
+          "counter",
+          "Tasks leased for this (corpus, service) activation since it was created.",
+          &series(&|share| share.leased_total.to_string()),
+        );
+      }
     },
     Err(_) => gauge(
       &mut out,
@@ -7,6 +7,7 @@ pub mod catchers;
 pub mod concerns;
 pub mod corpora;
 pub mod cors;
+pub mod dead_letters;
 pub mod helpers;
 pub mod jobs;
 pub mod management;
@@ -16,8 +17,10 @@ pub mod render;
 pub mod reports;
 pub mod retention;
 pub mod runs;
+pub mod search;
 pub mod server;
 pub mod services;
 pub mod sessions;
 pub mod telemetry;
 pub mod webauthn;
+pub mod worker_keys;
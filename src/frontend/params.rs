@@ -26,12 +26,44 @@ pub struct ReportParams {
   pub page_size: Option<i64>,
 }
 
+#[derive(FromForm, Default)]
+/// Query parameters of the document-facet breakdown (`/breakdown/<corpus>/<service>`)
+pub struct BreakdownParams {
+  /// the document fact to group by: `month` (default), `category`, `size` or `files`
+  pub by: Option<String>,
+  /// only documents of this arXiv submission month (`yymm`)
+  pub yymm: Option<String>,
+  /// only documents of this arXiv primary category, or of any category of this archive
+  pub primary_category: Option<String>,
+  /// only sources of at least this many bytes
+  pub min_source_bytes: Option<i64>,
+  /// only sources of at most this many bytes
+  pub max_source_bytes: Option<i64>,
+  /// only sources of at least this many files
+  pub min_files: Option<i32>,
+  /// only sources of at most this many files
+  pub max_files: Option<i32>,
+  /// only tasks that emitted a message of this severity
+  pub severity: Option<String>,
+  /// narrows `severity` to messages of this category
+  pub category: Option<String>,
+  /// narrows `category` to messages of this `what`
+  pub what: Option<String>,
+}
+
 /// The JSON body of a human rerun request. Auth is the signed-in [`crate::frontend::actor::
-/// AdminSession`] cookie now — no token in the body — so only the free-text purpose remains.
+/// AdminSession`] cookie now — no token in the body — so only the free-text purpose (and an
+/// optional leasing priority) remains.
 #[derive(Serialize, Deserialize)]
 pub struct RerunRequestParams {
   /// a plain text description for the purpose of the rerun
   pub description: String,
+  /// optional leasing priority for the rerun tasks (higher is leased sooner; omitted = the ordinary backlog, 0)
+  #[serde(default)]
+  pub priority: Option<i32>,
+  /// optional message search query: rerun only the documents with a matching message
+  #[serde(default)]
+  pub text: Option<String>,
 }
 
 /// A backend-retrieved report used for filling in Tera-templated pages
@@ -57,6 +89,8 @@ pub struct TemplateContext {
   pub history: Option<Vec<RunMetadata>>,
   /// serialized data for easy plotting of rerun history
   pub history_serialized: Option<String>,
+  /// outstanding backlog per leasing priority (`priority`, `tasks`), highest priority first
+  pub backlog: Option<Vec<HashMap<String, String>>>,
   /// Whether the current viewer is a signed-in admin. Gates admin-only affordances in the shared
   /// templates (e.g. the corpus screen's "Corpus actions"); defaults to `false` (anonymous), so a
   /// page that doesn't set it shows nothing privileged.
@@ -13,6 +13,9 @@
 //! paginated. This is the structured counterpart to the HTML reports the legacy routes render;
 //! both reflect the same rollup, so humans and agents see the same numbers.
 
+use std::collections::HashMap;
+use std::path::Path;
+
 use rocket::http::Status;
 use rocket::response::Redirect;
 use rocket::serde::json::Json;
@@ -21,16 +24,25 @@ use rocket_dyn_templates::Template;
 use serde::Serialize;
 
 use crate::backend::{
-  DatabaseUrl, DbPool, MessageCounts, ReportSummaryRow, RerunOptions, TaskReportOptions,
-  category_rollup, category_total, from_address, progress_report, severity_total, task_messages,
-  task_report, what_rollup,
+  ArchiveDiff, ArchiveView, ControlKind, ControlSignal, DOCUMENT_MESSAGE_CAP, DatabaseUrl, DbPool,
+  DocumentFacet, DocumentFilter, FacetRow, MessageCounts, MessageScope, ReportSummaryRow,
+  RerunOptions, TaskReportOptions, category_rollup, category_total, diff_archives, facet_breakdown,
+  from_address, message_clusters, priority_backlog, progress_report, read_archive_view,
+  severity_total, task_messages, task_report, validate_message_text, wake_dispatcher, what_rollup,
 };
 use crate::frontend::actor::{Actor, AdminReject, AdminSession, require_admin};
 use crate::frontend::concerns::{LiveReportLimiter, serve_report};
-use crate::frontend::params::{MAX_REPORT_OFFSET, MAX_REPORT_PAGE_SIZE, ReportParams};
-use crate::helpers::TaskStatus;
+use crate::frontend::helpers::iso_utc;
+use crate::frontend::params::{
+  BreakdownParams, MAX_REPORT_OFFSET, MAX_REPORT_PAGE_SIZE, ReportParams,
+};
+use crate::helpers::fingerprint::fingerprint_hex;
+use crate::helpers::{TaskStatus, entry_document_name, result_archive_path};
 use crate::jobs;
-use crate::models::{Corpus, Service, Task};
+use crate::models::{
+  Corpus, Document, EntryMetadata, LogRecord, PoisonEntry, ResultArchiveVersion, Service, Task,
+};
+use crate::storage::storage;
 
 /// One status bucket in the service overview: a conversion-status key with its task count and its
 /// share of the valid-task total.
@@ -45,6 +57,15 @@ pub struct StatusCountDto {
   pub percent: f64,
 }
 
+/// The outstanding backlog at one leasing priority.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct PriorityBacklogDto {
+  /// Leasing priority (higher is dispatched sooner; `0` is the ordinary backlog).
+  pub priority: i32,
+  /// TODO + in-progress tasks at this priority.
+  pub tasks: i64,
+}
+
 /// The service-overview hub (the macro top rung of the report ladder): the `(corpus, service)`
 /// conversion-status breakdown an agent reads first, before drilling into a severity. The `status`
 /// keys double as the `<severity>` path segment for the category report
@@ -59,6 +80,9 @@ pub struct ServiceOverviewDto {
   pub total: i64,
   /// One bucket per conversion status, in canonical severity order.
   pub statuses: Vec<StatusCountDto>,
+  /// The outstanding (TODO + in-progress) backlog per leasing priority, highest first — the order
+  /// the dispatcher works through it. Empty once the run has drained.
+  pub backlog: Vec<PriorityBacklogDto>,
 }
 
 /// One report row: a category (in the category report) or a `what` class (in the drill-down), with
@@ -178,6 +202,219 @@ pub struct DocumentReportDto {
   pub result_url: String,
   /// Path to the human preview page.
   pub preview_url: String,
+  /// The entry's crash/timeout history across services and runs (`null` if no lease of it ever
+  /// ended without a usable result), including whether it is held.
+  pub poison: Option<PoisonEntryDto>,
+  /// The fields its import manifest listed for it besides the path (`null` if it was not imported
+  /// from a manifest).
+  pub metadata: Option<serde_json::Value>,
+  /// What the importer recorded about its source (`null` if the entry was never inspected).
+  pub document: Option<DocumentFactsDto>,
+  /// The previous result archives kept for it, newest first (empty unless the service keeps
+  /// versions).
+  pub versions: Vec<ResultVersionDto>,
+  /// Path to the side-by-side comparison of two of its results (`null` with no kept versions,
+  /// outside a sandbox).
+  pub compare_url: Option<String>,
+  /// Path to the diff of two of its results' output (`null` like `compare_url`); the agent twin is
+  /// the same path under `/api/corpus/…/document/<name>/diff`.
+  pub diff_url: Option<String>,
+}
+
+/// A previous result archive of a document, kept when its service keeps versions.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ResultVersionDto {
+  /// The version's id (the `left`/`right` of the comparison screen).
+  pub id: i64,
+  /// The historical run it was produced in (`null` if no run was open).
+  pub run_id: Option<i32>,
+  /// That run's description.
+  pub run: Option<String>,
+  /// When it was committed as the then-current result, as an RFC 3339 UTC timestamp.
+  pub written_at: String,
+  /// When a newer result replaced it, as an RFC 3339 UTC timestamp.
+  pub saved_at: String,
+  /// Its size, in bytes.
+  pub size_bytes: i64,
+  /// Hex SHA-256 of the archive (`null` for an archive committed before checksums were recorded).
+  pub sha256: Option<String>,
+}
+
+/// The kept result versions of task `task_id`, newest first, with their runs' descriptions.
+fn result_versions(
+  connection: &mut diesel::PgConnection,
+  task_id: i64,
+) -> Result<Vec<ResultVersionDto>, Status> {
+  use crate::schema::historical_runs;
+  use diesel::prelude::*;
+  let versions =
+    ResultArchiveVersion::for_task(connection, task_id).map_err(|_| Status::InternalServerError)?;
+  let run_ids: Vec<i32> = versions
+    .iter()
+    .filter_map(|version| version.run_id)
+    .collect();
+  let runs: HashMap<i32, String> = historical_runs::table
+    .filter(historical_runs::id.eq_any(&run_ids))
+    .select((historical_runs::id, historical_runs::description))
+    .load::<(i32, String)>(connection)
+    .map_err(|_| Status::InternalServerError)?
+    .into_iter()
+    .collect();
+  Ok(
+    versions
+      .into_iter()
+      .map(|version| ResultVersionDto {
+        id: version.id,
+        run_id: version.run_id,
+        run: version.run_id.and_then(|id| runs.get(&id).cloned()),
+        written_at: iso_utc(version.written_at),
+        saved_at: iso_utc(version.saved_at),
+        size_bytes: version.size_bytes,
+        sha256: version.sha256,
+      })
+      .collect(),
+  )
+}
+
+/// The facts the importer recorded about a document's source (`models::Document`).
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DocumentFactsDto {
+  /// arXiv submission month (`yymm`), if the path or name carries one.
+  pub yymm: Option<String>,
+  /// Size of the source file, in bytes.
+  pub source_bytes: Option<i64>,
+  /// Files in the source (1 for a single `.tex`).
+  pub file_count: Option<i32>,
+  /// The source member declaring `\documentclass`.
+  pub main_tex: Option<String>,
+  /// arXiv primary category (e.g. `hep-th`).
+  pub primary_category: Option<String>,
+  /// Hex SHA-256 of the source file, as of the last import or extend.
+  pub content_hash: Option<String>,
+  /// When an extend found the source file missing, as an RFC 3339 UTC timestamp (`null` while it
+  /// is present).
+  pub vanished_at: Option<String>,
+}
+
+impl From<Document> for DocumentFactsDto {
+  fn from(document: Document) -> Self {
+    DocumentFactsDto {
+      yymm: document.yymm,
+      source_bytes: document.source_bytes,
+      file_count: document.file_count,
+      main_tex: document.main_tex,
+      primary_category: document.primary_category,
+      content_hash: document.content_hash,
+      vanished_at: document.vanished_at.map(iso_utc),
+    }
+  }
+}
+
+/// One group of the document-facet breakdown: a month / category / size / file-count bucket and its
+/// tasks' statuses.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct FacetGroupDto {
+  /// The group label (`null` for documents whose fact is unknown — e.g. no recorded category).
+  pub facet: Option<String>,
+  /// Tasks in the group.
+  pub tasks: i64,
+  /// Not yet converted (TODO or in progress).
+  pub todo: i64,
+  /// Converted without a problem.
+  pub no_problem: i64,
+  /// Converted with warnings.
+  pub warning: i64,
+  /// Converted with errors.
+  pub error: i64,
+  /// Failed fatally.
+  pub fatal: i64,
+  /// Invalid input.
+  pub invalid: i64,
+  /// Paused, quarantined or awaiting an upstream service.
+  pub blocked: i64,
+}
+
+impl From<FacetRow> for FacetGroupDto {
+  fn from(row: FacetRow) -> Self {
+    FacetGroupDto {
+      facet: row.facet,
+      tasks: row.tasks,
+      todo: row.todo,
+      no_problem: row.no_problem,
+      warning: row.warning,
+      error: row.error,
+      fatal: row.fatal,
+      invalid: row.invalid,
+      blocked: row.blocked,
+    }
+  }
+}
+
+/// A `(corpus, service)`'s task statuses grouped by a document fact — "which months / subjects /
+/// source sizes fail most?" — over the tasks matching the document filter (and, if given, that
+/// emitted the given message).
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct BreakdownDto {
+  /// Corpus name.
+  pub corpus: String,
+  /// Service name.
+  pub service: String,
+  /// The fact grouped by: `month` | `category` | `size` | `files`.
+  pub by: String,
+  /// The document filter applied, summarized (empty for none).
+  pub filter: String,
+  /// The message severity tasks were narrowed to, if any.
+  pub severity: Option<String>,
+  /// The message category, if any.
+  pub category: Option<String>,
+  /// The message `what`, if any.
+  pub what: Option<String>,
+  /// The groups, months chronologically and buckets small to large; unknown last.
+  pub groups: Vec<FacetGroupDto>,
+}
+
+/// An entry's crash/timeout history and hold state (`models::PoisonEntry`). A held entry is still
+/// reported, but no service leases it until an operator releases it.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct PoisonEntryDto {
+  /// Whether the entry is currently held.
+  pub held: bool,
+  /// When it was held, as an RFC 3339 UTC timestamp (`null` unless held).
+  pub held_at: Option<String>,
+  /// Lifetime count of its leases that expired or whose worker reported a crash or timeout,
+  /// across services and runs.
+  pub incidents: i32,
+  /// Incidents since it was last released (the hold triggers at
+  /// `dispatcher.poison_hold_threshold`).
+  pub strikes: i32,
+  /// When its latest incident happened, as an RFC 3339 UTC timestamp.
+  pub last_incident_at: String,
+  /// The service of the latest incident.
+  pub last_service: Option<String>,
+  /// The worker of the latest incident, if known.
+  pub last_worker: Option<String>,
+  /// When it was last released, as an RFC 3339 UTC timestamp.
+  pub released_at: Option<String>,
+  /// Who last released it.
+  pub released_by: Option<String>,
+}
+
+impl PoisonEntryDto {
+  /// The DTO of `entry`, resolving its last service's name.
+  pub fn load(entry: PoisonEntry, connection: &mut diesel::PgConnection) -> Self {
+    let last_service = entry.last_service_name(connection).ok().flatten();
+    PoisonEntryDto {
+      held: entry.is_held(),
+      held_at: entry.held_at.map(iso_utc),
+      incidents: entry.incidents,
+      strikes: entry.strikes,
+      last_incident_at: iso_utc(entry.last_incident_at),
+      last_service,
+      last_worker: entry.last_worker,
+      released_at: entry.released_at.map(iso_utc),
+      released_by: entry.released_by,
+    }
+  }
 }
 
 /// Severities the rollup aggregates over (the four message severities plus the all-messages `info`
@@ -187,13 +424,13 @@ fn is_rollup_severity(severity: &str) -> bool {
 }
 
 /// Severities valid for a **rerun** filter — which differs from the report rollup, because rerun
-/// filters *tasks* (or their messages), it doesn't aggregate a report (R-9). With a `category` the
-/// scope is over log messages (`warning`/`error`/`fatal`/`invalid` + the all-messages `info`);
-/// without one it is over task *status* (`no_problem`/`warning`/`error`/`fatal`/`invalid` —
-/// `no_problem` IS a legitimate rerun scope, e.g. regenerating output after a worker upgrade, and
-/// `info` is not a task status). One shared validator for the agent (`rerun_report`) and human
-/// (`serve_rerun`) surfaces, so both reject the same typos instead of silently mis-scoping to
-/// `no_problem`.
+/// filters *tasks* (or their messages), it doesn't aggregate a report (R-9). With a `category` (or
+/// a message text query, passed as `has_category` too) the scope is over log messages
+/// (`warning`/`error`/`fatal`/`invalid` + the all-messages `info`); without one it is over task
+/// *status* (`no_problem`/`warning`/`error`/`fatal`/`invalid` — `no_problem` IS a legitimate rerun
+/// scope, e.g. regenerating output after a worker upgrade, and `info` is not a task status). One
+/// shared validator for the agent (`rerun_report`) and human (`serve_rerun`) surfaces, so both
+/// reject the same typos instead of silently mis-scoping to `no_problem`.
 pub fn is_valid_rerun_severity(severity: &str, has_category: bool) -> bool {
   if has_category {
     matches!(severity, "warning" | "error" | "fatal" | "invalid" | "info")
@@ -250,11 +487,16 @@ pub fn api_service_overview(
       }
     })
     .collect();
+  let backlog = priority_backlog(&mut connection, corpus.id, service.id)
+    .into_iter()
+    .map(|(priority, tasks)| PriorityBacklogDto { priority, tasks })
+    .collect();
   Ok(Json(ServiceOverviewDto {
     corpus: corpus.name,
     service: service.name,
     total: stats.get("total").copied().unwrap_or(0.0) as i64,
     statuses,
+    backlog,
   }))
 }
 
@@ -459,6 +701,198 @@ pub fn api_entry_list(
   }))
 }
 
+/// Example documents shown per message cluster.
+const CLUSTER_EXAMPLES: i64 = 3;
+
+/// One cluster of a `what`'s messages: those whose `details` share a fingerprint once line numbers,
+/// paths, file names, quoted tokens and macro arguments are masked — one root cause.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct MessageClusterDto {
+  /// The shared fingerprint, as 16 hex digits.
+  pub fingerprint: String,
+  /// The masked details the cluster's messages share (e.g. `at <PATH>; line <N> col <N>`).
+  pub pattern: String,
+  /// Distinct tasks with a message in the cluster.
+  pub tasks: i64,
+  /// Messages in the cluster.
+  pub messages: i64,
+  /// A few example documents, by entry, each with its own unmasked details.
+  pub examples: Vec<EntryRowDto>,
+}
+
+/// The rung below the entry list: the messages of a `(severity, category, what)` grouped into
+/// clusters of near-identical `details`, largest first — the distinct root causes behind a `what`.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ClusterReportDto {
+  /// Corpus name.
+  pub corpus: String,
+  /// Service name.
+  pub service: String,
+  /// The severity reported on.
+  pub severity: String,
+  /// The category drilled into.
+  pub category: String,
+  /// The `what` drilled into.
+  pub what: String,
+  /// Messages under the `what` in total.
+  pub total_messages: i64,
+  /// Of those, messages stored before fingerprints existed — in no cluster until
+  /// `cortex backfill-fingerprints` fills them in.
+  pub unfingerprinted_messages: i64,
+  /// Distinct clusters in total.
+  pub total_clusters: i64,
+  /// Pagination offset echoed back.
+  pub offset: i64,
+  /// Page size echoed back (the cap actually applied).
+  pub page_size: i64,
+  /// The clusters of this page, by descending task count.
+  pub clusters: Vec<MessageClusterDto>,
+}
+
+/// Builds the [`ClusterReportDto`] of a `(corpus, service, severity, category, what)` — shared by
+/// the agent endpoint and the clusters screen. `400` on an unknown severity or an offset beyond
+/// `MAX_REPORT_OFFSET`, `404` on an unknown corpus/service, `503` if the aggregation overruns its
+/// budget.
+#[allow(clippy::too_many_arguments)]
+fn cluster_report(
+  corpus: &str,
+  service: &str,
+  severity: &str,
+  category: &str,
+  what: &str,
+  offset: Option<i64>,
+  page_size: Option<i64>,
+  connection: &mut diesel::PgConnection,
+) -> Result<ClusterReportDto, Status> {
+  if !is_rollup_severity(severity) {
+    return Err(Status::BadRequest);
+  }
+  let offset = offset.unwrap_or(0).max(0);
+  if offset > MAX_REPORT_OFFSET {
+    return Err(Status::BadRequest);
+  }
+  let page_size = page_size.unwrap_or(100).clamp(1, MAX_REPORT_PAGE_SIZE);
+  let (corpus, service) = resolve(corpus, service, connection)?;
+  let report = message_clusters(
+    connection,
+    corpus.id,
+    service.id,
+    severity,
+    category,
+    what,
+    page_size,
+    offset,
+    CLUSTER_EXAMPLES,
+  )
+  .map_err(|_| Status::ServiceUnavailable)?
+  .unwrap_or_default();
+  let clusters = report
+    .clusters
+    .into_iter()
+    .map(|cluster| MessageClusterDto {
+      fingerprint: fingerprint_hex(cluster.fingerprint),
+      pattern: cluster.pattern,
+      tasks: cluster.task_count,
+      messages: cluster.message_count,
+      examples: cluster
+        .examples
+        .into_iter()
+        .map(|example| EntryRowDto {
+          name: entry_document_name(&example.entry),
+          task_id: example.task_id,
+          details: example.details,
+        })
+        .collect(),
+    })
+    .collect();
+  Ok(ClusterReportDto {
+    corpus: corpus.name,
+    service: service.name,
+    severity: severity.to_string(),
+    category: category.to_string(),
+    what: what.to_string(),
+    total_messages: report.total_messages,
+    unfingerprinted_messages: report.unfingerprinted_messages,
+    total_clusters: report.cluster_count,
+    offset,
+    page_size,
+    clusters,
+  })
+}
+
+/// The message clusters of a `(corpus, service, severity, category, what)` — the rung below the
+/// entry list ([`api_entry_list`]): its messages grouped by the fingerprint of their `details` with
+/// line numbers, paths, file names, quoted tokens and macro arguments masked, by descending task
+/// count, each with its counts and a few example documents. Paginated like the entry list. Live, so
+/// it waits for a [`LiveReportLimiter`] permit like the other live reports (P-2), and runs under
+/// the cluster query budget. `400` on an unknown severity or too deep an offset, `404` on an
+/// unknown corpus/service, `503` if the aggregation overruns its budget.
+#[rocket_okapi::openapi(tag = "Reports")]
+#[get("/api/reports/<corpus>/<service>/<severity>/<category>/<what>/clusters?<offset>&<page_size>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn api_message_clusters(
+  corpus: &str,
+  service: &str,
+  severity: &str,
+  category: &str,
+  what: &str,
+  offset: Option<i64>,
+  page_size: Option<i64>,
+  limiter: &State<LiveReportLimiter>,
+  pool: &State<DbPool>,
+) -> Result<Json<ClusterReportDto>, Status> {
+  let _permit = limiter.acquire().await?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  Ok(Json(cluster_report(
+    corpus,
+    service,
+    severity,
+    category,
+    what,
+    offset,
+    page_size,
+    &mut connection,
+  )?))
+}
+
+/// The message clusters **screen** (HTML twin of [`api_message_clusters`]), linked from the entry
+/// list: the distinct root causes behind a `what`, largest first. Gated by the
+/// [`LiveReportLimiter`] like its agent twin.
+#[allow(clippy::too_many_arguments)]
+#[get("/corpus/<corpus>/<service>/<severity>/<category>/<what>/clusters?<offset>&<page_size>")]
+pub async fn message_clusters_page(
+  corpus: &str,
+  service: &str,
+  severity: &str,
+  category: &str,
+  what: &str,
+  offset: Option<i64>,
+  page_size: Option<i64>,
+  limiter: &State<LiveReportLimiter>,
+  pool: &State<DbPool>,
+) -> Result<Template, Status> {
+  let _permit = limiter.acquire().await?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let report = cluster_report(
+    corpus,
+    service,
+    severity,
+    category,
+    what,
+    offset,
+    page_size,
+    &mut connection,
+  )?;
+  let global = serde_json::json!({
+    "title": format!("{} — {}/{} message clusters", report.what, report.corpus, report.service),
+    "description": "The distinct root causes behind a message class",
+  });
+  Ok(Template::render(
+    "message-clusters",
+    rocket_dyn_templates::context! { global, report },
+  ))
+}
+
 /// Builds the [`DocumentReportDto`] for one `(corpus, service, document-name)` — the shared backend
 /// of the agent endpoint and the human forensic screen, so both show identical status + messages.
 /// `404` on an unknown corpus / service / document.
@@ -483,6 +917,20 @@ fn document_report(
     })
     .collect();
   let messages_truncated = (messages.len() as i64) < counts.total();
+  let poison = PoisonEntry::find(connection, &task.entry)
+    .map_err(|_| Status::InternalServerError)?
+    .map(|entry| PoisonEntryDto::load(entry, connection));
+  let metadata = EntryMetadata::find(connection, &corpus, &task.entry)
+    .map_err(|_| Status::InternalServerError)?;
+  let document = Document::find(connection, corpus.id, &task.entry)
+    .map_err(|_| Status::InternalServerError)?
+    .map(DocumentFactsDto::from);
+  let versions = result_versions(connection, task.id)?;
+  // Two results to compare: a kept version and the current one, or a sandbox's and its parent's.
+  let comparable = !versions.is_empty() || corpus.sandbox_id().is_some();
+  let document_url = format!("/document/{}/{}/{}", corpus.name, service.name, name);
+  let compare_url = comparable.then(|| format!("{document_url}/compare"));
+  let diff_url = comparable.then(|| format!("{document_url}/diff"));
   Ok(DocumentReportDto {
     corpus: corpus.name.clone(),
     service: service.name.clone(),
@@ -496,6 +944,12 @@ fn document_report(
     messages_truncated,
     result_url: format!("/entry/{}/{}", service.name, task.id),
     preview_url: format!("/preview/{}/{}/{}", corpus.name, service.name, name),
+    poison,
+    metadata,
+    document,
+    versions,
+    compare_url,
+    diff_url,
   })
 }
 
@@ -531,6 +985,7 @@ pub fn document_report_page(
   corpus: &str,
   service: &str,
   name: &str,
+  session: Option<AdminSession>,
   pool: &State<DbPool>,
 ) -> Result<(Status, Template), Status> {
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
@@ -544,7 +999,7 @@ pub fn document_report_page(
         Status::Ok,
         Template::render(
           "document-report",
-          rocket_dyn_templates::context! { global, report },
+          rocket_dyn_templates::context! { global, report, is_admin: session.is_some() },
         ),
       ))
     },
@@ -575,6 +1030,640 @@ pub fn document_report_page(
   }
 }
 
+/// One side of the side-by-side comparison of a document's results: the current result archive, a
+/// sandbox's parent-corpus result or a kept version, read back from storage.
+#[derive(Debug, Default, Serialize)]
+pub struct ComparedResultDto {
+  /// `current`, `parent` or the kept version's id — the side's selector value.
+  pub selector: String,
+  /// What the side shows, e.g. `current result` or `run 12: mark for rerun …`.
+  pub label: String,
+  /// When the archive was committed, as an RFC 3339 UTC timestamp (unknown for the current
+  /// result).
+  pub written_at: Option<String>,
+  /// The status its `cortex.log` reports.
+  pub status: Option<String>,
+  /// Its messages, in log order (capped at `DOCUMENT_MESSAGE_CAP`).
+  pub messages: Vec<MessageDto>,
+  /// Its messages in total.
+  pub message_count: usize,
+  /// Its archive members in total.
+  pub member_count: usize,
+  /// The name of its main HTML document.
+  pub main_html_name: Option<String>,
+  /// That document's text, for the side's output pane.
+  pub main_html: Option<String>,
+  /// Whether the text was cut at `backend::MAIN_HTML_LIMIT`.
+  pub main_html_truncated: bool,
+  /// Why the archive could not be read (missing, or not a readable result), if it could not.
+  pub error: Option<String>,
+}
+
+/// The side-by-side comparison of two results of one document.
+#[derive(Debug, Serialize)]
+pub struct DocumentCompareDto {
+  /// Corpus name.
+  pub corpus: String,
+  /// Service name.
+  pub service: String,
+  /// The document's short name.
+  pub name: String,
+  /// The task id.
+  pub task_id: i64,
+  /// Whether the corpus is a sandbox (so `parent` selects its parent corpus's result).
+  pub sandbox: bool,
+  /// The document's kept versions, newest first (the selectable sides besides `current`).
+  pub versions: Vec<ResultVersionDto>,
+  /// The left (older) side.
+  pub left: ComparedResultDto,
+  /// The right (newer) side.
+  pub right: ComparedResultDto,
+}
+
+/// A result archive of a document picked by a selector: `current` (the corpus's own result),
+/// `parent` (a sandbox's parent-corpus result) or a kept version's id.
+struct ResultSide {
+  selector: String,
+  label: String,
+  written_at: Option<String>,
+  path: String,
+}
+
+/// Resolves the `selector` of a comparison side to its result archive. `404` on an unknown version,
+/// or on `parent` outside a sandbox.
+fn result_side(
+  selector: &str,
+  corpus: &Corpus,
+  service: &Service,
+  task: &Task,
+  versions: &[ResultVersionDto],
+  connection: &mut diesel::PgConnection,
+) -> Result<ResultSide, Status> {
+  let (label, written_at, path) = match selector {
+    "current" => {
+      let path = result_archive_path(&task.entry, &service.name, corpus.sandbox_id())
+        .ok_or(Status::NotFound)?;
+      let label = if corpus.sandbox_id().is_some() {
+        "sandbox result".to_string()
+      } else {
+        "current result".to_string()
+      };
+      (label, None, path.to_string_lossy().into_owned())
+    },
+    "parent" => {
+      let parent_id = corpus.parent_corpus_id.ok_or(Status::NotFound)?;
+      let parent =
+        Corpus::find_by_id(parent_id, connection).map_err(|_| Status::InternalServerError)?;
+      // A sandbox shares its parent's entries; only the result archive names differ.
+      let path = result_archive_path(&task.entry, &service.name, None).ok_or(Status::NotFound)?;
+      (
+        format!("{} result", parent.name),
+        None,
+        path.to_string_lossy().into_owned(),
+      )
+    },
+    _ => {
+      let id: i64 = selector.parse().map_err(|_| Status::NotFound)?;
+      let dto = versions
+        .iter()
+        .find(|version| version.id == id)
+        .ok_or(Status::NotFound)?;
+      let version = ResultArchiveVersion::for_task(connection, task.id)
+        .map_err(|_| Status::InternalServerError)?
+        .into_iter()
+        .find(|version| version.id == id)
+        .ok_or(Status::NotFound)?;
+      let label = match (dto.run_id, dto.run.as_deref()) {
+        (Some(run), Some(description)) => format!("run {run}: {description}"),
+        (Some(run), None) => format!("run {run}"),
+        _ => format!("version {id}"),
+      };
+      (label, Some(dto.written_at.clone()), version.path)
+    },
+  };
+  Ok(ResultSide {
+    selector: selector.to_string(),
+    label,
+    written_at,
+    path,
+  })
+}
+
+/// The default sides of a comparison: a sandbox's parent result against its own, else the newest
+/// kept version against the current result.
+fn default_sides(corpus: &Corpus, versions: &[ResultVersionDto]) -> (String, String) {
+  let left = if corpus.sandbox_id().is_some() {
+    "parent".to_string()
+  } else {
+    versions
+      .first()
+      .map_or_else(|| "current".to_string(), |version| version.id.to_string())
+  };
+  (left, "current".to_string())
+}
+
+/// Reads one side of a comparison back from storage.
+fn compared_result(
+  side: ResultSide,
+  name: &str,
+  task: &Task,
+  service: &Service,
+) -> ComparedResultDto {
+  let mut compared = ComparedResultDto {
+    selector: side.selector,
+    label: side.label,
+    written_at: side.written_at,
+    ..ComparedResultDto::default()
+  };
+  match read_archive_view(
+    &*storage(),
+    Path::new(&side.path),
+    name,
+    task.id,
+    service.log_parser(),
+  ) {
+    Ok(view) => {
+      compared.status = Some(view.status.to_key());
+      compared.message_count = view.messages.len();
+      compared.messages = view
+        .messages
+        .iter()
+        .take(DOCUMENT_MESSAGE_CAP as usize)
+        .map(|message| MessageDto {
+          severity: message.severity().to_string(),
+          category: message.category().to_string(),
+          what: message.what().to_string(),
+          details: message.details().to_string(),
+        })
+        .collect();
+      compared.member_count = view.members.len();
+      compared.main_html_name = view.main_html_name;
+      compared.main_html = view.main_html;
+      compared.main_html_truncated = view.main_html_truncated;
+    },
+    Err(error) => compared.error = Some(error),
+  }
+  compared
+}
+
+/// Builds the [`DocumentCompareDto`] of a document's results `left` and `right` (each `current`,
+/// `parent` in a sandbox, or a kept version's id; by default see [`default_sides`]). `404` on an
+/// unknown corpus / service / document / version.
+fn document_compare(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  left: Option<&str>,
+  right: Option<&str>,
+  connection: &mut diesel::PgConnection,
+) -> Result<DocumentCompareDto, Status> {
+  let (corpus, service) = resolve(corpus, service, connection)?;
+  let task =
+    Task::find_by_name(name, &corpus, &service, connection).map_err(|_| Status::NotFound)?;
+  let versions = result_versions(connection, task.id)?;
+  let (default_left, default_right) = default_sides(&corpus, &versions);
+  let left = result_side(
+    left.unwrap_or(&default_left),
+    &corpus,
+    &service,
+    &task,
+    &versions,
+    connection,
+  )?;
+  let right = result_side(
+    right.unwrap_or(&default_right),
+    &corpus,
+    &service,
+    &task,
+    &versions,
+    connection,
+  )?;
+  Ok(DocumentCompareDto {
+    sandbox: corpus.sandbox_id().is_some(),
+    left: compared_result(left, name, &task, &service),
+    right: compared_result(right, name, &task, &service),
+    corpus: corpus.name,
+    service: service.name,
+    name: name.to_string(),
+    task_id: task.id,
+    versions,
+  })
+}
+
+/// One side of a document diff: which result archive it is, and what its `cortex.log` says.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DiffSideDto {
+  /// `current`, `parent` (a sandbox's parent-corpus result) or the kept version's id.
+  pub selector: String,
+  /// What the side is, e.g. `current result` or `run 12: mark for rerun …`.
+  pub label: String,
+  /// When a kept version was committed, as an RFC 3339 UTC timestamp.
+  pub written_at: Option<String>,
+  /// The status its `cortex.log` reports (`null` if the archive could not be read).
+  pub status: Option<String>,
+  /// Its messages in total.
+  pub message_count: usize,
+  /// Its archive members in total.
+  pub member_count: usize,
+  /// Why the archive could not be read (missing, or not a readable result), if it could not.
+  pub error: Option<String>,
+}
+
+/// What changed between two result archives of one document.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DocumentDiffDto {
+  /// Corpus name.
+  pub corpus: String,
+  /// Service name.
+  pub service: String,
+  /// The document's short name.
+  pub name: String,
+  /// The task id.
+  pub task_id: i64,
+  /// Whether the corpus is a sandbox (so `parent` selects its parent corpus's result).
+  pub sandbox: bool,
+  /// The document's kept versions, newest first (the selectable sides besides `current`).
+  pub versions: Vec<ResultVersionDto>,
+  /// The left (older) side.
+  pub left: DiffSideDto,
+  /// The right (newer) side.
+  pub right: DiffSideDto,
+  /// The diff: messages, archive members and main HTML text (`null` unless both sides could be
+  /// read).
+  pub diff: Option<ArchiveDiff>,
+  /// Path to the side-by-side comparison of the same two results.
+  pub compare_url: String,
+}
+
+/// Reads one side of a document diff back from storage: its [`DiffSideDto`], and its view unless
+/// it could not be read.
+fn diff_side(
+  side: ResultSide,
+  name: &str,
+  task: &Task,
+  service: &Service,
+) -> (DiffSideDto, Option<ArchiveView>) {
+  let view = read_archive_view(
+    &*storage(),
+    Path::new(&side.path),
+    name,
+    task.id,
+    service.log_parser(),
+  );
+  let dto = DiffSideDto {
+    selector: side.selector,
+    label: side.label,
+    written_at: side.written_at,
+    status: view.as_ref().ok().map(|view| view.status.to_key()),
+    message_count: view.as_ref().map_or(0, |view| view.messages.len()),
+    member_count: view.as_ref().map_or(0, |view| view.members.len()),
+    error: view.as_ref().err().cloned(),
+  };
+  (dto, view.ok())
+}
+
+/// Builds the [`DocumentDiffDto`] of a document's results `left` and `right` (selected as for
+/// [`document_compare`]). `404` on an unknown corpus / service / document / version.
+fn document_diff(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  left: Option<&str>,
+  right: Option<&str>,
+  connection: &mut diesel::PgConnection,
+) -> Result<DocumentDiffDto, Status> {
+  let (corpus, service) = resolve(corpus, service, connection)?;
+  let task =
+    Task::find_by_name(name, &corpus, &service, connection).map_err(|_| Status::NotFound)?;
+  let versions = result_versions(connection, task.id)?;
+  let (default_left, default_right) = default_sides(&corpus, &versions);
+  let left = result_side(
+    left.unwrap_or(&default_left),
+    &corpus,
+    &service,
+    &task,
+    &versions,
+    connection,
+  )?;
+  let right = result_side(
+    right.unwrap_or(&default_right),
+    &corpus,
+    &service,
+    &task,
+    &versions,
+    connection,
+  )?;
+  let (left, left_view) = diff_side(left, name, &task, &service);
+  let (right, right_view) = diff_side(right, name, &task, &service);
+  let diff = match (&left_view, &right_view) {
+    (Some(left_view), Some(right_view)) => Some(diff_archives(left_view, right_view)),
+    _ => None,
+  };
+  let compare_url = format!(
+    "/document/{}/{}/{}/compare?left={}&right={}",
+    corpus.name, service.name, name, left.selector, right.selector
+  );
+  Ok(DocumentDiffDto {
+    sandbox: corpus.sandbox_id().is_some(),
+    corpus: corpus.name,
+    service: service.name,
+    name: name.to_string(),
+    task_id: task.id,
+    versions,
+    left,
+    right,
+    diff,
+    compare_url,
+  })
+}
+
+/// What changed in a document's output between two of its results (agent twin of the document diff
+/// screen): a message-level diff of their parsed `cortex.log`s, the archive members added, removed
+/// or resized, and a line diff of the main HTML. `?left=` / `?right=` pick `current`, `parent` (in
+/// a sandbox: the parent corpus's result) or a kept version's id (see `versions` of
+/// [`api_document`]); by default a sandbox's parent result against its own, else the newest kept
+/// version against the current result. A side that cannot be read carries an `error` and leaves
+/// `diff` null. `404` on an unknown corpus / service / document / version.
+#[rocket_okapi::openapi(tag = "Reports")]
+#[get("/api/corpus/<corpus>/<service>/document/<name>/diff?<left>&<right>")]
+pub fn api_document_diff(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  left: Option<&str>,
+  right: Option<&str>,
+  pool: &State<DbPool>,
+) -> Result<Json<DocumentDiffDto>, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  Ok(Json(document_diff(
+    corpus,
+    service,
+    name,
+    left,
+    right,
+    &mut connection,
+  )?))
+}
+
+/// The document diff **screen** (HTML twin of [`api_document_diff`]): the message, member and main
+/// HTML changes between two results of a document, with the same side selectors.
+#[get("/document/<corpus>/<service>/<name>/diff?<left>&<right>")]
+pub fn document_diff_page(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  left: Option<&str>,
+  right: Option<&str>,
+  pool: &State<DbPool>,
+) -> Result<Template, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let diff = document_diff(corpus, service, name, left, right, &mut connection)?;
+  let global = serde_json::json!({
+    "title": format!("{} — {}/{} output diff", diff.name, diff.corpus, diff.service),
+    "description": "What changed in a document's output between two results",
+  });
+  Ok(Template::render(
+    "document-diff",
+    rocket_dyn_templates::context! { global, diff },
+  ))
+}
+
+/// The side-by-side comparison **screen** of two results of a document: the current result and the
+/// previous ones its service kept, or a sandbox's result and its parent corpus's (`?left=` /
+/// `?right=` pick `current`, `parent` or a kept version's id; by default see [`default_sides`]).
+/// Each side shows its verdict, its messages and its rendered main HTML (sandboxed), read back from
+/// the archives themselves. `404` on an unknown corpus / service / document / version.
+#[get("/document/<corpus>/<service>/<name>/compare?<left>&<right>")]
+pub fn document_compare_page(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  left: Option<&str>,
+  right: Option<&str>,
+  pool: &State<DbPool>,
+) -> Result<Template, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let compare = document_compare(corpus, service, name, left, right, &mut connection)?;
+  let global = serde_json::json!({
+    "title": format!("{} — {}/{} results side by side", compare.name, compare.corpus, compare.service),
+    "description": "Two results of one document, side by side",
+  });
+  Ok(Template::render(
+    "document-compare",
+    rocket_dyn_templates::context! { global, compare },
+  ))
+}
+
+/// Builds the [`BreakdownDto`] for a `(corpus, service)` — the shared core of the agent endpoint
+/// and the human screen. `404` on an unknown corpus/service; `422` on an unknown facet or message
+/// severity, an invalid document filter, or a `what` without a `category`; `503` if the scan
+/// overruns its budget.
+fn breakdown_report(
+  corpus: &str,
+  service: &str,
+  params: BreakdownParams,
+  connection: &mut diesel::PgConnection,
+) -> Result<BreakdownDto, Status> {
+  let (corpus, service) = resolve(corpus, service, connection)?;
+  let blank_to_none = |value: Option<String>| value.filter(|text| !text.trim().is_empty());
+  let facet = DocumentFacet::from_key(blank_to_none(params.by).as_deref().unwrap_or("month"))
+    .ok_or(Status::UnprocessableEntity)?;
+  let filter = DocumentFilter {
+    yymm: params.yymm,
+    primary_category: params.primary_category,
+    min_source_bytes: params.min_source_bytes,
+    max_source_bytes: params.max_source_bytes,
+    min_files: params.min_files,
+    max_files: params.max_files,
+  }
+  .normalized();
+  filter.validate().map_err(|_| Status::UnprocessableEntity)?;
+  let (severity, category, what) = (
+    blank_to_none(params.severity),
+    blank_to_none(params.category),
+    blank_to_none(params.what),
+  );
+  let message = match &severity {
+    Some(severity) => Some(MessageScope {
+      severity: severity.clone(),
+      category: category.clone(),
+      what: what.clone(),
+    }),
+    None if category.is_some() || what.is_some() => return Err(Status::UnprocessableEntity),
+    None => None,
+  };
+  let groups = facet_breakdown(
+    connection,
+    corpus.id,
+    service.id,
+    facet,
+    &filter,
+    message.as_ref(),
+  )
+  .map_err(|error| match error {
+    diesel::result::Error::QueryBuilderError(_) => Status::UnprocessableEntity,
+    _ => Status::ServiceUnavailable,
+  })?;
+  Ok(BreakdownDto {
+    corpus: corpus.name,
+    service: service.name,
+    by: facet.to_key().to_string(),
+    filter: filter.summary(),
+    severity,
+    category,
+    what,
+    groups: groups.into_iter().map(FacetGroupDto::from).collect(),
+  })
+}
+
+/// The document-facet breakdown (agent twin of the `/breakdown` screen): a `(corpus, service)`'s
+/// task statuses grouped `by` a document fact — `month` (default), `category` (arXiv primary
+/// category), `size` or `files` — over the tasks whose documents match the optional filter, and
+/// that emitted a message of `severity` (narrowed by `category`/`what`) if given. Live, one
+/// grouped scan, so it waits for a [`LiveReportLimiter`] permit like the other live reports (P-2),
+/// and runs under a statement budget. `404` on an unknown corpus/service, `422` on an invalid facet
+/// or filter, `503` if the scan overruns its budget.
+#[rocket_okapi::openapi(tag = "Reports")]
+#[get(
+  "/api/breakdown/<corpus>/<service>?<by>&<yymm>&<primary_category>&<min_source_bytes>&\
+   <max_source_bytes>&<min_files>&<max_files>&<severity>&<category>&<what>"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn api_breakdown(
+  corpus: &str,
+  service: &str,
+  by: Option<String>,
+  yymm: Option<String>,
+  primary_category: Option<String>,
+  min_source_bytes: Option<i64>,
+  max_source_bytes: Option<i64>,
+  min_files: Option<i32>,
+  max_files: Option<i32>,
+  severity: Option<String>,
+  category: Option<String>,
+  what: Option<String>,
+  limiter: &State<LiveReportLimiter>,
+  pool: &State<DbPool>,
+) -> Result<Json<BreakdownDto>, Status> {
+  let params = BreakdownParams {
+    by,
+    yymm,
+    primary_category,
+    min_source_bytes,
+    max_source_bytes,
+    min_files,
+    max_files,
+    severity,
+    category,
+    what,
+  };
+  // The permit comes BEFORE the pooled connection, so a burst of breakdowns waits without pinning
+  // the pool.
+  let _permit = limiter.acquire().await?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  Ok(Json(breakdown_report(
+    corpus,
+    service,
+    params,
+    &mut connection,
+  )?))
+}
+
+/// The document-facet breakdown **screen** (HTML twin of [`api_breakdown`]): a status table per
+/// month / category / size / file count, with the filter as a form. A sibling top-level path (like
+/// `/runs/<c>/<s>`) so it never collides with the report ladder's `/corpus/<c>/<s>/<severity>`
+/// rung. Gated by the [`LiveReportLimiter`] like its agent twin.
+#[get("/breakdown/<corpus>/<service>?<params..>")]
+pub async fn breakdown_page(
+  corpus: &str,
+  service: &str,
+  params: BreakdownParams,
+  session: Option<AdminSession>,
+  limiter: &State<LiveReportLimiter>,
+  pool: &State<DbPool>,
+) -> Result<Template, Status> {
+  let _permit = limiter.acquire().await?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let report = breakdown_report(corpus, service, params, &mut connection)?;
+  let global = serde_json::json!({
+    "title": format!("Breakdown by {} — {}/{}", report.by, report.corpus, report.service),
+    "description": "Task statuses grouped by a document fact",
+  });
+  Ok(Template::render(
+    "breakdown",
+    rocket_dyn_templates::context! { global, report, is_admin: session.is_some() },
+  ))
+}
+
+/// Acknowledgement of a poison-document release.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ReleaseAckDto {
+  /// The released entry path.
+  pub entry: String,
+  /// Its lifetime count of incidents (its strikes start over).
+  pub incidents: i32,
+  /// The actor who released it (audit identity).
+  pub actor: String,
+}
+
+/// Releases the held entry behind `(corpus, service, name)` as `actor` — the shared core of the
+/// agent and human writes. The hold is per entry, so this releases it for every service. `404` on
+/// an unknown corpus / service / document, or if the entry is not held.
+fn release_document(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  actor: &str,
+  connection: &mut diesel::PgConnection,
+) -> Result<PoisonEntry, Status> {
+  let (corpus, service) = resolve(corpus, service, connection)?;
+  let task =
+    Task::find_by_name(name, &corpus, &service, connection).map_err(|_| Status::NotFound)?;
+  PoisonEntry::release(connection, &task.entry, actor).map_err(|error| match error {
+    diesel::result::Error::NotFound => Status::NotFound,
+    _ => Status::InternalServerError,
+  })
+}
+
+/// Releases a held poison document (agent twin of the forensic screen's "Release" button): its
+/// entry is leased again by every service, and its strikes start over. **Token-gated** and audited.
+/// `404` on an unknown corpus / service / document, or if the document is not held.
+#[rocket_okapi::openapi(tag = "Management")]
+#[post("/api/corpus/<corpus>/<service>/document/<name>/release")]
+pub fn release_document_api(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<ReleaseAckDto>, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let entry = release_document(corpus, service, name, &actor.owner, &mut connection)?;
+  tracing::info!(actor = %actor.owner, entry = %entry.entry, "released held poison entry via API");
+  Ok(Json(ReleaseAckDto {
+    entry: entry.entry,
+    incidents: entry.incidents,
+    actor: actor.owner,
+  }))
+}
+
+/// The human twin of [`release_document_api`]: the forensic screen's "Release" button. Signed-in
+/// admins only; redirects back to the document.
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[post("/document/<corpus>/<service>/<name>/release")]
+pub fn release_document_human(
+  corpus: &str,
+  service: &str,
+  name: &str,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Redirect, AdminReject> {
+  let session = require_admin(session)?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  release_document(corpus, service, name, &session.owner, &mut connection)?;
+  Ok(Redirect::to(format!("/document/{corpus}/{service}/{name}")))
+}
+
 /// Query-style entry to the per-article forensic screen: `GET
 /// /document/<corpus>/<service>?name=<id>` → **303** to the canonical path URL
 /// `/document/<corpus>/<service>/<id>`. This is the **no-JS fallback** target for the
@@ -610,15 +1699,22 @@ pub struct RerunAckDto {
   pub actor: String,
   /// The recorded run description.
   pub description: String,
+  /// The leasing priority stamped on the rerun tasks, if one was requested.
+  pub priority: Option<i32>,
 }
 
 /// Marks the selected `(corpus, service[, severity, category, what])` scope for reprocessing — the
 /// agent twin of the report screen's rerun action, and a new historical run. **Token-gated** via
 /// the [`Actor`] guard (`X-Cortex-Token` header or `?token=`); `401` without a valid token, so
-/// results can't be wiped by an unauthenticated caller. `400` on an unknown severity, `404` on an
-/// unknown corpus/service. Returns `202 Accepted`.
+/// results can't be wiped by an unauthenticated caller. An optional `priority` (higher is leased
+/// sooner) moves the rerun ahead of the service's ordinary backlog. An optional `text` — a
+/// `GET /api/search/messages` query — reruns only the documents with a matching message, the
+/// `severity` then naming the message severity searched. `400` on an unknown severity or a blank
+/// `text`, `404` on an unknown corpus/service. Returns `202 Accepted`.
 #[rocket_okapi::openapi(tag = "Reports")]
-#[post("/api/reports/<corpus>/<service>/rerun?<severity>&<category>&<what>&<description>")]
+#[post(
+  "/api/reports/<corpus>/<service>/rerun?<severity>&<category>&<what>&<description>&<priority>&<text>"
+)]
 #[allow(clippy::too_many_arguments)]
 pub fn rerun_report(
   corpus: &str,
@@ -627,12 +1723,18 @@ pub fn rerun_report(
   category: Option<&str>,
   what: Option<&str>,
   description: Option<&str>,
+  priority: Option<i32>,
+  text: Option<&str>,
   actor: Actor,
   database_url: &State<DatabaseUrl>,
   _pool: &State<DbPool>,
 ) -> Result<(Status, Json<RerunAckDto>), Status> {
+  let text = text
+    .map(validate_message_text)
+    .transpose()
+    .map_err(|_| Status::BadRequest)?;
   if let Some(severity) = severity
-    && !is_valid_rerun_severity(severity, category.is_some())
+    && !is_valid_rerun_severity(severity, category.is_some() || text.is_some())
   {
     return Err(Status::BadRequest);
   }
@@ -653,9 +1755,15 @@ pub fn rerun_report(
       what_opt: what.map(str::to_string),
       description_opt: Some(description.clone()),
       owner_opt: Some(actor.owner.clone()),
+      priority_opt: priority,
+      text_opt: text.map(str::to_string),
     })
     .map_err(|_| Status::InternalServerError)?;
-  tracing::info!(actor = %actor.owner, corpus, service, severity = ?severity, category = ?category, what = ?what, "rerun via API");
+  tracing::info!(actor = %actor.owner, corpus, service, severity = ?severity, category = ?category, what = ?what, text = ?text, priority = ?priority, "rerun via API");
+  wake_dispatcher(
+    &mut backend.connection,
+    ControlSignal::scoped(ControlKind::Rerun, corpus_record.id, service_record.id),
+  );
   // The reran (corpus, service) scope's report cache was already invalidated inside the rerun
   // transaction (`mark_rerun`), so its reports repopulate fresh on the next view — no separate,
   // globally-scoped refresh job needed.
@@ -666,6 +1774,7 @@ pub fn rerun_report(
       service: service.to_string(),
       actor: actor.owner,
       description,
+      priority,
     }),
   ))
 }
@@ -722,10 +1831,10 @@ pub fn pause_run_api(
   api_run_control(corpus, service, &actor.owner, true, pool)
 }
 
-/// **Resume a run** — return every Blocked task (`status < -5`) of a `(corpus, service)` to TODO so
-/// the dispatcher picks them up again. The agent twin of the report screen's "Resume run" button.
-/// **Token-gated** via the [`Actor`] guard; `404` on an unknown corpus/service. Returns the count
-/// resumed.
+/// **Resume a run** — return every paused task of a `(corpus, service)` to TODO so the dispatcher
+/// picks them up again (pipeline tasks awaiting an upstream stay Blocked). The agent twin of the
+/// report screen's "Resume run" button. **Token-gated** via the [`Actor`] guard; `404` on an
+/// unknown corpus/service. Returns the count resumed.
 #[rocket_okapi::openapi(tag = "Reports")]
 #[post("/api/reports/<corpus>/<service>/resume")]
 pub fn resume_run_api(
@@ -1119,8 +2228,13 @@ pub fn routes() -> Vec<Route> {
     category_service_report_all,
     what_service_report,
     what_service_report_all,
+    message_clusters_page,
     document_report_page,
-    document_lookup_redirect
+    document_compare_page,
+    document_diff_page,
+    release_document_human,
+    document_lookup_redirect,
+    breakdown_page
   ]
 }
 
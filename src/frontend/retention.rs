@@ -10,7 +10,12 @@
 //! oldest snapshot, lets the admin pick a cutoff date, shows a **dry-run count** of exactly what a
 //! prune would remove, and only then offers a confirmed delete (gated + audited — same safety
 //! pattern as `delete_corpus`). The run *summaries* (`historical_runs`) are never touched, so the
-//! run history and charts survive; only the bulky per-task snapshots age out.
+//! run history and charts survive; only the bulky per-task snapshots age out — and with them, the
+//! previous result archives kept by services that keep versions (`backend::prune_versions_before`).
+//!
+//! The same screen collects the **result archives** left in storage by deleted corpora, services
+//! and sandboxes (`backend::collect_archives`): a background scan reports what a collection would
+//! delete and how many bytes it would free, and only then is a confirmed delete offered.
 
 use chrono::{NaiveDate, NaiveDateTime};
 use rocket::form::Form;
@@ -19,11 +24,14 @@ use rocket::response::Redirect;
 use rocket::serde::json::Json;
 use rocket::{Route, State};
 use rocket_dyn_templates::{Template, context};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::backend::DbPool;
 use crate::frontend::actor::{Actor, AdminReject, AdminSession, ReturnTo, require_admin_to};
-use crate::models::HistoricalTask;
+use crate::frontend::management::MaintenanceAckDto;
+use crate::jobs;
+use crate::models::{HistoricalTask, ResultArchiveVersion};
+use crate::storage::storage;
 
 /// Parses a `YYYY-MM-DD` cutoff to the start of that day (midnight); `None` on a malformed date.
 fn parse_cutoff(date: &str) -> Option<NaiveDateTime> {
@@ -44,6 +52,10 @@ pub struct HistoricalStatsDto {
   pub snapshot_rows: i64,
   /// The oldest snapshot's timestamp, formatted (`none` if there are no snapshots).
   pub oldest: String,
+  /// Kept previous result archives (`result_archive_versions`), pruned with the snapshots.
+  pub kept_versions: i64,
+  /// Their total size, in bytes.
+  pub kept_version_bytes: i64,
 }
 
 /// The per-task snapshot retention stats (agent twin of the `/admin/retention` screen). **Token-
@@ -57,21 +69,27 @@ pub fn api_historical_stats(
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
   let (total, oldest) =
     HistoricalTask::retention_stats(&mut connection).map_err(|_| Status::InternalServerError)?;
+  let (kept_versions, kept_version_bytes) = ResultArchiveVersion::retention_stats(&mut connection)
+    .map_err(|_| Status::InternalServerError)?;
   Ok(Json(HistoricalStatsDto {
     snapshot_rows: total,
     oldest: fmt_oldest(oldest),
+    kept_versions,
+    kept_version_bytes,
   }))
 }
 
-/// The data-retention screen (`GET /admin/retention?<before>&<pruned>`): snapshot stats; with
-/// `?before=YYYY-MM-DD`, a **dry-run** count of how many snapshots that cutoff would prune (the
-/// page then offers a confirmed delete). `?pruned=N` flashes the result of a completed prune.
+/// The data-retention screen (`GET /admin/retention?<before>&<pruned>&<pruned_versions>`): snapshot
+/// and kept-version stats; with `?before=YYYY-MM-DD`, a **dry-run** count of how many snapshots and
+/// kept versions that cutoff would prune (the page then offers a confirmed delete). `?pruned=N`
+/// (and `pruned_versions`) flashes the result of a completed prune.
 /// Signed-in admins only (unauthenticated → sign-in, returning here).
 #[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
-#[get("/admin/retention?<before>&<pruned>")]
+#[get("/admin/retention?<before>&<pruned>&<pruned_versions>")]
 pub fn retention_page(
   before: Option<String>,
   pruned: Option<i64>,
+  pruned_versions: Option<i64>,
   session: Option<AdminSession>,
   return_to: ReturnTo,
   pool: &State<DbPool>,
@@ -79,16 +97,32 @@ pub fn retention_page(
   let admin = require_admin_to(session, &return_to)?;
   let mut snapshot_rows = 0i64;
   let mut oldest = "none".to_string();
+  let mut kept_versions = 0i64;
+  let mut kept_version_bytes = 0i64;
   let mut preview: Option<serde_json::Value> = None;
+  let mut archive_gc: Option<serde_json::Value> = None;
   if let Ok(mut connection) = pool.get() {
+    // The latest archive collection's report (a scan's dry-run accounting, or what was deleted).
+    archive_gc = jobs::latest_archive_gc(&mut connection).map(|job| {
+      serde_json::json!({
+        "uuid": job.uuid.to_string(),
+        "finished": crate::frontend::helpers::iso_utc(job.updated_at),
+        "report": job.result,
+      })
+    });
     if let Ok((total, old)) = HistoricalTask::retention_stats(&mut connection) {
       snapshot_rows = total;
       oldest = fmt_oldest(old);
     }
+    if let Ok((count, bytes)) = ResultArchiveVersion::retention_stats(&mut connection) {
+      kept_versions = count;
+      kept_version_bytes = bytes;
+    }
     // A dry-run preview only when the cutoff parses (a bad date shows no preview, never deletes).
     if let Some(cutoff) = before.as_deref().and_then(parse_cutoff) {
       let count = HistoricalTask::count_before(&mut connection, cutoff).unwrap_or(0);
-      preview = Some(serde_json::json!({ "before": before, "count": count }));
+      let versions = ResultArchiveVersion::count_before(&mut connection, cutoff).unwrap_or(0);
+      preview = Some(serde_json::json!({ "before": before, "count": count, "versions": versions }));
     }
   }
   let global = serde_json::json!({
@@ -97,7 +131,18 @@ pub fn retention_page(
   });
   Ok(Template::render(
     "retention",
-    context! { global, owner: admin.owner, snapshot_rows, oldest, preview, pruned },
+    context! {
+      global,
+      owner: admin.owner,
+      snapshot_rows,
+      oldest,
+      kept_versions,
+      kept_version_bytes,
+      preview,
+      pruned,
+      pruned_versions,
+      archive_gc
+    },
   ))
 }
 
@@ -110,9 +155,9 @@ pub struct PruneForm {
 
 /// Prunes per-task snapshots older than the cutoff (`POST /admin/retention/prune`). Confirmed by
 /// the two-step preview + the form's `confirm()` dialog; **gated** (AdminSession) and **audited**
-/// (the audit fairing records the action + actor). Only `historical_tasks` is touched — run
-/// summaries survive. Redirects back to the screen with the count removed; a malformed cutoff is a
-/// no-op.
+/// (the audit fairing records the action + actor). Only `historical_tasks` and the kept result
+/// versions of the same age are touched — run summaries survive. Redirects back to the screen with
+/// the counts removed; a malformed cutoff is a no-op.
 #[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
 #[post("/admin/retention/prune", data = "<form>")]
 pub fn prune(
@@ -125,20 +170,92 @@ pub fn prune(
   let Some(cutoff) = parse_cutoff(&form.before) else {
     return Ok(Redirect::to("/admin/retention"));
   };
-  let pruned = pool
-    .get()
-    .ok()
-    .and_then(|mut connection| HistoricalTask::prune_before(&mut connection, cutoff).ok())
-    .unwrap_or(0);
+  let (pruned, pruned_versions) = match pool.get() {
+    Ok(mut connection) => {
+      let pruned = HistoricalTask::prune_before(&mut connection, cutoff).unwrap_or(0);
+      let versions =
+        match crate::backend::prune_versions_before(&mut connection, &*storage(), cutoff) {
+          Ok((versions, _)) => versions,
+          Err(error) => {
+            tracing::warn!(%error, "retention prune: kept result versions not fully pruned");
+            0
+          },
+        };
+      (pruned, versions)
+    },
+    Err(_) => (0, 0),
+  };
   // Server-side record of who pruned what (the audit fairing also logs the action + actor +
   // outcome to the DB; this is the operational journal line).
   tracing::info!(
     actor = %admin.owner,
     pruned,
+    pruned_versions,
     before = %form.before,
     "retention prune"
   );
-  Ok(Redirect::to(format!("/admin/retention?pruned={pruned}")))
+  Ok(Redirect::to(format!(
+    "/admin/retention?pruned={pruned}&pruned_versions={pruned_versions}"
+  )))
+}
+
+/// The archive collection form: `delete` is absent for a scan, `true` for the confirmed delete.
+#[derive(FromForm)]
+pub struct ArchiveGcForm {
+  /// delete the collectible archives (otherwise a dry-run scan)
+  pub delete: Option<bool>,
+}
+
+/// Starts a result archive collection from the retention screen (`POST /admin/retention/archives`):
+/// a dry-run scan, or — confirmed by the form's `confirm()` dialog after a scan — the delete.
+/// **Gated** (AdminSession); the collection writes its own audit entry when it deletes. Redirects
+/// to the job's progress page.
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[post("/admin/retention/archives", data = "<form>")]
+pub fn archive_gc_human(
+  form: Form<ArchiveGcForm>,
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Redirect, AdminReject> {
+  let admin = require_admin_to(session, &return_to)?;
+  let delete = form.delete.unwrap_or(false);
+  let uuid = jobs::spawn_archive_gc(pool.inner().clone(), &admin.owner, delete)
+    .map_err(|_| Status::InternalServerError)?;
+  Ok(Redirect::to(format!("/jobs/{uuid}")))
+}
+
+/// Request body for a result archive collection ([`api_archive_gc`]); an empty body is a dry run.
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct ArchiveGcRequest {
+  /// Delete the collectible archives (and the records of deleted tasks) instead of only reporting
+  /// them.
+  #[serde(default)]
+  pub delete: bool,
+}
+
+/// Collects the result archives of deleted corpora, services and sandboxes as a background job —
+/// the agent twin of the retention screen's archive collection and of `cortex gc-archives`. The
+/// job result reports every collectible archive with its size and the bytes a delete would free
+/// (or freed). A dry run unless `delete` is set; a deleting run is audited. **Token-gated**;
+/// returns `202` + the job handle. Debounced.
+#[rocket_okapi::openapi(tag = "Management")]
+#[post("/api/maintenance/archive-gc", format = "json", data = "<request>")]
+pub fn api_archive_gc(
+  request: Json<ArchiveGcRequest>,
+  actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<(Status, Json<MaintenanceAckDto>), Status> {
+  let job_uuid = jobs::spawn_archive_gc(pool.inner().clone(), &actor.owner, request.delete)
+    .map_err(|_| Status::InternalServerError)?;
+  Ok((
+    Status::Accepted,
+    Json(MaintenanceAckDto {
+      job: job_uuid.to_string(),
+      poll: format!("/api/jobs/{job_uuid}"),
+      actor: actor.owner,
+    }),
+  ))
 }
 
 // NOTE (history immutability, owner directive 2026-06-15): the historical tables are append-only as
@@ -147,6 +264,7 @@ pub fn prune(
 // is a gated, audited, confirm-dialog **human admin** operation only (`prune`, above). History
 // stays immutable and reliable for every programmatic consumer.
 
-/// The human retention screen + prune (the agent `api_historical_stats` read twin is mounted via
-/// `frontend::apidoc`; there is intentionally no agent mutation twin — see the note above).
-pub fn routes() -> Vec<Route> { routes![retention_page, prune] }
+/// The human retention screen, prune and archive collection (the agent `api_historical_stats` and
+/// `api_archive_gc` twins are mounted via `frontend::apidoc`; there is intentionally no agent
+/// snapshot-prune twin — see the note above).
+pub fn routes() -> Vec<Route> { routes![retention_page, prune, archive_gc_human] }
@@ -24,7 +24,10 @@ use serde::Serialize;
 
 use std::collections::HashMap;
 
-use crate::backend::{DbPool, list_task_diffs, summary_task_diffs};
+use crate::backend::{
+  CLASS_SEVERITIES, ClassGrain, ClassMove, ClassTrendPoint, DEFAULT_TREND_RUNS, DbPool,
+  MAX_TREND_RUNS, RunRegressions, class_movers, class_trend, list_task_diffs, summary_task_diffs,
+};
 use crate::frontend::actor::{AdminReject, AdminSession, ReturnTo, require_admin_to};
 use crate::frontend::helpers::uri_escape;
 use crate::frontend::params::{MAX_REPORT_OFFSET, MAX_REPORT_PAGE_SIZE};
@@ -68,10 +71,15 @@ pub struct RunDto {
   pub invalid: i32,
   /// Tasks still in progress when the run closed.
   pub in_progress: i32,
+  /// The regression summary against the run before it, computed when the run closed on drain;
+  /// `None` while the run is open, or if it closed without one (superseded by a newer run, or the
+  /// summary failed).
+  pub regressions: Option<RunRegressions>,
 }
 
 impl From<HistoricalRun> for RunDto {
   fn from(run: HistoricalRun) -> RunDto {
+    let regressions = RunRegressions::of(&run);
     RunDto {
       public_id: run.public_id.to_string(),
       id: run.id,
@@ -87,6 +95,7 @@ impl From<HistoricalRun> for RunDto {
       fatal: run.fatal,
       invalid: run.invalid,
       in_progress: run.in_progress,
+      regressions,
     }
   }
 }
@@ -730,6 +739,16 @@ pub fn runs_page(corpus: &str, service: &str, pool: &State<DbPool>) -> Result<Te
       .collect();
   let runs_meta: Vec<RunMetadata> = historical.iter().cloned().map(RunMetadata::from).collect();
   let runs: Vec<RunDto> = historical.into_iter().map(RunDto::from).collect();
+  // The regression summary of the latest completed run, shown as a banner above the table (the
+  // per-row badges cover older runs). `None` if that run closed without one.
+  let latest = runs
+    .iter()
+    .find(|run| run.completed)
+    .and_then(|run| Some((run.start_time.clone(), run.regressions.clone()?)));
+  let latest_regressed = latest
+    .as_ref()
+    .is_some_and(|(_, summary)| summary.regressed());
+  let (latest_started, latest_regressions) = latest.unzip();
   // Run-over-run deltas (newest-first, so row `i` compares against the next-older row `i+1`) so the
   // human table answers "how did this run move the conversion tallies" without the reader
   // subtracting consecutive rows. The agent `/api/runs` feed stays raw (agents diff themselves).
@@ -776,7 +795,327 @@ pub fn runs_page(corpus: &str, service: &str, pool: &State<DbPool>) -> Result<Te
   });
   Ok(Template::render(
     "runs",
-    context! { global, corpus, service, runs, history_serialized },
+    context! {
+      global, corpus, service, runs, history_serialized,
+      latest_started, latest_regressions, latest_regressed,
+    },
+  ))
+}
+
+/// One run of a message-class trend: the run and the class's counts at its end (0 when absent).
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ClassTrendPointDto {
+  /// The run's stable external handle.
+  pub run: String,
+  /// Run start, ISO-8601.
+  pub start_time: String,
+  /// Why the run was initiated.
+  pub description: String,
+  /// Tasks with the class at the end of the run.
+  pub task_count: i64,
+  /// Messages of the class at the end of the run.
+  pub message_count: i64,
+}
+
+impl From<ClassTrendPoint> for ClassTrendPointDto {
+  fn from(point: ClassTrendPoint) -> ClassTrendPointDto {
+    ClassTrendPointDto {
+      run: point.run.public_id,
+      start_time: crate::frontend::helpers::iso_utc(point.run.start_time),
+      description: point.run.description,
+      task_count: point.task_count,
+      message_count: point.message_count,
+    }
+  }
+}
+
+/// How one message class — a `category`, or a `category`/`what` — evolved over the last runs of a
+/// `(corpus, service)` that froze their classes on drain, oldest first.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ClassTrendDto {
+  /// Message severity.
+  pub severity: String,
+  /// Message category.
+  pub category: String,
+  /// Message `what`; `None` for the category as a whole.
+  pub what: Option<String>,
+  /// The class's counts per run, oldest first.
+  pub points: Vec<ClassTrendPointDto>,
+}
+
+/// A message class whose task count moved between the first and the last run of a trend window.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ClassMoveDto {
+  /// Message severity.
+  pub severity: String,
+  /// Message category.
+  pub category: String,
+  /// Message `what`; `None` at the category grain.
+  pub what: Option<String>,
+  /// Tasks with the class in the window's first run.
+  pub first_tasks: i64,
+  /// Tasks with the class in the window's last run.
+  pub last_tasks: i64,
+  /// The change, `last_tasks - first_tasks`.
+  pub change: i64,
+}
+
+impl From<ClassMove> for ClassMoveDto {
+  fn from(class: ClassMove) -> ClassMoveDto {
+    let change = class.change();
+    ClassMoveDto {
+      severity: class.severity,
+      category: class.category,
+      what: class.what,
+      first_tasks: class.first_tasks,
+      last_tasks: class.last_tasks,
+      change,
+    }
+  }
+}
+
+/// The fastest-growing and fastest-shrinking message classes of a `(corpus, service)` over its last
+/// runs that froze their classes, comparing the window's first run with its last.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ClassMoversDto {
+  /// The grain compared: `category` or `what`.
+  pub grain: String,
+  /// The severity the classes were narrowed to, if any.
+  pub severity: Option<String>,
+  /// Runs in the window.
+  pub run_count: usize,
+  /// The window's first run (`public_id`); `None` without runs.
+  pub first_run: Option<String>,
+  /// The window's last run (`public_id`).
+  pub last_run: Option<String>,
+  /// The classes that grew the most, largest growth first.
+  pub growing: Vec<ClassMoveDto>,
+  /// The classes that shrank the most, largest drop first.
+  pub shrinking: Vec<ClassMoveDto>,
+}
+
+/// Movers listed per direction by default.
+const DEFAULT_MOVERS: i64 = 20;
+
+/// The most movers listed per direction.
+const MAX_MOVERS: i64 = 100;
+
+/// Parses an optional trend severity (one of [`CLASS_SEVERITIES`]), mapping an unknown value to
+/// `400`. Absent or empty means "every severity".
+fn parse_trend_severity(raw: Option<&str>) -> Result<Option<&str>, Status> {
+  match raw.map(str::trim).filter(|value| !value.is_empty()) {
+    None => Ok(None),
+    Some(value) if CLASS_SEVERITIES.contains(&value) => Ok(Some(value)),
+    Some(_) => Err(Status::BadRequest),
+  }
+}
+
+/// Loads the trend of one class — the shared core of the trend API and screen. `400` without a
+/// severity or category, or on an unknown severity; an empty `what` reads the category grain.
+fn load_class_trend(
+  connection: &mut diesel::PgConnection,
+  corpus: &Corpus,
+  service: &Service,
+  severity: Option<&str>,
+  category: Option<&str>,
+  what: Option<&str>,
+  runs: Option<i64>,
+) -> Result<ClassTrendDto, Status> {
+  let severity = parse_trend_severity(severity)?.ok_or(Status::BadRequest)?;
+  let category = category
+    .filter(|category| !category.is_empty())
+    .ok_or(Status::BadRequest)?;
+  let what = what.filter(|what| !what.is_empty());
+  let points = class_trend(
+    connection,
+    corpus.id,
+    service.id,
+    severity,
+    category,
+    what,
+    runs.unwrap_or(DEFAULT_TREND_RUNS),
+  )
+  .map_err(|_| Status::InternalServerError)?;
+  Ok(ClassTrendDto {
+    severity: severity.to_string(),
+    category: category.to_string(),
+    what: what.map(str::to_string),
+    points: points.into_iter().map(ClassTrendPointDto::from).collect(),
+  })
+}
+
+/// Loads the movers of a trend window — the shared core of the movers API and the trends screen.
+/// `400` on an unknown grain or severity.
+fn load_class_movers(
+  connection: &mut diesel::PgConnection,
+  corpus: &Corpus,
+  service: &Service,
+  grain: Option<&str>,
+  severity: Option<&str>,
+  runs: Option<i64>,
+  limit: Option<i64>,
+) -> Result<ClassMoversDto, Status> {
+  let grain = match grain.filter(|grain| !grain.is_empty()) {
+    None => ClassGrain::What,
+    Some(key) => ClassGrain::from_key(key).ok_or(Status::BadRequest)?,
+  };
+  let severity = parse_trend_severity(severity)?;
+  let movers = class_movers(
+    connection,
+    corpus.id,
+    service.id,
+    grain,
+    severity,
+    runs.unwrap_or(DEFAULT_TREND_RUNS),
+    limit.unwrap_or(DEFAULT_MOVERS).clamp(1, MAX_MOVERS),
+  )
+  .map_err(|_| Status::InternalServerError)?;
+  Ok(ClassMoversDto {
+    grain: grain.to_key().to_string(),
+    severity: severity.map(str::to_string),
+    run_count: movers.runs.len(),
+    first_run: movers.runs.first().map(|run| run.public_id.clone()),
+    last_run: movers.runs.last().map(|run| run.public_id.clone()),
+    growing: movers.growing.into_iter().map(ClassMoveDto::from).collect(),
+    shrinking: movers
+      .shrinking
+      .into_iter()
+      .map(ClassMoveDto::from)
+      .collect(),
+  })
+}
+
+/// How one message class evolved over the last `runs` (default 20, max 200) runs of a
+/// `(corpus, service)` that froze their classes on drain: `severity` (warning, error, fatal or
+/// invalid) and `category` are required; add `what` for a single class, or leave it out for the
+/// whole category. `400` on a missing or unknown severity or a missing category, `404` if the
+/// corpus/service is unknown.
+#[rocket_okapi::openapi(tag = "Runs")]
+#[get("/api/runs/<corpus>/<service>/trend?<severity>&<category>&<what>&<runs>")]
+pub fn api_class_trend(
+  corpus: &str,
+  service: &str,
+  severity: Option<&str>,
+  category: Option<&str>,
+  what: Option<&str>,
+  runs: Option<i64>,
+  pool: &State<DbPool>,
+) -> Result<Json<ClassTrendDto>, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let (corpus, service) = resolve(corpus, service, &mut connection)?;
+  Ok(Json(load_class_trend(
+    &mut connection,
+    &corpus,
+    &service,
+    severity,
+    category,
+    what,
+    runs,
+  )?))
+}
+
+/// The message classes of a `(corpus, service)` that grew and shrank the most between the first
+/// and the last of its last `runs` (default 20, max 200) runs that froze their classes, at the
+/// `what` (default) or `category` `grain`, optionally narrowed to one `severity`; at most `limit`
+/// (default 20, max 100) per direction. `400` on an unknown grain or severity, `404` if the
+/// corpus/service is unknown.
+#[rocket_okapi::openapi(tag = "Runs")]
+#[get("/api/runs/<corpus>/<service>/movers?<grain>&<severity>&<runs>&<limit>")]
+pub fn api_class_movers(
+  corpus: &str,
+  service: &str,
+  grain: Option<&str>,
+  severity: Option<&str>,
+  runs: Option<i64>,
+  limit: Option<i64>,
+  pool: &State<DbPool>,
+) -> Result<Json<ClassMoversDto>, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let (corpus, service) = resolve(corpus, service, &mut connection)?;
+  Ok(Json(load_class_movers(
+    &mut connection,
+    &corpus,
+    &service,
+    grain,
+    severity,
+    runs,
+    limit,
+  )?))
+}
+
+/// The message-class trends screen of a `(corpus, service)`: the fastest-growing and
+/// fastest-shrinking classes over the last runs, and — once a class is picked (`severity` +
+/// `category`, optionally `what`) — its task count charted run by run. The human twin of the trend
+/// and movers APIs.
+#[allow(clippy::too_many_arguments)]
+#[get("/runs/<corpus>/<service>/trends?<severity>&<category>&<what>&<grain>&<runs>")]
+pub fn runs_trends_page(
+  corpus: &str,
+  service: &str,
+  severity: Option<&str>,
+  category: Option<&str>,
+  what: Option<&str>,
+  grain: Option<&str>,
+  runs: Option<i64>,
+  pool: &State<DbPool>,
+) -> Result<Template, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let (corpus_record, service_record) = resolve(corpus, service, &mut connection)?;
+  let runs = runs.unwrap_or(DEFAULT_TREND_RUNS).clamp(1, MAX_TREND_RUNS);
+  let movers = load_class_movers(
+    &mut connection,
+    &corpus_record,
+    &service_record,
+    grain,
+    severity,
+    Some(runs),
+    None,
+  )?;
+  // A class is charted once both its severity and category are picked.
+  let picked = |value: Option<&str>| value.is_some_and(|value| !value.is_empty());
+  let trend = if picked(severity) && picked(category) {
+    Some(load_class_trend(
+      &mut connection,
+      &corpus_record,
+      &service_record,
+      severity,
+      category,
+      what,
+      Some(runs),
+    )?)
+  } else {
+    None
+  };
+  // Embedded in a `<script>` block, escaped as the history chart's data is (run descriptions are
+  // user-controlled).
+  let trend_serialized = trend
+    .as_ref()
+    .map(|trend| {
+      serde_json::to_string(&trend.points)
+        .unwrap_or_default()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+    })
+    .unwrap_or_default();
+  let global = serde_json::json!({
+    "title": format!("Message trends · {service} / {corpus}"),
+    "description": format!("Message-class trends of service {service} over corpus {corpus}"),
+    "service_name": service,
+    "corpus_name": corpus,
+    "corpus_name_uri": uri_escape(Some(corpus.to_string())).unwrap_or_default(),
+    "service_name_uri": uri_escape(Some(service.to_string())).unwrap_or_default(),
+  });
+  Ok(Template::render(
+    "runs-trends",
+    context! {
+      global, corpus, service, movers, trend, trend_serialized, runs,
+      severities: CLASS_SEVERITIES,
+      form_severity: severity.unwrap_or_default(),
+      form_category: category.unwrap_or_default(),
+      form_what: what.unwrap_or_default(),
+      form_grain: grain.filter(|grain| !grain.is_empty()).unwrap_or("what"),
+    },
   ))
 }
 
@@ -798,6 +1137,7 @@ pub fn routes() -> Vec<Route> {
     all_runs_page,
     runs_tasks_page,
     runs_diff_page,
+    runs_trends_page,
     runs_page,
     history_page
   ]
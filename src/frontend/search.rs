@@ -0,0 +1,260 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Message search** — the full-text search over conversion messages (`backend::search_messages`)
+//! as an agent endpoint (`GET /api/search/messages`) and a human screen (`/search`, reached from
+//! the search box of the report pages). Narrowed to a `(corpus, service)`, a search's results can
+//! be carved into a sandbox or rerun as they are: both take the same `text` query.
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use rocket_dyn_templates::Template;
+use serde::Serialize;
+
+use crate::backend::{
+  DbPool, MessageHit, MessageSearch, highlights, search_messages, validate_message_text,
+};
+use crate::frontend::actor::AdminSession;
+use crate::frontend::params::{MAX_REPORT_OFFSET, MAX_REPORT_PAGE_SIZE};
+use crate::helpers::entry_document_name;
+use crate::models::{Corpus, Service};
+
+/// A stretch of a matching message's details: matched text or the text around it.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct HighlightDto {
+  /// The text.
+  pub text: String,
+  /// Whether it matched the query.
+  pub matched: bool,
+}
+
+/// One message matching a search, with the document it belongs to.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct MessageHitDto {
+  /// Corpus name.
+  pub corpus: String,
+  /// Service name.
+  pub service: String,
+  /// The document's short name — feed it to `GET /api/corpus/<c>/<svc>/document/<name>`.
+  pub name: String,
+  /// The task id.
+  pub task_id: i64,
+  /// Message severity.
+  pub severity: String,
+  /// Message category.
+  pub category: String,
+  /// Message `what`.
+  pub what: String,
+  /// The fragments of the message's details around the matches, split into matched and unmatched
+  /// stretches.
+  pub highlights: Vec<HighlightDto>,
+}
+
+impl From<MessageHit> for MessageHitDto {
+  fn from(hit: MessageHit) -> Self {
+    MessageHitDto {
+      name: entry_document_name(&hit.entry),
+      highlights: highlights(&hit.headline)
+        .into_iter()
+        .map(|stretch| HighlightDto {
+          text: stretch.text,
+          matched: stretch.matched,
+        })
+        .collect(),
+      corpus: hit.corpus,
+      service: hit.service,
+      task_id: hit.task_id,
+      severity: hit.severity,
+      category: hit.category,
+      what: hit.what,
+    }
+  }
+}
+
+/// A page of full-text search results over the conversion messages.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct MessageSearchDto {
+  /// The query searched for.
+  pub q: String,
+  /// The corpus searched, if narrowed to one.
+  pub corpus: Option<String>,
+  /// The service searched, if narrowed to one.
+  pub service: Option<String>,
+  /// The message severity searched, if narrowed to one.
+  pub severity: Option<String>,
+  /// The message category searched, if narrowed to one.
+  pub category: Option<String>,
+  /// Matching messages in total.
+  pub total_messages: i64,
+  /// Distinct documents (tasks) with a matching message — what a sandbox carve or a rerun of the
+  /// same query would take.
+  pub total_tasks: i64,
+  /// Pagination offset echoed back.
+  pub offset: i64,
+  /// Page size echoed back (the cap actually applied).
+  pub page_size: i64,
+  /// The messages of this page, by document.
+  pub hits: Vec<MessageHitDto>,
+}
+
+/// The search scope as resolved records: each name given must exist (`404` otherwise).
+fn resolve_scope(
+  corpus: Option<&str>,
+  service: Option<&str>,
+  connection: &mut diesel::PgConnection,
+) -> Result<(Option<Corpus>, Option<Service>), Status> {
+  let corpus = match corpus {
+    Some(name) => Some(Corpus::find_by_name(name, connection).map_err(|_| Status::NotFound)?),
+    None => None,
+  };
+  let service = match service {
+    Some(name) => Some(Service::find_by_name(name, connection).map_err(|_| Status::NotFound)?),
+    None => None,
+  };
+  Ok((corpus, service))
+}
+
+/// Blank query parameters (an empty field of the search form) are no narrowing.
+fn given(value: Option<&str>) -> Option<&str> { value.map(str::trim).filter(|v| !v.is_empty()) }
+
+/// Builds the [`MessageSearchDto`] — shared by the agent endpoint and the search screen. `400` on a
+/// blank or overlong query, an unknown severity, or an offset beyond `MAX_REPORT_OFFSET`; `503` if
+/// the search overruns its budget.
+#[allow(clippy::too_many_arguments)]
+fn message_search(
+  q: &str,
+  corpus: Option<&Corpus>,
+  service: Option<&Service>,
+  severity: Option<&str>,
+  category: Option<&str>,
+  offset: Option<i64>,
+  page_size: Option<i64>,
+  connection: &mut diesel::PgConnection,
+) -> Result<MessageSearchDto, Status> {
+  let text = validate_message_text(q).map_err(|_| Status::BadRequest)?;
+  let offset = offset.unwrap_or(0).max(0);
+  if offset > MAX_REPORT_OFFSET {
+    return Err(Status::BadRequest);
+  }
+  let page_size = page_size.unwrap_or(100).clamp(1, MAX_REPORT_PAGE_SIZE);
+  let search = MessageSearch {
+    text: text.to_string(),
+    corpus_id: corpus.map(|corpus| corpus.id),
+    service_id: service.map(|service| service.id),
+    severity: severity.map(str::to_string),
+    category: category.map(str::to_string),
+  };
+  let report = search_messages(connection, &search, page_size, offset)
+    .map_err(|_| Status::ServiceUnavailable)?
+    .ok_or(Status::BadRequest)?;
+  let hits = report.hits.into_iter().map(MessageHitDto::from).collect();
+  Ok(MessageSearchDto {
+    q: text.to_string(),
+    corpus: corpus.map(|corpus| corpus.name.clone()),
+    service: service.map(|service| service.name.clone()),
+    severity: search.severity,
+    category: search.category,
+    total_messages: report.total_messages,
+    total_tasks: report.total_tasks,
+    offset,
+    page_size,
+    hits,
+  })
+}
+
+/// Full-text search over the conversion messages: the messages whose `what` or `details` match
+/// `q` (web-search syntax — `"a phrase"`, `or`, `-word`; TeX tokens such as `\usepackage{foo}`
+/// match word by word), optionally narrowed to a `corpus`, `service`, message `severity` and
+/// `category`. Each hit carries its document and the highlighted fragments of its details; the
+/// totals count matching messages and documents. Paginated with `offset`/`page_size` (default 100,
+/// max 1000). The same `q` scopes a sandbox carve (`text` in `POST /api/corpora/<c>/sandbox`) and a
+/// rerun (`text` in `POST /api/reports/<c>/<s>/rerun`). `400` on a blank query, an unknown severity
+/// or too deep an offset, `404` on an unknown corpus/service, `503` if the search overruns its
+/// budget.
+#[rocket_okapi::openapi(tag = "Reports")]
+#[get("/api/search/messages?<q>&<corpus>&<service>&<severity>&<category>&<offset>&<page_size>")]
+#[allow(clippy::too_many_arguments)]
+pub fn api_search_messages(
+  q: Option<&str>,
+  corpus: Option<&str>,
+  service: Option<&str>,
+  severity: Option<&str>,
+  category: Option<&str>,
+  offset: Option<i64>,
+  page_size: Option<i64>,
+  pool: &State<DbPool>,
+) -> Result<Json<MessageSearchDto>, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let (corpus, service) = resolve_scope(given(corpus), given(service), &mut connection)?;
+  Ok(Json(message_search(
+    q.unwrap_or_default(),
+    corpus.as_ref(),
+    service.as_ref(),
+    given(severity),
+    given(category),
+    offset,
+    page_size,
+    &mut connection,
+  )?))
+}
+
+/// The message search **screen** (HTML twin of [`api_search_messages`]), reached from the search
+/// box of the report pages. Without a query it shows just the search form. Narrowed to a `(corpus,
+/// service)`, a signed-in admin can carve the matching documents into a sandbox or rerun them.
+#[allow(clippy::too_many_arguments)]
+#[get("/search?<q>&<corpus>&<service>&<severity>&<category>&<offset>&<page_size>")]
+pub fn search_page(
+  q: Option<&str>,
+  corpus: Option<&str>,
+  service: Option<&str>,
+  severity: Option<&str>,
+  category: Option<&str>,
+  offset: Option<i64>,
+  page_size: Option<i64>,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Template, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let (corpus, service) = resolve_scope(given(corpus), given(service), &mut connection)?;
+  let report = match given(q) {
+    Some(q) => Some(message_search(
+      q,
+      corpus.as_ref(),
+      service.as_ref(),
+      given(severity),
+      given(category),
+      offset,
+      page_size,
+      &mut connection,
+    )?),
+    None => None,
+  };
+  let global = serde_json::json!({
+    "title": match &report {
+      Some(report) => format!("“{}” — message search", report.q),
+      None => "Message search".to_string(),
+    },
+    "description": "Full-text search over conversion messages",
+  });
+  // The form's fields echo the query, found or not.
+  let form = serde_json::json!({
+    "q": given(q).unwrap_or_default(),
+    "corpus": corpus.as_ref().map(|corpus| corpus.name.as_str()).unwrap_or_default(),
+    "service": service.as_ref().map(|service| service.name.as_str()).unwrap_or_default(),
+    "service_id": service.as_ref().map(|service| service.id),
+    "severity": given(severity).unwrap_or_default(),
+    "category": given(category).unwrap_or_default(),
+  });
+  Ok(Template::render(
+    "message-search",
+    rocket_dyn_templates::context! { global, form, report, is_admin: session.is_some() },
+  ))
+}
+
+/// The search screen's routes (the agent endpoint is mounted via `frontend::apidoc`).
+pub fn routes() -> Vec<Route> { routes![search_page] }
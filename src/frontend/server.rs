@@ -93,7 +93,10 @@ pub fn mount_api_with(
     .mount("/", crate::frontend::admin::routes())
     .mount("/", crate::frontend::audit::routes())
     .mount("/", crate::frontend::sessions::routes())
+    .mount("/", crate::frontend::dead_letters::routes())
+    .mount("/", crate::frontend::worker_keys::routes())
     .mount("/", crate::frontend::retention::routes())
+    .mount("/", crate::frontend::search::routes())
     .mount("/", crate::frontend::metrics::routes())
     .mount("/", crate::frontend::webauthn::routes())
     .register("/", crate::frontend::catchers::catchers())
@@ -31,12 +31,10 @@ use crate::frontend::actor::{Actor, AdminReject, AdminSession, ReturnTo, require
 use crate::frontend::corpora::start_activate;
 use crate::frontend::helpers::{decorate_uri_encodings, group_thousands};
 use crate::frontend::params::{MAX_REPORT_OFFSET, MAX_REPORT_PAGE_SIZE, TemplateContext};
-use crate::models::{Corpus, NewService, Service, WorkerMetadata};
-
-/// Magic service-id ceiling: ids `1=init` and `2=import` are infrastructure and must never be
-/// destroyed (deleting `import` would wipe a corpus's document registry). Mirrors the same guard in
-/// [`crate::frontend::corpora`].
-const IMPORT_SERVICE_ID: i32 = 2;
+use crate::helpers::log_parsers::{self, DEFAULT_LOG_PARSER};
+use crate::models::{
+  Corpus, IMPORT_SERVICE_ID, NewService, Service, WorkerMetadata, iso_utc_system,
+};
 
 /// A registered service as exposed over the API/UI — the service-registry view. `name` is the
 /// stable external handle used by every service route.
@@ -62,6 +60,52 @@ pub struct ServiceDto {
   /// Per-service lease / visibility-timeout override in seconds (D-17), or `null` to use the
   /// global `dispatcher.lease_timeout_seconds`. Set via `PUT /api/services/<service>/lease`.
   pub lease_timeout_seconds: Option<i32>,
+  /// Per-service cap on lease renewals (worker "still working" heartbeats) per lease, or `null` to
+  /// use the global `dispatcher.max_lease_renewals`. Set via
+  /// `PUT /api/services/<service>/renewals`.
+  pub max_lease_renewals: Option<i32>,
+  /// How many previous result archives per document this service keeps (tagged with the run that
+  /// wrote them) for side-by-side comparison; `0` overwrites in place. Set via
+  /// `PUT /api/services/<service>/versions`.
+  pub keep_result_versions: i32,
+  /// Key of the log parser that reads this service's worker logs (`latexml` unless set; see
+  /// `helpers::log_parsers`). Set via `PUT /api/services/<service>/log-parser`.
+  pub log_parser: String,
+  /// Pipeline upstream: the registered service whose result archive this service consumes (its
+  /// `inputconverter`, unless that is the magic `import`), or `null` for a source-reading service.
+  pub upstream: Option<String>,
+  /// Pipeline downstream: the services that consume this service's result archives.
+  pub downstream: Vec<String>,
+  /// What happens to this service's results when its upstream reruns a document: `rerun` (reset to
+  /// await the fresh upstream result) or `keep`. Set via `PUT /api/services/<service>/pipeline`.
+  pub on_upstream_rerun: String,
+}
+
+impl ServiceDto {
+  /// The registry view of `services`, each with its pipeline `downstream` resolved from the same
+  /// list (the registry is small, so one pass over it beats a query per service).
+  pub fn registry(services: Vec<Service>) -> Vec<ServiceDto> {
+    let edges: Vec<(String, String)> = services
+      .iter()
+      .filter_map(|service| {
+        service
+          .upstream()
+          .map(|upstream| (upstream.to_string(), service.name.clone()))
+      })
+      .collect();
+    services
+      .into_iter()
+      .map(|service| {
+        let mut dto = ServiceDto::from(service);
+        dto.downstream = edges
+          .iter()
+          .filter(|(upstream, _)| *upstream == dto.name)
+          .map(|(_, downstream)| downstream.clone())
+          .collect();
+        dto
+      })
+      .collect()
+  }
 }
 
 impl From<Service> for ServiceDto {
@@ -76,6 +120,12 @@ impl From<Service> for ServiceDto {
       complex: service.complex,
       description: service.description,
       lease_timeout_seconds: service.lease_timeout_seconds,
+      max_lease_renewals: service.max_lease_renewals,
+      keep_result_versions: service.keep_result_versions,
+      log_parser: service.log_parser_key().to_string(),
+      upstream: service.upstream().map(str::to_string),
+      downstream: Vec::new(),
+      on_upstream_rerun: service.on_upstream_rerun,
     }
   }
 }
@@ -92,6 +142,9 @@ pub struct WorkerDto {
   /// Dispatched-but-not-yet-returned tasks (`dispatched - returned`); a large or growing value
   /// flags a stuck or struggling worker.
   pub in_flight: i32,
+  /// Lease renewals accepted from this worker's "still working" heartbeats — high for a worker
+  /// that relies on renewals to finish slow conversions.
+  pub total_renewals: i32,
   /// The id of the most recent task dispatched to this worker.
   pub last_dispatched_task_id: i64,
   /// The id of the most recent task this worker returned (`None` if it never has).
@@ -105,6 +158,29 @@ pub struct WorkerDto {
   /// Whether the worker has been active within the last minute — the at-a-glance liveness flag
   /// (matches the human screen's `fresh`/`stale` threshold).
   pub fresh: bool,
+  /// What the worker declared in its registration frame (`None` if it never registered).
+  pub registration: Option<WorkerRegistrationDto>,
+}
+
+/// A worker's registration declaration, and the dispatcher's verdict on it.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct WorkerRegistrationDto {
+  /// When the worker last registered, as an RFC 3339 UTC timestamp.
+  pub registered_at: String,
+  /// The service version the worker declared (compared with the registered `Service.version`).
+  pub version: Option<f32>,
+  /// The worker's host.
+  pub host: Option<String>,
+  /// The git sha of the worker build.
+  pub git_sha: Option<String>,
+  /// The most tasks the worker takes at once.
+  pub concurrency: Option<i32>,
+  /// The largest input archive, in bytes, the worker takes.
+  pub max_input_bytes: Option<i64>,
+  /// The input formats the worker accepts (empty = any).
+  pub input_formats: Vec<String>,
+  /// Why the dispatcher refuses to lease to the worker (`None` = it leases normally).
+  pub refusal: Option<String>,
 }
 
 impl From<WorkerMetadata> for WorkerDto {
@@ -124,10 +200,23 @@ impl From<WorkerMetadata> for WorkerDto {
       name: worker.name,
       total_dispatched: worker.total_dispatched,
       total_returned: worker.total_returned,
+      total_renewals: worker.total_renewals,
       last_dispatched_task_id: worker.last_dispatched_task_id,
       last_returned_task_id: worker.last_returned_task_id,
       seconds_since_last_active,
       fresh: seconds_since_last_active < 60,
+      registration: worker
+        .registered_at
+        .map(|registered_at| WorkerRegistrationDto {
+          registered_at: iso_utc_system(registered_at),
+          version: worker.declared_version,
+          host: worker.host,
+          git_sha: worker.git_sha,
+          concurrency: worker.concurrency,
+          max_input_bytes: worker.max_input_bytes,
+          input_formats: worker.input_formats,
+          refusal: worker.refusal,
+        }),
     }
   }
 }
@@ -137,6 +226,20 @@ fn resolve(service: &str, connection: &mut diesel::PgConnection) -> Result<Servi
   Service::find_by_name(service, connection).map_err(|_| Status::NotFound)
 }
 
+/// A single service's [`ServiceDto`], with its pipeline `downstream` resolved.
+fn service_dto(service: Service, connection: &mut diesel::PgConnection) -> ServiceDto {
+  let downstream = service
+    .downstream(connection)
+    .unwrap_or_default()
+    .into_iter()
+    .map(|service| service.name)
+    .collect();
+  ServiceDto {
+    downstream,
+    ..ServiceDto::from(service)
+  }
+}
+
 /// The service registry (agent twin of the registry screen): every registered service. `503` if the
 /// pool is exhausted.
 #[rocket_okapi::openapi(tag = "Services")]
@@ -144,7 +247,7 @@ fn resolve(service: &str, connection: &mut diesel::PgConnection) -> Result<Servi
 pub fn api_services(_caller: Actor, pool: &State<DbPool>) -> Result<Json<Vec<ServiceDto>>, Status> {
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
   let services = Service::all(&mut connection).unwrap_or_default();
-  Ok(Json(services.into_iter().map(ServiceDto::from).collect()))
+  Ok(Json(ServiceDto::registry(services)))
 }
 
 /// Inserts a new service definition (`409` if the name is taken). Shared by the agent endpoint and
@@ -160,6 +263,14 @@ fn insert_service(pool: &DbPool, mut service: NewService) -> Result<(), Status>
   if Service::find_by_name(&service.name, &mut connection).is_ok() {
     return Err(Status::Conflict);
   }
+  // The converter is the service's pipeline upstream: it must name a registered service (or the
+  // magic `import`, i.e. the document source) — never the service itself.
+  if let Some(converter) = service.inputconverter.as_deref()
+    && converter != "import"
+    && (converter == service.name || Service::find_by_name(converter, &mut connection).is_err())
+  {
+    return Err(Status::BadRequest);
+  }
   service
     .create(&mut connection)
     .map_err(|_| Status::InternalServerError)?;
@@ -183,13 +294,25 @@ pub struct ServiceRegisterRequest {
   pub complex: bool,
   /// Optional human-readable description.
   pub description: Option<String>,
+  /// Optional log parser key — the format its workers' `cortex.log` is in (`latexml`,
+  /// `json-lines` or `exit-code`; see `helpers::log_parsers`). Defaults to `latexml`.
+  pub log_parser: Option<String>,
+}
+
+/// A requested log parser key, normalized: blank or the default become `None`. `Err` (`422`) if the
+/// key is not a registered parser. Shared by registration and the log-parser setters.
+fn log_parser_choice(key: Option<String>) -> Result<Option<String>, Status> {
+  match key.filter(|key| !key.trim().is_empty() && key != DEFAULT_LOG_PARSER) {
+    Some(key) if log_parsers::find(&key).is_none() => Err(Status::UnprocessableEntity),
+    choice => Ok(choice),
+  }
 }
 
 /// Registers (defines) a new service in the registry — the agent twin of the "Add a service" screen
 /// (`/services/new`, `create_service_human`). **Token-gated** via the [`Actor`] guard; `401`
-/// without a valid token, `409` if the service name already exists, `201` with the service on
-/// success. (This *defines* a service; activating it on a corpus — creating tasks — is `POST
-/// /api/corpora/<c>/services/<s>`.)
+/// without a valid token, `409` if the service name already exists, `422` if the `log_parser` is
+/// not a registered one, `201` with the service on success. (This *defines* a service; activating
+/// it on a corpus — creating tasks — is `POST /api/corpora/<c>/services/<s>`.)
 #[rocket_okapi::openapi(tag = "Services")]
 #[post("/api/services", format = "json", data = "<request>")]
 pub fn register_service(
@@ -199,6 +322,7 @@ pub fn register_service(
 ) -> Result<(Status, Json<ServiceDto>), Status> {
   let request = request.into_inner();
   let name = request.name.clone();
+  let log_parser = log_parser_choice(request.log_parser)?;
   insert_service(
     pool,
     NewService {
@@ -212,9 +336,15 @@ pub fn register_service(
     },
   )?;
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
-  let service =
+  let mut service =
     Service::find_by_name(&name, &mut connection).map_err(|_| Status::InternalServerError)?;
-  Ok((Status::Created, Json(ServiceDto::from(service))))
+  if log_parser.is_some() {
+    service
+      .set_log_parser(log_parser.as_deref(), &mut connection)
+      .map_err(|_| Status::InternalServerError)?;
+    service.log_parser = log_parser;
+  }
+  Ok((Status::Created, Json(service_dto(service, &mut connection))))
 }
 
 /// Request body for setting a service's per-service lease timeout (D-17).
@@ -253,7 +383,7 @@ pub fn set_service_lease(
     .set_lease_timeout(seconds, &mut connection)
     .map_err(|_| Status::InternalServerError)?;
   let updated = resolve(service, &mut connection)?;
-  Ok(Json(ServiceDto::from(updated)))
+  Ok(Json(service_dto(updated, &mut connection)))
 }
 
 /// Fields of the registry screen's inline "set lease" form. A blank value parses to `None` (clear).
@@ -289,6 +419,285 @@ pub fn set_service_lease_human(
   Ok(Redirect::to("/services"))
 }
 
+/// Request body for setting a service's per-service lease-renewal cap.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RenewalsUpdateRequest {
+  /// How many times one lease may be extended by the worker's heartbeat (`0` disables renewal), or
+  /// `null` to clear the override and fall back to the global `dispatcher.max_lease_renewals`.
+  pub max_renewals: Option<i32>,
+}
+
+/// Sets (or clears) a service's cap on lease renewals — the agent twin of the registry screen's
+/// inline "renewals" form. **Token-gated** via the [`Actor`] guard (`401` without a valid token);
+/// `400` if `max_renewals` is negative, `404` if the service is unknown, `200` with the updated
+/// [`ServiceDto`] on success. Takes effect on the next dispatch.
+#[rocket_okapi::openapi(tag = "Services")]
+#[put(
+  "/api/services/<service>/renewals",
+  format = "json",
+  data = "<request>"
+)]
+pub fn set_service_renewals(
+  service: &str,
+  request: Json<RenewalsUpdateRequest>,
+  _actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<ServiceDto>, Status> {
+  let max_renewals = request.into_inner().max_renewals;
+  if matches!(max_renewals, Some(value) if value < 0) {
+    return Err(Status::BadRequest);
+  }
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record = resolve(service, &mut connection)?;
+  service_record
+    .set_max_lease_renewals(max_renewals, &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  let updated = resolve(service, &mut connection)?;
+  Ok(Json(service_dto(updated, &mut connection)))
+}
+
+/// Fields of the registry screen's inline "renewals" form. A blank value parses to `None` (clear).
+#[derive(FromForm)]
+pub struct SetRenewalsForm {
+  /// New renewal cap; blank clears the per-service override (the global default applies).
+  pub max_renewals: Option<i32>,
+}
+
+/// The human twin of [`set_service_renewals`]: the registry screen's inline renewal-cap form.
+/// **Gated by the signed-in [`AdminSession`] cookie** (anonymous → sign-in). A blank value clears
+/// the override; a negative value is `400`. Redirects back to `/services`; `404` if unknown.
+#[post("/services/<service>/renewals", data = "<form>")]
+pub fn set_service_renewals_human(
+  service: &str,
+  form: Form<SetRenewalsForm>,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Redirect, Status> {
+  if session.is_none() {
+    return Ok(Redirect::to("/admin/login"));
+  }
+  let max_renewals = form.into_inner().max_renewals;
+  if matches!(max_renewals, Some(value) if value < 0) {
+    return Err(Status::BadRequest);
+  }
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record =
+    Service::find_by_name(service, &mut connection).map_err(|_| Status::NotFound)?;
+  service_record
+    .set_max_lease_renewals(max_renewals, &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  Ok(Redirect::to("/services"))
+}
+
+/// Request body for setting how many previous result archives a service keeps.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct VersionsUpdateRequest {
+  /// How many previous result archives to keep per document (`0` overwrites results in place).
+  pub keep_result_versions: i32,
+}
+
+/// Sets how many previous result archives per document a service keeps — the agent twin of the
+/// registry screen's inline "versions" form. **Token-gated** via the [`Actor`] guard (`401` without
+/// a valid token); `400` if the count is negative, `404` if the service is unknown, `200` with the
+/// updated [`ServiceDto`] on success. Applies to results received after the change (lowering it
+/// prunes each document's surplus versions at its next result).
+#[rocket_okapi::openapi(tag = "Services")]
+#[put(
+  "/api/services/<service>/versions",
+  format = "json",
+  data = "<request>"
+)]
+pub fn set_service_versions(
+  service: &str,
+  request: Json<VersionsUpdateRequest>,
+  _actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<ServiceDto>, Status> {
+  let versions = request.into_inner().keep_result_versions;
+  if versions < 0 {
+    return Err(Status::BadRequest);
+  }
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record = resolve(service, &mut connection)?;
+  service_record
+    .set_keep_result_versions(versions, &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  let updated = resolve(service, &mut connection)?;
+  Ok(Json(service_dto(updated, &mut connection)))
+}
+
+/// Fields of the registry screen's inline "versions" form.
+#[derive(FromForm)]
+pub struct SetVersionsForm {
+  /// Previous result archives to keep per document; `0` overwrites in place.
+  pub keep_result_versions: i32,
+}
+
+/// The human twin of [`set_service_versions`]: the registry screen's inline result-versions form.
+/// **Gated by the signed-in [`AdminSession`] cookie** (anonymous → sign-in). A negative value is
+/// `400`. Redirects back to `/services`; `404` if unknown.
+#[post("/services/<service>/versions", data = "<form>")]
+pub fn set_service_versions_human(
+  service: &str,
+  form: Form<SetVersionsForm>,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Redirect, Status> {
+  if session.is_none() {
+    return Ok(Redirect::to("/admin/login"));
+  }
+  let versions = form.into_inner().keep_result_versions;
+  if versions < 0 {
+    return Err(Status::BadRequest);
+  }
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record =
+    Service::find_by_name(service, &mut connection).map_err(|_| Status::NotFound)?;
+  service_record
+    .set_keep_result_versions(versions, &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  Ok(Redirect::to("/services"))
+}
+
+/// Request body for selecting a service's log parser.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LogParserUpdateRequest {
+  /// Key of the log parser (`latexml`, `json-lines` or `exit-code`), or `null` to restore the
+  /// default (`latexml`).
+  pub log_parser: Option<String>,
+}
+
+/// Selects the log parser that reads a service's worker logs — the agent twin of the registry
+/// screen's inline "log format" form. **Token-gated** via the [`Actor`] guard (`401` without a
+/// valid token); `422` if the key is not a registered parser, `404` if the service is unknown,
+/// `200` with the updated [`ServiceDto`] on success. Applies to results received once the
+/// dispatcher's service cache refreshes; results already stored keep the messages they were
+/// committed with.
+#[rocket_okapi::openapi(tag = "Services")]
+#[put(
+  "/api/services/<service>/log-parser",
+  format = "json",
+  data = "<request>"
+)]
+pub fn set_service_log_parser(
+  service: &str,
+  request: Json<LogParserUpdateRequest>,
+  _actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<ServiceDto>, Status> {
+  let log_parser = log_parser_choice(request.into_inner().log_parser)?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record = resolve(service, &mut connection)?;
+  service_record
+    .set_log_parser(log_parser.as_deref(), &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  let updated = resolve(service, &mut connection)?;
+  Ok(Json(service_dto(updated, &mut connection)))
+}
+
+/// Fields of the registry screen's inline "log format" form.
+#[derive(FromForm)]
+pub struct SetLogParserForm {
+  /// Key of the log parser.
+  pub log_parser: String,
+}
+
+/// The human twin of [`set_service_log_parser`]: the registry screen's inline log-format picker.
+/// **Gated by the signed-in [`AdminSession`] cookie** (anonymous → sign-in). An unknown key is
+/// `422`. Redirects back to `/services`; `404` if unknown.
+#[post("/services/<service>/log-parser", data = "<form>")]
+pub fn set_service_log_parser_human(
+  service: &str,
+  form: Form<SetLogParserForm>,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Redirect, Status> {
+  if session.is_none() {
+    return Ok(Redirect::to("/admin/login"));
+  }
+  let log_parser = log_parser_choice(Some(form.into_inner().log_parser))?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record =
+    Service::find_by_name(service, &mut connection).map_err(|_| Status::NotFound)?;
+  service_record
+    .set_log_parser(log_parser.as_deref(), &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  Ok(Redirect::to("/services"))
+}
+
+/// Request body for setting a service's pipeline policy.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PipelineUpdateRequest {
+  /// What happens to this service's results when its upstream service reruns a document: `rerun`
+  /// (reset them to await the fresh upstream result, cascading further downstream) or `keep`.
+  pub on_upstream_rerun: String,
+}
+
+/// Whether `policy` is a valid `on_upstream_rerun` value. Shared by the agent and human setters.
+fn valid_upstream_rerun(policy: &str) -> bool { matches!(policy, "rerun" | "keep") }
+
+/// Sets a service's pipeline policy — the agent twin of the registry screen's inline "on upstream
+/// rerun" form. **Token-gated** via the [`Actor`] guard (`401` without a valid token); `400` if the
+/// policy is not `rerun`/`keep`, `404` if the service is unknown, `200` with the updated
+/// [`ServiceDto`] on success. Applies to upstream reruns marked after the change.
+#[rocket_okapi::openapi(tag = "Services")]
+#[put(
+  "/api/services/<service>/pipeline",
+  format = "json",
+  data = "<request>"
+)]
+pub fn set_service_pipeline(
+  service: &str,
+  request: Json<PipelineUpdateRequest>,
+  _actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<ServiceDto>, Status> {
+  let policy = request.into_inner().on_upstream_rerun;
+  if !valid_upstream_rerun(&policy) {
+    return Err(Status::BadRequest);
+  }
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record = resolve(service, &mut connection)?;
+  service_record
+    .set_on_upstream_rerun(&policy, &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  let updated = resolve(service, &mut connection)?;
+  Ok(Json(service_dto(updated, &mut connection)))
+}
+
+/// Fields of the registry screen's inline "on upstream rerun" form.
+#[derive(FromForm)]
+pub struct SetPipelineForm {
+  /// `rerun` or `keep`.
+  pub on_upstream_rerun: String,
+}
+
+/// The human twin of [`set_service_pipeline`]: the registry screen's inline pipeline-policy form.
+/// **Gated by the signed-in [`AdminSession`] cookie** (anonymous → sign-in). An unknown policy is
+/// `400`. Redirects back to `/services`; `404` if unknown.
+#[post("/services/<service>/pipeline", data = "<form>")]
+pub fn set_service_pipeline_human(
+  service: &str,
+  form: Form<SetPipelineForm>,
+  session: Option<AdminSession>,
+  pool: &State<DbPool>,
+) -> Result<Redirect, Status> {
+  if session.is_none() {
+    return Ok(Redirect::to("/admin/login"));
+  }
+  let policy = form.into_inner().on_upstream_rerun;
+  if !valid_upstream_rerun(&policy) {
+    return Err(Status::BadRequest);
+  }
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let service_record =
+    Service::find_by_name(service, &mut connection).map_err(|_| Status::NotFound)?;
+  service_record
+    .set_on_upstream_rerun(&policy, &mut connection)
+    .map_err(|_| Status::InternalServerError)?;
+  Ok(Redirect::to("/services"))
+}
+
 /// The corpus ids a service is already activated on (i.e. has at least one task for) — so the
 /// "register on a corpus" screen can **exclude** them from the picker. Re-activating an existing
 /// `(service, corpus)` pair is *destructive* (`register_service` wipes & re-creates that pair's
@@ -478,7 +887,16 @@ pub fn create_service_human(
   // service.
   let mut activated_any = false;
   for corpus in form.corpora.iter().filter(|name| !name.is_empty()) {
-    if start_activate(pool, &database_url.0, &session.owner, corpus, &form.name).is_ok() {
+    if start_activate(
+      pool,
+      &database_url.0,
+      &session.owner,
+      corpus,
+      &form.name,
+      None,
+    )
+    .is_ok()
+    {
       activated_any = true;
     }
   }
@@ -558,7 +976,14 @@ pub fn activate_on_corpus_human(
   let Some(session) = session else {
     return Ok(Redirect::to("/admin/login"));
   };
-  let uuid = start_activate(pool, &database_url.0, &session.owner, &form.corpus, service)?;
+  let uuid = start_activate(
+    pool,
+    &database_url.0,
+    &session.owner,
+    &form.corpus,
+    service,
+    None,
+  )?;
   Ok(Redirect::to(format!("/jobs/{uuid}")))
 }
 
@@ -575,10 +1000,29 @@ pub fn services_page(
 ) -> Result<Template, AdminReject> {
   require_admin_to(session, &return_to)?;
   let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
-  let services: Vec<HashMap<String, String>> = Service::all(&mut connection)
-    .unwrap_or_default()
+  let registry = Service::all(&mut connection).unwrap_or_default();
+  let services: Vec<HashMap<String, String>> = registry
     .iter()
-    .map(Service::to_hash)
+    .map(|service| {
+      let mut hash = service.to_hash();
+      let downstream: Vec<&str> = registry
+        .iter()
+        .filter(|other| other.upstream() == Some(service.name.as_str()))
+        .map(|other| other.name.as_str())
+        .collect();
+      hash.insert("downstream".to_string(), downstream.join(", "));
+      hash
+    })
+    .collect();
+  // The log-format picker's choices.
+  let parsers = log_parsers::all()
+    .iter()
+    .map(|parser| {
+      HashMap::from([
+        ("key".to_string(), parser.key().to_string()),
+        ("description".to_string(), parser.description().to_string()),
+      ])
+    })
     .collect();
   let mut global = HashMap::new();
   global.insert("title".to_string(), "Registered services".to_string());
@@ -589,6 +1033,7 @@ pub fn services_page(
   let mut context = TemplateContext {
     global,
     services: Some(services),
+    entries: Some(parsers),
     ..TemplateContext::default()
   };
   decorate_uri_encodings(&mut context);
@@ -1100,7 +1545,9 @@ pub fn service_runtimes_page(
 /// The route set for the services capability (registry + worker-fleet, screens + agent API).
 pub fn routes() -> Vec<Route> {
   // NB: `api_services` + `api_service_workers` + `register_service` + `delete_service` +
-  // `set_service_lease` + `api_service_runtimes` are mounted via `frontend::apidoc` (rocket_okapi).
+  // `set_service_lease` + `set_service_renewals` + `set_service_versions` + `set_service_pipeline`
+  // + `set_service_log_parser` + `api_service_runtimes` are mounted via `frontend::apidoc`
+  // (rocket_okapi).
   routes![
     add_service_page,
     create_service_human,
@@ -1110,6 +1557,10 @@ pub fn routes() -> Vec<Route> {
     worker_report_page,
     service_runtimes_page,
     delete_service_human,
-    set_service_lease_human
+    set_service_lease_human,
+    set_service_renewals_human,
+    set_service_versions_human,
+    set_service_log_parser_human,
+    set_service_pipeline_human
   ]
 }
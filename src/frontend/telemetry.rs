@@ -91,6 +91,7 @@ fn cached_summary(
     &service.name,
     corpus.sandbox_id(),
     entries,
+    &*crate::storage::storage(),
   ));
   // Store under the lock (last writer wins; a concurrent miss recomputing the same scope is
   // acceptable and rare).
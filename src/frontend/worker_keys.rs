@@ -0,0 +1,254 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The **worker key allowlist** screen and agent API (`models::WorkerKey`): the CURVE public keys
+//! the dispatcher accepts worker connections from when `dispatcher.worker_auth` is on. Uniform
+//! authz — any signed-in admin (or token-holding agent) may list, add and revoke keys; every
+//! change is audited by the audit fairing, refused handshakes and rejected results by the
+//! dispatcher.
+
+use diesel::result::{DatabaseErrorKind, Error};
+use rocket::form::Form;
+use rocket::http::Status;
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use rocket_dyn_templates::{Template, context};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::DbPool;
+use crate::config::config;
+use crate::dispatcher::worker_auth::{CurveKeys, curve_key_path};
+use crate::frontend::actor::{Actor, AdminReject, AdminSession, ReturnTo, require_admin_to};
+use crate::frontend::helpers::iso_utc;
+use crate::models::WorkerKey;
+
+/// An allowlisted worker key as exposed over the API/UI.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct WorkerKeyDto {
+  /// The key id (the handle for revocation).
+  pub id: i32,
+  /// The worker's CURVE public key, Z85-encoded.
+  pub public_key: String,
+  /// The operator's note.
+  pub label: String,
+  /// Whether the key is still honored.
+  pub active: bool,
+  /// When the key was allowlisted, as an RFC 3339 UTC timestamp.
+  pub created_at: String,
+  /// Who allowlisted the key.
+  pub created_by: String,
+  /// When the key was revoked (`null` while active).
+  pub revoked_at: Option<String>,
+  /// Who revoked the key (`null` while active).
+  pub revoked_by: Option<String>,
+  /// The latest accepted handshake with the key (`null` if never seen).
+  pub last_seen_at: Option<String>,
+}
+
+impl From<WorkerKey> for WorkerKeyDto {
+  fn from(key: WorkerKey) -> WorkerKeyDto {
+    WorkerKeyDto {
+      id: key.id,
+      active: key.is_active(),
+      public_key: key.public_key,
+      label: key.label,
+      created_at: iso_utc(key.created_at),
+      created_by: key.created_by,
+      revoked_at: key.revoked_at.map(iso_utc),
+      revoked_by: key.revoked_by,
+      last_seen_at: key.last_seen_at.map(iso_utc),
+    }
+  }
+}
+
+/// The allowlist together with the transport settings it applies under.
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct WorkerKeysDto {
+  /// Whether the dispatcher authenticates workers (`dispatcher.worker_auth`).
+  pub worker_auth: bool,
+  /// The dispatcher's CURVE public key, which workers connect with (`null` if its key file is not
+  /// readable from this server).
+  pub server_public_key: Option<String>,
+  /// Every key, active ones first.
+  pub keys: Vec<WorkerKeyDto>,
+}
+
+/// Loads the allowlist as the shared [`WorkerKeysDto`].
+fn load_worker_keys(pool: &DbPool) -> Result<WorkerKeysDto, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  let keys = WorkerKey::all(&mut connection).map_err(|_| Status::InternalServerError)?;
+  Ok(WorkerKeysDto {
+    worker_auth: config().dispatcher.worker_auth,
+    server_public_key: CurveKeys::load(&curve_key_path())
+      .ok()
+      .map(|keys| keys.public_key),
+    keys: keys.into_iter().map(WorkerKeyDto::from).collect(),
+  })
+}
+
+/// Allowlists `public_key` as `actor` — the shared core of the agent and human writes. `400` for a
+/// key that is not a Z85-encoded CURVE public key, `409` if the key was listed before (revoked keys
+/// are never reused).
+fn add_key(pool: &DbPool, public_key: &str, label: &str, actor: &str) -> Result<WorkerKey, Status> {
+  let key = WorkerKey::parse_public_key(public_key).ok_or(Status::BadRequest)?;
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  WorkerKey::add(&mut connection, &key, label.trim(), actor).map_err(|error| match error {
+    Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => Status::Conflict,
+    _ => Status::InternalServerError,
+  })
+}
+
+/// Revokes key `id` as `actor`. `404` unless the key exists and is still active.
+fn revoke_key(pool: &DbPool, id: i32, actor: &str) -> Result<WorkerKey, Status> {
+  let mut connection = pool.get().map_err(|_| Status::ServiceUnavailable)?;
+  WorkerKey::revoke(&mut connection, id, actor).map_err(|error| match error {
+    Error::NotFound => Status::NotFound,
+    _ => Status::InternalServerError,
+  })
+}
+
+/// The worker key allowlist (agent twin of the `/admin/worker-keys` screen), with whether worker
+/// auth is on and the dispatcher's public key. **Token-gated**.
+#[rocket_okapi::openapi(tag = "Management")]
+#[get("/api/worker-keys")]
+pub fn api_worker_keys(
+  _caller: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<WorkerKeysDto>, Status> {
+  Ok(Json(load_worker_keys(pool)?))
+}
+
+/// Request body for allowlisting a worker key.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct NewWorkerKeyRequest {
+  /// The worker's CURVE public key, Z85-encoded (40 characters).
+  pub public_key: String,
+  /// Optional operator note (host, fleet, owner).
+  pub label: Option<String>,
+}
+
+/// Allowlists a worker key: workers connecting with it are accepted once `dispatcher.worker_auth`
+/// is on. **Token-gated** and audited. `400` for a malformed key, `409` if the key was listed
+/// before.
+#[rocket_okapi::openapi(tag = "Management")]
+#[post("/api/worker-keys", format = "json", data = "<request>")]
+pub fn api_add_worker_key(
+  request: Json<NewWorkerKeyRequest>,
+  actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<WorkerKeyDto>, Status> {
+  let request = request.into_inner();
+  let key = add_key(
+    pool,
+    &request.public_key,
+    request.label.as_deref().unwrap_or_default(),
+    &actor.owner,
+  )?;
+  Ok(Json(WorkerKeyDto::from(key)))
+}
+
+/// Revokes worker key `id`: new handshakes with it are refused, and a worker still connected with
+/// it is no longer leased to. **Token-gated** and audited. `404` for an unknown or already revoked
+/// key.
+#[rocket_okapi::openapi(tag = "Management")]
+#[delete("/api/worker-keys/<id>")]
+pub fn api_revoke_worker_key(
+  id: i32,
+  actor: Actor,
+  pool: &State<DbPool>,
+) -> Result<Json<WorkerKeyDto>, Status> {
+  Ok(Json(WorkerKeyDto::from(revoke_key(
+    pool,
+    id,
+    &actor.owner,
+  )?)))
+}
+
+/// The worker key screen (`GET /admin/worker-keys`): the allowlist with a revoke button per active
+/// key, and a form to add one. Signed-in admins only (unauthenticated → sign-in page, returning
+/// here).
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[get("/admin/worker-keys")]
+pub fn worker_keys_page(
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Template, AdminReject> {
+  let session = require_admin_to(session, &return_to)?;
+  // Best-effort, like the other admin screens: a db hiccup renders an empty table, never a 500.
+  let listing = load_worker_keys(pool).unwrap_or(WorkerKeysDto {
+    worker_auth: config().dispatcher.worker_auth,
+    server_public_key: None,
+    keys: Vec::new(),
+  });
+  let global = serde_json::json!({
+    "title": "Worker keys",
+    "description": "CURVE public keys the dispatcher accepts worker connections from",
+  });
+  Ok(Template::render(
+    "worker-keys",
+    context! {
+      global,
+      owner: session.owner,
+      worker_auth: listing.worker_auth,
+      server_public_key: listing.server_public_key,
+      rows: listing.keys,
+    },
+  ))
+}
+
+/// Fields of the screen's add-key form.
+#[derive(FromForm)]
+pub struct AddWorkerKeyForm {
+  /// The worker's CURVE public key, Z85-encoded.
+  pub public_key: String,
+  /// Optional operator note.
+  pub label: String,
+}
+
+/// Allowlists a key from the screen (`POST /admin/worker-keys`): the human twin of
+/// [`api_add_worker_key`]. Redirects back to the screen. Signed-in admins only; a malformed key is
+/// `400`, a key listed before `409`.
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[post("/admin/worker-keys", data = "<form>")]
+pub fn add_worker_key_human(
+  form: Form<AddWorkerKeyForm>,
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Redirect, AdminReject> {
+  let session = require_admin_to(session, &return_to)?;
+  add_key(pool, &form.public_key, &form.label, &session.owner)?;
+  Ok(Redirect::to("/admin/worker-keys"))
+}
+
+/// Revokes key `id` from the screen (`POST /admin/worker-keys/<id>/revoke`): the human twin of
+/// [`api_revoke_worker_key`]. Redirects back to the screen. Signed-in admins only; an unknown or
+/// already revoked key is `404`.
+#[allow(clippy::result_large_err)] // AdminReject carries a Redirect; see actor::AdminReject.
+#[post("/admin/worker-keys/<id>/revoke")]
+pub fn revoke_worker_key_human(
+  id: i32,
+  session: Option<AdminSession>,
+  return_to: ReturnTo,
+  pool: &State<DbPool>,
+) -> Result<Redirect, AdminReject> {
+  let session = require_admin_to(session, &return_to)?;
+  revoke_key(pool, id, &session.owner)?;
+  Ok(Redirect::to("/admin/worker-keys"))
+}
+
+/// The human worker key screen + its forms (the agent endpoints are mounted via
+/// `frontend::apidoc`).
+pub fn routes() -> Vec<Route> {
+  routes![
+    worker_keys_page,
+    add_worker_key_human,
+    revoke_worker_key_human
+  ]
+}
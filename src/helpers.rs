@@ -8,7 +8,6 @@
 //! Helper structures and methods for Task
 use rand::RngExt;
 use regex::Regex;
-use std::fs::File;
 use std::io;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -21,8 +20,15 @@ use diesel::result::Error;
 use crate::concerns::CortexInsertable;
 use crate::models::{
   LogError, LogFatal, LogInfo, LogInvalid, LogRecord, LogWarning, NewLogError, NewLogFatal,
-  NewLogInfo, NewLogInvalid, NewLogWarning, Task,
+  NewLogInfo, NewLogInvalid, NewLogWarning, NewResultArchive, NewResultArchiveVersion, Service,
+  Task,
 };
+use crate::storage::{ReadSeek, Storage};
+
+pub mod fingerprint;
+pub mod log_parsers;
+use fingerprint::details_fingerprint;
+use log_parsers::LogParser;
 
 static MESSAGE_LINE_REGEX: LazyLock<Regex> =
   LazyLock::new(|| Regex::new(r"^([^ :]+):([^ :]+):([^ ]+)(\s(.*))?$").unwrap());
@@ -80,14 +86,78 @@ pub struct TaskProgress {
   /// (D-17). Stored per-task so a config change never re-times an already-leased task, and so one
   /// dispatcher can serve fast (latexml-oxide) and slow (Perl) services with different leases.
   pub lease_timeout_seconds: i64,
+  /// how many times the current lease was extended by a worker heartbeat (see
+  /// [`TaskProgress::renew`]); reset when the task is re-leased
+  pub renewals: i64,
+  /// time of the latest lease renewal (`0` = never renewed)
+  pub renewed_at: i64,
+  /// the cap on `renewals` for this lease, captured at dispatch time like the lease itself
+  pub max_renewals: i64,
+  /// every lease of this task so far (one per dispatch, oldest first), kept across reaper
+  /// re-queues so a task given up on can be dead-lettered with its full lease history
+  pub leases: Vec<LeaseRecord>,
+  /// the size of the task's input archive in bytes, taken the first time a worker with a declared
+  /// input limit considers the task: `None` until then, `Some(None)` if it could not be sized
+  pub input_bytes: Option<Option<u64>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// One lease of an in-flight task: which worker it went to, and when
+pub struct LeaseRecord {
+  /// the worker's ZMQ identity
+  pub worker: String,
+  /// time of the lease (unix seconds)
+  pub leased_at: i64,
+  /// the lease token its result must carry (only with `dispatcher.worker_auth`)
+  pub token: Option<String>,
 }
 impl TaskProgress {
   /// What is the latest admissible time for this task to be completed? The base deadline is this
   /// task's captured lease / visibility timeout (`lease_timeout_seconds`); each retry extends it by
   /// another full timeout (`(retries + 1) × timeout`), so a task that keeps timing out backs off
-  /// rather than re-leasing ever-faster.
+  /// rather than re-leasing ever-faster. A renewed lease runs at least one full timeout past its
+  /// latest renewal.
   pub fn expected_at(&self) -> i64 {
-    self.created_at + ((self.retries + 1) * self.lease_timeout_seconds)
+    let base = self.created_at + ((self.retries + 1) * self.lease_timeout_seconds);
+    if self.renewed_at > 0 {
+      base.max(self.renewed_at + self.lease_timeout_seconds)
+    } else {
+      base
+    }
+  }
+
+  /// Whether a returned result carrying `token` may complete this task: any lease token of its
+  /// history will do (a slow earlier worker's result is as good as the latest one's), and a task
+  /// leased without tokens accepts any result.
+  pub fn admits_token(&self, token: Option<&str>) -> bool {
+    if self.leases.iter().all(|lease| lease.token.is_none()) {
+      return true;
+    }
+    token.is_some_and(|token| {
+      self
+        .leases
+        .iter()
+        .any(|lease| lease.token.as_deref() == Some(token))
+    })
+  }
+
+  /// Whether `worker`, echoing lease `token`, holds this task's current lease — the latest one;
+  /// a lease granted without a token is matched on the worker alone.
+  pub fn held_by(&self, worker: &str, token: Option<&str>) -> bool {
+    self.leases.last().is_some_and(|lease| {
+      lease.worker == worker && (lease.token.is_none() || lease.token.as_deref() == token)
+    })
+  }
+
+  /// Extends the lease from a worker's "still working" heartbeat at time `now`, unless the lease
+  /// already used its `max_renewals`. Returns whether the lease was extended.
+  pub fn renew(&mut self, now: i64) -> bool {
+    if self.renewals >= self.max_renewals {
+      return false;
+    }
+    self.renewals += 1;
+    self.renewed_at = now;
+    true
   }
 }
 
@@ -100,6 +170,10 @@ pub struct TaskReport {
   pub status: TaskStatus,
   /// a vector of `TaskMessage` log entries
   pub messages: Vec<NewTaskMessage>,
+  /// the size and checksum of the result archive, when one was committed
+  pub archive: Option<NewResultArchive>,
+  /// the previous result archive, when the sink moved it aside to keep it as a version
+  pub previous_archive: Option<NewResultArchiveVersion>,
 }
 
 #[derive(Clone, Debug)]
@@ -181,6 +255,25 @@ impl LogRecord for TaskMessage {
 }
 
 impl TaskStatus {
+  /// A task of a **paused** run (`mark_blocked`); resume returns it to TODO.
+  pub const PAUSED: TaskStatus = TaskStatus::Blocked(-6);
+  /// A pipeline task **awaiting its upstream** service's result for the same document; the
+  /// finalize path releases it to TODO when the upstream task succeeds (`backend::pipelines`).
+  /// Distinct from [`TaskStatus::PAUSED`] so a resume never releases it early.
+  pub const AWAITING_UPSTREAM: TaskStatus = TaskStatus::Blocked(-7);
+  /// A task **quarantined** by an operator from the dispatch dead-letter queue
+  /// (`models::DeadLetter`): never leased again and left out of whole-corpus reruns, until
+  /// requeued explicitly.
+  pub const QUARANTINED: TaskStatus = TaskStatus::Blocked(-8);
+  /// A pipeline task of a **paused** run that was still [`TaskStatus::AWAITING_UPSTREAM`]: an
+  /// upstream result arriving meanwhile leaves it parked, and resume re-checks its upstream.
+  pub const PAUSED_AWAITING_UPSTREAM: TaskStatus = TaskStatus::Blocked(-9);
+
+  /// Whether a raw status is a terminal **success** — the task produced a result archive a
+  /// downstream pipeline service can consume (NoProblem, Warning, or Error; a Fatal or Invalid
+  /// conversion has no usable output).
+  pub fn raw_is_success(raw: i32) -> bool { (-3..=-1).contains(&raw) }
+
   /// Maps the enumeration into the raw ints for the Task store
   pub fn raw(&self) -> i32 {
     match *self {
@@ -365,6 +458,7 @@ impl NewTaskMessage {
     what: String,
     details: String,
   ) -> NewTaskMessage {
+    let fingerprint = details_fingerprint(&details);
     match severity.to_lowercase().as_str() {
       // Canonical Perl-LaTeXML token is "Warning"; tolerate the abbreviated "Warn" too so a
       // producer that emits `Warn:` doesn't silently land in the `_ => Info` default (which once
@@ -374,12 +468,14 @@ impl NewTaskMessage {
         category,
         what,
         details,
+        fingerprint,
       }),
       "error" => NewTaskMessage::Error(NewLogError {
         task_id,
         category,
         what,
         details,
+        fingerprint,
       }),
       // Perl-LaTeXML emits `Fatal('invalid', …)` for an *unprocessable input* (no TeX source,
       // PDF-only, binary, …): a fatal whose CATEGORY is `invalid`. CorTeX treats that as its
@@ -392,24 +488,28 @@ impl NewTaskMessage {
         category,
         what,
         details,
+        fingerprint,
       }),
       "fatal" => NewTaskMessage::Fatal(NewLogFatal {
         category,
         task_id,
         what,
         details,
+        fingerprint,
       }),
       "invalid" => NewTaskMessage::Invalid(NewLogInvalid {
         task_id,
         category,
         what,
         details,
+        fingerprint,
       }),
       _ => NewTaskMessage::Info(NewLogInfo {
         task_id,
         category,
         what,
         details,
+        fingerprint,
       }), // unknown severity will be treated as info
     }
   }
@@ -420,6 +520,8 @@ impl NewTaskMessage {
 pub fn parse_log(task_id: i64, log: &str) -> Vec<NewTaskMessage> {
   let mut messages: Vec<NewTaskMessage> = Vec::new();
   let mut in_details_mode = false;
+  // The details of the last message, extended by its tab-led continuation lines.
+  let mut pending_details: Option<String> = None;
 
   for line in log.lines() {
     // Skip empty lines
@@ -430,20 +532,27 @@ pub fn parse_log(task_id: i64, log: &str) -> Vec<NewTaskMessage> {
     if in_details_mode {
       // If the line starts with tab, we are indeed reading in details
       if line.starts_with('\t') {
-        // Append the details line to the last message. `in_details_mode` is only ever set true
-        // right after a message is pushed, so `messages` is non-empty here by invariant — but we
-        // never `.expect()`-panic the dispatch path on a hostile log (DESIGN_PRINCIPLES): if that
-        // invariant is ever broken, treat the orphan details line as noise and carry on rather than
-        // aborting the whole task's log parse.
-        if let Some(mut last_message) = messages.pop() {
-          let mut truncated_details = last_message.details().to_string() + "\n" + line;
-          utf_truncate(&mut truncated_details, 2000);
-          last_message.set_details(truncated_details);
-          messages.push(last_message);
+        // Append the details line to the last message's details, which are set on it once the
+        // message ends (so they and their fingerprint are rebuilt once per message, not per line).
+        // `in_details_mode` is only ever set true right after a message is pushed, so `messages` is
+        // non-empty here by invariant — but we never `.expect()`-panic the dispatch path on a
+        // hostile log (DESIGN_PRINCIPLES): if that invariant is ever broken, treat the orphan
+        // details line as noise and carry on rather than aborting the whole task's log parse.
+        let details = pending_details.get_or_insert_with(|| {
+          messages
+            .last()
+            .map(|message| message.details().to_string())
+            .unwrap_or_default()
+        });
+        if details.len() < 2000 {
+          details.push('\n');
+          details.push_str(line);
+          utf_truncate(details, 2000);
         }
         continue; // This line has been consumed, next
       } else {
         // Otherwise, no tab at the line beginning means last message has ended
+        flush_details(&mut messages, &mut pending_details);
         in_details_mode = false;
         if in_details_mode {} // hacky? disable "unused" warning
       }
@@ -515,9 +624,18 @@ pub fn parse_log(task_id: i64, log: &str) -> Vec<NewTaskMessage> {
       }
     }
   }
+  flush_details(&mut messages, &mut pending_details);
   messages
 }
 
+/// Sets the details collected from continuation lines on the last parsed message. If `messages` is
+/// empty (an invariant break — details mode only follows a pushed message) they are noise.
+fn flush_details(messages: &mut [NewTaskMessage], pending_details: &mut Option<String>) {
+  if let (Some(details), Some(last_message)) = (pending_details.take(), messages.last_mut()) {
+    last_message.set_details(details);
+  }
+}
+
 /// Decodes raw worker-log bytes into a string, **tolerating non-UTF-8 input** (arXiv data is
 /// hostile and workers are unpredictable — W-2). A single invalid byte used to discard the whole
 /// log and force-mark the task `Fatal`, throwing away every real conversion message + the true
@@ -543,9 +661,11 @@ fn decode_worker_log(raw: &[u8]) -> String {
 /// ~1.4× libarchive on this op, see `docs/archive/ARCHIVE_RATIONALIZATION.md`). Returns the decoded
 /// log text, or an `Err` describing why it couldn't (a non-zip / corrupt archive, or a missing
 /// `cortex.log` → the task is left `Fatal`), rather than `.expect()`-panicking the dispatch path as
-/// the old libarchive reader did.
-fn read_cortex_log(result: &Path) -> Result<String, String> {
-  let file = File::open(result).map_err(|e| format!("cannot open result archive: {e}"))?;
+/// the old libarchive reader did. The archive is read through `storage`.
+pub(crate) fn read_cortex_log(result: &Path, storage: &dyn Storage) -> Result<String, String> {
+  let file = storage
+    .open(result)
+    .map_err(|e| format!("cannot open result archive: {e}"))?;
   let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("not a readable zip: {e}"))?;
   let mut entry = archive
     .by_name("cortex.log")
@@ -557,8 +677,9 @@ fn read_cortex_log(result: &Path) -> Result<String, String> {
   Ok(decode_worker_log(&raw))
 }
 
-/// Generates a `TaskReport` from a result archive (`.zip`) of a `CorTeX` processing job, following
-/// the `LaTeXML` messaging conventions in its `cortex.log`. Returns **`None` when the archive is
+/// Generates a `TaskReport` from a result archive (`.zip`) of a `CorTeX` processing job, reading
+/// its `cortex.log` with the service's `parser` (see [`log_parsers`]; LaTeXML's conventions unless
+/// the service selected another). Returns **`None` when the archive is
 /// unreadable or empty** (0-byte, truncated, non-zip, or no `cortex.log` entry): that is an
 /// *infrastructure* failure — the worker returned nothing usable — **not** a conversion verdict, so
 /// we do **not** fabricate a terminal `Fatal`. The caller (sink) skips finalizing it, leaving the
@@ -566,8 +687,13 @@ fn read_cortex_log(result: &Path) -> Result<String, String> {
 /// `cortex/never_completed_with_retries` message) after `MAX_DISPATCH_RETRIES` — never a silent,
 /// unretried Fatal (KNOWN_ISSUES D-18). A *readable* `cortex.log` always yields `Some` — a genuine
 /// verdict, even when it parses to `Fatal`.
-pub fn generate_report(task: Task, result: &Path) -> Option<TaskReport> {
-  let log_string = match read_cortex_log(result) {
+pub fn generate_report(
+  task: Task,
+  result: &Path,
+  storage: &dyn Storage,
+  parser: &dyn LogParser,
+) -> Option<TaskReport> {
+  let log_string = match read_cortex_log(result, storage) {
     Ok(log_string) => log_string,
     Err(reason) => {
       // Infrastructure failure (D-18): don't invent a verdict — defer to the reaper's retry path.
@@ -575,10 +701,25 @@ pub fn generate_report(task: Task, result: &Path) -> Option<TaskReport> {
       return None;
     },
   };
+  let (status, messages) = parser.classify(task.id, &log_string);
+  Some(TaskReport {
+    task,
+    status,
+    messages,
+    archive: None,
+    previous_archive: None,
+  })
+}
+
+/// The conversion status and messages of a worker's `cortex.log`, following the `LaTeXML`
+/// messaging conventions: the `Info:conversion:<N>` status line sets the status (and is not kept as
+/// a message), any `Invalid` message makes it `Invalid`, and a log without a status line is
+/// `Fatal`. The [`log_parsers::Latexml`] parser, the default of every service.
+pub fn classify_log(task_id: i64, log_string: &str) -> (TaskStatus, Vec<NewTaskMessage>) {
   let mut messages = Vec::new();
   let mut status = TaskStatus::Fatal; // Fatal by default — overridden by the conversion status line.
   // Look for the special status message - Fatal otherwise!
-  for message in parse_log(task.id, &log_string).into_iter() {
+  for message in parse_log(task_id, log_string).into_iter() {
     // Invalids are a bit of a workaround for now, they're fatal messages in latexml, but
     // we want them separated out in cortex
     let mut skip_message = false;
@@ -614,17 +755,42 @@ pub fn generate_report(task: Task, result: &Path) -> Option<TaskReport> {
     }
   }
 
-  Some(TaskReport {
-    task,
-    status,
-    messages,
-  })
+  (status, messages)
 }
 
-/// Returns an open file handle to the task's entry
-pub fn prepare_input_stream(task: &Task) -> Result<File, io::Error> {
-  let entry_path = Path::new(&task.entry);
-  File::open(entry_path)
+/// Returns an open reader of the task's input (see [`task_input_path`]), from `storage`
+pub fn prepare_input_stream(
+  task: &Task,
+  service: &Service,
+  sandbox_id: Option<i32>,
+  storage: &dyn Storage,
+) -> Result<Box<dyn ReadSeek>, io::Error> {
+  let input_path = task_input_path(task, service, sandbox_id, storage).ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("no input path for entry {:?}", task.entry),
+    )
+  })?;
+  storage.open(&input_path)
+}
+
+/// Where a task's **input** lives: the document source `entry`, or — for a pipeline service (see
+/// [`Service::upstream`]) — the upstream service's result archive for the same document. A sandbox
+/// reads its own scoped upstream archive when it ran the upstream itself (it exists in `storage`),
+/// else the parent's. `None` only for a malformed entry with no parent directory.
+pub fn task_input_path(
+  task: &Task,
+  service: &Service,
+  sandbox_id: Option<i32>,
+  storage: &dyn Storage,
+) -> Option<PathBuf> {
+  let Some(upstream) = service.upstream() else {
+    return Some(PathBuf::from(&task.entry));
+  };
+  sandbox_id
+    .and_then(|id| result_archive_path(&task.entry, upstream, Some(id)))
+    .filter(|scoped| storage.exists(scoped))
+    .or_else(|| result_archive_path(&task.entry, upstream, None))
 }
 
 /// The single source of truth for where a service's **result archive** lives, given a task's source
@@ -656,6 +822,30 @@ pub fn result_archive_path(
   Some(PathBuf::from(format!("{dir}/{stem}.zip")))
 }
 
+/// Where a service that keeps result versions moves a task's previous result `archive` (a
+/// [`result_archive_path`]) before writing the new one: `<entry-dir>/<service>.v<stamp>.zip`, with
+/// `stamp` the microsecond it was moved aside, so a task's versions sort oldest to newest. The
+/// archive-GC and `fsck` orphan scans do not mistake these for orphans (see
+/// [`result_version_stamp`]). `None` for a path with no `.zip` file name.
+pub fn result_version_path(archive: &Path, stamp: i64) -> Option<PathBuf> {
+  let stem = archive.file_name()?.to_str()?.strip_suffix(".zip")?;
+  Some(archive.with_file_name(format!("{stem}.v{stamp}.zip")))
+}
+
+/// The stamp of a kept result version named `file_name`, if it is one of the versions
+/// [`result_version_path`] names for `archive_name` (the current archive's file name).
+pub fn result_version_stamp(file_name: &str, archive_name: &str) -> Option<i64> {
+  let stem = archive_name.strip_suffix(".zip")?;
+  let stamp = file_name
+    .strip_suffix(".zip")?
+    .strip_prefix(stem)?
+    .strip_prefix(".v")?;
+  if stamp.is_empty() || !stamp.bytes().all(|byte| byte.is_ascii_digit()) {
+    return None;
+  }
+  stamp.parse().ok()
+}
+
 /// Utility functions, until they find a better place
 pub fn utf_truncate(input: &mut String, maxsize: usize) {
   let mut utf_maxsize = input.len();
@@ -813,6 +1003,7 @@ mod log_decode_tests {
   #[test]
   fn read_cortex_log_extracts_from_zip_and_errors_gracefully() {
     use super::read_cortex_log;
+    use crate::storage::LocalStorage;
     use std::fs::File;
     use std::io::Write;
     let zip_path = std::env::temp_dir().join("cortex_read_log_unit_test.zip");
@@ -827,19 +1018,20 @@ mod log_decode_tests {
         .unwrap();
       zw.finish().unwrap();
     }
-    let log = read_cortex_log(&zip_path).expect("reads cortex.log out of the zip");
+    let log = read_cortex_log(&zip_path, &LocalStorage).expect("reads cortex.log out of the zip");
     assert!(
       log.contains("hello a worker log line"),
       "by_name extracted the cortex.log content, got: {log:?}"
     );
     // A missing / non-zip path is a graceful Err (task left Fatal), never a panic.
-    assert!(read_cortex_log(std::path::Path::new("/nonexistent/x.zip")).is_err());
+    assert!(read_cortex_log(std::path::Path::new("/nonexistent/x.zip"), &LocalStorage).is_err());
     std::fs::remove_file(&zip_path).ok();
   }
 
   #[test]
   fn generate_report_defers_unreadable_archives_to_the_reaper() {
-    use super::{Task, TaskStatus, generate_report};
+    use super::{Task, TaskStatus, generate_report, log_parsers};
+    use crate::storage::LocalStorage;
     use std::fs::File;
     use std::io::Write;
     let task = || Task {
@@ -848,17 +1040,25 @@ mod log_decode_tests {
       corpus_id: 2,
       status: 0,
       entry: "/d/x.zip".to_string(),
+      priority: 0,
+      dispatcher_id: None,
     };
     // D-18: a 0-byte / missing / non-zip archive is an *infrastructure* failure → None, so the sink
     // defers to the reaper (retry → dead-letter with a message) instead of a silent terminal Fatal.
     let empty = std::env::temp_dir().join("cortex_d18_empty_result.zip");
     File::create(&empty).unwrap(); // 0 bytes — "not a readable zip"
     assert!(
-      generate_report(task(), &empty).is_none(),
+      generate_report(task(), &empty, &LocalStorage, &log_parsers::Latexml).is_none(),
       "a 0-byte result archive defers to the reaper (None), never a fabricated Fatal"
     );
     assert!(
-      generate_report(task(), std::path::Path::new("/nonexistent/x.zip")).is_none(),
+      generate_report(
+        task(),
+        std::path::Path::new("/nonexistent/x.zip"),
+        &LocalStorage,
+        &log_parsers::Latexml
+      )
+      .is_none(),
       "a missing result archive defers to the reaper (None)"
     );
     // A *readable* cortex.log is always a genuine verdict — Some, even when it parses a conversion
@@ -871,8 +1071,8 @@ mod log_decode_tests {
       zw.write_all(b"Info:conversion:0 clean run\n").unwrap();
       zw.finish().unwrap();
     }
-    let report =
-      generate_report(task(), &good).expect("a readable cortex.log yields a verdict (Some)");
+    let report = generate_report(task(), &good, &LocalStorage, &log_parsers::Latexml)
+      .expect("a readable cortex.log yields a verdict (Some)");
     assert_eq!(
       report.status,
       TaskStatus::NoProblem,
@@ -958,6 +1158,40 @@ mod log_decode_tests {
     assert!(result_archive_path("", "tex_to_html", None).is_none());
     assert!(result_archive_path("/", "tex_to_html", None).is_none());
   }
+
+  #[test]
+  fn result_versions_sit_next_to_their_archive() {
+    use super::{result_version_path, result_version_stamp};
+    let archive = std::path::Path::new("/data/arxiv/5678/tex_to_html.zip");
+    let version = result_version_path(archive, 1_760_000_000_123_456).unwrap();
+    assert_eq!(
+      version,
+      std::path::PathBuf::from("/data/arxiv/5678/tex_to_html.v1760000000123456.zip")
+    );
+    let name = version.file_name().unwrap().to_str().unwrap();
+    assert_eq!(
+      result_version_stamp(name, "tex_to_html.zip"),
+      Some(1_760_000_000_123_456)
+    );
+    // Neither the archive itself, a sandbox's archive, nor another service's version is one.
+    assert_eq!(
+      result_version_stamp("tex_to_html.zip", "tex_to_html.zip"),
+      None
+    );
+    assert_eq!(
+      result_version_stamp("tex_to_html.sandbox-4.zip", "tex_to_html.zip"),
+      None
+    );
+    assert_eq!(
+      result_version_stamp("tex_to_pdf.v12.zip", "tex_to_html.zip"),
+      None
+    );
+    assert_eq!(
+      result_version_stamp("tex_to_html.v.zip", "tex_to_html.zip"),
+      None
+    );
+    assert!(result_version_path(std::path::Path::new("/data/x/readme"), 1).is_none());
+  }
 }
 
 #[cfg(test)]
@@ -0,0 +1,113 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Message fingerprints**: the `details` of a log message with everything document-specific
+//! masked, hashed to one `BIGINT`. Two messages of one `(category, what)` with the same fingerprint
+//! share a root cause in all but their line numbers, file names and arguments, so the report ladder
+//! can group a `what`'s long tail of near-identical rows into clusters
+//! (`GET /api/reports/<c>/<s>/<severity>/<category>/<what>/clusters`).
+//!
+//! [`normalize_details`] masks, in order:
+//!
+//! | what | becomes |
+//! | --- | --- |
+//! | a quoted token (`"…"`, `'…'`, TeX's `` `…' ``) | `<Q>` |
+//! | a path (anything with a `/` between name characters) | `<PATH>` |
+//! | a file name with a TeX-side extension (`paper.tex`, `figure1.png`) | `<FILE>` |
+//! | a brace group's contents (a macro argument) | `{<A>}` |
+//! | a number (`12`, `3.5`, `12:4`) | `<N>` |
+//! | any other token holding a digit (`S3.E2`, `x1`, a hex id) | `<ID>` |
+//!
+//! and collapses whitespace. The fingerprint is computed when a message is built
+//! ([`crate::helpers::NewTaskMessage::new`]); messages stored before fingerprints existed are
+//! backfilled by `cortex backfill-fingerprints`.
+
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+
+static QUOTED_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r#"(^|[^\w])(?:"[^"\n]*"|'[^'\n]*'|`[^'\n]*')"#).unwrap());
+static PATH_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"(?:[~.]*/)?(?:[\w.+-]+/)+[\w.+-]*").unwrap());
+static FILE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(
+    r"\b[\w.+-]+\.(?:tex|ltx|sty|cls|clo|cfg|def|fd|dtx|ins|bib|bbl|bst|aux|toc|png|jpe?g|gif|eps|pdf|ps|svg|pgf|tikz|zip)\b",
+  )
+  .unwrap()
+});
+static BRACE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{[^{}\n]*\}").unwrap());
+static DIGIT_TOKEN_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"[\w.:-]*\d[\w.:-]*").unwrap());
+static WHITESPACE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+/// The `details` of a message with its document-specific parts masked (see the module docs) —
+/// the text a cluster is shown as.
+pub fn normalize_details(details: &str) -> String {
+  let masked = QUOTED_REGEX.replace_all(details, "${1}<Q>");
+  let masked = PATH_REGEX.replace_all(&masked, "<PATH>");
+  let masked = FILE_REGEX.replace_all(&masked, "<FILE>");
+  let masked = BRACE_REGEX.replace_all(&masked, "{<A>}");
+  let masked = DIGIT_TOKEN_REGEX.replace_all(&masked, |token: &Captures| {
+    let token = &token[0];
+    if token
+      .chars()
+      .all(|c| c.is_ascii_digit() || matches!(c, '.' | ':' | '-'))
+    {
+      "<N>"
+    } else {
+      "<ID>"
+    }
+  });
+  WHITESPACE_REGEX
+    .replace_all(masked.trim(), " ")
+    .into_owned()
+}
+
+/// The fingerprint of `details`: the first eight bytes of the SHA-256 of its
+/// [`normalize_details`] form, as a signed `BIGINT`.
+pub fn details_fingerprint(details: &str) -> i64 {
+  let digest = Sha256::digest(normalize_details(details).as_bytes());
+  let mut head = [0u8; 8];
+  head.copy_from_slice(&digest[..8]);
+  i64::from_be_bytes(head)
+}
+
+/// A fingerprint as the 16 hex digits the API shows it as (a JSON number would lose precision).
+pub fn fingerprint_hex(fingerprint: i64) -> String { format!("{:016x}", fingerprint as u64) }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn masks_document_specific_parts() {
+    assert_eq!(
+      normalize_details("at /tmp/cortex_x1/0801.1234.tex; line 120 col 4 - line 120 col 19"),
+      "at <PATH>; line <N> col <N> - line <N> col <N>"
+    );
+    assert_eq!(
+      normalize_details("Missing file 'fig3.png' in\t\t figure1.png, see \\label{S3.E2}"),
+      "Missing file <Q> in <FILE>, see \\label{<A>}"
+    );
+    assert_eq!(
+      normalize_details("don't know how to handle x1 at 12:4"),
+      "don't know how to handle <ID> at <N>"
+    );
+  }
+
+  #[test]
+  fn near_identical_details_share_a_fingerprint() {
+    let one = details_fingerprint("at paper.tex; line 12 col 3 \\foo{alpha}");
+    let other = details_fingerprint("at  main.tex; line 907 col 11 \\foo{beta gamma}");
+    assert_eq!(one, other);
+    assert_ne!(one, details_fingerprint("while expanding \\foo{alpha}"));
+    assert_eq!(fingerprint_hex(-1), "ffffffffffffffff");
+    assert_eq!(fingerprint_hex(one).len(), 16);
+  }
+}
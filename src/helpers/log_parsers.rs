@@ -0,0 +1,324 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Log parsers**: how the `cortex.log` a worker ships in its result archive is read into the
+//! task's status and messages. A service selects one by key (`services.log_parser`; unset is
+//! [`DEFAULT_LOG_PARSER`], LaTeXML's convention), so a tool other than LaTeXML reports in its own
+//! format instead of faking LaTeXML's:
+//!
+//! | key | `cortex.log` holds |
+//! | --- | --- |
+//! | `latexml` | `Severity:category:what details` lines, tab-continued details, and the `Info:conversion:<N>` status line |
+//! | `json-lines` | one JSON object per line: `{"severity", "category", "what", "details"}` messages and a `{"status": …}` line |
+//! | `exit-code` | the tool's output, ending with a line holding only its exit code |
+//!
+//! Every parser keeps the guarantees of the LaTeXML path on hostile input: it never panics, lines
+//! it cannot read are noise, and every message field is clamped to its column
+//! ([`bounded_message`]). A log without a status is `Fatal`.
+
+use serde::Deserialize;
+
+use super::{NewTaskMessage, TaskStatus, classify_log, utf_truncate};
+use crate::models::LogRecord;
+
+/// The log parser of a service that selects none: LaTeXML's message convention.
+pub const DEFAULT_LOG_PARSER: &str = "latexml";
+
+/// One way of reading a worker's `cortex.log`.
+pub trait LogParser: Sync {
+  /// The key a service selects it by.
+  fn key(&self) -> &'static str;
+  /// A one-line description of the log format it reads.
+  fn description(&self) -> &'static str;
+  /// The conversion status and messages of `log` (the decoded `cortex.log` of task `task_id`). The
+  /// status line(s) the format uses are consumed, not kept as messages.
+  fn classify(&self, task_id: i64, log: &str) -> (TaskStatus, Vec<NewTaskMessage>);
+}
+
+static LOG_PARSERS: [&dyn LogParser; 3] = [&Latexml, &JsonLines, &ExitCode];
+
+/// Every registered log parser.
+pub fn all() -> &'static [&'static dyn LogParser] { &LOG_PARSERS }
+
+/// The log parser registered under `key`.
+pub fn find(key: &str) -> Option<&'static dyn LogParser> {
+  LOG_PARSERS
+    .iter()
+    .copied()
+    .find(|parser| parser.key() == key)
+}
+
+/// The [`DEFAULT_LOG_PARSER`].
+pub fn default_parser() -> &'static dyn LogParser { &Latexml }
+
+/// The registered keys, comma-separated (for error messages).
+pub fn keys() -> String {
+  LOG_PARSERS
+    .iter()
+    .map(|parser| parser.key())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// A message with every field clamped to its column — `severity` 50, `category` 100, `what` 200
+/// and `details` 2000 bytes (at character boundaries) — so a hostile log can never overflow an
+/// insert.
+pub fn bounded_message(
+  task_id: i64,
+  severity: &str,
+  mut category: String,
+  mut what: String,
+  mut details: String,
+) -> NewTaskMessage {
+  let mut severity = severity.to_lowercase();
+  utf_truncate(&mut severity, 50);
+  utf_truncate(&mut category, 100);
+  utf_truncate(&mut what, 200);
+  utf_truncate(&mut details, 2000);
+  NewTaskMessage::new(task_id, &severity, category, what, details)
+}
+
+/// LaTeXML's convention (described at
+/// [the Manual](http://dlmf.nist.gov/LaTeXML/manual/errorcodes/index.html)): see
+/// [`super::parse_log`] and [`super::classify_log`].
+pub struct Latexml;
+
+impl LogParser for Latexml {
+  fn key(&self) -> &'static str { "latexml" }
+  fn description(&self) -> &'static str {
+    "LaTeXML messages: Severity:category:what details lines and an Info:conversion:<N> status"
+  }
+  fn classify(&self, task_id: i64, log: &str) -> (TaskStatus, Vec<NewTaskMessage>) {
+    classify_log(task_id, log)
+  }
+}
+
+/// One line of a JSON-lines log: a message, or the status (or both — the status wins).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JsonLine {
+  status: Option<String>,
+  severity: Option<String>,
+  category: Option<String>,
+  what: Option<String>,
+  details: Option<String>,
+}
+
+/// A structured log: one JSON object per line. A message line carries `severity` (`info`,
+/// `warning`, `error`, `fatal` or `invalid`; anything else is info), `category`, `what` and
+/// `details`; the status line carries `status` (`no_problem`, `warning`, `error`, `fatal` or
+/// `invalid`), the last one counting. Any `invalid` message makes the task `Invalid`, as with
+/// LaTeXML. Lines that are not such objects (a tool's stray output) are noise.
+pub struct JsonLines;
+
+impl LogParser for JsonLines {
+  fn key(&self) -> &'static str { "json-lines" }
+  fn description(&self) -> &'static str {
+    "one JSON object per line: severity/category/what/details messages and a status line"
+  }
+  fn classify(&self, task_id: i64, log: &str) -> (TaskStatus, Vec<NewTaskMessage>) {
+    let mut status = None;
+    let mut messages = Vec::new();
+    for line in log.lines() {
+      let line = line.trim();
+      if !line.starts_with('{') {
+        continue;
+      }
+      let Ok(record) = serde_json::from_str::<JsonLine>(line) else {
+        continue;
+      };
+      if let Some(key) = record.status {
+        status = match key.trim().to_lowercase().as_str() {
+          "no_problem" => Some(TaskStatus::NoProblem),
+          "warning" => Some(TaskStatus::Warning),
+          "error" => Some(TaskStatus::Error),
+          "invalid" => Some(TaskStatus::Invalid),
+          _ => Some(TaskStatus::Fatal),
+        };
+        continue;
+      }
+      let Some(severity) = record.severity else {
+        continue;
+      };
+      messages.push(bounded_message(
+        task_id,
+        &severity,
+        record.category.unwrap_or_default(),
+        record.what.unwrap_or_default(),
+        record.details.unwrap_or_default(),
+      ));
+    }
+    let invalid = messages
+      .iter()
+      .any(|message| matches!(message, NewTaskMessage::Invalid(_)));
+    let status = if invalid {
+      TaskStatus::Invalid
+    } else {
+      status.unwrap_or(TaskStatus::Fatal)
+    };
+    (status, messages)
+  }
+}
+
+/// The output kept as the details of a failed exit: its last bytes, up to a details column.
+const EXIT_OUTPUT_TAIL: usize = 2000;
+
+/// A tool that reports nothing but how it exited: the log is its output, ending with a line holding
+/// only its exit code. `0` is `NoProblem`; any other code is `Fatal`, with a `fatal:exit_code:<N>`
+/// message carrying the tail of the output. A log not ending in an exit code is `Fatal` too
+/// (`fatal:exit_code:missing`).
+pub struct ExitCode;
+
+impl LogParser for ExitCode {
+  fn key(&self) -> &'static str { "exit-code" }
+  fn description(&self) -> &'static str {
+    "the tool's output, ending with a line holding only its exit code (0 succeeds)"
+  }
+  fn classify(&self, task_id: i64, log: &str) -> (TaskStatus, Vec<NewTaskMessage>) {
+    let trimmed = log.trim_end();
+    let (output, last_line) = match trimmed.rfind('\n') {
+      Some(index) => (&trimmed[..index], &trimmed[index + 1..]),
+      None => ("", trimmed),
+    };
+    let (code, output) = match last_line.trim().parse::<i64>() {
+      Ok(code) => (Some(code), output),
+      Err(_) => (None, trimmed),
+    };
+    if code == Some(0) {
+      return (TaskStatus::NoProblem, Vec::new());
+    }
+    let what = code.map_or_else(|| "missing".to_string(), |code| code.to_string());
+    let message = bounded_message(
+      task_id,
+      "fatal",
+      "exit_code".to_string(),
+      what,
+      output_tail(output).to_string(),
+    );
+    (TaskStatus::Fatal, vec![message])
+  }
+}
+
+/// The last [`EXIT_OUTPUT_TAIL`] bytes of `output`, starting at a character boundary.
+fn output_tail(output: &str) -> &str {
+  let output = output.trim();
+  let mut start = output.len().saturating_sub(EXIT_OUTPUT_TAIL);
+  while !output.is_char_boundary(start) {
+    start += 1;
+  }
+  &output[start..]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn summary(messages: &[NewTaskMessage]) -> Vec<(String, String, String)> {
+    messages
+      .iter()
+      .map(|message| {
+        (
+          message.severity().to_string(),
+          message.category().to_string(),
+          message.what().to_string(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn finds_parsers_by_key() {
+    assert_eq!(find("json-lines").map(LogParser::key), Some("json-lines"));
+    assert!(find("pandoc").is_none());
+    assert_eq!(default_parser().key(), DEFAULT_LOG_PARSER);
+    assert_eq!(keys(), "latexml, json-lines, exit-code");
+  }
+
+  #[test]
+  fn latexml_keeps_todays_behaviour() {
+    let log = "Warning:undefined:\\foo line 3\n\tmore context\nInfo:conversion:1\n";
+    let (status, messages) = Latexml.classify(5, log);
+    let (expected_status, expected) = classify_log(5, log);
+    assert_eq!(status, expected_status);
+    assert_eq!(status, TaskStatus::Warning);
+    assert_eq!(summary(&messages), summary(&expected));
+    assert_eq!(messages[0].details(), "line 3\n\tmore context");
+  }
+
+  #[test]
+  fn json_lines_reads_messages_and_the_status() {
+    let log = concat!(
+      "pandoc 3.1 starting\n",
+      r#"{"severity":"warning","category":"unsupported","what":"\\tikz","details":"line 7"}"#,
+      "\n",
+      r#"{"severity":"shout","category":"x","what":"y"}"#,
+      "\n",
+      "{not json\n",
+      r#"{"status":"warning"}"#,
+      "\n",
+    );
+    let (status, messages) = JsonLines.classify(3, log);
+    assert_eq!(status, TaskStatus::Warning);
+    assert_eq!(
+      summary(&messages),
+      [
+        (
+          "warning".to_string(),
+          "unsupported".to_string(),
+          "\\tikz".to_string()
+        ),
+        ("info".to_string(), "x".to_string(), "y".to_string()),
+      ]
+    );
+    assert_eq!(messages[0].details(), "line 7");
+
+    let (status, _) = JsonLines.classify(3, r#"{"severity":"error","category":"a","what":"b"}"#);
+    assert_eq!(status, TaskStatus::Fatal, "no status line is Fatal");
+    let (status, _) = JsonLines.classify(
+      3,
+      "{\"severity\":\"invalid\",\"category\":\"no_tex\",\"what\":\"all\"}\n{\"status\":\"no_problem\"}",
+    );
+    assert_eq!(status, TaskStatus::Invalid, "an invalid message is final");
+  }
+
+  #[test]
+  fn json_lines_clamps_hostile_fields() {
+    let long = "é".repeat(5_000);
+    let log =
+      format!(r#"{{"severity":"warning","category":"{long}","what":"{long}","details":"{long}"}}"#);
+    let (_, messages) = JsonLines.classify(1, &log);
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].category().len() <= 100);
+    assert!(messages[0].what().len() <= 200);
+    assert!(messages[0].details().len() <= 2000);
+  }
+
+  #[test]
+  fn exit_code_reads_the_last_line() {
+    let (status, messages) = ExitCode.classify(1, "converted 3 pages\n0\n");
+    assert_eq!(status, TaskStatus::NoProblem);
+    assert!(messages.is_empty());
+    let (status, messages) = ExitCode.classify(1, "error: bad xref\nfailed\n2\n");
+    assert_eq!(status, TaskStatus::Fatal);
+    assert_eq!(
+      summary(&messages),
+      [(
+        "fatal".to_string(),
+        "exit_code".to_string(),
+        "2".to_string()
+      )]
+    );
+    assert_eq!(messages[0].details(), "error: bad xref\nfailed");
+    let (status, messages) = ExitCode.classify(1, "segmentation fault");
+    assert_eq!(status, TaskStatus::Fatal);
+    assert_eq!(messages[0].what(), "missing");
+    assert_eq!(messages[0].details(), "segmentation fault");
+    let noisy = format!("{}\n1\n", "ü".repeat(3_000));
+    let (_, messages) = ExitCode.classify(1, &noisy);
+    assert!(messages[0].details().len() <= EXIT_OUTPUT_TAIL);
+  }
+}
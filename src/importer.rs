@@ -6,19 +6,33 @@
 // except according to those terms.
 
 //! Import a new corpus into the framework
+pub mod changes;
+pub mod inspect;
+pub mod manifest;
+pub mod preview;
+pub mod unpackers;
+
 use crate::backend::Backend;
 use crate::helpers::TaskStatus;
-use crate::models::{Corpus, NewTask};
-use glob::glob;
+use crate::models::{Corpus, Document, IMPORT_SERVICE_ID, NewDocument, NewEntryMetadata, NewTask};
+use changes::{ExtendReport, SourceCheck};
+use diesel::prelude::*;
+use manifest::{Manifest, ManifestReport};
 use std::collections::HashSet;
 use std::env;
 use std::error::Error;
 use std::fs;
-use std::fs::File;
-use std::io::{Read, Write};
 use std::path::Path;
 use std::path::PathBuf;
 
+/// Depth bound on the directory walks of an import (the import walk, and the unpackers').
+/// `fs::metadata` follows symlinks, so a corpus containing a symlink loop (malicious, or an
+/// accidental backup/snapshot link) would otherwise recurse forever — unbounded paths → unbounded
+/// tasks + a job that keeps "progressing", so the heartbeat-keyed stale-reap never fires.
+/// arXiv-topology corpora are ~3 levels deep, so 64 bounds any loop without truncating a real
+/// corpus (DESIGN_PRINCIPLES: no unbounded per-event resource acquisition on hostile data).
+pub const MAX_WALK_DEPTH: usize = 64;
+
 /// Struct for performing corpus imports into `CorTeX`
 #[derive(Debug)]
 pub struct Importer {
@@ -50,6 +64,7 @@ impl Default for Importer {
         // A nil sentinel: this mock corpus is never registered in the DB, so it carries no real
         // external handle (a registered corpus gets its UUIDv7 from the column default).
         public_id: uuid::Uuid::nil(),
+        unpacker: None,
       },
       backend: default_backend,
       cwd: Importer::cwd(),
@@ -61,202 +76,126 @@ impl Default for Importer {
 impl Importer {
   /// Convenience method for (recklessly?) obtaining the current working dir
   pub fn cwd() -> PathBuf { env::current_dir().unwrap() }
-  /// Top-level method for unpacking an arxiv-toplogy corpus from its tar-ed form
-  fn unpack(&mut self) -> Result<(), Box<dyn Error>> {
-    self.unpack_arxiv_top()?;
-    self.unpack_arxiv_months()?;
-    Ok(())
-  }
-  fn unpack_extend(&mut self) -> Result<(), Box<dyn Error>> {
-    self.unpack_extend_arxiv_top()?;
-    // We can reuse the monthly unpack, as it deletes all unpacked document archives
-    // In other words, it always acts as a conservative extension
-    self.unpack_arxiv_months()?;
-    Ok(())
-  }
-
-  /// Unpack the top-level tar files from an arxiv-topology corpus
-  fn unpack_arxiv_top(&mut self) -> Result<(), Box<dyn Error>> {
-    let path_str = self.corpus.path.clone();
-    println!("-- Starting top-level unpack at {path_str}");
-    // A corpus path containing glob metacharacters (`[`, `{`, …) makes `glob` return a
-    // `PatternError`; propagate it as a clean import failure rather than `.unwrap()`-panicking.
-    for entry in glob(&(path_str.clone() + "/*.tar"))? {
-      match entry {
-        Ok(path) => match unpack_top_tar(&path, &path_str, false) {
-          Ok(prefixes) => self.active_prefixes.extend(prefixes),
-          Err(reason) => eprintln!("-- import: skipping tar {path:?}: {reason}"),
-        },
-        Err(e) => println!("Failed tar glob: {e:?}"),
-      }
-    }
-    Ok(())
-  }
-
-  /// Top-level extension unpacking for arxiv-topology corpora
-  fn unpack_extend_arxiv_top(&mut self) -> Result<(), Box<dyn Error>> {
-    let mut path_str = self.corpus.path.clone();
-    if !path_str.ends_with('/') {
-      path_str.push('/');
-    }
-    println!("-- Starting top-level unpack-extend at {path_str}");
-    for entry in glob(&(path_str.clone() + "/*.tar"))? {
-      match entry {
-        Ok(path) => match unpack_top_tar(&path, &path_str, true) {
-          Ok(prefixes) => self.active_prefixes.extend(prefixes),
-          Err(reason) => eprintln!("-- import: skipping tar {path:?}: {reason}"),
-        },
-        Err(e) => println!("Failed tar glob: {e:?}"),
-      }
-    }
-    Ok(())
-  }
-
-  /// Unpack the monthly sub-archives of an arxiv-topology corpus, into the CorTeX organization
-  fn unpack_arxiv_months(&self) -> Result<(), Box<dyn Error>> {
-    println!("-- Starting to unpack monthly .gz archives");
-    let path_str = self.corpus.path.clone();
-    let gzs_paths = if self.active_prefixes.is_empty() {
-      vec![path_str + "/*/*.gz"]
-    } else {
-      self
-        .active_prefixes
-        .iter()
-        .map(|ap| format!("{path_str}/{ap}/*.gz"))
-        .collect()
-    };
-    // Skip (with a log) any glob pattern that fails to compile rather than `.unwrap()`-panicking
-    // the whole unpack; the valid patterns still contribute their entries.
-    let globs_iter = gzs_paths
-      .iter()
-      .filter_map(|pattern| match glob(pattern) {
-        Ok(paths) => Some(paths),
-        Err(e) => {
-          eprintln!("-- import: skipping invalid glob pattern {pattern:?}: {e}");
-          None
-        },
-      })
-      .flatten();
-    for entry in globs_iter {
-      match entry {
-        Ok(path) => {
-          if let Err(reason) = unpack_one_gz(&path) {
-            eprintln!("-- import: skipping .gz {path:?}: {reason}");
-          }
-        },
-        Err(e) => println!("Failed gz glob: {e:?}"),
-      }
-    }
+  /// Brings the sources of a complex corpus into the CorTeX layout with the corpus's unpacker (see
+  /// [`unpackers`]), limiting the import walk to the top-level directories it reports. With
+  /// `extend`, sources the unpacker can tell were unpacked before are skipped.
+  fn unpack_sources(&mut self, extend: bool) -> Result<(), Box<dyn Error>> {
+    let key = self.corpus.unpacker_key();
+    let unpacker = unpackers::find(key).ok_or_else(|| {
+      format!(
+        "unknown unpacker {key:?} for corpus {:?} (known: {})",
+        self.corpus.name,
+        unpackers::keys()
+      )
+    })?;
+    let prefixes = unpacker.unpack(Path::new(self.corpus.path.trim_end()), extend)?;
+    self.active_prefixes.extend(prefixes);
     Ok(())
   }
   /// Given a CorTeX-topology corpus, walk the file system and import it into the Task store
   pub fn walk_import(&mut self) -> Result<usize, Box<dyn Error>> {
-    // Depth bound on the directory walk. `fs::metadata` follows symlinks, so a corpus containing a
-    // symlink loop (malicious, or an accidental backup/snapshot link) would otherwise recurse
-    // forever — unbounded paths → unbounded tasks + a job that keeps "progressing", so the
-    // heartbeat-keyed stale-reap never fires. arXiv-topology corpora are ~3 levels deep, so 64
-    // bounds any loop without truncating a real corpus (DESIGN_PRINCIPLES: no unbounded per-event
-    // resource acquisition on hostile data).
-    const MAX_WALK_DEPTH: usize = 64;
-    let import_extension = if self.corpus.complex { "zip" } else { "tex" };
-    let mut walk_q: Vec<(PathBuf, usize)> = vec![(Path::new(&self.corpus.path).to_owned(), 0)];
-    println!("-- Starting import walk at {}", self.corpus.path);
+    let root = self.corpus.path.clone();
+    let active_prefixes = self.active_prefixes.clone();
+    println!("-- Starting import walk at {root}");
     let mut import_q: Vec<NewTask> = Vec::new();
     let mut import_counter = 0;
-    while let Some((current_path, depth)) = walk_q.pop() {
-      if depth > MAX_WALK_DEPTH {
-        eprintln!(
-          "-- import: skipping {current_path:?} beyond max walk depth {MAX_WALK_DEPTH} \
-           (possible symlink loop)"
-        );
-        continue;
-      }
-      // arXiv data is hostile: one unreadable path (a broken symlink, a vanished or
-      // permission-denied entry) or a non-UTF-8 name must **skip**, not abort the whole import —
-      // blast-radius isolation + transparent (logged) failure (docs/DESIGN_PRINCIPLES.md). Only a
-      // backend write error is fatal (the DB is essential), and it now propagates as a `Result`
-      // rather than `.unwrap()`-panicking.
-      let current_metadata = match fs::metadata(&current_path) {
-        Ok(meta) => meta,
-        Err(e) => {
-          eprintln!("-- import: skipping unreadable path {current_path:?}: {e}");
-          continue;
-        },
-      };
-      if !current_metadata.is_dir() {
-        continue;
-      }
-      let current_path_str = match current_path.to_str() {
-        Some(path_str) => path_str.to_string(),
-        None => {
-          eprintln!("-- import: skipping non-UTF-8 path {current_path:?}");
-          continue;
-        },
-      };
-      let rel_path = current_path_str.replace(&self.corpus.path, "");
-      let mut slash_iter = rel_path.split('/');
-      if rel_path.starts_with('/') {
-        // drop the corpus root piece.
-        slash_iter.next();
-      }
-      if let Some(base) = slash_iter.next() {
-        // if we have an "active_prefixes" filter, comply with it
-        if !base.is_empty()
-          && !self.active_prefixes.is_empty()
-          && !self.active_prefixes.contains(base)
-        {
-          continue;
-        }
-      }
-      // First, test if we just found an entry:
-      let current_entry = match current_path.file_name().and_then(|name| name.to_str()) {
-        Some(local_dir) => local_dir.to_string() + "." + import_extension,
-        None => {
-          eprintln!("-- import: skipping path with no usable file name {current_path:?}");
-          continue;
-        },
-      };
-      let current_entry_path = current_path_str + "/" + &current_entry;
-      match fs::metadata(&current_entry_path) {
-        Ok(_) => {
+    walk_entries(&root, self.corpus.complex, &active_prefixes, &mut |event| {
+      match event {
+        WalkEvent::Entry { path, .. } => {
           // Found the expected file, import this entry:
-          println!("Found entry: {current_entry_path:?}");
+          println!("Found entry: {path:?}");
           import_counter += 1;
-          import_q.push(self.new_task(&current_entry_path));
+          import_q.push(self.new_task(&path));
           if import_q.len() >= 1000 {
             // Flush the import queue to backend:
             println!("Checkpoint backend writer: job {import_counter:?}");
-            self.backend.mark_imported(&import_q)?;
+            self.import_batch(&import_q)?;
             import_q.clear();
           }
         },
-        Err(_) => {
-          // No such entry found, traverse into the directory — skipping (not aborting) an
-          // unreadable directory or directory entry.
-          match fs::read_dir(&current_path) {
-            Ok(entries) => {
-              for subentry in entries {
-                match subentry {
-                  Ok(subentry) => walk_q.push((subentry.path(), depth + 1)),
-                  Err(e) => {
-                    eprintln!("-- import: skipping unreadable entry under {current_path:?}: {e}")
-                  },
-                }
-              }
-            },
-            Err(e) => eprintln!("-- import: skipping unreadable directory {current_path:?}: {e}"),
-          }
-        },
+        skipped => skipped.log(),
       }
-    }
+      Ok(())
+    })?;
     if !import_q.is_empty() {
       println!("Checkpoint backend writer: job {:?}", import_q.len());
-      self.backend.mark_imported(&import_q)?;
+      self.import_batch(&import_q)?;
     }
     println!("--- Imported {import_counter:?} entries.");
     Ok(import_counter)
   }
 
+  /// Writes a batch of found entries: their import tasks, plus a document row for each entry not
+  /// inspected before (so the re-walk of an `extend` only inspects the new arrivals).
+  fn import_batch(&mut self, batch: &[NewTask]) -> Result<usize, Box<dyn Error>> {
+    let documents = self.inspect_new(batch.iter().map(|task| task.entry.as_str()))?;
+    Ok(
+      self
+        .backend
+        .mark_imported_documents(batch, &documents, &[])?,
+    )
+  }
+
+  /// Inspects those of `entries` that have no document row yet.
+  fn inspect_new<'e>(
+    &mut self,
+    entries: impl Iterator<Item = &'e str>,
+  ) -> Result<Vec<NewDocument>, diesel::result::Error> {
+    let entries: Vec<String> = entries.map(str::to_string).collect();
+    let known = Document::known_entries(&mut self.backend.connection, self.corpus.id, &entries)?;
+    Ok(
+      entries
+        .iter()
+        .filter(|entry| !known.contains(*entry))
+        .map(|entry| inspect::inspect(self.corpus.id, entry, self.corpus.complex))
+        .collect(),
+    )
+  }
+
+  /// Records a document row for every entry of the corpus that has none yet — the backfill for a
+  /// corpus imported before documents were inspected. Pages through the import tasks by entry, so
+  /// any corpus size runs in bounded memory. Returns the number of entries inspected.
+  pub fn index_documents(&mut self) -> Result<usize, Box<dyn Error>> {
+    use crate::schema::tasks::dsl;
+    const PAGE_SIZE: i64 = 1000;
+    let mut inspected = 0;
+    let mut after = String::new();
+    loop {
+      let page: Vec<String> = dsl::tasks
+        .filter(dsl::corpus_id.eq(self.corpus.id))
+        .filter(dsl::service_id.eq(IMPORT_SERVICE_ID))
+        .filter(dsl::entry.gt(after.clone()))
+        .select(dsl::entry)
+        .order(dsl::entry.asc())
+        .limit(PAGE_SIZE)
+        .load(&mut self.backend.connection)?;
+      let documents = self.inspect_new(page.iter().map(String::as_str))?;
+      inspected += Document::insert_new(&mut self.backend.connection, &documents)?;
+      match page.last() {
+        Some(last) if page.len() as i64 == PAGE_SIZE => after.clone_from(last),
+        _ => break,
+      }
+      println!("Checkpoint document index: {inspected:?} inspected");
+    }
+    println!("--- Inspected {inspected:?} entries.");
+    Ok(inspected)
+  }
+
+  /// Sets the arXiv primary category of the corpus's documents from an arXiv metadata file (JSON
+  /// lines with `id` and `categories`, as in the public arXiv metadata snapshot), streamed in
+  /// batches. Returns the number of documents updated.
+  pub fn apply_arxiv_metadata(&mut self, metadata_path: &Path) -> Result<usize, Box<dyn Error>> {
+    let corpus_id = self.corpus.id;
+    let connection = &mut self.backend.connection;
+    let mut updated = 0;
+    let read = inspect::read_arxiv_categories(metadata_path, 10_000, |batch| {
+      updated += Document::set_primary_categories(connection, corpus_id, batch)?;
+      Ok::<(), diesel::result::Error>(())
+    })?;
+    println!("--- Read {read:?} arXiv categories; updated {updated:?} documents.");
+    Ok(updated)
+  }
+
   /// Create a new TODO task for the "import" service and the Importer-specified corpus
   /// (should get marked as "NoProblem" once the extension is completed)
   pub fn new_task(&self, entry: &str) -> NewTask {
@@ -272,7 +211,7 @@ impl Importer {
       entry: abs_entry,
       status: TaskStatus::TODO.raw(),
       corpus_id: self.corpus.id,
-      service_id: 2,
+      service_id: IMPORT_SERVICE_ID,
     }
   }
   /// Top-level import driver, performs an optional unpack, and then an import into the Task store
@@ -280,7 +219,7 @@ impl Importer {
     // println!("Greetings from the import processor");
     if self.corpus.complex {
       // Complex setup has an unpack step:
-      self.unpack()?;
+      self.unpack_sources(false)?;
     }
     // Walk the directory tree and import the files in the Task store:
     self.walk_import()?;
@@ -288,12 +227,91 @@ impl Importer {
     Ok(())
   }
 
-  /// Top-level corpus extension, performs a check for newly added documents and extracts+adds
-  /// them to the existing corpus tasks
-  pub fn extend_corpus(&mut self) -> Result<(), Box<dyn Error>> {
+  /// Top-level manifest import: validates every path the manifest at `manifest_path` lists
+  /// (relative ones resolve against the corpus root), then imports the valid entries with their
+  /// manifest metadata attached. Missing, unreadable and malformed rows are skipped and reported,
+  /// not fatal; only an unreadable manifest or a backend write error is. No unpack step — the
+  /// manifest names the document files themselves.
+  pub fn import_manifest(
+    &mut self,
+    manifest_path: &Path,
+  ) -> Result<ManifestReport, Box<dyn Error>> {
+    let manifest = Manifest::read(manifest_path)?;
+    let mut report = ManifestReport::default();
+    let valid = manifest.validate(
+      Path::new(self.corpus.path.trim_end()),
+      self.corpus.complex,
+      &mut report,
+    );
+    for problem in &report.problems {
+      eprintln!(
+        "-- import: skipping manifest line {} ({:?}): {}",
+        problem.line,
+        problem.path.as_deref().unwrap_or(""),
+        problem.reason
+      );
+    }
+    if report.skipped() > report.problems.len() {
+      eprintln!(
+        "-- import: … and {} more skipped manifest rows",
+        report.skipped() - report.problems.len()
+      );
+    }
+    for batch in valid.chunks(1000) {
+      let tasks: Vec<NewTask> = batch
+        .iter()
+        .map(|valid| self.new_task(&valid.entry))
+        .collect();
+      let mut documents = self.inspect_new(batch.iter().map(|valid| valid.entry.as_str()))?;
+      // A manifest may carry the arXiv categories itself.
+      for document in &mut documents {
+        let listed = batch
+          .iter()
+          .find(|valid| valid.entry == document.entry)
+          .and_then(|valid| {
+            valid
+              .metadata
+              .get("primary_category")
+              .or_else(|| valid.metadata.get("categories"))
+          })
+          .and_then(|categories| categories.as_str())
+          .and_then(inspect::primary_category);
+        if listed.is_some() {
+          document.primary_category = listed;
+        }
+      }
+      // A path-only row upserts an empty object, clearing what an earlier manifest attached.
+      let metadata: Vec<NewEntryMetadata> = batch
+        .iter()
+        .map(|valid| NewEntryMetadata {
+          corpus_id: self.corpus.id,
+          entry: valid.entry.clone(),
+          metadata: serde_json::Value::Object(valid.metadata.clone()),
+        })
+        .collect();
+      report.imported += self
+        .backend
+        .mark_imported_documents(&tasks, &documents, &metadata)?;
+      println!("Checkpoint backend writer: job {:?}", report.imported);
+    }
+    println!(
+      "--- Imported {:?} entries from the manifest ({} listed, {} skipped).",
+      report.imported,
+      report.listed,
+      report.skipped()
+    );
+    Ok(report)
+  }
+
+  /// Top-level corpus extension: extracts and adds newly arrived documents to the existing corpus
+  /// tasks, then checks every known entry's source for in-place changes — a changed entry's
+  /// conversions are reset to TODO, a vanished one is flagged (see [`changes`]). Returns the
+  /// report of new, changed and vanished entries.
+  pub fn extend_corpus(&mut self) -> Result<ExtendReport, Box<dyn Error>> {
+    let before = self.import_task_count()?;
     if self.corpus.complex {
       // Complex setup has an unpack step:
-      self.unpack_extend()?;
+      self.unpack_sources(true)?;
     }
     // Before we import, mark any current runs as completed.
     for service in self
@@ -312,229 +330,222 @@ impl Importer {
     // Use the regular walk_import, at the cost of more database work,
     // the "Backend::mark_imported" ORM method allows us to insert only if new
     self.walk_import()?;
+    let mut report = ExtendReport {
+      new: self.import_task_count()?.saturating_sub(before),
+      ..ExtendReport::default()
+    };
+    self.check_sources(&mut report)?;
+    println!(
+      "--- Extended: {} new, {} changed ({} tasks reset), {} vanished, {} unreadable.",
+      report.new, report.changed, report.reset_tasks, report.vanished, report.unreadable
+    );
+    Ok(report)
+  }
+
+  /// Checks the source of every known entry of the corpus against its recorded fingerprint,
+  /// paging through the documents by entry so any corpus size runs in bounded memory: changed
+  /// entries are re-inspected and their conversions reset, vanished ones flagged, and fresh
+  /// fingerprints recorded. Tallies the outcome into `report`.
+  fn check_sources(&mut self, report: &mut ExtendReport) -> Result<(), Box<dyn Error>> {
+    const PAGE_SIZE: i64 = 1000;
+    let corpus_id = self.corpus.id;
+    let mut after = String::new();
+    let mut checked = 0;
+    loop {
+      let page = Document::page(&mut self.backend.connection, corpus_id, &after, PAGE_SIZE)?;
+      let mut changed = Vec::new();
+      let mut vanished = Vec::new();
+      let mut fingerprints = Vec::new();
+      for document in &page {
+        let check = changes::check_source(document, self.corpus.complex);
+        if document.vanished_at.is_some()
+          && matches!(
+            check,
+            SourceCheck::Fingerprinted { .. } | SourceCheck::Changed(_)
+          )
+        {
+          report.reappeared += 1;
+        }
+        match check {
+          SourceCheck::Unchanged => {},
+          SourceCheck::Fingerprinted {
+            content_hash,
+            source_modified,
+          } => fingerprints.push((document.entry.clone(), content_hash, source_modified)),
+          SourceCheck::Changed(inspected) => {
+            report.record_changed(&document.entry);
+            changed.push(inspected);
+          },
+          SourceCheck::Vanished => {
+            report.record_vanished(&document.entry);
+            vanished.push(document.entry.clone());
+          },
+          SourceCheck::Unreadable(reason) => {
+            eprintln!(
+              "-- extend: cannot check {:?}: {reason}",
+              document.entry.trim_end()
+            );
+            report.unreadable += 1;
+          },
+        }
+      }
+      checked += page.len();
+      report.reset_tasks += self.backend.mark_changed_sources(&self.corpus, &changed)?;
+      Document::mark_vanished(&mut self.backend.connection, corpus_id, &vanished)?;
+      Document::record_fingerprints(&mut self.backend.connection, corpus_id, &fingerprints)?;
+      match page.last() {
+        Some(last) if page.len() as i64 == PAGE_SIZE => after.clone_from(&last.entry),
+        _ => break,
+      }
+      println!("Checkpoint source check: {checked:?} checked");
+    }
     Ok(())
   }
+
+  /// The number of import tasks (documents) of the corpus.
+  fn import_task_count(&mut self) -> Result<usize, diesel::result::Error> {
+    use crate::schema::tasks::dsl;
+    let count: i64 = dsl::tasks
+      .filter(dsl::corpus_id.eq(self.corpus.id))
+      .filter(dsl::service_id.eq(IMPORT_SERVICE_ID))
+      .count()
+      .get_result(&mut self.backend.connection)?;
+    Ok(usize::try_from(count).unwrap_or_default())
+  }
+}
+
+/// What a walk over a corpus meets: the import walk, and the preview of an unpack (see
+/// [`unpackers::Unpacker::preview`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WalkEvent {
+  /// a document entry — or, in a preview, the entry an unpack would write — and its source size
+  Entry {
+    /// path of the entry
+    path: String,
+    /// size of its source, in bytes
+    bytes: u64,
+  },
+  /// a path skipped, and why
+  Skipped {
+    /// the skipped path
+    path: PathBuf,
+    /// why it was skipped
+    reason: String,
+  },
+  /// a directory beyond [`MAX_WALK_DEPTH`], most likely inside a symlink loop
+  TooDeep(PathBuf),
+}
+
+impl WalkEvent {
+  /// Logs a skip the way an import reports it; an entry is not logged.
+  pub fn log(&self) {
+    match self {
+      WalkEvent::Entry { .. } => {},
+      WalkEvent::Skipped { path, reason } => eprintln!("-- import: skipping {path:?}: {reason}"),
+      WalkEvent::TooDeep(path) => eprintln!(
+        "-- import: skipping {path:?} beyond max walk depth {MAX_WALK_DEPTH} (possible symlink loop)"
+      ),
+    }
+  }
 }
 
-/// Transfer the data contained within `Reader` to a `Writer`, assuming it was a single file
-/// Extracts the entries of one top-level arXiv `.tar` to disk (skipping `.pdf`s, and entries
-/// already unpacked), returning the set of top-level prefixes seen. Hostile-data tolerant (I-1): a
-/// bad entry is logged + skipped, never a panic. `extend` additionally skips an entry whose
-/// unpacked directory (the entry name without its `.gz` suffix) already exists.
-fn unpack_top_tar(
-  tar_path: &Path,
-  path_str: &str,
-  extend: bool,
-) -> Result<HashSet<String>, String> {
-  let file = File::open(tar_path).map_err(|e| format!("open: {e}"))?;
-  let mut archive = tar::Archive::new(file);
-  let mut prefixes = HashSet::new();
-  for entry in archive.entries().map_err(|e| format!("tar entries: {e}"))? {
-    let mut entry = match entry {
-      Ok(entry) => entry,
+/// Walks a CorTeX-topology corpus at `root` — every `<dir>/<dir>.zip` (`.tex` if not `complex`)
+/// is an entry — reporting each entry and each skipped path to `visit`, and writing nothing. With
+/// `active_prefixes`, only the top-level directories they name are walked. Only an error of `visit`
+/// stops the walk.
+pub fn walk_entries(
+  root: &str,
+  complex: bool,
+  active_prefixes: &HashSet<String>,
+  visit: &mut dyn FnMut(WalkEvent) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+  let import_extension = if complex { "zip" } else { "tex" };
+  let mut walk_q: Vec<(PathBuf, usize)> = vec![(Path::new(root).to_owned(), 0)];
+  while let Some((current_path, depth)) = walk_q.pop() {
+    if depth > MAX_WALK_DEPTH {
+      visit(WalkEvent::TooDeep(current_path))?;
+      continue;
+    }
+    // arXiv data is hostile: one unreadable path (a broken symlink, a vanished or
+    // permission-denied entry) or a non-UTF-8 name must **skip**, not abort the whole import —
+    // blast-radius isolation + transparent (logged) failure (docs/DESIGN_PRINCIPLES.md). Only a
+    // backend write error is fatal (the DB is essential), and it propagates as a `Result` rather
+    // than `.unwrap()`-panicking.
+    let current_metadata = match fs::metadata(&current_path) {
+      Ok(meta) => meta,
       Err(e) => {
-        eprintln!("-- import: skipping unreadable tar entry in {tar_path:?}: {e}");
+        let reason = format!("unreadable path: {e}");
+        visit(WalkEvent::Skipped {
+          path: current_path,
+          reason,
+        })?;
         continue;
       },
     };
-    let entry_pathname = match entry.path() {
-      Ok(path) => path.to_string_lossy().into_owned(),
-      Err(_) => continue,
-    };
-    if entry_pathname.ends_with(".pdf") {
+    if !current_metadata.is_dir() {
       continue;
     }
-    if let Some(base) = entry_pathname.split('/').next() {
-      prefixes.insert(base.to_owned());
-    }
-    let full_extract_path = format!("{path_str}{entry_pathname}");
-    if fs::metadata(&full_extract_path).is_ok() {
-      continue; // already unpacked
-    }
-    if extend {
-      let dir_extract_path = full_extract_path
-        .strip_suffix(".gz")
-        .unwrap_or(&full_extract_path);
-      if dir_extract_path != full_extract_path && fs::metadata(dir_extract_path).is_ok() {
+    let current_path_str = match current_path.to_str() {
+      Some(path_str) => path_str.to_string(),
+      None => {
+        visit(WalkEvent::Skipped {
+          path: current_path,
+          reason: "non-UTF-8 path".to_string(),
+        })?;
         continue;
-      }
-    }
-    if let Some(parent) = Path::new(&full_extract_path).parent() {
-      let _ = fs::create_dir_all(parent);
-    }
-    if let Err(e) = entry.unpack(&full_extract_path) {
-      eprintln!("-- import: failed to extract {full_extract_path:?}: {e}");
+      },
+    };
+    let rel_path = current_path_str.replace(root, "");
+    let mut slash_iter = rel_path.split('/');
+    if rel_path.starts_with('/') {
+      // drop the corpus root piece.
+      slash_iter.next();
     }
-  }
-  Ok(prefixes)
-}
-
-/// Repacks one arXiv per-paper `.gz` source into a `<base>.zip` under `<dir>/<base>/`, then removes
-/// the `.gz`. The decompressed content is **content-detected** (filenames lie): a `tar` becomes its
-/// file entries; headerless content (the arXiv "surprise" — a plain gzipped `.tex`) becomes
-/// `<base>.tex`; any other detected type (e.g. a raw PDF) is **rejected** (the `.gz` kept for
-/// inspection). Hostile-data tolerant (I-1): returns `Err` rather than panicking, so the caller
-/// logs
-/// + skips this one and continues the import.
-fn unpack_one_gz(path: &Path) -> Result<(), String> {
-  let entry_path = path
-    .to_str()
-    .ok_or_else(|| format!("non-UTF8 path {path:?}"))?;
-  let entry_dir = path
-    .parent()
-    .and_then(|p| p.to_str())
-    .ok_or_else(|| format!("no parent dir for {entry_path}"))?;
-  let base_name = path
-    .file_stem()
-    .and_then(|s| s.to_str())
-    .ok_or_else(|| format!("no file stem for {entry_path}"))?;
-  let entry_cp_dir = format!("{entry_dir}/{base_name}");
-  if let Err(reason) = fs::create_dir_all(&entry_cp_dir) {
-    println!("Failed to mkdir -p {entry_cp_dir:?}: {:?}", reason.kind());
-  }
-  let full_extract_path = format!("{entry_cp_dir}/{base_name}.zip");
-
-  // Decompress fully (arXiv per-paper source archives are small).
-  let decompressed = {
-    let file = File::open(entry_path).map_err(|e| format!("open {entry_path}: {e}"))?;
-    let mut decoder = flate2::read::GzDecoder::new(file);
-    let mut buf = Vec::new();
-    decoder
-      .read_to_end(&mut buf)
-      .map_err(|e| format!("gunzip {entry_path}: {e}"))?;
-    buf
-  };
-
-  // Reject mislabeled non-source content *before* writing anything (no stray empty zip left
-  // behind).
-  let detected = infer::get(&decompressed).map(|t| t.extension());
-  if let Some(other) = detected
-    && other != "tar"
-  {
-    return Err(format!(
-      "decompressed content is `{other}`, not a TeX source — rejected"
-    ));
-  }
-
-  let out =
-    File::create(&full_extract_path).map_err(|e| format!("create {full_extract_path}: {e}"))?;
-  let mut zw = zip::ZipWriter::new(out);
-  let opts: zip::write::FileOptions<()> =
-    zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-  if detected == Some("tar") {
-    let mut tar = tar::Archive::new(std::io::Cursor::new(decompressed.as_slice()));
-    for tar_entry in tar.entries().map_err(|e| format!("tar entries: {e}"))? {
-      let mut tar_entry = match tar_entry {
-        Ok(entry) => entry,
-        Err(e) => {
-          eprintln!("-- import: skipping unreadable tar entry in {entry_path}: {e}");
-          continue;
-        },
-      };
-      if !tar_entry.header().entry_type().is_file() {
+    if let Some(base) = slash_iter.next() {
+      // if we have an "active_prefixes" filter, comply with it
+      if !base.is_empty() && !active_prefixes.is_empty() && !active_prefixes.contains(base) {
         continue;
       }
-      let name = match tar_entry.path() {
-        Ok(path) => path.to_string_lossy().into_owned(),
-        Err(_) => continue,
-      };
-      let mut data = Vec::new();
-      if let Err(e) = tar_entry.read_to_end(&mut data) {
-        eprintln!("-- import: reading tar entry {name} failed: {e}");
+    }
+    // First, test if we just found an entry:
+    let current_entry = match current_path.file_name().and_then(|name| name.to_str()) {
+      Some(local_dir) => local_dir.to_string() + "." + import_extension,
+      None => {
+        visit(WalkEvent::Skipped {
+          path: current_path,
+          reason: "no usable file name".to_string(),
+        })?;
         continue;
-      }
-      zw.start_file(&name, opts)
-        .map_err(|e| format!("zip start_file {name}: {e}"))?;
-      zw.write_all(&data)
-        .map_err(|e| format!("zip write {name}: {e}"))?;
+      },
+    };
+    let current_entry_path = current_path_str + "/" + &current_entry;
+    match fs::metadata(&current_entry_path) {
+      Ok(entry_metadata) => visit(WalkEvent::Entry {
+        path: current_entry_path,
+        bytes: entry_metadata.len(),
+      })?,
+      Err(_) => {
+        // No such entry found, traverse into the directory — skipping (not aborting) an
+        // unreadable directory or directory entry.
+        match fs::read_dir(&current_path) {
+          Ok(entries) => {
+            for subentry in entries {
+              match subentry {
+                Ok(subentry) => walk_q.push((subentry.path(), depth + 1)),
+                Err(e) => visit(WalkEvent::Skipped {
+                  path: current_path.clone(),
+                  reason: format!("unreadable entry: {e}"),
+                })?,
+              }
+            }
+          },
+          Err(e) => visit(WalkEvent::Skipped {
+            reason: format!("unreadable directory: {e}"),
+            path: current_path,
+          })?,
+        }
+      },
     }
-  } else {
-    // Headerless = the arXiv "surprise": a plain gzipped TeX file.
-    let tex_target = format!("{base_name}.tex");
-    zw.start_file(&tex_target, opts)
-      .map_err(|e| format!("zip start_file {tex_target}: {e}"))?;
-    zw.write_all(&decompressed)
-      .map_err(|e| format!("zip write {tex_target}: {e}"))?;
-  }
-  zw.finish()
-    .map_err(|e| format!("finalize zip {full_extract_path}: {e}"))?;
-
-  if let Err(e) = fs::remove_file(path) {
-    println!("Can't remove source .gz: {e:?}");
   }
   Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-  use super::*;
-
-  fn write_gz(path: &Path, content: &[u8]) {
-    let mut enc =
-      flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::default());
-    enc.write_all(content).unwrap();
-    enc.finish().unwrap();
-  }
-
-  fn zip_names(zip_path: &Path) -> Vec<String> {
-    let mut archive = zip::ZipArchive::new(File::open(zip_path).unwrap()).unwrap();
-    (0..archive.len())
-      .map(|i| archive.by_index(i).unwrap().name().to_string())
-      .collect()
-  }
-
-  #[test]
-  fn unpack_one_gz_handles_targz_plaintex_and_rejects_wrong_content() {
-    let prefix_dir =
-      std::env::temp_dir().join(format!("cortex_importer_test_{}/0001", std::process::id()));
-    fs::create_dir_all(&prefix_dir).unwrap();
-
-    // (a) a tar.gz with two source files -> a .zip carrying both.
-    let tar_bytes = {
-      let mut builder = tar::Builder::new(Vec::new());
-      for (name, body) in [
-        ("paper.tex", b"\\documentclass{article}".as_ref()),
-        ("fig.eps", b"%!PS-Adobe".as_ref()),
-      ] {
-        let mut header = tar::Header::new_gnu();
-        header.set_size(body.len() as u64);
-        header.set_cksum();
-        builder.append_data(&mut header, name, body).unwrap();
-      }
-      builder.into_inner().unwrap()
-    };
-    let targz = prefix_dir.join("paperA.gz");
-    write_gz(&targz, &tar_bytes);
-    unpack_one_gz(&targz).expect("a tar.gz unpacks");
-    let names = zip_names(&prefix_dir.join("paperA/paperA.zip"));
-    assert!(
-      names.contains(&"paper.tex".to_string()) && names.contains(&"fig.eps".to_string()),
-      "tar.gz -> zip with both files, got {names:?}"
-    );
-    assert!(!targz.exists(), "the source .gz is removed");
-
-    // (b) a plain gzipped .tex (the arXiv "surprise") -> a .zip carrying <base>.tex.
-    let plaintex = prefix_dir.join("paperB.gz");
-    write_gz(
-      &plaintex,
-      b"\\documentclass{article}\\begin{document}hi\\end{document}",
-    );
-    unpack_one_gz(&plaintex).expect("a plain gzipped tex unpacks");
-    assert_eq!(
-      zip_names(&prefix_dir.join("paperB/paperB.zip")),
-      vec!["paperB.tex".to_string()],
-      "a plain gz -> <base>.tex"
-    );
-
-    // (c) a gzipped PDF (wrong content) is rejected; the .gz is kept for inspection.
-    let pdfgz = prefix_dir.join("paperC.gz");
-    write_gz(&pdfgz, b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\nrest of a pdf");
-    assert!(unpack_one_gz(&pdfgz).is_err(), "a gzipped PDF is rejected");
-    assert!(pdfgz.exists(), "the rejected .gz is kept");
-
-    fs::remove_dir_all(
-      std::env::temp_dir().join(format!("cortex_importer_test_{}", std::process::id())),
-    )
-    .ok();
-  }
-}
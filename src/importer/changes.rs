@@ -0,0 +1,209 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Source changes**: how `extend` compares the entries a corpus already knows with what is on
+//! disk now. arXiv replaces a paper's source with its new version *in place* — same directory, same
+//! entry path — so a re-walk that only looks for new directories never sees it. Each known entry's
+//! source is checked against the fingerprint recorded at inspection (size, modification time,
+//! content hash): unchanged, changed (its conversions are reset), or vanished (flagged).
+//!
+//! The check is cheap for the untouched majority: a source whose size and modification time match
+//! the recorded ones is not re-read. Only a touched (or never hashed) source is hashed again.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use super::inspect;
+use crate::models::{Document, NewDocument};
+
+/// Most entries an [`ExtendReport`] lists one by one per kind (the rest are only counted), so an
+/// extend over a vanished mount cannot grow the report without bound.
+pub const MAX_LISTED_ENTRIES: usize = 200;
+
+/// What became of one known entry's source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SourceCheck {
+  /// size and modification time are as recorded
+  Unchanged,
+  /// the content is as recorded (or was never hashed), but the fingerprint needs recording: the
+  /// file was touched, the row predates hashing, or the source is back after vanishing
+  Fingerprinted {
+    /// hex SHA-256 of the source
+    content_hash: String,
+    /// its modification time
+    source_modified: Option<NaiveDateTime>,
+  },
+  /// the content differs from the recorded one: the facts of the re-inspected source
+  Changed(NewDocument),
+  /// the source file is gone
+  Vanished,
+  /// the source exists but cannot be read; its recorded facts are kept
+  Unreadable(String),
+}
+
+/// Checks the source of a known entry against its recorded fingerprint. `complex` as for
+/// [`inspect::inspect`].
+pub fn check_source(document: &Document, complex: bool) -> SourceCheck {
+  let path = Path::new(document.entry.trim_end());
+  let metadata = match fs::metadata(path) {
+    Ok(metadata) if metadata.is_file() => metadata,
+    Ok(_) => return SourceCheck::Vanished,
+    Err(e) if e.kind() == ErrorKind::NotFound => return SourceCheck::Vanished,
+    Err(e) => return SourceCheck::Unreadable(e.to_string()),
+  };
+  let source_bytes = i64::try_from(metadata.len()).ok();
+  let source_modified = inspect::modified_time(&metadata);
+  if document.vanished_at.is_none()
+    && document.content_hash.is_some()
+    && source_bytes == document.source_bytes
+    && source_modified.is_some()
+    && source_modified == document.source_modified
+  {
+    return SourceCheck::Unchanged;
+  }
+  let Some(content_hash) = inspect::content_hash(path) else {
+    return SourceCheck::Unreadable("cannot read the source to hash it".to_string());
+  };
+  let changed = match document.content_hash {
+    Some(ref recorded) => *recorded != content_hash,
+    // Never hashed: only a different size tells a replaced source apart.
+    None => source_bytes != document.source_bytes,
+  };
+  if changed {
+    SourceCheck::Changed(inspect::inspect(
+      document.corpus_id,
+      &document.entry,
+      complex,
+    ))
+  } else {
+    SourceCheck::Fingerprinted {
+      content_hash,
+      source_modified,
+    }
+  }
+}
+
+/// The outcome of an `extend`, as printed by the CLI and returned as the extend job's result.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ExtendReport {
+  /// entries imported for the first time
+  pub new: usize,
+  /// known entries whose source content changed
+  pub changed: usize,
+  /// known entries whose source file is missing (flagged on their document)
+  pub vanished: usize,
+  /// entries flagged vanished before whose source is back
+  pub reappeared: usize,
+  /// known entries whose source could not be read (left as recorded)
+  pub unreadable: usize,
+  /// conversion tasks of the changed entries reset to TODO, across the activated services of the
+  /// corpus and of its sandboxes
+  pub reset_tasks: usize,
+  /// the first [`MAX_LISTED_ENTRIES`] changed entries
+  pub changed_entries: Vec<String>,
+  /// the first [`MAX_LISTED_ENTRIES`] vanished entries
+  pub vanished_entries: Vec<String>,
+}
+
+impl ExtendReport {
+  /// Counts a changed `entry`, and lists it while the list has room.
+  pub fn record_changed(&mut self, entry: &str) {
+    self.changed += 1;
+    if self.changed_entries.len() < MAX_LISTED_ENTRIES {
+      self.changed_entries.push(entry.trim_end().to_string());
+    }
+  }
+  /// Counts a vanished `entry`, and lists it while the list has room.
+  pub fn record_vanished(&mut self, entry: &str) {
+    self.vanished += 1;
+    if self.vanished_entries.len() < MAX_LISTED_ENTRIES {
+      self.vanished_entries.push(entry.trim_end().to_string());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn recorded(entry: &str) -> Document {
+    let document = inspect::inspect(7, entry, false);
+    Document {
+      corpus_id: document.corpus_id,
+      entry: document.entry,
+      name: document.name,
+      yymm: document.yymm,
+      source_bytes: document.source_bytes,
+      file_count: document.file_count,
+      main_tex: document.main_tex,
+      primary_category: document.primary_category,
+      inspected_at: NaiveDateTime::default(),
+      content_hash: document.content_hash,
+      source_modified: document.source_modified,
+      vanished_at: None,
+    }
+  }
+
+  #[test]
+  fn a_source_is_unchanged_touched_changed_or_vanished() {
+    let dir = std::env::temp_dir().join(format!("cortex_changes_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("paper.tex");
+    let entry = path.to_str().unwrap().to_string();
+    fs::write(&path, b"\\documentclass{article} v1").unwrap();
+    let mut document = recorded(&entry);
+    assert_eq!(check_source(&document, false), SourceCheck::Unchanged);
+
+    // Touched with the same content: re-hashed, only the fingerprint is recorded.
+    document.source_modified = document
+      .source_modified
+      .map(|time| time - chrono::Duration::seconds(60));
+    assert!(matches!(
+      check_source(&document, false),
+      SourceCheck::Fingerprinted { ref content_hash, .. }
+        if Some(content_hash) == document.content_hash.as_ref()
+    ));
+
+    // Replaced in place with a same-sized new version: the hash tells it apart.
+    fs::write(&path, b"\\documentclass{article} v2").unwrap();
+    match check_source(&document, false) {
+      SourceCheck::Changed(inspected) => {
+        assert_eq!(inspected.entry, entry);
+        assert_ne!(inspected.content_hash, document.content_hash);
+      },
+      other => panic!("expected a change, got {other:?}"),
+    }
+
+    // A row from before hashing only compares sizes.
+    document.content_hash = None;
+    assert!(matches!(
+      check_source(&document, false),
+      SourceCheck::Fingerprinted { .. }
+    ));
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(check_source(&document, false), SourceCheck::Vanished);
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn the_report_lists_a_bounded_sample() {
+    let mut report = ExtendReport::default();
+    for index in 0..MAX_LISTED_ENTRIES + 5 {
+      report.record_vanished(&format!("/gone/{index}.zip "));
+    }
+    report.record_changed("/data/0801.1234/0801.1234.zip");
+    assert_eq!(report.vanished, MAX_LISTED_ENTRIES + 5);
+    assert_eq!(report.vanished_entries.len(), MAX_LISTED_ENTRIES);
+    assert_eq!(report.vanished_entries[0], "/gone/0.zip");
+    assert_eq!(report.changed_entries, ["/data/0801.1234/0801.1234.zip"]);
+  }
+}
@@ -0,0 +1,363 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Entry inspection**: the cheap facts the importer records per document
+//! (`models::NewDocument`) — submission month, source size, file count, main `.tex`, and the
+//! source fingerprint (content hash, modification time) — plus the reader for an optional arXiv
+//! metadata file supplying primary categories.
+//!
+//! Every read is bounded: a source archive's central directory is listed once, and at most
+//! [`MAX_TEX_PROBES`] of its `.tex` members are opened, each for its first [`TEX_PROBE_BYTES`]; the
+//! content hash streams the file once. A source that cannot be read simply leaves its facts empty;
+//! inspection never fails an import.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, NaiveDateTime};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use crate::helpers::entry_document_name;
+use crate::models::NewDocument;
+
+/// Most `.tex` members of one archive probed for a `\documentclass`.
+pub const MAX_TEX_PROBES: usize = 64;
+/// Bytes read from the start of each probed `.tex` member.
+pub const TEX_PROBE_BYTES: u64 = 16 * 1024;
+/// Longest member name kept as `main_tex` (`documents.main_tex` is `VARCHAR(1024)`).
+const MAX_MAIN_TEX_LENGTH: usize = 1024;
+
+/// A new-style arXiv id (`0801.1234`, `2105.13573v2`): the month is its first four digits.
+static NEW_STYLE_ID: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^(\d{4})\.\d{4,5}(?:v\d+)?$").unwrap());
+/// An old-style arXiv id with its slash dropped (`cond-mat0302605`, `math.AG0102003`).
+static OLD_STYLE_ID: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^[A-Za-z-]+(?:\.[A-Za-z-]+)?(\d{4})\d{3}(?:v\d+)?$").unwrap());
+/// An arXiv subject category (`hep-th`, `math.AG`, `cs.LG`).
+static CATEGORY: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9.-]{0,63}$").unwrap());
+/// A LaTeX main-file declaration, outside a comment.
+static DOCUMENT_CLASS: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"(?m)^[^%\n]*\\document(?:class|style)\b").unwrap());
+
+/// Whether `yymm` names a real month.
+fn is_yymm(yymm: &str) -> bool {
+  yymm.len() == 4
+    && yymm.bytes().all(|byte| byte.is_ascii_digit())
+    && matches!(yymm[2..].parse::<u8>(), Ok(1..=12))
+}
+
+/// The arXiv submission month of an entry: from its document name when that is an arXiv id, else
+/// from the `<yymm>/<paper>/<paper>.ext` directory layout of an arXiv-topology corpus.
+pub fn arxiv_yymm(entry: &str) -> Option<String> {
+  let name = entry_document_name(entry);
+  let from_name = NEW_STYLE_ID
+    .captures(&name)
+    .or_else(|| OLD_STYLE_ID.captures(&name))
+    .map(|captures| captures[1].to_string());
+  from_name.filter(|yymm| is_yymm(yymm)).or_else(|| {
+    Path::new(entry.trim_end())
+      .parent()
+      .and_then(Path::parent)
+      .and_then(Path::file_name)
+      .and_then(|name| name.to_str())
+      .filter(|yymm| is_yymm(yymm))
+      .map(str::to_string)
+  })
+}
+
+/// The primary category of an arXiv `categories` field (space-separated, primary first).
+pub fn primary_category(categories: &str) -> Option<String> {
+  categories
+    .split_whitespace()
+    .next()
+    .filter(|category| CATEGORY.is_match(category))
+    .map(str::to_string)
+}
+
+/// The document name of an arXiv id, as the importer names entries: the old-style slash dropped
+/// (`cond-mat/0302605` → `cond-mat0302605`) and any version suffix stripped.
+pub fn arxiv_id_name(id: &str) -> String {
+  let name = id.trim().replace('/', "");
+  match name.rfind('v') {
+    Some(at)
+      if at + 1 < name.len()
+        && name[at + 1..].bytes().all(|byte| byte.is_ascii_digit())
+        && name[..at].ends_with(|c: char| c.is_ascii_digit()) =>
+    {
+      name[..at].to_string()
+    },
+    _ => name,
+  }
+}
+
+/// The hex SHA-256 of the file at `path`, streamed; `None` if it cannot be read.
+pub fn content_hash(path: &Path) -> Option<String> {
+  let mut file = File::open(path).ok()?;
+  let mut hasher = Sha256::new();
+  let mut buffer = vec![0u8; 64 * 1024];
+  loop {
+    match file.read(&mut buffer) {
+      Ok(0) => break,
+      Ok(read) => hasher.update(&buffer[..read]),
+      Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+      Err(_) => return None,
+    }
+  }
+  Some(
+    hasher
+      .finalize()
+      .iter()
+      .map(|byte| format!("{byte:02x}"))
+      .collect(),
+  )
+}
+
+/// The modification time of a file, as a UTC timestamp (whole microseconds, as Postgres keeps it).
+pub fn modified_time(metadata: &fs::Metadata) -> Option<NaiveDateTime> {
+  let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+  let micros = i64::try_from(since_epoch.as_micros()).ok()?;
+  DateTime::from_timestamp_micros(micros).map(|time| time.naive_utc())
+}
+
+/// Inspects the source at `entry` (a `.zip` for a complex corpus, else a `.tex`).
+pub fn inspect(corpus_id: i32, entry: &str, complex: bool) -> NewDocument {
+  let path = Path::new(entry.trim_end());
+  let metadata = fs::metadata(path).ok();
+  let mut document = NewDocument {
+    corpus_id,
+    entry: entry.to_string(),
+    name: entry_document_name(entry).chars().take(255).collect(),
+    yymm: arxiv_yymm(entry),
+    source_bytes: metadata
+      .as_ref()
+      .and_then(|meta| i64::try_from(meta.len()).ok()),
+    source_modified: metadata.as_ref().and_then(modified_time),
+    content_hash: content_hash(path),
+    ..NewDocument::default()
+  };
+  if complex {
+    if let Some((file_count, main_tex)) = inspect_zip(path) {
+      document.file_count = Some(file_count);
+      document.main_tex = main_tex;
+    }
+  } else {
+    document.file_count = Some(1);
+    document.main_tex = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .map(str::to_string);
+  }
+  document
+}
+
+/// Counts the files of a source archive and finds its main `.tex`: the first probed member that
+/// declares `\documentclass`, else the only `.tex` member. `None` if the archive is unreadable.
+fn inspect_zip(path: &Path) -> Option<(i32, Option<String>)> {
+  let mut archive = zip::ZipArchive::new(File::open(path).ok()?).ok()?;
+  let mut file_count = 0i32;
+  let mut tex_members = Vec::new();
+  for index in 0..archive.len() {
+    let Ok(member) = archive.by_index_raw(index) else {
+      continue;
+    };
+    if member.is_dir() {
+      continue;
+    }
+    file_count = file_count.saturating_add(1);
+    if member.name().to_ascii_lowercase().ends_with(".tex")
+      && member.name().len() <= MAX_MAIN_TEX_LENGTH
+    {
+      tex_members.push(index);
+    }
+  }
+  let mut main_tex = None;
+  for &index in tex_members.iter().take(MAX_TEX_PROBES) {
+    let Ok(member) = archive.by_index(index) else {
+      continue;
+    };
+    let name = member.name().to_string();
+    let mut head = Vec::new();
+    if member.take(TEX_PROBE_BYTES).read_to_end(&mut head).is_err() {
+      continue;
+    }
+    if DOCUMENT_CLASS.is_match(&String::from_utf8_lossy(&head)) {
+      main_tex = Some(name);
+      break;
+    }
+  }
+  if main_tex.is_none() && tex_members.len() == 1 {
+    main_tex = archive
+      .by_index_raw(tex_members[0])
+      .ok()
+      .map(|member| member.name().to_string());
+  }
+  Some((file_count, main_tex))
+}
+
+/// Streams an arXiv metadata file — JSON lines with an `id` and a `categories` (or
+/// `primary_category`) field, as in the public arXiv metadata snapshot — handing `(name,
+/// category)` pairs to `apply` in batches of `batch_size`, so a multi-gigabyte file is never held
+/// in memory. Lines without a usable id or category are skipped. Returns the number of pairs read.
+pub fn read_arxiv_categories<E>(
+  path: &Path,
+  batch_size: usize,
+  mut apply: impl FnMut(&[(String, String)]) -> Result<(), E>,
+) -> Result<usize, String>
+where
+  E: std::fmt::Display,
+{
+  let file = File::open(path).map_err(|e| format!("cannot read {path:?}: {e}"))?;
+  let mut batch = Vec::with_capacity(batch_size);
+  let mut read = 0;
+  for line in BufReader::new(file).lines() {
+    let line = line.map_err(|e| format!("reading {path:?}: {e}"))?;
+    let Ok(serde_json::Value::Object(record)) = serde_json::from_str(&line) else {
+      continue;
+    };
+    let id = record.get("id").and_then(|id| id.as_str());
+    let category = record
+      .get("primary_category")
+      .or_else(|| record.get("categories"))
+      .and_then(|categories| categories.as_str())
+      .and_then(primary_category);
+    if let (Some(id), Some(category)) = (id, category) {
+      batch.push((arxiv_id_name(id), category));
+      read += 1;
+    }
+    if batch.len() >= batch_size {
+      apply(&batch).map_err(|e| e.to_string())?;
+      batch.clear();
+    }
+  }
+  if !batch.is_empty() {
+    apply(&batch).map_err(|e| e.to_string())?;
+  }
+  Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+
+  #[test]
+  fn yymm_comes_from_the_id_or_the_layout() {
+    assert_eq!(
+      arxiv_yymm("/data/arxmliv/0811/0811.0417/0811.0417.zip").as_deref(),
+      Some("0811")
+    );
+    assert_eq!(
+      arxiv_yymm("/data/2105.13573v2.tex").as_deref(),
+      Some("2105")
+    );
+    assert_eq!(
+      arxiv_yymm("/d/cond-mat0302605/cond-mat0302605.zip").as_deref(),
+      Some("0302")
+    );
+    assert_eq!(
+      arxiv_yymm("/d/9912/paper/paper.zip").as_deref(),
+      Some("9912"),
+      "a non-arXiv name falls back to the month directory"
+    );
+    assert_eq!(arxiv_yymm("/d/misc/paper/paper.zip"), None);
+    assert_eq!(
+      arxiv_yymm("/d/1234.5678/1234.5678.zip"),
+      None,
+      "no month 34"
+    );
+  }
+
+  #[test]
+  fn categories_and_ids_normalize() {
+    assert_eq!(
+      primary_category("hep-th math.AG").as_deref(),
+      Some("hep-th")
+    );
+    assert_eq!(primary_category("  "), None);
+    assert_eq!(primary_category("'; drop"), None);
+    assert_eq!(arxiv_id_name("cond-mat/0302605v3"), "cond-mat0302605");
+    assert_eq!(arxiv_id_name("0801.1234"), "0801.1234");
+    assert_eq!(arxiv_id_name("solv-int/9901001"), "solv-int9901001");
+  }
+
+  #[test]
+  fn a_zip_is_counted_and_its_main_tex_found() {
+    let dir = std::env::temp_dir().join(format!("cortex_inspect_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("paper.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.add_directory("figs/", options).unwrap();
+    zip.start_file("macros.tex", options).unwrap();
+    zip
+      .write_all(b"% \\documentclass{article} is commented out\n\\newcommand\\x{}")
+      .unwrap();
+    zip.start_file("paper.tex", options).unwrap();
+    zip
+      .write_all(b"\\documentclass[12pt]{article}\n\\begin{document}\\end{document}")
+      .unwrap();
+    zip.start_file("figs/plot.png", options).unwrap();
+    zip.finish().unwrap();
+
+    let document = inspect(7, path.to_str().unwrap(), true);
+    assert_eq!(document.corpus_id, 7);
+    assert_eq!(document.name, "paper");
+    assert_eq!(document.file_count, Some(3));
+    assert_eq!(document.main_tex.as_deref(), Some("paper.tex"));
+    assert!(document.source_bytes.is_some_and(|bytes| bytes > 0));
+    assert_eq!(
+      document.content_hash.as_deref().map(str::len),
+      Some(64),
+      "a hex SHA-256"
+    );
+    assert!(document.source_modified.is_some());
+
+    let broken = dir.join("broken.zip");
+    fs::write(&broken, b"not a zip").unwrap();
+    let document = inspect(7, broken.to_str().unwrap(), true);
+    assert_eq!((document.file_count, document.main_tex), (None, None));
+    assert_eq!(
+      document.content_hash.as_deref(),
+      Some("a3989126344744ef800dbc88bf7e744853f3f2eac75c9ab3301f4e845ef22078"),
+      "an unreadable archive is still fingerprinted"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn arxiv_metadata_is_streamed_in_batches() {
+    let path = std::env::temp_dir().join(format!("cortex_arxiv_meta_{}.json", std::process::id()));
+    fs::write(
+      &path,
+      "{\"id\": \"0801.1234\", \"categories\": \"hep-th gr-qc\"}\n\
+       not json\n\
+       {\"id\": \"cond-mat/0302605\", \"categories\": \"cond-mat.str-el\"}\n\
+       {\"id\": \"0801.9999\"}\n\
+       {\"id\": \"0802.0001\", \"primary_category\": \"math.AG\"}\n",
+    )
+    .unwrap();
+    let mut batches: Vec<Vec<(String, String)>> = Vec::new();
+    let read = read_arxiv_categories::<String>(&path, 2, |batch| {
+      batches.push(batch.to_vec());
+      Ok(())
+    })
+    .unwrap();
+    assert_eq!(read, 3);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(
+      batches[0][1],
+      ("cond-mat0302605".to_string(), "cond-mat.str-el".to_string())
+    );
+    assert_eq!(batches[1][0].1, "math.AG");
+    fs::remove_file(&path).unwrap();
+  }
+}
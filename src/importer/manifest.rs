@@ -0,0 +1,633 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Import manifests**: a corpus given as a list of documents rather than an arXiv directory
+//! layout — a PubMed Central dump, a conference's proceedings, a hand-picked regression set.
+//!
+//! A manifest is either **JSON lines** (one object per line, with a `path` key) or **CSV** (a
+//! header row naming a `path` column); `entry` is accepted for `path` in both. Every other field of
+//! a row is kept as the entry's metadata (`models::EntryMetadata`). A relative path resolves
+//! against the corpus root, and every path is normalized (`.`, `..` and doubled slashes) before
+//! it becomes an entry, so two spellings of one file import once. Every listed path is checked
+//! before anything is written: a missing, unreadable or malformed row is reported and skipped,
+//! never fatal, as in the directory walk.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Most problems a [`ManifestReport`] lists one by one (the rest are only counted), so a manifest
+/// that points at the wrong directory cannot grow the report without bound.
+pub const MAX_LISTED_PROBLEMS: usize = 200;
+/// Largest metadata a single row may carry (serialized), so one hostile row cannot bloat the store.
+pub const MAX_METADATA_BYTES: usize = 64 * 1024;
+/// Longest entry path the task store holds (`tasks.entry` is `VARCHAR(4096)`).
+const MAX_ENTRY_LENGTH: usize = 4096;
+
+/// The two manifest encodings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestFormat {
+  /// one JSON object per line
+  JsonLines,
+  /// RFC 4180 comma-separated values with a header row
+  Csv,
+}
+
+impl ManifestFormat {
+  /// The format of the manifest at `path`: by its extension (`.jsonl`, `.ndjson`, `.json` or
+  /// `.csv`), else by whether its first non-blank character opens a JSON object.
+  pub fn detect(path: &Path) -> io::Result<ManifestFormat> {
+    let extension = path
+      .extension()
+      .and_then(|extension| extension.to_str())
+      .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+      Some("jsonl" | "ndjson" | "json") => return Ok(ManifestFormat::JsonLines),
+      Some("csv") => return Ok(ManifestFormat::Csv),
+      _ => {},
+    }
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+      match line
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .chars()
+        .next()
+      {
+        Some('{') => return Ok(ManifestFormat::JsonLines),
+        Some(_) => return Ok(ManifestFormat::Csv),
+        None => line.clear(),
+      }
+    }
+    Ok(ManifestFormat::JsonLines)
+  }
+}
+
+/// One row of a manifest: the listed path and the rest of its fields.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestEntry {
+  /// the manifest line the row starts on (1-based)
+  pub line: usize,
+  /// the path as listed
+  pub path: String,
+  /// the row's other fields
+  pub metadata: Map<String, Value>,
+}
+
+/// Why a manifest row was skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+  /// the listed path does not exist
+  Missing,
+  /// the listed path exists but cannot be read
+  Unreadable,
+  /// the row itself is unusable: malformed, without a path, a duplicate, or not a document file
+  Invalid,
+}
+
+/// A skipped manifest row.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ManifestProblem {
+  /// the manifest line the row starts on (1-based)
+  pub line: usize,
+  /// the path as listed, when the row had one
+  pub path: Option<String>,
+  /// the kind of problem
+  pub kind: ProblemKind,
+  /// what exactly is wrong
+  pub reason: String,
+}
+
+/// A parsed manifest: its usable rows, and the rows that could not be parsed.
+#[derive(Clone, Debug, Default)]
+pub struct Manifest {
+  /// rows with a path, in manifest order
+  pub entries: Vec<ManifestEntry>,
+  /// rows that were malformed or had no path
+  pub problems: Vec<ManifestProblem>,
+}
+
+/// An entry that passed validation, ready to import.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidEntry {
+  /// the absolute entry path
+  pub entry: String,
+  /// the row's metadata (an empty object if it only listed the path)
+  pub metadata: Map<String, Value>,
+}
+
+/// The outcome of a manifest import, as printed by the CLI and returned as the import job's result.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ManifestReport {
+  /// rows in the manifest (excluding blank lines and the CSV header)
+  pub listed: usize,
+  /// entries that passed validation
+  pub valid: usize,
+  /// new import tasks created (a re-import only adds the entries not already in the corpus)
+  pub imported: usize,
+  /// skipped rows whose path does not exist
+  pub missing: usize,
+  /// skipped rows whose path cannot be read
+  pub unreadable: usize,
+  /// skipped malformed, path-less, duplicate or non-document rows
+  pub invalid: usize,
+  /// the first [`MAX_LISTED_PROBLEMS`] skipped rows, in manifest order
+  pub problems: Vec<ManifestProblem>,
+}
+
+impl ManifestReport {
+  /// Counts `problem`, and lists it while the list has room.
+  pub fn record(&mut self, problem: ManifestProblem) {
+    match problem.kind {
+      ProblemKind::Missing => self.missing += 1,
+      ProblemKind::Unreadable => self.unreadable += 1,
+      ProblemKind::Invalid => self.invalid += 1,
+    }
+    if self.problems.len() < MAX_LISTED_PROBLEMS {
+      self.problems.push(problem);
+    }
+  }
+  /// Total skipped rows.
+  pub fn skipped(&self) -> usize { self.missing + self.unreadable + self.invalid }
+}
+
+impl Manifest {
+  /// Reads and parses the manifest file at `path`. Only an unreadable file, or a CSV header
+  /// without a path column, is an error; a bad row is recorded as a problem and skipped.
+  pub fn read(path: &Path) -> Result<Manifest, String> {
+    let format =
+      ManifestFormat::detect(path).map_err(|e| format!("cannot read manifest {path:?}: {e}"))?;
+    let file = File::open(path).map_err(|e| format!("cannot read manifest {path:?}: {e}"))?;
+    Manifest::parse(BufReader::new(file), format).map_err(|e| format!("manifest {path:?}: {e}"))
+  }
+
+  /// Parses a manifest in the given format.
+  pub fn parse(reader: impl BufRead, format: ManifestFormat) -> Result<Manifest, String> {
+    match format {
+      ManifestFormat::JsonLines => parse_json_lines(reader),
+      ManifestFormat::Csv => parse_csv(reader),
+    }
+  }
+
+  /// Checks every listed path, resolving relative ones against `root` and normalizing them: it
+  /// must be a readable regular file with the corpus's document extension (`.zip` for a complex
+  /// corpus, else `.tex`), listed once — two rows naming the same file, by whatever spelling or
+  /// symlink, are duplicates — and its metadata within [`MAX_METADATA_BYTES`]. Returns the valid
+  /// entries in manifest order, and records every skipped row (including the parse problems) in
+  /// `report`.
+  pub fn validate(
+    self,
+    root: &Path,
+    complex: bool,
+    report: &mut ManifestReport,
+  ) -> Vec<ValidEntry> {
+    let extension = if complex { "zip" } else { "tex" };
+    let mut problems = self.problems.into_iter().peekable();
+    let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+    let mut valid = Vec::with_capacity(self.entries.len());
+    report.listed += self.entries.len() + problems.len();
+    for row in self.entries {
+      // Keep the report in manifest order: parse problems of earlier lines come first.
+      while let Some(problem) = problems.next_if(|problem| problem.line < row.line) {
+        report.record(problem);
+      }
+      let problem = |kind, reason: String| ManifestProblem {
+        line: row.line,
+        path: Some(row.path.clone()),
+        kind,
+        reason,
+      };
+      let listed = Path::new(&row.path);
+      let path = normalize(&if listed.is_relative() {
+        root.join(listed)
+      } else {
+        listed.to_path_buf()
+      });
+      let Some(entry) = path.to_str().map(str::to_string) else {
+        report.record(problem(
+          ProblemKind::Invalid,
+          "path is not UTF-8".to_string(),
+        ));
+        continue;
+      };
+      if entry.len() > MAX_ENTRY_LENGTH {
+        report.record(problem(
+          ProblemKind::Invalid,
+          format!("path is longer than {MAX_ENTRY_LENGTH} bytes"),
+        ));
+        continue;
+      }
+      if path.extension().and_then(|found| found.to_str()) != Some(extension) {
+        report.record(problem(
+          ProblemKind::Invalid,
+          format!("not a .{extension} document, as this corpus expects"),
+        ));
+        continue;
+      }
+      match fs::metadata(&path) {
+        Ok(meta) if !meta.is_file() => {
+          report.record(problem(
+            ProblemKind::Invalid,
+            "not a regular file".to_string(),
+          ));
+          continue;
+        },
+        Ok(_) => {},
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+          report.record(problem(
+            ProblemKind::Missing,
+            format!("no such file: {entry}"),
+          ));
+          continue;
+        },
+        Err(e) => {
+          report.record(problem(ProblemKind::Unreadable, e.to_string()));
+          continue;
+        },
+      }
+      if let Err(e) = File::open(&path) {
+        report.record(problem(ProblemKind::Unreadable, e.to_string()));
+        continue;
+      }
+      // The file itself, past any symlink, is what must be listed once.
+      let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+      if let Some(first) = seen.get(&canonical) {
+        report.record(problem(
+          ProblemKind::Invalid,
+          format!("duplicate of line {first}"),
+        ));
+        continue;
+      }
+      let metadata_bytes = serde_json::to_vec(&row.metadata).map_or(0, |bytes| bytes.len());
+      if metadata_bytes > MAX_METADATA_BYTES {
+        report.record(problem(
+          ProblemKind::Invalid,
+          format!("metadata of {metadata_bytes} bytes exceeds {MAX_METADATA_BYTES}"),
+        ));
+        continue;
+      }
+      seen.insert(canonical, row.line);
+      valid.push(ValidEntry {
+        entry,
+        metadata: row.metadata,
+      });
+    }
+    for problem in problems {
+      report.record(problem);
+    }
+    report.valid += valid.len();
+    valid
+  }
+}
+
+/// `path` without `.` components, doubled separators or `..` that back out of a named directory —
+/// purely lexical, so a missing path normalizes too and the entry keeps the corpus root's spelling.
+fn normalize(path: &Path) -> PathBuf {
+  let mut normal = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {},
+      Component::ParentDir
+        if matches!(normal.components().next_back(), Some(Component::Normal(_))) =>
+      {
+        normal.pop();
+      },
+      Component::ParentDir if normal.has_root() => {},
+      other => normal.push(other),
+    }
+  }
+  normal
+}
+
+/// Whether a field or column name designates the entry path.
+fn is_path_key(key: &str) -> bool {
+  key.eq_ignore_ascii_case("path") || key.eq_ignore_ascii_case("entry")
+}
+
+fn parse_json_lines(reader: impl BufRead) -> Result<Manifest, String> {
+  let mut manifest = Manifest::default();
+  for (index, line) in reader.lines().enumerate() {
+    let line_number = index + 1;
+    let line = line.map_err(|e| format!("line {line_number}: {e}"))?;
+    let line = line.trim_start_matches('\u{feff}').trim();
+    if line.is_empty() {
+      continue;
+    }
+    let invalid = |reason: String| ManifestProblem {
+      line: line_number,
+      path: None,
+      kind: ProblemKind::Invalid,
+      reason,
+    };
+    let mut object = match serde_json::from_str::<Value>(line) {
+      Ok(Value::Object(object)) => object,
+      Ok(_) => {
+        manifest
+          .problems
+          .push(invalid("not a JSON object".to_string()));
+        continue;
+      },
+      Err(e) => {
+        manifest
+          .problems
+          .push(invalid(format!("malformed JSON: {e}")));
+        continue;
+      },
+    };
+    let key = object.keys().find(|key| is_path_key(key)).cloned();
+    match key.and_then(|key| object.remove(&key)) {
+      Some(Value::String(path)) if !path.trim().is_empty() => {
+        manifest.entries.push(ManifestEntry {
+          line: line_number,
+          path: path.trim().to_string(),
+          metadata: object,
+        })
+      },
+      Some(_) => manifest
+        .problems
+        .push(invalid("\"path\" is not a non-empty string".to_string())),
+      None => manifest
+        .problems
+        .push(invalid("no \"path\" field".to_string())),
+    }
+  }
+  Ok(manifest)
+}
+
+fn parse_csv(reader: impl BufRead) -> Result<Manifest, String> {
+  let mut records = CsvRecords { reader, line: 0 };
+  let mut manifest = Manifest::default();
+  // The header is the first non-blank record.
+  let header = loop {
+    match records.next_record()? {
+      None => return Ok(manifest),
+      Some((_, Err(reason))) => return Err(format!("unreadable header row: {reason}")),
+      Some((_, Ok(fields))) if fields.iter().all(|field| field.trim().is_empty()) => continue,
+      Some((_, Ok(fields))) => break fields,
+    }
+  };
+  let header: Vec<String> = header
+    .iter()
+    .map(|name| name.trim_start_matches('\u{feff}').trim().to_string())
+    .collect();
+  let path_column = header
+    .iter()
+    .position(|name| is_path_key(name))
+    .ok_or("the header row names no \"path\" column")?;
+  for (index, name) in header.iter().enumerate() {
+    if header[..index].contains(name) && !name.is_empty() {
+      return Err(format!("the header row names column {name:?} twice"));
+    }
+  }
+  while let Some((line, record)) = records.next_record()? {
+    let invalid = |reason: String| ManifestProblem {
+      line,
+      path: None,
+      kind: ProblemKind::Invalid,
+      reason,
+    };
+    let fields = match record {
+      Ok(fields) => fields,
+      Err(reason) => {
+        manifest.problems.push(invalid(reason));
+        continue;
+      },
+    };
+    if fields.iter().all(|field| field.trim().is_empty()) {
+      continue;
+    }
+    if fields.len() > header.len() {
+      manifest.problems.push(invalid(format!(
+        "{} fields, but the header names {}",
+        fields.len(),
+        header.len()
+      )));
+      continue;
+    }
+    let path = fields.get(path_column).map_or("", |path| path.trim());
+    if path.is_empty() {
+      manifest
+        .problems
+        .push(invalid("empty \"path\" field".to_string()));
+      continue;
+    }
+    let metadata = header
+      .iter()
+      .zip(&fields)
+      .enumerate()
+      .filter(|(index, (name, value))| {
+        *index != path_column && !name.is_empty() && !value.is_empty()
+      })
+      .map(|(_, (name, value))| (name.clone(), Value::String(value.clone())))
+      .collect();
+    manifest.entries.push(ManifestEntry {
+      line,
+      path: path.to_string(),
+      metadata,
+    });
+  }
+  Ok(manifest)
+}
+
+/// An RFC 4180 record reader: comma-separated fields, double-quoted where they contain commas,
+/// quotes or line breaks, with `""` escaping a quote inside quotes.
+struct CsvRecords<R> {
+  reader: R,
+  line: usize,
+}
+
+impl<R: BufRead> CsvRecords<R> {
+  /// The next record and the line it starts on, or `None` at the end of input. A malformed record
+  /// (an unterminated quote, or text after a closing quote) is an `Err` with the reason; only an
+  /// I/O failure ends the parse.
+  #[allow(clippy::type_complexity)]
+  fn next_record(&mut self) -> Result<Option<(usize, Result<Vec<String>, String>)>, String> {
+    let mut text = String::new();
+    let start = self.line + 1;
+    loop {
+      let mut physical = String::new();
+      let read = self
+        .reader
+        .read_line(&mut physical)
+        .map_err(|e| format!("line {}: {e}", self.line + 1))?;
+      if read == 0 {
+        if text.is_empty() {
+          return Ok(None);
+        }
+        return Ok(Some((start, Err("unterminated quoted field".to_string()))));
+      }
+      self.line += 1;
+      text.push_str(&physical);
+      // A record is complete once its quotes balance; an odd count means a quoted line break.
+      if text.matches('"').count() % 2 == 0 {
+        break;
+      }
+    }
+    let record = text.trim_end_matches(['\n', '\r']);
+    Ok(Some((start, split_csv_record(record))))
+  }
+}
+
+/// Splits one complete CSV record into its fields.
+fn split_csv_record(record: &str) -> Result<Vec<String>, String> {
+  let mut fields = Vec::new();
+  let mut field = String::new();
+  let mut chars = record.chars().peekable();
+  let mut quoted = false;
+  let mut closed = false;
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted => {
+        if chars.next_if_eq(&'"').is_some() {
+          field.push('"');
+        } else {
+          quoted = false;
+          closed = true;
+        }
+      },
+      '"' if field.is_empty() && !closed => quoted = true,
+      ',' if !quoted => {
+        fields.push(std::mem::take(&mut field));
+        closed = false;
+      },
+      _ if closed => return Err("text after a closing quote".to_string()),
+      _ => field.push(c),
+    }
+  }
+  fields.push(field);
+  Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(text: &str, format: ManifestFormat) -> Manifest {
+    Manifest::parse(text.as_bytes(), format).expect("parses")
+  }
+
+  #[test]
+  fn json_lines_keep_every_other_field_as_metadata() {
+    let manifest = parse(
+      "{\"path\": \"a/a.zip\", \"doi\": \"10.1/x\", \"year\": 2024}\n\n\
+       [1, 2]\n{\"entry\": \"b/b.zip\"}\n{\"title\": \"no path\"}\nnot json\n",
+      ManifestFormat::JsonLines,
+    );
+    assert_eq!(manifest.entries.len(), 2);
+    assert_eq!(manifest.entries[0].line, 1);
+    assert_eq!(manifest.entries[0].path, "a/a.zip");
+    assert_eq!(manifest.entries[0].metadata["doi"], "10.1/x");
+    assert_eq!(manifest.entries[0].metadata["year"], 2024);
+    assert!(!manifest.entries[0].metadata.contains_key("path"));
+    assert_eq!(manifest.entries[1].line, 4);
+    assert!(manifest.entries[1].metadata.is_empty());
+    let lines: Vec<usize> = manifest.problems.iter().map(|p| p.line).collect();
+    assert_eq!(lines, vec![3, 5, 6]);
+  }
+
+  #[test]
+  fn csv_handles_quotes_and_reports_bad_rows() {
+    let manifest = parse(
+      "\u{feff}Path,venue,note\n\
+       a/a.zip,ACL,\"commas, \"\"quotes\"\"\nand a line break\"\n\
+       b/b.zip,,\n\
+       ,orphan,\n\
+       c/c.zip,x,y,z\n\
+       d/d.zip,\"bad\"tail,\n",
+      ManifestFormat::Csv,
+    );
+    assert_eq!(manifest.entries.len(), 2);
+    assert_eq!(manifest.entries[0].path, "a/a.zip");
+    assert_eq!(
+      manifest.entries[0].metadata["note"],
+      "commas, \"quotes\"\nand a line break"
+    );
+    assert_eq!(manifest.entries[1].line, 4);
+    assert!(
+      manifest.entries[1].metadata.is_empty(),
+      "empty cells are not metadata"
+    );
+    let lines: Vec<usize> = manifest.problems.iter().map(|p| p.line).collect();
+    assert_eq!(lines, vec![5, 6, 7]);
+  }
+
+  #[test]
+  fn csv_without_a_path_column_is_refused() {
+    assert!(Manifest::parse("doi,venue\n10.1/x,ACL\n".as_bytes(), ManifestFormat::Csv).is_err());
+  }
+
+  #[test]
+  fn validation_reports_missing_duplicate_and_foreign_paths() {
+    let root = std::env::temp_dir().join(format!("cortex_manifest_test_{}", std::process::id()));
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("a/a.zip"), b"PK").unwrap();
+    fs::create_dir_all(root.join("dir.zip")).unwrap();
+    let absolute = root.join("a/a.zip").to_str().unwrap().to_string();
+    let manifest = parse(
+      &format!(
+        "{{\"path\": \"a/a.zip\", \"doi\": \"10.1/x\"}}\n\
+         {{\"path\": \"gone/gone.zip\"}}\n\
+         oops\n\
+         {{\"path\": \"{absolute}\"}}\n\
+         {{\"path\": \"a/a.tex\"}}\n\
+         {{\"path\": \"dir.zip\"}}\n"
+      ),
+      ManifestFormat::JsonLines,
+    );
+    let mut report = ManifestReport::default();
+    let valid = manifest.validate(&root, true, &mut report);
+    assert_eq!(
+      valid,
+      vec![ValidEntry {
+        entry: absolute,
+        metadata: serde_json::json!({"doi": "10.1/x"})
+          .as_object()
+          .cloned()
+          .unwrap(),
+      }]
+    );
+    assert_eq!((report.listed, report.valid), (6, 1));
+    assert_eq!(
+      (report.missing, report.unreadable, report.invalid),
+      (1, 0, 4)
+    );
+    let lines: Vec<usize> = report.problems.iter().map(|p| p.line).collect();
+    assert_eq!(lines, vec![2, 3, 4, 5, 6], "problems in manifest order");
+    assert_eq!(report.problems[2].reason, "duplicate of line 1");
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn validation_deduplicates_normalized_and_linked_paths() {
+    let root = std::env::temp_dir().join(format!("cortex_manifest_alias_{}", std::process::id()));
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("a/a.tex"), b"\\documentclass{article}").unwrap();
+    std::os::unix::fs::symlink(root.join("a"), root.join("alias")).unwrap();
+    let manifest = parse(
+      "{\"path\": \"./a//a.tex\", \"note\": \"first\"}\n\
+       {\"path\": \"b/../a/a.tex\"}\n\
+       {\"path\": \"alias/a.tex\"}\n",
+      ManifestFormat::JsonLines,
+    );
+    let mut report = ManifestReport::default();
+    let valid = manifest.validate(&root, false, &mut report);
+    assert_eq!(valid.len(), 1);
+    assert_eq!(valid[0].entry, root.join("a/a.tex").to_str().unwrap());
+    let reasons: Vec<&str> = report.problems.iter().map(|p| p.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["duplicate of line 1", "duplicate of line 1"]);
+    assert_eq!(normalize(Path::new("/../x/./y//../z")), Path::new("/x/z"));
+    assert_eq!(normalize(Path::new("../x")), Path::new("../x"));
+    fs::remove_dir_all(&root).unwrap();
+  }
+}
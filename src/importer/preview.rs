@@ -0,0 +1,282 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Import preview** (dry run): what an import of a corpus path would find, computed without
+//! touching the database or the disk. The unpacker is asked what it *would* write
+//! ([`Unpacker::preview`]), the corpus is walked as the import walks it ([`walk_entries`]), and the
+//! two are merged into one report — the projected entry count, its spread over months, what would
+//! be skipped and why, symlink-loop hits, and the largest entries — for the operator to confirm
+//! before a long import job starts.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::changes::MAX_LISTED_ENTRIES;
+use super::manifest::{Manifest, ManifestReport};
+use super::unpackers::{self, DEFAULT_UNPACKER, Unpacker};
+use super::{WalkEvent, inspect, walk_entries};
+
+/// How many of the largest entries a preview lists.
+pub const LARGEST_LISTED: usize = 20;
+
+/// A path an import would skip, and why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct SkippedPath {
+  /// the skipped path (`<archive>/<member>` for a member inside an archive)
+  pub path: String,
+  /// why it would be skipped
+  pub reason: String,
+}
+
+/// One projected entry and the size of its source.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct PreviewEntry {
+  /// the entry path, as the import would register it
+  pub entry: String,
+  /// the size of its source, in bytes (compressed, for a source still to unpack)
+  pub bytes: u64,
+}
+
+/// The outcome of an import preview.
+#[derive(Clone, Debug, Default, Serialize, JsonSchema)]
+pub struct ImportPreview {
+  /// the unpacker the import would use (`None` for a non-complex corpus or a manifest import)
+  pub unpacker: Option<String>,
+  /// the projected number of entries the import would register
+  pub entries: usize,
+  /// of those, entries already in the CorTeX layout
+  pub unpacked: usize,
+  /// of those, entries the unpacker would (re)write
+  pub to_unpack: usize,
+  /// the projected entries per arXiv month (`yymm`)
+  pub months: BTreeMap<String, usize>,
+  /// projected entries whose month cannot be told from the id or the layout
+  pub unknown_month: usize,
+  /// paths that would be skipped
+  pub skipped: usize,
+  /// the first [`MAX_LISTED_ENTRIES`] of them, with the reasons
+  pub skipped_paths: Vec<SkippedPath>,
+  /// directories beyond the walk's depth bound — symlink loops, most likely
+  pub symlink_loops: usize,
+  /// the first [`MAX_LISTED_ENTRIES`] of them
+  pub loop_paths: Vec<String>,
+  /// the [`LARGEST_LISTED`] largest projected entries, largest first
+  pub largest: Vec<PreviewEntry>,
+  /// every projected entry, so an entry both on disk and to be rewritten counts once
+  #[serde(skip)]
+  seen: HashSet<String>,
+}
+
+impl ImportPreview {
+  /// Tallies one event of the unpack preview (`planned`) or of the walk.
+  pub fn record(&mut self, event: WalkEvent, planned: bool) {
+    match event {
+      WalkEvent::Entry { path, bytes } => self.record_entry(path, bytes, planned),
+      WalkEvent::Skipped { path, reason } => {
+        self.skipped += 1;
+        if self.skipped_paths.len() < MAX_LISTED_ENTRIES {
+          self.skipped_paths.push(SkippedPath {
+            path: path.to_string_lossy().into_owned(),
+            reason,
+          });
+        }
+      },
+      WalkEvent::TooDeep(path) => {
+        self.symlink_loops += 1;
+        if self.loop_paths.len() < MAX_LISTED_ENTRIES {
+          self.loop_paths.push(path.to_string_lossy().into_owned());
+        }
+      },
+    }
+  }
+
+  fn record_entry(&mut self, entry: String, bytes: u64, planned: bool) {
+    if self.seen.contains(&entry) {
+      return;
+    }
+    self.entries += 1;
+    if planned {
+      self.to_unpack += 1;
+    } else {
+      self.unpacked += 1;
+    }
+    match inspect::arxiv_yymm(&entry) {
+      Some(yymm) => *self.months.entry(yymm).or_default() += 1,
+      None => self.unknown_month += 1,
+    }
+    let rank = self.largest.partition_point(|larger| larger.bytes >= bytes);
+    if rank < LARGEST_LISTED {
+      self.largest.insert(
+        rank,
+        PreviewEntry {
+          entry: entry.clone(),
+          bytes,
+        },
+      );
+      self.largest.truncate(LARGEST_LISTED);
+    }
+    self.seen.insert(entry);
+  }
+}
+
+/// Previews an import as the CLI, the web form and the agent request it: of the corpus at `path`,
+/// from `manifest` when given, else by walking (a complex corpus unpacked by the `unpacker` key,
+/// [`DEFAULT_UNPACKER`] when `None`).
+pub fn preview_import(
+  path: &str,
+  complex: bool,
+  unpacker: Option<&str>,
+  manifest: Option<&Path>,
+) -> Result<ImportPreview, Box<dyn Error>> {
+  let root = Path::new(path.trim_end());
+  if let Some(manifest) = manifest {
+    return preview_manifest(manifest, root, complex);
+  }
+  let key = unpacker.unwrap_or(DEFAULT_UNPACKER);
+  let unpacker = unpackers::find(key).ok_or_else(|| {
+    format!(
+      "{key:?} is not a known unpacker (use {})",
+      unpackers::keys()
+    )
+  })?;
+  preview(root, complex, Some(unpacker))
+}
+
+/// Previews an import of the corpus at `root`: for a `complex` corpus, what `unpacker` would write,
+/// then what the import walk would find. Reads only; an unusable `root` is the only error.
+pub fn preview(
+  root: &Path,
+  complex: bool,
+  unpacker: Option<&dyn Unpacker>,
+) -> Result<ImportPreview, Box<dyn Error>> {
+  let mut report = ImportPreview::default();
+  let mut prefixes = HashSet::new();
+  if complex && let Some(unpacker) = unpacker {
+    report.unpacker = Some(unpacker.key().to_string());
+    prefixes = unpacker.preview(root, &mut |event| report.record(event, true))?;
+  }
+  let root = root.to_str().ok_or("non-UTF-8 corpus path")?;
+  walk_entries(root, complex, &prefixes, &mut |event| {
+    report.record(event, false);
+    Ok(())
+  })?;
+  Ok(report)
+}
+
+/// Previews a manifest import: the entries the manifest at `manifest_path` lists that would be
+/// imported, and the rows that would be skipped. `root` and `complex` as for
+/// [`Manifest::validate`].
+pub fn preview_manifest(
+  manifest_path: &Path,
+  root: &Path,
+  complex: bool,
+) -> Result<ImportPreview, Box<dyn Error>> {
+  let mut manifest_report = ManifestReport::default();
+  let valid = Manifest::read(manifest_path)?.validate(root, complex, &mut manifest_report);
+  let mut report = ImportPreview::default();
+  for entry in valid {
+    let bytes = std::fs::metadata(&entry.entry).map_or(0, |metadata| metadata.len());
+    report.record_entry(entry.entry, bytes, false);
+  }
+  for problem in manifest_report.problems {
+    report.record(
+      WalkEvent::Skipped {
+        path: problem
+          .path
+          .unwrap_or_else(|| format!("line {}", problem.line))
+          .into(),
+        reason: format!("manifest line {}: {}", problem.line, problem.reason),
+      },
+      false,
+    );
+  }
+  // Rows beyond the listed problems are counted only.
+  report.skipped = report.skipped.max(manifest_report.skipped());
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::importer::unpackers;
+  use std::fs;
+  use std::io::Write;
+
+  #[test]
+  fn a_preview_projects_entries_without_writing() {
+    let root = std::env::temp_dir().join(format!("cortex_preview_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    // An arXiv month: one source to unpack, one unpacked, one that is not a TeX source.
+    let month = root.join("2401");
+    fs::create_dir_all(month.join("2401.00002")).unwrap();
+    let gz = |path: &Path, content: &[u8]| {
+      let mut encoder = flate2::write::GzEncoder::new(
+        fs::File::create(path).unwrap(),
+        flate2::Compression::default(),
+      );
+      encoder.write_all(content).unwrap();
+      encoder.finish().unwrap();
+    };
+    gz(&month.join("2401.00001.gz"), b"\\documentclass{article}");
+    gz(
+      &month.join("2401.00003.gz"),
+      b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n",
+    );
+    fs::write(month.join("2401.00002/2401.00002.zip"), vec![0; 4096]).unwrap();
+    // A symlink loop away from the documents (one through them would find them again and again).
+    fs::create_dir_all(root.join("stray")).unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(root.join("stray"), root.join("stray/loop")).unwrap();
+
+    let report = preview(&root, true, unpackers::find("arxiv")).unwrap();
+    assert_eq!(report.unpacker.as_deref(), Some("arxiv"));
+    assert_eq!(
+      (report.entries, report.unpacked, report.to_unpack),
+      (2, 1, 1)
+    );
+    assert_eq!(report.months.get("2401"), Some(&2));
+    assert_eq!(report.largest[0].bytes, 4096);
+    assert!(
+      report
+        .skipped_paths
+        .iter()
+        .any(|skipped| skipped.path.ends_with("2401.00003.gz") && skipped.reason.contains("pdf"))
+    );
+    #[cfg(unix)]
+    assert!(report.symlink_loops > 0, "the symlink loop is reported");
+    // Nothing was written: the sources are still packed.
+    assert!(month.join("2401.00001.gz").exists());
+    assert!(!month.join("2401.00001").exists());
+    fs::remove_dir_all(&root).ok();
+  }
+
+  #[test]
+  fn the_largest_entries_are_ranked_and_bounded() {
+    let mut report = ImportPreview::default();
+    for index in 0..LARGEST_LISTED as u64 + 5 {
+      report.record_entry(format!("/c/{index}/{index}.zip"), index, false);
+    }
+    report.record_entry("/c/0/0.zip".to_string(), 1_000, true);
+    assert_eq!(
+      report.entries,
+      LARGEST_LISTED + 5,
+      "a repeated entry counts once"
+    );
+    assert_eq!(report.largest.len(), LARGEST_LISTED);
+    assert_eq!(report.largest[0].bytes, LARGEST_LISTED as u64 + 4);
+    assert!(
+      report
+        .largest
+        .windows(2)
+        .all(|pair| pair[0].bytes >= pair[1].bytes)
+    );
+  }
+}
@@ -0,0 +1,1935 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Unpackers**: how the sources of a complex corpus are brought into the layout the import walk
+//! reads — one `<name>/<name>.zip` per document. A corpus selects one by key (`corpora.unpacker`;
+//! unset is [`DEFAULT_UNPACKER`], arXiv's bulk layout):
+//!
+//! | key | sources on disk |
+//! | --- | --- |
+//! | `arxiv` | top-level `.tar`s of per-paper `.gz` members, under month directories (arXiv bulk data) |
+//! | `archives` | a directory tree of per-paper archives: `.zip`, `.tar.gz`, `.tar.zst`, `.tar.xz`, `.gz` |
+//! | `flat-zip` | top-level `.zip`s, each holding many papers |
+//! | `bundles` | top-level `.tar.zst` / `.tar.xz` / `.tar.gz` / `.tar` bundles, each holding many papers |
+//! | `loose` | one subdirectory per document, holding its files as they are |
+//!
+//! Inside a many-paper collection (`flat-zip`, `bundles`), every top-level folder is one paper, and
+//! so is every top-level archive or `.tex` file.
+//!
+//! Formats are told apart by content ([`infer`] sniffing), never by file name. Every unpacker keeps
+//! the guarantees of the arXiv path on hostile input: walks and member paths are depth-bounded
+//! ([`MAX_WALK_DEPTH`], [`MAX_MEMBER_DEPTH`]); a member that is absolute, climbs out with `..` or
+//! is a link is skipped, so nothing is written outside its document; a document that decompresses
+//! past [`MAX_DOCUMENT_BYTES`] is skipped; and a bad archive or member is logged and skipped, never
+//! fatal to the import. Except for `arxiv` (which removes each `.gz` once
+//! repacked), the sources stay in place, and a document is rewritten only when its source is newer
+//! than its archive — so an `extend` picks up a replaced source (see [`super::changes`]).
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use std::sync::mpsc;
+use std::thread;
+
+use flate2::read::MultiGzDecoder;
+use glob::glob;
+
+use super::{MAX_WALK_DEPTH, WalkEvent};
+
+/// The unpacker of a corpus that selects none: arXiv's bulk layout.
+pub const DEFAULT_UNPACKER: &str = "arxiv";
+/// Deepest member path, in components, written into a document archive; deeper members are
+/// skipped.
+pub const MAX_MEMBER_DEPTH: usize = 32;
+/// Longest document name (directory and archive stem) an unpacker writes.
+const MAX_NAME_LENGTH: usize = 255;
+/// Suffixes stripped from a source file name to name its document, longest first.
+const SOURCE_SUFFIXES: [&str; 10] = [
+  ".tar.gz", ".tar.zst", ".tar.xz", ".tgz", ".tar", ".zip", ".gz", ".zst", ".xz", ".tex",
+];
+/// How many leading bytes of a source a preview reads to tell what it holds.
+pub const SNIFF_BYTES: u64 = 8192;
+/// Most bytes one document may take in memory while it is unpacked: its source, its decompressed
+/// payload, and its members together. A document over it (a compression bomb, or a mislabeled
+/// dataset) is logged and skipped.
+pub const MAX_DOCUMENT_BYTES: u64 = 1 << 30;
+/// Where a bundle's papers are extracted before repacking — hidden, so the import walk never
+/// takes it for a document.
+const STAGING_DIR: &str = ".cortex-staging";
+
+/// One way of bringing the sources of a complex corpus into the import layout.
+pub trait Unpacker: Sync {
+  /// The key a corpus selects it by.
+  fn key(&self) -> &'static str;
+  /// A one-line description of the source layout it reads.
+  fn description(&self) -> &'static str;
+  /// Writes a `<name>/<name>.zip` under `root` for every document found there, and returns the
+  /// top-level directories of `root` holding documents, to which the import walk is then limited.
+  /// With `extend`, work done by an earlier import is skipped where it can be told apart cheaply.
+  /// Only an unusable `root` is an error; a bad source is logged and skipped.
+  fn unpack(&self, root: &Path, extend: bool) -> Result<HashSet<String>, String>;
+  /// Reports to `visit`, writing nothing, the entries an [`unpack`](Unpacker::unpack) of `root`
+  /// would write (and their source sizes) and the sources it would skip, and returns the same
+  /// top-level directories. Archives are read through their headers and the first
+  /// [`SNIFF_BYTES`] of each source only, so damage deeper inside a source goes unnoticed.
+  fn preview(
+    &self,
+    root: &Path,
+    visit: &mut dyn FnMut(WalkEvent),
+  ) -> Result<HashSet<String>, String>;
+}
+
+static UNPACKERS: [&dyn Unpacker; 5] =
+  [&ArxivTars, &PaperArchives, &FlatZips, &Bundles, &LooseFiles];
+
+/// Every registered unpacker.
+pub fn all() -> &'static [&'static dyn Unpacker] { &UNPACKERS }
+
+/// The unpacker registered under `key`.
+pub fn find(key: &str) -> Option<&'static dyn Unpacker> {
+  UNPACKERS
+    .iter()
+    .copied()
+    .find(|unpacker| unpacker.key() == key)
+}
+
+/// The registered keys, comma-separated (for error messages).
+pub fn keys() -> String {
+  UNPACKERS
+    .iter()
+    .map(|unpacker| unpacker.key())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// The path of an archive member normalized to `/`-separated components, if it is safe to write
+/// inside a document: relative, without `..`, and at most [`MAX_MEMBER_DEPTH`] deep. `None` for
+/// anything else — the caller logs and skips the member.
+pub fn safe_member_path(name: &str) -> Option<String> {
+  if name.contains('\0') {
+    return None;
+  }
+  let name = name.replace('\\', "/");
+  let mut parts = Vec::new();
+  for component in Path::new(&name).components() {
+    match component {
+      Component::Normal(part) => parts.push(part.to_str()?),
+      Component::CurDir => {},
+      Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+    }
+  }
+  if parts.is_empty() || parts.len() > MAX_MEMBER_DEPTH {
+    return None;
+  }
+  Some(parts.join("/"))
+}
+
+/// Whether `name` can name a document: a single path component, not hidden, of bounded length.
+pub fn is_document_name(name: &str) -> bool {
+  !name.is_empty()
+    && name.len() <= MAX_NAME_LENGTH
+    && !name.starts_with('.')
+    && !name.contains(['/', '\\', '\0'])
+}
+
+/// The document name of a source file: its archive (or `.tex`) suffix stripped, so
+/// `0801.1234.tar.gz` names `0801.1234`.
+pub fn source_stem(file_name: &str) -> &str {
+  let lower = file_name.to_ascii_lowercase();
+  SOURCE_SUFFIXES
+    .iter()
+    .find(|suffix| lower.len() > suffix.len() && lower.ends_with(*suffix))
+    .map_or(file_name, |suffix| {
+      &file_name[..file_name.len() - suffix.len()]
+    })
+}
+
+/// The content-detected format of `bytes` (an [`infer`] extension: `zip`, `tar`, `gz`, `zst`,
+/// `xz`, `pdf`, …), `None` for headerless content such as plain TeX.
+fn sniff(bytes: &[u8]) -> Option<&'static str> { infer::get(bytes).map(|kind| kind.extension()) }
+
+/// The content-detected format of the file at `path`, from its first bytes.
+fn sniff_file(path: &Path) -> Option<&'static str> {
+  infer::get_from_path(path)
+    .ok()
+    .flatten()
+    .map(|kind| kind.extension())
+}
+
+/// Whether a format is an archive, or a compression of one, that can hold a paper's sources.
+fn is_archive(format: Option<&str>) -> bool {
+  matches!(format, Some("zip" | "tar" | "gz" | "zst" | "xz"))
+}
+
+/// Reads `reader` to its end, through `.take(limit + 1)` so that more than `limit` bytes is told
+/// apart from exactly `limit`. Errors are prefixed with `what`.
+fn read_bounded(reader: impl Read, limit: u64, what: &str) -> Result<Vec<u8>, String> {
+  let mut out = Vec::new();
+  reader
+    .take(limit.saturating_add(1))
+    .read_to_end(&mut out)
+    .map_err(|e| format!("{what}: {e}"))?;
+  if out.len() as u64 > limit {
+    return Err(format!("{what}: more than {limit} bytes — skipped"));
+  }
+  Ok(out)
+}
+
+/// A writer that keeps at most `limit` bytes and fails past them, for the decoders that write
+/// rather than read.
+struct BoundedWriter {
+  out: Vec<u8>,
+  limit: u64,
+  overflowed: bool,
+}
+
+impl Write for BoundedWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    if (self.out.len() + buf.len()) as u64 > self.limit {
+      self.overflowed = true;
+      return Err(io::Error::other("document size limit"));
+    }
+    self.out.extend_from_slice(buf);
+    Ok(buf.len())
+  }
+  fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// Decompresses a gzip, zstd or xz payload held in memory, up to [`MAX_DOCUMENT_BYTES`].
+fn decompress(format: &str, bytes: &[u8]) -> Result<Vec<u8>, String> {
+  match format {
+    "gz" => read_bounded(MultiGzDecoder::new(bytes), MAX_DOCUMENT_BYTES, "gunzip"),
+    "zst" => read_bounded(
+      ruzstd::decoding::StreamingDecoder::new(bytes).map_err(|e| format!("zstd: {e:?}"))?,
+      MAX_DOCUMENT_BYTES,
+      "zstd",
+    ),
+    "xz" => {
+      let mut out = BoundedWriter {
+        out: Vec::new(),
+        limit: MAX_DOCUMENT_BYTES,
+        overflowed: false,
+      };
+      match lzma_rs::xz_decompress(&mut Cursor::new(bytes), &mut out) {
+        Ok(()) => Ok(out.out),
+        Err(_) if out.overflowed => Err(format!(
+          "xz: more than {MAX_DOCUMENT_BYTES} bytes — skipped"
+        )),
+        Err(e) => Err(format!("xz: {e:?}")),
+      }
+    },
+    other => Err(format!("`{other}` is not a compression format")),
+  }
+}
+
+/// The content of the source file at `path`, up to [`MAX_DOCUMENT_BYTES`].
+fn read_source(path: &Path) -> Result<Vec<u8>, String> {
+  let file = File::open(path).map_err(|e| format!("open {path:?}: {e}"))?;
+  read_bounded(file, MAX_DOCUMENT_BYTES, &format!("read {path:?}"))
+}
+
+/// The first [`SNIFF_BYTES`] of the file at `path`.
+fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+  let mut head = Vec::new();
+  File::open(path)?.take(SNIFF_BYTES).read_to_end(&mut head)?;
+  Ok(head)
+}
+
+/// The start of a compressed `head`, as far as it decodes. xz only decodes whole streams, so its
+/// start is usually empty.
+fn decompress_head(format: &str, head: &[u8]) -> Vec<u8> {
+  let mut out = Vec::new();
+  // A truncated stream ends in an error, after the bytes it did decode.
+  let _ = match format {
+    "gz" => MultiGzDecoder::new(head)
+      .take(SNIFF_BYTES)
+      .read_to_end(&mut out),
+    "zst" => match ruzstd::decoding::StreamingDecoder::new(head) {
+      Ok(decoder) => decoder.take(SNIFF_BYTES).read_to_end(&mut out),
+      Err(_) => Ok(0),
+    },
+    _ => Ok(0),
+  };
+  out
+}
+
+/// Whether a source starting with `head` would unpack — the preview's twin of [`document_files`],
+/// peeling one compression layer off the head instead of the whole source. Content that does not
+/// decode far enough to be told apart is given the benefit of the doubt.
+fn check_source_head(head: &[u8]) -> Result<(), String> {
+  let format = match sniff(head) {
+    Some(compression @ ("gz" | "zst" | "xz")) => {
+      let inner = decompress_head(compression, head);
+      if inner.is_empty() {
+        return Ok(());
+      }
+      sniff(&inner)
+    },
+    format => format,
+  };
+  match format {
+    Some("tar" | "zip") | None => Ok(()),
+    Some(other) => Err(format!("content is `{other}`, not a TeX source — rejected")),
+  }
+}
+
+/// The files of one document, held in memory (per-paper sources are small): the members of a zip
+/// or tar, compressed with gzip, zstd or xz or not; or, for headerless content (the arXiv
+/// "surprise", a bare gzipped TeX file), a single `<name>.tex`. Any other detected type — a PDF,
+/// an image — is **rejected**. Member paths are checked by [`write_document`].
+fn document_files(bytes: Vec<u8>, name: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+  let (format, bytes) = match sniff(&bytes) {
+    Some(compression @ ("gz" | "zst" | "xz")) => {
+      let inner = decompress(compression, &bytes)?;
+      (sniff(&inner), inner)
+    },
+    format => (format, bytes),
+  };
+  match format {
+    Some("tar") => tar_files(Cursor::new(bytes)),
+    Some("zip") => zip_files(bytes),
+    None => Ok(vec![(format!("{name}.tex"), bytes)]),
+    Some(other) => Err(format!("content is `{other}`, not a TeX source — rejected")),
+  }
+}
+
+/// The regular-file members of a tar stream; links, devices and unreadable members are skipped.
+/// Members past [`MAX_DOCUMENT_BYTES`] in all are an error.
+fn tar_files(reader: impl Read) -> Result<Vec<(String, Vec<u8>)>, String> {
+  let mut archive = tar::Archive::new(reader);
+  let mut files = Vec::new();
+  let mut budget = MAX_DOCUMENT_BYTES;
+  for entry in archive.entries().map_err(|e| format!("tar entries: {e}"))? {
+    let mut entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        eprintln!("-- import: skipping unreadable tar entry: {e}");
+        continue;
+      },
+    };
+    if !entry.header().entry_type().is_file() {
+      continue;
+    }
+    let name = match entry.path() {
+      Ok(path) => path.to_string_lossy().into_owned(),
+      Err(_) => continue,
+    };
+    let mut data = Vec::new();
+    if let Err(e) = (&mut entry).take(budget + 1).read_to_end(&mut data) {
+      eprintln!("-- import: reading tar entry {name} failed: {e}");
+      continue;
+    }
+    budget = budget
+      .checked_sub(data.len() as u64)
+      .ok_or_else(|| format!("tar: more than {MAX_DOCUMENT_BYTES} bytes — skipped"))?;
+    files.push((name, data));
+  }
+  Ok(files)
+}
+
+/// The file members of a zip held in memory; directories, links and unreadable members are
+/// skipped. Members past [`MAX_DOCUMENT_BYTES`] in all are an error.
+fn zip_files(bytes: Vec<u8>) -> Result<Vec<(String, Vec<u8>)>, String> {
+  let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("zip: {e}"))?;
+  let mut files = Vec::new();
+  let mut budget = MAX_DOCUMENT_BYTES;
+  for index in 0..archive.len() {
+    if let Some((name, data)) = read_zip_member(&mut archive, index, budget)? {
+      budget -= data.len() as u64;
+      files.push((name, data));
+    }
+  }
+  Ok(files)
+}
+
+/// The name and content of the `index`-th member of a zip, if it is a readable regular file. A
+/// member of more than `limit` bytes is an error.
+fn read_zip_member<R: Read + io::Seek>(
+  archive: &mut zip::ZipArchive<R>,
+  index: usize,
+  limit: u64,
+) -> Result<Option<(String, Vec<u8>)>, String> {
+  let mut member = match archive.by_index(index) {
+    Ok(member) => member,
+    Err(e) => {
+      eprintln!("-- import: skipping unreadable zip member #{index}: {e}");
+      return Ok(None);
+    },
+  };
+  if member.is_dir() || member.is_symlink() {
+    return Ok(None);
+  }
+  let name = member.name().to_string();
+  let mut data = Vec::new();
+  if let Err(e) = (&mut member).take(limit + 1).read_to_end(&mut data) {
+    eprintln!("-- import: reading zip member {name} failed: {e}");
+    return Ok(None);
+  }
+  if data.len() as u64 > limit {
+    return Err(format!(
+      "zip: more than {MAX_DOCUMENT_BYTES} bytes — skipped"
+    ));
+  }
+  Ok(Some((name, data)))
+}
+
+/// Writes `<dir>/<name>/<name>.zip` from `files`, skipping (and logging) every member whose path
+/// is unsafe. The archive is written aside and renamed into place, so an interrupted unpack never
+/// leaves a truncated one behind; a document without a single usable member is an error and
+/// leaves nothing behind.
+fn write_document(
+  dir: &Path,
+  name: &str,
+  files: impl IntoIterator<Item = (String, Vec<u8>)>,
+) -> Result<(), String> {
+  if !is_document_name(name) {
+    return Err(format!("unusable document name {name:?}"));
+  }
+  let document_dir = dir.join(name);
+  fs::create_dir_all(&document_dir).map_err(|e| format!("mkdir {document_dir:?}: {e}"))?;
+  let target = document_dir.join(format!("{name}.zip"));
+  let partial = document_dir.join(format!("{name}.zip.part"));
+  match write_zip(&partial, files) {
+    Ok(0) => {
+      let _ = fs::remove_file(&partial);
+      let _ = fs::remove_dir(&document_dir);
+      Err("no usable member".to_string())
+    },
+    Ok(_) => fs::rename(&partial, &target).map_err(|e| format!("rename to {target:?}: {e}")),
+    Err(reason) => {
+      let _ = fs::remove_file(&partial);
+      Err(reason)
+    },
+  }
+}
+
+/// Writes the safe members of `files` as a zip at `path`, returning how many were written.
+fn write_zip(
+  path: &Path,
+  files: impl IntoIterator<Item = (String, Vec<u8>)>,
+) -> Result<usize, String> {
+  let out = File::create(path).map_err(|e| format!("create {path:?}: {e}"))?;
+  let mut zw = zip::ZipWriter::new(BufWriter::new(out));
+  let opts =
+    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+  let mut written = HashSet::new();
+  for (member, data) in files {
+    let Some(name) = safe_member_path(&member) else {
+      eprintln!("-- import: skipping unsafe member path {member:?} for {path:?}");
+      continue;
+    };
+    // A repeated name would make an invalid zip; the first one wins.
+    if !written.insert(name.clone()) {
+      continue;
+    }
+    zw.start_file(name.as_str(), opts)
+      .map_err(|e| format!("zip start_file {name}: {e}"))?;
+    zw.write_all(&data)
+      .map_err(|e| format!("zip write {name}: {e}"))?;
+  }
+  zw.finish()
+    .and_then(|mut out| out.flush().map_err(Into::into))
+    .map_err(|e| format!("finalize zip {path:?}: {e}"))?;
+  Ok(written.len())
+}
+
+/// The modification time of the file at `path`.
+fn modified(path: &Path) -> Option<SystemTime> {
+  fs::metadata(path)
+    .and_then(|metadata| metadata.modified())
+    .ok()
+}
+
+/// Whether the archive of document `name` under `dir` is missing, or older than its source.
+fn is_stale(dir: &Path, name: &str, source_time: Option<SystemTime>) -> bool {
+  match (
+    modified(&dir.join(name).join(format!("{name}.zip"))),
+    source_time,
+  ) {
+    (Some(written), Some(source)) => source > written,
+    (Some(_), None) => false,
+    (None, _) => true,
+  }
+}
+
+/// A file of a document directory: its member path, file path and modification time.
+type TreeFile = (String, PathBuf, Option<SystemTime>);
+
+/// The regular files under `dir`, at most [`MAX_MEMBER_DEPTH`] deep and sorted by member path.
+/// Symbolic links are skipped, so a document's files never come from outside its directory; so
+/// are the top-level file `skip` (the document's own archive) and half-written archives.
+fn document_tree(dir: &Path, skip: &str) -> Vec<TreeFile> {
+  let mut files = Vec::new();
+  let mut queue = vec![(dir.to_path_buf(), String::new(), 0)];
+  while let Some((current, prefix, depth)) = queue.pop() {
+    let entries = match fs::read_dir(&current) {
+      Ok(entries) => entries,
+      Err(e) => {
+        eprintln!("-- import: skipping unreadable directory {current:?}: {e}");
+        continue;
+      },
+    };
+    for entry in entries {
+      let entry = match entry {
+        Ok(entry) => entry,
+        Err(e) => {
+          eprintln!("-- import: skipping unreadable entry under {current:?}: {e}");
+          continue;
+        },
+      };
+      let Some(file_name) = entry.file_name().to_str().map(str::to_string) else {
+        eprintln!("-- import: skipping non-UTF-8 path {:?}", entry.path());
+        continue;
+      };
+      let member = if prefix.is_empty() {
+        file_name.clone()
+      } else {
+        format!("{prefix}/{file_name}")
+      };
+      // `DirEntry::file_type` does not follow symlinks: a link is neither a file nor a directory.
+      let Ok(file_type) = entry.file_type() else {
+        continue;
+      };
+      if file_type.is_dir() {
+        if depth + 1 < MAX_MEMBER_DEPTH {
+          queue.push((entry.path(), member, depth + 1));
+        } else {
+          eprintln!(
+            "-- import: skipping {:?} beyond max member depth {MAX_MEMBER_DEPTH}",
+            entry.path()
+          );
+        }
+      } else if file_type.is_file()
+        && !(depth == 0 && (file_name == skip || file_name.ends_with(".zip.part")))
+      {
+        let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+        files.push((member, entry.path(), modified));
+      }
+    }
+  }
+  files.sort();
+  files
+}
+
+/// Reads the files of a [`document_tree`] one at a time, skipping (and logging) unreadable ones.
+fn read_tree(tree: Vec<TreeFile>) -> impl Iterator<Item = (String, Vec<u8>)> {
+  tree
+    .into_iter()
+    .filter_map(|(member, path, _)| match fs::read(&path) {
+      Ok(data) => Some((member, data)),
+      Err(e) => {
+        eprintln!("-- import: skipping unreadable file {path:?}: {e}");
+        None
+      },
+    })
+}
+
+/// The regular files directly under `root`, sorted.
+fn root_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+  let entries = fs::read_dir(root).map_err(|e| format!("cannot read {root:?}: {e}"))?;
+  let mut files: Vec<PathBuf> = entries
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .filter(|path| path.is_file())
+    .collect();
+  files.sort();
+  Ok(files)
+}
+
+/// The collection name of a many-paper archive at the root, if it can name a directory.
+fn collection_name(path: &Path) -> Option<&str> {
+  path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .map(source_stem)
+    .filter(|name| is_document_name(name))
+}
+
+/// Whether a top-level file of a many-paper collection is a paper — an archive, or a `.tex` file —
+/// judging by its name and first bytes.
+fn check_collection_file(member: &str, head: &[u8]) -> Result<(), String> {
+  if !is_archive(sniff(head)) && !member.to_ascii_lowercase().ends_with(".tex") {
+    return Err("neither an archive nor a .tex file".to_string());
+  }
+  check_source_head(head)
+}
+
+/// Repacks a top-level file of a many-paper collection as document `<out_dir>/<stem>/<stem>.zip`
+/// — if it is a paper. Anything else is skipped.
+fn unpack_collection_file(out_dir: &Path, member: &str, bytes: Vec<u8>) -> Result<(), String> {
+  check_collection_file(member, &bytes)?;
+  let name = source_stem(member);
+  write_document(out_dir, name, document_files(bytes, name)?)
+}
+
+/// The path of the archive an unpack writes for document `name` under `dir`, as the import walk
+/// then finds it.
+fn entry_path(dir: &Path, name: &str) -> String {
+  dir
+    .join(name)
+    .join(format!("{name}.zip"))
+    .to_string_lossy()
+    .into_owned()
+}
+
+/// The display path of a member inside an archive, for skip reports.
+fn member_of(archive: &Path, member: &str) -> PathBuf { archive.join(member) }
+
+/// arXiv's bulk layout: top-level `.tar`s whose members are per-paper `.gz` sources under month
+/// directories.
+struct ArxivTars;
+
+/// The corpus root of an arXiv-topology corpus as a `/`-terminated string, the prefix its tar
+/// members are extracted under.
+fn arxiv_root(root: &Path) -> Result<String, String> {
+  let mut path_str = root
+    .to_str()
+    .ok_or_else(|| format!("non-UTF-8 corpus path {root:?}"))?
+    .to_string();
+  if !path_str.ends_with('/') {
+    path_str.push('/');
+  }
+  Ok(path_str)
+}
+
+impl Unpacker for ArxivTars {
+  fn key(&self) -> &'static str { "arxiv" }
+  fn description(&self) -> &'static str {
+    "arXiv bulk data: top-level .tar files of per-paper .gz sources (each .gz is removed once \
+     repacked)"
+  }
+  fn unpack(&self, root: &Path, extend: bool) -> Result<HashSet<String>, String> {
+    let path_str = arxiv_root(root)?;
+    println!("-- Starting top-level unpack at {path_str}");
+    let mut prefixes = HashSet::new();
+    // A corpus path containing glob metacharacters (`[`, `{`, …) makes `glob` return a
+    // `PatternError`; propagate it as a clean import failure rather than `.unwrap()`-panicking.
+    for entry in glob(&(path_str.clone() + "*.tar")).map_err(|e| e.to_string())? {
+      match entry {
+        Ok(path) => match unpack_top_tar(&path, &path_str, extend) {
+          Ok(seen) => prefixes.extend(seen),
+          Err(reason) => eprintln!("-- import: skipping tar {path:?}: {reason}"),
+        },
+        Err(e) => println!("Failed tar glob: {e:?}"),
+      }
+    }
+    // The monthly unpack removes every `.gz` it repacks, so it is a conservative extension too.
+    println!("-- Starting to unpack monthly .gz archives");
+    month_sources(&path_str, &prefixes, &mut |source| match source {
+      Ok(path) => {
+        if let Err(reason) = unpack_one_gz(&path) {
+          eprintln!("-- import: skipping .gz {path:?}: {reason}");
+        }
+      },
+      Err(event) => event.log(),
+    });
+    Ok(prefixes)
+  }
+  fn preview(
+    &self,
+    root: &Path,
+    visit: &mut dyn FnMut(WalkEvent),
+  ) -> Result<HashSet<String>, String> {
+    let path_str = arxiv_root(root)?;
+    let mut prefixes = HashSet::new();
+    for entry in glob(&(path_str.clone() + "*.tar")).map_err(|e| e.to_string())? {
+      match entry {
+        Ok(path) => {
+          if let Err(reason) = preview_top_tar(&path, &path_str, &mut prefixes, visit) {
+            visit(WalkEvent::Skipped { path, reason });
+          }
+        },
+        Err(e) => visit(WalkEvent::Skipped {
+          path: e.path().to_path_buf(),
+          reason: format!("unreadable: {}", e.error()),
+        }),
+      }
+    }
+    // `.gz` sources already extracted, e.g. by an interrupted import.
+    month_sources(&path_str, &prefixes, &mut |source| {
+      visit(match source {
+        Ok(path) => preview_source_file(&path),
+        Err(event) => event,
+      })
+    });
+    Ok(prefixes)
+  }
+}
+
+/// Reports the monthly per-paper `.gz` sources of an arXiv-topology corpus — under the `prefixes`
+/// just extracted, or every month directory if none — or, as an `Err`, each path it cannot list.
+fn month_sources(
+  path_str: &str,
+  prefixes: &HashSet<String>,
+  visit: &mut dyn FnMut(Result<PathBuf, WalkEvent>),
+) {
+  let gzs_paths = if prefixes.is_empty() {
+    vec![format!("{path_str}*/*.gz")]
+  } else {
+    prefixes
+      .iter()
+      .map(|prefix| format!("{path_str}{prefix}/*.gz"))
+      .collect()
+  };
+  // Skip (with a report) any glob pattern that fails to compile rather than
+  // `.unwrap()`-panicking the whole unpack; the valid patterns still contribute their entries.
+  for pattern in gzs_paths {
+    match glob(&pattern) {
+      Ok(paths) => {
+        for entry in paths {
+          visit(entry.map_err(|e| WalkEvent::Skipped {
+            path: e.path().to_path_buf(),
+            reason: format!("unreadable: {}", e.error()),
+          }));
+        }
+      },
+      Err(e) => visit(Err(WalkEvent::Skipped {
+        path: PathBuf::from(&pattern),
+        reason: format!("invalid glob pattern: {e}"),
+      })),
+    }
+  }
+}
+
+/// The preview of a per-paper source file: the entry its unpack would write next to it, or why it
+/// would be skipped.
+fn preview_source_file(path: &Path) -> WalkEvent {
+  let name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .map(source_stem);
+  let checked = match (name, path.parent()) {
+    (Some(name), Some(dir)) if is_document_name(name) => fs::metadata(path)
+      .map_err(|e| format!("unreadable: {e}"))
+      .and_then(|metadata| {
+        let head = read_head(path).map_err(|e| format!("unreadable: {e}"))?;
+        check_source_head(&head)?;
+        Ok(WalkEvent::Entry {
+          path: entry_path(dir, name),
+          bytes: metadata.len(),
+        })
+      }),
+    _ => Err("unusable document name".to_string()),
+  };
+  checked.unwrap_or_else(|reason| WalkEvent::Skipped {
+    path: path.to_path_buf(),
+    reason,
+  })
+}
+
+/// Previews the members of one top-level arXiv `.tar` — headers and the first bytes of each
+/// `.gz`, not its content — adding the top-level prefixes seen to `prefixes`.
+fn preview_top_tar(
+  tar_path: &Path,
+  path_str: &str,
+  prefixes: &mut HashSet<String>,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> Result<(), String> {
+  let file = File::open(tar_path).map_err(|e| format!("open: {e}"))?;
+  let mut archive = tar::Archive::new(file);
+  for entry in archive
+    .entries_with_seek()
+    .map_err(|e| format!("tar entries: {e}"))?
+  {
+    let mut entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        visit(WalkEvent::Skipped {
+          path: tar_path.to_path_buf(),
+          reason: format!("unreadable tar entry: {e}"),
+        });
+        continue;
+      },
+    };
+    let entry_pathname = match entry.path() {
+      Ok(path) => path.to_string_lossy().into_owned(),
+      Err(_) => continue,
+    };
+    let entry_type = entry.header().entry_type();
+    if entry_pathname.ends_with(".pdf") || entry_type.is_dir() {
+      continue;
+    }
+    if !entry_type.is_file() {
+      visit(WalkEvent::Skipped {
+        path: member_of(tar_path, &entry_pathname),
+        reason: "a link or special file".to_string(),
+      });
+      continue;
+    }
+    let Some(member) = safe_member_path(&entry_pathname) else {
+      visit(WalkEvent::Skipped {
+        path: member_of(tar_path, &entry_pathname),
+        reason: "unsafe member path".to_string(),
+      });
+      continue;
+    };
+    if let Some(base) = member.split('/').next() {
+      prefixes.insert(base.to_owned());
+    }
+    let Some(stem) = member.strip_suffix(".gz") else {
+      continue;
+    };
+    let name = stem.rsplit('/').next().unwrap_or(stem);
+    let mut head = Vec::new();
+    let checked = if !is_document_name(name) {
+      Err("unusable document name".to_string())
+    } else {
+      (&mut entry)
+        .take(SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| format!("unreadable: {e}"))
+        .and_then(|_| check_source_head(&head))
+    };
+    visit(match checked {
+      Ok(()) => WalkEvent::Entry {
+        path: format!("{path_str}{stem}/{name}.zip"),
+        bytes: entry.header().size().unwrap_or_default(),
+      },
+      Err(reason) => WalkEvent::Skipped {
+        path: member_of(tar_path, &member),
+        reason,
+      },
+    });
+  }
+  Ok(())
+}
+
+/// Extracts the entries of one top-level arXiv `.tar` to disk (skipping `.pdf`s, and entries
+/// already unpacked), returning the set of top-level prefixes seen. Hostile-data tolerant (I-1): a
+/// bad entry is logged + skipped, never a panic, and only regular files and directories at safe
+/// paths are extracted. `extend` additionally skips an entry whose unpacked directory (the entry
+/// name without its `.gz` suffix) already exists — unless the tar member is newer than the archive
+/// unpacked there: a replaced (new-version) source, re-extracted so the source check of `extend`
+/// sees the change.
+fn unpack_top_tar(
+  tar_path: &Path,
+  path_str: &str,
+  extend: bool,
+) -> Result<HashSet<String>, String> {
+  let file = File::open(tar_path).map_err(|e| format!("open: {e}"))?;
+  let mut archive = tar::Archive::new(file);
+  let mut prefixes = HashSet::new();
+  for entry in archive.entries().map_err(|e| format!("tar entries: {e}"))? {
+    let mut entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        eprintln!("-- import: skipping unreadable tar entry in {tar_path:?}: {e}");
+        continue;
+      },
+    };
+    let entry_pathname = match entry.path() {
+      Ok(path) => path.to_string_lossy().into_owned(),
+      Err(_) => continue,
+    };
+    if entry_pathname.ends_with(".pdf") {
+      continue;
+    }
+    let entry_type = entry.header().entry_type();
+    if !(entry_type.is_file() || entry_type.is_dir()) {
+      continue; // links could point outside the corpus
+    }
+    let Some(entry_pathname) = safe_member_path(&entry_pathname) else {
+      eprintln!("-- import: skipping unsafe tar entry {entry_pathname:?} in {tar_path:?}");
+      continue;
+    };
+    if let Some(base) = entry_pathname.split('/').next() {
+      prefixes.insert(base.to_owned());
+    }
+    let full_extract_path = format!("{path_str}{entry_pathname}");
+    if fs::metadata(&full_extract_path).is_ok() {
+      continue; // already unpacked
+    }
+    if extend {
+      let dir_extract_path = full_extract_path
+        .strip_suffix(".gz")
+        .unwrap_or(&full_extract_path);
+      if dir_extract_path != full_extract_path
+        && fs::metadata(dir_extract_path).is_ok()
+        && !is_replacement(&entry, dir_extract_path)
+      {
+        continue;
+      }
+    }
+    if let Some(parent) = Path::new(&full_extract_path).parent() {
+      let _ = fs::create_dir_all(parent);
+    }
+    if let Err(e) = entry.unpack(&full_extract_path) {
+      eprintln!("-- import: failed to extract {full_extract_path:?}: {e}");
+    }
+  }
+  Ok(prefixes)
+}
+
+/// Whether a tar member is newer than the `<dir>/<base>.zip` already unpacked from an earlier
+/// version of it (no unpacked archive counts as newer).
+fn is_replacement<R: Read>(entry: &tar::Entry<'_, R>, dir_extract_path: &str) -> bool {
+  let Some(base) = Path::new(dir_extract_path)
+    .file_name()
+    .and_then(|name| name.to_str())
+  else {
+    return false;
+  };
+  let unpacked = Path::new(dir_extract_path).join(format!("{base}.zip"));
+  let Ok(unpacked_time) = fs::metadata(&unpacked).and_then(|meta| meta.modified()) else {
+    return true;
+  };
+  let unpacked_secs = unpacked_time
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|since| since.as_secs())
+    .unwrap_or_default();
+  entry
+    .header()
+    .mtime()
+    .is_ok_and(|member_secs| member_secs > unpacked_secs)
+}
+
+/// Repacks one arXiv per-paper `.gz` source into a `<base>.zip` under `<dir>/<base>/`, then removes
+/// the `.gz`. The decompressed content is **content-detected** (filenames lie): a `tar` becomes its
+/// file entries; headerless content (the arXiv "surprise" — a plain gzipped `.tex`) becomes
+/// `<base>.tex`; any other detected type (e.g. a raw PDF) is **rejected** (the `.gz` kept for
+/// inspection). Hostile-data tolerant (I-1): returns `Err` rather than panicking, so the caller
+/// logs + skips this one and continues the import.
+fn unpack_one_gz(path: &Path) -> Result<(), String> {
+  let entry_dir = path
+    .parent()
+    .ok_or_else(|| format!("no parent dir for {path:?}"))?;
+  let base_name = path
+    .file_stem()
+    .and_then(|s| s.to_str())
+    .ok_or_else(|| format!("no file stem for {path:?}"))?;
+  let bytes = read_source(path)?;
+  // Rejects mislabeled non-source content *before* writing anything.
+  let files = document_files(bytes, base_name)?;
+  write_document(entry_dir, base_name, files)?;
+  if let Err(e) = fs::remove_file(path) {
+    println!("Can't remove source .gz: {e:?}");
+  }
+  Ok(())
+}
+
+/// A directory tree holding one source archive per paper, each repacked next to itself as
+/// `<name>/<name>.zip`.
+struct PaperArchives;
+
+/// What [`walk_paper_archives`] meets.
+enum ArchiveWalk<'a> {
+  /// a per-paper source archive: its directory, document name, path and metadata
+  Source(&'a Path, &'a str, &'a Path, &'a fs::Metadata),
+  /// a skipped path
+  Event(WalkEvent),
+}
+
+/// Walks a tree of per-paper archives at `root` (at most [`MAX_WALK_DEPTH`] deep), reporting every
+/// source archive found — told apart by content — and every skipped path to `visit`. Returns the
+/// top-level directories holding sources. The archives an unpack writes are not sources.
+fn walk_paper_archives(
+  root: &Path,
+  visit: &mut dyn FnMut(ArchiveWalk<'_>),
+) -> Result<HashSet<String>, String> {
+  if !root.is_dir() {
+    return Err(format!("cannot read {root:?}: not a directory"));
+  }
+  let mut prefixes = HashSet::new();
+  let mut queue = vec![(root.to_path_buf(), 0)];
+  while let Some((dir, depth)) = queue.pop() {
+    if depth > MAX_WALK_DEPTH {
+      visit(ArchiveWalk::Event(WalkEvent::TooDeep(dir)));
+      continue;
+    }
+    let entries = match fs::read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(e) => {
+        visit(ArchiveWalk::Event(WalkEvent::Skipped {
+          reason: format!("unreadable directory: {e}"),
+          path: dir,
+        }));
+        continue;
+      },
+    };
+    let dir_name = dir.file_name().and_then(|name| name.to_str());
+    for entry in entries {
+      let path = match entry {
+        Ok(entry) => entry.path(),
+        Err(e) => {
+          visit(ArchiveWalk::Event(WalkEvent::Skipped {
+            path: dir.clone(),
+            reason: format!("unreadable entry: {e}"),
+          }));
+          continue;
+        },
+      };
+      let metadata = match fs::metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+          visit(ArchiveWalk::Event(WalkEvent::Skipped {
+            reason: format!("unreadable path: {e}"),
+            path,
+          }));
+          continue;
+        },
+      };
+      if metadata.is_dir() {
+        queue.push((path, depth + 1));
+        continue;
+      }
+      let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        continue;
+      };
+      let name = source_stem(file_name);
+      // A document archive written by an unpacker (`<name>/<name>.zip`), or one being written.
+      if file_name.ends_with(".zip.part") || (dir_name == Some(name) && file_name.ends_with(".zip"))
+      {
+        continue;
+      }
+      if !is_archive(sniff_file(&path)) {
+        continue;
+      }
+      if !is_document_name(name) {
+        visit(ArchiveWalk::Event(WalkEvent::Skipped {
+          path,
+          reason: "unusable document name".to_string(),
+        }));
+        continue;
+      }
+      let top = match dir
+        .strip_prefix(root)
+        .ok()
+        .and_then(|rel| rel.iter().next())
+      {
+        Some(top) => top.to_string_lossy().into_owned(),
+        None => name.to_string(),
+      };
+      prefixes.insert(top);
+      visit(ArchiveWalk::Source(&dir, name, &path, &metadata));
+    }
+  }
+  Ok(prefixes)
+}
+
+impl Unpacker for PaperArchives {
+  fn key(&self) -> &'static str { "archives" }
+  fn description(&self) -> &'static str {
+    "a directory tree of per-paper archives (.zip, .tar.gz, .tar.zst, .tar.xz, .gz), kept in place"
+  }
+  fn unpack(&self, root: &Path, _extend: bool) -> Result<HashSet<String>, String> {
+    println!("-- Starting per-paper archive unpack at {root:?}");
+    walk_paper_archives(root, &mut |found| match found {
+      ArchiveWalk::Source(dir, name, path, metadata) => {
+        if !is_stale(dir, name, metadata.modified().ok()) {
+          return;
+        }
+        let repacked = read_source(path)
+          .and_then(|bytes| document_files(bytes, name))
+          .and_then(|files| write_document(dir, name, files));
+        if let Err(reason) = repacked {
+          eprintln!("-- import: skipping {path:?}: {reason}");
+        }
+      },
+      ArchiveWalk::Event(event) => event.log(),
+    })
+  }
+  fn preview(
+    &self,
+    root: &Path,
+    visit: &mut dyn FnMut(WalkEvent),
+  ) -> Result<HashSet<String>, String> {
+    walk_paper_archives(root, &mut |found| match found {
+      ArchiveWalk::Source(_, _, path, _) => visit(preview_source_file(path)),
+      ArchiveWalk::Event(event) => visit(event),
+    })
+  }
+}
+
+/// Top-level `.zip`s, each a collection of papers; collection `c.zip` unpacks into
+/// `c/<paper>/<paper>.zip`.
+struct FlatZips;
+
+/// The top-level zips of a flat-zip corpus with their collection names, reporting the unusable
+/// ones to `visit`.
+fn flat_zips(
+  root: &Path,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> Result<Vec<(PathBuf, String)>, String> {
+  let mut zips = Vec::new();
+  for path in root_files(root)? {
+    if sniff_file(&path) != Some("zip") {
+      continue;
+    }
+    match collection_name(&path) {
+      Some(collection) => {
+        let collection = collection.to_string();
+        zips.push((path, collection));
+      },
+      None => visit(WalkEvent::Skipped {
+        path,
+        reason: "unusable collection name".to_string(),
+      }),
+    }
+  }
+  Ok(zips)
+}
+
+impl Unpacker for FlatZips {
+  fn key(&self) -> &'static str { "flat-zip" }
+  fn description(&self) -> &'static str {
+    "top-level .zip files of many papers each — one per top-level folder, archive or .tex file"
+  }
+  fn unpack(&self, root: &Path, _extend: bool) -> Result<HashSet<String>, String> {
+    println!("-- Starting flat zip unpack at {root:?}");
+    let mut prefixes = HashSet::new();
+    for (path, collection) in flat_zips(root, &mut |event| event.log())? {
+      if let Err(reason) = unpack_flat_zip(&path, &root.join(&collection)) {
+        eprintln!("-- import: skipping zip {path:?}: {reason}");
+      }
+      prefixes.insert(collection);
+    }
+    Ok(prefixes)
+  }
+  fn preview(
+    &self,
+    root: &Path,
+    visit: &mut dyn FnMut(WalkEvent),
+  ) -> Result<HashSet<String>, String> {
+    let mut prefixes = HashSet::new();
+    for (path, collection) in flat_zips(root, visit)? {
+      if let Err(reason) = preview_flat_zip(&path, &root.join(&collection), visit) {
+        visit(WalkEvent::Skipped {
+          path: path.clone(),
+          reason,
+        });
+      }
+      prefixes.insert(collection);
+    }
+    Ok(prefixes)
+  }
+}
+
+/// The members of a many-paper zip, grouped by paper: each top-level folder with its members
+/// (index, path inside the folder, size), and each top-level file (index, name, size). Unsafe and
+/// unreadable members are reported to `visit` and left out.
+type FlatZipLayout = (
+  BTreeMap<String, Vec<(usize, String, u64)>>,
+  Vec<(usize, String, u64)>,
+);
+
+/// Reads the [`FlatZipLayout`] of the many-paper zip at `path` from its central directory.
+fn flat_zip_layout<R: Read + io::Seek>(
+  archive: &mut zip::ZipArchive<R>,
+  path: &Path,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> FlatZipLayout {
+  let mut folders: BTreeMap<String, Vec<(usize, String, u64)>> = BTreeMap::new();
+  let mut loose = Vec::new();
+  for index in 0..archive.len() {
+    let member = match archive.by_index_raw(index) {
+      Ok(member) => member,
+      Err(e) => {
+        visit(WalkEvent::Skipped {
+          path: member_of(path, &format!("#{index}")),
+          reason: format!("unreadable zip member: {e}"),
+        });
+        continue;
+      },
+    };
+    if member.is_dir() {
+      continue;
+    }
+    let Some(member_path) = safe_member_path(member.name()).filter(|_| !member.is_symlink()) else {
+      visit(WalkEvent::Skipped {
+        path: member_of(path, member.name()),
+        reason: "unsafe member path or link".to_string(),
+      });
+      continue;
+    };
+    let size = member.size();
+    match member_path.split_once('/') {
+      Some((folder, rest)) => {
+        folders
+          .entry(folder.to_string())
+          .or_default()
+          .push((index, rest.to_string(), size))
+      },
+      None => loose.push((index, member_path, size)),
+    }
+  }
+  (folders, loose)
+}
+
+/// Unpacks the papers of one many-paper zip into `out_dir`, rewriting only the stale ones.
+fn unpack_flat_zip(path: &Path, out_dir: &Path) -> Result<(), String> {
+  let source_time = modified(path);
+  let file = File::open(path).map_err(|e| format!("open: {e}"))?;
+  let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("zip: {e}"))?;
+  let (folders, loose) = flat_zip_layout(&mut archive, path, &mut |event| event.log());
+  for (name, members) in folders {
+    if !is_document_name(&name) {
+      eprintln!("-- import: skipping folder {name:?} in {path:?}: unusable document name");
+      continue;
+    }
+    if !is_stale(out_dir, &name, source_time) {
+      continue;
+    }
+    let mut files = Vec::new();
+    let mut budget = MAX_DOCUMENT_BYTES;
+    let mut read = Ok(());
+    for (index, member, _) in members {
+      match read_zip_member(&mut archive, index, budget) {
+        Ok(Some((_, data))) => {
+          budget -= data.len() as u64;
+          files.push((member, data));
+        },
+        Ok(None) => {},
+        Err(reason) => {
+          read = Err(reason);
+          break;
+        },
+      }
+    }
+    if let Err(reason) = read.and_then(|()| write_document(out_dir, &name, files)) {
+      eprintln!("-- import: skipping {name:?} in {path:?}: {reason}");
+    }
+  }
+  for (index, member, _) in loose {
+    if !is_stale(out_dir, source_stem(&member), source_time) {
+      continue;
+    }
+    let repacked = read_zip_member(&mut archive, index, MAX_DOCUMENT_BYTES)
+      .and_then(|file| file.ok_or_else(|| "unreadable member".to_string()))
+      .and_then(|(_, bytes)| unpack_collection_file(out_dir, &member, bytes));
+    if let Err(reason) = repacked {
+      eprintln!("-- import: skipping {member:?} in {path:?}: {reason}");
+    }
+  }
+  Ok(())
+}
+
+/// Previews the papers of one many-paper zip: its central directory, and the first bytes of each
+/// top-level file.
+fn preview_flat_zip(
+  path: &Path,
+  out_dir: &Path,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> Result<(), String> {
+  let file = File::open(path).map_err(|e| format!("open: {e}"))?;
+  let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("zip: {e}"))?;
+  let (folders, loose) = flat_zip_layout(&mut archive, path, visit);
+  for (name, members) in folders {
+    visit(if is_document_name(&name) {
+      WalkEvent::Entry {
+        path: entry_path(out_dir, &name),
+        bytes: members.iter().map(|(_, _, size)| size).sum(),
+      }
+    } else {
+      WalkEvent::Skipped {
+        path: member_of(path, &name),
+        reason: "unusable document name".to_string(),
+      }
+    });
+  }
+  for (index, member, size) in loose {
+    let mut head = Vec::new();
+    let checked = match archive.by_index(index) {
+      Ok(file) => file
+        .take(SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| format!("unreadable: {e}"))
+        .and_then(|_| check_collection_file(&member, &head)),
+      Err(e) => Err(format!("unreadable: {e}")),
+    };
+    visit(match checked {
+      Ok(()) => WalkEvent::Entry {
+        path: entry_path(out_dir, source_stem(&member)),
+        bytes: size,
+      },
+      Err(reason) => WalkEvent::Skipped {
+        path: member_of(path, &member),
+        reason,
+      },
+    });
+  }
+  Ok(())
+}
+
+/// Top-level compressed tar bundles, each a collection of papers; bundle `b.tar.zst` unpacks into
+/// `b/<paper>/<paper>.zip`.
+struct Bundles;
+
+/// The top-level bundles of a bundles corpus with their detected format and collection names,
+/// reporting the unusable ones to `visit`.
+fn bundles(
+  root: &Path,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> Result<Vec<(PathBuf, &'static str, String)>, String> {
+  let mut found = Vec::new();
+  for path in root_files(root)? {
+    let Some(format @ ("tar" | "gz" | "zst" | "xz")) = sniff_file(&path) else {
+      continue;
+    };
+    match collection_name(&path) {
+      Some(collection) => {
+        let collection = collection.to_string();
+        found.push((path, format, collection));
+      },
+      None => visit(WalkEvent::Skipped {
+        path,
+        reason: "unusable collection name".to_string(),
+      }),
+    }
+  }
+  Ok(found)
+}
+
+impl Unpacker for Bundles {
+  fn key(&self) -> &'static str { "bundles" }
+  fn description(&self) -> &'static str {
+    "top-level .tar.zst, .tar.xz, .tar.gz or .tar bundles of many papers each — one per \
+     top-level folder, archive or .tex file"
+  }
+  fn unpack(&self, root: &Path, extend: bool) -> Result<HashSet<String>, String> {
+    println!("-- Starting bundle unpack at {root:?}");
+    let mut prefixes = HashSet::new();
+    for (path, format, collection) in bundles(root, &mut |event| event.log())? {
+      let out_dir = root.join(&collection);
+      prefixes.insert(collection);
+      // Decompressing a whole bundle is the expensive part: an extend skips a bundle unpacked
+      // since it last changed.
+      if extend
+        && matches!(
+          (modified(&out_dir), modified(&path)),
+          (Some(unpacked), Some(source)) if unpacked >= source
+        )
+      {
+        continue;
+      }
+      if let Err(reason) = unpack_bundle(&path, format, &out_dir) {
+        eprintln!("-- import: skipping bundle {path:?}: {reason}");
+      }
+    }
+    Ok(prefixes)
+  }
+  fn preview(
+    &self,
+    root: &Path,
+    visit: &mut dyn FnMut(WalkEvent),
+  ) -> Result<HashSet<String>, String> {
+    let mut prefixes = HashSet::new();
+    for (path, format, collection) in bundles(root, visit)? {
+      if let Err(reason) = preview_bundle(&path, format, &root.join(&collection), visit) {
+        visit(WalkEvent::Skipped {
+          path: path.clone(),
+          reason,
+        });
+      }
+      prefixes.insert(collection);
+    }
+    Ok(prefixes)
+  }
+}
+
+/// Feeds the chunks an xz decoder writes into a bounded channel (see [`bundle_reader`]).
+struct ChannelWriter(mpsc::SyncSender<Result<Vec<u8>, String>>);
+
+impl Write for ChannelWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    if !buf.is_empty() {
+      // A dropped reader stops the decoder.
+      self
+        .0
+        .send(Ok(buf.to_vec()))
+        .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+    }
+    Ok(buf.len())
+  }
+  fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// Reads the chunks a [`ChannelWriter`] sends, until its decoder is done (or failed).
+struct ChannelReader {
+  receiver: mpsc::Receiver<Result<Vec<u8>, String>>,
+  chunk: Vec<u8>,
+  offset: usize,
+}
+
+impl Read for ChannelReader {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    while self.offset == self.chunk.len() {
+      match self.receiver.recv() {
+        Ok(Ok(chunk)) => {
+          self.chunk = chunk;
+          self.offset = 0;
+        },
+        Ok(Err(reason)) => return Err(io::Error::other(reason)),
+        Err(_) => return Ok(0),
+      }
+    }
+    let read = buf.len().min(self.chunk.len() - self.offset);
+    buf[..read].copy_from_slice(&self.chunk[self.offset..self.offset + read]);
+    self.offset += read;
+    Ok(read)
+  }
+}
+
+/// The decompressed stream of a bundle. The xz decoder only writes, so an xz bundle is decoded on
+/// a helper thread into a bounded channel — streamed like the others, never spooled to disk.
+fn bundle_reader(path: &Path, format: &str) -> Result<Box<dyn Read>, String> {
+  let file = File::open(path)
+    .map(BufReader::new)
+    .map_err(|e| format!("open {path:?}: {e}"))?;
+  Ok(match format {
+    "gz" => Box::new(MultiGzDecoder::new(file)),
+    "zst" => {
+      Box::new(ruzstd::decoding::StreamingDecoder::new(file).map_err(|e| format!("zstd: {e:?}"))?)
+    },
+    "xz" => {
+      let (sender, receiver) = mpsc::sync_channel(16);
+      thread::spawn(move || {
+        let mut file = file;
+        let mut writer = ChannelWriter(sender.clone());
+        if let Err(e) = lzma_rs::xz_decompress(&mut file, &mut writer) {
+          let _ = sender.send(Err(format!("xz: {e:?}")));
+        }
+      });
+      Box::new(ChannelReader {
+        receiver,
+        chunk: Vec::new(),
+        offset: 0,
+      })
+    },
+    _ => Box::new(file),
+  })
+}
+
+/// Unpacks the papers of one bundle into `out_dir`, streaming it once: each paper folder is staged
+/// under [`STAGING_DIR`] and then repacked, rewriting only the stale ones.
+fn unpack_bundle(path: &Path, format: &str, out_dir: &Path) -> Result<(), String> {
+  let source_time = modified(path);
+  let staging = out_dir.join(STAGING_DIR);
+  // Leftovers of an interrupted unpack.
+  let _ = fs::remove_dir_all(&staging);
+  fs::create_dir_all(&staging).map_err(|e| format!("mkdir {staging:?}: {e}"))?;
+  let streamed = bundle_reader(path, format)
+    .and_then(|reader| stage_bundle(reader, &staging, out_dir, source_time));
+  if let Err(reason) = streamed {
+    let _ = fs::remove_dir_all(&staging);
+    return Err(reason);
+  }
+  let mut staged: Vec<PathBuf> = fs::read_dir(&staging)
+    .map_err(|e| format!("cannot read {staging:?}: {e}"))?
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .collect();
+  staged.sort();
+  for folder in staged {
+    let Some(name) = folder.file_name().and_then(|name| name.to_str()) else {
+      continue;
+    };
+    if let Err(reason) = write_document(out_dir, name, read_tree(document_tree(&folder, ""))) {
+      eprintln!("-- import: skipping {name:?} in {path:?}: {reason}");
+    }
+  }
+  let _ = fs::remove_dir_all(&staging);
+  Ok(())
+}
+
+/// Streams the members of a bundle: the regular files of each stale paper folder are staged
+/// under `staging`, and each top-level paper file is repacked into `out_dir` right away.
+fn stage_bundle(
+  reader: impl Read,
+  staging: &Path,
+  out_dir: &Path,
+  source_time: Option<SystemTime>,
+) -> Result<(), String> {
+  let mut archive = tar::Archive::new(reader);
+  for entry in archive.entries().map_err(|e| format!("tar entries: {e}"))? {
+    let mut entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        eprintln!("-- import: skipping unreadable bundle entry: {e}");
+        continue;
+      },
+    };
+    if !entry.header().entry_type().is_file() {
+      continue; // directories are implied; links could point outside the corpus
+    }
+    let member = match entry.path() {
+      Ok(path) => path.to_string_lossy().into_owned(),
+      Err(_) => continue,
+    };
+    let Some(member_path) = safe_member_path(&member) else {
+      eprintln!("-- import: skipping unsafe bundle entry {member:?}");
+      continue;
+    };
+    match member_path.split_once('/') {
+      Some((folder, _)) => {
+        if !is_document_name(folder) || !is_stale(out_dir, folder, source_time) {
+          continue;
+        }
+        let target = staging.join(&member_path);
+        if let Some(parent) = target.parent() {
+          let _ = fs::create_dir_all(parent);
+        }
+        let staged = File::create(&target).and_then(|mut file| io::copy(&mut entry, &mut file));
+        if let Err(e) = staged {
+          eprintln!("-- import: failed to stage {member_path:?}: {e}");
+        }
+      },
+      None => {
+        if !is_stale(out_dir, source_stem(&member_path), source_time) {
+          continue;
+        }
+        let repacked = read_bounded(&mut entry, MAX_DOCUMENT_BYTES, "read")
+          .and_then(|bytes| unpack_collection_file(out_dir, &member_path, bytes));
+        if let Err(reason) = repacked {
+          eprintln!("-- import: skipping bundle entry {member_path:?}: {reason}");
+        }
+      },
+    }
+  }
+  Ok(())
+}
+
+/// Previews the papers of one bundle, streaming it once through its headers and the first bytes
+/// of each top-level file.
+fn preview_bundle(
+  path: &Path,
+  format: &str,
+  out_dir: &Path,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> Result<(), String> {
+  let mut archive = tar::Archive::new(bundle_reader(path, format)?);
+  let mut folders: BTreeMap<String, u64> = BTreeMap::new();
+  for entry in archive.entries().map_err(|e| format!("tar entries: {e}"))? {
+    let mut entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        visit(WalkEvent::Skipped {
+          path: path.to_path_buf(),
+          reason: format!("unreadable bundle entry: {e}"),
+        });
+        continue;
+      },
+    };
+    let member = match entry.path() {
+      Ok(member) => member.to_string_lossy().into_owned(),
+      Err(_) => continue,
+    };
+    let entry_type = entry.header().entry_type();
+    if entry_type.is_dir() {
+      continue;
+    }
+    let member_path = safe_member_path(&member).filter(|_| entry_type.is_file());
+    let Some(member_path) = member_path else {
+      visit(WalkEvent::Skipped {
+        path: member_of(path, &member),
+        reason: "unsafe member path, link or special file".to_string(),
+      });
+      continue;
+    };
+    let size = entry.header().size().unwrap_or_default();
+    match member_path.split_once('/') {
+      Some((folder, _)) => *folders.entry(folder.to_string()).or_default() += size,
+      None => {
+        let mut head = Vec::new();
+        let checked = (&mut entry)
+          .take(SNIFF_BYTES)
+          .read_to_end(&mut head)
+          .map_err(|e| format!("unreadable: {e}"))
+          .and_then(|_| check_collection_file(&member_path, &head));
+        visit(match checked {
+          Ok(()) => WalkEvent::Entry {
+            path: entry_path(out_dir, source_stem(&member_path)),
+            bytes: size,
+          },
+          Err(reason) => WalkEvent::Skipped {
+            path: member_of(path, &member_path),
+            reason,
+          },
+        });
+      },
+    }
+  }
+  for (name, bytes) in folders {
+    visit(if is_document_name(&name) {
+      WalkEvent::Entry {
+        path: entry_path(out_dir, &name),
+        bytes,
+      }
+    } else {
+      WalkEvent::Skipped {
+        path: member_of(path, &name),
+        reason: "unusable document name".to_string(),
+      }
+    });
+  }
+  Ok(())
+}
+
+/// One subdirectory of the root per document, holding its files as they are — packed (not moved)
+/// into `<dir>/<dir>.zip`.
+struct LooseFiles;
+
+/// The document directories of a loose-files corpus, sorted. A symlinked directory is skipped: a
+/// document's files stay inside the corpus.
+fn loose_dirs(
+  root: &Path,
+  visit: &mut dyn FnMut(WalkEvent),
+) -> Result<Vec<(String, PathBuf)>, String> {
+  let mut dirs = Vec::new();
+  for entry in fs::read_dir(root).map_err(|e| format!("cannot read {root:?}: {e}"))? {
+    let entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        visit(WalkEvent::Skipped {
+          path: root.to_path_buf(),
+          reason: format!("unreadable entry: {e}"),
+        });
+        continue;
+      },
+    };
+    if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+      continue;
+    }
+    match entry.file_name().to_str() {
+      Some(name) if is_document_name(name) => dirs.push((name.to_string(), entry.path())),
+      _ => {},
+    }
+  }
+  dirs.sort();
+  Ok(dirs)
+}
+
+impl Unpacker for LooseFiles {
+  fn key(&self) -> &'static str { "loose" }
+  fn description(&self) -> &'static str {
+    "one subdirectory per document holding its loose files, packed in place"
+  }
+  fn unpack(&self, root: &Path, _extend: bool) -> Result<HashSet<String>, String> {
+    println!("-- Starting loose-files pack at {root:?}");
+    let mut prefixes = HashSet::new();
+    for (name, dir) in loose_dirs(root, &mut |event| event.log())? {
+      let tree = document_tree(&dir, &format!("{name}.zip"));
+      let newest = tree.iter().filter_map(|(_, _, time)| *time).max();
+      if !tree.is_empty()
+        && is_stale(root, &name, newest)
+        && let Err(reason) = write_document(root, &name, read_tree(tree))
+      {
+        eprintln!("-- import: skipping {dir:?}: {reason}");
+      }
+      prefixes.insert(name);
+    }
+    Ok(prefixes)
+  }
+  fn preview(
+    &self,
+    root: &Path,
+    visit: &mut dyn FnMut(WalkEvent),
+  ) -> Result<HashSet<String>, String> {
+    let mut prefixes = HashSet::new();
+    for (name, dir) in loose_dirs(root, visit)? {
+      let tree = document_tree(&dir, &format!("{name}.zip"));
+      visit(if tree.is_empty() {
+        WalkEvent::Skipped {
+          path: dir,
+          reason: "no files".to_string(),
+        }
+      } else {
+        WalkEvent::Entry {
+          path: entry_path(root, &name),
+          bytes: tree
+            .iter()
+            .filter_map(|(_, path, _)| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum(),
+        }
+      });
+      prefixes.insert(name);
+    }
+    Ok(prefixes)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cortex_unpackers_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn write_gz(path: &Path, content: &[u8]) {
+    let mut enc =
+      flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::default());
+    enc.write_all(content).unwrap();
+    enc.finish().unwrap();
+  }
+
+  fn zip_names(zip_path: &Path) -> Vec<String> {
+    let mut archive = zip::ZipArchive::new(File::open(zip_path).unwrap()).unwrap();
+    let mut names: Vec<String> = (0..archive.len())
+      .map(|i| archive.by_index(i).unwrap().name().to_string())
+      .collect();
+    names.sort();
+    names
+  }
+
+  /// A tar of `files`, written with raw header names so hostile paths survive the builder.
+  fn tar_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, body) in files {
+      let mut header = tar::Header::new_gnu();
+      header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+      header.set_size(body.len() as u64);
+      header.set_mode(0o644);
+      header.set_cksum();
+      builder.append(&header, *body).unwrap();
+    }
+    builder.into_inner().unwrap()
+  }
+
+  fn zip_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zw = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, body) in files {
+      zw.start_file(*name, zip::write::SimpleFileOptions::default())
+        .unwrap();
+      zw.write_all(body).unwrap();
+    }
+    zw.finish().unwrap().into_inner()
+  }
+
+  #[test]
+  fn member_paths_stay_inside_the_document() {
+    assert_eq!(
+      safe_member_path("./src/paper.tex").as_deref(),
+      Some("src/paper.tex")
+    );
+    assert_eq!(
+      safe_member_path("figs\\a.eps").as_deref(),
+      Some("figs/a.eps")
+    );
+    for hostile in [
+      "../../etc/passwd",
+      "/etc/passwd",
+      "a/../../b",
+      "",
+      ".",
+      "a\0b",
+    ] {
+      assert_eq!(safe_member_path(hostile), None, "{hostile:?} is rejected");
+    }
+    let deep = vec!["d"; MAX_MEMBER_DEPTH + 1].join("/");
+    assert_eq!(safe_member_path(&deep), None);
+    assert_eq!(source_stem("0801.1234.tar.gz"), "0801.1234");
+    assert_eq!(source_stem("Paper.TAR.ZST"), "Paper");
+    assert_eq!(source_stem(".zip"), ".zip");
+    assert!(!is_document_name(".cortex-staging"));
+  }
+
+  #[test]
+  fn registry_resolves_every_key() {
+    assert_eq!(find(DEFAULT_UNPACKER).unwrap().key(), "arxiv");
+    for unpacker in all() {
+      assert_eq!(find(unpacker.key()).unwrap().key(), unpacker.key());
+    }
+    assert!(find("nope").is_none());
+    assert_eq!(keys(), "arxiv, archives, flat-zip, bundles, loose");
+  }
+
+  #[test]
+  fn decoded_content_stops_past_the_limit() {
+    let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    enc.write_all(&[0; 64]).unwrap();
+    let gz = enc.finish().unwrap();
+    assert_eq!(
+      read_bounded(MultiGzDecoder::new(&gz[..]), 64, "gunzip")
+        .unwrap()
+        .len(),
+      64
+    );
+    let reason = read_bounded(MultiGzDecoder::new(&gz[..]), 63, "gunzip").unwrap_err();
+    assert!(reason.contains("more than 63 bytes"), "{reason}");
+
+    let mut out = BoundedWriter {
+      out: Vec::new(),
+      limit: 8,
+      overflowed: false,
+    };
+    assert!(out.write_all(&[0; 8]).is_ok());
+    assert!(out.write_all(&[0]).is_err());
+    assert!(out.overflowed);
+  }
+
+  #[test]
+  fn unpack_one_gz_handles_targz_plaintex_and_rejects_wrong_content() {
+    let root = test_dir("gz");
+    let prefix_dir = root.join("0001");
+    fs::create_dir_all(&prefix_dir).unwrap();
+
+    // (a) a tar.gz with two source files -> a .zip carrying both.
+    let targz = prefix_dir.join("paperA.gz");
+    write_gz(
+      &targz,
+      &tar_bytes(&[
+        ("paper.tex", b"\\documentclass{article}"),
+        ("fig.eps", b"%!PS-Adobe"),
+      ]),
+    );
+    unpack_one_gz(&targz).expect("a tar.gz unpacks");
+    assert_eq!(
+      zip_names(&prefix_dir.join("paperA/paperA.zip")),
+      ["fig.eps", "paper.tex"],
+      "tar.gz -> zip with both files"
+    );
+    assert!(!targz.exists(), "the source .gz is removed");
+
+    // (b) a plain gzipped .tex (the arXiv "surprise") -> a .zip carrying <base>.tex.
+    let plaintex = prefix_dir.join("paperB.gz");
+    write_gz(
+      &plaintex,
+      b"\\documentclass{article}\\begin{document}hi\\end{document}",
+    );
+    unpack_one_gz(&plaintex).expect("a plain gzipped tex unpacks");
+    assert_eq!(
+      zip_names(&prefix_dir.join("paperB/paperB.zip")),
+      ["paperB.tex"],
+      "a plain gz -> <base>.tex"
+    );
+
+    // (c) a gzipped PDF (wrong content) is rejected; the .gz is kept for inspection.
+    let pdfgz = prefix_dir.join("paperC.gz");
+    write_gz(&pdfgz, b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\nrest of a pdf");
+    assert!(unpack_one_gz(&pdfgz).is_err(), "a gzipped PDF is rejected");
+    assert!(pdfgz.exists(), "the rejected .gz is kept");
+    assert!(!prefix_dir.join("paperC").exists(), "nothing is written");
+
+    fs::remove_dir_all(&root).ok();
+  }
+
+  #[test]
+  fn paper_archives_are_repacked_in_place_without_escaping() {
+    let root = test_dir("archives");
+    let month = root.join("2401");
+    fs::create_dir_all(&month).unwrap();
+    fs::write(
+      month.join("2401.0001.zip"),
+      zip_bytes(&[
+        ("main.tex", b"\\documentclass{article}"),
+        ("../../escaped.tex", b"hostile"),
+      ]),
+    )
+    .unwrap();
+    write_gz(
+      &month.join("2401.0002.tar.gz"),
+      &tar_bytes(&[("/abs.tex", b"hostile"), ("paper.tex", b"\\input{x}")]),
+    );
+    fs::write(month.join("notes.txt"), b"not an archive").unwrap();
+
+    let prefixes = find("archives").unwrap().unpack(&root, false).unwrap();
+    assert_eq!(prefixes, HashSet::from(["2401".to_string()]));
+    assert_eq!(
+      zip_names(&month.join("2401.0001/2401.0001.zip")),
+      ["main.tex"]
+    );
+    assert_eq!(
+      zip_names(&month.join("2401.0002/2401.0002.zip")),
+      ["paper.tex"]
+    );
+    assert!(month.join("2401.0001.zip").exists(), "sources are kept");
+    assert!(!root.parent().unwrap().join("escaped.tex").exists());
+    assert!(!month.join("notes").exists(), "non-archives are skipped");
+
+    // A second pass finds every document fresh and does not nest archives.
+    find("archives").unwrap().unpack(&root, true).unwrap();
+    assert!(!month.join("2401.0001/2401.0001/").exists());
+    fs::remove_dir_all(&root).ok();
+  }
+
+  #[test]
+  fn a_flat_zip_unpacks_one_document_per_top_level_folder() {
+    let root = test_dir("flat");
+    fs::write(
+      root.join("batch1.zip"),
+      zip_bytes(&[
+        ("p1/p1.tex", b"\\documentclass{article}"),
+        ("p1/figs/a.eps", b"%!PS"),
+        ("p2/main.tex", b"\\documentclass{book}"),
+        ("p3.tex", b"\\documentclass{article}"),
+        ("README.txt", b"a readme"),
+        ("../p4/evil.tex", b"hostile"),
+      ]),
+    )
+    .unwrap();
+
+    let prefixes = find("flat-zip").unwrap().unpack(&root, false).unwrap();
+    assert_eq!(prefixes, HashSet::from(["batch1".to_string()]));
+    let out = root.join("batch1");
+    assert_eq!(zip_names(&out.join("p1/p1.zip")), ["figs/a.eps", "p1.tex"]);
+    assert_eq!(zip_names(&out.join("p2/p2.zip")), ["main.tex"]);
+    assert_eq!(zip_names(&out.join("p3/p3.zip")), ["p3.tex"]);
+    assert!(!out.join("README.txt").exists() && !root.join("p4").exists());
+    fs::remove_dir_all(&root).ok();
+  }
+
+  #[test]
+  fn a_bundle_is_streamed_and_staging_removed() {
+    let root = test_dir("bundles");
+    write_gz(
+      &root.join("2024-01.tar.gz"),
+      &tar_bytes(&[
+        ("a/a.tex", b"\\documentclass{article}"),
+        ("a/sub/b.tex", b"\\section{b}"),
+        ("b/../../evil.tex", b"hostile"),
+        ("c.tex", b"\\documentclass{article}"),
+      ]),
+    );
+
+    let prefixes = find("bundles").unwrap().unpack(&root, false).unwrap();
+    assert_eq!(prefixes, HashSet::from(["2024-01".to_string()]));
+    let out = root.join("2024-01");
+    assert_eq!(zip_names(&out.join("a/a.zip")), ["a.tex", "sub/b.tex"]);
+    assert_eq!(zip_names(&out.join("c/c.zip")), ["c.tex"]);
+    assert!(
+      !out.join(STAGING_DIR).exists(),
+      "the staging area is removed"
+    );
+    assert!(!root.join("evil.tex").exists() && !out.join("b").exists());
+    fs::remove_dir_all(&root).ok();
+  }
+
+  #[test]
+  fn loose_directories_are_packed_in_place() {
+    let root = test_dir("loose");
+    fs::create_dir_all(root.join("doc1/figs")).unwrap();
+    fs::write(root.join("doc1/main.tex"), b"\\documentclass{article}").unwrap();
+    fs::write(root.join("doc1/figs/a.png"), b"png").unwrap();
+    fs::create_dir_all(root.join(".hidden")).unwrap();
+    fs::write(root.join(".hidden/x.tex"), b"x").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("/etc/hostname", root.join("doc1/link.tex")).unwrap();
+
+    let loose = find("loose").unwrap();
+    let prefixes = loose.unpack(&root, false).unwrap();
+    assert_eq!(prefixes, HashSet::from(["doc1".to_string()]));
+    let archive = root.join("doc1/doc1.zip");
+    assert_eq!(zip_names(&archive), ["figs/a.png", "main.tex"]);
+    // Repacking again leaves the archive out of itself.
+    let packed = modified(&archive);
+    loose.unpack(&root, true).unwrap();
+    assert_eq!(
+      modified(&archive),
+      packed,
+      "an up-to-date document is not rewritten"
+    );
+    assert!(!root.join(".hidden/.hidden.zip").exists());
+    fs::remove_dir_all(&root).ok();
+  }
+}
@@ -337,6 +337,148 @@ pub fn spawn_analyze(pool: DbPool, actor: &str) -> Result<Uuid, String> {
   )
 }
 
+/// The job `kind` for a result archive garbage collection.
+pub const ARCHIVE_GC_KIND: &str = "archive_gc";
+
+/// Spawns a background job that finds the result archives no live task corresponds to — those of
+/// deleted corpora, services and sandboxes (`backend::collect_archives`) — and, with `delete`,
+/// removes them. The job result is the size-accounted report. **Debounced:** a collection already
+/// queued/running is reused (a scan and a delete must not race over the same files).
+pub fn spawn_archive_gc(pool: DbPool, actor: &str, delete: bool) -> Result<Uuid, String> {
+  {
+    let mut connection = pool.get().map_err(|e| e.to_string())?;
+    if let Some(existing) = list_recent(&mut connection, true, 200)
+      .into_iter()
+      .find(|job| job.kind == ARCHIVE_GC_KIND)
+    {
+      return Ok(existing.uuid);
+    }
+  }
+  let owner = actor.to_string();
+  spawn_job(
+    pool,
+    ARCHIVE_GC_KIND,
+    actor,
+    serde_json::json!({ "delete": delete }),
+    move |progress| {
+      let mut connection = progress.pool.get().map_err(|e| e.to_string())?;
+      let report = crate::backend::collect_archives(
+        &mut connection,
+        &*crate::storage::storage(),
+        delete,
+        &owner,
+        |line| progress.step(0, None, line),
+      )?;
+      let found = report.archive_count as i32;
+      progress.step(found, Some(found), "archive collection complete");
+      serde_json::to_value(&report).map_err(|e| e.to_string())
+    },
+  )
+}
+
+/// The job `kind` for a closed run's regression summary.
+pub const RUN_REGRESSIONS_KIND: &str = "run_regressions";
+
+/// Spawns a background job that compares the closed run `run_id`, frozen on drain, with the run
+/// before it (`backend::record_run_regressions`), storing the summary on the run. The dispatcher
+/// spawns one for every run owing a summary ([`retry_pending_run_regressions`]), so the comparison
+/// — bounded by `REGRESSION_QUERY_BUDGET_MS` — runs on its own connection instead of holding up
+/// the finalize thread. The job result is the summary (`null` if the run is gone). **Debounced**
+/// per run: an already-active job for the same run is reused.
+pub fn spawn_run_regressions(pool: DbPool, run_id: i32, actor: &str) -> Result<Uuid, String> {
+  {
+    let mut connection = pool.get().map_err(|e| e.to_string())?;
+    if let Some(existing) = list_recent(&mut connection, true, 200)
+      .into_iter()
+      .find(|job| {
+        job.kind == RUN_REGRESSIONS_KIND
+          && job.params.get("run_id").and_then(Value::as_i64) == Some(i64::from(run_id))
+      })
+    {
+      return Ok(existing.uuid);
+    }
+  }
+  spawn_job(
+    pool,
+    RUN_REGRESSIONS_KIND,
+    actor,
+    serde_json::json!({ "run_id": run_id }),
+    move |progress| {
+      let mut connection = progress.pool.get().map_err(|e| e.to_string())?;
+      let summary = crate::backend::record_run_regressions(
+        &mut connection,
+        run_id,
+        crate::backend::RegressionThresholds::from_config(),
+      )
+      .map_err(|e| format!("regression summary of run {run_id} failed: {e}"))?;
+      if let Some(summary) = summary.as_ref().filter(|summary| summary.regressed()) {
+        tracing::warn!(
+          run_id,
+          worse_documents = summary.worse_documents,
+          new_classes = summary.new_class_count,
+          jumped_classes = summary.jumped_class_count,
+          "run regressions: the closed run regressed"
+        );
+      }
+      serde_json::to_value(&summary).map_err(|e| e.to_string())
+    },
+  )
+}
+
+/// How long a run whose regression job failed waits before [`retry_pending_run_regressions`]
+/// starts another, so a comparison that keeps hitting its budget is not rerun on every tick.
+pub const RUN_REGRESSIONS_RETRY_SECONDS: i64 = 600;
+
+/// Starts a [`spawn_run_regressions`] job for every closed run still owing its regression summary
+/// (`backend::pending_regression_runs`): the runs that just closed on drain, and those whose job
+/// was lost to a restart, interrupted, or failed — the latter once
+/// [`RUN_REGRESSIONS_RETRY_SECONDS`] have passed since. Runs with an active job are skipped.
+/// Returns the number of jobs started; a failure to start one is logged and retried next time.
+pub fn retry_pending_run_regressions(pool: &DbPool, actor: &str) -> Result<usize, String> {
+  let (pending, recent, clock) = {
+    let mut connection = pool.get().map_err(|e| e.to_string())?;
+    let pending = crate::backend::pending_regression_runs(&mut connection)
+      .map_err(|e| format!("listing the runs owing a regression summary failed: {e}"))?;
+    if pending.is_empty() {
+      return Ok(0);
+    }
+    let recent = list_recent(&mut connection, false, 200);
+    let clock = db_now(&mut connection).ok_or("the database clock is unreadable")?;
+    (pending, recent, clock)
+  };
+  // Skew-free, against the clock `updated_at` is written in (see `db_now`).
+  let retry_after = clock - chrono::Duration::seconds(RUN_REGRESSIONS_RETRY_SECONDS);
+  let mut started = 0;
+  for run_id in pending {
+    let waiting = recent.iter().any(|job| {
+      job.kind == RUN_REGRESSIONS_KIND
+        && job.params.get("run_id").and_then(Value::as_i64) == Some(i64::from(run_id))
+        && (matches!(job.status.as_str(), "queued" | "running")
+          || (job.status == "failed" && job.updated_at > retry_after))
+    });
+    if waiting {
+      continue;
+    }
+    match spawn_run_regressions(pool.clone(), run_id, actor) {
+      Ok(_) => started += 1,
+      Err(error) => tracing::warn!(run_id, %error, "run regressions: could not start the job"),
+    }
+  }
+  Ok(started)
+}
+
+/// The most recent finished archive collection, for the retention screen's summary.
+pub fn latest_archive_gc(connection: &mut PgConnection) -> Option<Job> {
+  jobs::table
+    .filter(jobs::kind.eq(ARCHIVE_GC_KIND))
+    .filter(jobs::status.eq("succeeded"))
+    .order(jobs::id.desc())
+    .first(connection)
+    .optional()
+    .ok()
+    .flatten()
+}
+
 /// Best-effort extraction of a human-readable message from a caught panic payload.
 fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
   if let Some(s) = panic.downcast_ref::<&str>() {
@@ -40,5 +40,6 @@ pub mod observability;
 pub mod reports;
 /// Auto-generated diesel schema for the backend DB
 pub mod schema;
+pub mod storage;
 pub mod telemetry;
 pub mod worker;
@@ -36,3 +36,27 @@ pub use webauthn::*;
 
 mod session;
 pub use session::*;
+
+mod activation_weights;
+pub use activation_weights::*;
+
+mod dead_letters;
+pub use dead_letters::*;
+
+mod poison_entries;
+pub use poison_entries::*;
+
+mod worker_keys;
+pub use worker_keys::*;
+
+mod dispatcher_instances;
+pub use dispatcher_instances::*;
+
+mod entry_metadata;
+pub use entry_metadata::*;
+
+mod documents;
+pub use documents::*;
+
+mod result_archives;
+pub use result_archives::*;
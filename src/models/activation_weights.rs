@@ -0,0 +1,188 @@
+//! Per-activation **fair-share weights** and the dispatcher's achieved lease share.
+//!
+//! One `activation_weights` row per `(corpus, service)` activation. The ventilator's lease query
+//! (`tasks_aggregate::fetch_tasks`) splits each batch of a service's TODO tasks across its corpora
+//! in proportion to `weight`, and stamps what each corpus actually received back onto the row, so
+//! the frontend can report target vs achieved share without a scan of `tasks`.
+use std::time::SystemTime;
+
+use diesel::result::Error;
+use diesel::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::IMPORT_SERVICE_ID;
+use crate::schema::{activation_weights, corpora, services, tasks};
+
+/// How much of a pair's `leased_recent` tally survives each lease batch of its service: the decay
+/// that turns the per-batch lease counts into a *recent* achieved share (about the last dozen
+/// batches dominate) rather than a lifetime one skewed by history.
+pub const LEASE_SHARE_DECAY: f64 = 0.8;
+
+#[derive(Identifiable, Queryable, Clone, Debug, Serialize)]
+#[diesel(table_name = activation_weights)]
+#[diesel(primary_key(corpus_id, service_id))]
+/// The fair-share weight of one `(corpus, service)` activation, and its lease tallies.
+pub struct ActivationWeight {
+  /// the activated corpus
+  pub corpus_id: i32,
+  /// the activated service
+  pub service_id: i32,
+  /// relative share of the service's leases this corpus is entitled to (`>= 1`)
+  pub weight: i32,
+  /// lifetime count of tasks leased for this pair
+  pub leased_total: i64,
+  /// decayed per-batch lease tally (see [`LEASE_SHARE_DECAY`])
+  pub leased_recent: f64,
+  /// when a task of this pair was last leased
+  pub last_leased_at: Option<SystemTime>,
+}
+
+/// One pair's target vs achieved lease share, for `/admin/status.json` and `/metrics`.
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+pub struct FairShareDto {
+  /// The corpus name.
+  pub corpus: String,
+  /// The service name.
+  pub service: String,
+  /// The configured weight.
+  pub weight: i32,
+  /// The share the weights entitle this corpus to, among the service's activations (0..=1).
+  pub target_share: f64,
+  /// The share of the service's recent leases this corpus actually received (0..=1); below the
+  /// target when the corpus had less TODO work than its entitlement.
+  pub achieved_share: f64,
+  /// Lifetime tasks leased for this pair.
+  pub leased_total: i64,
+}
+
+impl ActivationWeight {
+  /// The `weight` an activation can be given, or the reason it cannot (below 1). Every surface that
+  /// sets a weight — the API, the corpus screen, the CLI — checks it here.
+  pub fn validate(weight: i32) -> Result<i32, String> {
+    if weight < 1 {
+      Err(format!("a fair-share weight is at least 1, not {weight}"))
+    } else {
+      Ok(weight)
+    }
+  }
+
+  /// Whether a real (non-magic) service `service_id` is activated on corpus `corpus_id`, i.e. has
+  /// tasks there — the activations a weight can be set on.
+  pub fn is_activated(connection: &mut PgConnection, corpus_id: i32, service_id: i32) -> bool {
+    service_id > IMPORT_SERVICE_ID
+      && tasks::table
+        .filter(tasks::corpus_id.eq(corpus_id))
+        .filter(tasks::service_id.eq(service_id))
+        .select(tasks::id)
+        .first::<i64>(connection)
+        .optional()
+        .ok()
+        .flatten()
+        .is_some()
+  }
+
+  /// Sets the weight of a `(corpus, service)` activation, creating its row if needed. Weights
+  /// below 1 are clamped to 1 (every activation keeps making progress).
+  pub fn set(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    service_id: i32,
+    weight: i32,
+  ) -> Result<usize, Error> {
+    let weight = weight.max(1);
+    insert_into(activation_weights::table)
+      .values((
+        activation_weights::corpus_id.eq(corpus_id),
+        activation_weights::service_id.eq(service_id),
+        activation_weights::weight.eq(weight),
+      ))
+      .on_conflict((
+        activation_weights::corpus_id,
+        activation_weights::service_id,
+      ))
+      .do_update()
+      .set(activation_weights::weight.eq(weight))
+      .execute(connection)
+  }
+
+  /// The weights of every corpus activated for `service_id`, by corpus id.
+  pub fn for_service(
+    connection: &mut PgConnection,
+    service_id: i32,
+  ) -> Result<Vec<ActivationWeight>, Error> {
+    activation_weights::table
+      .filter(activation_weights::service_id.eq(service_id))
+      .order(activation_weights::corpus_id.asc())
+      .load(connection)
+  }
+
+  /// Records one lease batch of `service_id`: every pair's `leased_recent` decays by
+  /// [`LEASE_SHARE_DECAY`], then each `(corpus_id, count)` in `leased` is added to its pair's
+  /// tallies. One decay statement plus one update per leased corpus, over the service's handful of
+  /// rows; run inside the lease transaction.
+  pub fn record_leases(
+    connection: &mut PgConnection,
+    service_id: i32,
+    leased: &[(i32, i64)],
+  ) -> Result<(), Error> {
+    update(activation_weights::table.filter(activation_weights::service_id.eq(service_id)))
+      .set(
+        activation_weights::leased_recent.eq(activation_weights::leased_recent * LEASE_SHARE_DECAY),
+      )
+      .execute(connection)?;
+    let now = SystemTime::now();
+    for &(corpus_id, count) in leased.iter().filter(|(_, count)| *count > 0) {
+      update(
+        activation_weights::table
+          .filter(activation_weights::service_id.eq(service_id))
+          .filter(activation_weights::corpus_id.eq(corpus_id)),
+      )
+      .set((
+        activation_weights::leased_total.eq(activation_weights::leased_total + count),
+        activation_weights::leased_recent.eq(activation_weights::leased_recent + count as f64),
+        activation_weights::last_leased_at.eq(Some(now)),
+      ))
+      .execute(connection)?;
+    }
+    Ok(())
+  }
+
+  /// Target vs achieved share for every activation of a real (non-magic) service, ordered by
+  /// service then corpus name. A small-table read (one row per activation) — cheap enough for the
+  /// admin status poll.
+  pub fn shares(connection: &mut PgConnection) -> Result<Vec<FairShareDto>, Error> {
+    let rows: Vec<(String, String, i32, i64, f64)> = activation_weights::table
+      .inner_join(corpora::table)
+      .inner_join(services::table)
+      .filter(activation_weights::service_id.gt(IMPORT_SERVICE_ID))
+      .order((services::name.asc(), corpora::name.asc()))
+      .select((
+        corpora::name,
+        services::name,
+        activation_weights::weight,
+        activation_weights::leased_total,
+        activation_weights::leased_recent,
+      ))
+      .load(connection)?;
+    let mut shares = Vec::with_capacity(rows.len());
+    for (corpus, service, weight, leased_total, leased_recent) in &rows {
+      let siblings = rows.iter().filter(|row| &row.1 == service);
+      let (weights, recent): (i64, f64) =
+        siblings.fold((0, 0.0), |(w, r), row| (w + i64::from(row.2), r + row.4));
+      shares.push(FairShareDto {
+        corpus: corpus.clone(),
+        service: service.clone(),
+        weight: *weight,
+        target_share: f64::from(*weight) / weights.max(1) as f64,
+        achieved_share: if recent > 0.0 {
+          leased_recent / recent
+        } else {
+          0.0
+        },
+        leased_total: *leased_total,
+      });
+    }
+    Ok(shares)
+  }
+}
@@ -76,6 +76,12 @@ impl NewAuditEntry {
     self
   }
 
+  /// Sets the short context line, builder-style (never secrets).
+  pub fn details(mut self, details: impl Into<String>) -> Self {
+    self.details = details.into();
+    self
+  }
+
   /// Records the action. **Best-effort**: the caller should ignore an error (a failed audit write
   /// must never fail the action it describes — accounting is observability, not a gate).
   pub fn record(&self, connection: &mut PgConnection) -> Result<usize, Error> {
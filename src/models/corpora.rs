@@ -5,11 +5,12 @@ use serde::Serialize;
 use std::collections::HashMap;
 
 use crate::concerns::CortexInsertable;
+use crate::importer::unpackers::DEFAULT_UNPACKER;
 use crate::schema::corpora;
 use crate::schema::services;
 use crate::schema::tasks;
 
-use super::services::Service;
+use super::services::{IMPORT_SERVICE_ID, Service};
 
 // Corpora
 
@@ -40,6 +41,9 @@ pub struct Corpus {
   /// Stable external handle (UUIDv7, DB-generated), independent of the mutable `name` — for
   /// public/API references that must survive a rename. Immutable once assigned (Arm 3 / D8).
   pub public_id: uuid::Uuid,
+  /// The key of the unpacker that brings this corpus's sources into the import layout (see
+  /// [`crate::importer::unpackers`]); `None` is arXiv's bulk layout.
+  pub unpacker: Option<String>,
 }
 
 diesel::define_sql_function! {
@@ -67,6 +71,21 @@ impl Corpus {
   /// so a rerun can't clobber the parent's archives — see [`crate::helpers::result_archive_path`]
   /// (F-6).
   pub fn sandbox_id(&self) -> Option<i32> { self.parent_corpus_id.map(|_| self.id) }
+  /// The key of this corpus's unpacker, [`DEFAULT_UNPACKER`] when none was selected.
+  pub fn unpacker_key(&self) -> &str { self.unpacker.as_deref().unwrap_or(DEFAULT_UNPACKER) }
+  /// Selects the unpacker of this corpus by key (`None` restores the default). The key is not
+  /// checked here — callers validate it against [`crate::importer::unpackers::find`].
+  pub fn set_unpacker(
+    &mut self,
+    key: Option<&str>,
+    connection: &mut PgConnection,
+  ) -> Result<(), Error> {
+    update(corpora::table.find(self.id))
+      .set(corpora::unpacker.eq(key))
+      .execute(connection)?;
+    self.unpacker = key.map(str::to_string);
+    Ok(())
+  }
   /// Total number of tasks registered under this corpus (across all services) — the blast radius a
   /// [`Corpus::destroy`] would remove. Used to preview a destructive delete before committing.
   pub fn task_count(&self, connection: &mut PgConnection) -> Result<i64, Error> {
@@ -95,11 +114,11 @@ impl Corpus {
     use crate::schema::tasks::dsl::{corpus_id, service_id, tasks};
     use diesel::dsl::sql;
     use diesel::sql_types::BigInt;
-    // The magic `import` service id is 2 (1=init, 2=import). Raw `count(*)` mirrors
-    // `progress_report` and sidesteps Diesel's aggregate/group-by type check.
+    // Raw `count(*)` mirrors `progress_report` and sidesteps Diesel's aggregate/group-by type
+    // check.
     tasks
       .select((corpus_id, sql::<BigInt>("count(*)")))
-      .filter(service_id.eq(2))
+      .filter(service_id.eq(IMPORT_SERVICE_ID))
       .group_by(corpus_id)
       .load::<(i32, i64)>(connection)
       .unwrap_or_default()
@@ -112,6 +131,7 @@ impl Corpus {
     hm.insert("name".to_string(), self.name.clone());
     hm.insert("path".to_string(), self.path.clone());
     hm.insert("description".to_string(), self.description.clone());
+    hm.insert("unpacker".to_string(), self.unpacker_key().to_string());
     hm
   }
 
@@ -0,0 +1,208 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The dispatch **dead-letter queue**: one row per task the dispatcher's reaper gave up on after
+//! [`MAX_DISPATCH_RETRIES`](crate::dispatcher::server::MAX_DISPATCH_RETRIES) timed-out leases.
+//!
+//! Such a task is still reported `Fatal` (`cortex/never_completed_with_retries`), which on its own
+//! reads like a genuine conversion fatal. The row here keeps what tells the two apart — the worker
+//! identities the task was leased to, and when — and carries the operator's triage: `open` until
+//! someone requeues the task for another attempt, or quarantines it for good
+//! ([`TaskStatus::QUARANTINED`]).
+
+use chrono::{DateTime, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::result::Error;
+
+use crate::helpers::{LeaseRecord, TaskProgress, TaskStatus};
+use crate::schema::{corpora, dead_letters, services, tasks};
+
+/// A freshly recorded dead-letter (`open`, stamped `dead_at = now()` by the database).
+#[derive(Insertable, Clone, Debug, PartialEq, Eq)]
+#[diesel(table_name = dead_letters)]
+pub struct NewDeadLetter {
+  /// the task given up on
+  pub task_id: i64,
+  /// the retries it was granted before being given up on
+  pub retries: i32,
+  /// the worker identity of each lease, in lease order
+  pub workers: Vec<String>,
+  /// the time of each lease, parallel to `workers`
+  pub leased_at: Vec<NaiveDateTime>,
+}
+
+impl NewDeadLetter {
+  /// The dead-letter of an in-flight task whose retry budget is exhausted, with its lease history.
+  pub fn from_progress(progress: &TaskProgress) -> Self {
+    let (workers, leased_at) = progress
+      .leases
+      .iter()
+      .map(
+        |LeaseRecord {
+           worker, leased_at, ..
+         }| {
+          let at = DateTime::from_timestamp(*leased_at, 0).unwrap_or_default();
+          (worker.clone(), at.naive_utc())
+        },
+      )
+      .unzip();
+    NewDeadLetter {
+      task_id: progress.task.id,
+      retries: i32::try_from(progress.retries).unwrap_or(i32::MAX),
+      workers,
+      leased_at,
+    }
+  }
+}
+
+/// A recorded dead-letter.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = dead_letters)]
+pub struct DeadLetter {
+  /// id of the entry
+  pub id: i64,
+  /// the task given up on
+  pub task_id: i64,
+  /// the retries it was granted before being given up on
+  pub retries: i32,
+  /// the worker identity of each lease, in lease order
+  pub workers: Vec<String>,
+  /// the time of each lease, parallel to `workers`
+  pub leased_at: Vec<NaiveDateTime>,
+  /// when the dispatcher gave up on the task
+  pub dead_at: NaiveDateTime,
+  /// the operator's triage: `open`, `requeued` or `quarantined`
+  pub state: String,
+  /// when the entry was requeued or quarantined
+  pub resolved_at: Option<NaiveDateTime>,
+  /// who requeued or quarantined the entry
+  pub resolved_by: Option<String>,
+}
+
+/// A dead-letter together with the task it names, for listings.
+#[derive(Clone, Debug)]
+pub struct DeadLetterListing {
+  /// the entry itself
+  pub letter: DeadLetter,
+  /// the task's corpus name
+  pub corpus: String,
+  /// the task's service name
+  pub service: String,
+  /// the task's entry path
+  pub entry: String,
+  /// the task's current raw status
+  pub status: i32,
+}
+
+/// An operator's triage of an `open` dead-letter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadLetterResolution {
+  /// Reset the task to TODO for another attempt (also releases a quarantined entry).
+  Requeue,
+  /// Park the task permanently as [`TaskStatus::QUARANTINED`].
+  Quarantine,
+}
+
+impl DeadLetterResolution {
+  /// The state an entry is left in.
+  pub fn state(self) -> &'static str {
+    match self {
+      DeadLetterResolution::Requeue => "requeued",
+      DeadLetterResolution::Quarantine => "quarantined",
+    }
+  }
+  /// The task status the resolution sets.
+  pub fn task_status(self) -> TaskStatus {
+    match self {
+      DeadLetterResolution::Requeue => TaskStatus::TODO,
+      DeadLetterResolution::Quarantine => TaskStatus::QUARANTINED,
+    }
+  }
+  /// Whether an entry in `state` may be resolved this way: anything still `open`, plus releasing a
+  /// `quarantined` entry back to the queue.
+  pub fn applies_to(self, state: &str) -> bool {
+    state == "open" || (self == DeadLetterResolution::Requeue && state == "quarantined")
+  }
+  /// Parses the CLI/API action name (`requeue` | `quarantine`).
+  pub fn from_key(key: &str) -> Option<Self> {
+    match key {
+      "requeue" => Some(DeadLetterResolution::Requeue),
+      "quarantine" => Some(DeadLetterResolution::Quarantine),
+      _ => None,
+    }
+  }
+}
+
+impl DeadLetter {
+  /// Records the dead-letters of one reaping pass. Returns the number recorded.
+  pub fn record(connection: &mut PgConnection, letters: &[NewDeadLetter]) -> Result<usize, Error> {
+    if letters.is_empty() {
+      return Ok(0);
+    }
+    diesel::insert_into(dead_letters::table)
+      .values(letters)
+      .execute(connection)
+  }
+
+  /// Loads one entry by id.
+  pub fn find(connection: &mut PgConnection, id: i64) -> Result<DeadLetter, Error> {
+    dead_letters::table.find(id).first(connection)
+  }
+
+  /// Newest-first listing, optionally only the entries in `state`, paged by `limit`/`offset`.
+  pub fn list(
+    connection: &mut PgConnection,
+    state: Option<&str>,
+    limit: i64,
+    offset: i64,
+  ) -> Result<Vec<DeadLetterListing>, Error> {
+    let mut query = dead_letters::table
+      .inner_join(
+        tasks::table
+          .inner_join(corpora::table)
+          .inner_join(services::table),
+      )
+      .select((
+        dead_letters::all_columns,
+        corpora::name,
+        services::name,
+        tasks::entry,
+        tasks::status,
+      ))
+      .order((dead_letters::dead_at.desc(), dead_letters::id.desc()))
+      .limit(limit)
+      .offset(offset)
+      .into_boxed();
+    if let Some(state) = state {
+      query = query.filter(dead_letters::state.eq(state.to_string()));
+    }
+    let rows: Vec<(DeadLetter, String, String, String, i32)> = query.load(connection)?;
+    Ok(
+      rows
+        .into_iter()
+        .map(
+          |(letter, corpus, service, entry, status)| DeadLetterListing {
+            letter,
+            corpus,
+            service,
+            entry,
+            status,
+          },
+        )
+        .collect(),
+    )
+  }
+
+  /// The number of entries in each state, as `(state, count)` pairs.
+  pub fn state_counts(connection: &mut PgConnection) -> Result<Vec<(String, i64)>, Error> {
+    dead_letters::table
+      .group_by(dead_letters::state)
+      .select((dead_letters::state, diesel::dsl::count_star()))
+      .order(dead_letters::state.asc())
+      .load(connection)
+  }
+}
@@ -0,0 +1,224 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Dispatcher instances**: one row per running dispatcher, so several can share the task store
+//! (one per storage node, or an old and a new one overlapping during a rolling restart). Each
+//! instance registers at start, freshens its heartbeat while it runs, and tags the leases it takes
+//! (`tasks.dispatcher_id`). Limbo recovery then resets only the leases of **dead** instances — a
+//! clean stop, or a heartbeat older than `dispatcher.instance_timeout_seconds` — and never a live
+//! peer's. Liveness is always judged by the database clock, so peers on different hosts agree.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::{BigInt, Bool, Integer, Nullable, Timestamp, Varchar};
+
+use crate::helpers::TaskStatus;
+use crate::schema::dispatcher_instances;
+
+/// A registered dispatcher.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = dispatcher_instances)]
+pub struct DispatcherInstance {
+  /// id of the instance (the tag on its leases)
+  pub id: i32,
+  /// host the dispatcher runs on
+  pub host: String,
+  /// its process id
+  pub pid: i32,
+  /// its ventilator port
+  pub source_port: i32,
+  /// its sink port
+  pub result_port: i32,
+  /// when it registered
+  pub started_at: NaiveDateTime,
+  /// its latest heartbeat
+  pub heartbeat_at: NaiveDateTime,
+  /// when it stopped cleanly, or was found dead by a peer
+  pub stopped_at: Option<NaiveDateTime>,
+}
+
+/// A dispatcher registering at start.
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = dispatcher_instances)]
+pub struct NewDispatcherInstance {
+  /// host the dispatcher runs on
+  pub host: String,
+  /// its process id
+  pub pid: i32,
+  /// its ventilator port
+  pub source_port: i32,
+  /// its sink port
+  pub result_port: i32,
+}
+
+/// An instance as listed on the health report, with its liveness judged by the database.
+#[derive(QueryableByName, Clone, Debug)]
+pub struct InstanceStatus {
+  /// id of the instance
+  #[diesel(sql_type = Integer)]
+  pub id: i32,
+  /// host the dispatcher runs on
+  #[diesel(sql_type = Varchar)]
+  pub host: String,
+  /// its process id
+  #[diesel(sql_type = Integer)]
+  pub pid: i32,
+  /// its ventilator port
+  #[diesel(sql_type = Integer)]
+  pub source_port: i32,
+  /// its sink port
+  #[diesel(sql_type = Integer)]
+  pub result_port: i32,
+  /// when it registered
+  #[diesel(sql_type = Timestamp)]
+  pub started_at: NaiveDateTime,
+  /// when it stopped (`None` while running)
+  #[diesel(sql_type = Nullable<Timestamp>)]
+  pub stopped_at: Option<NaiveDateTime>,
+  /// seconds since its latest heartbeat
+  #[diesel(sql_type = BigInt)]
+  pub heartbeat_age_seconds: i64,
+  /// whether it is running and its heartbeat is within the timeout
+  #[diesel(sql_type = Bool)]
+  pub alive: bool,
+  /// tasks it currently holds leased
+  #[diesel(sql_type = BigInt)]
+  pub leases: i64,
+}
+
+/// SQL predicate, over a `tasks` row, for "its lease holder is not a live instance" — the instance
+/// stopped, its heartbeat is older than `$timeout` seconds (the placeholder named by
+/// `timeout_param`), or its row is gone. Untagged leases (`dispatcher_id IS NULL`) are not matched.
+pub(crate) fn dead_lease_holder(timeout_param: &str) -> String {
+  format!(
+    "(tasks.dispatcher_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM dispatcher_instances i \
+     WHERE i.id = tasks.dispatcher_id AND i.stopped_at IS NULL \
+     AND i.heartbeat_at >= now() - {timeout_param} * interval '1 second'))"
+  )
+}
+
+/// The name this host reports, or an empty string if it cannot be read.
+fn host_name() -> String {
+  let mut buffer = [0u8; 256];
+  // SAFETY: the buffer outlives the call, and its length is passed along with it.
+  let status = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+  if status != 0 {
+    return String::new();
+  }
+  let end = buffer
+    .iter()
+    .position(|byte| *byte == 0)
+    .unwrap_or(buffer.len());
+  String::from_utf8_lossy(&buffer[..end]).into_owned()
+}
+
+impl DispatcherInstance {
+  /// Registers this process as a running dispatcher serving the given ports.
+  pub fn register(
+    connection: &mut PgConnection,
+    source_port: usize,
+    result_port: usize,
+  ) -> Result<DispatcherInstance, Error> {
+    diesel::insert_into(dispatcher_instances::table)
+      .values(NewDispatcherInstance {
+        host: host_name(),
+        pid: i32::try_from(std::process::id()).unwrap_or_default(),
+        source_port: i32::try_from(source_port).unwrap_or_default(),
+        result_port: i32::try_from(result_port).unwrap_or_default(),
+      })
+      .get_result(connection)
+  }
+
+  /// Freshens instance `id`'s heartbeat. Returns true if a peer had meanwhile found it dead (its
+  /// heartbeat lapsed past the timeout) and recovered its leases; the instance is revived either
+  /// way, and its later leases are honored as usual.
+  pub fn heartbeat(connection: &mut PgConnection, id: i32) -> Result<bool, Error> {
+    #[derive(QueryableByName)]
+    struct Revived {
+      #[diesel(sql_type = Bool)]
+      revived: bool,
+    }
+    let revived: Option<Revived> = diesel::sql_query(
+      "UPDATE dispatcher_instances i SET heartbeat_at = now(), stopped_at = NULL
+       FROM dispatcher_instances old
+       WHERE i.id = $1 AND old.id = i.id
+       RETURNING old.stopped_at IS NOT NULL AS revived",
+    )
+    .bind::<Integer, _>(id)
+    .get_result(connection)
+    .optional()?;
+    match revived {
+      Some(Revived { revived }) => Ok(revived),
+      None => Err(Error::NotFound),
+    }
+  }
+
+  /// Marks instance `id` stopped and returns its remaining leases to TODO (tasks it leased but
+  /// never saw a result for). Called on a clean shutdown, after the in-flight set drained, so peers
+  /// pick the work up immediately instead of after the heartbeat timeout. Returns the number of
+  /// leases released.
+  pub fn stop(connection: &mut PgConnection, id: i32) -> Result<usize, Error> {
+    use crate::schema::tasks;
+    connection.transaction(|connection| {
+      diesel::update(dispatcher_instances::table.find(id))
+        .set(dispatcher_instances::stopped_at.eq(diesel::dsl::now.nullable()))
+        .execute(connection)?;
+      diesel::update(
+        tasks::table
+          .filter(tasks::dispatcher_id.eq(id))
+          .filter(tasks::status.gt(TaskStatus::TODO.raw())),
+      )
+      .set(tasks::status.eq(TaskStatus::TODO.raw()))
+      .execute(connection)
+    })
+  }
+
+  /// Recovers the leases of dead instances (see [`dead_lease_holder`]) back to TODO, and marks the
+  /// instances whose heartbeat lapsed past `timeout_seconds` as stopped. Run periodically by every
+  /// live dispatcher; concurrent runs are harmless (the second finds nothing left). Returns the
+  /// number of leases recovered.
+  pub fn recover_dead(connection: &mut PgConnection, timeout_seconds: i64) -> Result<usize, Error> {
+    connection.transaction(|connection| {
+      diesel::sql_query(
+        "UPDATE dispatcher_instances SET stopped_at = heartbeat_at
+         WHERE stopped_at IS NULL AND heartbeat_at < now() - $1 * interval '1 second'",
+      )
+      .bind::<BigInt, _>(timeout_seconds)
+      .execute(connection)?;
+      diesel::sql_query(format!(
+        "UPDATE tasks SET status = $2 WHERE status > $2 AND {}",
+        dead_lease_holder("$1")
+      ))
+      .bind::<BigInt, _>(timeout_seconds)
+      .bind::<Integer, _>(TaskStatus::TODO.raw())
+      .execute(connection)
+    })
+  }
+
+  /// The running instances and those stopped within the last day, newest first, with their
+  /// liveness under `timeout_seconds` and their current lease counts.
+  pub fn listing(
+    connection: &mut PgConnection,
+    timeout_seconds: i64,
+  ) -> Result<Vec<InstanceStatus>, Error> {
+    diesel::sql_query(
+      "SELECT i.id, i.host, i.pid, i.source_port, i.result_port, i.started_at, i.stopped_at,
+              EXTRACT(EPOCH FROM now() - i.heartbeat_at)::bigint AS heartbeat_age_seconds,
+              (i.stopped_at IS NULL AND i.heartbeat_at >= now() - $1 * interval '1 second')
+                AS alive,
+              (SELECT count(*) FROM tasks t WHERE t.status > 0 AND t.dispatcher_id = i.id)
+                AS leases
+       FROM dispatcher_instances i
+       WHERE i.stopped_at IS NULL OR i.stopped_at > now() - interval '1 day'
+       ORDER BY i.id DESC
+       LIMIT 50",
+    )
+    .bind::<BigInt, _>(timeout_seconds)
+    .load(connection)
+  }
+}
@@ -0,0 +1,262 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Documents**: the facts the importer extracts about each entry of a corpus — submission month,
+//! source size, file count, main `.tex`, arXiv primary category (see `importer::inspect`) — so
+//! reports, sandbox carves and exports can filter and group by them (`backend::documents`) instead
+//! of pattern-matching the entry path. A source fingerprint (size, modification time, content hash)
+//! lets `extend` tell an entry replaced in place from an untouched one (`importer::changes`).
+
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::{Array, Integer, Nullable, Text, Timestamp};
+
+use crate::schema::documents;
+
+/// What the importer knows about one entry.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = documents)]
+#[diesel(primary_key(corpus_id, entry))]
+pub struct Document {
+  /// the corpus holding the entry
+  pub corpus_id: i32,
+  /// the entry path
+  pub entry: String,
+  /// the document name, as in reports (e.g. `0801.1234`)
+  pub name: String,
+  /// the arXiv submission month (`yymm`), if the path or the name carries one
+  pub yymm: Option<String>,
+  /// size of the source file, in bytes
+  pub source_bytes: Option<i64>,
+  /// files in the source (1 for a single `.tex`)
+  pub file_count: Option<i32>,
+  /// the source member declaring `\documentclass`
+  pub main_tex: Option<String>,
+  /// the arXiv primary category (e.g. `hep-th`)
+  pub primary_category: Option<String>,
+  /// when the entry was inspected
+  pub inspected_at: NaiveDateTime,
+  /// hex SHA-256 of the source file (unknown for entries inspected before hashing)
+  pub content_hash: Option<String>,
+  /// the source file's modification time when it was hashed
+  pub source_modified: Option<NaiveDateTime>,
+  /// when `extend` found the source file missing, if it is still gone
+  pub vanished_at: Option<NaiveDateTime>,
+}
+
+/// The facts of a freshly inspected entry.
+#[derive(Insertable, Clone, Debug, Default, PartialEq, Eq)]
+#[diesel(table_name = documents)]
+pub struct NewDocument {
+  /// the corpus holding the entry
+  pub corpus_id: i32,
+  /// the entry path
+  pub entry: String,
+  /// the document name, as in reports
+  pub name: String,
+  /// the arXiv submission month (`yymm`)
+  pub yymm: Option<String>,
+  /// size of the source file, in bytes
+  pub source_bytes: Option<i64>,
+  /// files in the source
+  pub file_count: Option<i32>,
+  /// the source member declaring `\documentclass`
+  pub main_tex: Option<String>,
+  /// the arXiv primary category
+  pub primary_category: Option<String>,
+  /// hex SHA-256 of the source file
+  pub content_hash: Option<String>,
+  /// the source file's modification time
+  pub source_modified: Option<NaiveDateTime>,
+}
+
+impl Document {
+  /// The facts of `entry` in corpus `corpus_id`, if it was inspected.
+  pub fn find(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    entry: &str,
+  ) -> Result<Option<Document>, Error> {
+    documents::table
+      .find((corpus_id, entry.trim_end()))
+      .first(connection)
+      .optional()
+  }
+
+  /// Records newly inspected entries; an entry inspected before keeps its row. Returns the number
+  /// recorded.
+  pub fn insert_new(connection: &mut PgConnection, rows: &[NewDocument]) -> Result<usize, Error> {
+    if rows.is_empty() {
+      return Ok(0);
+    }
+    diesel::insert_into(documents::table)
+      .values(rows)
+      .on_conflict_do_nothing()
+      .execute(connection)
+  }
+
+  /// Which of `entries` already have a row in corpus `corpus_id` — so a re-walk only inspects the
+  /// new ones.
+  pub fn known_entries(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    entries: &[String],
+  ) -> Result<HashSet<String>, Error> {
+    use crate::schema::documents::dsl;
+    Ok(
+      dsl::documents
+        .filter(dsl::corpus_id.eq(corpus_id))
+        .filter(dsl::entry.eq_any(entries))
+        .select(dsl::entry)
+        .load::<String>(connection)?
+        .into_iter()
+        .collect(),
+    )
+  }
+
+  /// Up to `limit` rows of corpus `corpus_id` with entries after `after`, in entry order — for
+  /// paging through a whole corpus in bounded memory.
+  pub fn page(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    after: &str,
+    limit: i64,
+  ) -> Result<Vec<Document>, Error> {
+    use crate::schema::documents::dsl;
+    dsl::documents
+      .filter(dsl::corpus_id.eq(corpus_id))
+      .filter(dsl::entry.gt(after))
+      .order(dsl::entry.asc())
+      .limit(limit)
+      .load(connection)
+  }
+
+  /// Replaces the source facts of an entry whose source changed with those of its re-inspection
+  /// (name, month and primary category are kept), and clears its vanished flag. Returns the number
+  /// of rows updated.
+  pub fn refresh(connection: &mut PgConnection, document: &NewDocument) -> Result<usize, Error> {
+    use crate::schema::documents::dsl;
+    diesel::update(documents::table.find((document.corpus_id, document.entry.as_str())))
+      .set((
+        dsl::source_bytes.eq(document.source_bytes),
+        dsl::file_count.eq(document.file_count),
+        dsl::main_tex.eq(&document.main_tex),
+        dsl::content_hash.eq(&document.content_hash),
+        dsl::source_modified.eq(document.source_modified),
+        dsl::inspected_at.eq(diesel::dsl::now),
+        dsl::vanished_at.eq(None::<NaiveDateTime>),
+      ))
+      .execute(connection)
+  }
+
+  /// Records the fingerprints of entries whose content is as recorded (or was never hashed), from
+  /// `(entry, content hash, modification time)` triples, and clears their vanished flag. Returns
+  /// the number of rows updated.
+  pub fn record_fingerprints(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    fingerprints: &[(String, String, Option<NaiveDateTime>)],
+  ) -> Result<usize, Error> {
+    if fingerprints.is_empty() {
+      return Ok(0);
+    }
+    let mut entries = Vec::with_capacity(fingerprints.len());
+    let mut hashes = Vec::with_capacity(fingerprints.len());
+    let mut modified = Vec::with_capacity(fingerprints.len());
+    for (entry, hash, time) in fingerprints {
+      entries.push(entry.as_str());
+      hashes.push(hash.as_str());
+      modified.push(*time);
+    }
+    diesel::sql_query(
+      "UPDATE documents d SET content_hash = v.hash, source_modified = v.modified,
+                              vanished_at = NULL
+       FROM unnest($2::text[], $3::text[], $4::timestamp[]) AS v(entry, hash, modified)
+       WHERE d.corpus_id = $1 AND d.entry = v.entry",
+    )
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Array<Text>, _>(entries)
+    .bind::<Array<Text>, _>(hashes)
+    .bind::<Array<Nullable<Timestamp>>, _>(modified)
+    .execute(connection)
+  }
+
+  /// Flags `entries` of corpus `corpus_id` as vanished; an entry flagged before keeps the time it
+  /// was first found missing. Returns the number newly flagged.
+  pub fn mark_vanished(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    entries: &[String],
+  ) -> Result<usize, Error> {
+    use crate::schema::documents::dsl;
+    if entries.is_empty() {
+      return Ok(0);
+    }
+    diesel::update(
+      dsl::documents
+        .filter(dsl::corpus_id.eq(corpus_id))
+        .filter(dsl::entry.eq_any(entries))
+        .filter(dsl::vanished_at.is_null()),
+    )
+    .set(dsl::vanished_at.eq(diesel::dsl::now))
+    .execute(connection)
+  }
+
+  /// Sets the primary category of the named documents of corpus `corpus_id`, from `(name,
+  /// category)` pairs (e.g. read from an arXiv metadata file). Returns the number of rows updated.
+  pub fn set_primary_categories(
+    connection: &mut PgConnection,
+    corpus_id: i32,
+    categories: &[(String, String)],
+  ) -> Result<usize, Error> {
+    if categories.is_empty() {
+      return Ok(0);
+    }
+    let (names, values): (Vec<&str>, Vec<&str>) = categories
+      .iter()
+      .map(|(name, category)| (name.as_str(), category.as_str()))
+      .unzip();
+    diesel::sql_query(
+      "UPDATE documents d SET primary_category = v.category
+       FROM unnest($2::text[], $3::text[]) AS v(name, category)
+       WHERE d.corpus_id = $1 AND d.name = v.name
+         AND d.primary_category IS DISTINCT FROM v.category",
+    )
+    .bind::<Integer, _>(corpus_id)
+    .bind::<Array<Text>, _>(names)
+    .bind::<Array<Text>, _>(values)
+    .execute(connection)
+  }
+
+  /// Copies the rows of corpus `from` to corpus `to` (a sandbox carved from it), for the entries
+  /// `to` has tasks of — so the sandbox filters and groups by the same facts. Returns the number
+  /// copied.
+  pub fn copy_to_sandbox(
+    connection: &mut PgConnection,
+    from: i32,
+    to: i32,
+  ) -> Result<usize, Error> {
+    diesel::sql_query(
+      "INSERT INTO documents (corpus_id, entry, name, yymm, source_bytes, file_count, main_tex,
+                              primary_category, inspected_at, content_hash, source_modified,
+                              vanished_at)
+       SELECT $2, d.entry, d.name, d.yymm, d.source_bytes, d.file_count, d.main_tex,
+              d.primary_category, d.inspected_at, d.content_hash, d.source_modified,
+              d.vanished_at
+       FROM documents d
+       WHERE d.corpus_id = $1 AND EXISTS (SELECT 1 FROM tasks t WHERE t.corpus_id = $2
+                                          AND t.entry = d.entry)
+       ON CONFLICT DO NOTHING",
+    )
+    .bind::<Integer, _>(from)
+    .bind::<Integer, _>(to)
+    .execute(connection)
+  }
+}
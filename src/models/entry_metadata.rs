@@ -0,0 +1,108 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Entry metadata**: the facts an import manifest lists about each document (a DOI, a venue, why
+//! it is in a regression set — see `importer::manifest`), kept per `(corpus, entry)` so every
+//! service's task of the entry shares them. A sandbox has none of its own and reads its parent's.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::upsert::excluded;
+use serde_json::Value;
+
+use crate::models::Corpus;
+use crate::schema::entry_metadata;
+
+/// The manifest metadata of one corpus entry.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = entry_metadata)]
+#[diesel(primary_key(corpus_id, entry))]
+pub struct EntryMetadata {
+  /// the corpus listing the entry
+  pub corpus_id: i32,
+  /// the entry path
+  pub entry: String,
+  /// the manifest row's fields, other than the path (a JSON object)
+  pub metadata: Value,
+  /// when the manifest row was last imported
+  pub imported_at: NaiveDateTime,
+}
+
+/// Manifest metadata to attach to an imported entry.
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = entry_metadata)]
+pub struct NewEntryMetadata {
+  /// the corpus listing the entry
+  pub corpus_id: i32,
+  /// the entry path
+  pub entry: String,
+  /// the manifest row's fields, other than the path (a JSON object)
+  pub metadata: Value,
+}
+
+impl EntryMetadata {
+  /// Attaches (or, on a re-import, replaces) the metadata of the given entries. A row with an
+  /// empty object clears the entry's metadata: a re-import that lists an entry by its path alone
+  /// drops what an earlier manifest said about it. Returns the number of entries written or
+  /// cleared.
+  pub fn upsert(connection: &mut PgConnection, rows: &[NewEntryMetadata]) -> Result<usize, Error> {
+    use crate::schema::entry_metadata::dsl;
+    let (cleared, kept): (Vec<NewEntryMetadata>, Vec<NewEntryMetadata>) =
+      rows.iter().cloned().partition(|row| {
+        row
+          .metadata
+          .as_object()
+          .is_some_and(|fields| fields.is_empty())
+      });
+    let mut written = 0;
+    for row in cleared {
+      written += diesel::delete(
+        dsl::entry_metadata
+          .filter(dsl::corpus_id.eq(row.corpus_id))
+          .filter(dsl::entry.eq(&row.entry)),
+      )
+      .execute(connection)?;
+    }
+    if kept.is_empty() {
+      return Ok(written);
+    }
+    written += diesel::insert_into(entry_metadata::table)
+      .values(&kept)
+      .on_conflict((dsl::corpus_id, dsl::entry))
+      .do_update()
+      .set((
+        dsl::metadata.eq(excluded(dsl::metadata)),
+        dsl::imported_at.eq(diesel::dsl::now),
+      ))
+      .execute(connection)?;
+    Ok(written)
+  }
+
+  /// The metadata of `entry` in `corpus`, falling back to the parent corpus for a sandbox. `None`
+  /// if the entry was not imported from a manifest (or its row carried nothing but the path).
+  pub fn find(
+    connection: &mut PgConnection,
+    corpus: &Corpus,
+    entry: &str,
+  ) -> Result<Option<Value>, Error> {
+    use crate::schema::entry_metadata::dsl;
+    let owners = [Some(corpus.id), corpus.parent_corpus_id];
+    for owner in owners.into_iter().flatten() {
+      let found = dsl::entry_metadata
+        .filter(dsl::corpus_id.eq(owner))
+        .filter(dsl::entry.eq(entry.trim_end()))
+        .select(dsl::metadata)
+        .first::<Value>(connection)
+        .optional()?;
+      if found.is_some() {
+        return Ok(found);
+      }
+    }
+    Ok(None)
+  }
+}
@@ -11,7 +11,7 @@ use std::collections::HashSet;
 use crate::backend::progress_report;
 use crate::concerns::CortexInsertable;
 use crate::helpers::TaskStatus;
-use crate::models::{Corpus, Service};
+use crate::models::{Corpus, IMPORT_SERVICE_ID, Service};
 use crate::schema::historical_runs;
 
 #[derive(Identifiable, Queryable, Associations, Clone, Debug, PartialEq, Eq, QueryableByName)]
@@ -51,6 +51,14 @@ pub struct HistoricalRun {
   /// Stable external handle (UUIDv7, DB-generated) for referencing this specific run independently
   /// of its (corpus, service, start_time) coordinates. Immutable once assigned (Arm 3 / D8).
   pub public_id: uuid::Uuid,
+  /// The regression summary against the previous run (a serialized
+  /// [`RunRegressions`](crate::backend::RunRegressions)), recorded by a background job when the
+  /// run closes on drain; `None` while open, until that job is done, and for runs closed before
+  /// summaries existed.
+  pub regressions: Option<serde_json::Value>,
+  /// Whether the run closed on drain and its regression summary is still owed (the dispatcher
+  /// retries the job until it stores one).
+  pub regressions_pending: bool,
 }
 
 #[derive(Debug, Serialize, Clone, Default)]
@@ -310,8 +318,8 @@ impl HistoricalRun {
   ) -> Result<bool, Error> {
     use crate::schema::historical_runs::dsl::{corpus_id, end_time, service_id};
     use crate::schema::tasks;
-    // `init` (1) / `import` (2) are bookkeeping services, not conversion runs — no run to close.
-    if service <= 2 {
+    // `init` / `import` are bookkeeping services, not conversion runs — no run to close.
+    if service <= IMPORT_SERVICE_ID {
       return Ok(false);
     }
     // Unfinished = anything OUTSIDE the terminal severity band [-5, -1]: `TODO` (0) and `Queued`
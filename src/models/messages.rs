@@ -5,6 +5,7 @@ use diesel::result::Error;
 use diesel::*;
 
 use crate::concerns::CortexInsertable;
+use crate::helpers::fingerprint::details_fingerprint;
 use crate::schema::log_errors;
 use crate::schema::log_fatals;
 use crate::schema::log_infos;
@@ -30,6 +31,10 @@ pub struct LogInfo {
   pub what: Option<String>,
   /// technical details of the message, e.g. localization info (nullable column)
   pub details: Option<String>,
+  /// fingerprint of `details` with its document-specific parts masked (see
+  /// [`crate::helpers::fingerprint`]); `None` for rows stored before fingerprints, until
+  /// backfilled
+  pub fingerprint: Option<i64>,
 }
 #[derive(Insertable, Clone, Debug)]
 #[diesel(table_name = log_infos)]
@@ -43,6 +48,8 @@ pub struct NewLogInfo {
   pub what: String,
   /// technical details of the message (e.g. localization info)
   pub details: String,
+  /// fingerprint of `details` (see [`crate::helpers::fingerprint`])
+  pub fingerprint: i64,
 }
 
 #[derive(Insertable, Clone, Debug)]
@@ -78,6 +85,10 @@ pub struct LogWarning {
   /// so the dispatcher's `Insertable` (which omits it) is unaffected. `None` for pre-migration
   /// rows.
   pub recorded_at: Option<chrono::DateTime<chrono::Utc>>,
+  /// fingerprint of `details` with its document-specific parts masked (see
+  /// [`crate::helpers::fingerprint`]); `None` for rows stored before fingerprints, until
+  /// backfilled
+  pub fingerprint: Option<i64>,
 }
 #[derive(Insertable, Clone, Debug)]
 #[diesel(table_name = log_warnings)]
@@ -91,6 +102,8 @@ pub struct NewLogWarning {
   pub what: String,
   /// technical details of the message (e.g. localization info)
   pub details: String,
+  /// fingerprint of `details` (see [`crate::helpers::fingerprint`])
+  pub fingerprint: i64,
 }
 
 #[derive(Identifiable, Queryable, AsChangeset, Associations, Clone, Debug)]
@@ -111,6 +124,10 @@ pub struct LogError {
   /// so the dispatcher's `Insertable` (which omits it) is unaffected. `None` for pre-migration
   /// rows.
   pub recorded_at: Option<chrono::DateTime<chrono::Utc>>,
+  /// fingerprint of `details` with its document-specific parts masked (see
+  /// [`crate::helpers::fingerprint`]); `None` for rows stored before fingerprints, until
+  /// backfilled
+  pub fingerprint: Option<i64>,
 }
 #[derive(Insertable, Clone, Debug)]
 #[diesel(table_name = log_errors)]
@@ -124,6 +141,8 @@ pub struct NewLogError {
   pub what: String,
   /// technical details of the message (e.g. localization info)
   pub details: String,
+  /// fingerprint of `details` (see [`crate::helpers::fingerprint`])
+  pub fingerprint: i64,
 }
 
 #[derive(Identifiable, Queryable, AsChangeset, Associations, Clone, Debug)]
@@ -144,6 +163,10 @@ pub struct LogFatal {
   /// so the dispatcher's `Insertable` (which omits it) is unaffected. `None` for pre-migration
   /// rows.
   pub recorded_at: Option<chrono::DateTime<chrono::Utc>>,
+  /// fingerprint of `details` with its document-specific parts masked (see
+  /// [`crate::helpers::fingerprint`]); `None` for rows stored before fingerprints, until
+  /// backfilled
+  pub fingerprint: Option<i64>,
 }
 #[derive(Insertable, Clone, Debug)]
 #[diesel(table_name = log_fatals)]
@@ -157,6 +180,8 @@ pub struct NewLogFatal {
   pub what: String,
   /// technical details of the message (e.g. localization info)
   pub details: String,
+  /// fingerprint of `details` (see [`crate::helpers::fingerprint`])
+  pub fingerprint: i64,
 }
 
 #[derive(Identifiable, Queryable, AsChangeset, Clone, Associations, Debug)]
@@ -173,6 +198,10 @@ pub struct LogInvalid {
   pub what: Option<String>,
   /// technical details of the message, e.g. localization info (nullable column)
   pub details: Option<String>,
+  /// fingerprint of `details` with its document-specific parts masked (see
+  /// [`crate::helpers::fingerprint`]); `None` for rows stored before fingerprints, until
+  /// backfilled
+  pub fingerprint: Option<i64>,
 }
 #[derive(Insertable, Clone, Debug)]
 #[diesel(table_name = log_invalids)]
@@ -186,6 +215,8 @@ pub struct NewLogInvalid {
   pub what: String,
   /// technical details of the message (e.g. localization info)
   pub details: String,
+  /// fingerprint of `details` (see [`crate::helpers::fingerprint`])
+  pub fingerprint: i64,
 }
 
 /// Log actor trait, assumes already Identifiable (for id())
@@ -234,7 +265,10 @@ impl LogRecord for NewLogInfo {
   fn category(&self) -> &str { &self.category }
   fn what(&self) -> &str { &self.what }
   fn details(&self) -> &str { &self.details }
-  fn set_details(&mut self, new_details: String) { self.details = new_details; }
+  fn set_details(&mut self, new_details: String) {
+    self.fingerprint = details_fingerprint(&new_details);
+    self.details = new_details;
+  }
   fn severity(&self) -> &str { "info" }
 }
 impl CortexInsertable for NewLogInfo {
@@ -257,7 +291,10 @@ impl LogRecord for NewLogWarning {
   fn category(&self) -> &str { &self.category }
   fn what(&self) -> &str { &self.what }
   fn details(&self) -> &str { &self.details }
-  fn set_details(&mut self, new_details: String) { self.details = new_details; }
+  fn set_details(&mut self, new_details: String) {
+    self.fingerprint = details_fingerprint(&new_details);
+    self.details = new_details;
+  }
   fn severity(&self) -> &str { "warning" }
 }
 impl CortexInsertable for NewLogWarning {
@@ -280,7 +317,10 @@ impl LogRecord for NewLogError {
   fn category(&self) -> &str { &self.category }
   fn what(&self) -> &str { &self.what }
   fn details(&self) -> &str { &self.details }
-  fn set_details(&mut self, new_details: String) { self.details = new_details; }
+  fn set_details(&mut self, new_details: String) {
+    self.fingerprint = details_fingerprint(&new_details);
+    self.details = new_details;
+  }
   fn severity(&self) -> &str { "error" }
 }
 impl CortexInsertable for NewLogError {
@@ -303,7 +343,10 @@ impl LogRecord for NewLogFatal {
   fn category(&self) -> &str { &self.category }
   fn what(&self) -> &str { &self.what }
   fn details(&self) -> &str { &self.details }
-  fn set_details(&mut self, new_details: String) { self.details = new_details; }
+  fn set_details(&mut self, new_details: String) {
+    self.fingerprint = details_fingerprint(&new_details);
+    self.details = new_details;
+  }
   fn severity(&self) -> &str { "fatal" }
 }
 impl CortexInsertable for NewLogFatal {
@@ -326,7 +369,10 @@ impl LogRecord for NewLogInvalid {
   fn category(&self) -> &str { &self.category }
   fn what(&self) -> &str { &self.what }
   fn details(&self) -> &str { &self.details }
-  fn set_details(&mut self, new_details: String) { self.details = new_details; }
+  fn set_details(&mut self, new_details: String) {
+    self.fingerprint = details_fingerprint(&new_details);
+    self.details = new_details;
+  }
   fn severity(&self) -> &str { "invalid" }
 }
 impl CortexInsertable for NewLogInvalid {
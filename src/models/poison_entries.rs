@@ -0,0 +1,278 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Poison-document holds**: the per-entry history of leases that ended without a usable result
+//! — the lease expired (the worker crashed, was OOM-killed or hung — KNOWN_ISSUES W-6), or the
+//! worker reported a crash or timeout itself — across services and runs.
+//!
+//! The dispatcher's reaper reports every expired lease as a [`LeaseIncident`], and the finalize
+//! thread every worker-reported crash ([`LeaseIncident::from_report`]); once an entry's strikes
+//! since its last release reach `dispatcher.poison_hold_threshold`, the entry is held. A held
+//! entry's tasks keep their status — reports still list them — but the lease query
+//! (`tasks_aggregate::fetch_tasks`) skips them until an operator releases the entry. (Not to be
+//! confused with [`TaskStatus::QUARANTINED`], an operator's parking of one dead-lettered task.)
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::{Array, Integer, Nullable, Text};
+
+use crate::helpers::{NewTaskMessage, TaskProgress, TaskReport, TaskStatus};
+use crate::models::LogRecord;
+use crate::schema::{poison_entries, services};
+
+/// The message categories and whats a worker reports a crash or timeout under (LaTeXML's
+/// `timeout`, the worker harness's `Fatal:oom`, a caught `panic`, a `killed` child).
+const CRASH_MARKERS: [&str; 4] = ["timeout", "oom", "panic", "killed"];
+/// The exit codes of a tool that timed out (124) or was killed by a signal (SIGABRT, SIGKILL — an
+/// OOM kill — and SIGSEGV), as the `exit-code` log parser reports them.
+const CRASH_EXIT_CODES: [&str; 4] = ["124", "134", "137", "139"];
+
+/// One lease of an entry that ended without a usable result: it expired (reported by the
+/// dispatcher's reaper), or its worker reported a crash or timeout (reported by the finalize
+/// thread).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaseIncident {
+  /// the entry path of the task whose lease ended
+  pub entry: String,
+  /// the service it was leased for
+  pub service_id: i32,
+  /// the worker identity it was last leased to, if known
+  pub worker: Option<String>,
+}
+
+impl LeaseIncident {
+  /// The incident of an in-flight task whose lease just expired.
+  pub fn from_progress(progress: &TaskProgress) -> Self {
+    LeaseIncident {
+      entry: progress.task.entry.clone(),
+      service_id: progress.task.service_id,
+      worker: progress.leases.last().map(|lease| lease.worker.clone()),
+    }
+  }
+
+  /// The incident of a finished task whose worker reported a crash or timeout — a `Fatal` result
+  /// with a crash message (see [`CRASH_MARKERS`], [`CRASH_EXIT_CODES`]) — or `None` for any other
+  /// result. The harness kills a runaway conversion and reports it rather than letting the lease
+  /// expire, so without these the strikes would miss most poison documents.
+  pub fn from_report(report: &TaskReport) -> Option<Self> {
+    (report.status == TaskStatus::Fatal && report.messages.iter().any(is_crash)).then(|| {
+      LeaseIncident {
+        entry: report.task.entry.clone(),
+        service_id: report.task.service_id,
+        worker: None,
+      }
+    })
+  }
+}
+
+/// Whether `message` is a worker's report of a crash or timeout.
+fn is_crash(message: &NewTaskMessage) -> bool {
+  let marked = |field: &str| {
+    CRASH_MARKERS
+      .iter()
+      .any(|marker| marker.eq_ignore_ascii_case(field))
+  };
+  matches!(message, NewTaskMessage::Fatal(_))
+    && (marked(message.category())
+      || marked(message.what())
+      || (message.category() == "exit_code" && CRASH_EXIT_CODES.contains(&message.what())))
+}
+
+/// An entry's crash/timeout history and hold state.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = poison_entries)]
+#[diesel(primary_key(entry))]
+pub struct PoisonEntry {
+  /// the entry path
+  pub entry: String,
+  /// lifetime count of the entry's leases that ended without a usable result
+  pub incidents: i32,
+  /// incidents since the entry was last released
+  pub strikes: i32,
+  /// when the latest incident happened
+  pub last_incident_at: NaiveDateTime,
+  /// the service of the latest incident
+  pub last_service_id: Option<i32>,
+  /// the worker of the latest incident, if known
+  pub last_worker: Option<String>,
+  /// when the entry was held (`None` unless held)
+  pub held_at: Option<NaiveDateTime>,
+  /// when the entry was last released
+  pub released_at: Option<NaiveDateTime>,
+  /// who last released the entry
+  pub released_by: Option<String>,
+}
+
+/// An entry newly held by [`PoisonEntry::record_incidents`].
+#[derive(QueryableByName, Clone, Debug, PartialEq, Eq)]
+pub struct HeldEntry {
+  /// the entry path
+  #[diesel(sql_type = Text)]
+  pub entry: String,
+  /// its strikes since the last release
+  #[diesel(sql_type = Integer)]
+  pub strikes: i32,
+}
+
+impl PoisonEntry {
+  /// Whether the entry is currently held.
+  pub fn is_held(&self) -> bool { self.held_at.is_some() }
+
+  /// Records the incidents of one reaping pass or finalize batch, then holds every entry whose
+  /// strikes reached `threshold` (`<= 0` disables holding). Returns the entries held by this
+  /// pass. Two statements regardless of the batch size.
+  pub fn record_incidents(
+    connection: &mut PgConnection,
+    incidents: &[LeaseIncident],
+    threshold: i64,
+  ) -> Result<Vec<HeldEntry>, Error> {
+    if incidents.is_empty() {
+      return Ok(Vec::new());
+    }
+    let entries: Vec<&str> = incidents.iter().map(|i| i.entry.as_str()).collect();
+    let services: Vec<i32> = incidents.iter().map(|i| i.service_id).collect();
+    let workers: Vec<Option<&str>> = incidents.iter().map(|i| i.worker.as_deref()).collect();
+    connection.transaction(|connection| {
+      // One row per incident; an entry struck twice in one pass counts twice, keeping its latest
+      // service/worker.
+      diesel::sql_query(
+        "INSERT INTO poison_entries AS p
+           (entry, incidents, strikes, last_incident_at, last_service_id, last_worker)
+         SELECT i.entry, i.n, i.n, now(), i.service_id, i.worker
+         FROM (SELECT entry, count(*)::int AS n,
+                      (array_agg(service_id ORDER BY ord DESC))[1] AS service_id,
+                      (array_agg(worker ORDER BY ord DESC))[1] AS worker
+               FROM unnest($1::text[], $2::int[], $3::text[]) WITH ORDINALITY
+                    AS u(entry, service_id, worker, ord)
+               GROUP BY entry) i
+         ON CONFLICT (entry) DO UPDATE SET
+           incidents = p.incidents + EXCLUDED.incidents,
+           strikes = p.strikes + EXCLUDED.strikes,
+           last_incident_at = EXCLUDED.last_incident_at,
+           last_service_id = EXCLUDED.last_service_id,
+           last_worker = EXCLUDED.last_worker",
+      )
+      .bind::<Array<Text>, _>(&entries)
+      .bind::<Array<Integer>, _>(&services)
+      .bind::<Array<Nullable<Text>>, _>(&workers)
+      .execute(connection)?;
+      if threshold <= 0 {
+        return Ok(Vec::new());
+      }
+      diesel::sql_query(
+        "UPDATE poison_entries SET held_at = now()
+         WHERE entry = ANY($1) AND held_at IS NULL AND strikes >= $2
+         RETURNING entry, strikes",
+      )
+      .bind::<Array<Text>, _>(&entries)
+      .bind::<Integer, _>(i32::try_from(threshold).unwrap_or(i32::MAX))
+      .load(connection)
+    })
+  }
+
+  /// The history of one entry, if it ever had an incident.
+  pub fn find(connection: &mut PgConnection, entry: &str) -> Result<Option<PoisonEntry>, Error> {
+    poison_entries::table
+      .find(entry)
+      .first(connection)
+      .optional()
+  }
+
+  /// The name of the service of the latest incident, if it still exists.
+  pub fn last_service_name(&self, connection: &mut PgConnection) -> Result<Option<String>, Error> {
+    match self.last_service_id {
+      Some(id) => services::table
+        .find(id)
+        .select(services::name)
+        .first(connection)
+        .optional(),
+      None => Ok(None),
+    }
+  }
+
+  /// Releases a held entry on behalf of `actor`: its tasks are leased again, and its strikes start
+  /// over (the lifetime `incidents` are kept). `NotFound` unless the entry is held.
+  pub fn release(
+    connection: &mut PgConnection,
+    entry: &str,
+    actor: &str,
+  ) -> Result<PoisonEntry, Error> {
+    diesel::update(
+      poison_entries::table
+        .find(entry)
+        .filter(poison_entries::held_at.is_not_null()),
+    )
+    .set((
+      poison_entries::held_at.eq(None::<NaiveDateTime>),
+      poison_entries::strikes.eq(0),
+      poison_entries::released_at.eq(Some(chrono::Utc::now().naive_utc())),
+      poison_entries::released_by.eq(Some(actor)),
+    ))
+    .get_result(connection)
+  }
+
+  /// The currently held entries, most recently held first.
+  pub fn held(connection: &mut PgConnection) -> Result<Vec<PoisonEntry>, Error> {
+    poison_entries::table
+      .filter(poison_entries::held_at.is_not_null())
+      .order(poison_entries::held_at.desc())
+      .load(connection)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::Task;
+
+  fn report(status: TaskStatus, messages: Vec<NewTaskMessage>) -> TaskReport {
+    TaskReport {
+      task: Task {
+        id: 1,
+        service_id: 3,
+        corpus_id: 1,
+        status: 0,
+        entry: String::from("/data/poison.zip"),
+        priority: 0,
+        dispatcher_id: None,
+      },
+      status,
+      messages,
+      archive: None,
+      previous_archive: None,
+    }
+  }
+
+  fn fatal(category: &str, what: &str) -> NewTaskMessage {
+    NewTaskMessage::new(1, "fatal", category.into(), what.into(), String::new())
+  }
+
+  #[test]
+  fn worker_reported_crashes_are_incidents() {
+    for crash in [
+      fatal("oom", "alloc"),
+      fatal("conversion", "timeout"),
+      fatal("Timeout", "timedout"),
+      fatal("exit_code", "137"),
+    ] {
+      let incident = LeaseIncident::from_report(&report(TaskStatus::Fatal, vec![crash]));
+      assert_eq!(
+        incident.map(|incident| (incident.entry, incident.service_id)),
+        Some(("/data/poison.zip".to_string(), 3))
+      );
+    }
+    // An ordinary conversion failure is a verdict on the document, not a crash.
+    let verdicts = [
+      report(TaskStatus::Fatal, vec![fatal("undefined", "\\foo")]),
+      report(TaskStatus::Fatal, vec![fatal("exit_code", "2")]),
+      report(TaskStatus::Error, vec![fatal("conversion", "timeout")]),
+    ];
+    for verdict in verdicts {
+      assert_eq!(LeaseIncident::from_report(&verdict), None);
+    }
+  }
+}
@@ -0,0 +1,147 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Result archives**: the size and SHA-256 of each task's result zip, as the sink committed it —
+//! the record `backend::fsck` checks the stored archives against. A record outlives its task: once
+//! the task is deleted (with its corpus, service or sandbox), the record is what lets `backend::gc`
+//! find and delete the archive.
+//!
+//! **Result archive versions**: the previous archives a service that keeps them
+//! (`services.keep_result_versions`) has moved aside instead of overwriting, each tagged with the
+//! historical run it came from.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::schema::{result_archive_versions, result_archives};
+
+/// A committed result archive of a task.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = result_archives)]
+#[diesel(primary_key(task_id))]
+pub struct ResultArchive {
+  /// the task the archive holds the result of
+  pub task_id: i64,
+  /// the archive's storage path
+  pub path: String,
+  /// size of the archive, in bytes
+  pub size_bytes: i64,
+  /// hex SHA-256 of the archive
+  pub sha256: String,
+  /// when the sink committed the archive
+  pub written_at: NaiveDateTime,
+}
+
+/// A freshly committed result archive, recorded when its task is marked done.
+#[derive(Insertable, Clone, Debug, PartialEq, Eq)]
+#[diesel(table_name = result_archives)]
+pub struct NewResultArchive {
+  /// the task the archive holds the result of
+  pub task_id: i64,
+  /// the archive's storage path
+  pub path: String,
+  /// size of the archive, in bytes
+  pub size_bytes: i64,
+  /// hex SHA-256 of the archive
+  pub sha256: String,
+}
+
+impl ResultArchive {
+  /// The recorded archive of task `task_id`, if the sink committed one.
+  pub fn find_by_task(
+    connection: &mut PgConnection,
+    task_id: i64,
+  ) -> Result<Option<ResultArchive>, diesel::result::Error> {
+    result_archives::table
+      .find(task_id)
+      .first(connection)
+      .optional()
+  }
+}
+
+/// A previous result archive of a task, kept when its service retains versions.
+#[derive(Queryable, Identifiable, Clone, Debug, PartialEq, Eq)]
+#[diesel(table_name = result_archive_versions)]
+pub struct ResultArchiveVersion {
+  /// auto-incremented postgres id
+  pub id: i64,
+  /// the task the archive holds a result of
+  pub task_id: i64,
+  /// the historical run the archive was produced in, if one was open
+  pub run_id: Option<i32>,
+  /// the kept archive's storage path
+  pub path: String,
+  /// size of the archive, in bytes
+  pub size_bytes: i64,
+  /// hex SHA-256 of the archive, if it was recorded when committed
+  pub sha256: Option<String>,
+  /// when the sink committed the archive (as the then-current result)
+  pub written_at: NaiveDateTime,
+  /// when the archive was moved aside for a newer result
+  pub saved_at: NaiveDateTime,
+}
+
+/// A previous result archive the sink moved aside, recorded when its task is marked done. The sink
+/// only knows the file; `mark_done` completes the checksum, commit time and run from the archive's
+/// [`ResultArchive`] record.
+#[derive(Insertable, Clone, Debug, PartialEq, Eq)]
+#[diesel(table_name = result_archive_versions)]
+pub struct NewResultArchiveVersion {
+  /// the task the archive holds a result of
+  pub task_id: i64,
+  /// the historical run the archive was produced in, if one was open
+  pub run_id: Option<i32>,
+  /// where the archive was moved to
+  pub path: String,
+  /// size of the archive, in bytes
+  pub size_bytes: i64,
+  /// hex SHA-256 of the archive, if it was recorded when committed
+  pub sha256: Option<String>,
+  /// when the sink committed the archive
+  pub written_at: NaiveDateTime,
+}
+
+impl ResultArchiveVersion {
+  /// The kept versions of task `task_id`, newest first.
+  pub fn for_task(
+    connection: &mut PgConnection,
+    task_id: i64,
+  ) -> Result<Vec<ResultArchiveVersion>, diesel::result::Error> {
+    result_archive_versions::table
+      .filter(result_archive_versions::task_id.eq(task_id))
+      .order(result_archive_versions::id.desc())
+      .load(connection)
+  }
+
+  /// Retention stats for the kept versions: how many there are and their total size, in bytes.
+  pub fn retention_stats(
+    connection: &mut PgConnection,
+  ) -> Result<(i64, i64), diesel::result::Error> {
+    use diesel::dsl::sql;
+    use diesel::sql_types::{BigInt, Nullable};
+    // `sum(bigint)` is a numeric in Postgres; cast it back, as the sizes fit a bigint.
+    let (count, bytes): (i64, Option<i64>) = result_archive_versions::table
+      .select((
+        diesel::dsl::count_star(),
+        sql::<Nullable<BigInt>>("sum(size_bytes)::bigint"),
+      ))
+      .first(connection)?;
+    Ok((count, bytes.unwrap_or(0)))
+  }
+
+  /// How many kept versions were moved aside strictly before `cutoff` — the retention screen's
+  /// dry-run count, next to the snapshots the same cutoff prunes.
+  pub fn count_before(
+    connection: &mut PgConnection,
+    cutoff: NaiveDateTime,
+  ) -> Result<i64, diesel::result::Error> {
+    result_archive_versions::table
+      .filter(result_archive_versions::saved_at.lt(cutoff))
+      .count()
+      .get_result(connection)
+  }
+}
@@ -8,7 +8,15 @@ use crate::schema::services;
 use crate::schema::worker_metadata;
 
 use super::worker_metadata::WorkerMetadata;
+use crate::backend::{ControlKind, ControlSignal, wake_dispatcher};
 use crate::concerns::CortexInsertable;
+use crate::helpers::log_parsers::{self, DEFAULT_LOG_PARSER, LogParser};
+
+/// The id of the magic `init` service, which marks a corpus's import.
+pub const INIT_SERVICE_ID: i32 = 1;
+/// The id of the magic `import` service, whose tasks are a corpus's imported entries. Real
+/// conversion services have ids above it.
+pub const IMPORT_SERVICE_ID: i32 = 2;
 
 // Services
 #[derive(Identifiable, Queryable, AsChangeset, Clone, Debug)]
@@ -42,6 +50,20 @@ pub struct Service {
   /// dead-worker recovery. Captured into `TaskProgress` at dispatch time, so a config change
   /// never re-times an already-leased task.
   pub lease_timeout_seconds: Option<i32>,
+  /// Pipeline invalidation policy when the upstream service (see [`Service::upstream`]) reruns a
+  /// document: `rerun` (reset this service's task to await the fresh upstream result) or `keep`.
+  pub on_upstream_rerun: String,
+  /// Per-service cap on lease renewals: how many times one in-flight lease may be extended by the
+  /// worker's "still working" heartbeat, or `None` to use the global
+  /// `dispatcher.max_lease_renewals`. `0` disables renewal. Captured into `TaskProgress` at
+  /// dispatch time, like `lease_timeout_seconds`.
+  pub max_lease_renewals: Option<i32>,
+  /// How many previous result archives to keep per task, each tagged with the historical run it
+  /// came from. `0` (the default) overwrites `<service>.zip` in place on every rerun.
+  pub keep_result_versions: i32,
+  /// The key of the log parser that reads this service's worker logs (see
+  /// [`crate::helpers::log_parsers`]); `None` is LaTeXML's message convention.
+  pub log_parser: Option<String>,
 }
 /// Insertable struct for `Service`
 #[derive(Insertable, Clone, Debug)]
@@ -93,15 +115,122 @@ impl Service {
   /// Sets (or clears, with `None`) this service's per-service lease / visibility-timeout override
   /// (D-17). `None` ⇒ fall back to the global `dispatcher.lease_timeout_seconds`. Captured into
   /// `TaskProgress` only at lease time, so a change takes effect on the **next** dispatch and never
-  /// re-times an already-leased task. Returns the rows updated (1, or 0 if the service vanished).
+  /// re-times an already-leased task. Wakes the dispatcher to re-read its cached row. Returns the
+  /// rows updated (1, or 0 if the service vanished).
   pub fn set_lease_timeout(
     &self,
     seconds: Option<i32>,
     connection: &mut PgConnection,
   ) -> Result<usize, Error> {
-    update(services::table.find(self.id))
+    let updated = update(services::table.find(self.id))
       .set(services::lease_timeout_seconds.eq(seconds))
-      .execute(connection)
+      .execute(connection)?;
+    self.announce_change(connection);
+    Ok(updated)
+  }
+
+  /// Sets (or clears, with `None`) this service's cap on lease renewals (see
+  /// [`Service::max_lease_renewals`]). `None` ⇒ fall back to the global
+  /// `dispatcher.max_lease_renewals`. Wakes the dispatcher to re-read its cached row, so the change
+  /// takes effect on the next dispatch.
+  pub fn set_max_lease_renewals(
+    &self,
+    renewals: Option<i32>,
+    connection: &mut PgConnection,
+  ) -> Result<usize, Error> {
+    let updated = update(services::table.find(self.id))
+      .set(services::max_lease_renewals.eq(renewals))
+      .execute(connection)?;
+    self.announce_change(connection);
+    Ok(updated)
+  }
+
+  /// Sets how many previous result archives to keep per task (see
+  /// [`Service::keep_result_versions`]); `0` stops keeping new ones. Wakes the dispatcher to
+  /// re-read its cached row; versions already kept age out with retention pruning.
+  pub fn set_keep_result_versions(
+    &self,
+    versions: i32,
+    connection: &mut PgConnection,
+  ) -> Result<usize, Error> {
+    let updated = update(services::table.find(self.id))
+      .set(services::keep_result_versions.eq(versions))
+      .execute(connection)?;
+    self.announce_change(connection);
+    Ok(updated)
+  }
+
+  /// The key of this service's log parser, [`DEFAULT_LOG_PARSER`] when none was selected.
+  pub fn log_parser_key(&self) -> &str { self.log_parser.as_deref().unwrap_or(DEFAULT_LOG_PARSER) }
+
+  /// The log parser that reads this service's worker logs. A key no longer registered falls back
+  /// to [`DEFAULT_LOG_PARSER`].
+  pub fn log_parser(&self) -> &'static dyn LogParser {
+    log_parsers::find(self.log_parser_key()).unwrap_or(log_parsers::default_parser())
+  }
+
+  /// Selects the log parser of this service by key (`None` restores the default). The key is not
+  /// checked here — callers validate it against [`crate::helpers::log_parsers::find`]. Wakes the
+  /// dispatcher to re-read its cached row.
+  pub fn set_log_parser(
+    &self,
+    key: Option<&str>,
+    connection: &mut PgConnection,
+  ) -> Result<usize, Error> {
+    let updated = update(services::table.find(self.id))
+      .set(services::log_parser.eq(key))
+      .execute(connection)?;
+    self.announce_change(connection);
+    Ok(updated)
+  }
+
+  /// The **pipeline upstream** of this service: the registered service whose result archive it
+  /// consumes, named by `inputconverter`. `None` when the service converts the document source —
+  /// no converter, or the magic `import`/`init` services (the historical default value).
+  pub fn upstream(&self) -> Option<&str> {
+    self
+      .inputconverter
+      .as_deref()
+      .map(str::trim)
+      .filter(|name| !name.is_empty() && *name != "import" && *name != "init" && *name != self.name)
+  }
+
+  /// The services directly downstream of this one (whose `inputconverter` names it), by name.
+  pub fn downstream(&self, connection: &mut PgConnection) -> Result<Vec<Service>, Error> {
+    services::table
+      .filter(services::inputconverter.eq(&self.name))
+      .filter(services::name.ne(&self.name))
+      .order(services::name.asc())
+      .get_results(connection)
+  }
+
+  /// Sets this service's pipeline invalidation policy (`rerun` or `keep`, see
+  /// [`Service::on_upstream_rerun`]). The column's CHECK constraint rejects any other value. Wakes
+  /// the dispatcher to re-read its cached row.
+  pub fn set_on_upstream_rerun(
+    &self,
+    policy: &str,
+    connection: &mut PgConnection,
+  ) -> Result<usize, Error> {
+    let updated = update(services::table.find(self.id))
+      .set(services::on_upstream_rerun.eq(policy))
+      .execute(connection)?;
+    self.announce_change(connection);
+    Ok(updated)
+  }
+
+  /// Tells a running dispatcher this service's row changed, so it re-reads its cached `Service`
+  /// instead of serving the old settings until a restart. Best-effort, like the run-control
+  /// wake-ups: the update itself has already happened.
+  fn announce_change(&self, connection: &mut PgConnection) {
+    wake_dispatcher(
+      connection,
+      ControlSignal {
+        kind: ControlKind::Service,
+        corpus_id: None,
+        service_id: Some(self.id),
+      },
+    );
   }
 
   /// Returns a hash representation of the `Service`, usually for frontend reports
@@ -121,6 +250,14 @@ impl Service {
       },
     );
     hm.insert("complex".to_string(), self.complex.to_string());
+    hm.insert(
+      "upstream".to_string(),
+      self.upstream().unwrap_or_default().to_string(),
+    );
+    hm.insert(
+      "on_upstream_rerun".to_string(),
+      self.on_upstream_rerun.clone(),
+    );
     // Empty string = no per-service override (the global dispatcher lease applies); the template
     // renders that as a "default" placeholder.
     hm.insert(
@@ -130,6 +267,18 @@ impl Service {
         .map(|seconds| seconds.to_string())
         .unwrap_or_default(),
     );
+    hm.insert(
+      "max_lease_renewals".to_string(),
+      self
+        .max_lease_renewals
+        .map(|renewals| renewals.to_string())
+        .unwrap_or_default(),
+    );
+    hm.insert(
+      "keep_result_versions".to_string(),
+      self.keep_result_versions.to_string(),
+    );
+    hm.insert("log_parser".to_string(), self.log_parser_key().to_string());
     hm
   }
 
@@ -176,16 +325,19 @@ impl Service {
   /// removed. The service *definition* and its work on other corpora are untouched. The `log_*`
   /// tables have no FK to `tasks`, so their rows are deleted explicitly **before** the tasks or
   /// they orphan (the same hazard closed in [`super::Corpus::destroy`]); transactional so a crash
-  /// can't half-delete. `historical_runs` tallies survive (no FK to tasks), while per-task
-  /// `historical_tasks` snapshots cascade away with the tasks — the same semantics as deleting a
-  /// corpus.
+  /// can't half-delete. The pair's `activation_weights` row goes with them, so a retired pair
+  /// neither shows on the status screen nor dilutes the service's remaining lease shares.
+  /// `historical_runs` tallies survive (no FK to tasks), while per-task `historical_tasks`
+  /// snapshots cascade away with the tasks — the same semantics as deleting a corpus.
   pub fn deactivate_from_corpus(
     &self,
     corpus: &super::Corpus,
     connection: &mut PgConnection,
   ) -> Result<usize, Error> {
     use crate::schema::tasks;
-    use crate::schema::{log_errors, log_fatals, log_infos, log_invalids, log_warnings};
+    use crate::schema::{
+      activation_weights, log_errors, log_fatals, log_infos, log_invalids, log_warnings,
+    };
     let service_id_val = self.id;
     let corpus_id_val = corpus.id;
     connection.transaction(|t_connection| {
@@ -205,6 +357,12 @@ impl Service {
         .execute(t_connection)?;
       delete(log_invalids::table.filter(log_invalids::task_id.eq_any(pair_task_ids())))
         .execute(t_connection)?;
+      delete(
+        activation_weights::table
+          .filter(activation_weights::service_id.eq(service_id_val))
+          .filter(activation_weights::corpus_id.eq(corpus_id_val)),
+      )
+      .execute(t_connection)?;
       delete(
         tasks::table
           .filter(tasks::service_id.eq(service_id_val))
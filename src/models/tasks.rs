@@ -2,7 +2,7 @@
 use diesel::result::Error;
 use diesel::*;
 
-use super::{Corpus, Service};
+use super::{Corpus, IMPORT_SERVICE_ID, Service};
 use crate::concerns::{CortexDeletable, CortexInsertable};
 use crate::helpers::TaskStatus;
 use crate::schema::tasks;
@@ -27,6 +27,12 @@ pub struct Task {
   pub status: i32,
   /// entry path on the file system
   pub entry: String,
+  /// leasing priority among this service's TODO tasks — higher is leased first, `0` is the
+  /// ordinary backlog (raised per scope by a prioritized rerun)
+  pub priority: i32,
+  /// the dispatcher instance holding the task's lease (`models::DispatcherInstance`); only
+  /// meaningful while the task is leased, `None` for a lease taken before instances existed
+  pub dispatcher_id: Option<i32>,
 }
 
 #[derive(Insertable, Debug, Clone)]
@@ -98,7 +104,7 @@ impl Task {
   pub fn count_todo(connection: &mut PgConnection) -> i64 {
     tasks::table
       .filter(tasks::status.eq(TaskStatus::TODO.raw()))
-      .filter(tasks::service_id.gt(2))
+      .filter(tasks::service_id.gt(IMPORT_SERVICE_ID))
       .count()
       .get_result(connection)
       .unwrap_or(0)
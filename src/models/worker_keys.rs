@@ -0,0 +1,135 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The **worker key allowlist** of the authenticated worker transport (`dispatcher.worker_auth`):
+//! the CURVE public keys whose connection handshake the ventilator accepts. Keys are Z85-encoded,
+//! as libzmq prints and parses them. A revoked key is kept for the record — its handshakes are
+//! refused from then on, and a worker still connected with it is no longer leased to.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::result::Error;
+
+use crate::schema::worker_keys;
+
+/// Length of a Z85-encoded CURVE key (32 bytes → 40 characters).
+pub const Z85_KEY_LENGTH: usize = 40;
+
+/// An allowlisted worker key.
+#[derive(Queryable, Identifiable, Clone, Debug)]
+#[diesel(table_name = worker_keys)]
+pub struct WorkerKey {
+  /// id of the key
+  pub id: i32,
+  /// the worker's CURVE public key, Z85-encoded
+  pub public_key: String,
+  /// free-form operator note (host, fleet, owner)
+  pub label: String,
+  /// when the key was allowlisted
+  pub created_at: NaiveDateTime,
+  /// who allowlisted the key
+  pub created_by: String,
+  /// when the key was revoked (`None` while active)
+  pub revoked_at: Option<NaiveDateTime>,
+  /// who revoked the key
+  pub revoked_by: Option<String>,
+  /// the latest accepted handshake with the key
+  pub last_seen_at: Option<NaiveDateTime>,
+}
+
+/// A key to allowlist.
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = worker_keys)]
+pub struct NewWorkerKey {
+  /// the worker's CURVE public key, Z85-encoded
+  pub public_key: String,
+  /// free-form operator note
+  pub label: String,
+  /// who allowlists the key
+  pub created_by: String,
+}
+
+impl WorkerKey {
+  /// Whether the key is still honored.
+  pub fn is_active(&self) -> bool { self.revoked_at.is_none() }
+
+  /// Normalizes an operator-supplied public key: surrounding whitespace is dropped, and the rest
+  /// must be a Z85 encoding of a 32-byte key. `None` for anything else.
+  pub fn parse_public_key(key: &str) -> Option<String> {
+    let key = key.trim();
+    if key.len() != Z85_KEY_LENGTH {
+      return None;
+    }
+    zmq::z85_decode(key)
+      .ok()
+      .filter(|bytes| bytes.len() == 32)
+      .map(|_| key.to_string())
+  }
+
+  /// Allowlists `key` on behalf of `actor`. The key must already be normalized (see
+  /// [`WorkerKey::parse_public_key`]); a key listed before — even a revoked one — is a unique
+  /// violation.
+  pub fn add(
+    connection: &mut PgConnection,
+    key: &str,
+    label: &str,
+    actor: &str,
+  ) -> Result<WorkerKey, Error> {
+    diesel::insert_into(worker_keys::table)
+      .values(NewWorkerKey {
+        public_key: key.to_string(),
+        label: label.to_string(),
+        created_by: actor.to_string(),
+      })
+      .get_result(connection)
+  }
+
+  /// Revokes key `id` on behalf of `actor`. `NotFound` unless the key exists and is still active.
+  pub fn revoke(connection: &mut PgConnection, id: i32, actor: &str) -> Result<WorkerKey, Error> {
+    diesel::update(
+      worker_keys::table
+        .find(id)
+        .filter(worker_keys::revoked_at.is_null()),
+    )
+    .set((
+      worker_keys::revoked_at.eq(Some(chrono::Utc::now().naive_utc())),
+      worker_keys::revoked_by.eq(Some(actor)),
+    ))
+    .get_result(connection)
+  }
+
+  /// Every key, active ones first, newest first within each group.
+  pub fn all(connection: &mut PgConnection) -> Result<Vec<WorkerKey>, Error> {
+    worker_keys::table
+      .order((
+        worker_keys::revoked_at.is_not_null(),
+        worker_keys::id.desc(),
+      ))
+      .load(connection)
+  }
+
+  /// The public keys currently honored.
+  pub fn active_keys(connection: &mut PgConnection) -> Result<Vec<String>, Error> {
+    worker_keys::table
+      .filter(worker_keys::revoked_at.is_null())
+      .select(worker_keys::public_key)
+      .load(connection)
+  }
+
+  /// Admits a connection handshake with `key`: true if the key is active, in which case its
+  /// `last_seen_at` is stamped.
+  pub fn admit(connection: &mut PgConnection, key: &str) -> Result<bool, Error> {
+    let admitted = diesel::update(
+      worker_keys::table
+        .filter(worker_keys::public_key.eq(key))
+        .filter(worker_keys::revoked_at.is_null()),
+    )
+    .set(worker_keys::last_seen_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(connection)?;
+    Ok(admitted > 0)
+  }
+}
@@ -7,7 +7,7 @@ use std::time::{Duration, SystemTime};
 use diesel::result::Error;
 use diesel::*;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::backend::DbPool;
 use crate::models::Service;
@@ -37,6 +37,8 @@ pub struct NewWorkerMetadata {
   pub time_last_return: Option<SystemTime>,
   /// identity of this worker, usually hostname:pid
   pub name: String,
+  /// lease renewals accepted from this worker
+  pub total_renewals: i32,
 }
 
 #[derive(Identifiable, Queryable, Associations, Clone, Debug, Serialize)]
@@ -66,6 +68,113 @@ pub struct WorkerMetadata {
   pub time_last_return: Option<SystemTime>,
   /// identity of this worker, usually hostname:pid
   pub name: String,
+  /// lease renewals accepted from this worker's "still working" heartbeats
+  pub total_renewals: i32,
+  /// the service version the worker declared at registration
+  pub declared_version: Option<f32>,
+  /// the host the worker declared at registration
+  pub host: Option<String>,
+  /// the git sha of the worker build, as declared at registration
+  pub git_sha: Option<String>,
+  /// the most tasks the worker takes at once, as declared at registration
+  pub concurrency: Option<i32>,
+  /// the largest input archive (bytes) the worker takes, as declared at registration
+  pub max_input_bytes: Option<i64>,
+  /// the input formats the worker accepts, as declared at registration (empty = any)
+  pub input_formats: Vec<String>,
+  /// when the worker last registered (`None` for a worker that never did)
+  pub registered_at: Option<SystemTime>,
+  /// why the dispatcher refuses to lease to this worker (`None` = it leases normally)
+  pub refusal: Option<String>,
+}
+
+/// The optional **registration frame** a worker may open with on the ventilator socket —
+/// `[identity, service, "register", <json>]` — declaring its build and what it can take. Every
+/// field is optional; an unregistered worker is leased to exactly as before the handshake existed.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct WorkerRegistration {
+  /// the service version the worker implements, compared with the registered `Service.version`
+  pub version: Option<f32>,
+  /// the worker's host name
+  pub host: Option<String>,
+  /// the git sha of the worker build
+  pub git_sha: Option<String>,
+  /// the most tasks the worker takes at once (its in-flight lease cap)
+  pub concurrency: Option<u32>,
+  /// the largest input archive, in bytes, the worker takes
+  pub max_input_bytes: Option<u64>,
+  /// the input formats the worker accepts (empty = any)
+  pub input_formats: Vec<String>,
+}
+
+/// Longest accepted `host` / `git_sha`, and most `input_formats` kept — the declaration is
+/// worker-supplied, so it is clipped to the columns it is mirrored into rather than trusted.
+const MAX_HOST_LEN: usize = 255;
+/// See [`MAX_HOST_LEN`].
+const MAX_GIT_SHA_LEN: usize = 64;
+/// See [`MAX_HOST_LEN`].
+const MAX_INPUT_FORMATS: usize = 16;
+
+impl WorkerRegistration {
+  /// Parses a registration frame's JSON payload, clipping the free-text fields to their column
+  /// widths and dropping non-positive limits (which would otherwise starve the worker).
+  pub fn parse(payload: &[u8]) -> Result<WorkerRegistration, serde_json::Error> {
+    let mut registration: WorkerRegistration = serde_json::from_slice(payload)?;
+    let clip = |value: Option<String>, max: usize| {
+      value
+        .map(|text| text.trim().chars().take(max).collect::<String>())
+        .filter(|text| !text.is_empty())
+    };
+    registration.host = clip(registration.host, MAX_HOST_LEN);
+    registration.git_sha = clip(registration.git_sha, MAX_GIT_SHA_LEN);
+    registration.concurrency = registration.concurrency.filter(|limit| *limit > 0);
+    registration.max_input_bytes = registration.max_input_bytes.filter(|limit| *limit > 0);
+    registration.input_formats.truncate(MAX_INPUT_FORMATS);
+    for format in &mut registration.input_formats {
+      *format = format
+        .trim()
+        .chars()
+        .take(50)
+        .collect::<String>()
+        .to_lowercase();
+    }
+    registration
+      .input_formats
+      .retain(|format| !format.is_empty());
+    Ok(registration)
+  }
+
+  /// Why the dispatcher must never lease `service` tasks to this worker, or `None` if it may: a
+  /// declared version other than the registered `Service.version`, or declared input formats that
+  /// leave out the service's `inputformat`.
+  pub fn refusal(&self, service: &Service) -> Option<String> {
+    let mismatched = |version: &f32| (version - service.version).abs() > f32::EPSILON;
+    if let Some(version) = self.version.filter(mismatched) {
+      return Some(format!(
+        "version mismatch: worker declares {version}, {} is registered at {}",
+        service.name, service.version
+      ));
+    }
+    let input = service.inputformat.to_lowercase();
+    if !self.input_formats.is_empty() && !self.input_formats.contains(&input) {
+      return Some(format!(
+        "input format mismatch: worker takes {}, {} needs {input}",
+        self.input_formats.join(", "),
+        service.name
+      ));
+    }
+    None
+  }
+
+  /// Whether a task whose input archive is `input_bytes` long fits the declared size limit. An
+  /// unknown size fits (the payload read reports the missing input as it always has).
+  pub fn accepts_input(&self, input_bytes: Option<u64>) -> bool {
+    match (self.max_input_bytes, input_bytes) {
+      (Some(limit), Some(size)) => size <= limit,
+      _ => true,
+    }
+  }
 }
 
 impl From<WorkerMetadata> for HashMap<String, String> {
@@ -132,6 +241,40 @@ impl From<WorkerMetadata> for HashMap<String, String> {
       }
       .to_string(),
     );
+    wh.insert(
+      "total_renewals".to_string(),
+      worker.total_renewals.to_string(),
+    );
+    // The registration declaration (all empty for a worker that never registered).
+    wh.insert(
+      "declared_version".to_string(),
+      worker
+        .declared_version
+        .map(|version| version.to_string())
+        .unwrap_or_default(),
+    );
+    wh.insert("host".to_string(), worker.host.unwrap_or_default());
+    wh.insert("git_sha".to_string(), worker.git_sha.unwrap_or_default());
+    wh.insert(
+      "concurrency".to_string(),
+      worker
+        .concurrency
+        .map(|limit| limit.to_string())
+        .unwrap_or_default(),
+    );
+    wh.insert(
+      "max_input_bytes".to_string(),
+      worker
+        .max_input_bytes
+        .map(|limit| limit.to_string())
+        .unwrap_or_default(),
+    );
+    wh.insert("input_formats".to_string(), worker.input_formats.join(", "));
+    wh.insert(
+      "registered_at".to_string(),
+      worker.registered_at.map(iso_utc_system).unwrap_or_default(),
+    );
+    wh.insert("refusal".to_string(), worker.refusal.unwrap_or_default());
     wh.insert("name".to_string(), worker.name);
     wh
   }
@@ -266,6 +409,24 @@ enum WorkerEvent {
     /// Service the worker is registered against.
     service_id: i32,
   },
+  /// `name` renewed the lease of a task it is still converting (a lease-renewal heartbeat).
+  Renewed {
+    /// Worker identity.
+    name: String,
+    /// Service the worker is registered against.
+    service_id: i32,
+  },
+  /// `name` sent a registration frame; `refusal` is why it is refused leases, if it is.
+  Registered {
+    /// Worker identity.
+    name: String,
+    /// Service the worker is registered against.
+    service_id: i32,
+    /// The declaration.
+    registration: WorkerRegistration,
+    /// Why the dispatcher refuses to lease to it, if it does.
+    refusal: Option<String>,
+  },
 }
 
 /// A cloneable, non-blocking handle the ventilator and sink use to enqueue worker-metadata events.
@@ -299,6 +460,27 @@ impl WorkerMetadataSender {
       .tx
       .try_send(WorkerEvent::Heartbeat { name, service_id });
   }
+  /// Enqueue an accepted lease renewal (non-blocking, best-effort). Counts toward the worker's
+  /// `total_renewals`.
+  pub fn renewed(&self, name: String, service_id: i32) {
+    let _ = self.tx.try_send(WorkerEvent::Renewed { name, service_id });
+  }
+  /// Enqueue a worker registration (non-blocking, best-effort). Mirrors the declaration — and the
+  /// dispatcher's verdict on it — onto the worker's row for the fleet screen.
+  pub fn registered(
+    &self,
+    name: String,
+    service_id: i32,
+    registration: WorkerRegistration,
+    refusal: Option<String>,
+  ) {
+    let _ = self.tx.try_send(WorkerEvent::Registered {
+      name,
+      service_id,
+      registration,
+      refusal,
+    });
+  }
 }
 
 /// Spawns the single background worker-metadata writer and returns a cloneable
@@ -330,6 +512,22 @@ pub fn start_metadata_writer(pool: DbPool) -> WorkerMetadataSender {
         WorkerEvent::Heartbeat { name, service_id } => {
           upsert_heartbeat(&mut pooled, &name, service_id, now)
         },
+        WorkerEvent::Renewed { name, service_id } => {
+          upsert_renewed(&mut pooled, &name, service_id, now)
+        },
+        WorkerEvent::Registered {
+          name,
+          service_id,
+          registration,
+          refusal,
+        } => upsert_registered(
+          &mut pooled,
+          &name,
+          service_id,
+          &registration,
+          refusal.as_deref(),
+          now,
+        ),
       };
       if let Err(error) = result {
         eprintln!("-- worker metadata writer: upsert failed: {error:?}");
@@ -360,6 +558,7 @@ fn upsert_dispatched(
       session_seen: Some(now),
       time_last_dispatch: now,
       time_last_return: None,
+      total_renewals: 0,
     })
     .on_conflict((worker_metadata::name, worker_metadata::service_id))
     .do_update()
@@ -394,6 +593,7 @@ fn upsert_heartbeat(
       session_seen: Some(now),
       time_last_dispatch: now,
       time_last_return: None,
+      total_renewals: 0,
     })
     .on_conflict((worker_metadata::name, worker_metadata::service_id))
     .do_update()
@@ -401,6 +601,90 @@ fn upsert_heartbeat(
     .execute(connection)
 }
 
+/// Inserts — or, on `(name, service_id)` conflict, increments — a worker's `total_renewals`. A
+/// renewal also proves the worker alive, so it refreshes the liveness timestamp like
+/// [`upsert_heartbeat`] (a worker deep in a slow conversion otherwise reads as stale).
+fn upsert_renewed(
+  connection: &mut PgConnection,
+  name: &str,
+  service_id: i32,
+  now: SystemTime,
+) -> QueryResult<usize> {
+  insert_into(worker_metadata::table)
+    .values(&NewWorkerMetadata {
+      name: name.to_string(),
+      service_id,
+      last_dispatched_task_id: 0,
+      last_returned_task_id: None,
+      total_dispatched: 0,
+      total_returned: 0,
+      first_seen: now,
+      session_seen: Some(now),
+      time_last_dispatch: now,
+      time_last_return: None,
+      total_renewals: 1,
+    })
+    .on_conflict((worker_metadata::name, worker_metadata::service_id))
+    .do_update()
+    .set((
+      worker_metadata::total_renewals.eq(worker_metadata::total_renewals + 1),
+      worker_metadata::time_last_dispatch.eq(now),
+    ))
+    .execute(connection)
+}
+
+/// Inserts — or, on `(name, service_id)` conflict, overwrites — a worker's registration
+/// declaration and the dispatcher's `refusal` verdict on it. A registration is contact too, so the
+/// insert branch seeds a zero-tally row like [`upsert_heartbeat`] and the update refreshes the
+/// liveness timestamp.
+fn upsert_registered(
+  connection: &mut PgConnection,
+  name: &str,
+  service_id: i32,
+  registration: &WorkerRegistration,
+  refusal: Option<&str>,
+  now: SystemTime,
+) -> QueryResult<usize> {
+  let concurrency = registration
+    .concurrency
+    .map(|limit| i32::try_from(limit).unwrap_or(i32::MAX));
+  let max_input_bytes = registration
+    .max_input_bytes
+    .map(|limit| i64::try_from(limit).unwrap_or(i64::MAX));
+  let refusal: Option<String> = refusal.map(|reason| reason.chars().take(200).collect());
+  let declaration = (
+    worker_metadata::declared_version.eq(registration.version),
+    worker_metadata::host.eq(registration.host.clone()),
+    worker_metadata::git_sha.eq(registration.git_sha.clone()),
+    worker_metadata::concurrency.eq(concurrency),
+    worker_metadata::max_input_bytes.eq(max_input_bytes),
+    worker_metadata::input_formats.eq(registration.input_formats.clone()),
+    worker_metadata::registered_at.eq(Some(now)),
+    worker_metadata::refusal.eq(refusal),
+  );
+  insert_into(worker_metadata::table)
+    .values((
+      &NewWorkerMetadata {
+        name: name.to_string(),
+        service_id,
+        last_dispatched_task_id: 0,
+        last_returned_task_id: None,
+        total_dispatched: 0,
+        total_returned: 0,
+        first_seen: now,
+        session_seen: Some(now),
+        time_last_dispatch: now,
+        time_last_return: None,
+        total_renewals: 0,
+      },
+      declaration.clone(),
+    ))
+    .on_conflict((worker_metadata::name, worker_metadata::service_id))
+    .do_update()
+    .set((worker_metadata::time_last_dispatch.eq(now), declaration))
+    .execute(connection)
+}
+
 /// Inserts — or, on conflict, increments — the return tallies for a worker. The insert branch
 /// covers the out-of-order case (a result recorded before the worker's first dispatch): it seeds a
 /// row whose dispatch fields are placeholders (`last_dispatched_task_id = 0`, `total_dispatched =
@@ -424,6 +708,7 @@ fn upsert_received(
       session_seen: Some(now),
       time_last_dispatch: now,
       time_last_return: Some(now),
+      total_renewals: 0,
     })
     .on_conflict((worker_metadata::name, worker_metadata::service_id))
     .do_update()
@@ -524,6 +809,24 @@ mod tests {
     clear(connection, worker);
   }
 
+  #[test]
+  fn renewals_are_counted_without_counting_a_dispatch() {
+    let worker = "wm-test:renewals:1";
+    let mut backend = backend::testdb();
+    let connection = &mut backend.connection;
+    clear(connection, worker);
+    let now = SystemTime::now();
+
+    upsert_dispatched(connection, worker, SERVICE_ID, 5, now).expect("dispatched");
+    upsert_renewed(connection, worker, SERVICE_ID, now).expect("renewed");
+    upsert_renewed(connection, worker, SERVICE_ID, now).expect("renewed");
+    let row = load(connection, worker);
+    assert_eq!(row.total_renewals, 2);
+    assert_eq!(row.total_dispatched, 1, "a renewal is not a dispatch");
+
+    clear(connection, worker);
+  }
+
   #[test]
   fn repeated_events_accumulate_in_one_row() {
     let worker = "wm-test:accumulate:1";
@@ -551,4 +854,96 @@ mod tests {
 
     clear(connection, worker);
   }
+
+  #[test]
+  fn registration_is_clipped_and_checked_against_the_service() {
+    let registration = WorkerRegistration::parse(
+      br#"{"version": 0.8, "host": "  node-7  ", "git_sha": "", "concurrency": 0,
+           "max_input_bytes": 1024, "input_formats": ["TeX", " "], "unknown": true}"#,
+    )
+    .expect("valid registration");
+    assert_eq!(registration.host.as_deref(), Some("node-7"));
+    assert_eq!(registration.git_sha, None, "blank is undeclared");
+    assert_eq!(registration.concurrency, None, "a zero cap is dropped");
+    assert_eq!(registration.input_formats, vec!["tex".to_string()]);
+    assert!(WorkerRegistration::parse(b"not json").is_err());
+
+    let mut service = Service {
+      id: SERVICE_ID,
+      name: "tex_to_html".to_string(),
+      version: 0.8,
+      inputformat: "tex".to_string(),
+      outputformat: "html".to_string(),
+      inputconverter: None,
+      complex: true,
+      description: String::new(),
+      public_id: uuid::Uuid::nil(),
+      lease_timeout_seconds: None,
+      on_upstream_rerun: "rerun".to_string(),
+      max_lease_renewals: None,
+      keep_result_versions: 0,
+      log_parser: None,
+    };
+    assert_eq!(registration.refusal(&service), None);
+    service.version = 0.9;
+    assert!(
+      registration
+        .refusal(&service)
+        .is_some_and(|reason| reason.starts_with("version mismatch"))
+    );
+    service.version = 0.8;
+    service.inputformat = "html".to_string();
+    assert!(
+      registration
+        .refusal(&service)
+        .is_some_and(|reason| reason.starts_with("input format mismatch"))
+    );
+    assert!(registration.accepts_input(Some(1024)));
+    assert!(!registration.accepts_input(Some(1025)));
+    assert!(registration.accepts_input(None), "an unknown size fits");
+  }
+
+  #[test]
+  fn registration_is_mirrored_onto_the_worker_row() {
+    let worker = "wm-test:registered:1";
+    let mut backend = backend::testdb();
+    let connection = &mut backend.connection;
+    clear(connection, worker);
+    let now = SystemTime::now();
+
+    upsert_dispatched(connection, worker, SERVICE_ID, 3, now).expect("dispatched");
+    let registration = WorkerRegistration {
+      version: Some(0.8),
+      host: Some("node-7".to_string()),
+      git_sha: Some("abc123".to_string()),
+      concurrency: Some(4),
+      max_input_bytes: Some(1 << 20),
+      input_formats: vec!["tex".to_string()],
+    };
+    upsert_registered(
+      connection,
+      worker,
+      SERVICE_ID,
+      &registration,
+      Some("version mismatch"),
+      now,
+    )
+    .expect("registered");
+    let row = load(connection, worker);
+    assert_eq!(row.total_dispatched, 1, "registering keeps the tallies");
+    assert_eq!(row.declared_version, Some(0.8));
+    assert_eq!(row.host.as_deref(), Some("node-7"));
+    assert_eq!(row.concurrency, Some(4));
+    assert_eq!(row.max_input_bytes, Some(1 << 20));
+    assert_eq!(row.input_formats, vec!["tex".to_string()]);
+    assert_eq!(row.refusal.as_deref(), Some("version mismatch"));
+    assert!(row.registered_at.is_some());
+
+    // Re-registering (e.g. after the operator fixed the version) clears the refusal.
+    upsert_registered(connection, worker, SERVICE_ID, &registration, None, now)
+      .expect("re-registered");
+    assert_eq!(load(connection, worker).refusal, None);
+
+    clear(connection, worker);
+  }
 }
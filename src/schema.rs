@@ -1,5 +1,49 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    /// Representation of the `activation_weights` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    activation_weights (corpus_id, service_id) {
+        /// The `corpus_id` column of the `activation_weights` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        corpus_id -> Int4,
+        /// The `service_id` column of the `activation_weights` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        service_id -> Int4,
+        /// The `weight` column of the `activation_weights` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        weight -> Int4,
+        /// The `leased_total` column of the `activation_weights` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        leased_total -> Int8,
+        /// The `leased_recent` column of the `activation_weights` table.
+        ///
+        /// Its SQL type is `Float8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        leased_recent -> Float8,
+        /// The `last_leased_at` column of the `activation_weights` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_leased_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `audit_log` table.
     ///
@@ -109,6 +153,253 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         public_id -> Uuid,
+        /// The `unpacker` column of the `corpora` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 32]
+        unpacker -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `dead_letters` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    dead_letters (id) {
+        /// The `id` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int8,
+        /// The `task_id` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        task_id -> Int8,
+        /// The `retries` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        retries -> Int4,
+        /// The `workers` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Array<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        workers -> Array<Text>,
+        /// The `leased_at` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Array<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        leased_at -> Array<Timestamp>,
+        /// The `dead_at` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        dead_at -> Timestamp,
+        /// The `state` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 16]
+        state -> Varchar,
+        /// The `resolved_at` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        resolved_at -> Nullable<Timestamp>,
+        /// The `resolved_by` column of the `dead_letters` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        resolved_by -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `dispatcher_instances` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    dispatcher_instances (id) {
+        /// The `id` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `host` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 255]
+        host -> Varchar,
+        /// The `pid` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        pid -> Int4,
+        /// The `source_port` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        source_port -> Int4,
+        /// The `result_port` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        result_port -> Int4,
+        /// The `started_at` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        started_at -> Timestamp,
+        /// The `heartbeat_at` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        heartbeat_at -> Timestamp,
+        /// The `stopped_at` column of the `dispatcher_instances` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        stopped_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `documents` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    documents (corpus_id, entry) {
+        /// The `corpus_id` column of the `documents` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        corpus_id -> Int4,
+        /// The `entry` column of the `documents` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 4096]
+        entry -> Varchar,
+        /// The `name` column of the `documents` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 255]
+        name -> Varchar,
+        /// The `yymm` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 4]
+        yymm -> Nullable<Varchar>,
+        /// The `source_bytes` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        source_bytes -> Nullable<Int8>,
+        /// The `file_count` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        file_count -> Nullable<Int4>,
+        /// The `main_tex` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 1024]
+        main_tex -> Nullable<Varchar>,
+        /// The `primary_category` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 64]
+        primary_category -> Nullable<Varchar>,
+        /// The `inspected_at` column of the `documents` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        inspected_at -> Timestamp,
+        /// The `content_hash` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 64]
+        content_hash -> Nullable<Varchar>,
+        /// The `source_modified` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        source_modified -> Nullable<Timestamp>,
+        /// The `vanished_at` column of the `documents` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        vanished_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `entry_metadata` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    entry_metadata (corpus_id, entry) {
+        /// The `corpus_id` column of the `entry_metadata` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        corpus_id -> Int4,
+        /// The `entry` column of the `entry_metadata` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 4096]
+        entry -> Varchar,
+        /// The `metadata` column of the `entry_metadata` table.
+        ///
+        /// Its SQL type is `Jsonb`.
+        ///
+        /// (Automatically generated by Diesel.)
+        metadata -> Jsonb,
+        /// The `imported_at` column of the `entry_metadata` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        imported_at -> Timestamp,
     }
 }
 
@@ -208,6 +499,18 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         public_id -> Uuid,
+        /// The `regressions` column of the `historical_runs` table.
+        ///
+        /// Its SQL type is `Nullable<Jsonb>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        regressions -> Nullable<Jsonb>,
+        /// The `regressions_pending` column of the `historical_runs` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        regressions_pending -> Bool,
     }
 }
 
@@ -370,6 +673,12 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         recorded_at -> Nullable<Timestamptz>,
+        /// The `fingerprint` column of the `log_errors` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fingerprint -> Nullable<Int8>,
     }
 }
 
@@ -417,6 +726,12 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         recorded_at -> Nullable<Timestamptz>,
+        /// The `fingerprint` column of the `log_fatals` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fingerprint -> Nullable<Int8>,
     }
 }
 
@@ -458,6 +773,12 @@ diesel::table! {
         /// (Automatically generated by Diesel.)
         #[max_length = 2000]
         details -> Nullable<Varchar>,
+        /// The `fingerprint` column of the `log_infos` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fingerprint -> Nullable<Int8>,
     }
 }
 
@@ -499,6 +820,12 @@ diesel::table! {
         /// (Automatically generated by Diesel.)
         #[max_length = 2000]
         details -> Nullable<Varchar>,
+        /// The `fingerprint` column of the `log_invalids` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fingerprint -> Nullable<Int8>,
     }
 }
 
@@ -546,47 +873,118 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         recorded_at -> Nullable<Timestamptz>,
+        /// The `fingerprint` column of the `log_warnings` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        fingerprint -> Nullable<Int8>,
     }
 }
 
 diesel::table! {
-    /// Representation of the `report_grain_cache` table.
+    /// Representation of the `poison_entries` table.
     ///
     /// (Automatically generated by Diesel.)
-    report_grain_cache (id) {
-        /// The `corpus_id` column of the `report_grain_cache` table.
+    poison_entries (entry) {
+        /// The `entry` column of the `poison_entries` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 4096]
+        entry -> Varchar,
+        /// The `incidents` column of the `poison_entries` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        corpus_id -> Int4,
-        /// The `service_id` column of the `report_grain_cache` table.
+        incidents -> Int4,
+        /// The `strikes` column of the `poison_entries` table.
         ///
         /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
-        service_id -> Int4,
-        /// The `severity` column of the `report_grain_cache` table.
+        strikes -> Int4,
+        /// The `last_incident_at` column of the `poison_entries` table.
         ///
-        /// Its SQL type is `Text`.
+        /// Its SQL type is `Timestamp`.
         ///
         /// (Automatically generated by Diesel.)
-        severity -> Text,
-        /// The `category` column of the `report_grain_cache` table.
+        last_incident_at -> Timestamp,
+        /// The `last_service_id` column of the `poison_entries` table.
         ///
-        /// Its SQL type is `Nullable<Varchar>`.
+        /// Its SQL type is `Nullable<Int4>`.
         ///
         /// (Automatically generated by Diesel.)
-        category -> Nullable<Varchar>,
-        /// The `what` column of the `report_grain_cache` table.
+        last_service_id -> Nullable<Int4>,
+        /// The `last_worker` column of the `poison_entries` table.
         ///
         /// Its SQL type is `Nullable<Varchar>`.
         ///
         /// (Automatically generated by Diesel.)
-        what -> Nullable<Varchar>,
-        /// The `category_is_total` column of the `report_grain_cache` table.
+        #[max_length = 200]
+        last_worker -> Nullable<Varchar>,
+        /// The `held_at` column of the `poison_entries` table.
         ///
-        /// Its SQL type is `Int4`.
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        held_at -> Nullable<Timestamp>,
+        /// The `released_at` column of the `poison_entries` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        released_at -> Nullable<Timestamp>,
+        /// The `released_by` column of the `poison_entries` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        released_by -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `report_grain_cache` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    report_grain_cache (id) {
+        /// The `corpus_id` column of the `report_grain_cache` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        corpus_id -> Int4,
+        /// The `service_id` column of the `report_grain_cache` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        service_id -> Int4,
+        /// The `severity` column of the `report_grain_cache` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        severity -> Text,
+        /// The `category` column of the `report_grain_cache` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        category -> Nullable<Varchar>,
+        /// The `what` column of the `report_grain_cache` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        what -> Nullable<Varchar>,
+        /// The `category_is_total` column of the `report_grain_cache` table.
+        ///
+        /// Its SQL type is `Int4`.
         ///
         /// (Automatically generated by Diesel.)
         category_is_total -> Int4,
@@ -643,6 +1041,189 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Representation of the `result_archive_versions` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    result_archive_versions (id) {
+        /// The `id` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int8,
+        /// The `task_id` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        task_id -> Int8,
+        /// The `run_id` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        run_id -> Nullable<Int4>,
+        /// The `path` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        path -> Varchar,
+        /// The `size_bytes` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        size_bytes -> Int8,
+        /// The `sha256` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 64]
+        sha256 -> Nullable<Varchar>,
+        /// The `written_at` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        written_at -> Timestamp,
+        /// The `saved_at` column of the `result_archive_versions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        saved_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `result_archives` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    result_archives (task_id) {
+        /// The `task_id` column of the `result_archives` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        task_id -> Int8,
+        /// The `path` column of the `result_archives` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        path -> Varchar,
+        /// The `size_bytes` column of the `result_archives` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        size_bytes -> Int8,
+        /// The `sha256` column of the `result_archives` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 64]
+        sha256 -> Varchar,
+        /// The `written_at` column of the `result_archives` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        written_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `run_message_categories` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    run_message_categories (run_id, severity, category) {
+        /// The `run_id` column of the `run_message_categories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        run_id -> Int4,
+        /// The `severity` column of the `run_message_categories` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 10]
+        severity -> Varchar,
+        /// The `category` column of the `run_message_categories` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 100]
+        category -> Varchar,
+        /// The `task_count` column of the `run_message_categories` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        task_count -> Int8,
+        /// The `message_count` column of the `run_message_categories` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        message_count -> Int8,
+    }
+}
+
+diesel::table! {
+    /// Representation of the `run_message_classes` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    run_message_classes (run_id, severity, category, what) {
+        /// The `run_id` column of the `run_message_classes` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        run_id -> Int4,
+        /// The `severity` column of the `run_message_classes` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 10]
+        severity -> Varchar,
+        /// The `category` column of the `run_message_classes` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 100]
+        category -> Varchar,
+        /// The `what` column of the `run_message_classes` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        what -> Varchar,
+        /// The `task_count` column of the `run_message_classes` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        task_count -> Int8,
+        /// The `message_count` column of the `run_message_classes` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        message_count -> Int8,
+    }
+}
+
 diesel::table! {
     /// Representation of the `services` table.
     ///
@@ -712,6 +1293,32 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         lease_timeout_seconds -> Nullable<Int4>,
+        /// The `on_upstream_rerun` column of the `services` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 16]
+        on_upstream_rerun -> Varchar,
+        /// The `max_lease_renewals` column of the `services` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        max_lease_renewals -> Nullable<Int4>,
+        /// The `keep_result_versions` column of the `services` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        keep_result_versions -> Int4,
+        /// The `log_parser` column of the `services` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 32]
+        log_parser -> Nullable<Varchar>,
     }
 }
 
@@ -818,6 +1425,18 @@ diesel::table! {
         /// (Automatically generated by Diesel.)
         #[max_length = 4096]
         entry -> Varchar,
+        /// The `priority` column of the `tasks` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        priority -> Int4,
+        /// The `dispatcher_id` column of the `tasks` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        dispatcher_id -> Nullable<Int4>,
     }
 }
 
@@ -894,6 +1513,66 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Representation of the `worker_keys` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    worker_keys (id) {
+        /// The `id` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `public_key` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 40]
+        public_key -> Varchar,
+        /// The `label` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        label -> Varchar,
+        /// The `created_at` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `created_by` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        created_by -> Varchar,
+        /// The `revoked_at` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        revoked_at -> Nullable<Timestamp>,
+        /// The `revoked_by` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        revoked_by -> Nullable<Varchar>,
+        /// The `last_seen_at` column of the `worker_keys` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_seen_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `worker_metadata` table.
     ///
@@ -966,23 +1645,94 @@ diesel::table! {
         /// (Automatically generated by Diesel.)
         #[max_length = 200]
         name -> Varchar,
+        /// The `total_renewals` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        total_renewals -> Int4,
+        /// The `declared_version` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Float4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        declared_version -> Nullable<Float4>,
+        /// The `host` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 255]
+        host -> Nullable<Varchar>,
+        /// The `git_sha` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 64]
+        git_sha -> Nullable<Varchar>,
+        /// The `concurrency` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        concurrency -> Nullable<Int4>,
+        /// The `max_input_bytes` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        max_input_bytes -> Nullable<Int8>,
+        /// The `input_formats` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Array<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        input_formats -> Array<Text>,
+        /// The `registered_at` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        registered_at -> Nullable<Timestamp>,
+        /// The `refusal` column of the `worker_metadata` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        #[max_length = 200]
+        refusal -> Nullable<Varchar>,
     }
 }
 
+diesel::joinable!(activation_weights -> corpora (corpus_id));
+diesel::joinable!(activation_weights -> services (service_id));
+diesel::joinable!(dead_letters -> tasks (task_id));
+diesel::joinable!(documents -> corpora (corpus_id));
+diesel::joinable!(entry_metadata -> corpora (corpus_id));
 diesel::joinable!(historical_tasks -> tasks (task_id));
 diesel::joinable!(log_errors -> tasks (task_id));
 diesel::joinable!(log_fatals -> tasks (task_id));
 diesel::joinable!(log_infos -> tasks (task_id));
 diesel::joinable!(log_invalids -> tasks (task_id));
 diesel::joinable!(log_warnings -> tasks (task_id));
+diesel::joinable!(poison_entries -> services (last_service_id));
+diesel::joinable!(result_archive_versions -> historical_runs (run_id));
+diesel::joinable!(run_message_categories -> historical_runs (run_id));
+diesel::joinable!(run_message_classes -> historical_runs (run_id));
 diesel::joinable!(task_runtimes -> tasks (task_id));
 diesel::joinable!(tasks -> corpora (corpus_id));
 diesel::joinable!(tasks -> services (service_id));
 diesel::joinable!(webauthn_credentials -> webauthn_users (owner));
 
 diesel::allow_tables_to_appear_in_same_query!(
+  activation_weights,
   audit_log,
   corpora,
+  dead_letters,
+  dispatcher_instances,
+  documents,
+  entry_metadata,
   historical_runs,
   historical_tasks,
   jobs,
@@ -991,13 +1741,19 @@ diesel::allow_tables_to_appear_in_same_query!(
   log_infos,
   log_invalids,
   log_warnings,
+  poison_entries,
   report_grain_cache,
   report_summary_meta,
+  result_archive_versions,
+  result_archives,
+  run_message_categories,
+  run_message_classes,
   services,
   sessions,
   task_runtimes,
   tasks,
   webauthn_credentials,
   webauthn_users,
+  worker_keys,
   worker_metadata,
 );
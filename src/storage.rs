@@ -0,0 +1,118 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! **Storage backends** for task inputs and result archives.
+//!
+//! A task's input (its `entry`, or an upstream service's result archive) and its result archive
+//! are named by the paths CorTeX has always recorded — `entry` in the task store, and
+//! [`result_archive_path`](crate::helpers::result_archive_path) next to it. Every read and write
+//! of those files goes through a [`Storage`], so the dispatchers and frontends need not share one
+//! POSIX mount:
+//!
+//! | backend | `storage.backend` | where the bytes live |
+//! | --- | --- | --- |
+//! | [`LocalStorage`] | `local` (default) | the paths themselves, on a (shared) filesystem |
+//! | [`S3Storage`] | `s3` | objects of an S3-compatible bucket (AWS S3, MinIO, Ceph RGW, …) |
+//! | [`MemoryStorage`] | — | an in-process map, the stand-in for tests |
+//!
+//! The ventilator streams inputs, the sink writes results, and the dataset export, telemetry
+//! aggregation and the frontend's download/preview read results, all through the process-wide
+//! [`storage()`]. The importer still walks a local corpus path: with an object store, the imported
+//! entries must be uploaded under the same paths (keys) before dispatch.
+
+pub mod local;
+pub mod memory;
+pub mod s3;
+
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+use crate::config::{StorageConfig, config};
+
+pub use local::LocalStorage;
+pub use memory::MemoryStorage;
+pub use s3::S3Storage;
+
+/// A readable, seekable object — what a `zip` reader needs to random-access a result archive.
+pub trait ReadSeek: Read + Seek + Send {}
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// An object being written. The bytes only become the object at [`ObjectWriter::commit`]; dropping
+/// an uncommitted writer discards them (and, for the local filesystem, the partial file).
+pub trait ObjectWriter: Write + Send {
+  /// Finishes the write: flushes the file, or uploads the object.
+  fn commit(self: Box<Self>) -> io::Result<()>;
+}
+
+/// An object found by [`Storage::list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredObject {
+  /// its path
+  pub path: PathBuf,
+  /// its size, in bytes
+  pub size: u64,
+}
+
+/// Where task inputs and result archives are read from and written to.
+pub trait Storage: Send + Sync + std::fmt::Debug {
+  /// The backend's `storage.backend` name, for logs and diagnostics.
+  fn kind(&self) -> &'static str;
+  /// Opens the object at `path` for reading. A missing object is an `io::ErrorKind::NotFound`.
+  fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>>;
+  /// Starts writing the object at `path`, replacing any existing one once committed.
+  fn create(&self, path: &Path) -> io::Result<Box<dyn ObjectWriter>>;
+  /// The size of the object at `path`, in bytes.
+  fn size(&self, path: &Path) -> io::Result<u64>;
+  /// Removes the object at `path`; removing a missing object is not an error.
+  fn remove(&self, path: &Path) -> io::Result<()>;
+  /// The objects directly in directory `dir` (not below its subdirectories), in path order. A
+  /// missing directory has no objects.
+  fn list(&self, dir: &Path) -> io::Result<Vec<StoredObject>>;
+  /// Moves the object at `from` to `to`, replacing any object there. By default a copy and a
+  /// remove; a backend that can move in place overrides it.
+  fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+    let mut writer = self.create(to)?;
+    io::copy(&mut self.open(from)?, &mut writer)?;
+    writer.commit()?;
+    self.remove(from)
+  }
+  /// Whether an object exists at `path`.
+  fn exists(&self, path: &Path) -> bool { self.size(path).is_ok() }
+  /// The local file holding the object at `path`, when the backend keeps one — so a caller can
+  /// stream it from disk (the frontend's downloads) or warm it into page cache (the prefetcher).
+  fn local_path(&self, _path: &Path) -> Option<PathBuf> { None }
+}
+
+/// Builds the storage backend `storage` configures.
+pub fn from_config(storage: &StorageConfig) -> Result<Arc<dyn Storage>, String> {
+  match storage.backend.as_str() {
+    "local" => Ok(Arc::new(LocalStorage)),
+    "s3" => Ok(Arc::new(S3Storage::from_config(storage)?)),
+    other => Err(format!(
+      "unknown storage backend {other:?} (use \"local\" or \"s3\")"
+    )),
+  }
+}
+
+/// Returns the process-wide storage backend, built from [`config()`] on first use. Panics with a
+/// clear message if the `[storage]` configuration is unusable, like the configuration itself.
+pub fn storage() -> Arc<dyn Storage> {
+  static STORAGE: LazyLock<Arc<dyn Storage>> = LazyLock::new(|| {
+    from_config(&config().storage)
+      .unwrap_or_else(|e| panic!("invalid CorTeX storage configuration: {e}"))
+  });
+  STORAGE.clone()
+}
+
+/// Reads the whole object at `path` — for the small objects (a `cortex.log` member aside) that a
+/// caller needs in memory anyway.
+pub fn read_all(storage: &dyn Storage, path: &Path) -> io::Result<Vec<u8>> {
+  let mut bytes = Vec::new();
+  storage.open(path)?.read_to_end(&mut bytes)?;
+  Ok(bytes)
+}
@@ -0,0 +1,145 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The local-filesystem storage backend: every path is a file on a (shared) POSIX mount — CorTeX's
+//! historical, and default, layout.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::{ObjectWriter, ReadSeek, Storage, StoredObject};
+
+/// Task inputs and result archives as plain files at their paths.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalStorage;
+
+/// A result file being written in place; removed again if dropped uncommitted.
+struct LocalWriter {
+  path: PathBuf,
+  file: File,
+  committed: bool,
+}
+
+impl Write for LocalWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.file.write(buf) }
+  fn flush(&mut self) -> io::Result<()> { self.file.flush() }
+}
+
+impl ObjectWriter for LocalWriter {
+  fn commit(mut self: Box<Self>) -> io::Result<()> {
+    self.file.flush()?;
+    self.committed = true;
+    Ok(())
+  }
+}
+
+impl Drop for LocalWriter {
+  fn drop(&mut self) {
+    if !self.committed {
+      // An abandoned (failed or rejected) write leaves no partial archive behind.
+      fs::remove_file(&self.path).ok();
+    }
+  }
+}
+
+impl Storage for LocalStorage {
+  fn kind(&self) -> &'static str { "local" }
+
+  fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> { Ok(Box::new(File::open(path)?)) }
+
+  fn create(&self, path: &Path) -> io::Result<Box<dyn ObjectWriter>> {
+    Ok(Box::new(LocalWriter {
+      path: path.to_path_buf(),
+      file: File::create(path)?,
+      committed: false,
+    }))
+  }
+
+  fn size(&self, path: &Path) -> io::Result<u64> { Ok(fs::metadata(path)?.len()) }
+
+  fn remove(&self, path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+      other => other,
+    }
+  }
+
+  fn list(&self, dir: &Path) -> io::Result<Vec<StoredObject>> {
+    let entries = match fs::read_dir(dir) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(e),
+    };
+    let mut objects = Vec::new();
+    for entry in entries {
+      let entry = entry?;
+      let metadata = entry.metadata()?;
+      if metadata.is_file() {
+        objects.push(StoredObject {
+          path: entry.path(),
+          size: metadata.len(),
+        });
+      }
+    }
+    objects.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(objects)
+  }
+
+  fn rename(&self, from: &Path, to: &Path) -> io::Result<()> { fs::rename(from, to) }
+
+  fn exists(&self, path: &Path) -> bool { path.exists() }
+
+  fn local_path(&self, path: &Path) -> Option<PathBuf> { Some(path.to_path_buf()) }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Read;
+
+  #[test]
+  fn only_committed_writes_are_kept() {
+    let dir = std::env::temp_dir().join(format!("cortex_local_storage_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let kept = dir.join("kept.zip");
+    let dropped = dir.join("dropped.zip");
+    let storage = LocalStorage;
+
+    let mut writer = storage.create(&kept).unwrap();
+    writer.write_all(b"result").unwrap();
+    writer.commit().unwrap();
+    let mut writer = storage.create(&dropped).unwrap();
+    writer.write_all(b"partial").unwrap();
+    drop(writer);
+
+    let mut read = String::new();
+    storage
+      .open(&kept)
+      .unwrap()
+      .read_to_string(&mut read)
+      .unwrap();
+    assert_eq!((read.as_str(), storage.size(&kept).unwrap()), ("result", 6));
+    assert!(
+      !storage.exists(&dropped),
+      "an uncommitted write is discarded"
+    );
+    assert_eq!(
+      storage.list(&dir).unwrap(),
+      vec![StoredObject {
+        path: kept.clone(),
+        size: 6
+      }]
+    );
+    assert!(storage.list(&dir.join("missing")).unwrap().is_empty());
+    storage.remove(&kept).unwrap();
+    storage
+      .remove(&kept)
+      .expect("removing a missing file is fine");
+    fs::remove_dir_all(&dir).ok();
+  }
+}
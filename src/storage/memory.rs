@@ -0,0 +1,119 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An in-process storage backend: objects in a map. The stand-in for tests of code that reads or
+//! writes through a [`Storage`] — nothing touches the disk.
+
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{ObjectWriter, ReadSeek, Storage, StoredObject};
+
+/// Objects held in memory, keyed by path. Clones share the same objects.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+  objects: Arc<Mutex<BTreeMap<PathBuf, Arc<Vec<u8>>>>>,
+}
+
+impl MemoryStorage {
+  /// Stores `bytes` at `path` directly (a test fixture).
+  pub fn insert(&self, path: impl Into<PathBuf>, bytes: Vec<u8>) {
+    self.lock().insert(path.into(), Arc::new(bytes));
+  }
+
+  /// The paths of every stored object, in order.
+  pub fn paths(&self) -> Vec<PathBuf> { self.lock().keys().cloned().collect() }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Arc<Vec<u8>>>> {
+    // A test that panicked mid-write poisons nothing worth protecting.
+    self
+      .objects
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+/// An object being written; stored at commit.
+struct MemoryWriter {
+  storage: MemoryStorage,
+  path: PathBuf,
+  buffer: Vec<u8>,
+}
+
+impl Write for MemoryWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.buffer.write(buf) }
+  fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl ObjectWriter for MemoryWriter {
+  fn commit(self: Box<Self>) -> io::Result<()> {
+    let MemoryWriter {
+      storage,
+      path,
+      buffer,
+    } = *self;
+    storage.insert(path, buffer);
+    Ok(())
+  }
+}
+
+/// A reader over a shared object.
+struct SharedBytes(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for SharedBytes {
+  fn as_ref(&self) -> &[u8] { &self.0 }
+}
+
+impl Storage for MemoryStorage {
+  fn kind(&self) -> &'static str { "memory" }
+
+  fn open(&self, path: &Path) -> io::Result<Box<dyn ReadSeek>> {
+    let bytes = self
+      .lock()
+      .get(path)
+      .cloned()
+      .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no object at {path:?}")))?;
+    Ok(Box::new(Cursor::new(SharedBytes(bytes))))
+  }
+
+  fn create(&self, path: &Path) -> io::Result<Box<dyn ObjectWriter>> {
+    Ok(Box::new(MemoryWriter {
+      storage: self.clone(),
+      path: path.to_path_buf(),
+      buffer: Vec::new(),
+    }))
+  }
+
+  fn size(&self, path: &Path) -> io::Result<u64> {
+    self
+      .lock()
+      .get(path)
+      .map(|bytes| bytes.len() as u64)
+      .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no object at {path:?}")))
+  }
+
+  fn remove(&self, path: &Path) -> io::Result<()> {
+    self.lock().remove(path);
+    Ok(())
+  }
+
+  fn list(&self, dir: &Path) -> io::Result<Vec<StoredObject>> {
+    Ok(
+      self
+        .lock()
+        .iter()
+        .filter(|(path, _)| path.parent() == Some(dir))
+        .map(|(path, bytes)| StoredObject {
+          path: path.clone(),
+          size: bytes.len() as u64,
+        })
+        .collect(),
+    )
+  }
+}
@@ -26,6 +26,7 @@ use std::thread::available_parallelism;
 use serde::{Deserialize, Serialize};
 
 use crate::helpers::result_archive_path;
+use crate::storage::Storage;
 
 /// The 17 conversion phases the worker times, in emission order — the index into a
 /// [`TelemetryRecord::phase_us`] entry and the label of a per-phase P99 row. Kept in lock-step with
@@ -113,9 +114,15 @@ pub struct TelemetryRecord {
 /// seeks straight to `telemetry.json` via the central directory, never decompressing the
 /// (potentially large) converted output. Returns the decoded record, or an `Err` describing why it
 /// couldn't (a non-zip / corrupt archive, a missing `telemetry.json`, or invalid JSON) — the caller
-/// counts that as a skipped sample rather than failing the whole aggregation.
-pub fn read_telemetry_json(result: &Path) -> Result<TelemetryRecord, String> {
-  let file = std::fs::File::open(result).map_err(|e| format!("cannot open result archive: {e}"))?;
+/// counts that as a skipped sample rather than failing the whole aggregation. The archive is read
+/// through `storage`.
+pub fn read_telemetry_json(
+  result: &Path,
+  storage: &dyn Storage,
+) -> Result<TelemetryRecord, String> {
+  let file = storage
+    .open(result)
+    .map_err(|e| format!("cannot open result archive: {e}"))?;
   let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("not a readable zip: {e}"))?;
   let mut entry = archive
     .by_name("telemetry.json")
@@ -514,12 +521,13 @@ fn read_chunk(
   chunk: &[String],
   service_name: &str,
   sandbox_id: Option<i32>,
+  storage: &dyn Storage,
 ) -> (Vec<TelemetryRecord>, usize) {
   let mut records = Vec::new();
   let mut skipped = 0usize;
   for entry in chunk {
     match result_archive_path(entry, service_name, sandbox_id) {
-      Some(path) => match read_telemetry_json(&path) {
+      Some(path) => match read_telemetry_json(&path, storage) {
         Ok(mut record) => {
           // The worker stamps `paper_id` with the numeric cortex task id, but the dashboard's
           // witness links target `/document/<corpus>/<service>/<name>`, which resolves by the
@@ -541,12 +549,13 @@ fn read_chunk(
 /// available_parallelism` capped at 16, the entries chunked evenly across threads), skipping any
 /// archive that is missing / unreadable / lacks a telemetry record, then [`summarize`]s.
 /// `sandbox_id` is threaded through [`result_archive_path`] so a **sandbox** corpus reads its own
-/// name-scoped archives, not the parent's.
+/// name-scoped archives, not the parent's. The archives are read through `storage`.
 pub fn aggregate(
   corpus_name: &str,
   service_name: &str,
   sandbox_id: Option<i32>,
   entries: Vec<String>,
+  storage: &dyn Storage,
 ) -> TelemetrySummary {
   let total = entries.len();
   if total == 0 {
@@ -570,7 +579,7 @@ pub fn aggregate(
       .map(|chunk| {
         (
           chunk.len(),
-          scope.spawn(move || read_chunk(chunk, service_name, sandbox_id)),
+          scope.spawn(move || read_chunk(chunk, service_name, sandbox_id, storage)),
         )
       })
       .collect();
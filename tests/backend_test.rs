@@ -5,13 +5,18 @@
 // This file may not be copied, modified, or distributed
 // except according to those terms.
 use cortex::backend;
-use cortex::backend::RerunOptions;
+use cortex::backend::{DocumentFacet, DocumentFilter, RerunOptions};
 use cortex::concerns::CortexInsertable;
 use cortex::helpers::{NewTaskMessage, TaskReport, TaskStatus, rand_in_range, random_mark};
-use cortex::models::{Corpus, NewCorpus, NewLogInfo, NewService, NewTask, Service, Task};
+use cortex::importer::Importer;
+use cortex::models::{
+  ActivationWeight, Corpus, DeadLetter, DeadLetterResolution, DispatcherInstance, Document,
+  EntryMetadata, LeaseIncident, NewCorpus, NewDeadLetter, NewDocument, NewEntryMetadata,
+  NewLogFatal, NewLogInfo, NewService, NewTask, PoisonEntry, Service, Task,
+};
 use cortex::schema::log_infos::dsl::task_id;
 use cortex::schema::tasks::dsl::{service_id, status};
-use cortex::schema::{log_infos, tasks};
+use cortex::schema::{dispatcher_instances, log_fatals, log_infos, tasks};
 use diesel::prelude::*;
 
 /// Seeds a real corpus + service so the Arm 3 `tasks -> corpora/services` foreign keys resolve, and
@@ -46,6 +51,13 @@ fn seed_corpus_service(connection: &mut PgConnection) -> (Corpus, Service) {
   (corpus, service)
 }
 
+/// Registers a dispatcher instance for a test to tag its leases with (every lease is tagged).
+fn lease_holder(connection: &mut PgConnection) -> i32 {
+  DispatcherInstance::register(connection, 51695, 51696)
+    .expect("register a dispatcher instance")
+    .id
+}
+
 #[test]
 fn task_table_crud() {
   let mut backend = backend::testdb();
@@ -66,13 +78,20 @@ fn task_table_crud() {
   assert_eq!(count_added, Ok(1)); // new task!
 
   // We should be able to fetch it back
-  let fetched_tasks_result = backend.fetch_tasks(&mock_service, 2);
+  let holder = lease_holder(&mut backend.connection);
+  let fetched_tasks_result = backend.fetch_tasks(&mock_service, 2, holder);
   assert!(fetched_tasks_result.is_ok());
   let fetched_tasks = fetched_tasks_result.unwrap();
   assert_eq!(fetched_tasks.len(), 1);
   let fetched_task = fetched_tasks.first().unwrap();
   assert!(fetched_task.id > 0);
   assert_eq!(fetched_task.entry, mock_task.entry);
+  assert_eq!(
+    fetched_task.dispatcher_id,
+    Some(holder),
+    "every lease is tagged"
+  );
+  DispatcherInstance::stop(&mut backend.connection, holder).expect("stop the instance");
 
   // Delete again and verify deletion works
   let cleanup = backend.delete_by(&mock_task, "entry");
@@ -103,7 +122,8 @@ fn task_lifecycle_test() {
     assert!(backend.add(&indexed_task).is_ok());
   }
   // fetch 17 tasks for work
-  let fetched_tasks_result = backend.fetch_tasks(&mock_service, 17);
+  let holder = lease_holder(&mut backend.connection);
+  let fetched_tasks_result = backend.fetch_tasks(&mock_service, 17, holder);
   assert!(fetched_tasks_result.is_ok());
   let fetched_tasks = fetched_tasks_result.unwrap();
   assert_eq!(fetched_tasks.len(), 17);
@@ -128,6 +148,7 @@ fn task_lifecycle_test() {
     .count()
     .get_result(&mut backend.connection);
   assert_eq!(marked_in_db_2, Ok(0));
+  DispatcherInstance::stop(&mut backend.connection, holder).expect("stop the instance");
 
   let post_cleanup = backend.delete_by(&mock_task, "service_id");
   assert_eq!(post_cleanup, Ok(100));
@@ -178,8 +199,11 @@ fn batch_ops_test() {
         category: String::from("trivial"),
         what: String::from("mock"),
         details: String::new(),
+        fingerprint: 0,
       })],
       task,
+      archive: None,
+      previous_archive: None,
     })
     .collect();
 
@@ -214,6 +238,8 @@ fn batch_ops_test() {
       status: TaskStatus::NoProblem,
       messages: vec![],
       task,
+      archive: None,
+      previous_archive: None,
     })
     .collect();
   assert!(backend.mark_done(&empty_reports).is_ok());
@@ -243,6 +269,8 @@ fn batch_ops_test() {
     what_opt: None,
     owner_opt: None,
     description_opt: None,
+    priority_opt: None,
+    text_opt: None,
   });
   println!("debug : {mark_rerun_result:?}");
   assert!(mark_rerun_result.is_ok());
@@ -313,15 +341,19 @@ fn mark_done_routes_messages_to_severity_tables() {
           category: String::from("cat"),
           what: String::from("what"),
           details: String::new(),
+          fingerprint: 0,
         }),
         NewTaskMessage::Error(NewLogError {
           task_id: id_a,
           category: String::from("cat"),
           what: String::from("what"),
           details: String::new(),
+          fingerprint: 0,
         }),
       ],
       task: tasks[0].clone(),
+      archive: None,
+      previous_archive: None,
     },
     TaskReport {
       status: TaskStatus::Fatal,
@@ -330,8 +362,11 @@ fn mark_done_routes_messages_to_severity_tables() {
         category: String::from("cat"),
         what: String::from("what"),
         details: String::new(),
+        fingerprint: 0,
       })],
       task: tasks[1].clone(),
+      archive: None,
+      previous_archive: None,
     },
   ];
   backend.mark_done(&reports).expect("mark_done");
@@ -406,7 +441,7 @@ fn clear_limbo_except_preserves_in_flight_tasks() {
     status: TaskStatus::TODO.raw(),
   };
   let _ = backend.delete_by(&mock_task, "service_id");
-  // Three tasks all marked Queued (a positive lease mark, > TODO).
+  // Three tasks all marked Queued (a positive lease mark, > TODO) by this dispatcher instance.
   let queued_mark: i32 = 4321;
   for index in 1..=3 {
     let indexed = NewTask {
@@ -416,14 +451,30 @@ fn clear_limbo_except_preserves_in_flight_tasks() {
     };
     backend.add(&indexed).expect("add queued task");
   }
+  let instance = DispatcherInstance::register(&mut backend.connection, 51695, 51696)
+    .expect("register a dispatcher instance");
+  diesel::update(tasks::table.filter(service_id.eq(mock_service_id)))
+    .set(tasks::dispatcher_id.eq(instance.id))
+    .execute(&mut backend.connection)
+    .expect("tag the leases");
   let queued: Vec<Task> = tasks::table
     .filter(service_id.eq(mock_service_id))
+    .order(tasks::id.asc())
     .get_results(&mut backend.connection)
     .expect("fetch queued tasks");
   assert_eq!(queued.len(), 3);
   let in_flight = vec![queued[0].id]; // pretend the first is in-flight (in progress_queue)
+  // An untagged lease, as a dispatcher predating instance tags takes, may belong to one still
+  // running: it is not recovered unless `dispatcher.recover_untagged_leases` is set.
+  backend
+    .add(&NewTask {
+      entry: String::from("limbo_except_untagged"),
+      status: queued_mark,
+      ..mock_task.clone()
+    })
+    .expect("add untagged queued task");
   backend
-    .clear_limbo_tasks_except(&in_flight)
+    .clear_limbo_tasks_except(instance.id, &in_flight)
     .expect("clear limbo except in-flight");
 
   // The in-flight task is preserved (still Queued); the other two reset to TODO.
@@ -446,6 +497,1461 @@ fn clear_limbo_except_preserves_in_flight_tasks() {
     todo_count, 2,
     "the other two Queued tasks were recovered to TODO"
   );
+  let untagged_status: i32 = tasks::table
+    .filter(tasks::entry.eq("limbo_except_untagged"))
+    .filter(service_id.eq(mock_service_id))
+    .select(status)
+    .first(&mut backend.connection)
+    .unwrap();
+  assert_eq!(
+    untagged_status, queued_mark,
+    "the untagged lease is left alone"
+  );
 
   let _ = backend.delete_by(&mock_task, "service_id");
+  DispatcherInstance::stop(&mut backend.connection, instance.id).expect("stop the instance");
+}
+
+#[test]
+fn limbo_recovery_spares_live_peer_dispatchers() {
+  // Three dispatchers share the task store: `own` is (re)starting, `peer` is alive, `dead` went
+  // silent an hour ago. Each holds one lease, tagged with its instance.
+  let mut backend = backend::testdb();
+  let (mock_corpus, mock_service) = seed_corpus_service(&mut backend.connection);
+  let mut register = || {
+    DispatcherInstance::register(&mut backend.connection, 51695, 51696)
+      .expect("register a dispatcher instance")
+  };
+  let (own, peer, dead) = (register(), register(), register());
+  for index in 1..=3 {
+    backend
+      .add(&NewTask {
+        entry: format!("peer_limbo_task{index}"),
+        service_id: mock_service.id,
+        corpus_id: mock_corpus.id,
+        status: TaskStatus::TODO.raw(),
+      })
+      .expect("add TODO task");
+  }
+  let mut leased = Vec::new();
+  for instance in [&own, &peer, &dead] {
+    let batch = backend
+      .fetch_tasks(&mock_service, 1, instance.id)
+      .expect("lease one task");
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].dispatcher_id, Some(instance.id), "tagged lease");
+    leased.push(batch[0].id);
+  }
+  diesel::update(dispatcher_instances::table.find(dead.id))
+    .set(
+      dispatcher_instances::heartbeat_at.eq(diesel::dsl::sql::<diesel::sql_types::Timestamp>(
+        "now() - interval '1 hour'",
+      )),
+    )
+    .execute(&mut backend.connection)
+    .expect("silence the dead instance");
+  let status_of = |connection: &mut PgConnection, id: i64| -> i32 {
+    tasks::table
+      .find(id)
+      .select(status)
+      .first(connection)
+      .unwrap()
+  };
+
+  backend
+    .clear_limbo_tasks_except(own.id, &[])
+    .expect("recover limbo tasks at start");
+  assert_eq!(
+    status_of(&mut backend.connection, leased[0]),
+    TaskStatus::TODO.raw(),
+    "the restarting dispatcher recovers its own lease"
+  );
+  assert!(
+    status_of(&mut backend.connection, leased[1]) > TaskStatus::TODO.raw(),
+    "a live peer's lease is left alone"
+  );
+  assert_eq!(
+    status_of(&mut backend.connection, leased[2]),
+    TaskStatus::TODO.raw(),
+    "a dead instance's lease is recovered"
+  );
+
+  // The periodic sweep marks the silent instance stopped; its heartbeat, if it ever returns, is
+  // told it was presumed dead.
+  DispatcherInstance::recover_dead(&mut backend.connection, 60).expect("recover dead instances");
+  assert_eq!(
+    DispatcherInstance::heartbeat(&mut backend.connection, peer.id),
+    Ok(false)
+  );
+  assert_eq!(
+    DispatcherInstance::heartbeat(&mut backend.connection, dead.id),
+    Ok(true),
+    "a revived instance learns its leases were recovered"
+  );
+
+  // A clean stop hands the peer's remaining lease back right away.
+  assert_eq!(
+    DispatcherInstance::stop(&mut backend.connection, peer.id),
+    Ok(1)
+  );
+  assert_eq!(
+    status_of(&mut backend.connection, leased[1]),
+    TaskStatus::TODO.raw()
+  );
+  for instance in [&own, &dead] {
+    DispatcherInstance::stop(&mut backend.connection, instance.id).expect("stop the instance");
+  }
+  let _ = backend.delete_by(
+    &NewTask {
+      entry: String::new(),
+      service_id: mock_service.id,
+      corpus_id: mock_corpus.id,
+      status: TaskStatus::TODO.raw(),
+    },
+    "service_id",
+  );
+}
+
+#[test]
+fn control_signals_round_trip_over_listen_notify() {
+  use cortex::backend::{ControlKind, ControlSignal};
+  // A listener on its own session, as the ventilator holds one; the notifier is a second session.
+  let mut listener = backend::testdb();
+  backend::listen_control(&mut listener.connection).expect("LISTEN on the control channel");
+  let mut notifier = backend::testdb();
+  let pause = ControlSignal::scoped(ControlKind::Pause, 3, 7);
+  backend::notify_control(&mut notifier.connection, &pause).expect("NOTIFY a pause");
+  let setting = ControlSignal {
+    kind: ControlKind::Service,
+    corpus_id: None,
+    service_id: Some(7),
+  };
+  backend::notify_control(&mut notifier.connection, &setting).expect("NOTIFY a setting change");
+
+  // Delivery is asynchronous; poll (non-blocking) briefly until both signals arrive.
+  let mut received = Vec::new();
+  for _ in 0..50 {
+    received.extend(backend::poll_control(&mut listener.connection).expect("poll the listener"));
+    if received.len() >= 2 {
+      break;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(20));
+  }
+  assert_eq!(received, vec![pause, setting]);
+  // Drained: a second poll returns nothing new.
+  assert!(
+    backend::poll_control(&mut listener.connection)
+      .unwrap()
+      .is_empty()
+  );
+}
+
+#[test]
+fn fetch_tasks_leases_highest_priority_first() {
+  // A prioritized rerun's tasks jump the service's ordinary (priority 0) backlog: `fetch_tasks`
+  // claims in `priority DESC, id` order and hands the batch back in that order.
+  let mut backend = backend::testdb();
+  let (mock_corpus, mock_service) = seed_corpus_service(&mut backend.connection);
+  for index in 1..=4 {
+    backend
+      .add(&NewTask {
+        entry: format!("priority_task{index}"),
+        service_id: mock_service.id,
+        corpus_id: mock_corpus.id,
+        status: TaskStatus::TODO.raw(),
+      })
+      .expect("add TODO task");
+  }
+  // Raise two of the later-imported tasks above the backlog, one higher than the other.
+  for (entry, priority) in [("priority_task3", 5), ("priority_task4", 9)] {
+    diesel::update(tasks::table)
+      .filter(service_id.eq(mock_service.id))
+      .filter(tasks::entry.eq(entry))
+      .set(tasks::priority.eq(priority))
+      .execute(&mut backend.connection)
+      .expect("prioritize task");
+  }
+  let holder = lease_holder(&mut backend.connection);
+  let leased: Vec<String> = backend
+    .fetch_tasks(&mock_service, 3, holder)
+    .expect("fetch tasks")
+    .into_iter()
+    .map(|task| task.entry)
+    .collect();
+  assert_eq!(
+    leased,
+    vec!["priority_task4", "priority_task3", "priority_task1"],
+    "highest priority first, then import order within the backlog"
+  );
+  assert_eq!(
+    backend.priority_backlog(&mock_corpus, &mock_service),
+    vec![(9, 1), (5, 1), (0, 2)],
+    "the leased (Queued) tasks still count as backlog at their priority"
+  );
+  DispatcherInstance::stop(&mut backend.connection, holder).expect("stop the instance");
+}
+
+#[test]
+fn fetch_tasks_fair_shares_a_service_across_its_corpora() {
+  // A giant corpus activated first must not starve a small campaign on the same service: each lease
+  // batch is split by activation weight, and a corpus short of its slice hands the rest back.
+  let mut backend = backend::testdb();
+  let (big_corpus, mock_service) = seed_corpus_service(&mut backend.connection);
+  let small_name = format!("{}_small", big_corpus.name);
+  NewCorpus {
+    name: small_name.clone(),
+    path: String::new(),
+    complex: false,
+    description: String::from("backend_test fixture"),
+  }
+  .create(&mut backend.connection)
+  .expect("seed second corpus");
+  let small_corpus =
+    Corpus::find_by_name(&small_name, &mut backend.connection).expect("find second corpus");
+  for (corpus, count) in [(&big_corpus, 40), (&small_corpus, 5)] {
+    for index in 1..=count {
+      backend
+        .add(&NewTask {
+          entry: format!("fair_share_{}_{index}", corpus.id),
+          service_id: mock_service.id,
+          corpus_id: corpus.id,
+          status: TaskStatus::TODO.raw(),
+        })
+        .expect("add TODO task");
+    }
+    backend
+      .set_activation_weight(corpus, &mock_service, 1)
+      .expect("set weight");
+  }
+  let corpora_of = |leased: &[Task]| {
+    let small = leased
+      .iter()
+      .filter(|task| task.corpus_id == small_corpus.id)
+      .count();
+    (leased.len() - small, small)
+  };
+
+  let holder = lease_holder(&mut backend.connection);
+  let first = backend
+    .fetch_tasks(&mock_service, 4, holder)
+    .expect("fetch tasks");
+  assert_eq!(
+    corpora_of(&first),
+    (2, 2),
+    "equal weights split the batch evenly"
+  );
+  assert_ne!(
+    first[0].corpus_id, first[1].corpus_id,
+    "the batch interleaves the corpora"
+  );
+
+  // Weight the big corpus 3:1 — the small one now has 3 TODO tasks left, more than its slice.
+  backend
+    .set_activation_weight(&big_corpus, &mock_service, 3)
+    .expect("reweight");
+  let second = backend
+    .fetch_tasks(&mock_service, 8, holder)
+    .expect("fetch tasks");
+  assert_eq!(
+    corpora_of(&second),
+    (6, 2),
+    "a 3:1 weight splits 8 leases 6:2"
+  );
+
+  // Only one small-corpus task remains: its unused slice goes to the big corpus.
+  let third = backend
+    .fetch_tasks(&mock_service, 8, holder)
+    .expect("fetch tasks");
+  assert_eq!(
+    corpora_of(&third),
+    (7, 1),
+    "a drained corpus hands its slice back"
+  );
+
+  let shares: Vec<_> = ActivationWeight::shares(&mut backend.connection)
+    .expect("fair shares")
+    .into_iter()
+    .filter(|share| share.service == mock_service.name)
+    .collect();
+  assert_eq!(shares.len(), 2);
+  let big = shares
+    .iter()
+    .find(|share| share.corpus == big_corpus.name)
+    .expect("big corpus share");
+  assert_eq!(big.leased_total, 15);
+  assert!((big.target_share - 0.75).abs() < 1e-9);
+  assert!(big.achieved_share > 0.5 && big.achieved_share < 1.0);
+
+  // A prioritized rerun on the small corpus goes ahead of the big corpus's backlog, weights or not.
+  for index in 1..=3 {
+    backend
+      .add(&NewTask {
+        entry: format!("fair_share_{}_rerun_{index}", small_corpus.id),
+        service_id: mock_service.id,
+        corpus_id: small_corpus.id,
+        status: TaskStatus::TODO.raw(),
+      })
+      .expect("add TODO task");
+  }
+  diesel::update(
+    tasks::table
+      .filter(tasks::corpus_id.eq(small_corpus.id))
+      .filter(tasks::status.eq(TaskStatus::TODO.raw())),
+  )
+  .set(tasks::priority.eq(9))
+  .execute(&mut backend.connection)
+  .expect("prioritize");
+  let fourth = backend
+    .fetch_tasks(&mock_service, 4, holder)
+    .expect("fetch tasks");
+  assert_eq!(
+    corpora_of(&fourth),
+    (1, 3),
+    "every prioritized task is leased before the weights split the rest"
+  );
+  assert!(fourth[..3].iter().all(|task| task.priority == 9));
+  DispatcherInstance::stop(&mut backend.connection, holder).expect("stop the instance");
+}
+
+#[test]
+fn pipeline_tasks_await_their_upstream_and_reset_on_upstream_rerun() {
+  // A service whose inputconverter names another service waits for that service's result per
+  // document, settles Invalid when the upstream fails, and is reset by an upstream rerun.
+  use cortex::schema::log_invalids;
+  let mut backend = backend::testdb();
+  let (corpus, upstream) = seed_corpus_service(&mut backend.connection);
+  let downstream_name = format!("{}_down", upstream.name);
+  NewService {
+    name: downstream_name.clone(),
+    version: 0.1,
+    inputformat: String::from("tex"),
+    outputformat: String::from("html"),
+    inputconverter: Some(upstream.name.clone()),
+    complex: false,
+    description: String::from("backend_test fixture"),
+  }
+  .create(&mut backend.connection)
+  .expect("seed downstream service");
+  let downstream =
+    Service::find_by_name(&downstream_name, &mut backend.connection).expect("find downstream");
+  assert_eq!(downstream.upstream(), Some(upstream.name.as_str()));
+
+  // Activating the downstream before its upstream is refused.
+  assert!(
+    backend
+      .register_service(&downstream, &corpus, "test".into(), "too early".into())
+      .is_err()
+  );
+  for index in 1..=3 {
+    for service in [2, upstream.id] {
+      backend
+        .add(&NewTask {
+          entry: format!("pipeline_{}_{index}", corpus.id),
+          service_id: service,
+          corpus_id: corpus.id,
+          status: TaskStatus::TODO.raw(),
+        })
+        .expect("add task");
+    }
+  }
+  backend
+    .register_service(&downstream, &corpus, "test".into(), "pipeline".into())
+    .expect("activate downstream");
+  let statuses = |backend: &mut backend::Backend, service: &Service| -> Vec<(String, i32)> {
+    tasks::table
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(service_id.eq(service.id))
+      .order(tasks::entry.asc())
+      .select((tasks::entry, status))
+      .load(&mut backend.connection)
+      .expect("load statuses")
+  };
+  let awaiting = TaskStatus::AWAITING_UPSTREAM.raw();
+  assert!(
+    statuses(&mut backend, &downstream)
+      .iter()
+      .all(|(_, raw)| *raw == awaiting)
+  );
+
+  // Upstream finalizes: entry 1 succeeds, entry 2 fails, entry 3 is still pending.
+  let upstream_tasks: Vec<Task> = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .filter(service_id.eq(upstream.id))
+    .order(tasks::entry.asc())
+    .get_results(&mut backend.connection)
+    .expect("upstream tasks");
+  let reports: Vec<TaskReport> = [(0, TaskStatus::NoProblem), (1, TaskStatus::Fatal)]
+    .into_iter()
+    .map(|(index, outcome)| TaskReport {
+      task: upstream_tasks[index].clone(),
+      status: outcome,
+      messages: Vec::new(),
+      archive: None,
+      previous_archive: None,
+    })
+    .collect();
+  backend.mark_done(&reports).expect("finalize upstream");
+  let after_upstream = statuses(&mut backend, &downstream);
+  assert_eq!(after_upstream[0].1, TaskStatus::TODO.raw());
+  assert_eq!(after_upstream[1].1, TaskStatus::Invalid.raw());
+  assert_eq!(after_upstream[2].1, awaiting);
+  let failed_task: i64 = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .filter(service_id.eq(downstream.id))
+    .filter(tasks::entry.eq(&after_upstream[1].0))
+    .select(tasks::id)
+    .first(&mut backend.connection)
+    .expect("failed downstream task");
+  let upstream_failed: i64 = log_invalids::table
+    .filter(log_invalids::task_id.eq(failed_task))
+    .filter(log_invalids::what.eq("upstream_failed"))
+    .count()
+    .get_result(&mut backend.connection)
+    .expect("count upstream_failed");
+  assert_eq!(upstream_failed, 1);
+
+  // A sandbox of the corpus reads its parent's upstream results: activating the downstream there
+  // releases the succeeded entry, settles the failed one and holds the pending one.
+  let sandbox_name = format!("{}_sandbox", corpus.name);
+  NewCorpus {
+    name: sandbox_name.clone(),
+    path: String::new(),
+    complex: false,
+    description: String::from("backend_test fixture"),
+  }
+  .create(&mut backend.connection)
+  .expect("seed sandbox");
+  let sandbox = Corpus::find_by_name(&sandbox_name, &mut backend.connection).expect("sandbox");
+  diesel::update(cortex::schema::corpora::table.find(sandbox.id))
+    .set(cortex::schema::corpora::parent_corpus_id.eq(corpus.id))
+    .execute(&mut backend.connection)
+    .expect("link the sandbox to its parent");
+  for index in 1..=3 {
+    backend
+      .add(&NewTask {
+        entry: format!("pipeline_{}_{index}", corpus.id),
+        service_id: 2,
+        corpus_id: sandbox.id,
+        status: TaskStatus::NoProblem.raw(),
+      })
+      .expect("add sandbox import task");
+  }
+  backend
+    .register_service(&downstream, &sandbox, "test".into(), "sandbox".into())
+    .expect("activate downstream on the sandbox");
+  let sandboxed: Vec<i32> = tasks::table
+    .filter(tasks::corpus_id.eq(sandbox.id))
+    .filter(service_id.eq(downstream.id))
+    .order(tasks::entry.asc())
+    .select(status)
+    .load(&mut backend.connection)
+    .expect("sandbox statuses");
+  assert_eq!(
+    sandboxed,
+    vec![TaskStatus::TODO.raw(), TaskStatus::Invalid.raw(), awaiting],
+    "the sandbox settles on its parent's failed upstream"
+  );
+
+  // Rerunning the upstream resets the downstream results for the same documents.
+  backend
+    .mark_rerun(RerunOptions {
+      corpus: &corpus,
+      service: &upstream,
+      severity_opt: None,
+      category_opt: None,
+      what_opt: None,
+      owner_opt: None,
+      description_opt: None,
+      priority_opt: None,
+      text_opt: None,
+    })
+    .expect("rerun upstream");
+  assert!(
+    statuses(&mut backend, &downstream)
+      .iter()
+      .all(|(_, raw)| *raw == awaiting)
+  );
+  let upstream_failed: i64 = log_invalids::table
+    .filter(log_invalids::task_id.eq(failed_task))
+    .count()
+    .get_result(&mut backend.connection)
+    .expect("count invalids");
+  assert_eq!(upstream_failed, 0, "the reset clears the stale messages");
+}
+
+#[test]
+fn paused_pipeline_tasks_stay_parked_when_their_upstream_finishes() {
+  // Pausing a downstream pair parks its awaiting tasks: an upstream finalizing meanwhile releases
+  // nothing leasable, and resume settles them against the upstream as it stands then.
+  let mut backend = backend::testdb();
+  let (corpus, upstream) = seed_corpus_service(&mut backend.connection);
+  let downstream_name = format!("{}_down", upstream.name);
+  NewService {
+    name: downstream_name.clone(),
+    version: 0.1,
+    inputformat: String::from("tex"),
+    outputformat: String::from("html"),
+    inputconverter: Some(upstream.name.clone()),
+    complex: false,
+    description: String::from("backend_test fixture"),
+  }
+  .create(&mut backend.connection)
+  .expect("seed downstream service");
+  let downstream =
+    Service::find_by_name(&downstream_name, &mut backend.connection).expect("find downstream");
+  for index in 1..=2 {
+    for service in [2, upstream.id] {
+      backend
+        .add(&NewTask {
+          entry: format!("paused_pipeline_{}_{index}", corpus.id),
+          service_id: service,
+          corpus_id: corpus.id,
+          status: TaskStatus::TODO.raw(),
+        })
+        .expect("add task");
+    }
+  }
+  backend
+    .register_service(&downstream, &corpus, "test".into(), "pipeline".into())
+    .expect("activate downstream");
+  let statuses = |backend: &mut backend::Backend| -> Vec<i32> {
+    tasks::table
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(service_id.eq(downstream.id))
+      .order(tasks::entry.asc())
+      .select(status)
+      .load(&mut backend.connection)
+      .expect("load statuses")
+  };
+  let parked = TaskStatus::PAUSED_AWAITING_UPSTREAM.raw();
+  assert_eq!(
+    backend.pause_run(corpus.id, downstream.id).expect("pause"),
+    2
+  );
+  assert_eq!(statuses(&mut backend), vec![parked, parked]);
+
+  // The upstream finalizes during the pause: entry 1 succeeds, entry 2 fails.
+  let upstream_tasks: Vec<Task> = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .filter(service_id.eq(upstream.id))
+    .order(tasks::entry.asc())
+    .get_results(&mut backend.connection)
+    .expect("upstream tasks");
+  let reports: Vec<TaskReport> = [(0, TaskStatus::NoProblem), (1, TaskStatus::Fatal)]
+    .into_iter()
+    .map(|(index, outcome)| TaskReport {
+      task: upstream_tasks[index].clone(),
+      status: outcome,
+      messages: Vec::new(),
+      archive: None,
+      previous_archive: None,
+    })
+    .collect();
+  backend.mark_done(&reports).expect("finalize upstream");
+  assert_eq!(
+    statuses(&mut backend),
+    vec![parked, parked],
+    "nothing is released to a paused run"
+  );
+
+  backend
+    .resume_run(corpus.id, downstream.id)
+    .expect("resume");
+  assert_eq!(
+    statuses(&mut backend),
+    vec![TaskStatus::TODO.raw(), TaskStatus::Invalid.raw()],
+    "resume releases the succeeded entry and settles the failed one"
+  );
+}
+
+#[test]
+fn dead_letters_requeue_or_quarantine_their_task() {
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  for index in 1..=2 {
+    backend
+      .add(&NewTask {
+        entry: format!("dead_letter_task{index}"),
+        service_id: service.id,
+        corpus_id: corpus.id,
+        status: TaskStatus::Fatal.raw(),
+      })
+      .expect("add Fatal task");
+  }
+  let task_ids: Vec<i64> = tasks::table
+    .filter(service_id.eq(service.id))
+    .order(tasks::entry.asc())
+    .select(tasks::id)
+    .load(&mut backend.connection)
+    .expect("task ids");
+  let leased_at = chrono::DateTime::from_timestamp(1_700_000_000, 0)
+    .expect("valid timestamp")
+    .naive_utc();
+  let letters: Vec<NewDeadLetter> = task_ids
+    .iter()
+    .map(|id| NewDeadLetter {
+      task_id: *id,
+      retries: 2,
+      workers: vec!["worker-a".into(), "worker-b".into()],
+      leased_at: vec![leased_at, leased_at],
+    })
+    .collect();
+  assert_eq!(backend.record_dead_letters(&letters), Ok(2));
+  let open: Vec<i64> = DeadLetter::list(&mut backend.connection, Some("open"), 1000, 0)
+    .expect("list open")
+    .into_iter()
+    .filter(|listing| listing.corpus == corpus.name)
+    .map(|listing| listing.letter.id)
+    .collect();
+  assert_eq!(open.len(), 2);
+  let task_status = |backend: &mut backend::Backend, id: i64| -> i32 {
+    tasks::table
+      .find(id)
+      .select(status)
+      .first(&mut backend.connection)
+      .expect("task status")
+  };
+
+  // Quarantine parks the task outside the queue, and a whole-corpus rerun leaves it there.
+  let quarantined = backend
+    .resolve_dead_letter(open[1], DeadLetterResolution::Quarantine, "tester")
+    .expect("quarantine");
+  assert_eq!(quarantined.state, "quarantined");
+  assert_eq!(quarantined.resolved_by.as_deref(), Some("tester"));
+  backend
+    .mark_rerun(RerunOptions {
+      corpus: &corpus,
+      service: &service,
+      severity_opt: None,
+      category_opt: None,
+      what_opt: None,
+      owner_opt: None,
+      description_opt: None,
+      priority_opt: None,
+      text_opt: None,
+    })
+    .expect("rerun corpus");
+  assert_eq!(
+    task_status(&mut backend, quarantined.task_id),
+    TaskStatus::QUARANTINED.raw()
+  );
+  assert_eq!(
+    task_status(&mut backend, task_ids[0]),
+    TaskStatus::TODO.raw()
+  );
+  assert!(
+    backend
+      .resolve_dead_letter(open[1], DeadLetterResolution::Quarantine, "tester")
+      .is_err(),
+    "an entry is quarantined once"
+  );
+  // The other entry is still open, but the rerun already took its task: resolving the stale entry
+  // leaves the task alone.
+  for resolution in [
+    DeadLetterResolution::Quarantine,
+    DeadLetterResolution::Requeue,
+  ] {
+    assert!(
+      backend
+        .resolve_dead_letter(open[0], resolution, "tester")
+        .is_err(),
+      "a stale entry cannot move a task that was since rerun"
+    );
+  }
+  assert_eq!(
+    task_status(&mut backend, task_ids[0]),
+    TaskStatus::TODO.raw()
+  );
+
+  // A quarantined entry can still be released back to the queue, without the fatal that parked it.
+  diesel::insert_into(log_fatals::table)
+    .values(&NewLogFatal {
+      task_id: quarantined.task_id,
+      category: "cortex".to_string(),
+      what: "never_completed_with_retries".to_string(),
+      details: String::new(),
+      fingerprint: 0,
+    })
+    .execute(&mut backend.connection)
+    .expect("insert the give-up fatal");
+  let requeued = backend
+    .resolve_dead_letter(open[1], DeadLetterResolution::Requeue, "tester")
+    .expect("requeue");
+  assert_eq!(requeued.state, "requeued");
+  assert_eq!(
+    task_status(&mut backend, requeued.task_id),
+    TaskStatus::TODO.raw()
+  );
+  let fatals: i64 = log_fatals::table
+    .filter(log_fatals::task_id.eq(requeued.task_id))
+    .count()
+    .get_result(&mut backend.connection)
+    .expect("count fatals");
+  assert_eq!(fatals, 0, "a requeue clears the give-up fatal like a rerun");
+  assert!(
+    backend
+      .resolve_dead_letter(open[1], DeadLetterResolution::Requeue, "tester")
+      .is_err(),
+    "a requeued entry is settled"
+  );
+}
+
+#[test]
+fn poison_entries_are_held_until_released() {
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  let poison = format!("{}/poison.zip", corpus.name);
+  let healthy = format!("{}/healthy.zip", corpus.name);
+  for entry in [&poison, &healthy] {
+    backend
+      .add(&NewTask {
+        entry: entry.clone(),
+        service_id: service.id,
+        corpus_id: corpus.id,
+        status: TaskStatus::TODO.raw(),
+      })
+      .expect("add TODO task");
+  }
+  let incident = |entry: &str| LeaseIncident {
+    entry: entry.to_string(),
+    service_id: service.id,
+    worker: Some("worker-a".into()),
+  };
+
+  // Below the threshold the entry only accrues strikes; the pass that reaches it holds it.
+  let held = backend
+    .record_lease_incidents(&[incident(&poison), incident(&healthy)], 3)
+    .expect("first pass");
+  assert!(held.is_empty());
+  let held = backend
+    .record_lease_incidents(&[incident(&poison), incident(&poison)], 3)
+    .expect("second pass");
+  assert_eq!(held.len(), 1);
+  assert_eq!(held[0].entry, poison);
+  assert_eq!(held[0].strikes, 3);
+
+  // The lease query skips it, but it keeps its TODO status (reports still list it).
+  let holder = lease_holder(&mut backend.connection);
+  let leased: Vec<String> = backend
+    .fetch_tasks(&service, 10, holder)
+    .expect("fetch tasks")
+    .into_iter()
+    .map(|task| task.entry)
+    .collect();
+  assert_eq!(leased, vec![healthy.clone()]);
+  let history = PoisonEntry::find(&mut backend.connection, &poison)
+    .expect("find poison entry")
+    .expect("poison entry recorded");
+  assert!(history.is_held());
+  assert_eq!(history.incidents, 3);
+  assert_eq!(history.last_worker.as_deref(), Some("worker-a"));
+
+  // Only a held entry can be released; releasing restarts its strikes.
+  assert_eq!(
+    backend.release_poison_entry(&healthy, "tester").map(|_| ()),
+    Err(diesel::result::Error::NotFound)
+  );
+  let released = backend
+    .release_poison_entry(&poison, "tester")
+    .expect("release");
+  assert!(!released.is_held());
+  assert_eq!((released.incidents, released.strikes), (3, 0));
+  assert_eq!(released.released_by.as_deref(), Some("tester"));
+  let leased: Vec<String> = backend
+    .fetch_tasks(&service, 10, holder)
+    .expect("fetch tasks")
+    .into_iter()
+    .map(|task| task.entry)
+    .collect();
+  assert_eq!(leased, vec![poison]);
+  DispatcherInstance::stop(&mut backend.connection, holder).expect("stop the instance");
+}
+
+#[test]
+fn manifest_import_attaches_metadata_once_per_entry() {
+  let mut backend = backend::testdb();
+  let (corpus, _service) = seed_corpus_service(&mut backend.connection);
+  let tag = random_mark();
+  let task = |name: &str| NewTask {
+    entry: format!("/manifest_test_{tag}/{name}/{name}.tex"),
+    service_id: 2,
+    corpus_id: corpus.id,
+    status: TaskStatus::TODO.raw(),
+  };
+  let metadata = |name: &str, doi: &str| NewEntryMetadata {
+    corpus_id: corpus.id,
+    entry: task(name).entry,
+    metadata: serde_json::json!({ "doi": doi }),
+  };
+
+  let inserted = backend
+    .mark_imported_documents(&[task("a"), task("b")], &[], &[metadata("a", "10.1/a")])
+    .expect("first import");
+  assert_eq!(inserted, 2);
+  // A re-import adds only new tasks, and replaces the metadata of entries it lists again.
+  let inserted = backend
+    .mark_imported_documents(&[task("a")], &[], &[metadata("a", "10.1/a-v2")])
+    .expect("re-import");
+  assert_eq!(inserted, 0);
+  assert_eq!(
+    EntryMetadata::find(&mut backend.connection, &corpus, &task("a").entry).expect("find a"),
+    Some(serde_json::json!({ "doi": "10.1/a-v2" }))
+  );
+  assert_eq!(
+    EntryMetadata::find(&mut backend.connection, &corpus, &task("b").entry).expect("find b"),
+    None,
+    "a path-only row carries no metadata"
+  );
+  // Listing an entry by its path alone on a re-import clears what the earlier manifest said.
+  let cleared = NewEntryMetadata {
+    metadata: serde_json::json!({}),
+    ..metadata("a", "")
+  };
+  backend
+    .mark_imported_documents(&[task("a")], &[], &[cleared])
+    .expect("path-only re-import");
+  assert_eq!(
+    EntryMetadata::find(&mut backend.connection, &corpus, &task("a").entry).expect("find a"),
+    None
+  );
+}
+
+#[test]
+fn document_facts_group_reports_and_narrow_sandboxes() {
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  let tag = random_mark();
+  let entry = |name: &str| format!("/documents_test_{tag}/{name}/{name}.zip");
+  let document = |name: &str, yymm: &str, category: &str, bytes: i64| NewDocument {
+    corpus_id: corpus.id,
+    entry: entry(name),
+    name: name.to_string(),
+    yymm: Some(yymm.to_string()),
+    source_bytes: Some(bytes),
+    file_count: Some(1),
+    main_tex: Some(format!("{name}.tex")),
+    primary_category: Some(category.to_string()),
+    ..NewDocument::default()
+  };
+  let import_task = |name: &str| NewTask {
+    entry: entry(name),
+    service_id: 2,
+    corpus_id: corpus.id,
+    status: TaskStatus::NoProblem.raw(),
+  };
+  backend
+    .mark_imported_documents(
+      &[
+        import_task("2605.00001"),
+        import_task("2605.00002"),
+        import_task("2606.00003"),
+      ],
+      &[
+        document("2605.00001", "2605", "hep-th", 5_000),
+        document("2605.00002", "2605", "math.AG", 2_000_000),
+        document("2606.00003", "2606", "math.NT", 50_000),
+      ],
+      &[],
+    )
+    .expect("import");
+  for (name, outcome) in [
+    ("2605.00001", TaskStatus::NoProblem),
+    ("2605.00002", TaskStatus::Error),
+    ("2606.00003", TaskStatus::Error),
+  ] {
+    NewTask {
+      entry: entry(name),
+      service_id: service.id,
+      corpus_id: corpus.id,
+      status: outcome.raw(),
+    }
+    .create(&mut backend.connection)
+    .expect("conversion task");
+  }
+
+  // One month, grouped by subject.
+  let by_category = backend::facet_breakdown(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    DocumentFacet::Category,
+    &DocumentFilter {
+      yymm: Some("2605".to_string()),
+      ..DocumentFilter::default()
+    },
+    None,
+  )
+  .expect("category breakdown");
+  let summary: Vec<(Option<&str>, i64, i64)> = by_category
+    .iter()
+    .map(|row| (row.facet.as_deref(), row.no_problem, row.error))
+    .collect();
+  assert_eq!(
+    summary,
+    vec![(Some("hep-th"), 1, 0), (Some("math.AG"), 0, 1)]
+  );
+
+  // A whole archive, grouped by month.
+  let by_month = backend::facet_breakdown(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    DocumentFacet::Month,
+    &DocumentFilter {
+      primary_category: Some("math".to_string()),
+      ..DocumentFilter::default()
+    },
+    None,
+  )
+  .expect("month breakdown");
+  let months: Vec<(Option<&str>, i64)> = by_month
+    .iter()
+    .map(|row| (row.facet.as_deref(), row.tasks))
+    .collect();
+  assert_eq!(months, vec![(Some("2605"), 1), (Some("2606"), 1)]);
+
+  // A sandbox of the math errors inherits their facts.
+  let sandbox_name = format!("documents_test_sandbox_{tag}");
+  let outcome = backend::create_sandbox(
+    &mut backend.connection,
+    &corpus,
+    &sandbox_name,
+    &backend::SandboxSelection {
+      service_id: service.id,
+      status: Some("error".to_string()),
+      message_severity: None,
+      category: None,
+      what: None,
+      text: None,
+      entry: None,
+      max_entries: None,
+      severity: None,
+      document: DocumentFilter {
+        primary_category: Some("math".to_string()),
+        min_source_bytes: Some(10_000),
+        max_source_bytes: Some(100_000),
+        ..DocumentFilter::default()
+      },
+    },
+  )
+  .expect("sandbox carve");
+  assert_eq!(
+    outcome.entry_count, 1,
+    "only 2606.00003 is a mid-sized math error"
+  );
+  let copied = Document::find(
+    &mut backend.connection,
+    outcome.sandbox.id,
+    &entry("2606.00003"),
+  )
+  .expect("find copied document")
+  .expect("the sandbox has the entry's facts");
+  assert_eq!(copied.primary_category.as_deref(), Some("math.NT"));
+
+  outcome
+    .sandbox
+    .destroy(&mut backend.connection)
+    .expect("drop sandbox");
+}
+
+#[test]
+fn extend_resets_changed_entries_and_flags_vanished_ones() {
+  let mut backend = backend::testdb();
+  let (mut corpus, service) = seed_corpus_service(&mut backend.connection);
+  let root = std::env::temp_dir().join(format!("cortex_extend_changes_{}", random_mark()));
+  let write_entry = |name: &str, body: &str| {
+    let dir = root.join(name);
+    std::fs::create_dir_all(&dir).expect("entry dir");
+    let path = dir.join(format!("{name}.tex"));
+    std::fs::write(&path, body).expect("entry file");
+    path.to_str().unwrap().to_string()
+  };
+  let kept = write_entry("kept", "\\documentclass{article} kept");
+  let replaced = write_entry("replaced", "\\documentclass{article} v1");
+  let removed = write_entry("removed", "\\documentclass{article} removed");
+  corpus.path = format!("{}/", root.to_str().unwrap());
+  let mut importer = Importer {
+    corpus: corpus.clone(),
+    backend: backend::testdb(),
+    ..Importer::default()
+  };
+  assert_eq!(importer.walk_import().expect("import"), 3);
+  for entry in [&kept, &replaced, &removed] {
+    NewTask {
+      entry: entry.clone(),
+      service_id: service.id,
+      corpus_id: corpus.id,
+      status: TaskStatus::Warning.raw(),
+    }
+    .create(&mut backend.connection)
+    .expect("conversion task");
+  }
+  let recorded = Document::find(&mut backend.connection, corpus.id, &replaced)
+    .expect("find document")
+    .expect("the import recorded the entry");
+  assert!(recorded.content_hash.is_some());
+  // A sandbox carved from the corpus reads the same sources, and has converted them too.
+  let sandbox = backend::create_sandbox(
+    &mut backend.connection,
+    &corpus,
+    &format!("extend_changes_sandbox_{}", random_mark()),
+    &backend::SandboxSelection {
+      service_id: service.id,
+      status: Some("warning".to_string()),
+      message_severity: None,
+      category: None,
+      what: None,
+      text: None,
+      entry: None,
+      max_entries: None,
+      severity: None,
+      document: DocumentFilter::default(),
+    },
+  )
+  .expect("sandbox carve")
+  .sandbox;
+  diesel::update(tasks::table.filter(tasks::corpus_id.eq(sandbox.id)))
+    .set(tasks::status.eq(TaskStatus::Warning.raw()))
+    .execute(&mut backend.connection)
+    .expect("sandbox conversions");
+
+  // arXiv replaces one paper in place, another disappears, a third arrives.
+  write_entry("replaced", "\\documentclass{article} version two");
+  std::fs::remove_file(&removed).expect("remove an entry");
+  write_entry("arrived", "\\documentclass{article} new");
+  let report = importer.extend_corpus().expect("extend");
+  assert_eq!(
+    (
+      report.new,
+      report.changed,
+      report.vanished,
+      report.reset_tasks
+    ),
+    (1, 1, 1, 2),
+    "the changed entry resets in the corpus and in its sandbox"
+  );
+  assert_eq!(report.changed_entries, [replaced.clone()]);
+  assert_eq!(report.vanished_entries, [removed.clone()]);
+  let status_in = |connection: &mut PgConnection, corpus_id: i32, entry: &str| -> i32 {
+    tasks::table
+      .filter(tasks::corpus_id.eq(corpus_id))
+      .filter(tasks::service_id.eq(service.id))
+      .filter(tasks::entry.eq(entry))
+      .select(tasks::status)
+      .first(connection)
+      .expect("task status")
+  };
+  let status_of =
+    |connection: &mut PgConnection, entry: &str| -> i32 { status_in(connection, corpus.id, entry) };
+  assert_eq!(
+    status_of(&mut backend.connection, &replaced),
+    TaskStatus::TODO.raw(),
+    "the changed entry converts again"
+  );
+  assert_eq!(
+    status_of(&mut backend.connection, &kept),
+    TaskStatus::Warning.raw()
+  );
+  assert_eq!(
+    status_of(&mut backend.connection, &removed),
+    TaskStatus::Warning.raw(),
+    "a vanished entry keeps its results"
+  );
+  let refreshed = Document::find(&mut backend.connection, corpus.id, &replaced)
+    .expect("find document")
+    .expect("still recorded");
+  assert_ne!(refreshed.content_hash, recorded.content_hash);
+  assert_eq!(
+    status_in(&mut backend.connection, sandbox.id, &replaced),
+    TaskStatus::TODO.raw(),
+    "the sandbox converts the changed entry again"
+  );
+  assert_eq!(
+    status_in(&mut backend.connection, sandbox.id, &kept),
+    TaskStatus::Warning.raw()
+  );
+  let sandbox_copy = Document::find(&mut backend.connection, sandbox.id, &replaced)
+    .expect("find document")
+    .expect("the sandbox has the entry's facts");
+  assert_eq!(sandbox_copy.content_hash, refreshed.content_hash);
+  let gone = Document::find(&mut backend.connection, corpus.id, &removed)
+    .expect("find document")
+    .expect("still recorded");
+  assert!(gone.vanished_at.is_some(), "the vanished entry is flagged");
+
+  // Nothing changed since: a second extend finds only the one still missing.
+  let report = importer.extend_corpus().expect("extend again");
+  assert_eq!(
+    (
+      report.new,
+      report.changed,
+      report.vanished,
+      report.reappeared
+    ),
+    (0, 0, 1, 0)
+  );
+  // The source comes back: the flag clears.
+  write_entry("removed", "\\documentclass{article} removed");
+  let report = importer.extend_corpus().expect("extend once more");
+  assert_eq!((report.vanished, report.reappeared), (0, 1));
+  let back = Document::find(&mut backend.connection, corpus.id, &removed)
+    .expect("find document")
+    .expect("still recorded");
+  assert_eq!(back.vanished_at, None);
+
+  sandbox
+    .destroy(&mut backend.connection)
+    .expect("drop sandbox");
+  let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn fsck_reports_and_repairs_damaged_and_orphan_archives() {
+  use cortex::backend::{FsckOptions, fsck_archives};
+  use cortex::models::NewResultArchive;
+  use cortex::storage::{MemoryStorage, Storage};
+  use sha2::{Digest, Sha256};
+  use std::io::Write;
+  use std::path::Path;
+
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  let storage = MemoryStorage::default();
+  let root = format!("/fsck_{}", random_mark());
+  let result_zip = || {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    let mut zip = zip::ZipWriter::new(&mut buffer);
+    let opts: zip::write::FileOptions<()> = zip::write::FileOptions::default();
+    zip.start_file("cortex.log", opts).unwrap();
+    zip.write_all(b"Info:conversion:0\n").unwrap();
+    zip.finish().unwrap();
+    buffer.into_inner()
+  };
+  // Two finished tasks whose archives the sink committed and recorded.
+  let mut reports = Vec::new();
+  for name in ["intact", "truncated"] {
+    let task = NewTask {
+      entry: format!("{root}/{name}/{name}.zip"),
+      service_id: service.id,
+      corpus_id: corpus.id,
+      status: TaskStatus::TODO.raw(),
+    };
+    task.create(&mut backend.connection).expect("task");
+    let task: Task = tasks::table
+      .filter(tasks::corpus_id.eq(corpus.id))
+      .filter(tasks::entry.eq(&task.entry))
+      .first(&mut backend.connection)
+      .expect("find task");
+    let path = format!("{root}/{name}/{}.zip", service.name);
+    let bytes = result_zip();
+    let sha256 = Sha256::digest(&bytes)
+      .iter()
+      .map(|byte| format!("{byte:02x}"))
+      .collect();
+    reports.push(TaskReport {
+      archive: Some(NewResultArchive {
+        task_id: task.id,
+        path: path.clone(),
+        size_bytes: bytes.len() as i64,
+        sha256,
+      }),
+      task,
+      status: TaskStatus::NoProblem,
+      messages: Vec::new(),
+      previous_archive: None,
+    });
+    storage.insert(path, bytes);
+  }
+  backend.mark_done(&reports).expect("mark_done");
+  // One archive is truncated behind the store's back, and a deleted sandbox left its result behind.
+  let truncated = format!("{root}/truncated/{}.zip", service.name);
+  let bytes = cortex::storage::read_all(&storage, Path::new(&truncated)).unwrap();
+  storage.insert(&truncated, bytes[..bytes.len() / 2].to_vec());
+  let orphan = format!("{root}/intact/{}.sandbox-{}.zip", service.name, i32::MAX);
+  storage.insert(&orphan, result_zip());
+
+  let report = fsck_archives(
+    &mut backend.connection,
+    &corpus,
+    &service,
+    &storage,
+    FsckOptions::default(),
+    "tester",
+    |_| {},
+  )
+  .expect("dry run");
+  assert!(report.dry_run);
+  assert_eq!(
+    (
+      report.checked,
+      report.missing_count,
+      report.checksum_mismatch_count,
+      report.no_cortex_log_count,
+      report.orphan_count
+    ),
+    (2, 0, 1, 0, 1)
+  );
+  assert_eq!(report.checksum_mismatch, [truncated.clone()]);
+  assert_eq!(report.orphans[0].path, orphan);
+  assert!(
+    storage.exists(Path::new(&orphan)),
+    "a dry run changes nothing"
+  );
+
+  let report = fsck_archives(
+    &mut backend.connection,
+    &corpus,
+    &service,
+    &storage,
+    FsckOptions {
+      reset_damaged: true,
+      remove_orphans: true,
+    },
+    "tester",
+    |_| {},
+  )
+  .expect("repair");
+  assert_eq!((report.reset, report.removed), (1, 1));
+  assert!(!storage.exists(Path::new(&orphan)));
+  let statuses: Vec<(String, i32)> = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .filter(tasks::service_id.eq(service.id))
+    .order(tasks::entry)
+    .select((tasks::entry, tasks::status))
+    .load(&mut backend.connection)
+    .expect("statuses");
+  assert_eq!(
+    statuses
+      .iter()
+      .map(|(_, status_val)| *status_val)
+      .collect::<Vec<_>>(),
+    [TaskStatus::NoProblem.raw(), TaskStatus::TODO.raw()],
+    "only the damaged task converts again"
+  );
+}
+
+#[test]
+fn archive_gc_collects_the_results_of_deleted_corpora_and_sandboxes() {
+  use cortex::backend::{GcReason, collect_archives};
+  use cortex::models::NewResultArchive;
+  use cortex::storage::{MemoryStorage, Storage};
+  use std::path::Path;
+
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  let storage = MemoryStorage::default();
+  let root = format!("/gc_{}", random_mark());
+  let entry = format!("{root}/paper/paper.zip");
+  NewTask {
+    entry: entry.clone(),
+    service_id: service.id,
+    corpus_id: corpus.id,
+    status: TaskStatus::TODO.raw(),
+  }
+  .create(&mut backend.connection)
+  .expect("task");
+  let task: Task = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .first(&mut backend.connection)
+    .expect("find task");
+  let recorded = format!("{root}/paper/{}.zip", service.name);
+  storage.insert(&recorded, vec![0; 100]);
+  backend
+    .mark_done(&[TaskReport {
+      archive: Some(NewResultArchive {
+        task_id: task.id,
+        path: recorded.clone(),
+        size_bytes: 100,
+        sha256: "0".repeat(64),
+      }),
+      task,
+      status: TaskStatus::NoProblem,
+      messages: Vec::new(),
+      previous_archive: None,
+    }])
+    .expect("mark_done");
+
+  // A live corpus's archive is kept; once the corpus is deleted, its archive is collectible.
+  let collectible = |report: &cortex::backend::ArchiveGcReport, path: &str| {
+    report
+      .archives
+      .iter()
+      .find(|archive| archive.path == path)
+      .map(|archive| (archive.bytes, archive.reason))
+  };
+  let report =
+    collect_archives(&mut backend.connection, &storage, false, "tester", |_| {}).expect("scan");
+  assert_eq!(collectible(&report, &recorded), None);
+
+  // A second corpus over another directory, holding the archive of a sandbox that is long gone.
+  let (other, _) = seed_corpus_service(&mut backend.connection);
+  NewTask {
+    entry: format!("{root}/other/other.zip"),
+    service_id: service.id,
+    corpus_id: other.id,
+    status: TaskStatus::NoProblem.raw(),
+  }
+  .create(&mut backend.connection)
+  .expect("other task");
+  let sandbox_leftover = format!("{root}/other/{}.sandbox-{}.zip", service.name, i32::MAX);
+  storage.insert(&sandbox_leftover, vec![0; 40]);
+  corpus
+    .destroy(&mut backend.connection)
+    .expect("delete corpus");
+
+  let report =
+    collect_archives(&mut backend.connection, &storage, false, "tester", |_| {}).expect("dry run");
+  assert!(report.dry_run);
+  assert_eq!(
+    collectible(&report, &recorded),
+    Some((100, GcReason::DeletedTask))
+  );
+  assert_eq!(
+    collectible(&report, &sandbox_leftover),
+    Some((40, GcReason::Orphan))
+  );
+  assert!(
+    storage.exists(Path::new(&recorded)),
+    "a dry run deletes nothing"
+  );
+
+  let report =
+    collect_archives(&mut backend.connection, &storage, true, "tester", |_| {}).expect("collect");
+  assert!(report.removed >= 2 && report.freed_bytes >= 140);
+  assert!(!storage.exists(Path::new(&recorded)));
+  assert!(!storage.exists(Path::new(&sandbox_leftover)));
+  let report =
+    collect_archives(&mut backend.connection, &storage, false, "tester", |_| {}).expect("rescan");
+  assert_eq!(collectible(&report, &recorded), None, "the record went too");
+}
+
+#[test]
+fn archive_gc_keeps_an_archive_a_live_task_may_write() {
+  use cortex::backend::collect_archives;
+  use cortex::models::NewResultArchive;
+  use cortex::storage::{MemoryStorage, Storage};
+  use std::path::Path;
+
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  let storage = MemoryStorage::default();
+  let root = format!("/gc_live_{}", random_mark());
+  let entry = format!("{root}/paper/paper.zip");
+  NewTask {
+    entry: entry.clone(),
+    service_id: service.id,
+    corpus_id: corpus.id,
+    status: TaskStatus::TODO.raw(),
+  }
+  .create(&mut backend.connection)
+  .expect("task");
+  let task: Task = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .first(&mut backend.connection)
+    .expect("find task");
+  let recorded = format!("{root}/paper/{}.zip", service.name);
+  storage.insert(&recorded, vec![0; 100]);
+  backend
+    .mark_done(&[TaskReport {
+      archive: Some(NewResultArchive {
+        task_id: task.id,
+        path: recorded.clone(),
+        size_bytes: 100,
+        sha256: "0".repeat(64),
+      }),
+      task,
+      status: TaskStatus::NoProblem,
+      messages: Vec::new(),
+      previous_archive: None,
+    }])
+    .expect("mark_done");
+
+  // The corpus is deleted and imported again over the same entries: its new, still pending task
+  // will write the same archive path, so the archive is not collected.
+  corpus
+    .destroy(&mut backend.connection)
+    .expect("delete corpus");
+  let (reimported, _) = seed_corpus_service(&mut backend.connection);
+  NewTask {
+    entry,
+    service_id: service.id,
+    corpus_id: reimported.id,
+    status: TaskStatus::TODO.raw(),
+  }
+  .create(&mut backend.connection)
+  .expect("re-imported task");
+
+  let report =
+    collect_archives(&mut backend.connection, &storage, true, "tester", |_| {}).expect("collect");
+  assert!(
+    report
+      .archives
+      .iter()
+      .all(|archive| archive.path != recorded),
+    "not collectible while a live task may write it"
+  );
+  assert!(storage.exists(Path::new(&recorded)));
+}
+
+#[test]
+fn mark_done_keeps_result_versions_up_to_the_services_count() {
+  use cortex::models::{NewResultArchive, NewResultArchiveVersion, ResultArchiveVersion};
+
+  let mut backend = backend::testdb();
+  let (corpus, service) = seed_corpus_service(&mut backend.connection);
+  service
+    .set_keep_result_versions(1, &mut backend.connection)
+    .expect("keep one version");
+  let entry = format!("/versions_{}/doc/doc.zip", random_mark());
+  NewTask {
+    entry: entry.clone(),
+    service_id: service.id,
+    corpus_id: corpus.id,
+    status: TaskStatus::TODO.raw(),
+  }
+  .create(&mut backend.connection)
+  .expect("task");
+  let task: Task = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .filter(tasks::entry.eq(&entry))
+    .first(&mut backend.connection)
+    .expect("find task");
+  let path = format!("/versions/doc/{}.zip", service.name);
+  let report = |round: u8, previous: bool| TaskReport {
+    task: task.clone(),
+    status: TaskStatus::NoProblem,
+    messages: Vec::new(),
+    archive: Some(NewResultArchive {
+      task_id: task.id,
+      path: path.clone(),
+      size_bytes: 10 + i64::from(round),
+      sha256: format!("{round:064}"),
+    }),
+    previous_archive: previous.then(|| NewResultArchiveVersion {
+      task_id: task.id,
+      run_id: None,
+      path: format!("/versions/doc/{}.v{round}.zip", service.name),
+      size_bytes: 9 + i64::from(round),
+      sha256: None,
+      written_at: chrono::Utc::now().naive_utc(),
+    }),
+  };
+  // The first result replaces nothing; each later one kept the result before it.
+  for round in 0..3u8 {
+    backend
+      .mark_done(&[report(round, round > 0)])
+      .expect("mark_done");
+  }
+  let versions =
+    ResultArchiveVersion::for_task(&mut backend.connection, task.id).expect("versions");
+  assert_eq!(versions.len(), 1, "only the service's count is kept");
+  assert_eq!(
+    versions[0].path,
+    format!("/versions/doc/{}.v2.zip", service.name)
+  );
+  assert_eq!(
+    versions[0].sha256.as_deref(),
+    Some(format!("{:064}", 1).as_str()),
+    "the version carries the checksum recorded when it was the current result"
+  );
 }
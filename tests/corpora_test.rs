@@ -7,11 +7,12 @@
 
 //! High-level contract test for the corpus-management capability (read side).
 
-use cortex::backend::{self, SandboxSelection, create_sandbox, test_db_address};
+use cortex::backend::{self, DocumentFilter, SandboxSelection, create_sandbox, test_db_address};
 use cortex::frontend::server::mount_api_with;
 use cortex::helpers::TaskStatus;
 use cortex::models::{
-  Corpus, NewCorpus, NewLogError, NewLogWarning, NewService, NewTask, Service, Task,
+  ActivationWeight, Corpus, NewCorpus, NewLogError, NewLogWarning, NewService, NewTask, Service,
+  Task,
 };
 use cortex::schema::{
   corpora, historical_runs, historical_tasks, log_errors, log_warnings, services, tasks,
@@ -287,6 +288,7 @@ fn register_service_creates_tasks_and_attributes_the_run() {
     category: "c".to_string(),
     what: "w".to_string(),
     details: "d".to_string(),
+    fingerprint: 0,
   })
   .expect("seed a log on the activated task");
   let reregister = db.register_service(
@@ -516,6 +518,7 @@ fn delete_corpus_removes_corpus_tasks_and_logs() {
     category: "c".to_string(),
     what: "w".to_string(),
     details: "d".to_string(),
+    fingerprint: 0,
   })
   .expect("insert log");
   // A second severity, to prove the transactional primitive cascades across *all* the log_* tables.
@@ -524,6 +527,7 @@ fn delete_corpus_removes_corpus_tasks_and_logs() {
     category: "c".to_string(),
     what: "w".to_string(),
     details: "d".to_string(),
+    fingerprint: 0,
   })
   .expect("insert error log");
 
@@ -791,8 +795,10 @@ fn deactivate_service_removes_pair_tasks_and_logs() {
     category: "c".to_string(),
     what: "w".to_string(),
     details: "d".to_string(),
+    fingerprint: 0,
   })
   .expect("log");
+  ActivationWeight::set(&mut db.connection, corpus.id, target.id, 3).expect("weight");
 
   let client = client();
   // Token-gated: no token -> 401 (before any deletion).
@@ -831,6 +837,13 @@ fn deactivate_service_removes_pair_tasks_and_logs() {
     .get_result(&mut db.connection)
     .unwrap();
   assert_eq!(log_count, 0, "the pair's logs are deleted (no orphans)");
+  assert!(
+    ActivationWeight::for_service(&mut db.connection, target.id)
+      .unwrap()
+      .iter()
+      .all(|weight| weight.corpus_id != corpus.id),
+    "the pair's lease weight is dropped with it"
+  );
   assert!(
     Service::find_by_name(target_svc, &mut db.connection).is_ok(),
     "the service definition survives deactivation"
@@ -912,6 +925,7 @@ fn sandbox_carves_matching_entries_into_a_new_corpus() {
     category: "missing_file".to_string(),
     what: "foo.cls".to_string(),
     details: String::new(),
+    fingerprint: 0,
   })
   .expect("matching log");
   db.add(&NewLogWarning {
@@ -919,6 +933,7 @@ fn sandbox_carves_matching_entries_into_a_new_corpus() {
     category: "missing_file".to_string(),
     what: "bar.cls".to_string(),
     details: String::new(),
+    fingerprint: 0,
   })
   .expect("other log");
 
@@ -1110,9 +1125,11 @@ fn sandbox_size_cap_and_entry_filter_limit_the_carve() {
     message_severity: None,
     category: None,
     what: None,
+    text: None,
     entry: None,
     max_entries: None,
     severity: None,
+    document: DocumentFilter::default(),
   };
   let load_entries = |db: &mut backend::Backend, corpus_id: i32| -> Vec<String> {
     let mut found: Vec<String> = tasks::table
@@ -1213,6 +1230,157 @@ fn sandbox_size_cap_and_entry_filter_limit_the_carve() {
   }
 }
 
+// Full-text message search: finds the documents whose messages mention a package, highlights the
+// match, rejects a blank query, and the same query carves exactly those documents into a sandbox.
+fn message_search_finds_documents_and_scopes_a_sandbox() {
+  let parent_name = "search_parent_corpus";
+  let sandbox_name = "search_sandbox_corpus";
+  let svc_name = "search_target_svc";
+  let mut db = backend::testdb();
+  cleanup(&mut db, parent_name, svc_name);
+  cleanup_corpus(&mut db, sandbox_name);
+
+  db.add(&NewCorpus {
+    name: parent_name.to_string(),
+    path: "/tmp/search_parent".to_string(),
+    complex: true,
+    description: "p".to_string(),
+  })
+  .expect("parent corpus");
+  let parent = Corpus::find_by_name(parent_name, &mut db.connection).unwrap();
+  db.add(&NewService {
+    name: svc_name.to_string(),
+    version: 0.1,
+    inputformat: "tex".to_string(),
+    outputformat: "html".to_string(),
+    inputconverter: None,
+    complex: true,
+    description: "svc".to_string(),
+  })
+  .expect("service");
+  let svc = Service::find_by_name(svc_name, &mut db.connection).unwrap();
+
+  // Two documents mention the package (one in a warning, one in an error); a third does not.
+  let mut task_ids = Vec::new();
+  for entry in [
+    "/tmp/search_parent/1/1.zip",
+    "/tmp/search_parent/2/2.zip",
+    "/tmp/search_parent/3/3.zip",
+  ] {
+    db.add(&NewTask {
+      service_id: svc.id,
+      corpus_id: parent.id,
+      status: TaskStatus::Error.raw(),
+      entry: entry.to_string(),
+    })
+    .expect("task");
+    let task: Task = tasks::table
+      .filter(tasks::corpus_id.eq(parent.id))
+      .filter(tasks::entry.eq(entry))
+      .first(&mut db.connection)
+      .expect("the task row");
+    task_ids.push(task.id);
+  }
+  db.add(&NewLogWarning {
+    task_id: task_ids[0],
+    category: "missing_file".to_string(),
+    what: "frobnicate.sty".to_string(),
+    details: "at 1.tex; line 3 while loading \\usepackage{frobnicate}".to_string(),
+    fingerprint: 0,
+  })
+  .expect("warning");
+  db.add(&NewLogError {
+    task_id: task_ids[1],
+    category: "undefined".to_string(),
+    what: "\\frobnicate".to_string(),
+    details: "the frobnicate package was not loaded".to_string(),
+    fingerprint: 0,
+  })
+  .expect("error");
+  db.add(&NewLogError {
+    task_id: task_ids[2],
+    category: "undefined".to_string(),
+    what: "\\foo".to_string(),
+    details: "unrelated".to_string(),
+    fingerprint: 0,
+  })
+  .expect("other error");
+
+  let client = client();
+  let response = client
+    .get(format!(
+      "/api/search/messages?q=frobnicate&corpus={parent_name}&service={svc_name}"
+    ))
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let body: serde_json::Value = response.into_json().expect("search json");
+  assert_eq!(body["total_messages"], 2);
+  assert_eq!(body["total_tasks"], 2);
+  let hits = body["hits"].as_array().expect("hits");
+  assert_eq!(hits[0]["name"], "1");
+  assert!(
+    hits[0]["highlights"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .any(|stretch| stretch["matched"] == true && stretch["text"] == "frobnicate"),
+    "the match is highlighted: {body}"
+  );
+  // Narrowed to a severity, only the error matches.
+  let response = client
+    .get(format!(
+      "/api/search/messages?q=frobnicate&corpus={parent_name}&severity=error"
+    ))
+    .dispatch();
+  let body: serde_json::Value = response.into_json().expect("search json");
+  assert_eq!(body["total_tasks"], 1);
+  assert_eq!(body["hits"][0]["severity"], "error");
+  assert_eq!(
+    client.get("/api/search/messages?q=%20").dispatch().status(),
+    Status::BadRequest,
+    "a blank query is a 400"
+  );
+  assert_eq!(
+    client
+      .get("/api/search/messages?q=frobnicate&severity=no_problem")
+      .dispatch()
+      .status(),
+    Status::BadRequest,
+    "no_problem is not a message severity"
+  );
+  assert_eq!(
+    client
+      .get(format!("/search?q=frobnicate&corpus={parent_name}"))
+      .dispatch()
+      .status(),
+    Status::Ok
+  );
+
+  // The same query as a sandbox selection carves exactly the two matching documents.
+  let outcome = create_sandbox(
+    &mut db.connection,
+    &parent,
+    sandbox_name,
+    &SandboxSelection {
+      service_id: svc.id,
+      status: None,
+      message_severity: None,
+      category: None,
+      what: None,
+      text: Some("frobnicate".to_string()),
+      entry: None,
+      max_entries: None,
+      severity: None,
+      document: DocumentFilter::default(),
+    },
+  )
+  .expect("text carve");
+  assert_eq!(outcome.entry_count, 2);
+
+  cleanup_corpus(&mut db, sandbox_name);
+  cleanup(&mut db, parent_name, svc_name);
+}
+
 // A human sandbox-form name collision re-shows the corpus page with a friendly flash (a redirect to
 // `?sandbox_taken=`), not a bare 409 page — the same courtesy the import form gives. (The agent
 // twin keeps its 409, asserted in the carve test above.)
@@ -1461,6 +1629,110 @@ fn import_rejects_an_unreadable_path() {
     .execute(&mut db.connection);
 }
 
+/// The import preview (`POST /api/corpora/preview`, and the "Preview" button of the human form)
+/// runs as a job that reports what an import would find — entries, skipped paths — without
+/// registering the corpus.
+fn import_preview_reports_without_registering() {
+  let root = std::env::temp_dir().join(format!("cortex_preview_api_{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&root);
+  std::fs::create_dir_all(root.join("doc1")).expect("create fixture dir");
+  std::fs::write(root.join("doc1/doc1.tex"), "\\documentclass{article}").expect("entry");
+  std::fs::create_dir_all(root.join("doc2")).expect("create fixture dir");
+  std::fs::write(root.join("doc2/notes.txt"), "not an entry").expect("non-entry");
+  let name = "preview-corpus-xyz";
+  let mut db = backend::testdb();
+  cleanup_corpus(&mut db, name);
+
+  let client = client();
+  let body = serde_json::json!({ "name": name, "path": root.to_str().unwrap(), "complex": false });
+  let denied = client
+    .post("/api/corpora/preview")
+    .header(ContentType::JSON)
+    .body(body.to_string())
+    .dispatch();
+  assert_eq!(
+    denied.status(),
+    Status::Unauthorized,
+    "a preview without a token is 401"
+  );
+  let response = client
+    .post("/api/corpora/preview?token=token1")
+    .header(ContentType::JSON)
+    .body(body.to_string())
+    .dispatch();
+  assert_eq!(
+    response.status(),
+    Status::Accepted,
+    "the preview runs as a job"
+  );
+  let job: serde_json::Value = response.into_json().expect("a job handle");
+  assert_eq!(job["kind"], "corpus_import_preview");
+  let uuid = job["uuid"].as_str().expect("a uuid").to_string();
+  let path = format!("/api/jobs/{uuid}?token=token1");
+  let mut last = serde_json::Value::Null;
+  for _ in 0..500 {
+    last = client
+      .get(path.as_str())
+      .dispatch()
+      .into_json()
+      .expect("job json");
+    let status = last["status"].as_str().unwrap_or_default();
+    if status == "succeeded" || status == "failed" || status == "interrupted" {
+      break;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(20));
+  }
+  assert_eq!(
+    last["status"], "succeeded",
+    "preview job did not succeed: {}",
+    last["message"]
+  );
+  let preview = &last["result"];
+  assert_eq!(preview["entries"], 1, "one projected entry: {preview}");
+  assert_eq!(preview["largest"][0]["bytes"], 23);
+  assert!(
+    Corpus::find_by_name(name, &mut db.connection).is_err(),
+    "a preview registers nothing"
+  );
+
+  // Human: the preview starts a job and redirects to its page, which re-renders the form with the
+  // values kept — and, once the job is done, the report.
+  sign_in(&client);
+  let response = client
+    .post("/corpus/import/preview")
+    .header(ContentType::Form)
+    .body(format!("name={name}&path={}&complex=false", root.display()))
+    .dispatch();
+  assert_eq!(response.status(), Status::SeeOther);
+  let page = response
+    .headers()
+    .get_one("Location")
+    .expect("a redirect to the preview page")
+    .to_string();
+  assert!(page.starts_with("/corpus/import/preview/"), "{page}");
+  let mut html = String::new();
+  for _ in 0..500 {
+    let response = client.get(page.as_str()).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    html = response.into_string().expect("html");
+    if !html.contains("refreshes until the preview is ready") {
+      break;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(20));
+  }
+  assert!(html.contains("Import preview"), "shows the preview");
+  assert!(
+    html.contains(&format!("value=\"{name}\"")),
+    "keeps the name"
+  );
+  assert!(
+    Corpus::find_by_name(name, &mut db.connection).is_err(),
+    "nor does the human preview"
+  );
+
+  let _ = std::fs::remove_dir_all(&root);
+}
+
 /// The agent dataset-export endpoint (`POST /api/corpora/<c>/services/<s>/export-dataset`) — the
 /// JSON twin of `cortex export-dataset`. Verifies token-gating (401), knob validation (422 on a bad
 /// severity / group_by), unknown corpus (404), and the happy path (202 + a `dataset_export` job
@@ -1728,6 +2000,7 @@ fn main() {
   extend_rejects_an_unreadable_corpus_path();
   import_form_reshows_friendly_error_on_name_collision();
   import_rejects_an_unreadable_path();
+  import_preview_reports_without_registering();
   api_corpus_detail_reports_services_and_counts();
   api_corpus_detail_is_404_for_unknown_corpus();
   activate_service_requires_a_token();
@@ -1741,6 +2014,7 @@ fn main() {
   deactivate_service_removes_pair_tasks_and_logs();
   sandbox_carves_matching_entries_into_a_new_corpus();
   sandbox_size_cap_and_entry_filter_limit_the_carve();
+  message_search_finds_documents_and_scopes_a_sandbox();
   sandbox_name_collision_reshows_a_friendly_error();
   snapshot_tasks_appends_history_and_is_token_gated();
   export_dataset_endpoint_and_human_form();
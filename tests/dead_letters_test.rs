@@ -0,0 +1,179 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Contract test for the **dead-letter queue** surfaces (`frontend::dead_letters`): the admin
+//! screen is signed-in-only, the agent twin is token-gated, and a requeue/quarantine resolves an
+//! entry exactly once (`409` after) while unknown actions / ids are `400` / `404`.
+
+use cortex::backend::{self, test_db_address};
+use cortex::concerns::CortexInsertable;
+use cortex::frontend::server::mount_api_with;
+use cortex::helpers::{TaskStatus, random_mark};
+use cortex::models::{Corpus, DeadLetter, NewCorpus, NewDeadLetter, NewTask};
+use cortex::schema::tasks;
+use diesel::prelude::*;
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+
+fn client() -> Client {
+  let figment = rocket::Config::figment().merge(("template_dir", "templates"));
+  let config_file = std::env::temp_dir().join("cortex_dead_letters_test.toml");
+  Client::tracked(mount_api_with(
+    rocket::custom(figment),
+    config_file,
+    test_db_address(),
+  ))
+  .expect("a valid rocket instance")
+}
+
+/// Seeds one Fatal `init` task on a fresh corpus and dead-letters it; returns the entry id.
+fn seed_dead_letter() -> i64 {
+  let mut backend = backend::testdb();
+  let corpus_name = format!("dead_letters_test_{}", random_mark());
+  NewCorpus {
+    name: corpus_name.clone(),
+    path: String::new(),
+    complex: false,
+    description: String::from("dead_letters_test fixture"),
+  }
+  .create(&mut backend.connection)
+  .expect("seed corpus");
+  let corpus = Corpus::find_by_name(&corpus_name, &mut backend.connection).expect("find corpus");
+  backend
+    .add(&NewTask {
+      entry: format!("{corpus_name}/paper.zip"),
+      service_id: 1,
+      corpus_id: corpus.id,
+      status: TaskStatus::Fatal.raw(),
+    })
+    .expect("seed task");
+  let task_id: i64 = tasks::table
+    .filter(tasks::corpus_id.eq(corpus.id))
+    .select(tasks::id)
+    .first(&mut backend.connection)
+    .expect("seeded task id");
+  backend
+    .record_dead_letters(&[NewDeadLetter {
+      task_id,
+      retries: 2,
+      workers: vec!["dead_letters_test_worker".into()],
+      leased_at: vec![chrono::Utc::now().naive_utc()],
+    }])
+    .expect("record dead-letter");
+  DeadLetter::list(&mut backend.connection, Some("open"), 1000, 0)
+    .expect("list dead-letters")
+    .into_iter()
+    .find(|listing| listing.corpus == corpus_name)
+    .map(|listing| listing.letter.id)
+    .expect("the seeded entry is listed")
+}
+
+fn screen_and_api_are_gated() {
+  let client = client();
+  let response = client.get("/admin/dead-letters").dispatch();
+  assert!(
+    response
+      .headers()
+      .get_one("Location")
+      .unwrap_or("")
+      .starts_with("/admin/login"),
+    "the dead-letter screen requires sign-in"
+  );
+  assert_eq!(
+    client.get("/api/dead-letters").dispatch().status(),
+    Status::Unauthorized,
+    "listing dead-letters requires a token"
+  );
+  assert_eq!(
+    client
+      .post("/api/dead-letters/1/requeue")
+      .dispatch()
+      .status(),
+    Status::Unauthorized,
+    "resolving a dead-letter requires a token"
+  );
+}
+
+fn agent_lists_and_resolves_once() {
+  let id = seed_dead_letter();
+  let client = client();
+  let token = || Header::new("X-Cortex-Token", "token1");
+
+  let response = client
+    .get("/api/dead-letters?state=open")
+    .header(token())
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let page: serde_json::Value = response.into_json().expect("dead-letter page json");
+  let entry = page["entries"]
+    .as_array()
+    .and_then(|entries| entries.iter().find(|entry| entry["id"] == id))
+    .expect("the seeded entry is listed")
+    .clone();
+  assert_eq!(entry["leases"][0]["worker"], "dead_letters_test_worker");
+  assert_eq!(entry["task_status"], "fatal");
+  assert_eq!(
+    client
+      .get("/api/dead-letters?state=lost")
+      .header(token())
+      .dispatch()
+      .status(),
+    Status::BadRequest
+  );
+
+  assert_eq!(
+    client
+      .post(format!("/api/dead-letters/{id}/forget"))
+      .header(token())
+      .dispatch()
+      .status(),
+    Status::BadRequest,
+    "only requeue and quarantine are actions"
+  );
+  let response = client
+    .post(format!("/api/dead-letters/{id}/quarantine"))
+    .header(token())
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let ack: serde_json::Value = response.into_json().expect("ack json");
+  assert_eq!(ack["state"], "quarantined");
+  assert_eq!(ack["actor"], "username1", "attributed to the token's owner");
+  assert_eq!(
+    client
+      .post(format!("/api/dead-letters/{id}/quarantine"))
+      .header(token())
+      .dispatch()
+      .status(),
+    Status::Conflict,
+    "an entry is quarantined once"
+  );
+  assert_eq!(
+    client
+      .post(format!("/api/dead-letters/{id}/requeue"))
+      .header(token())
+      .dispatch()
+      .status(),
+    Status::Ok,
+    "a quarantined entry can be released"
+  );
+  assert_eq!(
+    client
+      .post("/api/dead-letters/-1/requeue")
+      .header(token())
+      .dispatch()
+      .status(),
+    Status::NotFound
+  );
+}
+
+// Custom harness (see KNOWN_ISSUES L-1): run the cases then `_exit(0)`.
+fn main() {
+  screen_and_api_are_gated();
+  agent_lists_and_resolves_once();
+  eprintln!("dead_letters_test: all cases passed");
+  unsafe { libc::_exit(0) }
+}
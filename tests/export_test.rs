@@ -11,9 +11,10 @@
 
 use std::io::{Read, Write};
 
-use cortex::backend::{self, GroupBy, export_html_dataset};
+use cortex::backend::{self, DocumentFilter, GroupBy, export_html_dataset};
 use cortex::helpers::TaskStatus;
 use cortex::models::{Corpus, NewCorpus, NewService, NewTask, Service};
+use cortex::storage::LocalStorage;
 
 /// Build a result archive `<dir>/tex_to_html.zip` carrying a single `<paper>.html`.
 fn write_result_zip(dir: &std::path::Path, paper: &str, body: &str) {
@@ -104,9 +105,11 @@ fn exports_html_into_month_and_severity_archives() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem],
+    &DocumentFilter::default(),
     GroupBy::Month,
     None,
     &out_month,
+    &LocalStorage,
     |_| {},
   )
   .expect("month export succeeds");
@@ -134,9 +137,11 @@ fn exports_html_into_month_and_severity_archives() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem],
+    &DocumentFilter::default(),
     GroupBy::Severity,
     None,
     &out_sev,
+    &LocalStorage,
     |_| {},
   )
   .expect("severity export succeeds");
@@ -154,9 +159,11 @@ fn exports_html_into_month_and_severity_archives() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem],
+    &DocumentFilter::default(),
     GroupBy::Month,
     None,
     &out_month,
+    &LocalStorage,
     |_| {},
   )
   .expect("re-run succeeds");
@@ -279,9 +286,11 @@ fn streams_multiple_months_and_severities() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem, TaskStatus::Warning],
+    &DocumentFilter::default(),
     GroupBy::Month,
     None,
     &out_month,
+    &LocalStorage,
     |_| {},
   )
   .expect("month export");
@@ -321,9 +330,11 @@ fn streams_multiple_months_and_severities() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem, TaskStatus::Warning],
+    &DocumentFilter::default(),
     GroupBy::Severity,
     None,
     &out_sev,
+    &LocalStorage,
     |_| {},
   )
   .expect("severity export");
@@ -415,9 +426,11 @@ fn chunks_a_large_bucket_by_the_configured_mb_cap() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem],
+    &DocumentFilter::default(),
     GroupBy::Month,
     Some(1),
     &out,
+    &LocalStorage,
     |_| {},
   )
   .expect("chunked export");
@@ -445,9 +458,11 @@ fn chunks_a_large_bucket_by_the_configured_mb_cap() {
     &corpus,
     &service,
     &[TaskStatus::NoProblem],
+    &DocumentFilter::default(),
     GroupBy::Month,
     None,
     &out_nocap,
+    &LocalStorage,
     |_| {},
   )
   .expect("uncapped export");
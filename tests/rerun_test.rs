@@ -6,22 +6,29 @@
 // except according to those terms.
 
 //! Backend effect test for `mark_rerun`: marking a severity for rerun resets those tasks to TODO
-//! and clears their log messages (the mutation behind the token-gated rerun action).
+//! and clears their log messages and runtimes, keeping the archive record for the next result to
+//! replace (the mutation behind the token-gated rerun action).
 
 use cortex::backend::{self, RerunOptions};
 use cortex::helpers::TaskStatus;
-use cortex::models::{Corpus, NewCorpus, NewService, Service};
-use cortex::schema::{corpora, log_warnings, services, tasks};
+use cortex::models::{Corpus, NewCorpus, NewResultArchive, NewService, NewTaskRuntime, Service};
+use cortex::schema::{
+  corpora, log_fatals, log_warnings, result_archives, services, task_runtimes, tasks,
+};
 use diesel::prelude::*;
 
 const CORPUS_NAME: &str = "rerun-test-corpus";
 const SERVICE_NAME: &str = "rerun_test_svc";
+const QUARANTINE_CORPUS_NAME: &str = "rerun-quarantine-test-corpus";
+const QUARANTINE_SERVICE_NAME: &str = "rerun_quarantine_test_svc";
 
-#[test]
-fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
-  let mut backend = backend::testdb();
-
-  if let Ok(existing) = Corpus::find_by_name(CORPUS_NAME, &mut backend.connection) {
+/// Drops the fixture corpus and service of an earlier run, then creates them afresh.
+fn fresh_fixture(
+  backend: &mut backend::Backend,
+  corpus_name: &str,
+  service_name: &str,
+) -> (Corpus, Service) {
+  if let Ok(existing) = Corpus::find_by_name(corpus_name, &mut backend.connection) {
     let ids: Vec<i64> = tasks::table
       .filter(tasks::corpus_id.eq(existing.id))
       .select(tasks::id)
@@ -30,6 +37,9 @@ fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
     diesel::delete(log_warnings::table.filter(log_warnings::task_id.eq_any(&ids)))
       .execute(&mut backend.connection)
       .ok();
+    diesel::delete(log_fatals::table.filter(log_fatals::task_id.eq_any(&ids)))
+      .execute(&mut backend.connection)
+      .ok();
     diesel::delete(tasks::table.filter(tasks::corpus_id.eq(existing.id)))
       .execute(&mut backend.connection)
       .ok();
@@ -37,22 +47,22 @@ fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
       .execute(&mut backend.connection)
       .ok();
   }
-  diesel::delete(services::table.filter(services::name.eq(SERVICE_NAME)))
+  diesel::delete(services::table.filter(services::name.eq(service_name)))
     .execute(&mut backend.connection)
     .ok();
 
   backend
     .add(&NewCorpus {
-      name: CORPUS_NAME.to_string(),
+      name: corpus_name.to_string(),
       path: "/tmp/rerun".to_string(),
       complex: true,
       description: String::new(),
     })
     .expect("add corpus");
-  let corpus = Corpus::find_by_name(CORPUS_NAME, &mut backend.connection).expect("corpus");
+  let corpus = Corpus::find_by_name(corpus_name, &mut backend.connection).expect("corpus");
   backend
     .add(&NewService {
-      name: SERVICE_NAME.to_string(),
+      name: service_name.to_string(),
       version: 0.1,
       inputformat: "tex".to_string(),
       outputformat: "html".to_string(),
@@ -61,7 +71,14 @@ fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
       description: String::from("rerun test service"),
     })
     .expect("add service");
-  let service = Service::find_by_name(SERVICE_NAME, &mut backend.connection).expect("service");
+  let service = Service::find_by_name(service_name, &mut backend.connection).expect("service");
+  (corpus, service)
+}
+
+#[test]
+fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
+  let mut backend = backend::testdb();
+  let (corpus, service) = fresh_fixture(&mut backend, CORPUS_NAME, SERVICE_NAME);
 
   // A completed warning task plus its log message.
   let task_id: i64 = diesel::insert_into(tasks::table)
@@ -70,6 +87,8 @@ fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
       tasks::service_id.eq(service.id),
       tasks::corpus_id.eq(corpus.id),
       tasks::status.eq(TaskStatus::Warning.raw()),
+      // left over from an earlier prioritized rerun
+      tasks::priority.eq(7),
     ))
     .returning(tasks::id)
     .get_result(&mut backend.connection)
@@ -83,6 +102,23 @@ fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
     ))
     .execute(&mut backend.connection)
     .expect("insert log");
+  diesel::insert_into(task_runtimes::table)
+    .values(&NewTaskRuntime {
+      task_id,
+      service_id: service.id,
+      runtime_ms: 1200,
+    })
+    .execute(&mut backend.connection)
+    .expect("insert runtime");
+  diesel::insert_into(result_archives::table)
+    .values(&NewResultArchive {
+      task_id,
+      path: "/rerun/rerun_test_svc.zip".to_string(),
+      size_bytes: 10,
+      sha256: "0".repeat(64),
+    })
+    .execute(&mut backend.connection)
+    .expect("insert archive record");
 
   backend
     .mark_rerun(RerunOptions {
@@ -93,24 +129,114 @@ fn rerun_severity_resets_tasks_to_todo_and_clears_logs() {
       what_opt: None,
       owner_opt: Some("tester".to_string()),
       description_opt: Some("test rerun".to_string()),
+      priority_opt: None,
+      text_opt: None,
     })
     .expect("mark_rerun");
 
-  // The task is back to TODO and its warning log is gone.
-  let status: i32 = tasks::table
+  // The task is back to TODO in the ordinary backlog, and its warning log is gone.
+  let (status, priority): (i32, i32) = tasks::table
     .filter(tasks::id.eq(task_id))
-    .select(tasks::status)
+    .select((tasks::status, tasks::priority))
     .first(&mut backend.connection)
     .expect("task status");
   assert_eq!(status, TaskStatus::TODO.raw(), "warning task reset to TODO");
+  assert_eq!(
+    priority, 0,
+    "a rerun without a priority drops the earlier one"
+  );
   let logs: i64 = log_warnings::table
     .filter(log_warnings::task_id.eq(task_id))
     .count()
     .get_result(&mut backend.connection)
     .unwrap();
   assert_eq!(logs, 0, "warning logs deleted on rerun");
+  let runtimes: i64 = task_runtimes::table
+    .filter(task_runtimes::task_id.eq(task_id))
+    .count()
+    .get_result(&mut backend.connection)
+    .unwrap();
+  assert_eq!(runtimes, 0, "the stale runtime is deleted on rerun");
+  let archives: i64 = result_archives::table
+    .filter(result_archives::task_id.eq(task_id))
+    .count()
+    .get_result(&mut backend.connection)
+    .unwrap();
+  assert_eq!(
+    archives, 1,
+    "the archive record stays until the rerun's result replaces it"
+  );
 
   diesel::delete(tasks::table.filter(tasks::corpus_id.eq(corpus.id)))
     .execute(&mut backend.connection)
     .ok();
 }
+
+#[test]
+fn quarantined_tasks_stay_parked_through_message_reruns() {
+  // A quarantined task keeps the `never_completed_with_retries` fatal that parked it; no rerun
+  // scoped by that message may release it.
+  let mut backend = backend::testdb();
+  let (corpus, service) = fresh_fixture(
+    &mut backend,
+    QUARANTINE_CORPUS_NAME,
+    QUARANTINE_SERVICE_NAME,
+  );
+  let task_id: i64 = diesel::insert_into(tasks::table)
+    .values((
+      tasks::entry.eq("/rerun/quarantined"),
+      tasks::service_id.eq(service.id),
+      tasks::corpus_id.eq(corpus.id),
+      tasks::status.eq(TaskStatus::QUARANTINED.raw()),
+    ))
+    .returning(tasks::id)
+    .get_result(&mut backend.connection)
+    .expect("insert task");
+  diesel::insert_into(log_fatals::table)
+    .values((
+      log_fatals::task_id.eq(task_id),
+      log_fatals::category.eq("cortex"),
+      log_fatals::what.eq("never_completed_with_retries"),
+      log_fatals::details.eq("never_completed_with_retries"),
+    ))
+    .execute(&mut backend.connection)
+    .expect("insert log");
+
+  let scopes = [
+    (Some("cortex"), None, None),
+    (Some("cortex"), Some("never_completed_with_retries"), None),
+    (None, None, Some("never_completed")),
+  ];
+  for (category, what, text) in scopes {
+    backend
+      .mark_rerun(RerunOptions {
+        corpus: &corpus,
+        service: &service,
+        severity_opt: Some("fatal".to_string()),
+        category_opt: category.map(str::to_string),
+        what_opt: what.map(str::to_string),
+        owner_opt: Some("tester".to_string()),
+        description_opt: Some("test rerun".to_string()),
+        priority_opt: None,
+        text_opt: text.map(str::to_string),
+      })
+      .expect("mark_rerun");
+    let status: i32 = tasks::table
+      .find(task_id)
+      .select(tasks::status)
+      .first(&mut backend.connection)
+      .expect("task status");
+    assert_eq!(
+      status,
+      TaskStatus::QUARANTINED.raw(),
+      "a rerun by {category:?}/{what:?}/{text:?} leaves the quarantined task parked"
+    );
+  }
+
+  diesel::delete(log_fatals::table.filter(log_fatals::task_id.eq(task_id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(tasks::table.filter(tasks::corpus_id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+}
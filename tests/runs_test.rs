@@ -9,11 +9,18 @@
 //! (open) run, as the agent twin of the human history screen.
 
 use chrono::{NaiveDate, NaiveDateTime};
-use cortex::backend::{self, test_db_address};
+use cortex::backend::{
+  self, ClassGrain, ClassMove, ClassTrendPoint, RegressionThresholds, RunRegressions, class_movers,
+  class_trend, test_db_address,
+};
 use cortex::frontend::server::mount_api_with;
 use cortex::helpers::TaskStatus;
-use cortex::models::{Corpus, HistoricalRun, NewCorpus, NewService, NewTask, Service};
-use cortex::schema::{corpora, historical_runs, historical_tasks, services, tasks};
+use cortex::models::{
+  Corpus, HistoricalRun, NewCorpus, NewLogError, NewLogFatal, NewService, NewTask, Service,
+};
+use cortex::schema::{
+  corpora, historical_runs, historical_tasks, log_errors, log_fatals, services, tasks,
+};
 use diesel::prelude::*;
 use rocket::http::{ContentType, Status};
 use rocket::local::blocking::Client;
@@ -391,6 +398,513 @@ fn run_completion_on_drain_closes_only_a_fully_drained_run() {
     .ok();
 }
 
+/// Run completion on drain records a regression summary against the run before it: the first run
+/// only freezes its message classes (nothing to compare with), the second — where a clean document
+/// turns fatal with a never-seen class and an error class spreads to ten more documents — reports
+/// the worse documents, the new class and the jumped class, on the run and over the API.
+fn run_completion_records_a_regression_summary(client: &Client) {
+  let mut backend = backend::testdb();
+  let corpus_name = "regression_summary_corpus";
+  let service_name = "regression_summary_svc";
+  if let Ok(existing) = Corpus::find_by_name(corpus_name, &mut backend.connection) {
+    diesel::delete(historical_runs::table.filter(historical_runs::corpus_id.eq(existing.id)))
+      .execute(&mut backend.connection)
+      .ok();
+    diesel::delete(tasks::table.filter(tasks::corpus_id.eq(existing.id)))
+      .execute(&mut backend.connection)
+      .ok();
+    diesel::delete(corpora::table.filter(corpora::id.eq(existing.id)))
+      .execute(&mut backend.connection)
+      .ok();
+  }
+  diesel::delete(services::table.filter(services::name.eq(service_name)))
+    .execute(&mut backend.connection)
+    .ok();
+  backend
+    .add(&NewCorpus {
+      name: corpus_name.to_string(),
+      path: "/tmp/regression-summary".to_string(),
+      complex: true,
+      description: String::new(),
+    })
+    .expect("corpus");
+  let corpus = Corpus::find_by_name(corpus_name, &mut backend.connection).expect("corpus");
+  backend
+    .add(&NewService {
+      name: service_name.to_string(),
+      version: 0.1,
+      inputformat: "tex".to_string(),
+      outputformat: "html".to_string(),
+      inputconverter: Some("import".to_string()),
+      complex: true,
+      description: String::from("regression-summary service"),
+    })
+    .expect("service");
+  let service = Service::find_by_name(service_name, &mut backend.connection).expect("service");
+  let error = |backend: &mut backend::Backend, task_id: i64, category: &str, what: &str| {
+    backend
+      .add(&NewLogError {
+        task_id,
+        category: category.to_string(),
+        what: what.to_string(),
+        details: String::new(),
+        fingerprint: 0,
+      })
+      .expect("error message");
+  };
+
+  // Run 1: one clean document, one with a `missing_file` error, and twelve documents of which two
+  // carry an `undefined` error.
+  backend
+    .mark_new_run(&corpus, &service, "tester".into(), "first run".into())
+    .expect("open run 1");
+  let clean = add_task(
+    &mut backend.connection,
+    "/tmp/regression-summary/clean.zip",
+    service.id,
+    corpus.id,
+    TaskStatus::NoProblem.raw(),
+  );
+  let missing = add_task(
+    &mut backend.connection,
+    "/tmp/regression-summary/missing.zip",
+    service.id,
+    corpus.id,
+    TaskStatus::Error.raw(),
+  );
+  error(&mut backend, missing, "missing_file", "a.sty");
+  let mut spreading = Vec::new();
+  for i in 0..12 {
+    let status = if i < 2 {
+      TaskStatus::Error
+    } else {
+      TaskStatus::NoProblem
+    };
+    let id = add_task(
+      &mut backend.connection,
+      &format!("/tmp/regression-summary/spread{i:02}.zip"),
+      service.id,
+      corpus.id,
+      status.raw(),
+    );
+    if i < 2 {
+      error(&mut backend, id, "undefined", "\\bar");
+    }
+    spreading.push(id);
+  }
+  assert!(
+    close_drained_run(&mut backend, corpus.id, service.id),
+    "the drained first run closes"
+  );
+  let first = HistoricalRun::find_by(&corpus, &service, &mut backend.connection)
+    .expect("runs")
+    .into_iter()
+    .next()
+    .expect("run 1");
+  let summary = RunRegressions::of(&first).expect("run 1 carries a summary");
+  assert_eq!(
+    summary.baseline_run, None,
+    "nothing to compare the first run with"
+  );
+  assert_eq!(
+    summary.compared_documents, 0,
+    "no snapshot predates the first run"
+  );
+  assert!(!summary.regressed(), "the first run cannot regress");
+
+  // Run 2: the clean document turns fatal with a never-seen class, and the `undefined` error
+  // spreads to the ten other documents.
+  backend
+    .mark_new_run(&corpus, &service, "tester".into(), "second run".into())
+    .expect("open run 2");
+  diesel::update(tasks::table.filter(tasks::id.eq(clean)))
+    .set(tasks::status.eq(TaskStatus::Fatal.raw()))
+    .execute(&mut backend.connection)
+    .expect("clean turns fatal");
+  backend
+    .add(&NewLogFatal {
+      task_id: clean,
+      category: "conversion".to_string(),
+      what: "timeout".to_string(),
+      details: String::new(),
+      fingerprint: 0,
+    })
+    .expect("fatal message");
+  for &id in &spreading[2..] {
+    diesel::update(tasks::table.filter(tasks::id.eq(id)))
+      .set(tasks::status.eq(TaskStatus::Error.raw()))
+      .execute(&mut backend.connection)
+      .expect("spread the error");
+    error(&mut backend, id, "undefined", "\\bar");
+  }
+  let second_id = backend
+    .complete_run_if_drained(corpus.id, service.id)
+    .expect("close run 2")
+    .expect("the drained second run closes");
+  assert!(
+    backend::pending_regression_runs(&mut backend.connection)
+      .expect("pending runs")
+      .contains(&second_id),
+    "the closed run owes its summary"
+  );
+  // A rerun clears the scope's logs and statuses before the deferred comparison gets to run: the
+  // summary still reads the run as it was frozen on drain.
+  diesel::delete(log_errors::table.filter(log_errors::task_id.eq_any(&spreading)))
+    .execute(&mut backend.connection)
+    .expect("clear errors");
+  diesel::delete(log_fatals::table.filter(log_fatals::task_id.eq(clean)))
+    .execute(&mut backend.connection)
+    .expect("clear fatals");
+  diesel::update(tasks::table.filter(tasks::corpus_id.eq(corpus.id)))
+    .set(tasks::status.eq(TaskStatus::TODO.raw()))
+    .execute(&mut backend.connection)
+    .expect("back to TODO");
+  backend::record_run_regressions(
+    &mut backend.connection,
+    second_id,
+    RegressionThresholds::from_config(),
+  )
+  .expect("record regressions")
+  .expect("run 2 is closed");
+  assert!(
+    !backend::pending_regression_runs(&mut backend.connection)
+      .expect("pending runs")
+      .contains(&second_id),
+    "the stored summary settles the debt"
+  );
+  let second = HistoricalRun::find_by(&corpus, &service, &mut backend.connection)
+    .expect("runs")
+    .into_iter()
+    .find(|run| run.id == second_id)
+    .expect("run 2");
+  let summary = RunRegressions::of(&second).expect("run 2 carries a summary");
+  assert_eq!(
+    summary.baseline_run,
+    Some(first.public_id.to_string()),
+    "compared with the first run"
+  );
+  assert_eq!(summary.compared_documents, 14);
+  assert_eq!(
+    summary.worse_documents, 11,
+    "1 clean → fatal, 10 clean → error"
+  );
+  let transitions: Vec<(&str, &str, i64)> = summary
+    .worse_transitions
+    .iter()
+    .map(|t| {
+      (
+        t.previous_status.as_str(),
+        t.current_status.as_str(),
+        t.task_count,
+      )
+    })
+    .collect();
+  assert_eq!(
+    transitions,
+    vec![("no_problem", "error", 10), ("no_problem", "fatal", 1)],
+    "the worsening transitions, largest first"
+  );
+  assert!(summary.worse_examples.contains(&"clean".to_string()));
+  assert_eq!(summary.new_class_count, 1, "only the fatal class is new");
+  assert_eq!(
+    (
+      summary.new_classes[0].severity.as_str(),
+      summary.new_classes[0].category.as_str(),
+      summary.new_classes[0].what.as_str(),
+      summary.new_classes[0].current_tasks,
+    ),
+    ("fatal", "conversion", "timeout", 1)
+  );
+  let expect_jump = RegressionThresholds::from_config().is_jump(2, 12);
+  assert_eq!(
+    summary.jumped_class_count,
+    i64::from(expect_jump),
+    "`undefined \\bar` went from 2 to 12 documents; `missing_file` stayed put"
+  );
+  if expect_jump {
+    assert_eq!(summary.jumped_classes[0].what, "\\bar");
+    assert_eq!(summary.jumped_classes[0].previous_tasks, 2);
+    assert_eq!(summary.jumped_classes[0].current_tasks, 12);
+  }
+  assert!(summary.regressed());
+
+  // The same summary over the API.
+  let response = client
+    .get(format!("/api/runs/{corpus_name}/{service_name}"))
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let body: Value = response.into_json().expect("runs json");
+  assert_eq!(body[0]["regressions"]["worse_documents"], 11);
+  assert_eq!(body[0]["regressions"]["new_class_count"], 1);
+  assert_eq!(body[1]["regressions"]["worse_documents"], 0);
+
+  // Clean up so a re-run starts fresh (the class snapshots cascade with their runs).
+  diesel::delete(historical_runs::table.filter(historical_runs::corpus_id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(tasks::table.filter(tasks::corpus_id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(corpora::table.filter(corpora::id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(services::table.filter(services::name.eq(service_name)))
+    .execute(&mut backend.connection)
+    .ok();
+}
+
+/// Replaces a task's error messages with the given `(category, what)` classes.
+fn set_errors(backend: &mut backend::Backend, task_id: i64, classes: &[(&str, &str)]) {
+  diesel::delete(log_errors::table.filter(log_errors::task_id.eq(task_id)))
+    .execute(&mut backend.connection)
+    .expect("clear errors");
+  for (category, what) in classes {
+    backend
+      .add(&NewLogError {
+        task_id,
+        category: category.to_string(),
+        what: what.to_string(),
+        details: String::new(),
+        fingerprint: 0,
+      })
+      .expect("error message");
+  }
+}
+
+/// Closes a drained run the way the dispatcher does (freezing its message classes), then runs its
+/// `run_regressions` job body inline (the summary comes from it), so the test sees the result
+/// without waiting on a background thread.
+fn close_drained_run(backend: &mut backend::Backend, corpus_id: i32, service_id: i32) -> bool {
+  let Some(run_id) = backend
+    .complete_run_if_drained(corpus_id, service_id)
+    .expect("close run")
+  else {
+    return false;
+  };
+  backend::record_run_regressions(
+    &mut backend.connection,
+    run_id,
+    RegressionThresholds::from_config(),
+  )
+  .expect("record regressions")
+  .is_some()
+}
+
+/// Message-class trends read the per-run rollup frozen on drain: a class's counts run by run (a
+/// category's documents counted once however many of its whats they carry), and the classes that
+/// grew and shrank the most across the window — over the backend and the API.
+fn class_trends_follow_runs_and_rank_movers(client: &Client) {
+  let mut backend = backend::testdb();
+  let corpus_name = "class_trends_corpus";
+  let service_name = "class_trends_svc";
+  if let Ok(existing) = Corpus::find_by_name(corpus_name, &mut backend.connection) {
+    diesel::delete(historical_runs::table.filter(historical_runs::corpus_id.eq(existing.id)))
+      .execute(&mut backend.connection)
+      .ok();
+    diesel::delete(tasks::table.filter(tasks::corpus_id.eq(existing.id)))
+      .execute(&mut backend.connection)
+      .ok();
+    diesel::delete(corpora::table.filter(corpora::id.eq(existing.id)))
+      .execute(&mut backend.connection)
+      .ok();
+  }
+  diesel::delete(services::table.filter(services::name.eq(service_name)))
+    .execute(&mut backend.connection)
+    .ok();
+  backend
+    .add(&NewCorpus {
+      name: corpus_name.to_string(),
+      path: "/tmp/class-trends".to_string(),
+      complex: true,
+      description: String::new(),
+    })
+    .expect("corpus");
+  let corpus = Corpus::find_by_name(corpus_name, &mut backend.connection).expect("corpus");
+  backend
+    .add(&NewService {
+      name: service_name.to_string(),
+      version: 0.1,
+      inputformat: "tex".to_string(),
+      outputformat: "html".to_string(),
+      inputconverter: Some("import".to_string()),
+      complex: true,
+      description: String::from("class-trends service"),
+    })
+    .expect("service");
+  let service = Service::find_by_name(service_name, &mut backend.connection).expect("service");
+  let ids: Vec<i64> = (0..3)
+    .map(|i| {
+      add_task(
+        &mut backend.connection,
+        &format!("/tmp/class-trends/doc{i}.zip"),
+        service.id,
+        corpus.id,
+        TaskStatus::Error.raw(),
+      )
+    })
+    .collect();
+
+  // Three runs: `undefined \foo` spreads from one document to three (picking up `\bar` on the
+  // first in the last run), while `missing_file a.sty` recedes from three to one.
+  const FOO: (&str, &str) = ("undefined", "\\foo");
+  const BAR: (&str, &str) = ("undefined", "\\bar");
+  const STY: (&str, &str) = ("missing_file", "a.sty");
+  let runs: [[&[(&str, &str)]; 3]; 3] = [
+    [&[FOO, STY], &[STY], &[STY]],
+    [&[FOO, STY], &[FOO, STY], &[]],
+    [&[FOO, BAR, STY], &[FOO], &[FOO]],
+  ];
+  for (run, classes) in runs.iter().enumerate() {
+    backend
+      .mark_new_run(&corpus, &service, "tester".into(), format!("run {run}"))
+      .expect("open run");
+    for (task_id, task_classes) in ids.iter().zip(classes) {
+      set_errors(&mut backend, *task_id, task_classes);
+    }
+    assert!(
+      close_drained_run(&mut backend, corpus.id, service.id),
+      "the drained run closes and freezes its classes"
+    );
+  }
+
+  let task_counts = |points: Vec<ClassTrendPoint>| -> Vec<i64> {
+    points.iter().map(|point| point.task_count).collect()
+  };
+  let foo = class_trend(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    "error",
+    "undefined",
+    Some("\\foo"),
+    20,
+  )
+  .expect("foo trend");
+  assert_eq!(
+    foo.first().map(|point| point.run.description.as_str()),
+    Some("run 0")
+  );
+  assert_eq!(task_counts(foo), vec![1, 2, 3], "oldest run first");
+  let undefined = class_trend(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    "error",
+    "undefined",
+    None,
+    20,
+  )
+  .expect("undefined trend");
+  assert_eq!(
+    undefined
+      .last()
+      .map(|point| (point.task_count, point.message_count)),
+    Some((3, 4)),
+    "the category counts doc0 once for its two whats, and all four messages"
+  );
+  let sty = class_trend(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    "error",
+    "missing_file",
+    Some("a.sty"),
+    2,
+  )
+  .expect("sty trend");
+  assert_eq!(
+    task_counts(sty),
+    vec![2, 1],
+    "a two-run window holds the last two runs"
+  );
+
+  let movers = class_movers(
+    &mut backend.connection,
+    corpus.id,
+    service.id,
+    ClassGrain::What,
+    Some("error"),
+    20,
+    10,
+  )
+  .expect("movers");
+  assert_eq!(movers.runs.len(), 3);
+  let moved = |classes: &[ClassMove]| -> Vec<(String, i64)> {
+    classes
+      .iter()
+      .map(|class| (class.what.clone().unwrap_or_default(), class.change()))
+      .collect()
+  };
+  assert_eq!(
+    moved(&movers.growing),
+    vec![("\\foo".to_string(), 2), ("\\bar".to_string(), 1)],
+    "largest growth first; a class new in the last run grows from 0"
+  );
+  assert_eq!(moved(&movers.shrinking), vec![("a.sty".to_string(), -2)]);
+
+  // The same over the API.
+  let response = client
+    .get(format!(
+      "/api/runs/{corpus_name}/{service_name}/trend?severity=error&category=undefined&what=%5Cfoo"
+    ))
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let body: Value = response.into_json().expect("trend json");
+  let counts: Vec<i64> = body["points"]
+    .as_array()
+    .expect("points")
+    .iter()
+    .map(|point| point["task_count"].as_i64().expect("task_count"))
+    .collect();
+  assert_eq!(counts, vec![1, 2, 3]);
+  let response = client
+    .get(format!(
+      "/api/runs/{corpus_name}/{service_name}/movers?grain=category"
+    ))
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let body: Value = response.into_json().expect("movers json");
+  assert_eq!(body["growing"][0]["category"], "undefined");
+  assert_eq!(body["growing"][0]["change"], 2);
+  assert_eq!(body["shrinking"][0]["category"], "missing_file");
+  let response = client
+    .get(format!(
+      "/api/runs/{corpus_name}/{service_name}/trend?severity=info&category=undefined"
+    ))
+    .dispatch();
+  assert_eq!(
+    response.status(),
+    Status::BadRequest,
+    "info classes are not frozen, so not a trend severity"
+  );
+  let page = client
+    .get(format!(
+      "/runs/{corpus_name}/{service_name}/trends?severity=error&category=undefined"
+    ))
+    .dispatch();
+  assert_eq!(page.status(), Status::Ok);
+  assert!(
+    page
+      .into_string()
+      .expect("trends html")
+      .contains("Fastest-growing"),
+    "the trends screen renders the movers"
+  );
+
+  diesel::delete(historical_runs::table.filter(historical_runs::corpus_id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(tasks::table.filter(tasks::corpus_id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(corpora::table.filter(corpora::id.eq(corpus.id)))
+    .execute(&mut backend.connection)
+    .ok();
+  diesel::delete(services::table.filter(services::name.eq(service_name)))
+    .execute(&mut backend.connection)
+    .ok();
+}
+
 fn main() {
   // Own the Client in `main` and `process::exit(0)` while it is still alive — never dropping it, so
   // the racy libpq/Tokio/r2d2 teardown never runs (the bench's `process::exit` trick).
@@ -403,6 +917,8 @@ fn main() {
   overview_lists_runs_system_wide(&client);
   history_chart_escapes_script_breakout_in_a_description(&client);
   run_completion_on_drain_closes_only_a_fully_drained_run();
+  run_completion_records_a_regression_summary(&client);
+  class_trends_follow_runs_and_rank_movers(&client);
   eprintln!("runs_test: all cases passed");
   // `_exit` (not `process::exit`): skip C atexit handlers — libpq/OpenSSL global cleanup races with
   // the still-live Tokio/r2d2 threads and SIGSEGVs (L-1). The OS reclaims everything cleanly.
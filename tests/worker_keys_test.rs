@@ -0,0 +1,151 @@
+// Copyright 2015-2025 Deyan Ginev. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+// Licensed under the MIT license <LICENSE-MIT or http://opensource.org/licenses/MIT>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Contract test for the **worker key allowlist** surfaces (`frontend::worker_keys`): the admin
+//! screen is signed-in-only, the agent twin is token-gated, a key is validated and listed once
+//! (`409` after), and a revoked key stops being admitted by the dispatcher's handshake check.
+
+use cortex::backend::{self, test_db_address};
+use cortex::frontend::server::mount_api_with;
+use cortex::helpers::random_mark;
+use cortex::models::WorkerKey;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+
+fn client() -> Client {
+  let figment = rocket::Config::figment().merge(("template_dir", "templates"));
+  let config_file = std::env::temp_dir().join("cortex_worker_keys_test.toml");
+  Client::tracked(mount_api_with(
+    rocket::custom(figment),
+    config_file,
+    test_db_address(),
+  ))
+  .expect("a valid rocket instance")
+}
+
+/// A fresh, well-formed Z85 public key (unique per run).
+fn fresh_key() -> String {
+  let mut bytes = [0u8; 32];
+  bytes[..4].copy_from_slice(&random_mark().to_be_bytes());
+  bytes[4..12].copy_from_slice(&chrono::Utc::now().timestamp_micros().to_be_bytes());
+  zmq::z85_encode(&bytes).expect("32 bytes encode")
+}
+
+fn screen_and_api_are_gated() {
+  let client = client();
+  let response = client.get("/admin/worker-keys").dispatch();
+  assert!(
+    response
+      .headers()
+      .get_one("Location")
+      .unwrap_or("")
+      .starts_with("/admin/login"),
+    "the worker key screen requires sign-in"
+  );
+  assert_eq!(
+    client.get("/api/worker-keys").dispatch().status(),
+    Status::Unauthorized,
+    "listing worker keys requires a token"
+  );
+  assert_eq!(
+    client
+      .post("/api/worker-keys")
+      .header(ContentType::JSON)
+      .body(format!(r#"{{"public_key":"{}"}}"#, fresh_key()))
+      .dispatch()
+      .status(),
+    Status::Unauthorized,
+    "adding a worker key requires a token"
+  );
+  assert_eq!(
+    client.delete("/api/worker-keys/1").dispatch().status(),
+    Status::Unauthorized,
+    "revoking a worker key requires a token"
+  );
+}
+
+fn agent_adds_lists_and_revokes() {
+  let client = client();
+  let token = || Header::new("X-Cortex-Token", "token1");
+  let key = fresh_key();
+
+  assert_eq!(
+    client
+      .post("/api/worker-keys")
+      .header(token())
+      .header(ContentType::JSON)
+      .body(r#"{"public_key":"not-a-key"}"#)
+      .dispatch()
+      .status(),
+    Status::BadRequest
+  );
+  let add = || {
+    client
+      .post("/api/worker-keys")
+      .header(token())
+      .header(ContentType::JSON)
+      .body(format!(
+        r#"{{"public_key":" {key} ","label":"worker_keys_test"}}"#
+      ))
+      .dispatch()
+  };
+  let response = add();
+  assert_eq!(response.status(), Status::Ok);
+  let added: serde_json::Value = response.into_json().expect("worker key json");
+  assert_eq!(added["public_key"], key.as_str(), "stored trimmed");
+  assert_eq!(
+    added["created_by"], "username1",
+    "attributed to the token's owner"
+  );
+  assert_eq!(added["active"], true);
+  assert_eq!(add().status(), Status::Conflict, "a key is listed once");
+  let id = added["id"].as_i64().expect("key id");
+
+  let response = client.get("/api/worker-keys").header(token()).dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let listing: serde_json::Value = response.into_json().expect("allowlist json");
+  assert!(
+    listing["keys"]
+      .as_array()
+      .is_some_and(|keys| keys.iter().any(|entry| entry["id"] == id)),
+    "the added key is listed"
+  );
+
+  // The dispatcher's handshake check admits the key (stamping it seen) until it is revoked.
+  let mut backend = backend::testdb();
+  assert_eq!(WorkerKey::admit(&mut backend.connection, &key), Ok(true));
+  let response = client
+    .delete(format!("/api/worker-keys/{id}"))
+    .header(token())
+    .dispatch();
+  assert_eq!(response.status(), Status::Ok);
+  let revoked: serde_json::Value = response.into_json().expect("revoked key json");
+  assert_eq!(revoked["active"], false);
+  assert_eq!(revoked["revoked_by"], "username1");
+  assert!(
+    revoked["last_seen_at"].is_string(),
+    "the admitted handshake was recorded"
+  );
+  assert_eq!(WorkerKey::admit(&mut backend.connection, &key), Ok(false));
+  assert_eq!(
+    client
+      .delete(format!("/api/worker-keys/{id}"))
+      .header(token())
+      .dispatch()
+      .status(),
+    Status::NotFound,
+    "a key is revoked once"
+  );
+}
+
+// Custom harness (see KNOWN_ISSUES L-1): run the cases then `_exit(0)`.
+fn main() {
+  screen_and_api_are_gated();
+  agent_adds_lists_and_revokes();
+  eprintln!("worker_keys_test: all cases passed");
+  unsafe { libc::_exit(0) }
+}